@@ -0,0 +1,102 @@
+// Benchmarks of the cell element writers in the inner loop of the worksheet
+// writer, and of a worksheet's sheet data.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use excel_xmlwriter::{CellValue, XMLWriter};
+use std::io;
+
+// The number of cells written in each iteration.
+const CELLS: u64 = 1_000;
+
+fn bench_cell_elements(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cells");
+    group.throughput(Throughput::Elements(CELLS));
+
+    group.bench_function("string", |b| {
+        let mut writer = XMLWriter::new(io::sink());
+        b.iter(|| {
+            for index in 0..CELLS as u32 {
+                writer.xml_string_element(black_box(index), &vec![("r", "A1"), ("s", "1")]);
+            }
+        });
+    });
+
+    group.bench_function("number", |b| {
+        let mut writer = XMLWriter::new(io::sink());
+        b.iter(|| {
+            for index in 0..CELLS {
+                writer.xml_number_element(black_box(index as f64 * 1.5), &vec![("r", "B1")]);
+            }
+        });
+    });
+
+    group.bench_function("formula", |b| {
+        let mut writer = XMLWriter::new(io::sink());
+        b.iter(|| {
+            for _ in 0..CELLS {
+                writer.xml_formula_element(black_box("SUM(A1:A10)"), 0.0, &vec![("r", "C1")]);
+            }
+        });
+    });
+
+    group.bench_function("inline_string", |b| {
+        let mut writer = XMLWriter::new(io::sink());
+        b.iter(|| {
+            for _ in 0..CELLS {
+                writer.xml_inline_string_element(black_box("North region"), &vec![("r", "D1")]);
+            }
+        });
+    });
+
+    let values = [
+        CellValue::SharedString(1),
+        CellValue::Number(123.5),
+        CellValue::Bool(true),
+        CellValue::InlineStr("Apples".to_string()),
+    ];
+    for value in values {
+        let name = format!("{value:?}");
+        let name = name.split('(').next().unwrap_or_default().to_string();
+
+        group.bench_with_input(BenchmarkId::new("cell", name), &value, |b, value| {
+            let mut writer = XMLWriter::new(io::sink());
+            b.iter(|| {
+                for _ in 0..CELLS {
+                    writer.xml_cell_element(black_box(value), &vec![("r", "E1")]);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rows");
+    group.throughput(Throughput::Elements(CELLS));
+
+    // Rows of 10 cells, as written in a worksheet's sheetData.
+    group.bench_function("sheet_data", |b| {
+        let mut writer = XMLWriter::new(io::sink());
+        b.iter(|| {
+            for row in 0..(CELLS / 10) as u32 {
+                writer.xml_row_element(black_box(row), Some((0, 9)), None, None, false, false);
+                for col in 0..10 {
+                    writer.xml_number_element(f64::from(col), &vec![("r", "A1")]);
+                }
+                writer.xml_end_tag("row");
+            }
+        });
+
+        let stats = writer.stats();
+        assert!(stats.elements() > 0 && stats.bytes() > 0);
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_cell_elements, bench_rows);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+// Benchmarks of the escaping of worksheet data and attributes, with typical
+// values that don't need escaping and text that does.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use excel_xmlwriter::{AttributeValue, XMLWriter};
+use std::io;
+
+// Typical worksheet strings, mostly without characters to escape.
+const PLAIN: [&str; 4] = ["Apples", "North region", "2022-10-01", "Total sales"];
+const ESCAPED: [&str; 4] = ["R&D", "a < b", "<b>Bold</b>", "Q1 & Q2 > Q3"];
+
+fn bench_escape(c: &mut Criterion) {
+    let mut group = c.benchmark_group("escape");
+
+    for (name, strings) in [("plain", PLAIN), ("escaped", ESCAPED)] {
+        let bytes: usize = strings.iter().map(|string| string.len()).sum();
+        group.throughput(Throughput::Bytes(bytes as u64));
+
+        group.bench_function(format!("data_element_{name}"), |b| {
+            let mut writer = XMLWriter::new(io::sink());
+            b.iter(|| {
+                for string in strings {
+                    writer.xml_data_element("t", black_box(string), &vec![]);
+                }
+            });
+        });
+
+        group.bench_function(format!("attribute_{name}"), |b| {
+            let mut writer = XMLWriter::new(io::sink());
+            b.iter(|| {
+                for string in strings {
+                    writer.xml_empty_tag("cfvo", &vec![("val", black_box(string))]);
+                }
+            });
+        });
+
+        group.bench_function(format!("inline_string_{name}"), |b| {
+            let mut writer = XMLWriter::new(io::sink());
+            b.iter(|| {
+                for string in strings {
+                    writer.xml_inline_string_element(black_box(string), &vec![("r", "A1")]);
+                }
+            });
+        });
+    }
+
+    // Values that are known not to need escaping skip the scan.
+    group.bench_function("attribute_escaped_plain", |b| {
+        let mut writer = XMLWriter::new(io::sink());
+        b.iter(|| {
+            for string in PLAIN {
+                let value = AttributeValue::Escaped(black_box(string));
+                writer.xml_empty_tag_typed("cfvo", &[("val", value)]);
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_escape);
+criterion_main!(benches);
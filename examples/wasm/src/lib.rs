@@ -0,0 +1,84 @@
+//! An example of writing worksheet XML in the browser with `wasm-bindgen`.
+//!
+//! The XML is written to a `Vec<u8>`, which is returned to JavaScript as a
+//! `Uint8Array`. Build it with [`wasm-pack`](https://rustwasm.github.io/wasm-pack/):
+//!
+//! ```text
+//! wasm-pack build --target web examples/wasm
+//! ```
+//!
+//! and call it from JavaScript:
+//!
+//! ```text
+//! import init, { worksheet_xml } from "./pkg/excel_xmlwriter_wasm.js";
+//!
+//! await init();
+//! const bytes = worksheet_xml(["Apples", "Pears"], new Float64Array([3, 7]));
+//! const blob = new Blob([bytes], { type: "application/xml" });
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use excel_xmlwriter::styles::{Format, Styles};
+use excel_xmlwriter::worksheet::Worksheet;
+use excel_xmlwriter::XMLWriter;
+use wasm_bindgen::prelude::*;
+
+/// Write a worksheet part with the names in the first column and the values
+/// in the second, followed by a total if there are any rows, and return its
+/// XML.
+#[wasm_bindgen]
+pub fn worksheet_xml(names: Vec<String>, values: Vec<f64>) -> Vec<u8> {
+    let mut writer = XMLWriter::new(vec![]);
+    let mut styles = Styles::new();
+    let worksheet = Worksheet::new();
+    let bold = Format::new().set_bold();
+
+    let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+    let mut row = 0;
+
+    for (name, value) in names.iter().zip(&values) {
+        stream.write_string(row, 0, name).unwrap_throw();
+        stream.write_number(row, 1, *value).unwrap_throw();
+        row += 1;
+    }
+
+    // An empty worksheet has nothing to total.
+    if row > 0 {
+        let total = format!("=SUM(B1:B{row})");
+        stream
+            .write_string_with_format(row, 0, "Total", &bold)
+            .unwrap_throw();
+        stream
+            .write_formula_with_format(row, 1, &total, &bold)
+            .unwrap_throw();
+    }
+    stream.finish();
+
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::worksheet_xml;
+
+    #[test]
+    fn test_worksheet_xml() {
+        let xml = worksheet_xml(vec!["Apples".to_string()], vec![3.0]);
+        let xml = String::from_utf8(xml).unwrap();
+
+        assert!(xml.contains(r#"<c r="A1" t="inlineStr"><is><t>Apples</t></is></c>"#));
+        assert!(xml.contains("<f>SUM(B1:B1)</f>"));
+    }
+
+    #[test]
+    fn test_worksheet_xml_empty() {
+        let xml = worksheet_xml(vec![], vec![]);
+        let xml = String::from_utf8(xml).unwrap();
+
+        assert!(!xml.contains("Total"));
+        assert!(!xml.contains("<f>"));
+        assert!(!xml.contains("<row"));
+    }
+}
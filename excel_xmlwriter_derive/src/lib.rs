@@ -0,0 +1,291 @@
+//! The `XmlElement` derive macro for `excel_xmlwriter`. It is re-exported
+//! by the `derive` feature of that crate, which documents its use.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Type,
+};
+
+/// Derive `excel_xmlwriter::XmlElement` for a struct with named fields.
+#[proc_macro_derive(XmlElement, attributes(xml))]
+pub fn derive_xml_element(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// The role of a field in the element.
+enum Role {
+    Attribute(String),
+    Child,
+    Text,
+    Skip,
+}
+
+// The wrapper type of a field, which decides how it is written.
+enum Wrapper<'a> {
+    Option(&'a Type),
+    Vec,
+    None,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "XmlElement can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "XmlElement can only be derived for structs",
+            ))
+        }
+    };
+
+    let tag = match struct_tag(&input.attrs)? {
+        Some(tag) => tag,
+        None => lower_camel_case(&input.ident.unraw().to_string()),
+    };
+
+    let mut attributes = vec![];
+    let mut children = vec![];
+    let mut text = None;
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+
+        match field_role(field.attrs.as_slice(), ident)? {
+            Role::Attribute(name) => attributes.push(write_attribute(&name, ident, &field.ty)),
+            Role::Child => children.push(write_child(ident, &field.ty)),
+            Role::Text if text.is_some() => {
+                return Err(Error::new_spanned(ident, "only one field can be the text"))
+            }
+            Role::Text => text = Some(write_text(&tag, ident, &field.ty)),
+            Role::Skip => {}
+        }
+    }
+
+    let body = match text {
+        Some(_) if !children.is_empty() => {
+            return Err(Error::new_spanned(
+                input,
+                "an element with a text field can't have child fields",
+            ))
+        }
+        Some(text) => text,
+        None if children.is_empty() => quote! {
+            writer.xml_empty_tag(#tag, &attributes);
+        },
+        None => quote! {
+            writer.xml_start_tag(#tag, &attributes);
+            #(#children)*
+            writer.xml_end_tag(#tag);
+        },
+    };
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::excel_xmlwriter::XmlElement for #name #type_generics #where_clause {
+            fn write_element<XmlOutput: ::std::io::Write>(
+                &self,
+                writer: &mut ::excel_xmlwriter::XMLWriter<XmlOutput>,
+            ) {
+                let mut values: ::std::vec::Vec<(&str, ::std::string::String)> =
+                    ::std::vec::Vec::new();
+                #(#attributes)*
+                let attributes: ::std::vec::Vec<(&str, &str)> = values
+                    .iter()
+                    .map(|(name, value)| (*name, value.as_str()))
+                    .collect();
+                #body
+            }
+        }
+    })
+}
+
+// The tag from a `#[xml(tag = "...")]` attribute on the struct.
+fn struct_tag(attrs: &[Attribute]) -> Result<Option<String>, Error> {
+    let mut tag = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("xml")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `tag = \"...\"`"))
+            }
+        })?;
+    }
+
+    Ok(tag)
+}
+
+// The role of a field from its `#[xml(...)]` attributes. Fields are
+// attributes named after the field by default.
+fn field_role(attrs: &[Attribute], ident: &Ident) -> Result<Role, Error> {
+    let mut role = Role::Attribute(lower_camel_case(&ident.unraw().to_string()));
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("xml")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                role = Role::Attribute(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("child") {
+                role = Role::Child;
+            } else if meta.path.is_ident("text") {
+                role = Role::Text;
+            } else if meta.path.is_ident("skip") {
+                role = Role::Skip;
+            } else {
+                return Err(meta.error("expected `rename`, `child`, `text` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(role)
+}
+
+// Add the value of an attribute field, unless it is `None`.
+fn write_attribute(name: &str, ident: &Ident, ty: &Type) -> TokenStream2 {
+    match wrapper(ty) {
+        Wrapper::Option(inner) => {
+            let value = attribute_value(quote!(value), inner);
+            quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    values.push((#name, #value));
+                }
+            }
+        }
+        _ => {
+            let value = attribute_value(quote!(&self.#ident), ty);
+            quote! {
+                values.push((#name, #value));
+            }
+        }
+    }
+}
+
+// The string value of a field. Booleans are written as "1" and "0", like
+// Excel, and other types with `Display`.
+fn attribute_value(value: TokenStream2, ty: &Type) -> TokenStream2 {
+    if is_bool(ty) {
+        quote! {
+            ::std::string::String::from(if *#value { "1" } else { "0" })
+        }
+    } else {
+        quote! {
+            ::std::string::ToString::to_string(#value)
+        }
+    }
+}
+
+// Write a child field, or each of the children in an `Option` or `Vec`.
+fn write_child(ident: &Ident, ty: &Type) -> TokenStream2 {
+    match wrapper(ty) {
+        Wrapper::Option(_) => quote! {
+            if let ::std::option::Option::Some(child) = &self.#ident {
+                ::excel_xmlwriter::XmlElement::write_element(child, writer);
+            }
+        },
+        Wrapper::Vec => quote! {
+            for child in &self.#ident {
+                ::excel_xmlwriter::XmlElement::write_element(child, writer);
+            }
+        },
+        Wrapper::None => quote! {
+            ::excel_xmlwriter::XmlElement::write_element(&self.#ident, writer);
+        },
+    }
+}
+
+// Write the element with the text field as its data. An element with a
+// `None` text is written as an empty element.
+fn write_text(tag: &str, ident: &Ident, ty: &Type) -> TokenStream2 {
+    match wrapper(ty) {
+        Wrapper::Option(inner) => {
+            let value = attribute_value(quote!(value), inner);
+            quote! {
+                match &self.#ident {
+                    ::std::option::Option::Some(value) => {
+                        writer.xml_data_element(#tag, &#value, &attributes)
+                    }
+                    ::std::option::Option::None => writer.xml_empty_tag(#tag, &attributes),
+                }
+            }
+        }
+        _ => {
+            let value = attribute_value(quote!(&self.#ident), ty);
+            quote! {
+                writer.xml_data_element(#tag, &#value, &attributes);
+            }
+        }
+    }
+}
+
+// Find the inner type of an `Option` or `Vec` field.
+fn wrapper(ty: &Type) -> Wrapper<'_> {
+    let Type::Path(path) = ty else {
+        return Wrapper::None;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Wrapper::None;
+    };
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return Wrapper::None;
+    };
+    let Some(GenericArgument::Type(inner)) = arguments.args.first() else {
+        return Wrapper::None;
+    };
+
+    if segment.ident == "Option" {
+        Wrapper::Option(inner)
+    } else if segment.ident == "Vec" {
+        Wrapper::Vec
+    } else {
+        Wrapper::None
+    }
+}
+
+// Check if a type is `bool`.
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident("bool"))
+}
+
+// Convert a struct or field name to the lower camel case that SpreadsheetML
+// uses, like `PageMargins` to `pageMargins` and `x_split` to `xSplit`.
+fn lower_camel_case(name: &str) -> String {
+    let mut converted = String::with_capacity(name.len());
+    let mut upper = false;
+
+    for (i, ch) in name.chars().enumerate() {
+        if ch == '_' {
+            upper = i > 0;
+        } else if converted.is_empty() {
+            converted.extend(ch.to_lowercase());
+        } else if upper {
+            converted.extend(ch.to_uppercase());
+            upper = false;
+        } else {
+            converted.push(ch);
+        }
+    }
+
+    converted
+}
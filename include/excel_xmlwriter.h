@@ -0,0 +1,100 @@
+/*
+ * excel_xmlwriter - the C interface to the XML writer.
+ *
+ * Build the library, target/release/libexcel_xmlwriter.so or the platform's
+ * equivalent, with:
+ *
+ *     cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Strings are UTF-8 and nul terminated. The attributes of a tag are an array
+ * of 2 * num_attributes strings with alternating names and values, and can
+ * be NULL if num_attributes is 0.
+ *
+ * SPDX-License-Identifier: MIT
+ * Copyright 2022, John McNamara, jmcnamara@cpan.org
+ */
+
+#ifndef EXCEL_XMLWRITER_H
+#define EXCEL_XMLWRITER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An opaque XML writer. */
+typedef struct FfiWriter xmlwriter;
+
+/* The result of the functions. */
+typedef enum xmlwriter_error {
+    /* No error. */
+    XMLWRITER_OK = 0,
+
+    /* A pointer argument was NULL. */
+    XMLWRITER_ERROR_NULL_ARGUMENT = 1,
+
+    /* A string argument wasn't valid UTF-8. */
+    XMLWRITER_ERROR_INVALID_UTF8 = 2,
+
+    /* The XML couldn't be written to the file. */
+    XMLWRITER_ERROR_WRITE = 3
+} xmlwriter_error;
+
+/* Create a writer that writes to a new file, or return NULL if the file
+ * can't be created. */
+xmlwriter *xmlwriter_new(const char *filename);
+
+/* Create a writer that writes to memory. */
+xmlwriter *xmlwriter_new_buffer(void);
+
+/* Write an XML file declaration. */
+xmlwriter_error xmlwriter_declaration(xmlwriter *writer);
+
+/* Write an XML start tag. */
+xmlwriter_error xmlwriter_start_tag(xmlwriter *writer, const char *tag,
+                                    const char **attributes,
+                                    size_t num_attributes);
+
+/* Write an XML end tag. */
+xmlwriter_error xmlwriter_end_tag(xmlwriter *writer, const char *tag);
+
+/* Write an empty XML tag. */
+xmlwriter_error xmlwriter_empty_tag(xmlwriter *writer, const char *tag,
+                                    const char **attributes,
+                                    size_t num_attributes);
+
+/* Write an XML element containing data. */
+xmlwriter_error xmlwriter_data_element(xmlwriter *writer, const char *tag,
+                                       const char *data,
+                                       const char **attributes,
+                                       size_t num_attributes);
+
+/* Write a <c> cell element with a number value. */
+xmlwriter_error xmlwriter_number_element(xmlwriter *writer, double number,
+                                         const char **attributes,
+                                         size_t num_attributes);
+
+/* Write a <c> cell element with the index of a shared string. */
+xmlwriter_error xmlwriter_string_element(xmlwriter *writer, uint32_t index,
+                                         const char **attributes,
+                                         size_t num_attributes);
+
+/* Flush and free a writer. A NULL writer is ignored. */
+xmlwriter_error xmlwriter_close(xmlwriter *writer);
+
+/* Free a writer from xmlwriter_new_buffer() and return the XML written to it,
+ * which isn't nul terminated, with its length in len. The XML must be freed
+ * with xmlwriter_free_buffer(). Returns NULL for a file writer, which isn't
+ * freed and must still be closed with xmlwriter_close(). */
+uint8_t *xmlwriter_take_buffer(xmlwriter *writer, size_t *len);
+
+/* Free the XML from xmlwriter_take_buffer(). */
+void xmlwriter_free_buffer(uint8_t *buffer, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EXCEL_XMLWRITER_H */
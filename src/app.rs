@@ -0,0 +1,217 @@
+//! The writer for the `docProps/app.xml` extended properties part.
+//!
+//! The extended properties hold the application name and the heading pairs
+//! and titles of parts that Excel shows in the file properties, such as the
+//! number and names of the worksheets.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::app::App;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut app = App::new();
+//!
+//! app.add_heading_pair("Worksheets", 2);
+//! app.add_part_name("Sheet1");
+//! app.add_part_name("Sheet2");
+//!
+//! app.assemble_xml_file(&mut writer);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{DOC_PROPS_VTYPES_NS, EXTENDED_PROPERTIES_NS};
+use crate::XMLWriter;
+
+/// The writer for the `docProps/app.xml` part.
+#[derive(Debug, Default)]
+pub struct App {
+    heading_pairs: Vec<(String, u16)>,
+    part_names: Vec<String>,
+    company: String,
+    manager: String,
+}
+
+impl App {
+    /// Create a new extended properties part.
+    pub fn new() -> App {
+        App::default()
+    }
+
+    /// Add a heading pair, such as `Worksheets` or `Named Ranges`, with the
+    /// number of parts it covers. The part names should be added in the same
+    /// order as the heading pairs.
+    pub fn add_heading_pair(&mut self, heading: &str, count: u16) {
+        if count == 0 {
+            return;
+        }
+
+        self.heading_pairs.push((heading.to_string(), count));
+    }
+
+    /// Add a part name, such as a worksheet name, to the titles of parts.
+    pub fn add_part_name(&mut self, name: &str) {
+        self.part_names.push(name.to_string());
+    }
+
+    /// Set the company property.
+    pub fn set_company(&mut self, company: &str) {
+        self.company = company.to_string();
+    }
+
+    /// Set the manager property.
+    pub fn set_manager(&mut self, manager: &str) {
+        self.manager = manager.to_string();
+    }
+
+    /// Write the `docProps/app.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns", EXTENDED_PROPERTIES_NS),
+            ("xmlns:vt", DOC_PROPS_VTYPES_NS),
+        ];
+        writer.xml_start_tag("Properties", &attributes);
+
+        writer.xml_data_element("Application", "Microsoft Excel", &vec![]);
+        writer.xml_data_element("DocSecurity", "0", &vec![]);
+        writer.xml_data_element("ScaleCrop", "false", &vec![]);
+
+        self.write_heading_pairs(writer);
+        self.write_titles_of_parts(writer);
+
+        if !self.manager.is_empty() {
+            writer.xml_data_element("Manager", &self.manager, &vec![]);
+        }
+        writer.xml_data_element("Company", &self.company, &vec![]);
+        writer.xml_data_element("LinksUpToDate", "false", &vec![]);
+        writer.xml_data_element("SharedDoc", "false", &vec![]);
+        writer.xml_data_element("HyperlinksChanged", "false", &vec![]);
+        writer.xml_data_element("AppVersion", "12.0000", &vec![]);
+
+        writer.xml_end_tag("Properties");
+    }
+
+    // Write the <HeadingPairs> element.
+    fn write_heading_pairs(&self, writer: &mut XMLWriter<impl Write>) {
+        let size = (self.heading_pairs.len() * 2).to_string();
+
+        writer.xml_start_tag("HeadingPairs", &vec![]);
+        writer.xml_start_tag(
+            "vt:vector",
+            &vec![("size", size.as_str()), ("baseType", "variant")],
+        );
+
+        for (heading, count) in &self.heading_pairs {
+            writer.xml_start_tag("vt:variant", &vec![]);
+            writer.xml_data_element("vt:lpstr", heading, &vec![]);
+            writer.xml_end_tag("vt:variant");
+
+            writer.xml_start_tag("vt:variant", &vec![]);
+            writer.xml_data_element_u64("vt:i4", (*count).into(), &vec![]);
+            writer.xml_end_tag("vt:variant");
+        }
+
+        writer.xml_end_tag("vt:vector");
+        writer.xml_end_tag("HeadingPairs");
+    }
+
+    // Write the <TitlesOfParts> element.
+    fn write_titles_of_parts(&self, writer: &mut XMLWriter<impl Write>) {
+        let size = self.part_names.len().to_string();
+
+        writer.xml_start_tag("TitlesOfParts", &vec![]);
+        writer.xml_start_tag(
+            "vt:vector",
+            &vec![("size", size.as_str()), ("baseType", "lpstr")],
+        );
+
+        for name in &self.part_names {
+            writer.xml_data_element("vt:lpstr", name, &vec![]);
+        }
+
+        writer.xml_end_tag("vt:vector");
+        writer.xml_end_tag("TitlesOfParts");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::App;
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_app(app: &App) -> String {
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        app.assemble_xml_file(&mut writer);
+
+        read_xmlfile_data(&mut tempfile)
+    }
+
+    #[test]
+    fn test_assemble_app() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/"#,
+            r#"extended-properties" xmlns:vt="http://schemas.openxmlformats.org/"#,
+            r#"officeDocument/2006/docPropsVTypes"><Application>Microsoft Excel"#,
+            r#"</Application><DocSecurity>0</DocSecurity><ScaleCrop>false</ScaleCrop>"#,
+            r#"<HeadingPairs><vt:vector size="2" baseType="variant"><vt:variant>"#,
+            r#"<vt:lpstr>Worksheets</vt:lpstr></vt:variant><vt:variant><vt:i4>1</vt:i4>"#,
+            r#"</vt:variant></vt:vector></HeadingPairs><TitlesOfParts>"#,
+            r#"<vt:vector size="1" baseType="lpstr"><vt:lpstr>Sheet1</vt:lpstr>"#,
+            r#"</vt:vector></TitlesOfParts><Company></Company>"#,
+            r#"<LinksUpToDate>false</LinksUpToDate><SharedDoc>false</SharedDoc>"#,
+            r#"<HyperlinksChanged>false</HyperlinksChanged><AppVersion>12.0000"#,
+            r#"</AppVersion></Properties>"#,
+        );
+
+        let mut app = App::new();
+        app.add_heading_pair("Worksheets", 1);
+        app.add_part_name("Sheet1");
+
+        assert_eq!(assemble_app(&app), expected);
+    }
+
+    #[test]
+    fn test_assemble_app_with_named_ranges() {
+        let expected = concat!(
+            r#"<HeadingPairs><vt:vector size="4" baseType="variant"><vt:variant>"#,
+            r#"<vt:lpstr>Worksheets</vt:lpstr></vt:variant><vt:variant><vt:i4>2</vt:i4>"#,
+            r#"</vt:variant><vt:variant><vt:lpstr>Named Ranges</vt:lpstr></vt:variant>"#,
+            r#"<vt:variant><vt:i4>1</vt:i4></vt:variant></vt:vector></HeadingPairs>"#,
+            r#"<TitlesOfParts><vt:vector size="3" baseType="lpstr">"#,
+            r#"<vt:lpstr>Sheet1</vt:lpstr><vt:lpstr>Sheet2</vt:lpstr>"#,
+            r#"<vt:lpstr>Sheet1!Print_Titles</vt:lpstr></vt:vector></TitlesOfParts>"#,
+            r#"<Manager>Ann</Manager><Company>A &amp; B</Company>"#,
+        );
+
+        let mut app = App::new();
+        app.add_heading_pair("Worksheets", 2);
+        app.add_heading_pair("Charts", 0);
+        app.add_heading_pair("Named Ranges", 1);
+        app.add_part_name("Sheet1");
+        app.add_part_name("Sheet2");
+        app.add_part_name("Sheet1!Print_Titles");
+        app.set_manager("Ann");
+        app.set_company("A & B");
+
+        assert!(assemble_app(&app).contains(expected));
+    }
+}
@@ -0,0 +1,256 @@
+// xlsx_diff - a development tool to compare the XML parts of two xlsx files.
+//
+// Usage: xlsx_diff [--width N] generated.xlsx reference.xlsx
+//
+// The corresponding XML parts in each file are canonicalized into a list of
+// elements, with sorted attributes, and the differences are displayed side by
+// side. This is useful for debugging "Excel found unreadable content" reports
+// by comparing a generated file against the same file re-saved by Excel.
+//
+// Build with `cargo run --features diff-tool --bin xlsx_diff -- a.xlsx b.xlsx`.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+mod zip;
+
+use excel_xmlwriter::testing::canonicalize;
+use std::collections::BTreeSet;
+use std::process::ExitCode;
+
+// The default display width of each side of the diff.
+const DEFAULT_WIDTH: usize = 60;
+
+// Limit on the size of the LCS table used to diff the changed region of a
+// part. Larger regions are reported as a single replaced block.
+const MAX_LCS_CELLS: usize = 16_000_000;
+
+fn main() -> ExitCode {
+    let mut width = DEFAULT_WIDTH;
+    let mut filenames = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--width" {
+            width = match args.next().and_then(|w| w.parse().ok()) {
+                Some(w) => w,
+                None => return usage(),
+            };
+        } else {
+            filenames.push(arg);
+        }
+    }
+
+    if filenames.len() != 2 {
+        return usage();
+    }
+
+    match diff_files(&filenames[0], &filenames[1], width) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("xlsx_diff: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("Usage: xlsx_diff [--width N] generated.xlsx reference.xlsx");
+    ExitCode::from(2)
+}
+
+// Compare the parts of two xlsx files and print the differences. Returns true
+// if the files are equivalent.
+fn diff_files(left_file: &str, right_file: &str, width: usize) -> Result<bool, String> {
+    let read = |filename: &str| {
+        let data = std::fs::read(filename).map_err(|e| format!("{filename}: {e}"))?;
+        zip::read_zip(&data).map_err(|e| format!("{filename}: {e}"))
+    };
+
+    let left_parts = read(left_file)?;
+    let right_parts = read(right_file)?;
+
+    let names: BTreeSet<&String> = left_parts.keys().chain(right_parts.keys()).collect();
+    let mut equivalent = true;
+
+    for name in names {
+        let (left, right) = match (left_parts.get(name), right_parts.get(name)) {
+            (Some(left), Some(right)) => (left, right),
+            (Some(_), None) => {
+                println!("Only in {left_file}: {name}");
+                equivalent = false;
+                continue;
+            }
+            (None, _) => {
+                println!("Only in {right_file}: {name}");
+                equivalent = false;
+                continue;
+            }
+        };
+
+        if !is_xml_part(name) {
+            if left != right {
+                println!("Binary part differs: {name}");
+                equivalent = false;
+            }
+            continue;
+        }
+
+        let left = canonicalize(&String::from_utf8_lossy(left));
+        let right = canonicalize(&String::from_utf8_lossy(right));
+
+        if left != right {
+            println!("\n=== {name}");
+            print_side_by_side(&left, &right, width);
+            equivalent = false;
+        }
+    }
+
+    Ok(equivalent)
+}
+
+fn is_xml_part(name: &str) -> bool {
+    name.ends_with(".xml") || name.ends_with(".rels") || name.ends_with(".vml")
+}
+
+#[derive(Debug, PartialEq)]
+enum DiffOp {
+    Equal,
+    Left(usize),
+    Right(usize),
+}
+
+// Diff two element lists. The common prefix and suffix are trimmed before
+// computing the longest common subsequence of the changed region.
+fn diff(left: &[String], right: &[String]) -> Vec<DiffOp> {
+    let prefix = left.iter().zip(right).take_while(|(l, r)| l == r).count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+
+    let left_changed = &left[prefix..left.len() - suffix];
+    let right_changed = &right[prefix..right.len() - suffix];
+    let (n, m) = (left_changed.len(), right_changed.len());
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|_| DiffOp::Equal).collect();
+
+    if n * m > MAX_LCS_CELLS {
+        ops.extend((prefix..prefix + n).map(DiffOp::Left));
+        ops.extend((prefix..prefix + m).map(DiffOp::Right));
+    } else {
+        let mut lcs = vec![vec![0_u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if left_changed[i] == right_changed[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && left_changed[i] == right_changed[j] {
+                ops.push(DiffOp::Equal);
+                i += 1;
+                j += 1;
+            } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                ops.push(DiffOp::Left(prefix + i));
+                i += 1;
+            } else {
+                ops.push(DiffOp::Right(prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    ops.extend((0..suffix).map(|_| DiffOp::Equal));
+    ops
+}
+
+// Print the changed elements side by side. Replaced elements are marked with
+// `|`, elements only on the left with `<` and only on the right with `>`.
+fn print_side_by_side(left: &[String], right: &[String], width: usize) {
+    let ops = diff(left, right);
+    let mut i = 0;
+    let mut left_position = 0;
+
+    while i < ops.len() {
+        if ops[i] == DiffOp::Equal {
+            left_position += 1;
+            i += 1;
+            continue;
+        }
+
+        println!("--- element {}", left_position + 1);
+
+        let mut left_run = vec![];
+        let mut right_run = vec![];
+        while let Some(op) = ops.get(i) {
+            match op {
+                DiffOp::Left(index) => {
+                    left_run.push(&left[*index]);
+                    left_position += 1;
+                }
+                DiffOp::Right(index) => right_run.push(&right[*index]),
+                DiffOp::Equal => break,
+            }
+            i += 1;
+        }
+
+        for row in 0..left_run.len().max(right_run.len()) {
+            let (l, r) = (left_run.get(row), right_run.get(row));
+            let marker = match (l, r) {
+                (Some(_), Some(_)) => '|',
+                (Some(_), None) => '<',
+                _ => '>',
+            };
+
+            let l = truncate(l.map_or("", |s| s.as_str()), width);
+            let r = r.map_or("", |s| s.as_str());
+            println!("{l:<width$} {marker} {r}");
+        }
+    }
+}
+
+fn truncate(string: &str, width: usize) -> String {
+    if string.chars().count() <= width {
+        return string.to_string();
+    }
+
+    let mut truncated: String = string.chars().take(width.saturating_sub(3)).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{diff, DiffOp};
+    use excel_xmlwriter::testing::canonicalize;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_diff() {
+        let left = canonicalize("<a><b/><c/><d/></a>");
+        let right = canonicalize("<a><b/><e/><d/><f/></a>");
+
+        let expected = vec![
+            DiffOp::Equal,
+            DiffOp::Equal,
+            DiffOp::Left(2),
+            DiffOp::Right(2),
+            DiffOp::Equal,
+            DiffOp::Right(4),
+            DiffOp::Equal,
+        ];
+
+        assert_eq!(diff(&left, &right), expected);
+    }
+}
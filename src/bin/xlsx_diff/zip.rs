@@ -0,0 +1,369 @@
+// A minimal zip reader for xlsx files, with support for stored and deflated
+// entries. It only supports the subset of the zip format that Excel and other
+// xlsx writers produce: single disk archives without encryption or zip64.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::collections::BTreeMap;
+
+// Read the entries of a zip archive into a map of part name to data.
+pub fn read_zip(zip: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let mut parts = BTreeMap::new();
+
+    // The end of central directory record is at least 22 bytes and may be
+    // followed by a variable length comment.
+    let end = (0..zip.len().saturating_sub(21))
+        .rev()
+        .find(|&i| read_u32(zip, i) == Ok(0x06054b50))
+        .ok_or("not a zip file: end of central directory not found")?;
+
+    let num_entries = read_u16(zip, end + 10)? as usize;
+    let mut offset = read_u32(zip, end + 16)? as usize;
+
+    for _ in 0..num_entries {
+        if read_u32(zip, offset)? != 0x02014b50 {
+            return Err("invalid central directory header".to_string());
+        }
+
+        let method = read_u16(zip, offset + 10)?;
+        let compressed_size = read_u32(zip, offset + 20)? as usize;
+        let name_len = read_u16(zip, offset + 28)? as usize;
+        let extra_len = read_u16(zip, offset + 30)? as usize;
+        let comment_len = read_u16(zip, offset + 32)? as usize;
+        let header_offset = read_u32(zip, offset + 42)? as usize;
+        let name = slice(zip, offset + 46, name_len)?;
+        let name = String::from_utf8_lossy(name).to_string();
+
+        // The data starts after the local header, which can have a different
+        // extra field length to the central header.
+        let local_name_len = read_u16(zip, header_offset + 26)? as usize;
+        let local_extra_len = read_u16(zip, header_offset + 28)? as usize;
+        let data_start = header_offset + 30 + local_name_len + local_extra_len;
+        let data = slice(zip, data_start, compressed_size)?;
+
+        let data = match method {
+            0 => data.to_vec(),
+            8 => inflate(data).map_err(|e| format!("{name}: {e}"))?,
+            _ => return Err(format!("{name}: unsupported compression method {method}")),
+        };
+
+        parts.insert(name, data);
+        offset += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(parts)
+}
+
+fn slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8], String> {
+    data.get(offset..offset + len)
+        .ok_or_else(|| "unexpected end of zip data".to_string())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    let bytes = slice(data, offset, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    let bytes = slice(data, offset, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// Decompress raw deflate data as described in RFC 1951.
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = BitReader::new(data);
+    let mut output = Vec::with_capacity(data.len() * 4);
+
+    loop {
+        let last_block = reader.bits(1)? == 1;
+
+        match reader.bits(2)? {
+            0 => inflate_stored(&mut reader, &mut output)?,
+            1 => {
+                let (lengths, distances) = fixed_tables();
+                inflate_codes(&mut reader, &mut output, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_tables(&mut reader)?;
+                inflate_codes(&mut reader, &mut output, &lengths, &distances)?;
+            }
+            _ => return Err("invalid deflate block type".to_string()),
+        }
+
+        if last_block {
+            return Ok(output);
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            data,
+            position: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    // Read `n` bits, least significant bit first.
+    fn bits(&mut self, n: u32) -> Result<u32, String> {
+        while self.count < n {
+            let byte = *self
+                .data
+                .get(self.position)
+                .ok_or("unexpected end of deflate data")?;
+            self.buffer |= (byte as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+
+        let value = self.buffer & ((1 << n) - 1);
+        self.buffer >>= n;
+        self.count -= n;
+
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+// A canonical Huffman code table stored as the number of codes of each length
+// and the symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|&symbol| lengths[symbol as usize] != 0)
+            .collect();
+        symbols.sort_by_key(|&symbol| lengths[symbol as usize]);
+
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let mut code = 0;
+        let mut first = 0;
+        let mut index = 0;
+
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err("invalid Huffman code".to_string())
+    }
+}
+
+fn inflate_stored(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), String> {
+    reader.align_to_byte();
+
+    let start = reader.position;
+    let len = read_u16(reader.data, start)? as usize;
+    let data = slice(reader.data, start + 4, len)?;
+
+    output.extend_from_slice(data);
+    reader.position = start + 4 + len;
+
+    Ok(())
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    lengths[0..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..288].fill(8);
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [
+        16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+    ];
+
+    let num_lengths = reader.bits(5)? as usize + 257;
+    let num_distances = reader.bits(5)? as usize + 1;
+    let num_codes = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for &index in ORDER.iter().take(num_codes) {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_table = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(num_lengths + num_distances);
+    while lengths.len() < num_lengths + num_distances {
+        let symbol = code_table.decode(reader)?;
+
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or("invalid length repeat")?;
+                (previous, reader.bits(2)? + 3)
+            }
+            17 => (0, reader.bits(3)? + 3),
+            _ => (0, reader.bits(7)? + 11),
+        };
+
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths.len() > num_lengths + num_distances {
+        return Err("too many code lengths".to_string());
+    }
+
+    Ok((
+        Huffman::new(&lengths[..num_lengths]),
+        Huffman::new(&lengths[num_lengths..]),
+    ))
+}
+
+fn inflate_codes(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    const LENGTH_BASE: [u16; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+        131, 163, 195, 227, 258,
+    ];
+    const LENGTH_EXTRA: [u8; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    const DISTANCE_BASE: [u16; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+        2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DISTANCE_EXTRA: [u8; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+        13, 13,
+    ];
+
+    loop {
+        let symbol = lengths.decode(reader)? as usize;
+
+        if symbol < 256 {
+            output.push(symbol as u8);
+            continue;
+        }
+
+        if symbol == 256 {
+            return Ok(());
+        }
+
+        let index = symbol - 257;
+        if index >= LENGTH_BASE.len() {
+            return Err("invalid length symbol".to_string());
+        }
+        let length =
+            LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+
+        let index = distances.decode(reader)? as usize;
+        if index >= DISTANCE_BASE.len() {
+            return Err("invalid distance symbol".to_string());
+        }
+        let distance =
+            DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+
+        if distance > output.len() {
+            return Err("distance too far back".to_string());
+        }
+
+        // The copy can overlap the output that it is generating so it has to
+        // be done byte by byte.
+        let start = output.len() - distance;
+        for i in 0..length {
+            output.push(output[start + i]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{inflate, read_zip};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_inflate_fixed() {
+        let data = [243, 72, 205, 201, 201, 7, 0];
+
+        assert_eq!(inflate(&data).unwrap(), b"Hello");
+    }
+
+    #[test]
+    fn test_inflate_fixed_with_repeats() {
+        let data = [
+            179, 41, 202, 47, 87, 40, 178, 85, 50, 84, 178, 179, 73, 6, 49, 28, 13, 149, 20, 74,
+            108, 149, 138, 129, 252, 50, 59, 3, 27, 253, 50, 59, 27, 253, 100, 32, 6, 170, 179,
+            179, 25, 85, 60, 170, 120, 84, 49, 181, 21, 3, 0,
+        ];
+        let expected = r#"<row r="1"><c r="A1" t="s"><v>0</v></c></row>"#.repeat(20);
+
+        assert_eq!(inflate(&data).unwrap(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_inflate_dynamic() {
+        let data = [
+            221, 205, 215, 17, 131, 64, 16, 3, 208, 86, 228, 6, 12, 54, 78, 204, 220, 92, 13, 124,
+            208, 0, 216, 7, 44, 105, 225, 2, 169, 122, 182, 14, 127, 235, 73, 202, 27, 131, 57,
+            208, 183, 67, 105, 121, 29, 81, 241, 134, 54, 12, 147, 3, 47, 198, 194, 75, 220, 23,
+            199, 142, 31, 215, 87, 40, 71, 90, 121, 157, 21, 194, 135, 29, 165, 216, 149, 124, 131,
+            138, 22, 35, 226, 48, 35, 122, 154, 3, 91, 153, 168, 221, 69, 69, 94, 171, 72, 58, 136,
+            111, 247, 228, 241, 124, 189, 63, 105, 254, 231, 127, 39,
+        ];
+        let expected = concat!(
+            "The quick brown fox jumps over the lazy dog. ",
+            "<si><t>Pack my box with five dozen liquor jugs!</t></si> 0123456789"
+        )
+        .repeat(3);
+
+        assert_eq!(inflate(&data).unwrap(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_inflate_stored() {
+        let data = [1, 3, 0, 252, 255, b'a', b'b', b'c'];
+
+        assert_eq!(inflate(&data).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn test_read_zip_invalid() {
+        assert!(read_zip(b"not a zip file").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! The form control buttons of a worksheet and the writer for the
+//! `xl/ctrlProps/ctrlPropN.xml` parts.
+//!
+//! A [`Button`] runs a macro of the workbook's VBA project when it is
+//! clicked, so it is only useful in a macro-enabled workbook, see
+//! [`Packager::add_vba_project()`](crate::packager::Packager::add_vba_project).
+//! It is added to a cell with
+//! [`Worksheet::insert_button()`](crate::worksheet::Worksheet::insert_button)
+//! or [`Worksheet::add_button()`](crate::worksheet::Worksheet::add_button).
+//!
+//! The buttons of a worksheet are written to the same
+//! [`VmlDrawing`](crate::vml::VmlDrawing) part as its notes, and each button
+//! also has a [`ControlProperties`] part, numbered from
+//! [`Worksheet::set_control_number()`](crate::worksheet::Worksheet::set_control_number).
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! worksheet.insert_button(1, 1, "Run", "Button1_Click");
+//!
+//! if let Some(vml_drawing) = worksheet.vml_drawing() {
+//!     content_types.add_vml();
+//!     packager.add_custom_part(&vml_drawing)?;
+//! }
+//! for control in worksheet.control_properties() {
+//!     content_types.add_control_properties(control.number());
+//!     packager.add_custom_part(&control)?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{CONTROL_PROPERTIES_CONTENT_TYPE, X14_NS};
+use crate::part::XlsxPart;
+use crate::XMLWriter;
+
+/// A form control button that runs a macro when it is clicked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Button {
+    caption: String,
+    macro_name: String,
+    width: u32,
+    height: u32,
+}
+
+impl Button {
+    /// Create a button with its caption. The button has no macro and the
+    /// size of a default cell, 64 by 20 pixels.
+    pub fn new(caption: &str) -> Button {
+        Button {
+            caption: caption.to_string(),
+            macro_name: String::new(),
+            width: 64,
+            height: 20,
+        }
+    }
+
+    /// Set the name of the macro that is run when the button is clicked,
+    /// like `Button1_Click`. The macro is in the workbook's VBA project.
+    pub fn set_macro(mut self, name: &str) -> Button {
+        self.macro_name = name.to_string();
+        self
+    }
+
+    /// Set the width of the button in pixels.
+    pub fn set_width(mut self, width: u32) -> Button {
+        self.width = width;
+        self
+    }
+
+    /// Set the height of the button in pixels.
+    pub fn set_height(mut self, height: u32) -> Button {
+        self.height = height;
+        self
+    }
+
+    // The caption of the button.
+    pub(crate) fn caption(&self) -> &str {
+        &self.caption
+    }
+
+    // The macro reference, like `[0]!Button1_Click`, if there is a macro.
+    pub(crate) fn macro_reference(&self) -> Option<String> {
+        if self.macro_name.is_empty() {
+            None
+        } else {
+            Some(format!("[0]!{}", self.macro_name))
+        }
+    }
+
+    // The size of the button in pixels, as (width, height).
+    pub(crate) fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// The `xl/ctrlProps/ctrlPropN.xml` part with the properties of a button,
+/// created with
+/// [`Worksheet::control_properties()`](crate::worksheet::Worksheet::control_properties).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlProperties {
+    number: u32,
+    part_name: String,
+}
+
+impl ControlProperties {
+    // Create the properties part with its number.
+    pub(crate) fn new(number: u32) -> ControlProperties {
+        ControlProperties {
+            number,
+            part_name: format!("xl/ctrlProps/ctrlProp{number}.xml"),
+        }
+    }
+
+    /// The number of the part, unique in the workbook.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Write the `xl/ctrlProps/ctrlPropN.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns", X14_NS),
+            ("objectType", "Button"),
+            ("lockText", "1"),
+        ];
+        writer.xml_empty_tag("formControlPr", &attributes);
+    }
+}
+
+impl XlsxPart for ControlProperties {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        CONTROL_PROPERTIES_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Button, ControlProperties};
+    use crate::part::XlsxPart;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_control_properties() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<formControlPr xmlns="http://schemas.microsoft.com/office/spreadsheetml/2009/9/main" "#,
+            r#"objectType="Button" lockText="1"/>"#,
+        );
+
+        let control = ControlProperties::new(3);
+        let mut writer = XMLWriter::new(vec![]);
+        control.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+        assert_eq!(control.part_name(), "xl/ctrlProps/ctrlProp3.xml");
+        assert_eq!(control.number(), 3);
+    }
+
+    #[test]
+    fn test_button_macro_reference() {
+        assert_eq!(Button::new("Run").macro_reference(), None);
+        assert_eq!(
+            Button::new("Run")
+                .set_macro("Button1_Click")
+                .macro_reference(),
+            Some("[0]!Button1_Click".to_string())
+        );
+    }
+}
@@ -0,0 +1,445 @@
+//! An iterator adapter that converts string records, such as rows from a CSV
+//! reader or a database driver, into typed [`CellValue`]s.
+//!
+//! Each column can be given an explicit [`ColumnType`] or left to be inferred
+//! from the string data. Empty fields are returned as `None`.
+//!
+//! ```
+//! use excel_xmlwriter::cell_iter::{typed_rows, ColumnType};
+//! use excel_xmlwriter::CellValue;
+//!
+//! let records = vec![vec!["Apples", "1.5", "TRUE", "00123"]];
+//! let column_types = [ColumnType::String];
+//!
+//! let rows: Vec<_> = typed_rows(records, &column_types)
+//!     .collect::<Result<_, _>>()
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     rows[0],
+//!     vec![
+//!         Some(CellValue::InlineStr("Apples".to_string())),
+//!         Some(CellValue::Number(1.5)),
+//!         Some(CellValue::Bool(true)),
+//!         Some(CellValue::InlineStr("00123".to_string())),
+//!     ]
+//! );
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::error::Error;
+use std::fmt;
+
+use crate::CellValue;
+
+/// The type used to convert the string data in a column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Infer the type from the data: numbers, `TRUE`/`FALSE` booleans, Excel
+    /// error values such as `#N/A` and ISO 8601 dates are converted and
+    /// everything else is a string. Numbers with leading zeros, like zip
+    /// codes, are kept as strings. Strings that look like formulas are not
+    /// converted to formulas. This is the default.
+    #[default]
+    Infer,
+
+    /// Always treat the data as a string.
+    String,
+
+    /// Convert the data to a number. It is an error if it isn't one.
+    Number,
+
+    /// Convert `TRUE`/`FALSE`, `1`/`0` and `yes`/`no` to a boolean. It is an
+    /// error if the data isn't one of these.
+    Bool,
+
+    /// Convert an ISO 8601 `yyyy-mm-dd` date or `yyyy-mm-ddThh:mm:ss`
+    /// datetime to an Excel serial date number. It is an error if it isn't
+    /// one of these. The cell needs a date number format to be displayed as a
+    /// date.
+    Date,
+
+    /// Treat the data as a formula, with or without a leading `=`.
+    Formula,
+}
+
+/// Error returned when a field can't be converted to its column's explicit
+/// [`ColumnType`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseCellError {
+    /// The zero-indexed record number.
+    pub row: usize,
+    /// The zero-indexed field number.
+    pub col: usize,
+    /// The column type that the field couldn't be converted to.
+    pub column_type: ColumnType,
+    /// The field data.
+    pub value: String,
+}
+
+impl fmt::Display for ParseCellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "couldn't convert '{}' in row {}, column {} to {:?}",
+            self.value, self.row, self.col, self.column_type
+        )
+    }
+}
+
+impl Error for ParseCellError {}
+
+/// An iterator that yields a row of typed cells for each string record. See
+/// [`typed_rows()`].
+pub struct TypedRows<'a, I> {
+    records: I,
+    column_types: &'a [ColumnType],
+    row: usize,
+}
+
+/// Create an iterator that converts each record of string fields into a row
+/// of typed cells. Columns beyond the end of `column_types` are inferred.
+pub fn typed_rows<I>(records: I, column_types: &[ColumnType]) -> TypedRows<'_, I::IntoIter>
+where
+    I: IntoIterator,
+{
+    TypedRows {
+        records: records.into_iter(),
+        column_types,
+        row: 0,
+    }
+}
+
+impl<I, R, S> Iterator for TypedRows<'_, I>
+where
+    I: Iterator<Item = R>,
+    R: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    type Item = Result<Vec<Option<CellValue>>, ParseCellError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.records.next()?;
+        let row = self.row;
+        self.row += 1;
+
+        let cells = record
+            .into_iter()
+            .enumerate()
+            .map(|(col, field)| {
+                let column_type = self.column_types.get(col).copied().unwrap_or_default();
+
+                parse_cell(field.as_ref(), column_type).map_err(|_| ParseCellError {
+                    row,
+                    col,
+                    column_type,
+                    value: field.as_ref().to_string(),
+                })
+            })
+            .collect();
+
+        Some(cells)
+    }
+}
+
+// Convert a single field to a typed cell using a column type. Empty fields
+// are returned as `Ok(None)`.
+fn parse_cell(field: &str, column_type: ColumnType) -> Result<Option<CellValue>, ()> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+
+    let value = match column_type {
+        ColumnType::Infer => infer_cell(field),
+        ColumnType::String => CellValue::InlineStr(field.to_string()),
+        ColumnType::Number => CellValue::Number(parse_number(field).ok_or(())?),
+        ColumnType::Bool => CellValue::Bool(parse_bool(field).ok_or(())?),
+        ColumnType::Date => CellValue::Number(parse_date(field).ok_or(())?),
+        ColumnType::Formula => CellValue::Formula {
+            formula: field.strip_prefix('=').unwrap_or(field).to_string(),
+            result: 0.0,
+        },
+    };
+
+    Ok(Some(value))
+}
+
+// The error values that Excel recognizes.
+const EXCEL_ERRORS: [&str; 8] = [
+    "#NULL!",
+    "#DIV/0!",
+    "#VALUE!",
+    "#REF!",
+    "#NAME?",
+    "#NUM!",
+    "#N/A",
+    "#GETTING_DATA",
+];
+
+fn infer_cell(field: &str) -> CellValue {
+    if let Some(number) = parse_number(field).filter(|_| !has_leading_zero(field)) {
+        return CellValue::Number(number);
+    }
+
+    if field.eq_ignore_ascii_case("true") || field.eq_ignore_ascii_case("false") {
+        return CellValue::Bool(field.eq_ignore_ascii_case("true"));
+    }
+
+    if EXCEL_ERRORS.contains(&field) {
+        return CellValue::Error(field.to_string());
+    }
+
+    if let Some(date) = parse_date(field) {
+        return CellValue::Number(date);
+    }
+
+    CellValue::InlineStr(field.to_string())
+}
+
+// Parse a finite number in the decimal or scientific notation that Excel
+// accepts. Rust's parser also accepts "inf" and "NaN" which Excel doesn't.
+fn parse_number(field: &str) -> Option<f64> {
+    let field = field.trim();
+
+    if !field
+        .bytes()
+        .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'-' | b'+' | b'e' | b'E'))
+    {
+        return None;
+    }
+
+    field.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+// Check for numbers like "007" that should stay as strings. A single zero and
+// decimals like "0.5" are still numbers.
+fn has_leading_zero(field: &str) -> bool {
+    let digits = field.trim_start_matches(['-', '+']);
+    digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.")
+}
+
+fn parse_bool(field: &str) -> Option<bool> {
+    match field.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" => Some(true),
+        "false" | "0" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+// Convert an ISO 8601 date or datetime to an Excel serial date, the number of
+// days since the 1900 epoch with the time as a fraction of a day.
+fn parse_date(field: &str) -> Option<f64> {
+    let (date, time) = match field.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (field, None),
+    };
+
+    let parts: Vec<&str> = date.split('-').collect();
+    if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 {
+        return None;
+    }
+
+    let year: i64 = parts[0].parse().ok()?;
+    let month: i64 = parts[1].parse().ok()?;
+    let day: i64 = parts[2].parse().ok()?;
+
+    let is_leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = [
+        31,
+        if is_leap { 29 } else { 28 },
+        31,
+        30,
+        31,
+        30,
+        31,
+        31,
+        30,
+        31,
+        30,
+        31,
+    ];
+
+    if !(1900..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month[month as usize - 1]
+    {
+        return None;
+    }
+
+    let mut serial = (days_from_civil(year, month, day) - days_from_civil(1899, 12, 31)) as f64;
+
+    // Excel treats 1900 as a leap year so dates after 1900-02-28 are offset
+    // by one day.
+    if serial > 59.0 {
+        serial += 1.0;
+    }
+
+    if let Some(time) = time {
+        let parts: Vec<&str> = time.split(':').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return None;
+        }
+
+        let hours: f64 = parts[0].parse().ok()?;
+        let minutes: f64 = parts[1].parse().ok()?;
+        let seconds: f64 = parts.get(2).map_or(Some(0.0), |s| s.parse().ok())?;
+
+        if !(0.0..24.0).contains(&hours)
+            || !(0.0..60.0).contains(&minutes)
+            || !(0.0..60.0).contains(&seconds)
+        {
+            return None;
+        }
+
+        serial += (hours * 3600.0 + minutes * 60.0 + seconds) / 86400.0;
+    }
+
+    Some(serial)
+}
+
+// The number of days from 1970-01-01 to a date in the proleptic Gregorian
+// calendar. From Howard Hinnant's date algorithms.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{parse_cell, typed_rows, ColumnType, ParseCellError};
+    use crate::CellValue;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_infer_cell() {
+        let string = |s: &str| Some(CellValue::InlineStr(s.to_string()));
+
+        let tests = vec![
+            ("", None),
+            ("foo", string("foo")),
+            ("1", Some(CellValue::Number(1.0))),
+            ("-1.5", Some(CellValue::Number(-1.5))),
+            ("1e3", Some(CellValue::Number(1000.0))),
+            ("0", Some(CellValue::Number(0.0))),
+            ("0.25", Some(CellValue::Number(0.25))),
+            ("007", string("007")),
+            ("inf", string("inf")),
+            ("NaN", string("NaN")),
+            ("1,000", string("1,000")),
+            ("TRUE", Some(CellValue::Bool(true))),
+            ("false", Some(CellValue::Bool(false))),
+            ("#N/A", Some(CellValue::Error("#N/A".to_string()))),
+            ("#DIV/0!", Some(CellValue::Error("#DIV/0!".to_string()))),
+            ("=SUM(A1:A2)", string("=SUM(A1:A2)")),
+            ("2022-13-01", string("2022-13-01")),
+        ];
+
+        for (field, expected) in tests {
+            assert_eq!(parse_cell(field, ColumnType::Infer), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let tests = vec![
+            ("1900-01-01", 1.0),
+            ("1900-02-28", 59.0),
+            ("1900-03-01", 61.0),
+            ("2000-02-29", 36585.0),
+            ("2022-01-01", 44562.0),
+            ("2022-01-01T12:00:00", 44562.5),
+            ("2022-01-01 06:00", 44562.25),
+            ("9999-12-31", 2958465.0),
+        ];
+
+        for (field, expected) in tests {
+            assert_eq!(
+                parse_cell(field, ColumnType::Date),
+                Ok(Some(CellValue::Number(expected)))
+            );
+        }
+
+        for field in ["1899-12-31", "2021-02-29", "2022-1-1", "2022-01-01T25:00"] {
+            assert_eq!(parse_cell(field, ColumnType::Date), Err(()));
+        }
+    }
+
+    #[test]
+    fn test_explicit_column_types() {
+        assert_eq!(
+            parse_cell("007", ColumnType::Number),
+            Ok(Some(CellValue::Number(7.0)))
+        );
+        assert_eq!(
+            parse_cell("123", ColumnType::String),
+            Ok(Some(CellValue::InlineStr("123".to_string())))
+        );
+        assert_eq!(
+            parse_cell("yes", ColumnType::Bool),
+            Ok(Some(CellValue::Bool(true)))
+        );
+        assert_eq!(parse_cell("maybe", ColumnType::Bool), Err(()));
+        assert_eq!(
+            parse_cell("=A1+1", ColumnType::Formula),
+            Ok(Some(CellValue::Formula {
+                formula: "A1+1".to_string(),
+                result: 0.0
+            }))
+        );
+    }
+
+    #[test]
+    fn test_typed_rows() {
+        let records = vec![vec!["1", "a", ""], vec!["2", "b", "3"]];
+        let column_types = [ColumnType::Number, ColumnType::String];
+
+        let rows: Vec<_> = typed_rows(records, &column_types)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    Some(CellValue::Number(1.0)),
+                    Some(CellValue::InlineStr("a".to_string())),
+                    None
+                ],
+                vec![
+                    Some(CellValue::Number(2.0)),
+                    Some(CellValue::InlineStr("b".to_string())),
+                    Some(CellValue::Number(3.0))
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typed_rows_error() {
+        let records = vec![vec!["1".to_string()], vec!["x".to_string()]];
+        let column_types = [ColumnType::Number];
+
+        let mut rows = typed_rows(records, &column_types);
+
+        assert!(rows.next().unwrap().is_ok());
+        assert_eq!(
+            rows.next().unwrap(),
+            Err(ParseCellError {
+                row: 1,
+                col: 0,
+                column_type: ColumnType::Number,
+                value: "x".to_string()
+            })
+        );
+        assert!(rows.next().is_none());
+    }
+}
@@ -0,0 +1,644 @@
+//! The charts and the writer for the `xl/charts/chartN.xml` parts.
+//!
+//! A [`Chart`] plots one or more [`ChartSeries`], each with the range of its
+//! values and, optionally, the range of its categories and its name. The
+//! ranges refer to cells in the worksheets of the workbook, and Excel reads
+//! the data from them when the file is opened.
+//!
+//! Each chart has a number that is unique in the workbook, starting at 1,
+//! which is used for its part name. The chart is written as a part of its
+//! own, with the [`XlsxPart`] trait, and needs a content type from
+//! [`ContentTypes::add_chart()`](crate::content_types::ContentTypes::add_chart).
+//! It is shown in a worksheet by a drawing part that refers to it.
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::chart::{Chart, ChartSeries, ChartType};
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::packager::Packager;
+//!
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! let chart = Chart::new(1, ChartType::Column)
+//!     .set_title("Sales")
+//!     .add_series(
+//!         ChartSeries::new("Sheet1", 1, 1, 4, 1)
+//!             .set_categories("Sheet1", 1, 0, 4, 0)
+//!             .set_name_cell("Sheet1", 0, 1),
+//!     );
+//!
+//! content_types.add_chart(chart.number());
+//! packager.add_custom_part(&chart)?;
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{CHART_CONTENT_TYPE, CHART_NS, DRAWINGML_NS, RELATIONSHIPS_NS};
+use crate::part::XlsxPart;
+use crate::utility::{quote_sheet_name, rowcol_to_cell_abs};
+use crate::XMLWriter;
+
+// The ids of the category, or x, axis and the value, or y, axis. They only
+// need to be unique in the chart.
+const CATEGORY_AXIS_ID: &str = "50010001";
+const VALUE_AXIS_ID: &str = "50010002";
+
+/// The types of chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartType {
+    /// Vertical bars.
+    Column,
+    /// Horizontal bars.
+    Bar,
+    /// Lines with markers.
+    Line,
+    /// A pie chart of the first series.
+    Pie,
+    /// Markers at the x and y values of the series, where the categories are
+    /// the x values.
+    Scatter,
+    /// Filled areas under lines.
+    Area,
+}
+
+/// A series of data in a chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartSeries {
+    values: String,
+    categories: Option<String>,
+    name: Option<SeriesName>,
+}
+
+// The name of a series, as a string or a reference to a cell.
+#[derive(Clone, Debug, PartialEq)]
+enum SeriesName {
+    Text(String),
+    Cell(String),
+}
+
+impl ChartSeries {
+    /// Create a series with the range of its values in a worksheet. The
+    /// first and last cells can be given in either order.
+    pub fn new(
+        sheet_name: &str,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+    ) -> ChartSeries {
+        ChartSeries {
+            values: range_formula(sheet_name, first_row, first_col, last_row, last_col),
+            categories: None,
+            name: None,
+        }
+    }
+
+    /// Set the range of the categories, the labels of the category axis. For
+    /// scatter charts the categories are the x values. The default is the
+    /// numbers 1, 2, 3, etc.
+    pub fn set_categories(
+        mut self,
+        sheet_name: &str,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+    ) -> ChartSeries {
+        self.categories = Some(range_formula(
+            sheet_name, first_row, first_col, last_row, last_col,
+        ));
+        self
+    }
+
+    /// Set the name of the series, shown in the legend. The default is
+    /// `Series1`, `Series2`, etc.
+    pub fn set_name(mut self, name: &str) -> ChartSeries {
+        self.name = Some(SeriesName::Text(name.to_string()));
+        self
+    }
+
+    /// Set the name of the series from a zero-indexed cell in a worksheet,
+    /// usually the header of the values.
+    pub fn set_name_cell(mut self, sheet_name: &str, row: u32, col: u16) -> ChartSeries {
+        self.name = Some(SeriesName::Cell(range_formula(
+            sheet_name, row, col, row, col,
+        )));
+        self
+    }
+}
+
+/// A chart, and the `xl/charts/chartN.xml` part.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chart {
+    number: u32,
+    part_name: String,
+    chart_type: ChartType,
+    series: Vec<ChartSeries>,
+    title: Option<String>,
+    x_axis_title: Option<String>,
+    y_axis_title: Option<String>,
+    legend: bool,
+}
+
+impl Chart {
+    /// Create a chart with its number in the workbook, starting at 1, and
+    /// its type. The chart has a legend on the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    pub fn new(number: u32, chart_type: ChartType) -> Chart {
+        assert!(number > 0, "chart numbers start at 1");
+
+        Chart {
+            number,
+            part_name: format!("xl/charts/chart{number}.xml"),
+            chart_type,
+            series: vec![],
+            title: None,
+            x_axis_title: None,
+            y_axis_title: None,
+            legend: true,
+        }
+    }
+
+    /// Add a series to the chart. Pie charts only show the first series.
+    pub fn add_series(mut self, series: ChartSeries) -> Chart {
+        self.series.push(series);
+        self
+    }
+
+    /// Set the title of the chart. By default Excel uses the name of the
+    /// series as the title of a chart with one series.
+    pub fn set_title(mut self, title: &str) -> Chart {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Set the title of the x axis, the horizontal axis of all charts but
+    /// bar charts. Pie charts don't have axes.
+    pub fn set_x_axis_title(mut self, title: &str) -> Chart {
+        self.x_axis_title = Some(title.to_string());
+        self
+    }
+
+    /// Set the title of the y axis, the vertical axis of all charts but bar
+    /// charts. Pie charts don't have axes.
+    pub fn set_y_axis_title(mut self, title: &str) -> Chart {
+        self.y_axis_title = Some(title.to_string());
+        self
+    }
+
+    /// Set whether the chart has a legend. The default is `true`.
+    pub fn set_legend(mut self, legend: bool) -> Chart {
+        self.legend = legend;
+        self
+    }
+
+    /// The number of the chart in the workbook.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Write the `xl/charts/chartN.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns:c", CHART_NS),
+            ("xmlns:a", DRAWINGML_NS),
+            ("xmlns:r", RELATIONSHIPS_NS),
+        ];
+        writer.xml_start_tag("c:chartSpace", &attributes);
+        writer.xml_empty_tag("c:lang", &vec![("val", "en-US")]);
+
+        writer.xml_start_tag("c:chart", &vec![]);
+        if let Some(title) = &self.title {
+            write_title(writer, title);
+        }
+
+        writer.xml_start_tag("c:plotArea", &vec![]);
+        writer.xml_empty_tag("c:layout", &vec![]);
+        self.write_plot(writer);
+        self.write_axes(writer);
+        writer.xml_end_tag("c:plotArea");
+
+        if self.legend {
+            writer.xml_start_tag("c:legend", &vec![]);
+            writer.xml_empty_tag("c:legendPos", &vec![("val", "r")]);
+            writer.xml_empty_tag("c:layout", &vec![]);
+            writer.xml_end_tag("c:legend");
+        }
+
+        writer.xml_empty_tag("c:plotVisOnly", &vec![("val", "1")]);
+        writer.xml_end_tag("c:chart");
+
+        // Excel's default print settings for a chart.
+        writer.xml_start_tag("c:printSettings", &vec![]);
+        writer.xml_empty_tag("c:headerFooter", &vec![]);
+        let attributes = vec![
+            ("b", "0.75"),
+            ("l", "0.7"),
+            ("r", "0.7"),
+            ("t", "0.75"),
+            ("header", "0.3"),
+            ("footer", "0.3"),
+        ];
+        writer.xml_empty_tag("c:pageMargins", &attributes);
+        writer.xml_empty_tag("c:pageSetup", &vec![]);
+        writer.xml_end_tag("c:printSettings");
+
+        writer.xml_end_tag("c:chartSpace");
+    }
+
+    // Write the element for the type of chart, with its series.
+    fn write_plot(&self, writer: &mut XMLWriter<impl Write>) {
+        let tag = match self.chart_type {
+            ChartType::Column | ChartType::Bar => "c:barChart",
+            ChartType::Line => "c:lineChart",
+            ChartType::Pie => "c:pieChart",
+            ChartType::Scatter => "c:scatterChart",
+            ChartType::Area => "c:areaChart",
+        };
+        writer.xml_start_tag(tag, &vec![]);
+
+        match self.chart_type {
+            ChartType::Column => {
+                writer.xml_empty_tag("c:barDir", &vec![("val", "col")]);
+                writer.xml_empty_tag("c:grouping", &vec![("val", "clustered")]);
+            }
+            ChartType::Bar => {
+                writer.xml_empty_tag("c:barDir", &vec![("val", "bar")]);
+                writer.xml_empty_tag("c:grouping", &vec![("val", "clustered")]);
+            }
+            ChartType::Line | ChartType::Area => {
+                writer.xml_empty_tag("c:grouping", &vec![("val", "standard")]);
+            }
+            ChartType::Pie => {
+                writer.xml_empty_tag("c:varyColors", &vec![("val", "1")]);
+            }
+            ChartType::Scatter => {
+                writer.xml_empty_tag("c:scatterStyle", &vec![("val", "lineMarker")]);
+            }
+        }
+
+        let series_count = match self.chart_type {
+            ChartType::Pie => self.series.len().min(1),
+            _ => self.series.len(),
+        };
+        for (index, series) in self.series.iter().take(series_count).enumerate() {
+            self.write_series(writer, index, series);
+        }
+
+        match self.chart_type {
+            ChartType::Line => writer.xml_empty_tag("c:marker", &vec![("val", "1")]),
+            ChartType::Pie => writer.xml_empty_tag("c:firstSliceAng", &vec![("val", "0")]),
+            _ => {}
+        }
+
+        if self.chart_type != ChartType::Pie {
+            writer.xml_empty_tag("c:axId", &vec![("val", CATEGORY_AXIS_ID)]);
+            writer.xml_empty_tag("c:axId", &vec![("val", VALUE_AXIS_ID)]);
+        }
+
+        writer.xml_end_tag(tag);
+    }
+
+    // Write a `<c:ser>` element.
+    fn write_series(&self, writer: &mut XMLWriter<impl Write>, index: usize, series: &ChartSeries) {
+        let index = index.to_string();
+
+        writer.xml_start_tag("c:ser", &vec![]);
+        writer.xml_empty_tag("c:idx", &vec![("val", index.as_str())]);
+        writer.xml_empty_tag("c:order", &vec![("val", index.as_str())]);
+
+        match &series.name {
+            Some(SeriesName::Text(name)) => {
+                writer.xml_start_tag("c:tx", &vec![]);
+                writer.xml_data_element("c:v", name, &vec![]);
+                writer.xml_end_tag("c:tx");
+            }
+            Some(SeriesName::Cell(formula)) => {
+                writer.xml_start_tag("c:tx", &vec![]);
+                write_reference(writer, "c:strRef", formula);
+                writer.xml_end_tag("c:tx");
+            }
+            None => {}
+        }
+
+        if self.chart_type == ChartType::Scatter {
+            // Markers without the lines between them.
+            writer.xml_start_tag("c:spPr", &vec![]);
+            writer.xml_start_tag("a:ln", &vec![("w", "28575")]);
+            writer.xml_empty_tag("a:noFill", &vec![]);
+            writer.xml_end_tag("a:ln");
+            writer.xml_end_tag("c:spPr");
+
+            if let Some(categories) = &series.categories {
+                writer.xml_start_tag("c:xVal", &vec![]);
+                write_reference(writer, "c:numRef", categories);
+                writer.xml_end_tag("c:xVal");
+            }
+
+            writer.xml_start_tag("c:yVal", &vec![]);
+            write_reference(writer, "c:numRef", &series.values);
+            writer.xml_end_tag("c:yVal");
+
+            writer.xml_empty_tag("c:smooth", &vec![("val", "0")]);
+        } else {
+            if let Some(categories) = &series.categories {
+                writer.xml_start_tag("c:cat", &vec![]);
+                write_reference(writer, "c:strRef", categories);
+                writer.xml_end_tag("c:cat");
+            }
+
+            writer.xml_start_tag("c:val", &vec![]);
+            write_reference(writer, "c:numRef", &series.values);
+            writer.xml_end_tag("c:val");
+        }
+
+        writer.xml_end_tag("c:ser");
+    }
+
+    // Write the axes of the chart, if it has them.
+    fn write_axes(&self, writer: &mut XMLWriter<impl Write>) {
+        let (category_position, value_position) = match self.chart_type {
+            ChartType::Pie => return,
+            ChartType::Bar => ("l", "b"),
+            _ => ("b", "l"),
+        };
+        let (category_title, value_title) = match self.chart_type {
+            ChartType::Bar => (&self.y_axis_title, &self.x_axis_title),
+            _ => (&self.x_axis_title, &self.y_axis_title),
+        };
+
+        // Scatter charts have two value axes, and the areas of area and
+        // scatter charts start at the first category.
+        let cross_between = match self.chart_type {
+            ChartType::Area | ChartType::Scatter => "midCat",
+            _ => "between",
+        };
+
+        if self.chart_type == ChartType::Scatter {
+            writer.xml_start_tag("c:valAx", &vec![]);
+        } else {
+            writer.xml_start_tag("c:catAx", &vec![]);
+        }
+        write_axis_start(
+            writer,
+            CATEGORY_AXIS_ID,
+            category_position,
+            false,
+            category_title,
+        );
+        writer.xml_empty_tag("c:crossAx", &vec![("val", VALUE_AXIS_ID)]);
+        writer.xml_empty_tag("c:crosses", &vec![("val", "autoZero")]);
+        if self.chart_type == ChartType::Scatter {
+            writer.xml_empty_tag("c:crossBetween", &vec![("val", cross_between)]);
+            writer.xml_end_tag("c:valAx");
+        } else {
+            writer.xml_empty_tag("c:auto", &vec![("val", "1")]);
+            writer.xml_empty_tag("c:lblAlgn", &vec![("val", "ctr")]);
+            writer.xml_empty_tag("c:lblOffset", &vec![("val", "100")]);
+            writer.xml_end_tag("c:catAx");
+        }
+
+        writer.xml_start_tag("c:valAx", &vec![]);
+        write_axis_start(writer, VALUE_AXIS_ID, value_position, true, value_title);
+        writer.xml_empty_tag("c:crossAx", &vec![("val", CATEGORY_AXIS_ID)]);
+        writer.xml_empty_tag("c:crosses", &vec![("val", "autoZero")]);
+        writer.xml_empty_tag("c:crossBetween", &vec![("val", cross_between)]);
+        writer.xml_end_tag("c:valAx");
+    }
+}
+
+impl XlsxPart for Chart {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        CHART_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+// Write the elements that start an axis, up to its crossing axis.
+fn write_axis_start(
+    writer: &mut XMLWriter<impl Write>,
+    axis_id: &str,
+    position: &str,
+    gridlines: bool,
+    title: &Option<String>,
+) {
+    writer.xml_empty_tag("c:axId", &vec![("val", axis_id)]);
+
+    writer.xml_start_tag("c:scaling", &vec![]);
+    writer.xml_empty_tag("c:orientation", &vec![("val", "minMax")]);
+    writer.xml_end_tag("c:scaling");
+
+    writer.xml_empty_tag("c:axPos", &vec![("val", position)]);
+    if gridlines {
+        writer.xml_empty_tag("c:majorGridlines", &vec![]);
+    }
+    if let Some(title) = title {
+        write_title(writer, title);
+    }
+
+    let attributes = vec![("formatCode", "General"), ("sourceLinked", "1")];
+    writer.xml_empty_tag("c:numFmt", &attributes);
+    writer.xml_empty_tag("c:tickLblPos", &vec![("val", "nextTo")]);
+}
+
+// Write a `<c:title>` element with rich text.
+fn write_title(writer: &mut XMLWriter<impl Write>, title: &str) {
+    writer.xml_start_tag("c:title", &vec![]);
+    writer.xml_start_tag("c:tx", &vec![]);
+    writer.xml_start_tag("c:rich", &vec![]);
+    writer.xml_empty_tag("a:bodyPr", &vec![]);
+    writer.xml_empty_tag("a:lstStyle", &vec![]);
+    writer.xml_start_tag("a:p", &vec![]);
+    writer.xml_start_tag("a:pPr", &vec![]);
+    writer.xml_empty_tag("a:defRPr", &vec![]);
+    writer.xml_end_tag("a:pPr");
+    writer.xml_start_tag("a:r", &vec![]);
+    writer.xml_data_element("a:t", title, &vec![]);
+    writer.xml_end_tag("a:r");
+    writer.xml_end_tag("a:p");
+    writer.xml_end_tag("c:rich");
+    writer.xml_end_tag("c:tx");
+    writer.xml_empty_tag("c:overlay", &vec![("val", "0")]);
+    writer.xml_end_tag("c:title");
+}
+
+// Write a `<c:numRef>` or `<c:strRef>` element with its formula.
+fn write_reference(writer: &mut XMLWriter<impl Write>, tag: &str, formula: &str) {
+    writer.xml_start_tag(tag, &vec![]);
+    writer.xml_data_element("c:f", formula, &vec![]);
+    writer.xml_end_tag(tag);
+}
+
+// The absolute reference to a range in a worksheet, like `Sheet1!$A$1:$A$5`,
+// or to a single cell.
+fn range_formula(
+    sheet_name: &str,
+    first_row: u32,
+    first_col: u16,
+    last_row: u32,
+    last_col: u16,
+) -> String {
+    let (first_row, last_row) = (first_row.min(last_row), first_row.max(last_row));
+    let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+
+    let sheet_name = quote_sheet_name(sheet_name);
+    if first_row == last_row && first_col == last_col {
+        format!("{sheet_name}!{}", rowcol_to_cell_abs(first_row, first_col))
+    } else {
+        format!(
+            "{sheet_name}!{}:{}",
+            rowcol_to_cell_abs(first_row, first_col),
+            rowcol_to_cell_abs(last_row, last_col)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{range_formula, Chart, ChartSeries, ChartType};
+    use crate::part::XlsxPart;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_chart(chart: &Chart) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        chart.assemble_xml_file(&mut writer);
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_range_formula() {
+        assert_eq!(range_formula("Sheet1", 4, 1, 0, 1), "Sheet1!$B$1:$B$5");
+        assert_eq!(range_formula("Q1 Sales", 0, 0, 0, 0), "'Q1 Sales'!$A$1");
+    }
+
+    #[test]
+    fn test_assemble_column_chart() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<c:chartSpace xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" "#,
+            r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            r#"<c:lang val="en-US"/><c:chart><c:plotArea><c:layout/><c:barChart>"#,
+            r#"<c:barDir val="col"/><c:grouping val="clustered"/><c:ser><c:idx val="0"/>"#,
+            r#"<c:order val="0"/><c:tx><c:strRef><c:f>Sheet1!$B$1</c:f></c:strRef></c:tx>"#,
+            r#"<c:cat><c:strRef><c:f>Sheet1!$A$2:$A$5</c:f></c:strRef></c:cat>"#,
+            r#"<c:val><c:numRef><c:f>Sheet1!$B$2:$B$5</c:f></c:numRef></c:val></c:ser>"#,
+            r#"<c:axId val="50010001"/><c:axId val="50010002"/></c:barChart>"#,
+            r#"<c:catAx><c:axId val="50010001"/><c:scaling><c:orientation val="minMax"/></c:scaling>"#,
+            r#"<c:axPos val="b"/><c:numFmt formatCode="General" sourceLinked="1"/>"#,
+            r#"<c:tickLblPos val="nextTo"/><c:crossAx val="50010002"/><c:crosses val="autoZero"/>"#,
+            r#"<c:auto val="1"/><c:lblAlgn val="ctr"/><c:lblOffset val="100"/></c:catAx>"#,
+            r#"<c:valAx><c:axId val="50010002"/><c:scaling><c:orientation val="minMax"/></c:scaling>"#,
+            r#"<c:axPos val="l"/><c:majorGridlines/><c:numFmt formatCode="General" sourceLinked="1"/>"#,
+            r#"<c:tickLblPos val="nextTo"/><c:crossAx val="50010001"/><c:crosses val="autoZero"/>"#,
+            r#"<c:crossBetween val="between"/></c:valAx></c:plotArea>"#,
+            r#"<c:legend><c:legendPos val="r"/><c:layout/></c:legend><c:plotVisOnly val="1"/>"#,
+            r#"</c:chart><c:printSettings><c:headerFooter/>"#,
+            r#"<c:pageMargins b="0.75" l="0.7" r="0.7" t="0.75" header="0.3" footer="0.3"/>"#,
+            r#"<c:pageSetup/></c:printSettings></c:chartSpace>"#,
+        );
+
+        let chart = Chart::new(2, ChartType::Column).add_series(
+            ChartSeries::new("Sheet1", 1, 1, 4, 1)
+                .set_categories("Sheet1", 1, 0, 4, 0)
+                .set_name_cell("Sheet1", 0, 1),
+        );
+
+        assert_eq!(assemble_chart(&chart), expected);
+        assert_eq!(chart.part_name(), "xl/charts/chart2.xml");
+    }
+
+    #[test]
+    fn test_assemble_pie_chart() {
+        let expected = concat!(
+            r#"<c:plotArea><c:layout/><c:pieChart><c:varyColors val="1"/><c:ser><c:idx val="0"/>"#,
+            r#"<c:order val="0"/><c:tx><c:v>Share</c:v></c:tx><c:val><c:numRef>"#,
+            r#"<c:f>Sheet1!$A$1:$A$3</c:f></c:numRef></c:val></c:ser>"#,
+            r#"<c:firstSliceAng val="0"/></c:pieChart></c:plotArea><c:plotVisOnly val="1"/>"#,
+        );
+
+        let chart = Chart::new(1, ChartType::Pie)
+            .add_series(ChartSeries::new("Sheet1", 0, 0, 2, 0).set_name("Share"))
+            .add_series(ChartSeries::new("Sheet1", 0, 1, 2, 1))
+            .set_legend(false);
+
+        assert!(assemble_chart(&chart).contains(expected));
+    }
+
+    #[test]
+    fn test_assemble_chart_titles() {
+        let chart = Chart::new(1, ChartType::Bar)
+            .add_series(ChartSeries::new("Sheet1", 0, 0, 2, 0))
+            .set_title("Sales & costs")
+            .set_x_axis_title("Amount")
+            .set_y_axis_title("Region");
+        let got = assemble_chart(&chart);
+
+        assert!(got.contains(concat!(
+            r#"<c:chart><c:title><c:tx><c:rich><a:bodyPr/><a:lstStyle/><a:p><a:pPr><a:defRPr/>"#,
+            r#"</a:pPr><a:r><a:t>Sales &amp; costs</a:t></a:r></a:p></c:rich></c:tx>"#,
+            r#"<c:overlay val="0"/></c:title><c:plotArea>"#,
+        )));
+
+        // The category axis of a bar chart is the vertical y axis.
+        assert!(got.contains(r#"<c:axPos val="l"/><c:title><c:tx><c:rich><a:bodyPr/>"#));
+        assert!(got.contains("<a:t>Region</a:t>"));
+        assert!(got.contains(r#"<c:axPos val="b"/><c:majorGridlines/><c:title>"#));
+    }
+
+    #[test]
+    fn test_assemble_scatter_chart() {
+        let chart = Chart::new(1, ChartType::Scatter)
+            .add_series(ChartSeries::new("Data", 0, 1, 9, 1).set_categories("Data", 0, 0, 9, 0));
+        let got = assemble_chart(&chart);
+
+        assert!(got.contains(concat!(
+            r#"<c:scatterChart><c:scatterStyle val="lineMarker"/><c:ser><c:idx val="0"/>"#,
+            r#"<c:order val="0"/><c:spPr><a:ln w="28575"><a:noFill/></a:ln></c:spPr>"#,
+            r#"<c:xVal><c:numRef><c:f>Data!$A$1:$A$10</c:f></c:numRef></c:xVal>"#,
+            r#"<c:yVal><c:numRef><c:f>Data!$B$1:$B$10</c:f></c:numRef></c:yVal>"#,
+            r#"<c:smooth val="0"/></c:ser>"#,
+        )));
+        assert!(got.contains(r#"<c:valAx><c:axId val="50010001"/>"#));
+        assert!(got.contains(r#"<c:crossBetween val="midCat"/></c:valAx><c:valAx>"#));
+    }
+
+    #[test]
+    fn test_assemble_line_and_area_charts() {
+        let series = ChartSeries::new("Sheet1", 0, 0, 2, 0);
+
+        let got = assemble_chart(&Chart::new(1, ChartType::Line).add_series(series.clone()));
+        assert!(got.contains(r#"<c:lineChart><c:grouping val="standard"/><c:ser>"#));
+        assert!(got.contains(r#"</c:ser><c:marker val="1"/><c:axId val="50010001"/>"#));
+
+        let got = assemble_chart(&Chart::new(1, ChartType::Area).add_series(series));
+        assert!(got.contains(r#"<c:areaChart><c:grouping val="standard"/><c:ser>"#));
+        assert!(got.contains(r#"<c:crossBetween val="midCat"/>"#));
+    }
+}
@@ -0,0 +1,284 @@
+// A writer for OLE compound files, the container format of the embedded
+// objects in the `xl/embeddings/oleObjectN.bin` parts.
+//
+// The files are version 3, with 512 byte sectors. Streams smaller than 4096
+// bytes are stored in 64 byte sectors of the mini stream, as the format
+// requires. All of the streams are in the root storage.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+// The signature at the start of a compound file.
+pub(crate) const SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+const SECTOR_SIZE: usize = 512;
+const MINI_SECTOR_SIZE: usize = 64;
+const MINI_STREAM_CUTOFF: usize = 4096;
+const DIRECTORY_ENTRY_SIZE: usize = 128;
+
+// The number of sector ids in a sector, and in the header.
+const IDS_PER_SECTOR: usize = SECTOR_SIZE / 4;
+const HEADER_DIFAT_IDS: usize = 109;
+
+// The special sector ids.
+const DIFAT_SECTOR: u32 = 0xFFFF_FFFC;
+const FAT_SECTOR: u32 = 0xFFFF_FFFD;
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+const FREE_SECTOR: u32 = 0xFFFF_FFFF;
+const NO_STREAM: u32 = 0xFFFF_FFFF;
+
+// The directory entry types.
+const STREAM: u8 = 2;
+const ROOT_STORAGE: u8 = 5;
+
+// Write a compound file with the CLSID of its root storage and its streams.
+pub(crate) fn compound_file(clsid: [u8; 16], streams: &[(&str, &[u8])]) -> Vec<u8> {
+    // The directory entries are linked in a chain of right siblings, so they
+    // are sorted in the order of the format: shorter names first, then by
+    // the uppercase names.
+    let mut streams = streams.to_vec();
+    streams.sort_by_key(|(name, _)| (name.encode_utf16().count(), name.to_uppercase()));
+
+    // Lay out the small streams in the mini stream.
+    let mut mini_stream = vec![];
+    let mut mini_fat = vec![];
+    let mut mini_starts = vec![];
+    for (_, data) in &streams {
+        if data.len() >= MINI_STREAM_CUTOFF {
+            mini_starts.push(None);
+            continue;
+        }
+
+        let start = mini_fat.len();
+        let count = sectors(data.len(), MINI_SECTOR_SIZE);
+        mini_fat.extend((1..count).map(|index| (start + index) as u32));
+        mini_fat.push(END_OF_CHAIN);
+        mini_starts.push(Some(start as u32));
+
+        mini_stream.extend_from_slice(data);
+        mini_stream.resize(mini_fat.len() * MINI_SECTOR_SIZE, 0);
+    }
+
+    // The sector counts of the parts of the file, after the FAT and DIFAT
+    // sectors, in the order they are written.
+    let directory_sectors = sectors((streams.len() + 1) * DIRECTORY_ENTRY_SIZE, SECTOR_SIZE);
+    let mini_fat_sectors = sectors(mini_fat.len() * 4, SECTOR_SIZE);
+    let mini_stream_sectors = sectors(mini_stream.len(), SECTOR_SIZE);
+    let stream_sectors: Vec<usize> = streams
+        .iter()
+        .map(|(_, data)| match data.len() {
+            length if length >= MINI_STREAM_CUTOFF => sectors(length, SECTOR_SIZE),
+            _ => 0,
+        })
+        .collect();
+
+    let content_sectors = directory_sectors
+        + mini_fat_sectors
+        + mini_stream_sectors
+        + stream_sectors.iter().sum::<usize>();
+
+    // The FAT also has entries for its own sectors and the DIFAT sectors.
+    let (mut fat_sectors, mut difat_sectors) = (0, 0);
+    loop {
+        let total = content_sectors + fat_sectors + difat_sectors;
+        let fat = sectors(total, IDS_PER_SECTOR);
+        let difat = sectors(fat.saturating_sub(HEADER_DIFAT_IDS), IDS_PER_SECTOR - 1);
+        if (fat, difat) == (fat_sectors, difat_sectors) {
+            break;
+        }
+        (fat_sectors, difat_sectors) = (fat, difat);
+    }
+
+    let mut fat = vec![FAT_SECTOR; fat_sectors];
+    fat.resize(fat_sectors + difat_sectors, DIFAT_SECTOR);
+
+    let directory_start = allocate(&mut fat, directory_sectors);
+    let mini_fat_start = allocate(&mut fat, mini_fat_sectors);
+    let mini_stream_start = allocate(&mut fat, mini_stream_sectors);
+    let stream_starts: Vec<u32> = stream_sectors
+        .iter()
+        .map(|count| allocate(&mut fat, *count))
+        .collect();
+
+    fat.resize(fat_sectors * IDS_PER_SECTOR, FREE_SECTOR);
+
+    // The header, with the first FAT sector ids.
+    let mut file = Vec::with_capacity((1 + fat.len()) * SECTOR_SIZE);
+    file.extend(SIGNATURE);
+    file.extend([0; 16]);
+    file.extend(0x003E_u16.to_le_bytes());
+    file.extend(0x0003_u16.to_le_bytes());
+    file.extend(0xFFFE_u16.to_le_bytes());
+    file.extend(9_u16.to_le_bytes());
+    file.extend(6_u16.to_le_bytes());
+    file.extend([0; 6]);
+    file.extend(0_u32.to_le_bytes());
+    file.extend((fat_sectors as u32).to_le_bytes());
+    file.extend(directory_start.to_le_bytes());
+    file.extend(0_u32.to_le_bytes());
+    file.extend((MINI_STREAM_CUTOFF as u32).to_le_bytes());
+    file.extend(mini_fat_start.to_le_bytes());
+    file.extend((mini_fat_sectors as u32).to_le_bytes());
+    let difat_start = if difat_sectors > 0 {
+        fat_sectors as u32
+    } else {
+        END_OF_CHAIN
+    };
+    file.extend(difat_start.to_le_bytes());
+    file.extend((difat_sectors as u32).to_le_bytes());
+    for index in 0..HEADER_DIFAT_IDS {
+        let id = if index < fat_sectors {
+            index as u32
+        } else {
+            FREE_SECTOR
+        };
+        file.extend(id.to_le_bytes());
+    }
+
+    // The FAT sectors.
+    for id in &fat {
+        file.extend(id.to_le_bytes());
+    }
+
+    // The DIFAT sectors, with the rest of the FAT sector ids and the id of
+    // the next DIFAT sector.
+    for sector in 0..difat_sectors {
+        let first = HEADER_DIFAT_IDS + sector * (IDS_PER_SECTOR - 1);
+        for index in first..first + IDS_PER_SECTOR - 1 {
+            let id = if index < fat_sectors {
+                index as u32
+            } else {
+                FREE_SECTOR
+            };
+            file.extend(id.to_le_bytes());
+        }
+        let next = if sector + 1 < difat_sectors {
+            (fat_sectors + sector + 1) as u32
+        } else {
+            END_OF_CHAIN
+        };
+        file.extend(next.to_le_bytes());
+    }
+
+    // The directory, with the root storage and a chain of the streams.
+    let child = if streams.is_empty() { NO_STREAM } else { 1 };
+    let root_start = if mini_stream.is_empty() {
+        END_OF_CHAIN
+    } else {
+        mini_stream_start
+    };
+    write_directory_entry(
+        &mut file,
+        "Root Entry",
+        ROOT_STORAGE,
+        (NO_STREAM, child),
+        clsid,
+        root_start,
+        mini_stream.len(),
+    );
+    for (index, (name, data)) in streams.iter().enumerate() {
+        let right = if index + 1 < streams.len() {
+            index as u32 + 2
+        } else {
+            NO_STREAM
+        };
+        let start = mini_starts[index].unwrap_or(stream_starts[index]);
+        write_directory_entry(
+            &mut file,
+            name,
+            STREAM,
+            (right, NO_STREAM),
+            [0; 16],
+            start,
+            data.len(),
+        );
+    }
+    for _ in streams.len() + 1..directory_sectors * SECTOR_SIZE / DIRECTORY_ENTRY_SIZE {
+        write_unused_directory_entry(&mut file);
+    }
+
+    // The mini FAT, the mini stream and the large streams.
+    for id in &mini_fat {
+        file.extend(id.to_le_bytes());
+    }
+    let padding = mini_fat_sectors * IDS_PER_SECTOR - mini_fat.len();
+    for _ in 0..padding {
+        file.extend(FREE_SECTOR.to_le_bytes());
+    }
+
+    file.extend(&mini_stream);
+    pad(&mut file);
+
+    for (_, data) in &streams {
+        if data.len() >= MINI_STREAM_CUTOFF {
+            file.extend_from_slice(data);
+            pad(&mut file);
+        }
+    }
+
+    file
+}
+
+// The number of sectors of a size that hold a length.
+fn sectors(length: usize, size: usize) -> usize {
+    length.div_ceil(size)
+}
+
+// Allocate a chain of sectors at the end of the FAT and return the id of
+// its first sector, or the end of chain id if it is empty.
+fn allocate(fat: &mut Vec<u32>, count: usize) -> u32 {
+    if count == 0 {
+        return END_OF_CHAIN;
+    }
+
+    let start = fat.len();
+    fat.extend((1..count).map(|index| (start + index) as u32));
+    fat.push(END_OF_CHAIN);
+
+    start as u32
+}
+
+// Pad the file with zeros to the end of its last sector.
+fn pad(file: &mut Vec<u8>) {
+    let length = sectors(file.len(), SECTOR_SIZE) * SECTOR_SIZE;
+    file.resize(length, 0);
+}
+
+// Write a directory entry with its name, type, (right sibling, child) ids,
+// CLSID, first sector and size. The entries are all black, and have no
+// left siblings.
+fn write_directory_entry(
+    file: &mut Vec<u8>,
+    name: &str,
+    entry_type: u8,
+    (right, child): (u32, u32),
+    clsid: [u8; 16],
+    start: u32,
+    size: usize,
+) {
+    let name: Vec<u16> = name.encode_utf16().take(31).collect();
+    let mut name_bytes = [0; 64];
+    for (index, unit) in name.iter().enumerate() {
+        name_bytes[index * 2..index * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+    }
+
+    file.extend(name_bytes);
+    file.extend((((name.len() + 1) * 2) as u16).to_le_bytes());
+    file.push(entry_type);
+    file.push(1);
+    file.extend(NO_STREAM.to_le_bytes());
+    file.extend(right.to_le_bytes());
+    file.extend(child.to_le_bytes());
+    file.extend(clsid);
+    file.extend([0; 4 + 8 + 8]);
+    file.extend(start.to_le_bytes());
+    file.extend((size as u64).to_le_bytes());
+}
+
+// Write an unused directory entry.
+fn write_unused_directory_entry(file: &mut Vec<u8>) {
+    file.extend([0; 68]);
+    file.extend(NO_STREAM.to_le_bytes());
+    file.extend(NO_STREAM.to_le_bytes());
+    file.extend(NO_STREAM.to_le_bytes());
+    file.extend([0; 16 + 4 + 8 + 8 + 4 + 8]);
+}
@@ -0,0 +1,623 @@
+//! The conditional formats of a worksheet.
+//!
+//! Each kind of rule has its own type, [`ConditionalFormatCell`],
+//! [`ConditionalFormatText`], [`ConditionalFormatDate`],
+//! [`ConditionalFormatBlanks`] and [`ConditionalFormatFormula`], which
+//! converts into a [`ConditionalFormat`] that is added to a range of cells
+//! with
+//! [`Worksheet::add_conditional_format()`](crate::worksheet::Worksheet::add_conditional_format).
+//!
+//! The [`Format`] of a rule is stored as a differential format, a `dxf`, in
+//! the styles part. Only the font, fill and border properties of the format
+//! are used. Rules are given priorities in the order that they are added to
+//! the worksheet, so the first rule added has the highest priority.
+//!
+//! ```
+//! use excel_xmlwriter::conditional_format::{
+//!     CellCriteria, ConditionalFormatCell, ConditionalFormatText, TextCriteria,
+//! };
+//! use excel_xmlwriter::styles::{Color, Format};
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let red = Format::new().set_font_color(Color::Rgb(0x9C0006));
+//!
+//! worksheet.add_conditional_format(
+//!     0,
+//!     0,
+//!     9,
+//!     0,
+//!     ConditionalFormatCell::new(CellCriteria::GreaterThan("100".to_string())).set_format(&red),
+//! );
+//! worksheet.add_conditional_format(
+//!     0,
+//!     1,
+//!     9,
+//!     1,
+//!     ConditionalFormatText::new(TextCriteria::Contains("late".to_string())).set_format(&red),
+//! );
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::styles::Format;
+use crate::XMLWriter;
+
+/// The criteria of a [`ConditionalFormatCell`] rule. The values are numbers,
+/// strings in double quotes, or formulas such as `$B$1`, with or without a
+/// leading `=`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CellCriteria {
+    /// The cell value is equal to the value.
+    EqualTo(String),
+
+    /// The cell value isn't equal to the value.
+    NotEqualTo(String),
+
+    /// The cell value is greater than the value.
+    GreaterThan(String),
+
+    /// The cell value is greater than or equal to the value.
+    GreaterThanOrEqualTo(String),
+
+    /// The cell value is less than the value.
+    LessThan(String),
+
+    /// The cell value is less than or equal to the value.
+    LessThanOrEqualTo(String),
+
+    /// The cell value is between the two values, inclusive.
+    Between(String, String),
+
+    /// The cell value isn't between the two values.
+    NotBetween(String, String),
+}
+
+/// The criteria of a [`ConditionalFormatText`] rule. The text is matched
+/// case-insensitively.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextCriteria {
+    /// The cell contains the text.
+    Contains(String),
+
+    /// The cell doesn't contain the text.
+    DoesNotContain(String),
+
+    /// The cell begins with the text.
+    BeginsWith(String),
+
+    /// The cell ends with the text.
+    EndsWith(String),
+}
+
+/// The time periods of a [`ConditionalFormatDate`] rule, relative to the
+/// current date.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatePeriod {
+    /// Yesterday.
+    Yesterday,
+
+    /// Today.
+    Today,
+
+    /// Tomorrow.
+    Tomorrow,
+
+    /// The last 7 days, including today.
+    Last7Days,
+
+    /// The previous calendar week.
+    LastWeek,
+
+    /// The current calendar week.
+    ThisWeek,
+
+    /// The next calendar week.
+    NextWeek,
+
+    /// The previous calendar month.
+    LastMonth,
+
+    /// The current calendar month.
+    ThisMonth,
+
+    /// The next calendar month.
+    NextMonth,
+}
+
+// The rule of a conditional format.
+#[derive(Clone, Debug, PartialEq)]
+enum Rule {
+    Cell(CellCriteria),
+    Text(TextCriteria),
+    Date(DatePeriod),
+    Blanks(bool),
+    Formula(String),
+}
+
+/// A conditional format rule and its format, created from one of the rule
+/// types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalFormat {
+    rule: Rule,
+    format: Format,
+}
+
+/// A rule that compares the cell value with one or two values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalFormatCell {
+    criteria: CellCriteria,
+    format: Format,
+}
+
+/// A rule that matches text in the cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalFormatText {
+    criteria: TextCriteria,
+    format: Format,
+}
+
+/// A rule that matches dates in a time period.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalFormatDate {
+    period: DatePeriod,
+    format: Format,
+}
+
+/// A rule that matches blank, or non-blank, cells. Cells that only contain
+/// spaces are blank.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalFormatBlanks {
+    blanks: bool,
+    format: Format,
+}
+
+/// A rule that matches cells where a formula is true. Cell references in the
+/// formula are relative to the first cell of the range, so `=$A1>5` applied
+/// to `A1:D10` formats each row where column A is greater than 5.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionalFormatFormula {
+    formula: String,
+    format: Format,
+}
+
+impl ConditionalFormatCell {
+    /// Create a rule for the criteria, with the default format.
+    pub fn new(criteria: CellCriteria) -> ConditionalFormatCell {
+        ConditionalFormatCell {
+            criteria,
+            format: Format::new(),
+        }
+    }
+
+    /// Set the format of the cells that match the rule.
+    pub fn set_format(mut self, format: &Format) -> ConditionalFormatCell {
+        self.format = format.clone();
+        self
+    }
+}
+
+impl ConditionalFormatText {
+    /// Create a rule for the criteria, with the default format.
+    pub fn new(criteria: TextCriteria) -> ConditionalFormatText {
+        ConditionalFormatText {
+            criteria,
+            format: Format::new(),
+        }
+    }
+
+    /// Set the format of the cells that match the rule.
+    pub fn set_format(mut self, format: &Format) -> ConditionalFormatText {
+        self.format = format.clone();
+        self
+    }
+}
+
+impl ConditionalFormatDate {
+    /// Create a rule for the time period, with the default format.
+    pub fn new(period: DatePeriod) -> ConditionalFormatDate {
+        ConditionalFormatDate {
+            period,
+            format: Format::new(),
+        }
+    }
+
+    /// Set the format of the cells that match the rule.
+    pub fn set_format(mut self, format: &Format) -> ConditionalFormatDate {
+        self.format = format.clone();
+        self
+    }
+}
+
+impl ConditionalFormatBlanks {
+    /// Create a rule that matches blank cells, with the default format.
+    pub fn new() -> ConditionalFormatBlanks {
+        ConditionalFormatBlanks {
+            blanks: true,
+            format: Format::new(),
+        }
+    }
+
+    /// Create a rule that matches cells that aren't blank, with the default
+    /// format.
+    pub fn new_no_blanks() -> ConditionalFormatBlanks {
+        ConditionalFormatBlanks {
+            blanks: false,
+            format: Format::new(),
+        }
+    }
+
+    /// Set the format of the cells that match the rule.
+    pub fn set_format(mut self, format: &Format) -> ConditionalFormatBlanks {
+        self.format = format.clone();
+        self
+    }
+}
+
+impl Default for ConditionalFormatBlanks {
+    fn default() -> Self {
+        ConditionalFormatBlanks::new()
+    }
+}
+
+impl ConditionalFormatFormula {
+    /// Create a rule for the formula, with the default format. The formula
+    /// can have a leading `=`.
+    pub fn new(formula: &str) -> ConditionalFormatFormula {
+        ConditionalFormatFormula {
+            formula: formula.strip_prefix('=').unwrap_or(formula).to_string(),
+            format: Format::new(),
+        }
+    }
+
+    /// Set the format of the cells that match the rule.
+    pub fn set_format(mut self, format: &Format) -> ConditionalFormatFormula {
+        self.format = format.clone();
+        self
+    }
+}
+
+impl From<ConditionalFormatCell> for ConditionalFormat {
+    fn from(rule: ConditionalFormatCell) -> ConditionalFormat {
+        ConditionalFormat {
+            rule: Rule::Cell(rule.criteria),
+            format: rule.format,
+        }
+    }
+}
+
+impl From<ConditionalFormatText> for ConditionalFormat {
+    fn from(rule: ConditionalFormatText) -> ConditionalFormat {
+        ConditionalFormat {
+            rule: Rule::Text(rule.criteria),
+            format: rule.format,
+        }
+    }
+}
+
+impl From<ConditionalFormatDate> for ConditionalFormat {
+    fn from(rule: ConditionalFormatDate) -> ConditionalFormat {
+        ConditionalFormat {
+            rule: Rule::Date(rule.period),
+            format: rule.format,
+        }
+    }
+}
+
+impl From<ConditionalFormatBlanks> for ConditionalFormat {
+    fn from(rule: ConditionalFormatBlanks) -> ConditionalFormat {
+        ConditionalFormat {
+            rule: Rule::Blanks(rule.blanks),
+            format: rule.format,
+        }
+    }
+}
+
+impl From<ConditionalFormatFormula> for ConditionalFormat {
+    fn from(rule: ConditionalFormatFormula) -> ConditionalFormat {
+        ConditionalFormat {
+            rule: Rule::Formula(rule.formula),
+            format: rule.format,
+        }
+    }
+}
+
+impl ConditionalFormat {
+    // The format of the cells that match the rule.
+    pub(crate) fn format(&self) -> &Format {
+        &self.format
+    }
+
+    // Write the <cfRule> element. The formulas of the text, date and blank
+    // rules refer to the first cell of the range, like `A1`.
+    pub(crate) fn write_rule(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        first_cell: &str,
+        dxf_id: u32,
+        priority: u32,
+    ) {
+        let dxf_id = dxf_id.to_string();
+        let priority = priority.to_string();
+
+        let (rule_type, operator, text, time_period, formulas) = match &self.rule {
+            Rule::Cell(criteria) => {
+                let (operator, formulas) = match criteria {
+                    CellCriteria::EqualTo(value) => ("equal", vec![value]),
+                    CellCriteria::NotEqualTo(value) => ("notEqual", vec![value]),
+                    CellCriteria::GreaterThan(value) => ("greaterThan", vec![value]),
+                    CellCriteria::GreaterThanOrEqualTo(value) => {
+                        ("greaterThanOrEqual", vec![value])
+                    }
+                    CellCriteria::LessThan(value) => ("lessThan", vec![value]),
+                    CellCriteria::LessThanOrEqualTo(value) => ("lessThanOrEqual", vec![value]),
+                    CellCriteria::Between(min, max) => ("between", vec![min, max]),
+                    CellCriteria::NotBetween(min, max) => ("notBetween", vec![min, max]),
+                };
+                let formulas = formulas
+                    .into_iter()
+                    .map(|value| value.strip_prefix('=').unwrap_or(value).to_string())
+                    .collect();
+
+                ("cellIs", Some(operator), None, None, formulas)
+            }
+            Rule::Text(criteria) => {
+                let (rule_type, operator, text, formula) = match criteria {
+                    TextCriteria::Contains(text) => (
+                        "containsText",
+                        "containsText",
+                        text,
+                        format!("NOT(ISERROR(SEARCH({},{first_cell})))", quote(text)),
+                    ),
+                    TextCriteria::DoesNotContain(text) => (
+                        "notContainsText",
+                        "notContains",
+                        text,
+                        format!("ISERROR(SEARCH({},{first_cell}))", quote(text)),
+                    ),
+                    TextCriteria::BeginsWith(text) => (
+                        "beginsWith",
+                        "beginsWith",
+                        text,
+                        format!(
+                            "LEFT({first_cell},{})={}",
+                            text.chars().count(),
+                            quote(text)
+                        ),
+                    ),
+                    TextCriteria::EndsWith(text) => (
+                        "endsWith",
+                        "endsWith",
+                        text,
+                        format!(
+                            "RIGHT({first_cell},{})={}",
+                            text.chars().count(),
+                            quote(text)
+                        ),
+                    ),
+                };
+
+                (
+                    rule_type,
+                    Some(operator),
+                    Some(text.as_str()),
+                    None,
+                    vec![formula],
+                )
+            }
+            Rule::Date(period) => {
+                let (time_period, formula) = date_period_formula(*period, first_cell);
+                ("timePeriod", None, None, Some(time_period), vec![formula])
+            }
+            Rule::Blanks(true) => (
+                "containsBlanks",
+                None,
+                None,
+                None,
+                vec![format!("LEN(TRIM({first_cell}))=0")],
+            ),
+            Rule::Blanks(false) => (
+                "notContainsBlanks",
+                None,
+                None,
+                None,
+                vec![format!("LEN(TRIM({first_cell}))>0")],
+            ),
+            Rule::Formula(formula) => ("expression", None, None, None, vec![formula.clone()]),
+        };
+
+        let mut attributes = vec![
+            ("type", rule_type),
+            ("dxfId", dxf_id.as_str()),
+            ("priority", priority.as_str()),
+        ];
+        if let Some(time_period) = time_period {
+            attributes.push(("timePeriod", time_period));
+        }
+        if let Some(operator) = operator {
+            attributes.push(("operator", operator));
+        }
+        if let Some(text) = text {
+            attributes.push(("text", text));
+        }
+
+        writer.xml_start_tag("cfRule", &attributes);
+        for formula in &formulas {
+            writer.xml_data_element("formula", formula, &vec![]);
+        }
+        writer.xml_end_tag("cfRule");
+    }
+}
+
+// Quote a string for a formula, doubling any double quotes.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+// The `timePeriod` value of a date period and the formula that Excel stores
+// for it.
+fn date_period_formula(period: DatePeriod, cell: &str) -> (&'static str, String) {
+    match period {
+        DatePeriod::Yesterday => ("yesterday", format!("FLOOR({cell},1)=TODAY()-1")),
+        DatePeriod::Today => ("today", format!("FLOOR({cell},1)=TODAY()")),
+        DatePeriod::Tomorrow => ("tomorrow", format!("FLOOR({cell},1)=TODAY()+1")),
+        DatePeriod::Last7Days => (
+            "last7Days",
+            format!("AND(TODAY()-FLOOR({cell},1)<=6,FLOOR({cell},1)<=TODAY())"),
+        ),
+        DatePeriod::LastWeek => (
+            "lastWeek",
+            format!(
+                "AND(TODAY()-ROUNDDOWN({cell},0)>=(WEEKDAY(TODAY())),\
+                 TODAY()-ROUNDDOWN({cell},0)<(WEEKDAY(TODAY())+7))"
+            ),
+        ),
+        DatePeriod::ThisWeek => (
+            "thisWeek",
+            format!(
+                "AND(TODAY()-ROUNDDOWN({cell},0)<=WEEKDAY(TODAY())-1,\
+                 ROUNDDOWN({cell},0)-TODAY()<=7-WEEKDAY(TODAY()))"
+            ),
+        ),
+        DatePeriod::NextWeek => (
+            "nextWeek",
+            format!(
+                "AND(ROUNDDOWN({cell},0)-TODAY()>(7-WEEKDAY(TODAY())),\
+                 ROUNDDOWN({cell},0)-TODAY()<(15-WEEKDAY(TODAY())))"
+            ),
+        ),
+        DatePeriod::LastMonth => (
+            "lastMonth",
+            format!(
+                "AND(MONTH({cell})=MONTH(TODAY())-1,OR(YEAR({cell})=YEAR(TODAY()),\
+                 AND(MONTH({cell})=1,YEAR({cell})=YEAR(TODAY())-1)))"
+            ),
+        ),
+        DatePeriod::ThisMonth => (
+            "thisMonth",
+            format!("AND(MONTH({cell})=MONTH(TODAY()),YEAR({cell})=YEAR(TODAY()))"),
+        ),
+        DatePeriod::NextMonth => (
+            "nextMonth",
+            format!(
+                "AND(MONTH({cell})=MONTH(TODAY())+1,OR(YEAR({cell})=YEAR(TODAY()),\
+                 AND(MONTH({cell})=12,YEAR({cell})=YEAR(TODAY())+1)))"
+            ),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{
+        CellCriteria, ConditionalFormat, ConditionalFormatBlanks, ConditionalFormatCell,
+        ConditionalFormatDate, ConditionalFormatFormula, ConditionalFormatText, DatePeriod,
+        TextCriteria,
+    };
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn write_rule(rule: impl Into<ConditionalFormat>) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        rule.into().write_rule(&mut writer, "B2", 0, 1);
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_cell_rules() {
+        let tests = [
+            (
+                CellCriteria::GreaterThan("=5".to_string()),
+                r#"<cfRule type="cellIs" dxfId="0" priority="1" operator="greaterThan"><formula>5</formula></cfRule>"#,
+            ),
+            (
+                CellCriteria::EqualTo("\"Yes\"".to_string()),
+                r#"<cfRule type="cellIs" dxfId="0" priority="1" operator="equal"><formula>"Yes"</formula></cfRule>"#,
+            ),
+            (
+                CellCriteria::NotBetween("1".to_string(), "$C$1".to_string()),
+                concat!(
+                    r#"<cfRule type="cellIs" dxfId="0" priority="1" operator="notBetween">"#,
+                    r#"<formula>1</formula><formula>$C$1</formula></cfRule>"#,
+                ),
+            ),
+        ];
+
+        for (criteria, expected) in tests {
+            assert_eq!(write_rule(ConditionalFormatCell::new(criteria)), expected);
+        }
+    }
+
+    #[test]
+    fn test_text_rules() {
+        let tests = [
+            (
+                TextCriteria::Contains("a \"b\"".to_string()),
+                concat!(
+                    r#"<cfRule type="containsText" dxfId="0" priority="1" operator="containsText" text="a &quot;b&quot;">"#,
+                    r#"<formula>NOT(ISERROR(SEARCH("a ""b""",B2)))</formula></cfRule>"#,
+                ),
+            ),
+            (
+                TextCriteria::DoesNotContain("x".to_string()),
+                concat!(
+                    r#"<cfRule type="notContainsText" dxfId="0" priority="1" operator="notContains" text="x">"#,
+                    r#"<formula>ISERROR(SEARCH("x",B2))</formula></cfRule>"#,
+                ),
+            ),
+            (
+                TextCriteria::BeginsWith("€ur".to_string()),
+                concat!(
+                    r#"<cfRule type="beginsWith" dxfId="0" priority="1" operator="beginsWith" text="€ur">"#,
+                    r#"<formula>LEFT(B2,3)="€ur"</formula></cfRule>"#,
+                ),
+            ),
+            (
+                TextCriteria::EndsWith("<z>".to_string()),
+                concat!(
+                    r#"<cfRule type="endsWith" dxfId="0" priority="1" operator="endsWith" text="&lt;z&gt;">"#,
+                    r#"<formula>RIGHT(B2,3)="&lt;z&gt;"</formula></cfRule>"#,
+                ),
+            ),
+        ];
+
+        for (criteria, expected) in tests {
+            assert_eq!(write_rule(ConditionalFormatText::new(criteria)), expected);
+        }
+    }
+
+    #[test]
+    fn test_other_rules() {
+        assert_eq!(
+            write_rule(ConditionalFormatDate::new(DatePeriod::Yesterday)),
+            concat!(
+                r#"<cfRule type="timePeriod" dxfId="0" priority="1" timePeriod="yesterday">"#,
+                r#"<formula>FLOOR(B2,1)=TODAY()-1</formula></cfRule>"#,
+            )
+        );
+        assert_eq!(
+            write_rule(ConditionalFormatDate::new(DatePeriod::ThisMonth)),
+            concat!(
+                r#"<cfRule type="timePeriod" dxfId="0" priority="1" timePeriod="thisMonth">"#,
+                r#"<formula>AND(MONTH(B2)=MONTH(TODAY()),YEAR(B2)=YEAR(TODAY()))</formula></cfRule>"#,
+            )
+        );
+        assert_eq!(
+            write_rule(ConditionalFormatBlanks::new()),
+            r#"<cfRule type="containsBlanks" dxfId="0" priority="1"><formula>LEN(TRIM(B2))=0</formula></cfRule>"#
+        );
+        assert_eq!(
+            write_rule(ConditionalFormatBlanks::new_no_blanks()),
+            r#"<cfRule type="notContainsBlanks" dxfId="0" priority="1"><formula>LEN(TRIM(B2))&gt;0</formula></cfRule>"#
+        );
+        assert_eq!(
+            write_rule(ConditionalFormatFormula::new("=$A2>5")),
+            r#"<cfRule type="expression" dxfId="0" priority="1"><formula>$A2&gt;5</formula></cfRule>"#
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! The namespace URIs and content types of the parts of an xlsx file, for
+//! writing the root elements of custom parts and the content type overrides
+//! without copying the long strings.
+//!
+//! ```
+//! # #[cfg(feature = "std")] {
+//! use excel_xmlwriter::constants::{RELATIONSHIPS_NS, SPREADSHEETML_NS};
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! let mut writer = XMLWriter::new(vec![]);
+//! writer.xml_start_tag(
+//!     "worksheet",
+//!     &vec![("xmlns", SPREADSHEETML_NS), ("xmlns:r", RELATIONSHIPS_NS)],
+//! );
+//! # }
+//! ```
+//!
+//! The namespaces are named after the schema, and their documentation gives
+//! the prefix that Excel uses for them.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+// -----------------------------------------------------------------------
+// Namespaces.
+// -----------------------------------------------------------------------
+
+/// The SpreadsheetML namespace of the workbook, worksheet, styles and other
+/// spreadsheet parts. It is the default namespace of the parts, or `x`.
+pub const SPREADSHEETML_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+
+/// The namespace of the relationship ids in the parts, with the `r` prefix.
+/// It is also the base of the relationship types of the parts.
+pub const RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// The namespace of the `.rels` relationships parts. It is also the base of
+/// the relationship types of the package parts.
+pub const PACKAGE_RELATIONSHIPS_NS: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships";
+
+/// The namespace of the `[Content_Types].xml` part.
+pub const CONTENT_TYPES_NS: &str = "http://schemas.openxmlformats.org/package/2006/content-types";
+
+/// The markup compatibility namespace, with the `mc` prefix, for
+/// `mc:Ignorable` and `mc:AlternateContent`.
+pub const MARKUP_COMPATIBILITY_NS: &str =
+    "http://schemas.openxmlformats.org/markup-compatibility/2006";
+
+/// The DrawingML namespace, with the `a` prefix.
+pub const DRAWINGML_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/main";
+
+/// The DrawingML chart namespace, with the `c` prefix.
+pub const CHART_NS: &str = "http://schemas.openxmlformats.org/drawingml/2006/chart";
+
+/// The spreadsheet drawing namespace of the drawing parts, with the `xdr`
+/// prefix.
+pub const SPREADSHEET_DRAWING_NS: &str =
+    "http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing";
+
+/// The namespace of the `docProps/app.xml` extended properties part.
+pub const EXTENDED_PROPERTIES_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/extended-properties";
+
+/// The namespace of the `docProps/custom.xml` custom properties part.
+pub const CUSTOM_PROPERTIES_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/custom-properties";
+
+/// The namespace of the variant types in the extended properties, with the
+/// `vt` prefix.
+pub const DOC_PROPS_VTYPES_NS: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes";
+
+/// The namespace of the `docProps/core.xml` core properties part, with the
+/// `cp` prefix.
+pub const CORE_PROPERTIES_NS: &str =
+    "http://schemas.openxmlformats.org/package/2006/metadata/core-properties";
+
+/// The VML namespace of the legacy drawing parts, with the `v` prefix.
+pub const VML_NS: &str = "urn:schemas-microsoft-com:vml";
+
+/// The Office VML namespace, with the `o` prefix.
+pub const VML_OFFICE_NS: &str = "urn:schemas-microsoft-com:office:office";
+
+/// The Excel VML namespace, with the `x` prefix.
+pub const VML_EXCEL_NS: &str = "urn:schemas-microsoft-com:office:excel";
+
+/// The Excel 2010 extensions namespace, with the `x14` prefix.
+pub const X14_NS: &str = "http://schemas.microsoft.com/office/spreadsheetml/2009/9/main";
+
+/// The Excel 2010 extensions namespace of the worksheet attributes, with the
+/// `x14ac` prefix.
+pub const X14AC_NS: &str = "http://schemas.microsoft.com/office/spreadsheetml/2009/9/ac";
+
+/// The Excel 2006 namespace of the formulas and ranges in extensions, with
+/// the `xm` prefix.
+pub const XM_NS: &str = "http://schemas.microsoft.com/office/excel/2006/main";
+
+/// The dynamic array properties namespace of the metadata part, with the
+/// `xda` prefix.
+pub const DYNAMIC_ARRAY_NS: &str =
+    "http://schemas.microsoft.com/office/spreadsheetml/2017/dynamicarray";
+
+/// The rich data namespace of the rich value metadata, with the `xlrd`
+/// prefix.
+pub const RICH_DATA_NS: &str = "http://schemas.microsoft.com/office/spreadsheetml/2017/richdata";
+
+/// The namespace of the `xl/richData/rdRichValueTypes.xml` part.
+pub const RICH_DATA2_NS: &str = "http://schemas.microsoft.com/office/spreadsheetml/2017/richdata2";
+
+/// The namespace of the `xl/richData/richValueRel.xml` part.
+pub const RICH_VALUE_REL_NS: &str =
+    "http://schemas.microsoft.com/office/spreadsheetml/2022/richvaluerel";
+
+/// The namespace of the threaded comments and persons parts.
+pub const THREADED_COMMENTS_NS: &str =
+    "http://schemas.microsoft.com/office/spreadsheetml/2018/threadedcomments";
+
+// -----------------------------------------------------------------------
+// Content types.
+// -----------------------------------------------------------------------
+
+/// The default content type of `.xml` parts.
+pub const XML_CONTENT_TYPE: &str = "application/xml";
+
+/// The default content type of `.rels` relationships parts.
+pub const RELATIONSHIPS_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-package.relationships+xml";
+
+/// The content type of the `docProps/core.xml` part.
+pub const CORE_PROPERTIES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-package.core-properties+xml";
+
+/// The content type of the `docProps/app.xml` part.
+pub const EXTENDED_PROPERTIES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.extended-properties+xml";
+
+/// The content type of the `docProps/custom.xml` part.
+pub const CUSTOM_PROPERTIES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.custom-properties+xml";
+
+/// The content type of the `xl/workbook.xml` part.
+pub const WORKBOOK_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml";
+
+/// The content type of the `xl/workbook.xml` part of a macro-enabled `.xlsm`
+/// workbook.
+pub const MACRO_WORKBOOK_CONTENT_TYPE: &str =
+    "application/vnd.ms-excel.sheet.macroEnabled.main+xml";
+
+/// The default content type of the `.bin` VBA project part.
+pub const VBA_PROJECT_CONTENT_TYPE: &str = "application/vnd.ms-office.vbaProject";
+
+/// The content type of the `xl/worksheets/sheetN.xml` parts.
+pub const WORKSHEET_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml";
+
+/// The content type of the `xl/styles.xml` part.
+pub const STYLES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml";
+
+/// The content type of the `xl/sharedStrings.xml` part.
+pub const SHARED_STRINGS_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml";
+
+/// The content type of the `xl/theme/theme1.xml` part.
+pub const THEME_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.theme+xml";
+
+/// The content type of the `xl/tables/tableN.xml` parts.
+pub const TABLE_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.table+xml";
+
+/// The content type of the `xl/commentsN.xml` notes parts.
+pub const COMMENTS_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.comments+xml";
+
+/// The content type of the `xl/metadata.xml` part.
+pub const METADATA_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheetMetadata+xml";
+
+/// The content type of the `xl/charts/chartN.xml` parts.
+pub const CHART_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.drawingml.chart+xml";
+
+/// The content type of the `xl/drawings/drawingN.xml` parts.
+pub const DRAWING_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.drawing+xml";
+
+/// The default content type of `.vml` legacy drawing parts.
+pub const VML_DRAWING_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.vmlDrawing";
+
+/// The content type of the `xl/richData/rdrichvalue.xml` part.
+pub const RICH_VALUE_CONTENT_TYPE: &str = "application/vnd.ms-excel.rdrichvalue+xml";
+
+/// The content type of the `xl/richData/rdrichvaluestructure.xml` part.
+pub const RICH_VALUE_STRUCTURE_CONTENT_TYPE: &str =
+    "application/vnd.ms-excel.rdrichvaluestructure+xml";
+
+/// The content type of the `xl/richData/rdRichValueTypes.xml` part.
+pub const RICH_VALUE_TYPES_CONTENT_TYPE: &str = "application/vnd.ms-excel.rdrichvaluetypes+xml";
+
+/// The content type of the `xl/richData/richValueRel.xml` part.
+pub const RICH_VALUE_REL_CONTENT_TYPE: &str = "application/vnd.ms-excel.richvaluerel+xml";
+
+/// The content type of the `xl/ctrlProps/ctrlPropN.xml` form control parts.
+pub const CONTROL_PROPERTIES_CONTENT_TYPE: &str = "application/vnd.ms-excel.controlproperties+xml";
+
+/// The content type of the `xl/embeddings/oleObjectN.bin` embedded object
+/// parts.
+pub const OLE_OBJECT_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.oleObject";
+
+/// The content type of the `xl/externalLinks/externalLinkN.xml` parts.
+pub const EXTERNAL_LINK_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.externalLink+xml";
+
+/// The content type of the `xl/threadedComments/threadedCommentN.xml` parts.
+pub const THREADED_COMMENTS_CONTENT_TYPE: &str = "application/vnd.ms-excel.threadedcomments+xml";
+
+/// The content type of the `xl/persons/person.xml` part.
+pub const PERSONS_CONTENT_TYPE: &str = "application/vnd.ms-excel.person+xml";
@@ -0,0 +1,383 @@
+//! The writer for the `[Content_Types].xml` part.
+//!
+//! Every part in an xlsx package needs a content type, either from a default
+//! for its file extension or from an override for its part name.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut content_types = ContentTypes::new();
+//!
+//! content_types.add_workbook();
+//! content_types.add_worksheet(1);
+//! content_types.add_styles();
+//! content_types.add_default("png", "image/png");
+//!
+//! content_types.assemble_xml_file(&mut writer);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{
+    CHART_CONTENT_TYPE, COMMENTS_CONTENT_TYPE, CONTENT_TYPES_NS, CONTROL_PROPERTIES_CONTENT_TYPE,
+    CORE_PROPERTIES_CONTENT_TYPE, CUSTOM_PROPERTIES_CONTENT_TYPE, DRAWING_CONTENT_TYPE,
+    EXTENDED_PROPERTIES_CONTENT_TYPE, EXTERNAL_LINK_CONTENT_TYPE, MACRO_WORKBOOK_CONTENT_TYPE,
+    METADATA_CONTENT_TYPE, PERSONS_CONTENT_TYPE, RELATIONSHIPS_CONTENT_TYPE,
+    SHARED_STRINGS_CONTENT_TYPE, STYLES_CONTENT_TYPE, TABLE_CONTENT_TYPE, THEME_CONTENT_TYPE,
+    THREADED_COMMENTS_CONTENT_TYPE, VBA_PROJECT_CONTENT_TYPE, VML_DRAWING_CONTENT_TYPE,
+    WORKBOOK_CONTENT_TYPE, WORKSHEET_CONTENT_TYPE, XML_CONTENT_TYPE,
+};
+use crate::part::XlsxPart;
+use crate::XMLWriter;
+
+/// The content type defaults and overrides of an xlsx package.
+#[derive(Debug)]
+pub struct ContentTypes {
+    defaults: Vec<(String, String)>,
+    overrides: Vec<(String, String)>,
+}
+
+impl Default for ContentTypes {
+    fn default() -> Self {
+        ContentTypes::new()
+    }
+}
+
+impl ContentTypes {
+    /// Create a new content types part with the defaults for the `rels` and
+    /// `xml` extensions that every package needs.
+    pub fn new() -> ContentTypes {
+        let mut content_types = ContentTypes {
+            defaults: vec![],
+            overrides: vec![],
+        };
+
+        content_types.add_default("rels", RELATIONSHIPS_CONTENT_TYPE);
+        content_types.add_default("xml", XML_CONTENT_TYPE);
+
+        content_types
+    }
+
+    /// Add a default content type for a file extension. Extensions that have
+    /// already been added are ignored.
+    pub fn add_default(&mut self, extension: &str, content_type: &str) {
+        if self.defaults.iter().any(|(e, _)| e == extension) {
+            return;
+        }
+
+        self.defaults
+            .push((extension.to_string(), content_type.to_string()));
+    }
+
+    /// Add a content type override for a part name like `/xl/workbook.xml`.
+    /// A leading `/` is added if it is missing. Part names that have already
+    /// been added are ignored.
+    pub fn add_override(&mut self, part_name: &str, content_type: &str) {
+        let part_name = if part_name.starts_with('/') {
+            part_name.to_string()
+        } else {
+            format!("/{part_name}")
+        };
+
+        if self.overrides.iter().any(|(p, _)| *p == part_name) {
+            return;
+        }
+
+        self.overrides.push((part_name, content_type.to_string()));
+    }
+
+    /// Add the override for the `xl/workbook.xml` part.
+    pub fn add_workbook(&mut self) {
+        self.add_override("/xl/workbook.xml", WORKBOOK_CONTENT_TYPE);
+    }
+
+    /// Add the default content type for the `xl/vbaProject.bin` part of a
+    /// macro-enabled workbook, and make the `xl/workbook.xml` part
+    /// macro-enabled, whether or not it has already been added with
+    /// [`add_workbook()`](ContentTypes::add_workbook). See
+    /// [`Packager::add_vba_project()`](crate::packager::Packager::add_vba_project).
+    pub fn add_vba_project(&mut self) {
+        self.add_default("bin", VBA_PROJECT_CONTENT_TYPE);
+
+        let workbook = self
+            .overrides
+            .iter_mut()
+            .find(|(part_name, _)| part_name == "/xl/workbook.xml");
+        match workbook {
+            Some((_, content_type)) => *content_type = MACRO_WORKBOOK_CONTENT_TYPE.to_string(),
+            None => self.add_override("/xl/workbook.xml", MACRO_WORKBOOK_CONTENT_TYPE),
+        }
+    }
+
+    /// Add the override for the `xl/worksheets/sheetN.xml` part, where the
+    /// sheet number starts at 1.
+    pub fn add_worksheet(&mut self, sheet_number: u16) {
+        self.add_override(
+            &format!("/xl/worksheets/sheet{sheet_number}.xml"),
+            WORKSHEET_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/tables/tableN.xml` part, where the table
+    /// number starts at 1.
+    pub fn add_table(&mut self, table_number: u32) {
+        self.add_override(
+            &format!("/xl/tables/table{table_number}.xml"),
+            TABLE_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/charts/chartN.xml` part, where the chart
+    /// number starts at 1.
+    pub fn add_chart(&mut self, chart_number: u32) {
+        self.add_override(
+            &format!("/xl/charts/chart{chart_number}.xml"),
+            CHART_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/drawings/drawingN.xml` part, where the
+    /// drawing number starts at 1.
+    pub fn add_drawing(&mut self, drawing_number: u32) {
+        self.add_override(
+            &format!("/xl/drawings/drawing{drawing_number}.xml"),
+            DRAWING_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/commentsN.xml` part, where the number
+    /// starts at 1.
+    pub fn add_comments(&mut self, comments_number: u32) {
+        self.add_override(
+            &format!("/xl/comments{comments_number}.xml"),
+            COMMENTS_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/threadedComments/threadedCommentN.xml`
+    /// part, where the number is the number of the worksheet's notes parts.
+    pub fn add_threaded_comments(&mut self, comments_number: u32) {
+        self.add_override(
+            &format!("/xl/threadedComments/threadedComment{comments_number}.xml"),
+            THREADED_COMMENTS_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/ctrlProps/ctrlPropN.xml` part of a form
+    /// control, where the number starts at 1.
+    pub fn add_control_properties(&mut self, control_number: u32) {
+        self.add_override(
+            &format!("/xl/ctrlProps/ctrlProp{control_number}.xml"),
+            CONTROL_PROPERTIES_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for an `xl/externalLinks/externalLinkN.xml` part.
+    pub fn add_external_link(&mut self, link_number: u32) {
+        self.add_override(
+            &format!("/xl/externalLinks/externalLink{link_number}.xml"),
+            EXTERNAL_LINK_CONTENT_TYPE,
+        );
+    }
+
+    /// Add the override for the `xl/persons/person.xml` part.
+    pub fn add_persons(&mut self) {
+        self.add_override("/xl/persons/person.xml", PERSONS_CONTENT_TYPE);
+    }
+
+    /// Add the override for the `xl/metadata.xml` part.
+    pub fn add_metadata(&mut self) {
+        self.add_override("/xl/metadata.xml", METADATA_CONTENT_TYPE);
+    }
+
+    /// Add the default content type for the `.vml` drawing parts.
+    pub fn add_vml(&mut self) {
+        self.add_default("vml", VML_DRAWING_CONTENT_TYPE);
+    }
+
+    /// Add the override for the `xl/styles.xml` part.
+    pub fn add_styles(&mut self) {
+        self.add_override("/xl/styles.xml", STYLES_CONTENT_TYPE);
+    }
+
+    /// Add the override for the `xl/sharedStrings.xml` part.
+    pub fn add_shared_strings(&mut self) {
+        self.add_override("/xl/sharedStrings.xml", SHARED_STRINGS_CONTENT_TYPE);
+    }
+
+    /// Add the override for the `xl/theme/theme1.xml` part.
+    pub fn add_theme(&mut self) {
+        self.add_override("/xl/theme/theme1.xml", THEME_CONTENT_TYPE);
+    }
+
+    /// Add the overrides for the `docProps/app.xml` and `docProps/core.xml`
+    /// document properties parts.
+    pub fn add_doc_properties(&mut self) {
+        self.add_override("/docProps/app.xml", EXTENDED_PROPERTIES_CONTENT_TYPE);
+        self.add_override("/docProps/core.xml", CORE_PROPERTIES_CONTENT_TYPE);
+    }
+
+    /// Add the override for the `docProps/custom.xml` custom properties
+    /// part.
+    pub fn add_custom_properties(&mut self) {
+        self.add_override("/docProps/custom.xml", CUSTOM_PROPERTIES_CONTENT_TYPE);
+    }
+
+    /// Add the override for a custom part, from its part name and content
+    /// type.
+    pub fn add_custom_part(&mut self, part: &dyn XlsxPart) {
+        self.add_override(part.part_name(), part.content_type());
+    }
+
+    /// Write the `[Content_Types].xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns", CONTENT_TYPES_NS)];
+        writer.xml_start_tag("Types", &attributes);
+
+        for (extension, content_type) in &self.defaults {
+            let attributes = vec![
+                ("Extension", extension.as_str()),
+                ("ContentType", content_type.as_str()),
+            ];
+            writer.xml_empty_tag("Default", &attributes);
+        }
+
+        for (part_name, content_type) in &self.overrides {
+            let attributes = vec![
+                ("PartName", part_name.as_str()),
+                ("ContentType", content_type.as_str()),
+            ];
+            writer.xml_empty_tag("Override", &attributes);
+        }
+
+        writer.xml_end_tag("Types");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ContentTypes;
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_xml_file() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+            r#"<Default Extension="rels" "#,
+            r#"ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            r#"<Default Extension="png" ContentType="image/png"/>"#,
+            r#"<Default Extension="vml" "#,
+            r#"ContentType="application/vnd.openxmlformats-officedocument.vmlDrawing"/>"#,
+            r#"<Override PartName="/xl/workbook.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+            r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            r#"<Override PartName="/xl/worksheets/sheet2.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            r#"<Override PartName="/xl/tables/table1.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.table+xml"/>"#,
+            r#"<Override PartName="/xl/charts/chart1.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.drawingml.chart+xml"/>"#,
+            r#"<Override PartName="/xl/drawings/drawing1.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.drawing+xml"/>"#,
+            r#"<Override PartName="/xl/comments1.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.comments+xml"/>"#,
+            r#"<Override PartName="/xl/threadedComments/threadedComment1.xml" "#,
+            r#"ContentType="application/vnd.ms-excel.threadedcomments+xml"/>"#,
+            r#"<Override PartName="/xl/persons/person.xml" "#,
+            r#"ContentType="application/vnd.ms-excel.person+xml"/>"#,
+            r#"<Override PartName="/xl/styles.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+            r#"<Override PartName="/xl/sharedStrings.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"/>"#,
+            r#"<Override PartName="/xl/theme/theme1.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.theme+xml"/>"#,
+            r#"<Override PartName="/docProps/app.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.extended-properties+xml"/>"#,
+            r#"<Override PartName="/docProps/core.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-package.core-properties+xml"/>"#,
+            r#"<Override PartName="/docProps/custom.xml" ContentType="application/"#,
+            r#"vnd.openxmlformats-officedocument.custom-properties+xml"/>"#,
+            r#"<Override PartName="/xl/custom.xml" ContentType="text/xml"/>"#,
+            r#"</Types>"#,
+        );
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        let mut content_types = ContentTypes::new();
+
+        content_types.add_default("png", "image/png");
+        content_types.add_default("png", "image/other");
+        content_types.add_vml();
+        content_types.add_workbook();
+        content_types.add_worksheet(1);
+        content_types.add_worksheet(2);
+        content_types.add_table(1);
+        content_types.add_chart(1);
+        content_types.add_drawing(1);
+        content_types.add_comments(1);
+        content_types.add_threaded_comments(1);
+        content_types.add_persons();
+        content_types.add_styles();
+        content_types.add_shared_strings();
+        content_types.add_shared_strings();
+        content_types.add_theme();
+        content_types.add_doc_properties();
+        content_types.add_custom_properties();
+        content_types.add_override("xl/custom.xml", "text/xml");
+
+        content_types.assemble_xml_file(&mut writer);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_vba_project() {
+        let expected = concat!(
+            r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+            r#"<Default Extension="bin" ContentType="application/vnd.ms-office.vbaProject"/>"#,
+            r#"<Override PartName="/xl/workbook.xml" "#,
+            r#"ContentType="application/vnd.ms-excel.sheet.macroEnabled.main+xml"/>"#,
+            r#"<Override PartName="/xl/worksheets/sheet1.xml" "#,
+        );
+
+        for vba_first in [false, true] {
+            let mut content_types = ContentTypes::new();
+            if vba_first {
+                content_types.add_vba_project();
+            }
+            content_types.add_workbook();
+            content_types.add_worksheet(1);
+            if !vba_first {
+                content_types.add_vba_project();
+            }
+
+            let mut writer = XMLWriter::new(vec![]);
+            content_types.assemble_xml_file(&mut writer);
+            let got = String::from_utf8(writer.into_inner()).unwrap();
+
+            assert!(got.contains(expected));
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! The writer for the `docProps/core.xml` core properties part.
+//!
+//! The core properties hold the document metadata such as the title, author
+//! and the created and modified timestamps.
+//!
+//! ```
+//! # use std::fs::File;
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use excel_xmlwriter::core_properties::Core;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut core = Core::new();
+//!
+//! core.set_title("Quarterly Sales");
+//! core.set_author("Ann");
+//! core.set_created(UNIX_EPOCH + Duration::from_secs(1_672_531_200));
+//!
+//! core.assemble_xml_file(&mut writer);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::constants::CORE_PROPERTIES_NS;
+use crate::XMLWriter;
+
+/// The writer for the `docProps/core.xml` part.
+#[derive(Debug)]
+pub struct Core {
+    title: String,
+    subject: String,
+    author: String,
+    keywords: String,
+    comment: String,
+    category: String,
+    status: String,
+    created: SystemTime,
+}
+
+impl Default for Core {
+    fn default() -> Self {
+        Core::new()
+    }
+}
+
+impl Core {
+    /// Create a new core properties part. The created and modified
+    /// timestamps default to the current time.
+    pub fn new() -> Core {
+        Core {
+            title: String::new(),
+            subject: String::new(),
+            author: String::new(),
+            keywords: String::new(),
+            comment: String::new(),
+            category: String::new(),
+            status: String::new(),
+            created: SystemTime::now(),
+        }
+    }
+
+    /// Set the title property.
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    /// Set the subject property.
+    pub fn set_subject(&mut self, subject: &str) {
+        self.subject = subject.to_string();
+    }
+
+    /// Set the author property. This is also used for the last modified by
+    /// property.
+    pub fn set_author(&mut self, author: &str) {
+        self.author = author.to_string();
+    }
+
+    /// Set the keywords property.
+    pub fn set_keywords(&mut self, keywords: &str) {
+        self.keywords = keywords.to_string();
+    }
+
+    /// Set the comment, or description, property.
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comment = comment.to_string();
+    }
+
+    /// Set the category property.
+    pub fn set_category(&mut self, category: &str) {
+        self.category = category.to_string();
+    }
+
+    /// Set the status property.
+    pub fn set_status(&mut self, status: &str) {
+        self.status = status.to_string();
+    }
+
+    /// Set the created timestamp, which is also used for the modified
+    /// timestamp. Setting a fixed time makes the output reproducible.
+    pub fn set_created(&mut self, created: SystemTime) {
+        self.created = created;
+    }
+
+    /// Write the `docProps/core.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns:cp", CORE_PROPERTIES_NS),
+            ("xmlns:dc", "http://purl.org/dc/elements/1.1/"),
+            ("xmlns:dcterms", "http://purl.org/dc/terms/"),
+            ("xmlns:dcmitype", "http://purl.org/dc/dcmitype/"),
+            ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+        ];
+        writer.xml_start_tag("cp:coreProperties", &attributes);
+
+        write_optional_element(writer, "dc:title", &self.title);
+        write_optional_element(writer, "dc:subject", &self.subject);
+        writer.xml_data_element("dc:creator", &self.author, &vec![]);
+        write_optional_element(writer, "cp:keywords", &self.keywords);
+        write_optional_element(writer, "dc:description", &self.comment);
+        writer.xml_data_element("cp:lastModifiedBy", &self.author, &vec![]);
+
+        let datetime = w3cdtf_datetime(self.created);
+        let attributes = vec![("xsi:type", "dcterms:W3CDTF")];
+        writer.xml_data_element("dcterms:created", &datetime, &attributes);
+        writer.xml_data_element("dcterms:modified", &datetime, &attributes);
+
+        write_optional_element(writer, "cp:category", &self.category);
+        write_optional_element(writer, "cp:contentStatus", &self.status);
+
+        writer.xml_end_tag("cp:coreProperties");
+    }
+}
+
+// Write an element only if it has data.
+fn write_optional_element(writer: &mut XMLWriter<impl Write>, tag: &str, data: &str) {
+    if !data.is_empty() {
+        writer.xml_data_element(tag, data, &vec![]);
+    }
+}
+
+// Format a time as a UTC W3CDTF datetime like `2023-01-01T00:00:00Z`. Times
+// before the Unix epoch are written as the epoch.
+pub(crate) fn w3cdtf_datetime(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let days = (seconds / 86_400) as i64;
+    let seconds = seconds % 86_400;
+
+    // Convert days since the epoch to a civil date, from Howard Hinnant's
+    // date algorithms.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{w3cdtf_datetime, Core};
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_core(core: &Core) -> String {
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        core.assemble_xml_file(&mut writer);
+
+        read_xmlfile_data(&mut tempfile)
+    }
+
+    #[test]
+    fn test_assemble_core() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/"#,
+            r#"2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" "#,
+            r#"xmlns:dcterms="http://purl.org/dc/terms/" "#,
+            r#"xmlns:dcmitype="http://purl.org/dc/dcmitype/" "#,
+            r#"xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+            r#"<dc:creator>Ann</dc:creator><cp:lastModifiedBy>Ann</cp:lastModifiedBy>"#,
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">2023-01-01T00:00:00Z"#,
+            r#"</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">"#,
+            r#"2023-01-01T00:00:00Z</dcterms:modified></cp:coreProperties>"#,
+        );
+
+        let mut core = Core::new();
+        core.set_author("Ann");
+        core.set_created(UNIX_EPOCH + Duration::from_secs(1_672_531_200));
+
+        assert_eq!(assemble_core(&core), expected);
+    }
+
+    #[test]
+    fn test_assemble_core_with_all_properties() {
+        let expected = concat!(
+            r#"<dc:title>Title</dc:title><dc:subject>Subject</dc:subject>"#,
+            r#"<dc:creator>Ann</dc:creator><cp:keywords>Sales, Q1</cp:keywords>"#,
+            r#"<dc:description>A &amp; B</dc:description>"#,
+            r#"<cp:lastModifiedBy>Ann</cp:lastModifiedBy>"#,
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">1970-01-01T00:00:00Z"#,
+            r#"</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">"#,
+            r#"1970-01-01T00:00:00Z</dcterms:modified><cp:category>Reports</cp:category>"#,
+            r#"<cp:contentStatus>Draft</cp:contentStatus></cp:coreProperties>"#,
+        );
+
+        let mut core = Core::new();
+        core.set_title("Title");
+        core.set_subject("Subject");
+        core.set_author("Ann");
+        core.set_keywords("Sales, Q1");
+        core.set_comment("A & B");
+        core.set_category("Reports");
+        core.set_status("Draft");
+        core.set_created(UNIX_EPOCH);
+
+        assert!(assemble_core(&core).contains(expected));
+    }
+
+    #[test]
+    fn test_w3cdtf_datetime() {
+        let datetime = |seconds| w3cdtf_datetime(UNIX_EPOCH + Duration::from_secs(seconds));
+
+        assert_eq!(datetime(0), "1970-01-01T00:00:00Z");
+        assert_eq!(datetime(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(datetime(1_677_628_799), "2023-02-28T23:59:59Z");
+        assert_eq!(datetime(4_102_444_800), "2100-01-01T00:00:00Z");
+        assert_eq!(
+            w3cdtf_datetime(UNIX_EPOCH - Duration::from_secs(1)),
+            "1970-01-01T00:00:00Z"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! The writer for the `docProps/custom.xml` custom properties part.
+//!
+//! Custom properties are the named text, number, date and yes or no values
+//! shown in the Custom tab of Excel's document properties dialog. The part
+//! is optional and is linked from the package relationships, like the core
+//! properties.
+//!
+//! ```
+//! # use std::fs::File;
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::custom::CustomProperties;
+//! use excel_xmlwriter::relationship::Relationships;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut content_types = ContentTypes::new();
+//! let mut package_relationships = Relationships::new();
+//! let mut custom = CustomProperties::new();
+//!
+//! custom.set_text("Checked by", "Ann");
+//! custom.set_number("Revision", 3.0);
+//! custom.set_date("Approved", UNIX_EPOCH + Duration::from_secs(1_672_531_200));
+//! custom.set_bool("Final", true);
+//!
+//! if !custom.is_empty() {
+//!     content_types.add_custom_properties();
+//!     package_relationships.add_document_relationship("custom-properties", "docProps/custom.xml");
+//!     custom.assemble_xml_file(&mut writer);
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+use std::time::SystemTime;
+
+use crate::constants::{CUSTOM_PROPERTIES_CONTENT_TYPE, CUSTOM_PROPERTIES_NS, DOC_PROPS_VTYPES_NS};
+use crate::core_properties::w3cdtf_datetime;
+use crate::part::XlsxPart;
+use crate::XMLWriter;
+
+// The format id of the user defined properties, which is the same for all
+// the custom properties.
+const USER_DEFINED_FMTID: &str = "{D5CDD505-2E9C-101B-9397-08002B2CF9AE}";
+
+// The value of a custom property.
+#[derive(Clone, Debug, PartialEq)]
+enum PropertyValue {
+    Text(String),
+    Number(f64),
+    Date(SystemTime),
+    Bool(bool),
+}
+
+/// The writer for the `docProps/custom.xml` part.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomProperties {
+    properties: Vec<(String, PropertyValue)>,
+}
+
+impl CustomProperties {
+    /// Create a new custom properties part with no properties.
+    pub fn new() -> CustomProperties {
+        CustomProperties::default()
+    }
+
+    /// Set a text property.
+    pub fn set_text(&mut self, name: &str, text: &str) {
+        self.set(name, PropertyValue::Text(text.to_string()));
+    }
+
+    /// Set a number property. Whole numbers in the range of an `i32` are
+    /// stored as integers, like Excel, and other numbers as doubles.
+    pub fn set_number(&mut self, name: &str, number: f64) {
+        self.set(name, PropertyValue::Number(number));
+    }
+
+    /// Set a date property, which is stored as a UTC timestamp.
+    pub fn set_date(&mut self, name: &str, date: SystemTime) {
+        self.set(name, PropertyValue::Date(date));
+    }
+
+    /// Set a yes or no property.
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.set(name, PropertyValue::Bool(value));
+    }
+
+    /// Check if there are no properties, in which case the part isn't
+    /// needed.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    // Set a property, replacing the value of an existing property with the
+    // same name so that it keeps its position.
+    fn set(&mut self, name: &str, value: PropertyValue) {
+        match self
+            .properties
+            .iter_mut()
+            .find(|(existing, _)| existing == name)
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.properties.push((name.to_string(), value)),
+        }
+    }
+
+    /// Write the `docProps/custom.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns", CUSTOM_PROPERTIES_NS),
+            ("xmlns:vt", DOC_PROPS_VTYPES_NS),
+        ];
+        writer.xml_start_tag("Properties", &attributes);
+
+        // The property ids start at 2, since 0 and 1 are reserved.
+        for (index, (name, value)) in self.properties.iter().enumerate() {
+            let pid = (index + 2).to_string();
+            let attributes = vec![
+                ("fmtid", USER_DEFINED_FMTID),
+                ("pid", pid.as_str()),
+                ("name", name.as_str()),
+            ];
+            writer.xml_start_tag("property", &attributes);
+            write_value(writer, value);
+            writer.xml_end_tag("property");
+        }
+
+        writer.xml_end_tag("Properties");
+    }
+}
+
+// Write the variant type element of a property value.
+fn write_value(writer: &mut XMLWriter<impl Write>, value: &PropertyValue) {
+    match value {
+        PropertyValue::Text(text) => writer.xml_data_element("vt:lpwstr", text, &vec![]),
+        PropertyValue::Number(number)
+            if number.fract() == 0.0
+                && (f64::from(i32::MIN)..=f64::from(i32::MAX)).contains(number) =>
+        {
+            writer.xml_data_element_i64("vt:i4", *number as i64, &vec![]);
+        }
+        PropertyValue::Number(number) => writer.xml_data_element_f64("vt:r8", *number, &vec![]),
+        PropertyValue::Date(date) => {
+            writer.xml_data_element("vt:filetime", &w3cdtf_datetime(*date), &vec![]);
+        }
+        PropertyValue::Bool(value) => {
+            let value = if *value { "true" } else { "false" };
+            writer.xml_data_element("vt:bool", value, &vec![]);
+        }
+    }
+}
+
+impl XlsxPart for CustomProperties {
+    fn part_name(&self) -> &str {
+        "docProps/custom.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        CUSTOM_PROPERTIES_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::CustomProperties;
+    use crate::XMLWriter;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_custom_properties() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" "#,
+            r#"xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes">"#,
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Checked by">"#,
+            r#"<vt:lpwstr>Ann &amp; Bob</vt:lpwstr></property>"#,
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="3" name="Revision">"#,
+            r#"<vt:i4>-4</vt:i4></property>"#,
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="4" name="Cost">"#,
+            r#"<vt:r8>12.5</vt:r8></property>"#,
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="5" name="Large">"#,
+            r#"<vt:r8>3000000000</vt:r8></property>"#,
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="6" name="Approved">"#,
+            r#"<vt:filetime>2023-01-01T00:00:00Z</vt:filetime></property>"#,
+            r#"<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="7" name="Final">"#,
+            r#"<vt:bool>false</vt:bool></property></Properties>"#,
+        );
+
+        let mut custom = CustomProperties::new();
+        assert!(custom.is_empty());
+
+        custom.set_text("Checked by", "Ann & Bob");
+        custom.set_number("Revision", 3.0);
+        custom.set_number("Cost", 12.5);
+        custom.set_number("Large", 3e9);
+        custom.set_date("Approved", UNIX_EPOCH + Duration::from_secs(1_672_531_200));
+        custom.set_bool("Final", true);
+        custom.set_number("Revision", -4.0);
+        custom.set_bool("Final", false);
+        assert!(!custom.is_empty());
+
+        let mut writer = XMLWriter::new(vec![]);
+        custom.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+}
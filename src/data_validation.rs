@@ -0,0 +1,519 @@
+//! The data validations of a worksheet.
+//!
+//! A [`DataValidation`] restricts the values that can be entered in a range
+//! of cells, optionally with an input message that is shown when a cell is
+//! selected and an error message that is shown for invalid values. It is
+//! added to a range of cells with
+//! [`Worksheet::add_data_validation()`](crate::worksheet::Worksheet::add_data_validation).
+//!
+//! ```
+//! use excel_xmlwriter::data_validation::{DataValidation, DataValidationCriteria};
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! # fn main() -> Result<(), excel_xmlwriter::error::XlsxError> {
+//! let mut worksheet = Worksheet::new();
+//!
+//! worksheet.add_data_validation(
+//!     1,
+//!     0,
+//!     9,
+//!     0,
+//!     &DataValidation::new()
+//!         .allow_whole_number(DataValidationCriteria::Between(
+//!             "1".to_string(),
+//!             "10".to_string(),
+//!         ))
+//!         .set_input_title("Quantity")
+//!         .set_input_message("Enter a number from 1 to 10"),
+//! )?;
+//! worksheet.add_data_validation(
+//!     1,
+//!     1,
+//!     9,
+//!     1,
+//!     &DataValidation::new().allow_list_strings(&["Open", "Closed"]),
+//! )?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::error::XlsxError;
+use crate::XMLWriter;
+
+// Excel's limits on the length of the titles, and of the messages and the
+// list of values.
+const MAX_TITLE_LENGTH: usize = 32;
+const MAX_MESSAGE_LENGTH: usize = 255;
+
+/// The criteria of a number, date, time or text length data validation. The
+/// values are numbers, or formulas such as `$B$1` or `DATE(2022,1,1)`, with
+/// or without a leading `=`. Dates and times are Excel serial numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataValidationCriteria {
+    /// The value is between the two values, inclusive.
+    Between(String, String),
+
+    /// The value isn't between the two values.
+    NotBetween(String, String),
+
+    /// The value is equal to the value.
+    EqualTo(String),
+
+    /// The value isn't equal to the value.
+    NotEqualTo(String),
+
+    /// The value is greater than the value.
+    GreaterThan(String),
+
+    /// The value is less than the value.
+    LessThan(String),
+
+    /// The value is greater than or equal to the value.
+    GreaterThanOrEqualTo(String),
+
+    /// The value is less than or equal to the value.
+    LessThanOrEqualTo(String),
+}
+
+/// The style of the error message of a data validation, which decides
+/// whether an invalid value can still be entered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataValidationErrorStyle {
+    /// Invalid values are rejected. This is the default.
+    #[default]
+    Stop,
+
+    /// The user is warned and can choose to keep an invalid value.
+    Warning,
+
+    /// The user is informed and an invalid value is kept.
+    Information,
+}
+
+// The type of values that a data validation allows.
+#[derive(Clone, Debug, PartialEq)]
+enum Rule {
+    Any,
+    WholeNumber(DataValidationCriteria),
+    DecimalNumber(DataValidationCriteria),
+    Date(DataValidationCriteria),
+    Time(DataValidationCriteria),
+    TextLength(DataValidationCriteria),
+    List(String),
+    Custom(String),
+}
+
+/// A data validation and its messages.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataValidation {
+    rule: Rule,
+    ignore_blank: bool,
+    show_dropdown: bool,
+    input_title: String,
+    input_message: String,
+    show_input_message: bool,
+    error_title: String,
+    error_message: String,
+    error_style: DataValidationErrorStyle,
+    show_error_message: bool,
+}
+
+impl Default for DataValidation {
+    fn default() -> Self {
+        DataValidation::new()
+    }
+}
+
+impl DataValidation {
+    /// Create a data validation that allows any value, which is used to only
+    /// show an input message.
+    pub fn new() -> DataValidation {
+        DataValidation {
+            rule: Rule::Any,
+            ignore_blank: true,
+            show_dropdown: true,
+            input_title: String::new(),
+            input_message: String::new(),
+            show_input_message: true,
+            error_title: String::new(),
+            error_message: String::new(),
+            error_style: DataValidationErrorStyle::Stop,
+            show_error_message: true,
+        }
+    }
+
+    /// Allow whole numbers that match the criteria.
+    pub fn allow_whole_number(mut self, criteria: DataValidationCriteria) -> DataValidation {
+        self.rule = Rule::WholeNumber(criteria);
+        self
+    }
+
+    /// Allow decimal numbers that match the criteria.
+    pub fn allow_decimal_number(mut self, criteria: DataValidationCriteria) -> DataValidation {
+        self.rule = Rule::DecimalNumber(criteria);
+        self
+    }
+
+    /// Allow dates that match the criteria.
+    pub fn allow_date(mut self, criteria: DataValidationCriteria) -> DataValidation {
+        self.rule = Rule::Date(criteria);
+        self
+    }
+
+    /// Allow times that match the criteria.
+    pub fn allow_time(mut self, criteria: DataValidationCriteria) -> DataValidation {
+        self.rule = Rule::Time(criteria);
+        self
+    }
+
+    /// Allow text whose length matches the criteria.
+    pub fn allow_text_length(mut self, criteria: DataValidationCriteria) -> DataValidation {
+        self.rule = Rule::TextLength(criteria);
+        self
+    }
+
+    /// Allow the values in a list, which are shown in a dropdown. Excel
+    /// separates the values with commas, so they can't contain commas, and
+    /// limits the joined list to 255 characters.
+    pub fn allow_list_strings(mut self, values: &[&str]) -> DataValidation {
+        self.rule = Rule::List(format!("\"{}\"", values.join(",").replace('"', "\"\"")));
+        self
+    }
+
+    /// Allow the values in a range or defined name, like `$E$1:$E$5`, which
+    /// are shown in a dropdown. The formula can have a leading `=`.
+    pub fn allow_list_formula(mut self, formula: &str) -> DataValidation {
+        self.rule = Rule::List(strip_equals(formula));
+        self
+    }
+
+    /// Allow values where a formula is true. Cell references in the formula
+    /// are relative to the first cell of the range. The formula can have a
+    /// leading `=`.
+    pub fn allow_custom(mut self, formula: &str) -> DataValidation {
+        self.rule = Rule::Custom(strip_equals(formula));
+        self
+    }
+
+    /// Set whether blank cells are valid. The default is `true`.
+    pub fn set_ignore_blank(mut self, ignore_blank: bool) -> DataValidation {
+        self.ignore_blank = ignore_blank;
+        self
+    }
+
+    /// Set whether the dropdown of a list is shown. The default is `true`.
+    pub fn set_show_dropdown(mut self, show_dropdown: bool) -> DataValidation {
+        self.show_dropdown = show_dropdown;
+        self
+    }
+
+    /// Set the title of the input message, up to 32 characters.
+    pub fn set_input_title(mut self, title: &str) -> DataValidation {
+        self.input_title = title.to_string();
+        self
+    }
+
+    /// Set the input message that is shown when a cell is selected, up to
+    /// 255 characters.
+    pub fn set_input_message(mut self, message: &str) -> DataValidation {
+        self.input_message = message.to_string();
+        self
+    }
+
+    /// Set whether the input message is shown. The default is `true`.
+    pub fn set_show_input_message(mut self, show: bool) -> DataValidation {
+        self.show_input_message = show;
+        self
+    }
+
+    /// Set the title of the error message, up to 32 characters.
+    pub fn set_error_title(mut self, title: &str) -> DataValidation {
+        self.error_title = title.to_string();
+        self
+    }
+
+    /// Set the error message that is shown for an invalid value, up to 255
+    /// characters. Excel shows a generic message if it isn't set.
+    pub fn set_error_message(mut self, message: &str) -> DataValidation {
+        self.error_message = message.to_string();
+        self
+    }
+
+    /// Set the style of the error message.
+    pub fn set_error_style(mut self, style: DataValidationErrorStyle) -> DataValidation {
+        self.error_style = style;
+        self
+    }
+
+    /// Set whether the error message is shown, and invalid values are
+    /// rejected. The default is `true`.
+    pub fn set_show_error_message(mut self, show: bool) -> DataValidation {
+        self.show_error_message = show;
+        self
+    }
+
+    // Check the lengths of the titles, messages and list against Excel's
+    // limits, for the data validation of a range like `B2:B10`.
+    pub(crate) fn validate(&self, range: &str) -> Result<(), XlsxError> {
+        let list_length = match &self.rule {
+            // The list length doesn't include the surrounding quotes.
+            Rule::List(list) if list.starts_with('"') => list.chars().count() - 2,
+            _ => 0,
+        };
+
+        let lengths = [
+            (
+                "input title",
+                self.input_title.chars().count(),
+                MAX_TITLE_LENGTH,
+            ),
+            (
+                "error title",
+                self.error_title.chars().count(),
+                MAX_TITLE_LENGTH,
+            ),
+            (
+                "input message",
+                self.input_message.chars().count(),
+                MAX_MESSAGE_LENGTH,
+            ),
+            (
+                "error message",
+                self.error_message.chars().count(),
+                MAX_MESSAGE_LENGTH,
+            ),
+            ("list of values", list_length, MAX_MESSAGE_LENGTH),
+        ];
+
+        for (property, length, max_length) in lengths {
+            if length > max_length {
+                return Err(XlsxError::DataValidationTooLong {
+                    property: property.to_string(),
+                    range: range.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Write the <dataValidation> element for a range like `B2:B10`.
+    pub(crate) fn write_data_validation(&self, writer: &mut XMLWriter<impl Write>, sqref: &str) {
+        let (rule_type, criteria) = match &self.rule {
+            Rule::Any => (None, None),
+            Rule::WholeNumber(criteria) => (Some("whole"), Some(criteria)),
+            Rule::DecimalNumber(criteria) => (Some("decimal"), Some(criteria)),
+            Rule::Date(criteria) => (Some("date"), Some(criteria)),
+            Rule::Time(criteria) => (Some("time"), Some(criteria)),
+            Rule::TextLength(criteria) => (Some("textLength"), Some(criteria)),
+            Rule::List(_) => (Some("list"), None),
+            Rule::Custom(_) => (Some("custom"), None),
+        };
+
+        let (operator, formulas) = match criteria {
+            Some(criteria) => {
+                let (operator, values) = match criteria {
+                    DataValidationCriteria::Between(min, max) => ("between", vec![min, max]),
+                    DataValidationCriteria::NotBetween(min, max) => ("notBetween", vec![min, max]),
+                    DataValidationCriteria::EqualTo(value) => ("equal", vec![value]),
+                    DataValidationCriteria::NotEqualTo(value) => ("notEqual", vec![value]),
+                    DataValidationCriteria::GreaterThan(value) => ("greaterThan", vec![value]),
+                    DataValidationCriteria::LessThan(value) => ("lessThan", vec![value]),
+                    DataValidationCriteria::GreaterThanOrEqualTo(value) => {
+                        ("greaterThanOrEqual", vec![value])
+                    }
+                    DataValidationCriteria::LessThanOrEqualTo(value) => {
+                        ("lessThanOrEqual", vec![value])
+                    }
+                };
+                let formulas = values
+                    .into_iter()
+                    .map(|value| strip_equals(value))
+                    .collect();
+
+                (Some(operator), formulas)
+            }
+            None => match &self.rule {
+                Rule::List(formula) | Rule::Custom(formula) => (None, vec![formula.clone()]),
+                _ => (None, vec![]),
+            },
+        };
+
+        let mut attributes = vec![];
+        if let Some(rule_type) = rule_type {
+            attributes.push(("type", rule_type));
+        }
+        match self.error_style {
+            DataValidationErrorStyle::Stop => {}
+            DataValidationErrorStyle::Warning => attributes.push(("errorStyle", "warning")),
+            DataValidationErrorStyle::Information => attributes.push(("errorStyle", "information")),
+        }
+        // The operator defaults to "between", which Excel doesn't write.
+        if let Some(operator) = operator.filter(|operator| *operator != "between") {
+            attributes.push(("operator", operator));
+        }
+        if self.ignore_blank {
+            attributes.push(("allowBlank", "1"));
+        }
+        // Despite its name, the showDropDown attribute hides the dropdown.
+        if !self.show_dropdown {
+            attributes.push(("showDropDown", "1"));
+        }
+        if self.show_input_message {
+            attributes.push(("showInputMessage", "1"));
+        }
+        if self.show_error_message {
+            attributes.push(("showErrorMessage", "1"));
+        }
+        if !self.error_title.is_empty() {
+            attributes.push(("errorTitle", &self.error_title));
+        }
+        if !self.error_message.is_empty() {
+            attributes.push(("error", &self.error_message));
+        }
+        if !self.input_title.is_empty() {
+            attributes.push(("promptTitle", &self.input_title));
+        }
+        if !self.input_message.is_empty() {
+            attributes.push(("prompt", &self.input_message));
+        }
+        attributes.push(("sqref", sqref));
+
+        if formulas.is_empty() {
+            writer.xml_empty_tag("dataValidation", &attributes);
+            return;
+        }
+
+        writer.xml_start_tag("dataValidation", &attributes);
+        for (index, formula) in formulas.iter().enumerate() {
+            let tag = format!("formula{}", index + 1);
+            writer.xml_data_element(&tag, formula, &vec![]);
+        }
+        writer.xml_end_tag("dataValidation");
+    }
+}
+
+// Store a formula or value without its leading `=`.
+fn strip_equals(formula: &str) -> String {
+    formula.strip_prefix('=').unwrap_or(formula).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{DataValidation, DataValidationCriteria, DataValidationErrorStyle};
+    use crate::error::XlsxError;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn write_data_validation(data_validation: &DataValidation) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        data_validation.write_data_validation(&mut writer, "B2:B10");
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_write_data_validations() {
+        let tests = [
+            (
+                DataValidation::new().set_input_message("Any value"),
+                r#"<dataValidation allowBlank="1" showInputMessage="1" showErrorMessage="1" prompt="Any value" sqref="B2:B10"/>"#,
+            ),
+            (
+                DataValidation::new().allow_whole_number(DataValidationCriteria::Between(
+                    "1".to_string(),
+                    "=$C$1".to_string(),
+                )),
+                concat!(
+                    r#"<dataValidation type="whole" allowBlank="1" showInputMessage="1" showErrorMessage="1" sqref="B2:B10">"#,
+                    r#"<formula1>1</formula1><formula2>$C$1</formula2></dataValidation>"#,
+                ),
+            ),
+            (
+                DataValidation::new()
+                    .allow_date(DataValidationCriteria::GreaterThan(
+                        "DATE(2022,1,1)".to_string(),
+                    ))
+                    .set_error_style(DataValidationErrorStyle::Warning)
+                    .set_error_title("Date")
+                    .set_error_message("Dates <= 2022 are \"old\"")
+                    .set_ignore_blank(false),
+                concat!(
+                    r#"<dataValidation type="date" errorStyle="warning" operator="greaterThan" "#,
+                    r#"showInputMessage="1" showErrorMessage="1" errorTitle="Date" "#,
+                    r#"error="Dates &lt;= 2022 are &quot;old&quot;" sqref="B2:B10">"#,
+                    r#"<formula1>DATE(2022,1,1)</formula1></dataValidation>"#,
+                ),
+            ),
+            (
+                DataValidation::new()
+                    .allow_list_strings(&["Open", "Say \"hi\""])
+                    .set_show_dropdown(false),
+                concat!(
+                    r#"<dataValidation type="list" allowBlank="1" showDropDown="1" showInputMessage="1" showErrorMessage="1" sqref="B2:B10">"#,
+                    r#"<formula1>"Open,Say ""hi"""</formula1></dataValidation>"#,
+                ),
+            ),
+            (
+                DataValidation::new().allow_list_formula("=$E$1:$E$5"),
+                concat!(
+                    r#"<dataValidation type="list" allowBlank="1" showInputMessage="1" showErrorMessage="1" sqref="B2:B10">"#,
+                    r#"<formula1>$E$1:$E$5</formula1></dataValidation>"#,
+                ),
+            ),
+            (
+                DataValidation::new()
+                    .allow_custom("=ISTEXT(B2)")
+                    .set_show_input_message(false)
+                    .set_show_error_message(false),
+                concat!(
+                    r#"<dataValidation type="custom" allowBlank="1" sqref="B2:B10">"#,
+                    r#"<formula1>ISTEXT(B2)</formula1></dataValidation>"#,
+                ),
+            ),
+        ];
+
+        for (data_validation, expected) in tests {
+            assert_eq!(write_data_validation(&data_validation), expected);
+        }
+    }
+
+    #[test]
+    fn test_data_validation_limits() {
+        let long_list: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        let long_list: Vec<&str> = long_list.iter().map(|value| value.as_str()).collect();
+
+        assert_eq!(
+            DataValidation::new()
+                .set_input_title(&"x".repeat(32))
+                .set_error_message(&"€".repeat(255))
+                .validate("B2"),
+            Ok(())
+        );
+        assert_eq!(
+            DataValidation::new()
+                .set_input_title(&"x".repeat(33))
+                .validate("B2"),
+            Err(XlsxError::DataValidationTooLong {
+                property: "input title".to_string(),
+                range: "B2".to_string(),
+            })
+        );
+        assert_eq!(
+            DataValidation::new()
+                .allow_list_strings(&long_list)
+                .validate("B2"),
+            Err(XlsxError::DataValidationTooLong {
+                property: "list of values".to_string(),
+                range: "B2".to_string(),
+            })
+        );
+    }
+}
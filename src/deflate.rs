@@ -0,0 +1,317 @@
+// A small RFC 1951 deflate compressor for the xlsx packager.
+//
+// The data is compressed as a single block with the fixed Huffman codes and
+// greedy LZ77 matching over a 32KB window. This gives most of the benefit of
+// full deflate for the repetitive XML in xlsx parts without the complexity
+// of building dynamic Huffman tables.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+const WINDOW_SIZE: usize = 32_768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+// The base lengths and extra bits of the length codes 257 to 285.
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// The base distances and extra bits of the distance codes 0 to 29.
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+// Write bits to a byte buffer, least significant bit first.
+struct BitWriter {
+    data: Vec<u8>,
+    buffer: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new(capacity: usize) -> BitWriter {
+        BitWriter {
+            data: Vec::with_capacity(capacity),
+            buffer: 0,
+            bits: 0,
+        }
+    }
+
+    fn write_bits(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.bits;
+        self.bits += bits;
+
+        while self.bits >= 8 {
+            self.data.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting with their most significant bit.
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.data.push(self.buffer as u8);
+        }
+        self.data
+    }
+}
+
+// Write a literal/length symbol with its fixed Huffman code.
+fn write_symbol(writer: &mut BitWriter, symbol: u16) {
+    let symbol = symbol as u32;
+
+    match symbol {
+        0..=143 => writer.write_code(0x30 + symbol, 8),
+        144..=255 => writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => writer.write_code(symbol - 256, 7),
+        _ => writer.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+// Write a back reference as a length code and a distance code, each with
+// their extra bits.
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let index = LENGTH_BASES.partition_point(|base| *base as usize <= length) - 1;
+    write_symbol(writer, 257 + index as u16);
+    writer.write_bits(
+        (length - LENGTH_BASES[index] as usize) as u32,
+        LENGTH_EXTRA_BITS[index] as u32,
+    );
+
+    let index = DISTANCE_BASES.partition_point(|base| *base as usize <= distance) - 1;
+    writer.write_code(index as u32, 5);
+    writer.write_bits(
+        (distance - DISTANCE_BASES[index] as usize) as u32,
+        DISTANCE_EXTRA_BITS[index] as u32,
+    );
+}
+
+// Find earlier occurrences of byte sequences in the window using chains of
+// positions with the same hash of their first 3 bytes.
+struct Matcher<'a> {
+    data: &'a [u8],
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(data: &'a [u8]) -> Matcher<'a> {
+        Matcher {
+            data,
+            head: vec![usize::MAX; 1 << HASH_BITS],
+            prev: vec![usize::MAX; WINDOW_SIZE],
+        }
+    }
+
+    fn hash(&self, position: usize) -> usize {
+        let data = self.data;
+        let value = (data[position] as usize) << 16
+            | (data[position + 1] as usize) << 8
+            | data[position + 2] as usize;
+
+        (value.wrapping_mul(2_654_435_761) >> 8) & ((1 << HASH_BITS) - 1)
+    }
+
+    // Add a position to the hash chains.
+    fn insert(&mut self, position: usize) {
+        if position + MIN_MATCH > self.data.len() {
+            return;
+        }
+
+        let hash = self.hash(position);
+        self.prev[position % WINDOW_SIZE] = self.head[hash];
+        self.head[hash] = position;
+    }
+
+    // Get the length and distance of the longest match for the data at a
+    // position, or a length of 0 if there is no match.
+    fn longest_match(&self, position: usize) -> (usize, usize) {
+        let data = self.data;
+        if position + MIN_MATCH > data.len() {
+            return (0, 0);
+        }
+
+        let max_length = MAX_MATCH.min(data.len() - position);
+        let mut best = (0, 0);
+        let mut candidate = self.head[self.hash(position)];
+
+        for _ in 0..MAX_CHAIN {
+            if candidate == usize::MAX || position - candidate > WINDOW_SIZE - 1 {
+                break;
+            }
+
+            let length = data[candidate..]
+                .iter()
+                .zip(&data[position..position + max_length])
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            if length > best.0 {
+                best = (length, position - candidate);
+                if length == max_length {
+                    break;
+                }
+            }
+
+            let next = self.prev[candidate % WINDOW_SIZE];
+            if next == usize::MAX || next >= candidate {
+                break;
+            }
+            candidate = next;
+        }
+
+        best
+    }
+}
+
+// Compress data into a raw deflate stream.
+pub(crate) fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new(data.len() / 4 + 16);
+    let mut matcher = Matcher::new(data);
+
+    // A single final block with the fixed Huffman codes.
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut position = 0;
+    while position < data.len() {
+        let (length, distance) = matcher.longest_match(position);
+
+        if length >= MIN_MATCH {
+            write_match(&mut writer, length, distance);
+            for offset in 0..length {
+                matcher.insert(position + offset);
+            }
+            position += length;
+        } else {
+            write_symbol(&mut writer, data[position] as u16);
+            matcher.insert(position);
+            position += 1;
+        }
+    }
+
+    // End of block.
+    write_symbol(&mut writer, 256);
+
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{deflate, DISTANCE_BASES, DISTANCE_EXTRA_BITS, LENGTH_BASES, LENGTH_EXTRA_BITS};
+
+    use pretty_assertions::assert_eq;
+
+    // A minimal inflater for the fixed Huffman blocks written by deflate().
+    fn inflate_fixed(data: &[u8]) -> Vec<u8> {
+        let mut position = 0;
+        let mut bit = |count: u32| -> u32 {
+            let mut value = 0;
+            for i in 0..count {
+                let byte = data[position / 8];
+                value |= (((byte >> (position % 8)) & 1) as u32) << i;
+                position += 1;
+            }
+            value
+        };
+
+        assert_eq!(bit(1), 1, "BFINAL");
+        assert_eq!(bit(2), 1, "BTYPE");
+
+        let mut output: Vec<u8> = vec![];
+        loop {
+            // Read the Huffman code a bit at a time, most significant first.
+            let mut code = 0;
+            for _ in 0..7 {
+                code = code << 1 | bit(1);
+            }
+            let symbol = if code <= 0x17 {
+                256 + code
+            } else {
+                code = code << 1 | bit(1);
+                match code {
+                    0x30..=0xBF => code - 0x30,
+                    0xC0..=0xC7 => 280 + code - 0xC0,
+                    _ => 144 + (code << 1 | bit(1)) - 0x190,
+                }
+            };
+
+            match symbol {
+                0..=255 => output.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let index = (symbol - 257) as usize;
+                    let length = LENGTH_BASES[index] as usize
+                        + bit(LENGTH_EXTRA_BITS[index] as u32) as usize;
+
+                    let mut index = 0;
+                    for _ in 0..5 {
+                        index = index << 1 | bit(1) as usize;
+                    }
+                    let distance = DISTANCE_BASES[index] as usize
+                        + bit(DISTANCE_EXTRA_BITS[index] as u32) as usize;
+
+                    for _ in 0..length {
+                        output.push(output[output.len() - distance]);
+                    }
+                }
+            }
+        }
+
+        output
+    }
+
+    #[test]
+    fn test_deflate_round_trip() {
+        let mut xml = String::new();
+        for row in 1..2000 {
+            xml.push_str(&format!(
+                r#"<row r="{row}"><c r="A{row}"><v>{}</v></c></row>"#,
+                row * 7
+            ));
+        }
+
+        let mut bytes: Vec<u8> = vec![];
+        let mut seed: u32 = 1;
+        for _ in 0..70_000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            bytes.push((seed >> 16) as u8 % 4);
+        }
+
+        let tests: [&[u8]; 7] = [
+            b"",
+            b"a",
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            b"\x00\xff\x90\x8f some bytes that need 9 bit codes \xc8\xfe",
+            "Unicode: αβγ αβγ αβγ 日本語 日本語".as_bytes(),
+            xml.as_bytes(),
+            &bytes,
+        ];
+
+        for data in tests {
+            let compressed = deflate(data);
+            assert_eq!(inflate_fixed(&compressed), data);
+        }
+
+        // Repetitive XML compresses well.
+        assert!(deflate(xml.as_bytes()).len() < xml.len() / 4);
+    }
+}
@@ -0,0 +1,420 @@
+//! The writer for the `xl/drawings/drawingN.xml` parts, which show the
+//! images and charts of a worksheet.
+//!
+//! Images are added to a worksheet with
+//! [`Worksheet::insert_image()`](crate::worksheet::Worksheet::insert_image)
+//! and charts with
+//! [`Worksheet::insert_chart()`](crate::worksheet::Worksheet::insert_chart).
+//! The worksheet then refers to a drawing part, created with
+//! [`Worksheet::drawing()`](crate::worksheet::Worksheet::drawing), which
+//! anchors each object to the cells under it and has relationships of its
+//! own to the images in `xl/media/` and to the chart parts.
+//!
+//! The drawing and the images have numbers that are unique in the workbook,
+//! starting at 1, which are set with
+//! [`Worksheet::set_drawing_number()`](crate::worksheet::Worksheet::set_drawing_number)
+//! and
+//! [`Worksheet::set_image_number()`](crate::worksheet::Worksheet::set_image_number).
+//! The images are stored with the part names from
+//! [`Worksheet::media()`](crate::worksheet::Worksheet::media).
+//!
+//! Like the boxes of notes, the objects are positioned from the default
+//! column width of 64 pixels and row height of 20 pixels.
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! # let path = std::env::temp_dir().join("drawing_doc.gif");
+//! # std::fs::write(&path, [b"GIF89a".as_slice(), &[120, 0, 40, 0]].concat())?;
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! worksheet.insert_image(1, 2, &path)?;
+//!
+//! for (part_name, image) in worksheet.media() {
+//!     let image_type = image.image_type();
+//!     content_types.add_default(image_type.extension(), image_type.content_type());
+//!     packager.add_part(&part_name, image.data())?;
+//! }
+//!
+//! if let Some(drawing) = worksheet.drawing() {
+//!     content_types.add_drawing(1);
+//!     packager.add_custom_part(&drawing)?;
+//!     packager.add_xml_part("xl/drawings/_rels/drawing1.xml.rels", |writer| {
+//!         drawing.relationships().assemble_xml_file(writer)
+//!     })?;
+//! }
+//! # std::fs::remove_file(&path)?;
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+#[cfg(feature = "charts")]
+use crate::chart::Chart;
+#[cfg(feature = "charts")]
+use crate::constants::CHART_NS;
+use crate::constants::{
+    DRAWINGML_NS, DRAWING_CONTENT_TYPE, RELATIONSHIPS_NS, SPREADSHEET_DRAWING_NS,
+};
+use crate::image::Image;
+use crate::part::XlsxPart;
+use crate::relationship::Relationships;
+use crate::utility::{COL_MAX, ROW_MAX};
+use crate::XMLWriter;
+
+// The default column width and row height, in pixels.
+const COL_WIDTH: u32 = 64;
+const ROW_HEIGHT: u32 = 20;
+
+// The size of a pixel in English Metric Units, at 96 dpi.
+const EMU_PER_PIXEL: u32 = 9525;
+
+// Excel's default size of a chart, in pixels.
+#[cfg(feature = "charts")]
+const CHART_WIDTH: u32 = 480;
+#[cfg(feature = "charts")]
+const CHART_HEIGHT: u32 = 288;
+
+// An image or chart in the drawing, with the target of its relationship.
+#[derive(Clone, Debug, PartialEq)]
+enum Object {
+    Image {
+        target: String,
+        alt_text: Option<String>,
+    },
+    #[cfg(feature = "charts")]
+    Chart { target: String },
+}
+
+// An object and the cells that it is anchored to.
+#[derive(Clone, Debug, PartialEq)]
+struct Anchor {
+    row: u32,
+    col: u16,
+    width: u32,
+    height: u32,
+    object: Object,
+}
+
+/// The `xl/drawings/drawingN.xml` part with the images and charts of a
+/// worksheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Drawing {
+    part_name: String,
+    anchors: Vec<Anchor>,
+}
+
+impl Drawing {
+    // Create an empty drawing part.
+    pub(crate) fn new(number: u32) -> Drawing {
+        Drawing {
+            part_name: format!("xl/drawings/drawing{number}.xml"),
+            anchors: vec![],
+        }
+    }
+
+    // Add an image at the top left corner of a cell, with the part name of
+    // its media part.
+    pub(crate) fn add_image(&mut self, row: u32, col: u16, image: &Image, media_part_name: &str) {
+        let (width, height) = image.size();
+        let target = media_part_name.replacen("xl/", "../", 1);
+
+        self.anchors.push(Anchor {
+            row,
+            col,
+            width,
+            height,
+            object: Object::Image {
+                target,
+                alt_text: image.alt_text().map(str::to_string),
+            },
+        });
+    }
+
+    // Add a chart at the top left corner of a cell, at Excel's default size.
+    #[cfg(feature = "charts")]
+    pub(crate) fn add_chart(&mut self, row: u32, col: u16, chart: &Chart) {
+        self.anchors.push(Anchor {
+            row,
+            col,
+            width: CHART_WIDTH,
+            height: CHART_HEIGHT,
+            object: Object::Chart {
+                target: format!("../charts/chart{}.xml", chart.number()),
+            },
+        });
+    }
+
+    /// The relationships of the drawing to its images and charts, for the
+    /// `xl/drawings/_rels/drawingN.xml.rels` part.
+    pub fn relationships(&self) -> Relationships {
+        let mut relationships = Relationships::new();
+
+        for anchor in &self.anchors {
+            match &anchor.object {
+                Object::Image { target, .. } => {
+                    relationships.add_document_relationship("image", target)
+                }
+                #[cfg(feature = "charts")]
+                Object::Chart { target } => {
+                    relationships.add_document_relationship("chart", target)
+                }
+            };
+        }
+
+        relationships
+    }
+
+    /// Write the `xl/drawings/drawingN.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns:xdr", SPREADSHEET_DRAWING_NS),
+            ("xmlns:a", DRAWINGML_NS),
+        ];
+        writer.xml_start_tag("xdr:wsDr", &attributes);
+
+        for (index, anchor) in self.anchors.iter().enumerate() {
+            write_anchor(writer, index, anchor);
+        }
+
+        writer.xml_end_tag("xdr:wsDr");
+    }
+}
+
+impl XlsxPart for Drawing {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        DRAWING_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+// Write the `<xdr:twoCellAnchor>` element of an object. Images move with
+// their top left cell but keep their size, like images inserted in Excel.
+fn write_anchor(writer: &mut XMLWriter<impl Write>, index: usize, anchor: &Anchor) {
+    let attributes = match anchor.object {
+        Object::Image { .. } => vec![("editAs", "oneCell")],
+        #[cfg(feature = "charts")]
+        Object::Chart { .. } => vec![],
+    };
+    writer.xml_start_tag("xdr:twoCellAnchor", &attributes);
+
+    // Objects at the edge of the worksheet end in its last cell.
+    let end_col = (anchor.col as u32 + anchor.width / COL_WIDTH).min(COL_MAX as u32 - 1);
+    let end_row = anchor
+        .row
+        .saturating_add(anchor.height / ROW_HEIGHT)
+        .min(ROW_MAX - 1);
+    write_cell_position(writer, "xdr:from", anchor.col.into(), 0, anchor.row, 0);
+    write_cell_position(
+        writer,
+        "xdr:to",
+        end_col,
+        anchor.width % COL_WIDTH,
+        end_row,
+        anchor.height % ROW_HEIGHT,
+    );
+
+    // The shape ids start at 2, after the id of the drawing itself.
+    let id = (index + 2).to_string();
+    let rel_id = format!("rId{}", index + 1);
+    let x = (anchor.col as u64 * (COL_WIDTH * EMU_PER_PIXEL) as u64).to_string();
+    let y = (anchor.row as u64 * (ROW_HEIGHT * EMU_PER_PIXEL) as u64).to_string();
+    let cx = (anchor.width * EMU_PER_PIXEL).to_string();
+    let cy = (anchor.height * EMU_PER_PIXEL).to_string();
+
+    match &anchor.object {
+        Object::Image { alt_text, .. } => {
+            let name = format!("Picture {}", index + 1);
+
+            writer.xml_start_tag("xdr:pic", &vec![]);
+            writer.xml_start_tag("xdr:nvPicPr", &vec![]);
+            let mut attributes = vec![("id", id.as_str()), ("name", name.as_str())];
+            if let Some(alt_text) = alt_text {
+                attributes.push(("descr", alt_text.as_str()));
+            }
+            writer.xml_empty_tag("xdr:cNvPr", &attributes);
+            writer.xml_start_tag("xdr:cNvPicPr", &vec![]);
+            writer.xml_empty_tag("a:picLocks", &vec![("noChangeAspect", "1")]);
+            writer.xml_end_tag("xdr:cNvPicPr");
+            writer.xml_end_tag("xdr:nvPicPr");
+
+            writer.xml_start_tag("xdr:blipFill", &vec![]);
+            let attributes = vec![("xmlns:r", RELATIONSHIPS_NS), ("r:embed", rel_id.as_str())];
+            writer.xml_empty_tag("a:blip", &attributes);
+            writer.xml_start_tag("a:stretch", &vec![]);
+            writer.xml_empty_tag("a:fillRect", &vec![]);
+            writer.xml_end_tag("a:stretch");
+            writer.xml_end_tag("xdr:blipFill");
+
+            writer.xml_start_tag("xdr:spPr", &vec![]);
+            write_transform(writer, "a:xfrm", &x, &y, &cx, &cy);
+            writer.xml_start_tag("a:prstGeom", &vec![("prst", "rect")]);
+            writer.xml_empty_tag("a:avLst", &vec![]);
+            writer.xml_end_tag("a:prstGeom");
+            writer.xml_end_tag("xdr:spPr");
+            writer.xml_end_tag("xdr:pic");
+        }
+        #[cfg(feature = "charts")]
+        Object::Chart { .. } => {
+            let name = format!("Chart {}", index + 1);
+
+            writer.xml_start_tag("xdr:graphicFrame", &vec![("macro", "")]);
+            writer.xml_start_tag("xdr:nvGraphicFramePr", &vec![]);
+            let attributes = vec![("id", id.as_str()), ("name", name.as_str())];
+            writer.xml_empty_tag("xdr:cNvPr", &attributes);
+            writer.xml_empty_tag("xdr:cNvGraphicFramePr", &vec![]);
+            writer.xml_end_tag("xdr:nvGraphicFramePr");
+
+            // Excel ignores the position of a chart's frame and uses the
+            // anchor.
+            write_transform(writer, "xdr:xfrm", "0", "0", "0", "0");
+
+            writer.xml_start_tag("a:graphic", &vec![]);
+            let attributes = vec![("uri", CHART_NS)];
+            writer.xml_start_tag("a:graphicData", &attributes);
+            let attributes = vec![
+                ("xmlns:c", CHART_NS),
+                ("xmlns:r", RELATIONSHIPS_NS),
+                ("r:id", rel_id.as_str()),
+            ];
+            writer.xml_empty_tag("c:chart", &attributes);
+            writer.xml_end_tag("a:graphicData");
+            writer.xml_end_tag("a:graphic");
+            writer.xml_end_tag("xdr:graphicFrame");
+        }
+    }
+
+    writer.xml_empty_tag("xdr:clientData", &vec![]);
+    writer.xml_end_tag("xdr:twoCellAnchor");
+}
+
+// Write an `<xdr:from>` or `<xdr:to>` element with a cell and the offset
+// within it in pixels.
+pub(crate) fn write_cell_position(
+    writer: &mut XMLWriter<impl Write>,
+    tag: &str,
+    col: u32,
+    col_offset: u32,
+    row: u32,
+    row_offset: u32,
+) {
+    writer.xml_start_tag(tag, &vec![]);
+    writer.xml_data_element_u64("xdr:col", col.into(), &vec![]);
+    writer.xml_data_element_u64(
+        "xdr:colOff",
+        u64::from(col_offset) * u64::from(EMU_PER_PIXEL),
+        &vec![],
+    );
+    writer.xml_data_element_u64("xdr:row", row.into(), &vec![]);
+    writer.xml_data_element_u64(
+        "xdr:rowOff",
+        u64::from(row_offset) * u64::from(EMU_PER_PIXEL),
+        &vec![],
+    );
+    writer.xml_end_tag(tag);
+}
+
+// Write an `<a:xfrm>` or `<xdr:xfrm>` element with a position and size in
+// English Metric Units.
+fn write_transform(
+    writer: &mut XMLWriter<impl Write>,
+    tag: &str,
+    x: &str,
+    y: &str,
+    cx: &str,
+    cy: &str,
+) {
+    writer.xml_start_tag(tag, &vec![]);
+    writer.xml_empty_tag("a:off", &vec![("x", x), ("y", y)]);
+    writer.xml_empty_tag("a:ext", &vec![("cx", cx), ("cy", cy)]);
+    writer.xml_end_tag(tag);
+}
+
+// The drawing test has a chart as well as an image.
+#[cfg(all(test, feature = "charts"))]
+mod tests {
+
+    use super::Drawing;
+    use crate::chart::{Chart, ChartType};
+    use crate::image::tests::png_data;
+    use crate::image::Image;
+    use crate::part::XlsxPart;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_drawing() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<xdr:wsDr xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing" "#,
+            r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">"#,
+            r#"<xdr:twoCellAnchor editAs="oneCell"><xdr:from><xdr:col>2</xdr:col><xdr:colOff>0</xdr:colOff>"#,
+            r#"<xdr:row>1</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from><xdr:to><xdr:col>5</xdr:col>"#,
+            r#"<xdr:colOff>76200</xdr:colOff><xdr:row>6</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:to>"#,
+            r#"<xdr:pic><xdr:nvPicPr><xdr:cNvPr id="2" name="Picture 1" descr="Logo &amp; name"/>"#,
+            r#"<xdr:cNvPicPr><a:picLocks noChangeAspect="1"/></xdr:cNvPicPr></xdr:nvPicPr>"#,
+            r#"<xdr:blipFill><a:blip xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+            r#"r:embed="rId1"/><a:stretch><a:fillRect/></a:stretch></xdr:blipFill><xdr:spPr>"#,
+            r#"<a:xfrm><a:off x="1219200" y="190500"/><a:ext cx="1905000" cy="952500"/></a:xfrm>"#,
+            r#"<a:prstGeom prst="rect"><a:avLst/></a:prstGeom></xdr:spPr></xdr:pic><xdr:clientData/>"#,
+            r#"</xdr:twoCellAnchor><xdr:twoCellAnchor><xdr:from><xdr:col>0</xdr:col>"#,
+            r#"<xdr:colOff>0</xdr:colOff><xdr:row>10</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>"#,
+            r#"<xdr:to><xdr:col>7</xdr:col><xdr:colOff>304800</xdr:colOff><xdr:row>24</xdr:row>"#,
+            r#"<xdr:rowOff>76200</xdr:rowOff></xdr:to><xdr:graphicFrame macro=""><xdr:nvGraphicFramePr>"#,
+            r#"<xdr:cNvPr id="3" name="Chart 2"/><xdr:cNvGraphicFramePr/></xdr:nvGraphicFramePr>"#,
+            r#"<xdr:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/></xdr:xfrm><a:graphic>"#,
+            r#"<a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/chart">"#,
+            r#"<c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="rId2"/>"#,
+            r#"</a:graphicData></a:graphic></xdr:graphicFrame><xdr:clientData/></xdr:twoCellAnchor>"#,
+            r#"</xdr:wsDr>"#,
+        );
+
+        let image = Image::from_bytes(&png_data(200, 100, 0))
+            .unwrap()
+            .set_alt_text("Logo & name");
+        let mut drawing = Drawing::new(3);
+        drawing.add_image(1, 2, &image, "xl/media/image4.png");
+        drawing.add_chart(10, 0, &Chart::new(2, ChartType::Pie));
+
+        let mut writer = XMLWriter::new(vec![]);
+        drawing.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+        assert_eq!(drawing.part_name(), "xl/drawings/drawing3.xml");
+
+        let mut writer = XMLWriter::new(vec![]);
+        drawing.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId1" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" "#,
+            r#"Target="../media/image4.png"/><Relationship Id="rId2" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" "#,
+            r#"Target="../charts/chart2.xml"/>"#,
+        )));
+    }
+}
@@ -0,0 +1,290 @@
+//! The error type for workbook structure that Excel would reject.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::error::Error;
+use std::fmt;
+
+/// Errors for workbook structure that Excel would reject or repair when the
+/// file is opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XlsxError {
+    /// The change would leave the workbook without a visible worksheet.
+    NoVisibleSheets,
+
+    /// The named worksheet is the active worksheet, which must be visible.
+    HiddenActiveSheet(String),
+
+    /// The named worksheet is the first visible tab, which must be visible.
+    HiddenFirstSheet(String),
+
+    /// A formula refers to a worksheet that isn't in the workbook. Excel
+    /// shows the reference as `#REF!` when the file is opened.
+    UnknownSheetReference {
+        /// The worksheet name in the formula.
+        sheet: String,
+        /// The formula.
+        formula: String,
+    },
+
+    /// The defined name or table name isn't one that Excel accepts. Names
+    /// must start with a letter, underscore or backslash, can't contain
+    /// spaces or punctuation other than underscores, periods and backslashes,
+    /// and can't look like a cell reference such as `A1` or `R1C1`.
+    InvalidName(String),
+
+    /// The defined name uses the reserved `_xlnm.` prefix of Excel's built-in
+    /// names but isn't a built-in name that can be defined, or isn't scoped
+    /// to a worksheet.
+    ReservedName(String),
+
+    /// The defined name or table name is already used in the same scope.
+    /// Table names share the namespace of the defined names.
+    DuplicateName(String),
+
+    /// A cell was written to a streaming worksheet before, or at the same
+    /// position as, the previous cell. Streaming worksheets must be written
+    /// in row order, and in column order within a row.
+    CellOutOfOrder {
+        /// The zero-indexed row of the cell.
+        row: u32,
+        /// The zero-indexed column of the cell.
+        col: u16,
+    },
+
+    /// A number written to a cell is NaN or infinite. Excel has no
+    /// representation for them and reports the part as corrupt.
+    NonFiniteNumber {
+        /// The zero-indexed row of the cell.
+        row: u32,
+        /// The zero-indexed column of the cell.
+        col: u16,
+    },
+
+    /// A merged range, like `B2`, is a single cell. Excel requires merged
+    /// ranges to span at least two cells.
+    MergeRangeSingleCell(String),
+
+    /// A merged range overlaps a range that is already merged. Excel removes
+    /// overlapping ranges when the file is opened.
+    MergeRangeOverlap {
+        /// The new range, like `B2:D4`.
+        range: String,
+        /// The existing range that it overlaps.
+        existing: String,
+    },
+
+    /// A hyperlink URL doesn't have a supported scheme, or is longer than
+    /// Excel's limit of 2079 characters.
+    InvalidUrl(String),
+
+    /// A title of a data validation is longer than Excel's limit of 32
+    /// characters, or a message or the list of values is longer than 255
+    /// characters.
+    DataValidationTooLong {
+        /// The property, like `input title`.
+        property: String,
+        /// The range of the data validation, like `B2:B10`.
+        range: String,
+    },
+
+    /// A table overlaps a table that is already in the worksheet.
+    TableOverlap {
+        /// The range of the new table, like `B2:D4`.
+        range: String,
+        /// The range of the existing table that it overlaps.
+        existing: String,
+    },
+
+    /// A table has more column headers than columns.
+    TableColumnCount {
+        /// The range of the table, like `B2:D4`.
+        range: String,
+        /// The number of column headers.
+        count: usize,
+    },
+
+    /// A table has two columns with the same header. Excel compares the
+    /// headers case-insensitively.
+    DuplicateTableColumn(String),
+
+    /// An image file couldn't be read.
+    ImageRead {
+        /// The path of the image.
+        path: String,
+        /// The description of the I/O error.
+        error: String,
+    },
+
+    /// The file of an embedded object couldn't be read.
+    ObjectRead {
+        /// The path of the file.
+        path: String,
+        /// The description of the I/O error.
+        error: String,
+    },
+
+    /// The hyperlinks of a
+    /// [`StreamingWorksheet`](crate::worksheet::StreamingWorksheet) couldn't
+    /// be written to their spill file, with the description of the I/O
+    /// error.
+    HyperlinkSpill(String),
+
+    /// The image data isn't a PNG, JPEG, GIF or BMP image, or its header is
+    /// truncated, so its size can't be read.
+    UnsupportedImage,
+
+    /// A value couldn't be serialized to XML with the `serializer` of the
+    /// `serde` feature, with the reason.
+    Serialize(String),
+
+    /// An event from a `quick-xml` reader couldn't be written with
+    /// `XMLWriter::write_event()`, because a name or text isn't UTF-8 or an
+    /// attribute or entity reference is malformed.
+    InvalidEvent(String),
+
+    /// A part doesn't match the element structure of the ECMA-376 schema,
+    /// such as child elements out of order, which Excel reports as
+    /// unreadable content. See `validation::validate_part()`, with the
+    /// `validation` feature.
+    SchemaViolation {
+        /// The path of the offending element, like
+        /// `worksheet/sheetData/row[3]/c`.
+        path: String,
+        /// The description of the violation.
+        reason: String,
+    },
+
+    /// An error that occurred while a part was written, with the part name
+    /// and the path of the open elements, like
+    /// `xl/worksheets/sheet3.xml at worksheet/sheetData/row[1042]`. See
+    /// [`XMLWriter::context()`](crate::XMLWriter::context).
+    Context {
+        /// The part name and element path.
+        context: String,
+        /// The underlying error.
+        error: Box<XlsxError>,
+    },
+}
+
+impl fmt::Display for XlsxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XlsxError::NoVisibleSheets => {
+                write!(f, "a workbook must have at least one visible worksheet")
+            }
+            XlsxError::HiddenActiveSheet(name) => {
+                write!(f, "the active worksheet '{name}' can't be hidden")
+            }
+            XlsxError::HiddenFirstSheet(name) => {
+                write!(
+                    f,
+                    "the first visible worksheet tab '{name}' can't be hidden"
+                )
+            }
+            XlsxError::UnknownSheetReference { sheet, formula } => {
+                write!(f, "unknown worksheet '{sheet}' in formula '{formula}'")
+            }
+            XlsxError::InvalidName(name) => {
+                write!(f, "'{name}' isn't a valid defined name or table name")
+            }
+            XlsxError::ReservedName(name) => {
+                write!(
+                    f,
+                    "'{name}' isn't a built-in name that can be defined for a worksheet"
+                )
+            }
+            XlsxError::DuplicateName(name) => {
+                write!(
+                    f,
+                    "the name '{name}' is already used by a defined name or table"
+                )
+            }
+            XlsxError::CellOutOfOrder { row, col } => {
+                write!(
+                    f,
+                    "cell ({row}, {col}) is before the last cell written to the streaming worksheet"
+                )
+            }
+            XlsxError::NonFiniteNumber { row, col } => {
+                write!(f, "the number in cell ({row}, {col}) is NaN or infinite")
+            }
+            XlsxError::MergeRangeSingleCell(range) => {
+                write!(f, "the merged range '{range}' is a single cell")
+            }
+            XlsxError::MergeRangeOverlap { range, existing } => {
+                write!(
+                    f,
+                    "the merged range '{range}' overlaps the merged range '{existing}'"
+                )
+            }
+            XlsxError::InvalidUrl(url) => {
+                write!(f, "the URL '{url}' isn't a valid hyperlink")
+            }
+            XlsxError::DataValidationTooLong { property, range } => {
+                write!(
+                    f,
+                    "the {property} of the data validation for '{range}' is too long"
+                )
+            }
+            XlsxError::TableOverlap { range, existing } => {
+                write!(f, "the table '{range}' overlaps the table '{existing}'")
+            }
+            XlsxError::TableColumnCount { range, count } => {
+                write!(
+                    f,
+                    "the table '{range}' has fewer columns than its {count} column headers"
+                )
+            }
+            XlsxError::DuplicateTableColumn(column) => {
+                write!(
+                    f,
+                    "the table column header '{column}' is used more than once"
+                )
+            }
+            XlsxError::ImageRead { path, error } => {
+                write!(f, "the image '{path}' couldn't be read: {error}")
+            }
+            XlsxError::ObjectRead { path, error } => {
+                write!(f, "the object file '{path}' couldn't be read: {error}")
+            }
+            XlsxError::HyperlinkSpill(error) => {
+                write!(
+                    f,
+                    "the hyperlinks couldn't be written to the spill file: {error}"
+                )
+            }
+            XlsxError::UnsupportedImage => {
+                write!(f, "the image isn't a PNG, JPEG, GIF or BMP image")
+            }
+            XlsxError::Serialize(reason) => {
+                write!(f, "the value couldn't be serialized: {reason}")
+            }
+            XlsxError::InvalidEvent(reason) => {
+                write!(f, "the XML event couldn't be written: {reason}")
+            }
+            XlsxError::SchemaViolation { path, reason } => {
+                write!(f, "{reason}, in {path}")
+            }
+            XlsxError::Context { context, error } => {
+                write!(f, "{error}, in {context}")
+            }
+        }
+    }
+}
+
+impl Error for XlsxError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            XlsxError::Context { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for XlsxError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        XlsxError::Serialize(message.to_string())
+    }
+}
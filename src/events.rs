@@ -0,0 +1,267 @@
+//! Re-emit the events of a [`quick-xml`](quick_xml) reader through the
+//! writer, for copying or modifying an existing part, such as a part from a
+//! template file, with the same escaping as the rest of the crate.
+//!
+//! ```
+//! use excel_xmlwriter::XMLWriter;
+//! use quick_xml::events::Event;
+//! use quick_xml::Reader;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut reader = Reader::from_str(r#"<sheetPr codeName='Sheet1'><tabColor rgb="FFFF0000"/></sheetPr>"#);
+//! let mut writer = XMLWriter::new(vec![]);
+//!
+//! loop {
+//!     match reader.read_event()? {
+//!         Event::Eof => break,
+//!         event => writer.write_event(&event)?,
+//!     }
+//! }
+//!
+//! assert_eq!(
+//!     String::from_utf8(writer.into_inner())?,
+//!     r#"<sheetPr codeName="Sheet1"><tabColor rgb="FFFF0000"/></sheetPr>"#
+//! );
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use quick_xml::escape::{resolve_predefined_entity, unescape};
+use quick_xml::events::{BytesRef, BytesStart, BytesText, Event};
+use quick_xml::name::QName;
+
+use crate::error::XlsxError;
+use crate::{escape_data, XMLWriter};
+
+impl<W: Write> XMLWriter<W> {
+    /// Write an event from a `quick-xml` reader. Elements are written with
+    /// the `xml_*` tag methods, so they are tracked for the
+    /// [`context()`](XMLWriter::context), and attribute values and text are
+    /// unescaped and escaped again in the way that Excel writes them, with
+    /// double quotes around attribute values and newlines in attributes as
+    /// `&#xA;`.
+    ///
+    /// An XML declaration is written as the standard `standalone`
+    /// declaration that Excel uses, whatever its version and encoding.
+    /// Comments, CDATA sections, processing instructions and document types
+    /// are written unchanged, and [`Event::Eof`] is ignored.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::InvalidEvent`] if a name or text isn't UTF-8, or an
+    /// attribute or entity reference is malformed. Write errors panic as in
+    /// the other methods of the writer.
+    pub fn write_event(&mut self, event: &Event) -> Result<(), XlsxError> {
+        match event {
+            Event::Start(start) => {
+                let (tag, attributes) = decode_start(start)?;
+                self.xml_start_tag(tag, &attribute_refs(&attributes));
+            }
+            Event::Empty(start) => {
+                let (tag, attributes) = decode_start(start)?;
+                self.xml_empty_tag(tag, &attribute_refs(&attributes));
+            }
+            Event::End(end) => {
+                let tag = decode_name(end.name())?;
+                self.xml_end_tag(tag);
+            }
+            Event::Text(text) => {
+                let text = decode_text(text)?;
+                self.write_raw(escape_data(&text).as_bytes());
+            }
+            Event::GeneralRef(reference) => {
+                let text = decode_reference(reference)?;
+                self.write_raw(text.as_bytes());
+            }
+            Event::Decl(_) => self.xml_declaration(),
+            Event::CData(data) => {
+                self.write_raw(b"<![CDATA[");
+                self.write_raw(data);
+                self.write_raw(b"]]>");
+            }
+            Event::Comment(comment) => {
+                self.write_raw(b"<!--");
+                self.write_raw(comment);
+                self.write_raw(b"-->");
+            }
+            Event::PI(instruction) => {
+                self.write_raw(b"<?");
+                self.write_raw(instruction);
+                self.write_raw(b"?>");
+            }
+            Event::DocType(doctype) => {
+                self.write_raw(b"<!DOCTYPE ");
+                self.write_raw(doctype);
+                self.write_raw(b">");
+            }
+            Event::Eof => {}
+        }
+
+        Ok(())
+    }
+}
+
+// The names and unescaped values of the attributes of a tag.
+type Attributes<'a> = Vec<(&'a str, Cow<'a, str>)>;
+
+// Decode the name and the unescaped attributes of a start or empty tag.
+fn decode_start<'a>(start: &'a BytesStart) -> Result<(&'a str, Attributes<'a>), XlsxError> {
+    let tag = decode_name(start.name())?;
+    let mut attributes = vec![];
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(invalid_event)?;
+        let value = attribute.unescape_value().map_err(invalid_event)?;
+        let QName(name) = attribute.key;
+        let name = std::str::from_utf8(name).map_err(invalid_event)?;
+        attributes.push((name, value));
+    }
+
+    Ok((tag, attributes))
+}
+
+// Decode an element or attribute name.
+fn decode_name<'a>(name: QName<'a>) -> Result<&'a str, XlsxError> {
+    std::str::from_utf8(name.into_inner()).map_err(invalid_event)
+}
+
+// Decode and unescape text. Text from a reader doesn't contain entity
+// references, which are separate events, but text created with
+// `BytesText::new()` is stored escaped.
+fn decode_text(text: &BytesText) -> Result<String, XlsxError> {
+    let text = text.xml_content().map_err(invalid_event)?;
+    let text = unescape(&text).map_err(invalid_event)?;
+
+    Ok(text.into_owned())
+}
+
+// Resolve a character or predefined entity reference to its escaped text.
+// Other entities can't be resolved without the document type, so they are
+// written unchanged.
+fn decode_reference(reference: &BytesRef) -> Result<String, XlsxError> {
+    if let Some(ch) = reference.resolve_char_ref().map_err(invalid_event)? {
+        return Ok(escape_data(ch.encode_utf8(&mut [0; 4])).into_owned());
+    }
+
+    let name = reference.decode().map_err(invalid_event)?;
+
+    match resolve_predefined_entity(&name) {
+        Some(text) => Ok(escape_data(text).into_owned()),
+        None => Ok(format!("&{name};")),
+    }
+}
+
+// Convert the attribute values to the references used by the tag methods.
+fn attribute_refs<'a>(attributes: &'a [(&'a str, Cow<'a, str>)]) -> Vec<(&'a str, &'a str)> {
+    attributes
+        .iter()
+        .map(|(name, value)| (*name, value.as_ref()))
+        .collect()
+}
+
+// Convert a quick-xml error to an error of the crate.
+fn invalid_event(error: impl std::fmt::Display) -> XlsxError {
+    XlsxError::InvalidEvent(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use quick_xml::events::{BytesCData, BytesDecl, BytesEnd};
+    use quick_xml::Reader;
+
+    // Copy a document through the writer.
+    fn copy(xml: &str) -> String {
+        let mut reader = Reader::from_str(xml);
+        let mut writer = XMLWriter::new(vec![]);
+
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Eof => break,
+                event => writer.write_event(&event).unwrap(),
+            }
+        }
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_write_event_copy() {
+        let xml = concat!(
+            r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            r#"<sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>Hello</t></is></c></row></sheetData>"#,
+            r#"<pageMargins left="0.7" right="0.7"/></worksheet>"#
+        );
+
+        assert_eq!(copy(xml), xml);
+    }
+
+    #[test]
+    fn test_write_event_escaping() {
+        let got = copy(concat!(
+            "<?xml version='1.0' encoding='UTF-8'?>",
+            "<si a='say \"hi\"' b=\"x&#10;y\"><t>a &amp; b &lt; c &#62; &apos;d&apos; &custom;</t></si>"
+        ));
+
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            r#"<si a="say &quot;hi&quot;" b="x&#xA;y"><t>a &amp; b &lt; c &gt; 'd' &custom;</t></si>"#
+        );
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_write_event_unchanged() {
+        let xml = concat!(
+            "<?mso-application progid=\"Excel.Sheet\"?>",
+            "<!-- a comment --><x><![CDATA[a < b]]></x>"
+        );
+
+        assert_eq!(copy(xml), xml);
+    }
+
+    #[test]
+    fn test_write_event_constructed() {
+        let mut writer = XMLWriter::new(vec![]);
+
+        let events = [
+            Event::Decl(BytesDecl::new("1.0", None, None)),
+            Event::Start(BytesStart::new("t").with_attributes([("xml:space", "preserve")])),
+            Event::Text(BytesText::new(" a & b ")),
+            Event::CData(BytesCData::new("c")),
+            Event::End(BytesEnd::new("t")),
+        ];
+
+        for event in &events {
+            writer.write_event(event).unwrap();
+        }
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            r#"<t xml:space="preserve"> a &amp; b <![CDATA[c]]></t>"#
+        );
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_write_event_invalid_attribute() {
+        let mut writer = XMLWriter::new(vec![]);
+        let start = BytesStart::from_content("c r", 1);
+
+        assert!(matches!(
+            writer.write_event(&Event::Empty(start)),
+            Err(XlsxError::InvalidEvent(_))
+        ));
+    }
+}
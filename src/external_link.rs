@@ -0,0 +1,409 @@
+//! The links to other workbooks and the writer for the
+//! `xl/externalLinks/externalLinkN.xml` parts.
+//!
+//! In the file, a formula refers to another workbook by the number of its
+//! external link part, like `[1]Sheet1!A1`, rather than by its name as in
+//! Excel's formula bar. Excel asks to repair a workbook with formulas that
+//! use the names, or numbers without a part.
+//!
+//! [`Workbook::external_formula()`](crate::workbook::Workbook::external_formula)
+//! converts the workbook names in a formula, like `[Book2.xlsx]Sheet1!A1`,
+//! to numbers and adds the links, which are written as [`ExternalLink`]
+//! parts from
+//! [`Workbook::external_links()`](crate::workbook::Workbook::external_links).
+//! Each part has a relationship to the path of its workbook.
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::workbook::Workbook;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut workbook = Workbook::new();
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! workbook.add_sheet("Sheet1");
+//! let formula = workbook.external_formula("=[Book2.xlsx]Sheet1!A1*2");
+//! assert_eq!(formula, "=[1]Sheet1!A1*2");
+//! worksheet.write_formula(0, 0, &formula);
+//!
+//! for link in workbook.external_links() {
+//!     content_types.add_external_link(link.number());
+//!     packager.add_custom_part(&link)?;
+//!     let rels = format!("xl/externalLinks/_rels/externalLink{}.xml.rels", link.number());
+//!     packager.add_xml_part(&rels, |writer| {
+//!         link.relationships().assemble_xml_file(writer)
+//!     })?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{EXTERNAL_LINK_CONTENT_TYPE, RELATIONSHIPS_NS, SPREADSHEETML_NS};
+use crate::part::XlsxPart;
+use crate::relationship::Relationships;
+use crate::XMLWriter;
+
+/// The `xl/externalLinks/externalLinkN.xml` part of a link to another
+/// workbook, created with
+/// [`Workbook::external_links()`](crate::workbook::Workbook::external_links).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExternalLink {
+    number: u32,
+    part_name: String,
+    target: String,
+    sheet_names: Vec<String>,
+    defined_names: Vec<String>,
+}
+
+impl ExternalLink {
+    // Create the part of a link with its number and the path of the
+    // workbook.
+    pub(crate) fn new(number: u32, target: &str) -> ExternalLink {
+        ExternalLink {
+            number,
+            part_name: format!("xl/externalLinks/externalLink{number}.xml"),
+            target: target.to_string(),
+            sheet_names: vec![],
+            defined_names: vec![],
+        }
+    }
+
+    // Add a worksheet name of the linked workbook, unless it is already
+    // there. Excel compares the names case-insensitively.
+    pub(crate) fn add_sheet_name(&mut self, name: &str) {
+        if !contains_ignore_case(&self.sheet_names, name) {
+            self.sheet_names.push(name.to_string());
+        }
+    }
+
+    // Add a workbook-level defined name of the linked workbook, unless it is
+    // already there.
+    pub(crate) fn add_defined_name(&mut self, name: &str) {
+        if !contains_ignore_case(&self.defined_names, name) {
+            self.defined_names.push(name.to_string());
+        }
+    }
+
+    /// The number of the part, which is the number of the workbook in
+    /// formulas.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// The path of the linked workbook, like `Book2.xlsx`.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The relationship of the link to the path of its workbook, for the
+    /// `xl/externalLinks/_rels/externalLinkN.xml.rels` part.
+    pub fn relationships(&self) -> Relationships {
+        let mut relationships = Relationships::new();
+        relationships.add_worksheet_relationship(
+            "externalLinkPath",
+            &self.target,
+            Some("External"),
+        );
+
+        relationships
+    }
+
+    /// Write the `xl/externalLinks/externalLinkN.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        writer.xml_start_tag("externalLink", &vec![("xmlns", SPREADSHEETML_NS)]);
+
+        let attributes = vec![("xmlns:r", RELATIONSHIPS_NS), ("r:id", "rId1")];
+        writer.xml_start_tag("externalBook", &attributes);
+
+        if !self.sheet_names.is_empty() {
+            writer.xml_start_tag("sheetNames", &vec![]);
+            for name in &self.sheet_names {
+                writer.xml_empty_tag("sheetName", &vec![("val", name.as_str())]);
+            }
+            writer.xml_end_tag("sheetNames");
+        }
+
+        if !self.defined_names.is_empty() {
+            writer.xml_start_tag("definedNames", &vec![]);
+            for name in &self.defined_names {
+                writer.xml_empty_tag("definedName", &vec![("name", name.as_str())]);
+            }
+            writer.xml_end_tag("definedNames");
+        }
+
+        writer.xml_end_tag("externalBook");
+        writer.xml_end_tag("externalLink");
+    }
+}
+
+impl XlsxPart for ExternalLink {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        EXTERNAL_LINK_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+/// A reference to another workbook in a formula, found by
+/// [`replace_external_references()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ExternalReference<'a> {
+    /// A worksheet of the workbook, like `[Book2.xlsx]Sheet1!`.
+    Sheet { book: &'a str, sheet: &'a str },
+    /// A workbook-level defined name, like `[Book2.xlsx]!Rates`.
+    Name { book: &'a str, name: &'a str },
+}
+
+// Replace the workbook names of the references to other workbooks in a
+// formula, with the strings returned for each reference, which are usually
+// the numbers of the links. References that already use a number, string
+// literals and structured references like `Table1[Column]` are unchanged.
+pub(crate) fn replace_external_references(
+    formula: &str,
+    mut replace: impl FnMut(ExternalReference) -> String,
+) -> String {
+    let mut output = String::with_capacity(formula.len());
+    let mut rest = formula;
+
+    while let Some(char) = rest.chars().next() {
+        match char {
+            // String literals, with "" as an escaped quote.
+            '"' => {
+                let end = literal_end(rest, '"').unwrap_or(rest.len());
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+
+            // Quoted sheet names, like '[Book 2.xlsx]Q1 Sales'!A1.
+            '\'' => {
+                let Some(end) = literal_end(rest, '\'') else {
+                    output.push_str(rest);
+                    break;
+                };
+                let quoted = &rest[1..end - 1];
+
+                match split_book(quoted) {
+                    Some((book, sheet)) if rest[end..].starts_with('!') => {
+                        let book = replace(ExternalReference::Sheet {
+                            book,
+                            sheet: &sheet.replace("''", "'"),
+                        });
+                        output.push_str(&format!("'[{book}]{sheet}'"));
+                    }
+                    _ => output.push_str(&rest[..end]),
+                }
+                rest = &rest[end..];
+            }
+
+            // A [book] prefix at the start of a reference, rather than a
+            // structured reference after a table name.
+            '[' if !output.ends_with(is_reference_char) => {
+                let Some(close) = rest.find(']') else {
+                    output.push_str(rest);
+                    break;
+                };
+                let book = &rest[1..close];
+                let after = &rest[close + 1..];
+                let sheet_length = after
+                    .find(|char: char| !is_reference_char(char) && char != ':')
+                    .unwrap_or(after.len());
+                let sheet = &after[..sheet_length];
+
+                let is_reference = !book.is_empty()
+                    && !book.contains('[')
+                    && !book.chars().all(|char| char.is_ascii_digit())
+                    && after[sheet_length..].starts_with('!');
+
+                if !is_reference {
+                    output.push_str(&rest[..close + 1]);
+                    rest = after;
+                    continue;
+                }
+
+                let reference = if sheet.is_empty() {
+                    let name = &after[1..];
+                    let name_length = name
+                        .find(|char: char| !is_reference_char(char))
+                        .unwrap_or(name.len());
+                    ExternalReference::Name {
+                        book,
+                        name: &name[..name_length],
+                    }
+                } else {
+                    ExternalReference::Sheet { book, sheet }
+                };
+
+                output.push_str(&format!("[{}]{sheet}", replace(reference)));
+                rest = &after[sheet_length..];
+            }
+
+            _ => {
+                output.push(char);
+                rest = &rest[char.len_utf8()..];
+            }
+        }
+    }
+
+    output
+}
+
+// The length of a quoted literal at the start of a string, including the
+// quotes, with doubled quotes as escaped quotes. Returns None if the literal
+// isn't closed.
+fn literal_end(string: &str, quote: char) -> Option<usize> {
+    let mut chars = string.char_indices().skip(1).peekable();
+
+    while let Some((index, char)) = chars.next() {
+        if char == quote && chars.next_if(|(_, char)| *char == quote).is_none() {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+// Split a quoted sheet name like `[Book2.xlsx]Sheet1` into the workbook and
+// sheet names, unless the workbook is already a number.
+fn split_book(quoted: &str) -> Option<(&str, &str)> {
+    let rest = quoted.strip_prefix('[')?;
+    let (book, sheet) = rest.split_once(']')?;
+
+    if book.is_empty() || book.chars().all(|char| char.is_ascii_digit()) {
+        None
+    } else {
+        Some((book, sheet))
+    }
+}
+
+// Check if a character can be part of an unquoted sheet name, defined name
+// or table name.
+fn is_reference_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_' || char == '.' || char == '\\'
+}
+
+// Check if a list of names has a name, case-insensitively.
+fn contains_ignore_case(names: &[String], name: &str) -> bool {
+    let name = name.to_lowercase();
+    names.iter().any(|known| known.to_lowercase() == name)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{replace_external_references, ExternalLink, ExternalReference};
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    // Replace the workbook names with 1, and return the new formula and the
+    // references as (book, sheet or !name).
+    fn replace(formula: &str) -> (String, Vec<(String, String)>) {
+        let mut references = vec![];
+        let formula = replace_external_references(formula, |reference| {
+            references.push(match reference {
+                ExternalReference::Sheet { book, sheet } => (book.to_string(), sheet.to_string()),
+                ExternalReference::Name { book, name } => (book.to_string(), format!("!{name}")),
+            });
+            "1".to_string()
+        });
+
+        (formula, references)
+    }
+
+    #[test]
+    fn test_assemble_external_link() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<externalLink xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            r#"<externalBook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" "#,
+            r#"r:id="rId1"><sheetNames><sheetName val="Sheet1"/><sheetName val="Q1 Sales"/>"#,
+            r#"</sheetNames><definedNames><definedName name="Rates"/></definedNames>"#,
+            r#"</externalBook></externalLink>"#,
+        );
+
+        let mut link = ExternalLink::new(2, "Book2.xlsx");
+        link.add_sheet_name("Sheet1");
+        link.add_sheet_name("Q1 Sales");
+        link.add_sheet_name("SHEET1");
+        link.add_defined_name("Rates");
+
+        let mut writer = XMLWriter::new(vec![]);
+        link.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+        assert_eq!((link.number(), link.target()), (2, "Book2.xlsx"));
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        link.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId1" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/externalLinkPath" "#,
+            r#"Target="Book2.xlsx" TargetMode="External"/>"#,
+        )));
+    }
+
+    #[test]
+    fn test_replace_external_references() {
+        let reference = |book: &str, sheet: &str| (book.to_string(), sheet.to_string());
+
+        assert_eq!(
+            replace("=SUM([Book2.xlsx]Data!A1:A9)+[Book2]Sheet1!B2"),
+            (
+                "=SUM([1]Data!A1:A9)+[1]Sheet1!B2".to_string(),
+                vec![
+                    reference("Book2.xlsx", "Data"),
+                    reference("Book2", "Sheet1")
+                ]
+            )
+        );
+        assert_eq!(
+            replace("='[Book 2.xlsx]Q1 ''22'!A1"),
+            (
+                "='[1]Q1 ''22'!A1".to_string(),
+                vec![reference("Book 2.xlsx", "Q1 '22")]
+            )
+        );
+        assert_eq!(
+            replace("=[Book2.xlsx]!Rates*2"),
+            (
+                "=[1]!Rates*2".to_string(),
+                vec![reference("Book2.xlsx", "!Rates")]
+            )
+        );
+
+        // Formulas without workbook names are unchanged.
+        let unchanged = [
+            "=[2]Sheet1!A1+'[3]Data'!A1",
+            "=Table1[Price]*Table1[[#This Row],[Qty]]",
+            "=\"[Book2]Sheet1!A1\"&'Sheet 1'!A1",
+            "='[Book2]Sheet1",
+            "=[Book2",
+        ];
+        for formula in unchanged {
+            assert_eq!(replace(formula), (formula.to_string(), vec![]));
+        }
+    }
+}
@@ -0,0 +1,485 @@
+//! A C interface to the [`XMLWriter`], for C programs that are migrating to
+//! the crate one part writer at a time.
+//!
+//! The functions are declared in `include/excel_xmlwriter.h`. The crate is
+//! a Rust library by default, so the C dynamic library, such as
+//! `target/release/libexcel_xmlwriter.so`, is built with an explicit crate
+//! type:
+//!
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! A writer is created with [`xmlwriter_new()`], to write to a file, or
+//! [`xmlwriter_new_buffer()`], to write to memory, and is freed with
+//! [`xmlwriter_close()`] or [`xmlwriter_take_buffer()`]. Strings are UTF-8
+//! and nul terminated, and the attributes of a tag are an array of
+//! alternating names and values:
+//!
+//! ```c
+//! xmlwriter *writer = xmlwriter_new("sheet1.xml");
+//! const char *attributes[] = {"r", "1", "spans", "1:3"};
+//!
+//! xmlwriter_declaration(writer);
+//! xmlwriter_start_tag(writer, "row", attributes, 2);
+//! xmlwriter_data_element(writer, "t", "Total", NULL, 0);
+//! xmlwriter_end_tag(writer, "row");
+//!
+//! xmlwriter_close(writer);
+//! ```
+//!
+//! The functions return an [`XmlWriterError`] instead of panicking, since a
+//! panic can't unwind into C.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::ffi::{c_char, CStr};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::XMLWriter;
+
+/// The result of the C functions.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XmlWriterError {
+    /// No error.
+    Ok = 0,
+
+    /// A pointer argument was NULL.
+    NullArgument = 1,
+
+    /// A string argument wasn't valid UTF-8.
+    InvalidUtf8 = 2,
+
+    /// The XML couldn't be written to the file.
+    Write = 3,
+}
+
+/// The writer behind the opaque `xmlwriter` pointer of the C interface.
+pub struct FfiWriter {
+    writer: XMLWriter<FfiSink>,
+    is_buffer: bool,
+}
+
+// The destination of a writer from the C interface.
+enum FfiSink {
+    File(BufWriter<File>),
+    Buffer(Vec<u8>),
+}
+
+impl Write for FfiSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            FfiSink::File(file) => file.write(buf),
+            FfiSink::Buffer(buffer) => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            FfiSink::File(file) => file.flush(),
+            FfiSink::Buffer(_) => Ok(()),
+        }
+    }
+}
+
+/// Create a writer that writes to a new file, or returns NULL if the file
+/// can't be created.
+///
+/// # Safety
+///
+/// `filename` must be NULL or a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_new(filename: *const c_char) -> *mut FfiWriter {
+    let Ok(filename) = to_str(filename) else {
+        return ptr::null_mut();
+    };
+    let Ok(file) = File::create(filename) else {
+        return ptr::null_mut();
+    };
+
+    new_writer(FfiSink::File(BufWriter::new(file)))
+}
+
+/// Create a writer that writes to memory. The XML is returned by
+/// [`xmlwriter_take_buffer()`].
+#[no_mangle]
+pub extern "C" fn xmlwriter_new_buffer() -> *mut FfiWriter {
+    new_writer(FfiSink::Buffer(vec![]))
+}
+
+/// Write an XML file declaration.
+///
+/// # Safety
+///
+/// `writer` must be NULL or a writer that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_declaration(writer: *mut FfiWriter) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        writer.xml_declaration();
+        Ok(())
+    })
+}
+
+/// Write an XML start tag with `num_attributes` attributes.
+///
+/// # Safety
+///
+/// `writer` must be NULL or a writer that hasn't been freed, `tag` must be
+/// NULL or a nul terminated string, and `attributes` must be NULL or an
+/// array of `2 * num_attributes` nul terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_start_tag(
+    writer: *mut FfiWriter,
+    tag: *const c_char,
+    attributes: *const *const c_char,
+    num_attributes: usize,
+) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        let tag = to_str(tag)?;
+        let attributes = to_attributes(attributes, num_attributes)?;
+        writer.xml_start_tag(tag, &attributes);
+        Ok(())
+    })
+}
+
+/// Write an XML end tag.
+///
+/// # Safety
+///
+/// `writer` must be NULL or a writer that hasn't been freed, and `tag` must
+/// be NULL or a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_end_tag(
+    writer: *mut FfiWriter,
+    tag: *const c_char,
+) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        writer.xml_end_tag(to_str(tag)?);
+        Ok(())
+    })
+}
+
+/// Write an empty XML tag with `num_attributes` attributes.
+///
+/// # Safety
+///
+/// The same as [`xmlwriter_start_tag()`].
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_empty_tag(
+    writer: *mut FfiWriter,
+    tag: *const c_char,
+    attributes: *const *const c_char,
+    num_attributes: usize,
+) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        let tag = to_str(tag)?;
+        let attributes = to_attributes(attributes, num_attributes)?;
+        writer.xml_empty_tag(tag, &attributes);
+        Ok(())
+    })
+}
+
+/// Write an XML element containing data, with `num_attributes` attributes.
+///
+/// # Safety
+///
+/// The same as [`xmlwriter_start_tag()`], and `data` must be NULL or a nul
+/// terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_data_element(
+    writer: *mut FfiWriter,
+    tag: *const c_char,
+    data: *const c_char,
+    attributes: *const *const c_char,
+    num_attributes: usize,
+) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        let tag = to_str(tag)?;
+        let data = to_str(data)?;
+        let attributes = to_attributes(attributes, num_attributes)?;
+        writer.xml_data_element(tag, data, &attributes);
+        Ok(())
+    })
+}
+
+/// Write a `<c>` cell element with a number value.
+///
+/// # Safety
+///
+/// The same as [`xmlwriter_start_tag()`], without the tag.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_number_element(
+    writer: *mut FfiWriter,
+    number: f64,
+    attributes: *const *const c_char,
+    num_attributes: usize,
+) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        let attributes = to_attributes(attributes, num_attributes)?;
+        writer.xml_number_element(number, &attributes);
+        Ok(())
+    })
+}
+
+/// Write a `<c>` cell element with the index of a shared string.
+///
+/// # Safety
+///
+/// The same as [`xmlwriter_start_tag()`], without the tag.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_string_element(
+    writer: *mut FfiWriter,
+    index: u32,
+    attributes: *const *const c_char,
+    num_attributes: usize,
+) -> XmlWriterError {
+    with_writer(writer, |writer| {
+        let attributes = to_attributes(attributes, num_attributes)?;
+        writer.xml_string_element(index, &attributes);
+        Ok(())
+    })
+}
+
+/// Flush and free a writer. The writer can't be used afterwards. A NULL
+/// writer is ignored.
+///
+/// # Safety
+///
+/// `writer` must be NULL or a writer that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_close(writer: *mut FfiWriter) -> XmlWriterError {
+    if writer.is_null() {
+        return XmlWriterError::Ok;
+    }
+
+    let writer = Box::from_raw(writer);
+    catch_write(|| match writer.writer.into_inner() {
+        FfiSink::File(mut file) => file.flush().map_err(|_| XmlWriterError::Write),
+        FfiSink::Buffer(_) => Ok(()),
+    })
+}
+
+/// Free a writer from [`xmlwriter_new_buffer()`] and return the XML that was
+/// written to it, with its length in `len`. The XML isn't nul terminated and
+/// must be freed with [`xmlwriter_free_buffer()`]. Returns NULL if the
+/// writer is NULL or writes to a file, in which case the writer isn't freed
+/// and must still be closed with [`xmlwriter_close()`].
+///
+/// # Safety
+///
+/// `writer` must be NULL or a writer that hasn't been freed, and `len` must
+/// be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_take_buffer(writer: *mut FfiWriter, len: *mut usize) -> *mut u8 {
+    if writer.is_null() || len.is_null() || !(*writer).is_buffer {
+        return ptr::null_mut();
+    }
+
+    let writer = Box::from_raw(writer);
+    match writer.writer.into_inner() {
+        FfiSink::Buffer(buffer) => {
+            let buffer = Box::into_raw(buffer.into_boxed_slice());
+            *len = buffer.len();
+            buffer.cast()
+        }
+        FfiSink::File(_) => ptr::null_mut(),
+    }
+}
+
+/// Free the XML returned by [`xmlwriter_take_buffer()`].
+///
+/// # Safety
+///
+/// `buffer` and `len` must be NULL and 0, or the XML and its length from
+/// `xmlwriter_take_buffer()`, which hasn't already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn xmlwriter_free_buffer(buffer: *mut u8, len: usize) {
+    if !buffer.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer, len)));
+    }
+}
+
+fn new_writer(sink: FfiSink) -> *mut FfiWriter {
+    Box::into_raw(Box::new(FfiWriter {
+        is_buffer: matches!(sink, FfiSink::Buffer(_)),
+        writer: XMLWriter::new(sink),
+    }))
+}
+
+// Run a write on a writer from C, catching the panic from a failed write.
+unsafe fn with_writer(
+    writer: *mut FfiWriter,
+    write: impl FnOnce(&mut XMLWriter<FfiSink>) -> Result<(), XmlWriterError>,
+) -> XmlWriterError {
+    let Some(writer) = writer.as_mut() else {
+        return XmlWriterError::NullArgument;
+    };
+
+    catch_write(|| write(&mut writer.writer))
+}
+
+fn catch_write(write: impl FnOnce() -> Result<(), XmlWriterError>) -> XmlWriterError {
+    match panic::catch_unwind(AssertUnwindSafe(write)) {
+        Ok(Ok(())) => XmlWriterError::Ok,
+        Ok(Err(error)) => error,
+        Err(_) => XmlWriterError::Write,
+    }
+}
+
+// Convert a C string to a str.
+unsafe fn to_str<'a>(string: *const c_char) -> Result<&'a str, XmlWriterError> {
+    if string.is_null() {
+        return Err(XmlWriterError::NullArgument);
+    }
+
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| XmlWriterError::InvalidUtf8)
+}
+
+// Convert an array of alternating C string names and values to attributes.
+unsafe fn to_attributes<'a>(
+    attributes: *const *const c_char,
+    num_attributes: usize,
+) -> Result<Vec<(&'a str, &'a str)>, XmlWriterError> {
+    if num_attributes == 0 {
+        return Ok(vec![]);
+    }
+    if attributes.is_null() {
+        return Err(XmlWriterError::NullArgument);
+    }
+
+    std::slice::from_raw_parts(attributes, 2 * num_attributes)
+        .chunks_exact(2)
+        .map(|pair| Ok((to_str(pair[0])?, to_str(pair[1])?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::ffi::CString;
+
+    use pretty_assertions::assert_eq;
+
+    // Take the XML from a buffer writer as a string.
+    unsafe fn take_string(writer: *mut FfiWriter) -> String {
+        let mut len = 0;
+        let buffer = xmlwriter_take_buffer(writer, &mut len);
+        let xml = String::from_utf8(std::slice::from_raw_parts(buffer, len).to_vec()).unwrap();
+        xmlwriter_free_buffer(buffer, len);
+        xml
+    }
+
+    #[test]
+    fn test_ffi_buffer_writer() {
+        let row = CString::new("row").unwrap();
+        let t = CString::new("t").unwrap();
+        let data = CString::new("Fish & Chips").unwrap();
+        let names = [CString::new("r").unwrap(), CString::new("spans").unwrap()];
+        let values = [CString::new("1").unwrap(), CString::new("1:3").unwrap()];
+        let attributes = [
+            names[0].as_ptr(),
+            values[0].as_ptr(),
+            names[1].as_ptr(),
+            values[1].as_ptr(),
+        ];
+
+        unsafe {
+            let writer = xmlwriter_new_buffer();
+
+            assert_eq!(
+                xmlwriter_start_tag(writer, row.as_ptr(), attributes.as_ptr(), 2),
+                XmlWriterError::Ok
+            );
+            assert_eq!(
+                xmlwriter_data_element(writer, t.as_ptr(), data.as_ptr(), ptr::null(), 0),
+                XmlWriterError::Ok
+            );
+            assert_eq!(
+                xmlwriter_number_element(writer, 1.5, attributes.as_ptr(), 1),
+                XmlWriterError::Ok
+            );
+            assert_eq!(
+                xmlwriter_string_element(writer, 3, ptr::null(), 0),
+                XmlWriterError::Ok
+            );
+            assert_eq!(
+                xmlwriter_empty_tag(writer, t.as_ptr(), ptr::null(), 0),
+                XmlWriterError::Ok
+            );
+            assert_eq!(xmlwriter_end_tag(writer, row.as_ptr()), XmlWriterError::Ok);
+
+            let expected = concat!(
+                r#"<row r="1" spans="1:3"><t>Fish &amp; Chips</t>"#,
+                r#"<c r="1"><v>1.5</v></c><c t="s"><v>3</v></c><t/></row>"#
+            );
+            assert_eq!(take_string(writer), expected);
+        }
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let tag = CString::new("foo").unwrap();
+        let invalid = [0xFF_u8, 0];
+
+        unsafe {
+            let writer = xmlwriter_new_buffer();
+
+            assert_eq!(
+                xmlwriter_end_tag(ptr::null_mut(), tag.as_ptr()),
+                XmlWriterError::NullArgument
+            );
+            assert_eq!(
+                xmlwriter_end_tag(writer, ptr::null()),
+                XmlWriterError::NullArgument
+            );
+            assert_eq!(
+                xmlwriter_start_tag(writer, tag.as_ptr(), ptr::null(), 1),
+                XmlWriterError::NullArgument
+            );
+            assert_eq!(
+                xmlwriter_end_tag(writer, invalid.as_ptr().cast()),
+                XmlWriterError::InvalidUtf8
+            );
+
+            assert_eq!(take_string(writer), "");
+            assert!(xmlwriter_new(ptr::null()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_ffi_file_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.xml");
+        let filename = CString::new(path.to_str().unwrap()).unwrap();
+        let tag = CString::new("foo").unwrap();
+
+        unsafe {
+            let writer = xmlwriter_new(filename.as_ptr());
+            assert!(!writer.is_null());
+
+            assert_eq!(xmlwriter_declaration(writer), XmlWriterError::Ok);
+            assert_eq!(
+                xmlwriter_empty_tag(writer, tag.as_ptr(), ptr::null(), 0),
+                XmlWriterError::Ok
+            );
+
+            // A file writer isn't freed by xmlwriter_take_buffer().
+            let mut len = 0;
+            assert!(xmlwriter_take_buffer(writer, &mut len).is_null());
+            assert_eq!(xmlwriter_close(writer), XmlWriterError::Ok);
+        }
+
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n<foo/>"
+        );
+        assert_eq!(std::fs::read_to_string(path).unwrap(), expected);
+    }
+}
@@ -0,0 +1,222 @@
+//! A writer for Excel's XML that writes to a [`core::fmt::Write`]
+//! implementation, such as a `String` or a fixed size buffer.
+//!
+//! The [`FmtXmlWriter`] has the element writers of the
+//! [`XMLWriter`](crate::XMLWriter), with the same escaping, but it only needs
+//! `core` and `alloc`, so it is available without the `std` feature for
+//! embedded targets. Since `fmt::Write` implementations can run out of space,
+//! the write methods return the [`fmt::Error`] instead of panicking.
+//!
+//! ```
+//! use excel_xmlwriter::fmt_writer::FmtXmlWriter;
+//!
+//! # fn main() -> Result<(), core::fmt::Error> {
+//! let mut writer = FmtXmlWriter::new(String::new());
+//!
+//! writer.xml_start_tag("row", &[("r", "1")])?;
+//! writer.xml_number_element(21.5, &[("r", "A1")])?;
+//! writer.xml_inline_string_element("Sensor 1", &[("r", "B1")])?;
+//! writer.xml_end_tag("row")?;
+//!
+//! assert_eq!(
+//!     writer.into_inner(),
+//!     concat!(
+//!         r#"<row r="1"><c r="A1"><v>21.5</v></c>"#,
+//!         r#"<c r="B1" t="inlineStr"><is><t>Sensor 1</t></is></c></row>"#
+//!     )
+//! );
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use core::fmt::{self, Write};
+
+use crate::{escape_data, write_escaped_attribute};
+
+/// A writer for Excel's XML to a [`fmt::Write`] implementation.
+#[derive(Debug)]
+pub struct FmtXmlWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> FmtXmlWriter<W> {
+    /// Create a new writer that writes XML to a `fmt::Write` implementation.
+    pub fn new(writer: W) -> FmtXmlWriter<W> {
+        FmtXmlWriter { writer }
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write an XML file declaration.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_declaration(&mut self) -> fmt::Result {
+        writeln!(
+            self.writer,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#
+        )
+    }
+
+    /// Write an XML start tag with attributes.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_start_tag(&mut self, tag: &str, attributes: &[(&str, &str)]) -> fmt::Result {
+        write!(self.writer, "<{tag}")?;
+        self.write_attributes(attributes)?;
+        self.writer.write_char('>')
+    }
+
+    /// Write an XML end tag.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_end_tag(&mut self, tag: &str) -> fmt::Result {
+        write!(self.writer, "</{tag}>")
+    }
+
+    /// Write an empty XML tag with attributes.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_empty_tag(&mut self, tag: &str, attributes: &[(&str, &str)]) -> fmt::Result {
+        write!(self.writer, "<{tag}")?;
+        self.write_attributes(attributes)?;
+        self.writer.write_str("/>")
+    }
+
+    /// Write an XML element containing data with attributes.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_data_element(
+        &mut self,
+        tag: &str,
+        data: &str,
+        attributes: &[(&str, &str)],
+    ) -> fmt::Result {
+        write!(self.writer, "<{tag}")?;
+        self.write_attributes(attributes)?;
+        write!(self.writer, ">{}</{tag}>", escape_data(data))
+    }
+
+    /// Write a `<c>` cell element with a number value.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_number_element(&mut self, number: f64, attributes: &[(&str, &str)]) -> fmt::Result {
+        self.writer.write_str("<c")?;
+        self.write_attributes(attributes)?;
+        write!(self.writer, "><v>{number}</v></c>")
+    }
+
+    /// Write a `<c>` cell element with an inline string value.
+    ///
+    /// # Errors
+    ///
+    /// The error from the underlying writer.
+    pub fn xml_inline_string_element(
+        &mut self,
+        string: &str,
+        attributes: &[(&str, &str)],
+    ) -> fmt::Result {
+        self.writer.write_str("<c")?;
+        self.write_attributes(attributes)?;
+        write!(
+            self.writer,
+            r#" t="inlineStr"><is><t>{}</t></is></c>"#,
+            escape_data(string)
+        )
+    }
+
+    // Write the attributes of a tag, with a leading space before each one.
+    fn write_attributes(&mut self, attributes: &[(&str, &str)]) -> fmt::Result {
+        for (key, value) in attributes {
+            self.writer.write_str(" ")?;
+            self.writer.write_str(key)?;
+            self.writer.write_str("=\"")?;
+            write_escaped_attribute(value, |piece| self.writer.write_str(piece))?;
+            self.writer.write_str("\"")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FmtXmlWriter;
+    use core::fmt::{self, Write};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_fmt_writer() {
+        let mut writer = FmtXmlWriter::new(String::new());
+
+        writer.xml_declaration().unwrap();
+        writer.xml_start_tag("sheetData", &[]).unwrap();
+        writer.xml_empty_tag("row", &[("r", "1")]).unwrap();
+        writer
+            .xml_data_element("t", "1 < 2 & \"3\"", &[("a", "\"x\" & <y>")])
+            .unwrap();
+        writer.xml_number_element(1.5, &[("r", "A2")]).unwrap();
+        writer
+            .xml_inline_string_element("<b>", &[("r", "B2"), ("s", "1")])
+            .unwrap();
+        writer.xml_end_tag("sheetData").unwrap();
+
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<sheetData><row r="1"/>"#,
+            r#"<t a="&quot;x&quot; &amp; &lt;y&gt;">1 &lt; 2 &amp; "3"</t>"#,
+            r#"<c r="A2"><v>1.5</v></c>"#,
+            r#"<c r="B2" s="1" t="inlineStr"><is><t>&lt;b&gt;</t></is></c>"#,
+            r#"</sheetData>"#,
+        );
+        assert_eq!(writer.into_inner(), expected);
+    }
+
+    // A fixed size buffer that fails once it is full.
+    struct Buffer<const N: usize> {
+        data: [u8; N],
+        len: usize,
+    }
+
+    impl<const N: usize> Write for Buffer<N> {
+        fn write_str(&mut self, string: &str) -> fmt::Result {
+            let end = self.len + string.len();
+            if end > N {
+                return Err(fmt::Error);
+            }
+            self.data[self.len..end].copy_from_slice(string.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fmt_writer_full() {
+        let mut writer = FmtXmlWriter::new(Buffer::<16> {
+            data: [0; 16],
+            len: 0,
+        });
+
+        assert!(writer.xml_empty_tag("row", &[("r", "1")]).is_ok());
+        assert!(writer.xml_empty_tag("row", &[("r", "2")]).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+//! The images that are inserted into worksheets.
+//!
+//! An [`Image`] is read from a file, or from the bytes of a PNG, JPEG, GIF or
+//! BMP image, and is added to a worksheet with
+//! [`Worksheet::insert_image()`](crate::worksheet::Worksheet::insert_image)
+//! or [`Worksheet::add_image()`](crate::worksheet::Worksheet::add_image). The
+//! size of the image is read from its header, and scaled by its resolution
+//! like Excel does, so that it is shown at the same size as an image inserted
+//! in Excel.
+//!
+//! The images of a worksheet are stored unchanged as `xl/media/imageN` parts,
+//! see the [`drawing`](crate::drawing) module.
+//!
+//! ```
+//! use excel_xmlwriter::image::{Image, ImageType};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // The header of a GIF image of 120 by 40 pixels.
+//! let data = [b"GIF89a".as_slice(), &[120, 0, 40, 0]].concat();
+//!
+//! let image = Image::from_bytes(&data)?.set_alt_text("Logo");
+//!
+//! assert_eq!(image.image_type(), ImageType::Gif);
+//! assert_eq!((image.width(), image.height()), (120, 40));
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::XlsxError;
+
+/// The types of image that Excel can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageType {
+    /// A PNG image.
+    Png,
+    /// A JPEG image.
+    Jpeg,
+    /// A GIF image.
+    Gif,
+    /// A Windows BMP image.
+    Bmp,
+}
+
+impl ImageType {
+    /// The file extension of the media parts of the image type, like `png`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageType::Png => "png",
+            ImageType::Jpeg => "jpeg",
+            ImageType::Gif => "gif",
+            ImageType::Bmp => "bmp",
+        }
+    }
+
+    /// The content type of the image type, for the default content type of
+    /// its extension with
+    /// [`ContentTypes::add_default()`](crate::content_types::ContentTypes::add_default).
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ImageType::Png => "image/png",
+            ImageType::Jpeg => "image/jpeg",
+            ImageType::Gif => "image/gif",
+            ImageType::Bmp => "image/bmp",
+        }
+    }
+}
+
+/// An image, with its data and the options for showing it in a worksheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    data: Vec<u8>,
+    image_type: ImageType,
+    width: u32,
+    height: u32,
+    x_dpi: f64,
+    y_dpi: f64,
+    scale_width: f64,
+    scale_height: f64,
+    alt_text: Option<String>,
+}
+
+impl Image {
+    /// Read an image from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`XlsxError::ImageRead`] if the file can't be read, and
+    /// [`XlsxError::UnsupportedImage`] if it isn't a supported image.
+    pub fn new(path: impl AsRef<Path>) -> Result<Image, XlsxError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|error| XlsxError::ImageRead {
+            path: path.display().to_string(),
+            error: error.to_string(),
+        })?;
+
+        Image::from_bytes(&data)
+    }
+
+    /// Create an image from the data of a PNG, JPEG, GIF or BMP image.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`XlsxError::UnsupportedImage`] if the data isn't a supported
+    /// image.
+    pub fn from_bytes(data: &[u8]) -> Result<Image, XlsxError> {
+        let (image_type, width, height, x_dpi, y_dpi) =
+            image_header(data).ok_or(XlsxError::UnsupportedImage)?;
+
+        Ok(Image {
+            data: data.to_vec(),
+            image_type,
+            width,
+            height,
+            x_dpi,
+            y_dpi,
+            scale_width: 1.0,
+            scale_height: 1.0,
+            alt_text: None,
+        })
+    }
+
+    /// Set the horizontal scale of the image, where `1.0` is its own size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scale isn't positive.
+    pub fn set_scale_width(mut self, scale: f64) -> Image {
+        assert!(scale > 0.0, "image scale {scale} must be positive");
+
+        self.scale_width = scale;
+        self
+    }
+
+    /// Set the vertical scale of the image, where `1.0` is its own size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scale isn't positive.
+    pub fn set_scale_height(mut self, scale: f64) -> Image {
+        assert!(scale > 0.0, "image scale {scale} must be positive");
+
+        self.scale_height = scale;
+        self
+    }
+
+    /// Set the alternative text of the image, for screen readers.
+    pub fn set_alt_text(mut self, alt_text: &str) -> Image {
+        self.alt_text = Some(alt_text.to_string());
+        self
+    }
+
+    /// The type of the image.
+    pub fn image_type(&self) -> ImageType {
+        self.image_type
+    }
+
+    /// The width of the image in pixels, from its header.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the image in pixels, from its header.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The data of the image, for its media part.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    // The alternative text of the image.
+    pub(crate) fn alt_text(&self) -> Option<&str> {
+        self.alt_text.as_deref()
+    }
+
+    // The size of the image in a worksheet, in pixels, as (width, height).
+    // Excel shows images at 96 dpi, so images with other resolutions are
+    // scaled.
+    pub(crate) fn size(&self) -> (u32, u32) {
+        let width = self.width as f64 * self.scale_width * 96.0 / self.x_dpi;
+        let height = self.height as f64 * self.scale_height * 96.0 / self.y_dpi;
+
+        (width.round() as u32, height.round() as u32)
+    }
+}
+
+// Read the type, the size in pixels and the horizontal and vertical
+// resolution in dpi from the header of an image. The resolution defaults to
+// 96 dpi if the image doesn't have one.
+fn image_header(data: &[u8]) -> Option<(ImageType, u32, u32, f64, f64)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_header(data)
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_header(data)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        let width = u16::from_le_bytes(data.get(6..8)?.try_into().ok()?);
+        let height = u16::from_le_bytes(data.get(8..10)?.try_into().ok()?);
+        Some((ImageType::Gif, width.into(), height.into(), 96.0, 96.0))
+    } else if data.starts_with(b"BM") {
+        let width = i32::from_le_bytes(data.get(18..22)?.try_into().ok()?);
+        let height = i32::from_le_bytes(data.get(22..26)?.try_into().ok()?);
+        // The height is negative for images stored from the top down.
+        Some((
+            ImageType::Bmp,
+            width.unsigned_abs(),
+            height.unsigned_abs(),
+            96.0,
+            96.0,
+        ))
+    } else {
+        None
+    }
+}
+
+// Read the size from the IHDR chunk of a PNG image, and the resolution from
+// the pHYs chunk if it comes before the image data.
+fn png_header(data: &[u8]) -> Option<(ImageType, u32, u32, f64, f64)> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(
+            data.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+
+    let mut size = None;
+    let (mut x_dpi, mut y_dpi) = (96.0, 96.0);
+
+    let mut offset = 8;
+    while let (Some(length), Some(chunk_type)) =
+        (read_u32(offset), data.get(offset + 4..offset + 8))
+    {
+        let chunk = offset + 8;
+        match chunk_type {
+            b"IHDR" => size = Some((read_u32(chunk)?, read_u32(chunk + 4)?)),
+            // The resolution in pixels per meter, if the unit is 1.
+            b"pHYs" if data.get(chunk + 8) == Some(&1) => {
+                x_dpi = read_u32(chunk)? as f64 * 0.0254;
+                y_dpi = read_u32(chunk + 4)? as f64 * 0.0254;
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+
+        // The chunk data is followed by a 4 byte CRC.
+        offset = chunk + length as usize + 4;
+    }
+
+    let (width, height) = size?;
+    Some((
+        ImageType::Png,
+        width,
+        height,
+        positive(x_dpi),
+        positive(y_dpi),
+    ))
+}
+
+// Read the size from the start of frame segment of a JPEG image, and the
+// resolution from the JFIF segment.
+fn jpeg_header(data: &[u8]) -> Option<(ImageType, u32, u32, f64, f64)> {
+    let read_u16 = |offset: usize| -> Option<u16> {
+        Some(u16::from_be_bytes(
+            data.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+
+    let mut size = None;
+    let (mut x_dpi, mut y_dpi) = (96.0, 96.0);
+
+    let mut offset = 2;
+    while size.is_none() {
+        let marker = read_u16(offset)?;
+        let length = read_u16(offset + 2)? as usize;
+
+        match marker {
+            // The start of frame markers, other than the DHT, JPG and DAC
+            // markers in the same range.
+            0xFFC0..=0xFFCF if !matches!(marker, 0xFFC4 | 0xFFC8 | 0xFFCC) => {
+                let height = read_u16(offset + 5)?;
+                let width = read_u16(offset + 7)?;
+                size = Some((width.into(), height.into()));
+            }
+            // The JFIF segment, with the density in dots per inch for unit 1
+            // or dots per centimeter for unit 2.
+            0xFFE0 if data.get(offset + 4..offset + 9) == Some(b"JFIF\0") => {
+                let scale = match data.get(offset + 11) {
+                    Some(1) => 1.0,
+                    Some(2) => 2.54,
+                    _ => 0.0,
+                };
+                if scale > 0.0 {
+                    x_dpi = read_u16(offset + 12)? as f64 * scale;
+                    y_dpi = read_u16(offset + 14)? as f64 * scale;
+                }
+            }
+            // The start of the image data.
+            0xFFDA => break,
+            _ => {}
+        }
+
+        offset += 2 + length;
+    }
+
+    let (width, height) = size?;
+    Some((
+        ImageType::Jpeg,
+        width,
+        height,
+        positive(x_dpi),
+        positive(y_dpi),
+    ))
+}
+
+// A resolution, or the default 96 dpi if it is 0.
+fn positive(dpi: f64) -> f64 {
+    if dpi > 0.0 {
+        dpi
+    } else {
+        96.0
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use super::{Image, ImageType};
+    use crate::error::XlsxError;
+
+    use pretty_assertions::assert_eq;
+
+    // The header of a PNG image, with a pHYs chunk for the resolution in
+    // pixels per meter if it isn't 0.
+    pub(crate) fn png_data(width: u32, height: u32, pixels_per_meter: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut chunk = |chunk_type: &[u8], chunk_data: &[u8]| {
+            data.extend((chunk_data.len() as u32).to_be_bytes());
+            data.extend(chunk_type);
+            data.extend(chunk_data);
+            data.extend([0; 4]);
+        };
+
+        let ihdr = [width.to_be_bytes(), height.to_be_bytes(), [8, 6, 0, 0]].concat();
+        chunk(b"IHDR", &[ihdr.as_slice(), &[0]].concat());
+        if pixels_per_meter > 0 {
+            let ppm = pixels_per_meter.to_be_bytes();
+            chunk(b"pHYs", &[ppm.as_slice(), &ppm, &[1]].concat());
+        }
+        chunk(b"IEND", &[]);
+
+        data
+    }
+
+    #[test]
+    fn test_png_image() {
+        let image = Image::from_bytes(&png_data(200, 100, 0)).unwrap();
+        assert_eq!(image.image_type(), ImageType::Png);
+        assert_eq!((image.width(), image.height()), (200, 100));
+        assert_eq!(image.size(), (200, 100));
+
+        // 144 dpi is 5669 pixels per meter.
+        let image = Image::from_bytes(&png_data(200, 100, 5669))
+            .unwrap()
+            .set_scale_width(2.0);
+        assert_eq!(image.size(), (267, 67));
+    }
+
+    #[test]
+    fn test_jpeg_image() {
+        let data = [
+            &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10][..],
+            b"JFIF\0",
+            &[1, 1, 1, 0, 72, 0, 72, 0, 0],
+            &[0xFF, 0xC0, 0x00, 0x11, 8, 0x01, 0x2C, 0x01, 0x90],
+        ]
+        .concat();
+
+        let image = Image::from_bytes(&data).unwrap();
+        assert_eq!(image.image_type(), ImageType::Jpeg);
+        assert_eq!((image.width(), image.height()), (400, 300));
+        assert_eq!(image.size(), (533, 400));
+    }
+
+    #[test]
+    fn test_gif_and_bmp_images() {
+        let image = Image::from_bytes(&[b"GIF87a".as_slice(), &[10, 1, 20, 0]].concat()).unwrap();
+        assert_eq!(image.image_type(), ImageType::Gif);
+        assert_eq!(image.size(), (266, 20));
+
+        let mut data = vec![0; 26];
+        data[..2].copy_from_slice(b"BM");
+        data[18..22].copy_from_slice(&64_i32.to_le_bytes());
+        data[22..26].copy_from_slice(&(-32_i32).to_le_bytes());
+        let image = Image::from_bytes(&data).unwrap();
+        assert_eq!(image.image_type(), ImageType::Bmp);
+        assert_eq!(image.size(), (64, 32));
+    }
+
+    #[test]
+    fn test_unsupported_images() {
+        assert_eq!(
+            Image::from_bytes(b"not an image"),
+            Err(XlsxError::UnsupportedImage)
+        );
+        assert_eq!(
+            Image::from_bytes(&png_data(1, 1, 0)[..12]),
+            Err(XlsxError::UnsupportedImage)
+        );
+        assert!(matches!(
+            Image::new("missing.png"),
+            Err(XlsxError::ImageRead { .. })
+        ));
+    }
+}
@@ -9,8 +9,10 @@
 //!
 //! ```
 //! use std::fs::File;
+//! # #[cfg(feature = "std")]
 //! use excel_xmlwriter::XMLWriter;
 //!
+//! # #[cfg(feature = "std")]
 //! fn main() -> Result<(), std::io::Error> {
 //!     let xmlfile = File::create("test.xml")?;
 //!     let mut writer = XMLWriter::new(&xmlfile);
@@ -22,6 +24,8 @@
 //!
 //!     Ok(())
 //! }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
 //! ```
 //! Output in `test.xml`:
 //!
@@ -29,18 +33,717 @@
 //! <?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 //! <foo bar="1">some text</foo>
 //! ```
+//!
+//! # Features
+//!
+//! The element writer in this module, along with the [`xml!`] macro and the
+//! [`cell_iter`], [`error`], [`rich_string`] and [`testing`] modules, is
+//! available with the `std` feature. The [`constants`], [`fmt_writer`],
+//! [`sanitize`] and [`utility`] modules are always available. The part
+//! writers are grouped into features, which are all enabled by default and
+//! which all require `std`:
+//!
+//! - `styles`: the [`styles`] module.
+//! - `worksheet`: the [`worksheet`], [`workbook`], [`shared_strings`],
+//!   [`page_setup`], [`conditional_format`], [`data_validation`], [`table`],
+//!   [`sparkline`], [`protection`], [`metadata`] and [`external_link`]
+//!   modules. Requires `styles` and `package`.
+//! - `drawings`: the [`drawing`], [`image`], [`vml`], [`note`],
+//!   [`threaded_comment`], [`button`], [`ole_object`] and [`rich_data`]
+//!   modules, and the worksheet methods that add images, notes, buttons and
+//!   objects. Notes and buttons are drawn with VML, so they are part of this
+//!   group. Requires `worksheet`.
+//! - `charts`: the [`chart`] module and
+//!   [`Worksheet::insert_chart()`](worksheet::Worksheet::insert_chart).
+//!   Requires `drawings`.
+//! - `package`: the [`app`], [`core_properties`], [`custom`],
+//!   [`content_types`], [`part`], [`relationship`] and [`theme`] modules.
+//! - `zip`: the [`packager`] module.
+//!
+//! The optional `ffi` feature adds the C interface in the `ffi` module, the
+//! optional `serde` feature adds the `serde` `serializer`, and the optional
+//! `quick-xml` feature adds `XMLWriter::write_event()` in the `events`
+//! module.
+//!
+//! The optional `validation` feature adds the `validation` module, which
+//! checks worksheet, styles and workbook parts against the element structure
+//! of the ECMA-376 schemas. It is intended for tests, and adds a dependency
+//! on `quick-xml`.
+//!
+//! The optional `derive` feature adds a derive macro for the [`XmlElement`]
+//! trait, from the companion `excel_xmlwriter_derive` crate, which maps
+//! struct fields to attributes and child elements.
+//!
+//! The optional `tracing` feature, which isn't enabled by default, emits
+//! [`tracing`](https://docs.rs/tracing) spans for the parts added to a
+//! package and debug events for each batch of 10,000 worksheet rows, so
+//! long-running exports can be observed with any `tracing` subscriber.
+//!
+//! Users who only need the element writer can disable the default features:
+//!
+//! ```toml
+//! [dependencies]
+//! excel_xmlwriter = { version = "0.1", default-features = false, features = ["std"] }
+//! ```
+//!
+//! Without the `std` feature the crate is `no_std`, for embedded targets,
+//! and only needs `alloc`. XML can then be written to any
+//! [`core::fmt::Write`] implementation with the
+//! [`FmtXmlWriter`](fmt_writer::FmtXmlWriter).
+//!
+//! The XML can be written to any [`Write`] implementation.
+//! The writer only seeks to patch placeholders when it is created with
+//! [`XMLWriter::new_seekable()`], so parts can also be streamed to stdout or
+//! a pipe:
+//!
+//! ```
+//! # #[cfg(feature = "std")] {
+//! use std::io::{stdout, BufWriter};
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! let mut writer = XMLWriter::new(BufWriter::new(stdout().lock()));
+//!
+//! writer.xml_declaration();
+//! writer.xml_data_element("foo", "some text", &vec![]);
+//! # }
+//! ```
+//!
+//! The crate doesn't need a file system, so it also builds for
+//! `wasm32-unknown-unknown`, where parts are written to a `Vec<u8>` and the
+//! bytes handed to JavaScript. See `examples/wasm` for an example that uses
+//! `wasm-bindgen`.
 // SPDX-License-Identifier: MIT
 // Copyright 2022, John McNamara, jmcnamara@cpan.org
 
-use std::fs::File;
-use std::io::Write;
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+extern crate alloc;
+
+pub mod constants;
+pub mod fmt_writer;
+pub mod sanitize;
+pub mod utility;
+
+#[cfg(feature = "std")]
+pub mod cell_iter;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+mod macros;
+#[cfg(feature = "std")]
+pub mod rich_string;
+#[cfg(feature = "std")]
+pub mod testing;
+
+#[cfg(feature = "styles")]
+pub mod styles;
+
+#[cfg(feature = "drawings")]
+pub mod button;
+#[cfg(feature = "charts")]
+pub mod chart;
+#[cfg(feature = "drawings")]
+mod compound_file;
+#[cfg(feature = "worksheet")]
+pub mod conditional_format;
+#[cfg(feature = "worksheet")]
+pub mod data_validation;
+#[cfg(feature = "drawings")]
+pub mod drawing;
+#[cfg(feature = "worksheet")]
+pub mod external_link;
+#[cfg(feature = "drawings")]
+pub mod image;
+#[cfg(feature = "worksheet")]
+pub mod metadata;
+#[cfg(feature = "drawings")]
+pub mod note;
+#[cfg(feature = "drawings")]
+pub mod ole_object;
+#[cfg(feature = "worksheet")]
+pub mod page_setup;
+#[cfg(feature = "worksheet")]
+pub mod protection;
+#[cfg(feature = "drawings")]
+pub mod rich_data;
+#[cfg(feature = "worksheet")]
+pub mod shared_strings;
+#[cfg(feature = "worksheet")]
+pub mod sparkline;
+#[cfg(feature = "worksheet")]
+pub mod table;
+#[cfg(feature = "drawings")]
+pub mod threaded_comment;
+#[cfg(feature = "drawings")]
+pub mod vml;
+#[cfg(feature = "worksheet")]
+pub mod workbook;
+#[cfg(feature = "worksheet")]
+pub mod worksheet;
+
+#[cfg(feature = "package")]
+pub mod app;
+#[cfg(feature = "package")]
+pub mod content_types;
+#[cfg(feature = "package")]
+pub mod core_properties;
+#[cfg(feature = "package")]
+pub mod custom;
+#[cfg(feature = "package")]
+pub mod part;
+#[cfg(feature = "package")]
+pub mod relationship;
+#[cfg(feature = "package")]
+pub mod theme;
+
+#[cfg(feature = "zip")]
+mod deflate;
+#[cfg(feature = "zip")]
+pub mod packager;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "serde")]
+pub mod serializer;
+
+#[cfg(feature = "quick-xml")]
+pub mod events;
+
+#[cfg(feature = "validation")]
+pub mod validation;
+
+#[cfg(feature = "derive")]
+pub use excel_xmlwriter_derive::XmlElement;
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+#[cfg(feature = "std")]
+use rich_string::RunProperties;
+#[cfg(feature = "std")]
+use sanitize::{sanitize_string, SanitizePolicy};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::io::{self, IoSlice, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// A typed cell value.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellValue {
+    /// The index of a string in the shared string table.
+    SharedString(u32),
+
+    /// A number. Dates are stored as numbers in Excel.
+    Number(f64),
+
+    /// A boolean.
+    Bool(bool),
+
+    /// A formula, without the leading `=`, and its calculated result.
+    Formula {
+        /// The formula string.
+        formula: String,
+        /// The formula result.
+        result: f64,
+    },
+
+    /// A string that is stored in the cell rather than in the shared string
+    /// table.
+    InlineStr(String),
+
+    /// An Excel error value such as `#N/A`.
+    Error(String),
+}
+
+/// An attribute value for
+/// [`xml_start_tag_typed()`](XMLWriter::xml_start_tag_typed) and
+/// [`xml_empty_tag_typed()`](XMLWriter::xml_empty_tag_typed), which is either
+/// escaped when it is written or known not to need escaping.
+///
+/// Most attribute values, such as cell references, style ids and booleans,
+/// can't contain the characters that are escaped, so they can be written as
+/// [`Escaped`](AttributeValue::Escaped) without being scanned.
+///
+/// ```
+/// # use excel_xmlwriter::{AttributeValue, XMLWriter};
+/// #
+/// let mut writer = XMLWriter::new(vec![]);
+///
+/// let attributes = [
+///     ("ref", AttributeValue::Escaped("A1")),
+///     ("display", AttributeValue::Text("R&D")),
+/// ];
+/// writer.xml_empty_tag_typed("hyperlink", &attributes);
+///
+/// assert_eq!(writer.into_inner(), br#"<hyperlink ref="A1" display="R&amp;D"/>"#);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeValue<'a> {
+    /// A value that is escaped when it is written.
+    Text(&'a str),
+
+    /// A value that doesn't contain any of the characters `&`, `"`, `<`, `>`
+    /// or a newline, and is written as is. Debug builds panic if it does.
+    Escaped(&'a str),
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<&'a str> for AttributeValue<'a> {
+    fn from(value: &'a str) -> Self {
+        AttributeValue::Text(value)
+    }
+}
+
+// An attribute in one of the attribute lists of the writer: a (name, value)
+// pair of strings, which are always escaped, or of typed values.
+#[cfg(feature = "std")]
+trait Attribute {
+    fn name(&self) -> &str;
+    fn value(&self) -> AttributeValue<'_>;
+}
+
+#[cfg(feature = "std")]
+impl Attribute for (&str, &str) {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn value(&self) -> AttributeValue<'_> {
+        AttributeValue::Text(self.1)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Attribute for (&str, AttributeValue<'_>) {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn value(&self) -> AttributeValue<'_> {
+        self.1
+    }
+}
+
+/// A writer for Excel's XML. The XML can be written to any [`Write`]
+/// implementation, such as a file, a `Vec<u8>` or stdout. The writer doesn't
+/// seek or buffer, apart from for placeholders and checkpoints, so for
+/// writes to a file or pipe it is usually best to wrap them in a
+/// [`BufWriter`](std::io::BufWriter).
+///
+/// The part writers in the crate always write attributes in the order that
+/// Excel uses, so the output is the same for the same input. Attribute lists
+/// built from unordered sources, such as a `HashMap`, can be caught with
+/// [`set_check_attribute_order()`](XMLWriter::set_check_attribute_order).
+///
+/// The writer keeps track of the open elements so that a failed write can be
+/// reported with its [`context()`](XMLWriter::context).
+///
+/// The writer owns its underlying writer and is `Send` when the underlying
+/// writer is, so parts can be written in parallel by moving one writer to
+/// each thread:
+///
+/// ```
+/// # use excel_xmlwriter::XMLWriter;
+/// # use std::thread;
+/// #
+/// let handles: Vec<_> = (1..=3)
+///     .map(|sheet| {
+///         let mut writer = XMLWriter::new(vec![]);
+///         thread::spawn(move || {
+///             writer.xml_data_element("sheet", &sheet.to_string(), &vec![]);
+///             writer.into_inner()
+///         })
+///     })
+///     .collect();
+///
+/// let parts: Vec<Vec<u8>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+/// assert_eq!(parts[2], b"<sheet>3</sheet>");
+/// ```
+#[cfg(feature = "std")]
+pub struct XMLWriter<W: Write> {
+    writer: CountingWriter<W>,
+    sanitize_policy: SanitizePolicy,
+    attribute_orders: Option<BTreeMap<String, Vec<String>>>,
+    part_name: Option<String>,
+    elements: Vec<OpenElement>,
+    scratch: String,
+    element_count: u64,
+    started: Option<Instant>,
+    seek_patch: Option<(SeekPatch<W>, u64)>,
+    pending_placeholders: Vec<(u64, u64)>,
+    placeholder_id: u64,
+    checkpoints: Vec<u64>,
+    namespaces: Vec<(String, String)>,
+    at_root: bool,
+}
+
+// Overwrite the bytes at a position in a seekable writer, set by
+// XMLWriter::new_seekable() so that the writer itself doesn't need to be
+// `Seek`.
+#[cfg(feature = "std")]
+type SeekPatch<W> = fn(&mut W, u64, &[u8]) -> io::Result<()>;
+
+/// A space in the output reserved with
+/// [`reserve_placeholder()`](XMLWriter::reserve_placeholder), to be filled
+/// in with [`patch()`](XMLWriter::patch).
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    id: u64,
+    offset: u64,
+    len: usize,
+}
+
+/// A position in the output from [`checkpoint()`](XMLWriter::checkpoint),
+/// which the writer can go back to with
+/// [`rollback()`](XMLWriter::rollback).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Checkpoint {
+    offset: u64,
+    elements: Vec<OpenElement>,
+    element_count: u64,
+}
+
+/// The values of an XML declaration written with
+/// [`xml_declaration_with()`](XMLWriter::xml_declaration_with). The default
+/// is the declaration that Excel writes for most parts,
+/// `<?xml version="1.0" encoding="UTF-8" standalone="yes"?>`, followed by a
+/// newline.
+///
+/// ```
+/// # use excel_xmlwriter::{XMLWriter, XmlDeclaration};
+/// #
+/// let mut writer = XMLWriter::new(vec![]);
+///
+/// let declaration = XmlDeclaration::new()
+///     .set_encoding(Some("ISO-8859-1"))
+///     .set_standalone(None)
+///     .set_newline(false);
+/// writer.xml_declaration_with(&declaration);
+///
+/// assert_eq!(
+///     writer.into_inner(),
+///     br#"<?xml version="1.0" encoding="ISO-8859-1"?>"#
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlDeclaration {
+    version: String,
+    encoding: Option<String>,
+    standalone: Option<bool>,
+    newline: bool,
+}
+
+#[cfg(feature = "std")]
+impl Default for XmlDeclaration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl XmlDeclaration {
+    /// Create the default declaration.
+    pub fn new() -> XmlDeclaration {
+        XmlDeclaration {
+            version: "1.0".to_string(),
+            encoding: Some("UTF-8".to_string()),
+            standalone: Some(true),
+            newline: true,
+        }
+    }
+
+    /// Set the XML version. The default is `1.0`.
+    pub fn set_version(mut self, version: &str) -> XmlDeclaration {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Set the encoding, or omit it with `None`. The default is `UTF-8`.
+    /// The writer doesn't encode the text, so other encodings are only
+    /// correct for ASCII text.
+    pub fn set_encoding(mut self, encoding: Option<&str>) -> XmlDeclaration {
+        self.encoding = encoding.map(str::to_string);
+        self
+    }
+
+    /// Set the `standalone` value to `yes` or `no`, or omit it with `None`.
+    /// The default is `yes`.
+    pub fn set_standalone(mut self, standalone: Option<bool>) -> XmlDeclaration {
+        self.standalone = standalone;
+        self
+    }
+
+    /// Turn the newline after the declaration on or off. It is on by
+    /// default.
+    pub fn set_newline(mut self, newline: bool) -> XmlDeclaration {
+        self.newline = newline;
+        self
+    }
+}
 
-pub struct XMLWriter<'a> {
-    xmlfile: &'a File,
+/// The throughput of an [`XMLWriter`], from
+/// [`stats()`](XMLWriter::stats), for catching performance regressions in
+/// the part writers.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WriterStats {
+    elements: u64,
+    bytes: u64,
+    elapsed: Duration,
 }
 
-impl<'a> XMLWriter<'a> {
-    /// Create a new XMLWriter struct to write XML to a given filehandle.
+#[cfg(feature = "std")]
+impl WriterStats {
+    /// The number of elements that have been written. A cell is counted as
+    /// one element, including its value and formula.
+    pub fn elements(&self) -> u64 {
+        self.elements
+    }
+
+    /// The number of bytes that have been written.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The time since the writer was created. It is always zero on
+    /// `wasm32-unknown-unknown`, which doesn't have a clock.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The number of elements written per second, or 0 if no time has
+    /// passed.
+    pub fn elements_per_sec(&self) -> f64 {
+        per_sec(self.elements, self.elapsed)
+    }
+
+    /// The number of bytes written per second, or 0 if no time has passed.
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.bytes, self.elapsed)
+    }
+}
+
+/// A chainable view of an [`XMLWriter`], from
+/// [`fluent()`](XMLWriter::fluent), for writing small structures in one
+/// expression. Each method writes with the matching `xml_*` method
+/// and returns the view so that the next call can follow it.
+///
+/// ```
+/// # use excel_xmlwriter::XMLWriter;
+/// #
+/// let mut writer = XMLWriter::new(vec![]);
+///
+/// writer
+///     .fluent()
+///     .start("sheetView", &vec![("workbookViewId", "0")])
+///     .empty("selection", &vec![("activeCell", "B2")])
+///     .data("extra", "text", &vec![])
+///     .end("sheetView");
+///
+/// assert_eq!(
+///     writer.into_inner(),
+///     br#"<sheetView workbookViewId="0"><selection activeCell="B2"/><extra>text</extra></sheetView>"#
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub struct FluentWriter<'a, W: Write> {
+    writer: &'a mut XMLWriter<W>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> FluentWriter<'_, W> {
+    /// Write a start tag, like [`xml_start_tag()`](XMLWriter::xml_start_tag).
+    pub fn start(self, tag: &str, attributes: &Vec<(&str, &str)>) -> Self {
+        self.writer.xml_start_tag(tag, attributes);
+        self
+    }
+
+    /// Write an end tag, like [`xml_end_tag()`](XMLWriter::xml_end_tag).
+    pub fn end(self, tag: &str) -> Self {
+        self.writer.xml_end_tag(tag);
+        self
+    }
+
+    /// Write an empty tag, like [`xml_empty_tag()`](XMLWriter::xml_empty_tag).
+    pub fn empty(self, tag: &str, attributes: &Vec<(&str, &str)>) -> Self {
+        self.writer.xml_empty_tag(tag, attributes);
+        self
+    }
+
+    /// Write an element containing data, like
+    /// [`xml_data_element()`](XMLWriter::xml_data_element).
+    pub fn data(self, tag: &str, data: &str, attributes: &Vec<(&str, &str)>) -> Self {
+        self.writer.xml_data_element(tag, data, attributes);
+        self
+    }
+}
+
+/// A type that is written as a single XML element, such as the many small
+/// record-like elements of the SpreadsheetML parts.
+///
+/// With the `derive` feature it can be derived for structs with named
+/// fields. The element is named after the struct in lower camel case, or
+/// with `#[xml(tag = "...")]`, and the fields are written as attributes
+/// named after the fields in lower camel case, with the Excel escaping. The
+/// field attributes change how a field is written:
+///
+/// - `#[xml(rename = "...")]`: the name of the attribute.
+/// - `#[xml(child)]`: a child element, which implements `XmlElement`. An
+///   `Option` child is omitted when it is `None` and a `Vec` is written as a
+///   repeated element.
+/// - `#[xml(text)]`: the text of the element, which can't also have child
+///   elements.
+/// - `#[xml(skip)]`: a field that isn't written.
+///
+/// Attributes that are `None` are omitted, booleans are written as `1` and
+/// `0`, and other values are written with their `Display` implementation.
+///
+/// ```
+/// # #[cfg(feature = "derive")] {
+/// use excel_xmlwriter::{XMLWriter, XmlElement};
+///
+/// #[derive(XmlElement)]
+/// struct Pane {
+///     x_split: u32,
+///     top_left_cell: String,
+///     active_pane: Option<String>,
+///     #[xml(rename = "state")]
+///     frozen_state: &'static str,
+/// }
+///
+/// #[derive(XmlElement)]
+/// #[xml(tag = "sheetView")]
+/// struct View {
+///     tab_selected: bool,
+///     #[xml(child)]
+///     pane: Option<Pane>,
+/// }
+///
+/// let view = View {
+///     tab_selected: true,
+///     pane: Some(Pane {
+///         x_split: 1,
+///         top_left_cell: "B1".to_string(),
+///         active_pane: None,
+///         frozen_state: "frozen",
+///     }),
+/// };
+///
+/// let mut writer = XMLWriter::new(vec![]);
+/// view.write_element(&mut writer);
+///
+/// assert_eq!(
+///     writer.into_inner(),
+///     br#"<sheetView tabSelected="1"><pane xSplit="1" topLeftCell="B1" state="frozen"/></sheetView>"#
+/// );
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub trait XmlElement {
+    /// Write the element with a writer.
+    fn write_element<W: Write>(&self, writer: &mut XMLWriter<W>);
+}
+
+// The current time, if the target has a clock. Instant::now() panics on
+// wasm32-unknown-unknown.
+#[cfg(feature = "std")]
+fn now() -> Option<Instant> {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        None
+    } else {
+        Some(Instant::now())
+    }
+}
+
+// The rate of a count over a duration.
+#[cfg(feature = "std")]
+fn per_sec(count: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        count as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+// A writer that counts the bytes written to the underlying writer. While
+// there is a buffer, the bytes are held in it, starting at `buffer_start`,
+// instead of being written.
+#[cfg(feature = "std")]
+struct CountingWriter<W: Write> {
+    inner: W,
+    bytes: u64,
+    buffer: Option<Vec<u8>>,
+    buffer_start: u64,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = match &mut self.buffer {
+            Some(buffer) => {
+                buffer.extend_from_slice(buf);
+                buf.len()
+            }
+            None => self.inner.write(buf)?,
+        };
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match &mut self.buffer {
+            Some(buffer) => buffer.extend_from_slice(buf),
+            None => self.inner.write_all(buf)?,
+        }
+        self.bytes += buf.len() as u64;
+        Ok(())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let written = match &mut self.buffer {
+            Some(buffer) => {
+                for buf in bufs {
+                    buffer.extend_from_slice(buf);
+                }
+                bufs.iter().map(|buf| buf.len()).sum()
+            }
+            None => self.inner.write_vectored(bufs)?,
+        };
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// An open element, its position among the consecutive siblings with the same
+// name, the name and position of its latest child element, and the
+// namespace prefixes that it declares, if they are checked. The first element
+// in the path is the document itself.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+struct OpenElement {
+    tag: String,
+    position: usize,
+    last_child: Option<(String, usize)>,
+    prefixes: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> XMLWriter<W> {
+    /// Create a new XMLWriter struct to write XML to a given filehandle or
+    /// other writer.
     /// ```
     /// # use std::fs::File;
     /// # use excel_xmlwriter::XMLWriter;
@@ -52,8 +755,474 @@ impl<'a> XMLWriter<'a> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn new(xmlfile: &File) -> XMLWriter {
-        XMLWriter { xmlfile }
+    pub fn new(writer: W) -> XMLWriter<W> {
+        XMLWriter {
+            writer: CountingWriter {
+                inner: writer,
+                bytes: 0,
+                buffer: None,
+                buffer_start: 0,
+            },
+            sanitize_policy: SanitizePolicy::None,
+            attribute_orders: None,
+            part_name: None,
+            elements: vec![OpenElement::default()],
+            scratch: String::new(),
+            element_count: 0,
+            started: now(),
+            seek_patch: None,
+            pending_placeholders: vec![],
+            placeholder_id: 0,
+            checkpoints: vec![],
+            namespaces: vec![],
+            at_root: false,
+        }
+    }
+
+    /// Return the underlying writer.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_empty_tag("foo", &vec![]);
+    ///
+    /// assert_eq!(writer.into_inner(), b"<foo/>");
+    /// ```
+    pub fn into_inner(mut self) -> W {
+        self.write_buffer();
+        self.writer.inner
+    }
+
+    /// Reserve `len` bytes of the output, which are written as spaces, to be
+    /// filled in later with [`patch()`](XMLWriter::patch). This is for
+    /// values that aren't known until the rest of the part is written, such
+    /// as the `<dimension>` of a worksheet. Since the unused bytes are left
+    /// as spaces, the placeholder should be somewhere that whitespace is
+    /// allowed, such as between elements.
+    ///
+    /// Writers created with [`new_seekable()`](XMLWriter::new_seekable) seek
+    /// back to the placeholder to patch it. Other writers hold the output
+    /// from the first unpatched placeholder in memory until all the
+    /// placeholders are patched, or until [`into_inner()`](XMLWriter::into_inner).
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_start_tag("worksheet", &vec![]);
+    /// let dimension = writer.reserve_placeholder(32);
+    /// writer.xml_empty_tag("sheetData", &vec![]);
+    /// writer.xml_end_tag("worksheet");
+    ///
+    /// writer.patch(dimension, r#"<dimension ref="A1:C3"/>"#);
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(writer.into_inner()).unwrap(),
+    ///     r#"<worksheet><dimension ref="A1:C3"/>        <sheetData/></worksheet>"#
+    /// );
+    /// ```
+    pub fn reserve_placeholder(&mut self, len: usize) -> Placeholder {
+        let offset = self.writer.bytes;
+        let id = self.placeholder_id;
+        self.placeholder_id += 1;
+
+        if self.seek_patch.is_none() {
+            self.start_buffer();
+        }
+        self.pending_placeholders.push((id, offset));
+
+        self.writer
+            .write_all(&vec![b' '; len])
+            .unwrap_or_else(|error| self.write_failed(error));
+
+        Placeholder { id, offset, len }
+    }
+
+    /// Fill in a placeholder from
+    /// [`reserve_placeholder()`](XMLWriter::reserve_placeholder) with XML
+    /// text, which is written as is and padded with spaces to the length of
+    /// the placeholder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the text is longer than the placeholder, if the placeholder
+    /// wasn't reserved with this writer, or if it was discarded by a
+    /// [`rollback()`](XMLWriter::rollback) to a checkpoint before it.
+    pub fn patch(&mut self, placeholder: Placeholder, text: &str) {
+        assert!(
+            text.len() <= placeholder.len,
+            "'{text}' is longer than the placeholder of {} bytes",
+            placeholder.len
+        );
+
+        let index = self
+            .pending_placeholders
+            .iter()
+            .position(|pending| *pending == (placeholder.id, placeholder.offset))
+            .expect("placeholder was discarded by a rollback, or wasn't reserved with this writer");
+        self.pending_placeholders.remove(index);
+
+        let mut data = text.as_bytes().to_vec();
+        data.resize(placeholder.len, b' ');
+
+        match &mut self.writer.buffer {
+            Some(buffer) if placeholder.offset >= self.writer.buffer_start => {
+                let start = (placeholder.offset - self.writer.buffer_start) as usize;
+                buffer[start..start + data.len()].copy_from_slice(&data);
+
+                self.release_buffer();
+            }
+            _ => {
+                let (seek_patch, stream_start) = self
+                    .seek_patch
+                    .expect("placeholder wasn't reserved with this writer");
+
+                seek_patch(
+                    &mut self.writer.inner,
+                    stream_start + placeholder.offset,
+                    &data,
+                )
+                .unwrap_or_else(|error| self.write_failed(error));
+            }
+        }
+    }
+
+    /// Mark the current position in the output, so that the elements written
+    /// after it can be discarded with [`rollback()`](XMLWriter::rollback),
+    /// such as a row that turns out to be invalid. The output is held in
+    /// memory until the checkpoint is committed with
+    /// [`commit()`](XMLWriter::commit) or rolled back. Checkpoints can be
+    /// nested.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_start_tag("sheetData", &vec![]);
+    ///
+    /// let checkpoint = writer.checkpoint();
+    /// writer.xml_start_tag("row", &vec![("r", "1")]);
+    /// writer.xml_formula_element("SUM(", 0.0, &vec![("r", "A1")]);
+    /// writer.rollback(checkpoint);
+    ///
+    /// writer.xml_end_tag("sheetData");
+    ///
+    /// assert_eq!(writer.into_inner(), b"<sheetData></sheetData>");
+    /// ```
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let offset = self.writer.bytes;
+
+        self.start_buffer();
+        self.checkpoints.push(offset);
+
+        Checkpoint {
+            offset,
+            elements: self.elements.clone(),
+            element_count: self.element_count,
+        }
+    }
+
+    /// Discard the output since a checkpoint from
+    /// [`checkpoint()`](XMLWriter::checkpoint), along with any checkpoints
+    /// and placeholders after it, and restore the open elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint has already been committed or rolled back, or
+    /// if it wasn't created with this writer.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.remove_checkpoint(&checkpoint);
+
+        let buffer = self.writer.buffer.as_mut().expect("checkpoint is open");
+        buffer.truncate((checkpoint.offset - self.writer.buffer_start) as usize);
+
+        self.writer.bytes = checkpoint.offset;
+        self.elements = checkpoint.elements;
+        self.element_count = checkpoint.element_count;
+        self.checkpoints
+            .retain(|offset| *offset < checkpoint.offset);
+        self.pending_placeholders
+            .retain(|(_, offset)| *offset < checkpoint.offset);
+
+        self.release_buffer();
+    }
+
+    /// Keep the output since a checkpoint from
+    /// [`checkpoint()`](XMLWriter::checkpoint). It is written to the
+    /// underlying writer once there are no open checkpoints or unpatched
+    /// placeholders before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint has already been committed or rolled back, or
+    /// if it wasn't created with this writer.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        self.remove_checkpoint(&checkpoint);
+        self.release_buffer();
+    }
+
+    // Remove a checkpoint from the open checkpoints.
+    fn remove_checkpoint(&mut self, checkpoint: &Checkpoint) {
+        let index = self
+            .checkpoints
+            .iter()
+            .rposition(|offset| *offset == checkpoint.offset)
+            .filter(|_| self.writer.buffer.is_some())
+            .expect("checkpoint has already been committed or rolled back");
+
+        self.checkpoints.remove(index);
+    }
+
+    // Hold the output in memory from the current position, if it isn't
+    // already held.
+    fn start_buffer(&mut self) {
+        if self.writer.buffer.is_none() {
+            self.writer.buffer = Some(vec![]);
+            self.writer.buffer_start = self.writer.bytes;
+        }
+    }
+
+    // Write the held output to the underlying writer once there are no
+    // checkpoints or buffered placeholders that need it. The placeholders of
+    // a seekable writer aren't buffered.
+    fn release_buffer(&mut self) {
+        if self.checkpoints.is_empty()
+            && (self.seek_patch.is_some() || self.pending_placeholders.is_empty())
+        {
+            self.write_buffer();
+        }
+    }
+
+    // Write the held output to the underlying writer.
+    fn write_buffer(&mut self) {
+        if let Some(buffer) = self.writer.buffer.take() {
+            self.writer
+                .inner
+                .write_all(&buffer)
+                .unwrap_or_else(|error| self.write_failed(error));
+        }
+    }
+
+    /// Get the number of elements and bytes that have been written, and the
+    /// time since the writer was created, for measuring its throughput.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_data_element("foo", "bar", &vec![]);
+    ///
+    /// let stats = writer.stats();
+    /// assert_eq!(stats.elements(), 1);
+    /// assert_eq!(stats.bytes(), 14);
+    /// println!("{:.0} elements/sec", stats.elements_per_sec());
+    /// ```
+    pub fn stats(&self) -> WriterStats {
+        WriterStats {
+            elements: self.element_count,
+            bytes: self.writer.bytes,
+            elapsed: self
+                .started
+                .map_or(Duration::ZERO, |started| started.elapsed()),
+        }
+    }
+
+    /// Set the name of the part that is being written, like
+    /// `xl/worksheets/sheet1.xml`, for the [`context()`](XMLWriter::context)
+    /// of errors. The [`Packager`](crate::packager::Packager) sets it for
+    /// the parts that it writes.
+    pub fn set_part_name(&mut self, part_name: &str) {
+        self.part_name = Some(part_name.to_string());
+    }
+
+    /// The part name, if it has been set, and the path of the open elements,
+    /// like `xl/worksheets/sheet3.xml at worksheet/sheetData/row[1042]`.
+    /// Elements that are preceded by siblings with the same name have their
+    /// 1-based position in brackets. The optimized cell writers, such as
+    /// [`xml_string_element()`](XMLWriter::xml_string_element), don't add to
+    /// the path.
+    ///
+    /// Write failures panic with the context in the message. It can also be
+    /// used to add context to other errors while a part is written.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.set_part_name("xl/worksheets/sheet1.xml");
+    ///
+    /// writer.xml_start_tag("worksheet", &vec![]);
+    /// writer.xml_start_tag("sheetData", &vec![]);
+    /// writer.xml_row_element(0, None, None, None, false, true);
+    /// writer.xml_row_element(1, None, None, None, false, false);
+    ///
+    /// assert_eq!(
+    ///     writer.context(),
+    ///     "xl/worksheets/sheet1.xml at worksheet/sheetData/row[2]"
+    /// );
+    /// ```
+    pub fn context(&self) -> String {
+        let path = self.elements[1..]
+            .iter()
+            .map(|element| {
+                if element.position > 1 {
+                    format!("{}[{}]", element.tag, element.position)
+                } else {
+                    element.tag.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        match (&self.part_name, path.is_empty()) {
+            (Some(part_name), true) => part_name.clone(),
+            (Some(part_name), false) => format!("{part_name} at {path}"),
+            (None, true) => "the top level".to_string(),
+            (None, false) => path,
+        }
+    }
+
+    /// The number of open elements. It is 0 at the top level and 1 inside
+    /// the root element. As with [`context()`](XMLWriter::context), the
+    /// elements of the optimized cell writers aren't counted.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// assert_eq!(writer.current_depth(), 0);
+    ///
+    /// writer.xml_start_tag("worksheet", &vec![]);
+    /// writer.xml_start_tag("sheetData", &vec![]);
+    /// assert_eq!(writer.current_depth(), 2);
+    /// ```
+    pub fn current_depth(&self) -> usize {
+        self.elements.len() - 1
+    }
+
+    /// The names of the open elements, from the root element to the
+    /// innermost one. It can be used to check that an element is only
+    /// written inside its expected parent while developing a part writer.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_start_tag("worksheet", &vec![]);
+    /// writer.xml_start_tag("sheetData", &vec![]);
+    /// writer.xml_start_tag("row", &vec![("r", "1")]);
+    ///
+    /// assert_eq!(writer.current_path(), ["worksheet", "sheetData", "row"]);
+    /// debug_assert_eq!(writer.current_path().last(), Some(&"row"));
+    /// ```
+    pub fn current_path(&self) -> Vec<&str> {
+        self.elements[1..]
+            .iter()
+            .map(|element| element.tag.as_str())
+            .collect()
+    }
+
+    /// Set the [`SanitizePolicy`] applied to strings written with
+    /// [`xml_si_element()`](XMLWriter::xml_si_element). The default is
+    /// `SanitizePolicy::None`.
+    /// ```
+    /// # use std::fs::File;
+    /// # use excel_xmlwriter::XMLWriter;
+    /// # use excel_xmlwriter::sanitize::SanitizePolicy;
+    /// #
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let xmlfile = File::create("test.xml")?;
+    /// # let mut writer = XMLWriter::new(&xmlfile);
+    /// #
+    /// writer.set_sanitize_policy(SanitizePolicy::Escape);
+    /// writer.xml_si_element("=HYPERLINK(\"http://example.com\")", &vec![]);
+    /// // Output: <si><t>'=HYPERLINK("http://example.com")</t></si>
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_sanitize_policy(&mut self, policy: SanitizePolicy) {
+        self.sanitize_policy = policy;
+    }
+
+    /// Get the [`SanitizePolicy`] of the writer.
+    pub fn sanitize_policy(&self) -> SanitizePolicy {
+        self.sanitize_policy
+    }
+
+    /// Register a namespace prefix and its URI. The registered namespaces
+    /// are written as `xmlns:prefix` attributes on the root element, in the
+    /// order that they were registered, after any `xmlns` attributes that are
+    /// passed with the root element. An empty prefix registers the default
+    /// namespace, which is written as `xmlns`. Registering a prefix again
+    /// replaces its URI.
+    ///
+    /// Once a namespace is registered, the prefixes of the element and
+    /// attribute names are checked, so they must be registered or declared
+    /// with an `xmlns:prefix` attribute on the element or one of its
+    /// ancestors. The `xml` prefix is always declared.
+    ///
+    /// # Panics
+    ///
+    /// With a namespace registered, writing an element or attribute with an
+    /// undeclared prefix panics.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.register_ns("", "http://schemas.openxmlformats.org/spreadsheetml/2006/main");
+    /// writer.register_ns(
+    ///     "r",
+    ///     "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+    /// );
+    ///
+    /// writer.xml_start_tag("worksheet", &vec![]);
+    /// writer.xml_empty_tag("drawing", &vec![("r:id", "rId1")]);
+    /// writer.xml_end_tag("worksheet");
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(writer.into_inner()).unwrap(),
+    ///     concat!(
+    ///         r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+    ///         r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+    ///         r#"<drawing r:id="rId1"/></worksheet>"#
+    ///     )
+    /// );
+    /// ```
+    pub fn register_ns(&mut self, prefix: &str, uri: &str) {
+        match self
+            .namespaces
+            .iter_mut()
+            .find(|(known, _)| known == prefix)
+        {
+            Some((_, known_uri)) => *known_uri = uri.to_string(),
+            None => self.namespaces.push((prefix.to_string(), uri.to_string())),
+        }
+    }
+
+    /// Turn on an assertion mode that checks that the attributes of each
+    /// element name are always written in the same relative order, and that
+    /// no attribute is repeated. The order is learned from the elements as
+    /// they are written, so attributes that are only sometimes present, like
+    /// `s` in `<c r="A1" s="1">`, are allowed.
+    ///
+    /// This is intended for tests, to catch attributes from a source with a
+    /// nondeterministic order, which makes the output hard to diff.
+    ///
+    /// # Panics
+    ///
+    /// With the check on, writing an element whose attributes are out of
+    /// order, or repeated, panics.
+    ///
+    /// ```should_panic
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.set_check_attribute_order(true);
+    ///
+    /// writer.xml_empty_tag("col", &vec![("min", "1"), ("max", "2")]);
+    /// writer.xml_empty_tag("col", &vec![("max", "4"), ("min", "3")]);
+    /// ```
+    pub fn set_check_attribute_order(&mut self, check: bool) {
+        self.attribute_orders = check.then(BTreeMap::new);
     }
 
     /// Write an XML file declaration.
@@ -72,11 +1241,39 @@ impl<'a> XMLWriter<'a> {
     /// # }
     ///
     pub fn xml_declaration(&mut self) {
-        writeln!(
-            &mut self.xmlfile,
-            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#
-        )
-        .expect("Couldn't write to file");
+        self.xml_declaration_with(&XmlDeclaration::new());
+    }
+
+    /// Write an XML file declaration with a different version, encoding or
+    /// `standalone` value, or without the trailing newline. See
+    /// [`XmlDeclaration`].
+    /// ```
+    /// # use excel_xmlwriter::{XMLWriter, XmlDeclaration};
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    ///
+    /// writer.xml_declaration_with(&XmlDeclaration::new().set_standalone(Some(false)));
+    /// // Output: <?xml version="1.0" encoding="UTF-8" standalone="no"?>
+    /// ```
+    pub fn xml_declaration_with(&mut self, declaration: &XmlDeclaration) {
+        write!(self.writer, r#"<?xml version="{}""#, declaration.version)
+            .unwrap_or_else(|error| self.write_failed(error));
+
+        if let Some(encoding) = &declaration.encoding {
+            write!(self.writer, r#" encoding="{encoding}""#)
+                .unwrap_or_else(|error| self.write_failed(error));
+        }
+
+        if let Some(standalone) = declaration.standalone {
+            let standalone = if standalone { "yes" } else { "no" };
+            write!(self.writer, r#" standalone="{standalone}""#)
+                .unwrap_or_else(|error| self.write_failed(error));
+        }
+
+        let end = if declaration.newline { "?>\n" } else { "?>" };
+        self.writer
+            .write_all(end.as_bytes())
+            .unwrap_or_else(|error| self.write_failed(error));
     }
 
     /// Write an XML start tag with attributes.
@@ -96,14 +1293,66 @@ impl<'a> XMLWriter<'a> {
     /// # }
     /// ```
     pub fn xml_start_tag(&mut self, tag: &str, attributes: &Vec<(&str, &str)>) {
-        let mut attribute_str = String::from("");
+        self.write_start_tag(tag, attributes);
+    }
+
+    /// Write an XML start tag with typed attribute values, so that values
+    /// that are known not to need escaping, such as cell references, are
+    /// written without being scanned. See [`AttributeValue`].
+    /// ```
+    /// # use excel_xmlwriter::{AttributeValue, XMLWriter};
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_start_tag_typed("row", &[("r", AttributeValue::Escaped("1"))]);
+    ///
+    /// assert_eq!(writer.into_inner(), br#"<row r="1">"#);
+    /// ```
+    pub fn xml_start_tag_typed(&mut self, tag: &str, attributes: &[(&str, AttributeValue)]) {
+        self.write_start_tag(tag, attributes);
+    }
+
+    // Write a start tag, recording the namespace prefixes that it declares
+    // if they are checked.
+    fn write_start_tag(&mut self, tag: &str, attributes: &[impl Attribute]) {
+        self.open_element(tag);
+        write!(self.writer, "<{tag}").unwrap_or_else(|error| self.write_failed(error));
+        self.write_attributes(tag, attributes);
+
+        if !self.namespaces.is_empty() {
+            let element = self.elements.last_mut().expect("the element is open");
+            element.prefixes = attributes
+                .iter()
+                .filter_map(|attribute| declared_prefix(attribute.name()))
+                .map(str::to_string)
+                .collect();
+        }
+
+        write!(self.writer, ">").unwrap_or_else(|error| self.write_failed(error));
+    }
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
+    /// Write an XML start tag without attributes. It is a fast path for
+    /// tags like `<sheetData>` that skips the attribute handling of
+    /// [`xml_start_tag()`](XMLWriter::xml_start_tag), unless namespaces
+    /// have been registered with [`register_ns()`](XMLWriter::register_ns).
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_start_tag_only("sheetData");
+    ///
+    /// assert_eq!(writer.into_inner(), b"<sheetData>");
+    /// ```
+    pub fn xml_start_tag_only(&mut self, tag: &str) {
+        // The registered namespaces are written on the root element and
+        // checked against the prefixes of the tags.
+        if !self.namespaces.is_empty() {
+            return self.xml_start_tag(tag, &vec![]);
         }
 
-        write!(&mut self.xmlfile, r"<{}{}>", tag, attribute_str).expect("Couldn't write to file");
+        self.open_element(tag);
+        self.write_raw(b"<");
+        self.write_raw(tag.as_bytes());
+        self.write_raw(b">");
     }
 
     /// Write an XML end tag.
@@ -123,7 +1372,8 @@ impl<'a> XMLWriter<'a> {
     /// # }
     /// ```
     pub fn xml_end_tag(&mut self, tag: &str) {
-        write!(&mut self.xmlfile, r"</{}>", tag).expect("Couldn't write to file");
+        write!(self.writer, r"</{}>", tag).unwrap_or_else(|error| self.write_failed(error));
+        self.close_element();
     }
 
     /// Write an empty XML tag with attributes.
@@ -143,14 +1393,50 @@ impl<'a> XMLWriter<'a> {
     /// # }
     /// ```
     pub fn xml_empty_tag(&mut self, tag: &str, attributes: &Vec<(&str, &str)>) {
-        let mut attribute_str = String::from("");
+        self.write_empty_tag(tag, attributes);
+    }
+
+    /// Write an empty XML tag with typed attribute values, like
+    /// [`xml_start_tag_typed()`](XMLWriter::xml_start_tag_typed).
+    /// ```
+    /// # use excel_xmlwriter::{AttributeValue, XMLWriter};
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_empty_tag_typed("col", &[("min", AttributeValue::Escaped("2"))]);
+    ///
+    /// assert_eq!(writer.into_inner(), br#"<col min="2"/>"#);
+    /// ```
+    pub fn xml_empty_tag_typed(&mut self, tag: &str, attributes: &[(&str, AttributeValue)]) {
+        self.write_empty_tag(tag, attributes);
+    }
+
+    // Write an empty tag.
+    fn write_empty_tag(&mut self, tag: &str, attributes: &[impl Attribute]) {
+        self.add_child(tag);
+        write!(self.writer, "<{tag}").unwrap_or_else(|error| self.write_failed(error));
+        self.write_attributes(tag, attributes);
+        write!(self.writer, "/>").unwrap_or_else(|error| self.write_failed(error));
+    }
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
+    /// Write an empty XML tag without attributes, like
+    /// [`xml_start_tag_only()`](XMLWriter::xml_start_tag_only).
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_empty_tag_only("sheetData");
+    ///
+    /// assert_eq!(writer.into_inner(), b"<sheetData/>");
+    /// ```
+    pub fn xml_empty_tag_only(&mut self, tag: &str) {
+        if !self.namespaces.is_empty() {
+            return self.xml_empty_tag(tag, &vec![]);
         }
 
-        write!(&mut self.xmlfile, r"<{}{}/>", tag, attribute_str).expect("Couldn't write to file");
+        self.add_child(tag);
+        self.write_raw(b"<");
+        self.write_raw(tag.as_bytes());
+        self.write_raw(b"/>");
     }
 
     /// Write an XML element containing data with optional attributes.
@@ -170,144 +1456,858 @@ impl<'a> XMLWriter<'a> {
     /// # }
     /// ```
     pub fn xml_data_element(&mut self, tag: &str, data: &str, attributes: &Vec<(&str, &str)>) {
-        let mut attribute_str = String::from("");
+        self.add_child(tag);
+        write!(self.writer, "<{tag}").unwrap_or_else(|error| self.write_failed(error));
+        self.write_attributes(tag, attributes);
+        self.write_raw(b">");
+        self.write_escaped_data(data);
+        write!(self.writer, "</{tag}>").unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    /// Write an XML element containing an unsigned integer with optional
+    /// attributes. The number is written directly, without being converted
+    /// to a string and escaped, for the counts and indexes in parts such as
+    /// `calcChain.xml`.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_data_element_u64("vt:i4", 3, &vec![]);
+    ///
+    /// assert_eq!(writer.into_inner(), b"<vt:i4>3</vt:i4>");
+    /// ```
+    pub fn xml_data_element_u64(&mut self, tag: &str, number: u64, attributes: &Vec<(&str, &str)>) {
+        self.write_number_element(tag, number, attributes);
+    }
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
+    /// Write an XML element containing a signed integer with optional
+    /// attributes, like
+    /// [`xml_data_element_u64()`](XMLWriter::xml_data_element_u64).
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_data_element_i64("c:idx", -1, &vec![]);
+    ///
+    /// assert_eq!(writer.into_inner(), b"<c:idx>-1</c:idx>");
+    /// ```
+    pub fn xml_data_element_i64(&mut self, tag: &str, number: i64, attributes: &Vec<(&str, &str)>) {
+        self.write_number_element(tag, number, attributes);
+    }
+
+    /// Write an XML element containing a number with optional attributes,
+    /// like [`xml_data_element_u64()`](XMLWriter::xml_data_element_u64). The
+    /// number is formatted in the same way as the values of
+    /// [`xml_number_element()`](XMLWriter::xml_number_element).
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_data_element_f64("c:v", 1.5, &vec![]);
+    ///
+    /// assert_eq!(writer.into_inner(), b"<c:v>1.5</c:v>");
+    /// ```
+    pub fn xml_data_element_f64(&mut self, tag: &str, number: f64, attributes: &Vec<(&str, &str)>) {
+        self.write_number_element(tag, number, attributes);
+    }
+
+    // Write an element containing a number, which doesn't need escaping.
+    fn write_number_element(
+        &mut self,
+        tag: &str,
+        number: impl std::fmt::Display,
+        attributes: &Vec<(&str, &str)>,
+    ) {
+        self.add_child(tag);
+        write!(self.writer, "<{tag}").unwrap_or_else(|error| self.write_failed(error));
+        self.write_attributes(tag, attributes);
+        write!(self.writer, ">{number}</{tag}>").unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    /// A [`FluentWriter`] view of the writer, for chaining the tag methods.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.fluent().start("a", &vec![]).data("b", "1", &vec![]).end("a");
+    ///
+    /// assert_eq!(writer.into_inner(), b"<a><b>1</b></a>");
+    /// ```
+    pub fn fluent(&mut self) -> FluentWriter<'_, W> {
+        FluentWriter { writer: self }
+    }
+
+    /// Optimized tag writer for `<row>` elements. This writes the row start
+    /// tag and the row attributes in a single call. The row should be closed
+    /// with `xml_end_tag("row")` after the cells have been written, unless it
+    /// is an empty row, in which case a self closing tag is written.
+    ///
+    /// The `row` and `spans` columns are zero-indexed. The `spans`, `height`
+    /// and `xf_index` attributes are omitted when they are `None`.
+    /// ```
+    /// # use std::fs::File;
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let xmlfile = File::create("test.xml")?;
+    /// # let mut writer = XMLWriter::new(&xmlfile);
+    /// #
+    /// writer.xml_row_element(0, Some((0, 2)), Some(30.0), None, false, false);
+    /// // Output: <row r="1" spans="1:3" ht="30" customHeight="1">
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn xml_row_element(
+        &mut self,
+        row: u32,
+        spans: Option<(u16, u16)>,
+        height: Option<f64>,
+        xf_index: Option<u32>,
+        hidden: bool,
+        empty: bool,
+    ) {
+        use std::fmt::Write as _;
+
+        if empty {
+            self.add_child("row");
+        } else {
+            self.open_element("row");
         }
 
-        write!(
-            &mut self.xmlfile,
-            r"<{}{}>{}</{}>",
-            tag,
-            attribute_str,
-            escape_data(data),
-            tag
-        )
-        .expect("Couldn't write to file");
+        // The row is formatted in the scratch buffer, which is reused for
+        // every row, and written with a single write.
+        let mut row_str = std::mem::take(&mut self.scratch);
+        row_str.clear();
+
+        // Writes to a String can't fail.
+        let _ = write!(row_str, r#"<row r="{}""#, row + 1);
+
+        if let Some((first_col, last_col)) = spans {
+            let _ = write!(row_str, r#" spans="{}:{}""#, first_col + 1, last_col + 1);
+        }
+
+        if let Some(xf_index) = xf_index {
+            let _ = write!(row_str, r#" s="{}" customFormat="1""#, xf_index);
+        }
+
+        if let Some(height) = height {
+            let _ = write!(row_str, r#" ht="{}""#, height);
+        }
+
+        if hidden {
+            row_str.push_str(r#" hidden="1""#);
+        }
+
+        if height.is_some() {
+            row_str.push_str(r#" customHeight="1""#);
+        }
+
+        row_str.push_str(if empty { "/>" } else { ">" });
+
+        self.writer
+            .write_all(row_str.as_bytes())
+            .unwrap_or_else(|error| self.write_failed(error));
+        self.scratch = row_str;
+    }
+
+    /// Optimized tag writer for `<c>` cell elements in the inner loop. The
+    /// cell type attribute, `t`, is written after the other attributes to
+    /// match the type of the value.
+    /// ```
+    /// # use excel_xmlwriter::{CellValue, XMLWriter};
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    ///
+    /// writer.xml_cell_element(&CellValue::Bool(true), &vec![("r", "A1")]);
+    /// writer.xml_cell_element(&CellValue::Number(1.5), &vec![("r", "B1"), ("s", "1")]);
+    ///
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     br#"<c r="A1" t="b"><v>1</v></c><c r="B1" s="1"><v>1.5</v></c>"#
+    /// );
+    /// ```
+    pub fn xml_cell_element(&mut self, value: &CellValue, attributes: &Vec<(&str, &str)>) {
+        match value {
+            CellValue::SharedString(index) => self.xml_string_element(*index, attributes),
+            CellValue::Number(number) => self.xml_number_element(*number, attributes),
+            CellValue::Bool(bool) => self.write_cell(attributes, "b", None, u8::from(*bool)),
+            CellValue::Formula { formula, result } => {
+                self.xml_formula_element(formula, *result, attributes)
+            }
+            CellValue::InlineStr(string) => self.xml_inline_string_element(string, attributes),
+            CellValue::Error(error) => self.write_cell(attributes, "e", None, escape_data(error)),
+        }
     }
 
     /// Optimized tag writer for `<c>` cell string elements in the inner loop.
     pub fn xml_string_element(&mut self, index: u32, attributes: &Vec<(&str, &str)>) {
-        let mut attribute_str = String::from("");
+        self.write_cell(attributes, "s", None, index);
+    }
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
-        }
+    /// Optimized tag writer for `<c>` cell number elements in the inner loop.
+    /// Numbers are the default cell type, so there is no `t` attribute.
+    pub fn xml_number_element(&mut self, number: f64, attributes: &Vec<(&str, &str)>) {
+        self.write_cell(attributes, "", None, number);
+    }
 
+    /// Optimized tag writer for `<c>` cell formula elements in the inner loop.
+    pub fn xml_formula_element(
+        &mut self,
+        formula: &str,
+        result: f64,
+        attributes: &Vec<(&str, &str)>,
+    ) {
+        self.write_cell(attributes, "", Some(formula), result);
+    }
+
+    /// Optimized tag writer for the first `<c>` cell of a shared formula,
+    /// which has the formula, the range of the cells that share it, and the
+    /// shared index, `si`, that the other cells refer to with
+    /// [`xml_shared_formula_ref_element()`](XMLWriter::xml_shared_formula_ref_element).
+    /// The formula is relative to the first cell, and Excel adjusts it for
+    /// the other cells in the range, which saves writing the formula for
+    /// each of them.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    ///
+    /// writer.xml_shared_formula_element("A1*2", "B1:B3", 0, 2.0, &vec![("r", "B1")]);
+    /// writer.xml_shared_formula_ref_element(0, 4.0, &vec![("r", "B2")]);
+    /// writer.xml_shared_formula_ref_element(0, 6.0, &vec![("r", "B3")]);
+    ///
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     concat!(
+    ///         r#"<c r="B1"><f t="shared" ref="B1:B3" si="0">A1*2</f><v>2</v></c>"#,
+    ///         r#"<c r="B2"><f t="shared" si="0"/><v>4</v></c>"#,
+    ///         r#"<c r="B3"><f t="shared" si="0"/><v>6</v></c>"#,
+    ///     )
+    ///     .as_bytes()
+    /// );
+    /// ```
+    pub fn xml_shared_formula_element(
+        &mut self,
+        formula: &str,
+        range: &str,
+        index: u32,
+        result: f64,
+        attributes: &Vec<(&str, &str)>,
+    ) {
+        self.write_cell_start(attributes, "");
+        self.write_raw(br#"<f t="shared" ref=""#);
+        self.write_escaped_attribute(range);
+        write!(self.writer, r#"" si="{index}">"#).unwrap_or_else(|error| self.write_failed(error));
+        self.write_escaped_data(formula);
+        write!(self.writer, "</f><v>{result}</v></c>")
+            .unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    /// Optimized tag writer for the other `<c>` cells of a shared formula,
+    /// which refer to the formula of
+    /// [`xml_shared_formula_element()`](XMLWriter::xml_shared_formula_element)
+    /// by its shared index.
+    pub fn xml_shared_formula_ref_element(
+        &mut self,
+        index: u32,
+        result: f64,
+        attributes: &Vec<(&str, &str)>,
+    ) {
+        self.write_cell_start(attributes, "");
         write!(
-            &mut self.xmlfile,
-            r#"<c{} t="s"><v>{}</v></c>"#,
-            attribute_str, index
+            self.writer,
+            r#"<f t="shared" si="{index}"/><v>{result}</v></c>"#
         )
-        .expect("Couldn't write to file");
+        .unwrap_or_else(|error| self.write_failed(error));
     }
 
-    /// Optimized tag writer for `<c>` cell number elements in the inner loop.
-    pub fn xml_number_element(&mut self, number: f64, attributes: &Vec<(&str, &str)>) {
-        // TODO: make this generic with the previous function.
-        let mut attribute_str = String::from("");
+    /// Optimized tag writer for `<c>` cell inline string elements in the
+    /// inner loop. Leading and trailing whitespace is preserved.
+    pub fn xml_inline_string_element(&mut self, string: &str, attributes: &Vec<(&str, &str)>) {
+        let string = sanitize_string(string, self.sanitize_policy);
+
+        let space =
+            if string.starts_with(char::is_whitespace) || string.ends_with(char::is_whitespace) {
+                r#" xml:space="preserve""#
+            } else {
+                ""
+            };
+
+        self.write_cell_start(attributes, "inlineStr");
+        write!(self.writer, "<is><t{space}>").unwrap_or_else(|error| self.write_failed(error));
+        self.write_escaped_data(&string);
+        self.write_raw(b"</t></is></c>");
+    }
+
+    /// Optimized tag writer for shared strings `<si>` elements.
+    pub fn xml_si_element(&mut self, string: &str, attributes: &Vec<(&str, &str)>) {
+        self.element_count += 1;
+        let string = sanitize_string(string, self.sanitize_policy);
+
+        write!(self.writer, "<si><t").unwrap_or_else(|error| self.write_failed(error));
+        self.write_attributes("t", attributes);
+        self.write_raw(b">");
+        self.write_escaped_data(&string);
+        self.write_raw(b"</t></si>");
+    }
+
+    // Write a <c> cell element with an optional formula and a value, which
+    // is already escaped.
+    fn write_cell(
+        &mut self,
+        attributes: &Vec<(&str, &str)>,
+        cell_type: &str,
+        formula: Option<&str>,
+        value: impl std::fmt::Display,
+    ) {
+        self.write_cell_start(attributes, cell_type);
+
+        if let Some(formula) = formula {
+            write!(self.writer, "<f>{}</f>", escape_data(formula))
+                .unwrap_or_else(|error| self.write_failed(error));
+        }
+
+        write!(self.writer, "<v>{value}</v></c>").unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    // Write the start tag of a <c> cell element, with the cell type last if
+    // it isn't the default number type.
+    fn write_cell_start(&mut self, attributes: &Vec<(&str, &str)>, cell_type: &str) {
+        self.element_count += 1;
+
+        write!(self.writer, "<c").unwrap_or_else(|error| self.write_failed(error));
+
+        // The cell type comes before the cell and value metadata attributes
+        // of dynamic arrays and rich values, in the schema order.
+        let metadata_at = attributes
+            .iter()
+            .position(|(name, _)| matches!(*name, "cm" | "vm" | "ph"));
+
+        if let (Some(index), false) = (metadata_at, cell_type.is_empty()) {
+            let mut attributes = attributes.clone();
+            attributes.insert(index, ("t", cell_type));
+            self.write_attributes("c", &attributes);
+            return self.write_raw(b">");
+        }
+
+        self.write_attributes("c", attributes);
+
+        if cell_type.is_empty() {
+            write!(self.writer, ">")
+        } else {
+            write!(self.writer, r#" t="{cell_type}">"#)
+        }
+        .unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    // Write the attributes of an element. The values are escaped directly to
+    // the writer, in chunks between the characters that need to be escaped,
+    // so long values such as list formulas aren't copied into intermediate
+    // strings.
+    fn write_attributes(&mut self, tag: &str, attributes: &[impl Attribute]) {
+        self.check_attribute_order(tag, attributes);
+
+        // The registered namespaces are written on the root element, after
+        // the namespaces that are passed with it. Elements such as cells,
+        // which aren't added with add_child(), are never the root.
+        let at_root = std::mem::take(&mut self.at_root);
+        let mut namespaces_at = None;
+        if !self.namespaces.is_empty() {
+            self.check_prefixes(tag, attributes);
+
+            if at_root {
+                let position = attributes
+                    .iter()
+                    .position(|attribute| !is_namespace_declaration(attribute.name()));
+                namespaces_at = Some(position.unwrap_or(attributes.len()));
+            }
+        }
+
+        for (index, attribute) in attributes.iter().enumerate() {
+            if namespaces_at == Some(index) {
+                self.write_namespaces(attributes);
+            }
+
+            self.write_attribute(attribute.name(), attribute.value());
+        }
+
+        if namespaces_at == Some(attributes.len()) {
+            self.write_namespaces(attributes);
+        }
+    }
+
+    // Write the registered namespaces that aren't already declared in the
+    // attributes of the root element.
+    fn write_namespaces(&mut self, attributes: &[impl Attribute]) {
+        let namespaces = std::mem::take(&mut self.namespaces);
+
+        for (prefix, uri) in &namespaces {
+            let declared = attributes.iter().any(|attribute| {
+                let name = attribute.name();
+                if prefix.is_empty() {
+                    name == "xmlns"
+                } else {
+                    declared_prefix(name) == Some(prefix)
+                }
+            });
+
+            if declared {
+                continue;
+            }
+
+            self.write_raw(b" xmlns");
+            if !prefix.is_empty() {
+                self.write_raw(b":");
+                self.write_raw(prefix.as_bytes());
+            }
+            self.write_raw(b"=\"");
+            self.write_escaped_attribute(uri);
+            self.write_raw(b"\"");
+        }
+
+        self.namespaces = namespaces;
+    }
+
+    // Write an attribute. The pieces are written directly to the writer,
+    // which is buffered by the callers that need it, rather than formatted
+    // into a string first. Values that are already escaped aren't scanned.
+    fn write_attribute(&mut self, name: &str, value: AttributeValue) {
+        self.write_raw(b" ");
+        self.write_raw(name.as_bytes());
+        self.write_raw(b"=\"");
+        match value {
+            AttributeValue::Text(value) => self.write_escaped_attribute(value),
+            AttributeValue::Escaped(value) => {
+                debug_assert!(
+                    find_special(value.as_bytes(), &ATTRIBUTE_SPECIALS).is_none(),
+                    "the attribute value '{value}' of '{name}' needs escaping"
+                );
+                self.write_raw(value.as_bytes());
+            }
+        }
+        self.write_raw(b"\"");
+    }
+
+    // Write data with the same escaping as escape_data(), without an escaped
+    // copy. The unescaped spans and the entities are gathered as slices of
+    // the data and written with write_vectored(), in batches of up to
+    // ESCAPE_SLICES slices, so the memory used doesn't grow with the length
+    // of the data.
+    fn write_escaped_data(&mut self, data: &str) {
+        let bytes = data.as_bytes();
+        let mut slices = [IoSlice::new(&[]); ESCAPE_SLICES];
+        let mut count = 0;
+        let mut start = 0;
+
+        while let Some(offset) = find_special(&bytes[start..], &DATA_SPECIALS) {
+            if count + 2 > ESCAPE_SLICES {
+                self.write_slices(&mut slices[..count]);
+                count = 0;
+            }
+
+            let index = start + offset;
+            slices[count] = IoSlice::new(&bytes[start..index]);
+            slices[count + 1] = IoSlice::new(data_entity(bytes[index]).as_bytes());
+            count += 2;
+            start = index + 1;
+        }
+
+        if count == ESCAPE_SLICES {
+            self.write_slices(&mut slices[..count]);
+            count = 0;
+        }
+
+        slices[count] = IoSlice::new(&bytes[start..]);
+        self.write_slices(&mut slices[..=count]);
+    }
+
+    // Write all of the slices, like the unstable Write::write_all_vectored().
+    fn write_slices(&mut self, mut slices: &mut [IoSlice<'_>]) {
+        IoSlice::advance_slices(&mut slices, 0);
+
+        while !slices.is_empty() {
+            match self.writer.write_vectored(slices) {
+                Ok(0) => self.write_failed(io::ErrorKind::WriteZero.into()),
+                Ok(written) => IoSlice::advance_slices(&mut slices, written),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => self.write_failed(error),
+            }
+        }
+    }
+
+    // Write bytes that are already escaped, or don't need to be.
+    fn write_raw(&mut self, bytes: &[u8]) {
+        self.writer
+            .write_all(bytes)
+            .unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    // Add a child element to the latest open element and return its position
+    // among the consecutive siblings with the same name.
+    fn add_child(&mut self, tag: &str) -> usize {
+        self.element_count += 1;
+        self.at_root = self.elements.len() == 1;
+
+        let parent = self
+            .elements
+            .last_mut()
+            .expect("the document is always open");
+
+        match &mut parent.last_child {
+            Some((name, position)) if name == tag => {
+                *position += 1;
+                *position
+            }
+            last_child => {
+                *last_child = Some((tag.to_string(), 1));
+                1
+            }
+        }
+    }
+
+    // Add an element to the path of open elements.
+    fn open_element(&mut self, tag: &str) {
+        let position = self.add_child(tag);
+
+        self.elements.push(OpenElement {
+            tag: tag.to_string(),
+            position,
+            last_child: None,
+            prefixes: vec![],
+        });
+    }
+
+    // Remove the latest element from the path. End tags aren't checked
+    // against the start tags, so the document itself is never removed.
+    fn close_element(&mut self) {
+        if self.elements.len() > 1 {
+            self.elements.pop();
+        }
+    }
+
+    // Panic with the context of a failed write.
+    fn write_failed(&self, error: io::Error) -> ! {
+        panic!("Couldn't write to file: {error}, in {}", self.context())
+    }
+
+    // Check the attributes against the order seen in previous elements with
+    // the same name, if the check is on. New attribute names are merged into
+    // the order after the previous attribute of the element.
+    fn check_attribute_order(&mut self, tag: &str, attributes: &[impl Attribute]) {
+        let Some(attribute_orders) = &mut self.attribute_orders else {
+            return;
+        };
+
+        let order = attribute_orders.entry(tag.to_string()).or_default();
+        let mut position = 0;
+
+        for (i, attribute) in attributes.iter().enumerate() {
+            let name = attribute.name();
+            match order.iter().position(|known| known == name) {
+                Some(index) if index >= position => position = index + 1,
+                Some(_) => panic!(
+                    "attribute '{name}' of <{tag}> is repeated or out of order after '{}', expected the order: {}",
+                    attributes[i - 1].name(),
+                    order.join(", ")
+                ),
+                None => {
+                    order.insert(position, name.to_string());
+                    position += 1;
+                }
+            }
+        }
+    }
+
+    // Check that the prefixes of an element and its attributes are
+    // registered, or declared on the element or one of its ancestors.
+    fn check_prefixes(&self, tag: &str, attributes: &[impl Attribute]) {
+        let is_declared = |prefix: &str| {
+            prefix == "xml"
+                || prefix == "xmlns"
+                || self.namespaces.iter().any(|(known, _)| known == prefix)
+                || attributes
+                    .iter()
+                    .any(|attribute| declared_prefix(attribute.name()) == Some(prefix))
+                || self
+                    .elements
+                    .iter()
+                    .any(|element| element.prefixes.iter().any(|known| known == prefix))
+        };
+
+        let names = std::iter::once(tag).chain(attributes.iter().map(Attribute::name));
+
+        for name in names {
+            if let Some((prefix, _)) = name.split_once(':') {
+                if !is_declared(prefix) {
+                    panic!(
+                        "the namespace prefix '{prefix}' of '{name}' in <{tag}> isn't registered or declared, in {}",
+                        self.context()
+                    );
+                }
+            }
+        }
+    }
+
+    // Write an attribute value with the same escaping as escape_attributes(),
+    // without copying it.
+    fn write_escaped_attribute(&mut self, value: &str) {
+        write_escaped_attribute(value, |piece| self.writer.write_all(piece.as_bytes()))
+            .unwrap_or_else(|error| self.write_failed(error));
+    }
+
+    /// Optimized tag writer for shared strings `<si>` rich string elements. The
+    /// runs are pairs of optional font properties and text, such as the
+    /// [`runs()`](rich_string::RichString::runs) of a rich string, which are
+    /// escaped and written as `<r>` elements.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// # use excel_xmlwriter::rich_string::{RichString, RunProperties};
+    /// #
+    /// let mut writer = XMLWriter::new(vec![]);
+    ///
+    /// let italic = RunProperties::new().set_italic();
+    /// writer.xml_rich_si_element(&[(None, "Some "), (Some(&italic), "text")]);
+    ///
+    /// let rich_string = RichString::new()
+    ///     .add_run(None, "a < ")
+    ///     .add_run(Some(&italic), "b");
+    /// writer.xml_rich_si_element(&rich_string.runs());
+    /// ```
+    pub fn xml_rich_si_element(&mut self, runs: &[(Option<&RunProperties>, &str)]) {
+        self.element_count += 1;
+
+        let mut xml = std::mem::take(&mut self.scratch);
+        xml.clear();
+        xml.push_str("<si>");
+
+        for (properties, text) in runs {
+            let text = sanitize_string(text, self.sanitize_policy);
+            rich_string::write_run(&mut xml, *properties, &text);
+        }
+
+        xml.push_str("</si>");
+
+        self.writer
+            .write_all(xml.as_bytes())
+            .unwrap_or_else(|error| self.write_failed(error));
+        self.scratch = xml;
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Seek> XMLWriter<W> {
+    /// Create a new XMLWriter struct for a seekable writer, such as a
+    /// [`File`](std::fs::File), whose placeholders from
+    /// [`reserve_placeholder()`](XMLWriter::reserve_placeholder) are patched
+    /// by seeking back to them instead of holding the output in memory. The
+    /// XML is written from the current position of the writer.
+    ///
+    /// # Errors
+    ///
+    /// An error if the position of the writer can't be read.
+    pub fn new_seekable(mut writer: W) -> io::Result<XMLWriter<W>> {
+        let stream_start = writer.stream_position()?;
+
+        let mut xml_writer = XMLWriter::new(writer);
+        xml_writer.seek_patch = Some((seek_patch::<W>, stream_start));
+
+        Ok(xml_writer)
+    }
+}
+
+// Overwrite the bytes at a position in a writer and return to the end.
+#[cfg(feature = "std")]
+fn seek_patch<W: Write + Seek>(writer: &mut W, position: u64, data: &[u8]) -> io::Result<()> {
+    let end = writer.stream_position()?;
+    writer.seek(SeekFrom::Start(position))?;
+    writer.write_all(data)?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+impl XMLWriter<io::Sink> {
+    /// Create a writer that escapes and formats the XML as usual but
+    /// discards it, only counting the bytes in its
+    /// [`stats()`](XMLWriter::stats). A part can be written once in a dry
+    /// run to get its exact size, such as for the header of a zip entry that
+    /// is streamed to a writer that can't seek, and then written again to
+    /// the real writer.
+    /// ```
+    /// # use excel_xmlwriter::XMLWriter;
+    /// #
+    /// fn write_part<W: std::io::Write>(writer: &mut XMLWriter<W>) {
+    ///     writer.xml_declaration();
+    ///     writer.xml_data_element("foo", "bar & baz", &vec![("a", "<1>")]);
+    /// }
+    ///
+    /// let mut dry_run = XMLWriter::dry_run();
+    /// write_part(&mut dry_run);
+    ///
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// write_part(&mut writer);
+    ///
+    /// assert_eq!(dry_run.stats().bytes(), writer.into_inner().len() as u64);
+    /// ```
+    pub fn dry_run() -> XMLWriter<io::Sink> {
+        XMLWriter::new(io::sink())
+    }
+}
+
+// Escape XML characters in attributes. Most values, such as cell references
+// and style ids, don't have any characters to escape and are borrowed
+// without being copied.
+#[cfg(feature = "std")]
+pub(crate) fn escape_attributes(attribute: &str) -> Cow<'_, str> {
+    escape(attribute, &ATTRIBUTE_SPECIALS, attribute_entity)
+}
+
+// Write an attribute value in pieces, the unescaped text between the
+// entities and the entities, so that it can be written directly to a writer
+// without an intermediate string.
+pub(crate) fn write_escaped_attribute<E>(
+    value: &str,
+    mut write: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    let bytes = value.as_bytes();
+    let mut start = 0;
+
+    while let Some(offset) = find_special(&bytes[start..], &ATTRIBUTE_SPECIALS) {
+        let index = start + offset;
+        write(&value[start..index])?;
+        write(attribute_entity(bytes[index]))?;
+        start = index + 1;
+    }
+
+    write(&value[start..])
+}
+
+// Escape XML characters in data sections of tags.  Note, this
+// is different from escape_attributes() because double quotes
+// and newline are not escaped by Excel. Data without any characters to
+// escape is borrowed.
+pub(crate) fn escape_data(data: &str) -> Cow<'_, str> {
+    escape(data, &DATA_SPECIALS, data_entity)
+}
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
-        }
+// Check if an attribute declares a namespace, with `xmlns` or `xmlns:prefix`.
+#[cfg(feature = "std")]
+fn is_namespace_declaration(name: &str) -> bool {
+    name == "xmlns" || name.starts_with("xmlns:")
+}
 
-        write!(
-            &mut self.xmlfile,
-            r#"<c{} t="s"><v>{}</v></c>"#,
-            attribute_str, number
-        )
-        .expect("Couldn't write to file");
-    }
+// The namespace prefix declared by an `xmlns:prefix` attribute.
+#[cfg(feature = "std")]
+fn declared_prefix(name: &str) -> Option<&str> {
+    name.strip_prefix("xmlns:")
+}
 
-    /// Optimized tag writer for `<c>` cell formula elements in the inner loop.
-    pub fn xml_formula_element(
-        &mut self,
-        formula: &str,
-        result: f64,
-        attributes: &Vec<(&str, &str)>,
-    ) {
-        let mut attribute_str = String::from("");
+// The number of slices in each vectored write of escaped data.
+#[cfg(feature = "std")]
+const ESCAPE_SLICES: usize = 64;
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
-        }
+// The bytes that are escaped in attribute values and element data.
+const ATTRIBUTE_SPECIALS: [u8; 5] = [b'&', b'"', b'<', b'>', b'\n'];
+const DATA_SPECIALS: [u8; 3] = [b'&', b'<', b'>'];
 
-        write!(
-            &mut self.xmlfile,
-            r#"<c{}><f>{}</f><v>{}</v></c>"#,
-            attribute_str,
-            escape_data(formula),
-            result
-        )
-        .expect("Couldn't write to file");
+// The entity that replaces a byte in an attribute value.
+fn attribute_entity(byte: u8) -> &'static str {
+    match byte {
+        b'"' => "&quot;",
+        b'\n' => "&#xA;",
+        _ => data_entity(byte),
     }
+}
 
-    /// Optimized tag writer for shared strings `<si>` elements.
-    pub fn xml_si_element(&mut self, string: &str, attributes: &Vec<(&str, &str)>) {
-        let mut attribute_str = String::from("");
+// The entity that replaces a byte in element data.
+fn data_entity(byte: u8) -> &'static str {
+    match byte {
+        b'&' => "&amp;",
+        b'<' => "&lt;",
+        _ => "&gt;",
+    }
+}
 
-        for attribute in attributes {
-            let pair = format!(r#" {}="{}""#, attribute.0, escape_attributes(attribute.1));
-            attribute_str.push_str(&pair);
+// Escape a string in a single pass. The string is scanned for the first byte
+// that needs to be escaped, and borrowed if there isn't one. Otherwise the
+// unescaped text between the entities is copied into a single allocation.
+// The escaped bytes are all ASCII, so the slices are on char boundaries.
+fn escape<'a, const N: usize>(
+    value: &'a str,
+    specials: &[u8; N],
+    entity: impl Fn(u8) -> &'static str,
+) -> Cow<'a, str> {
+    let bytes = value.as_bytes();
+
+    let Some(mut index) = find_special(bytes, specials) else {
+        return Cow::Borrowed(value);
+    };
+
+    let mut escaped = String::with_capacity(value.len() + value.len() / 8 + 8);
+    let mut start = 0;
+
+    loop {
+        escaped.push_str(&value[start..index]);
+        escaped.push_str(entity(bytes[index]));
+        start = index + 1;
+
+        match find_special(&bytes[start..], specials) {
+            Some(offset) => index = start + offset,
+            None => break,
         }
-
-        write!(
-            &mut self.xmlfile,
-            r#"<si><t{}>{}</t></si>"#,
-            attribute_str,
-            escape_data(string)
-        )
-        .expect("Couldn't write to file");
     }
+    escaped.push_str(&value[start..]);
 
-    /// Optimized tag writer for shared strings <si> rich string elements.
-    pub fn xml_rich_si_element(&mut self, string: &str) {
-        write!(&mut self.xmlfile, r#"<si>{}</si>"#, string).expect("Couldn't write to file");
-    }
+    Cow::Owned(escaped)
 }
 
-// Escape XML characters in attributes.
-fn escape_attributes(attribute: &str) -> String {
-    attribute
-        .replace('&', "&amp;")
-        .replace('"', "&quot;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('\n', "&#xA;")
-}
+// Find the first of the special bytes, like memchr(). The bytes are checked
+// 8 at a time, as a u64 word, and only a word with one of the special bytes
+// is searched byte by byte.
+fn find_special<const N: usize>(bytes: &[u8], specials: &[u8; N]) -> Option<usize> {
+    const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
 
-// Escape XML characters in data sections of tags.  Note, this
-// is different from escape_attributes() because double quotes
-// and newline are not escaped by Excel.
-fn escape_data(attribute: &str) -> String {
-    attribute
-        .replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
+    // A word has a zero byte if subtracting 1 from each byte borrows into
+    // the high bit of a byte that didn't have it set.
+    let has_byte = |word: u64, byte: u8| {
+        let word = word ^ (LOW_BITS * byte as u64);
+        word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS != 0
+    };
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut offset = 0;
+
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+
+        if specials.iter().any(|byte| has_byte(word, *byte)) {
+            break;
+        }
+        offset += 8;
+    }
+
+    bytes[offset..]
+        .iter()
+        .position(|byte| specials.contains(byte))
+        .map(|position| offset + position)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
 
-    use super::XMLWriter;
+    use super::{AttributeValue, CellValue, XMLWriter, XmlDeclaration};
+    use crate::rich_string::RunProperties;
+    use crate::sanitize::SanitizePolicy;
+    use crate::testing::read_xmlfile_data;
+    use std::borrow::Cow;
     use std::fs::File;
-    use std::io::{Read, Seek, SeekFrom};
+    use std::io::{self, Write};
     use tempfile::tempfile;
 
     use pretty_assertions::assert_eq;
 
-    fn read_xmlfile_data(tempfile: &mut File) -> String {
-        let mut got = String::new();
-        tempfile.seek(SeekFrom::Start(0)).unwrap();
-        tempfile.read_to_string(&mut got).unwrap();
-        got
-    }
-
     #[test]
     fn test_xml_declaration() {
         let expected = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
@@ -321,6 +2321,28 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_xml_declaration_with() {
+        let mut writer = XMLWriter::new(vec![]);
+
+        writer.xml_declaration_with(&XmlDeclaration::new());
+        writer.xml_declaration_with(
+            &XmlDeclaration::new()
+                .set_version("1.1")
+                .set_encoding(None)
+                .set_standalone(Some(false))
+                .set_newline(false),
+        );
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<?xml version=\"1.1\" standalone=\"no\"?>"
+        );
+
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn test_xml_start_tag() {
         let expected = "<foo>";
@@ -432,6 +2454,79 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_xml_row_element() {
+        let expected = r#"<row r="1">"#;
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.xml_row_element(0, None, None, None, false, false);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_row_element_with_spans() {
+        let expected = r#"<row r="3" spans="2:5">"#;
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.xml_row_element(2, Some((1, 4)), None, None, false, false);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_row_element_with_all_attributes() {
+        let expected =
+            r#"<row r="1" spans="1:1" s="1" customFormat="1" ht="30" hidden="1" customHeight="1">"#;
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.xml_row_element(0, Some((0, 0)), Some(30.0), Some(1), true, false);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_row_element_empty() {
+        let expected = r#"<row r="1048576" ht="7.5" hidden="1" customHeight="1"/>"#;
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.xml_row_element(1_048_575, None, Some(7.5), None, true, true);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_row_elements_reuse_buffer() {
+        // A short row after a long one, and a rich string after both, which
+        // are formatted in the same scratch buffer.
+        let expected = concat!(
+            r#"<row r="1" spans="1:1" s="1" customFormat="1" ht="30" hidden="1" customHeight="1">"#,
+            r#"</row><row r="2"/><si><r><t>a</t></r></si>"#,
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+
+        writer.xml_row_element(0, Some((0, 0)), Some(30.0), Some(1), true, false);
+        writer.xml_end_tag("row");
+        writer.xml_row_element(1, None, None, None, false, true);
+        writer.xml_rich_si_element(&[(None, "a")]);
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn test_xml_string_element() {
         let expected = r#"<c span="8" t="s"><v>99</v></c>"#;
@@ -448,7 +2543,7 @@ mod tests {
 
     #[test]
     fn test_xml_number_element() {
-        let expected = r#"<c span="8" t="s"><v>99</v></c>"#;
+        let expected = r#"<c span="8"><v>99</v></c>"#;
         let attributes = vec![("span", "8")];
 
         let mut tempfile = tempfile().unwrap();
@@ -460,6 +2555,26 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_xml_number_element_excel_output() {
+        // The number cells of an Excel file with a formatted cell, a
+        // negative number and a fraction.
+        let expected = concat!(
+            r#"<c r="A1"><v>123</v></c><c r="B1" s="1"><v>-4.5</v></c>"#,
+            r#"<c r="C1"><v>0.1</v></c>"#,
+        );
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.xml_number_element(123.0, &vec![("r", "A1")]);
+        writer.xml_number_element(-4.5, &vec![("r", "B1"), ("s", "1")]);
+        writer.xml_number_element(0.1, &vec![("r", "C1")]);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn test_xml_formula_element() {
         let expected = r#"<c span="8"><f>1+2</f><v>3</v></c>"#;
@@ -474,6 +2589,76 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_xml_cell_element_metadata() {
+        let expected = concat!(
+            r#"<c r="A1" s="1" t="s" vm="1"><v>0</v></c>"#,
+            r#"<c r="B1" t="str" cm="1" vm="2"><v>a</v></c>"#,
+            r#"<c r="C1" cm="1"><f>A1</f><v>0</v></c>"#,
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_check_attribute_order(true);
+        writer.xml_string_element(0, &vec![("r", "A1"), ("s", "1"), ("vm", "1")]);
+        writer.write_cell_start(&vec![("r", "B1"), ("cm", "1"), ("vm", "2")], "str");
+        writer.write_raw(b"<v>a</v></c>");
+        writer.xml_formula_element("A1", 0.0, &vec![("r", "C1"), ("cm", "1")]);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_xml_shared_formula_element() {
+        let expected = concat!(
+            r#"<c r="C1" s="1"><f t="shared" ref="C1:C2" si="3">A1&amp;B1</f><v>0</v></c>"#,
+            r#"<c r="C2" s="1"><f t="shared" si="3"/><v>1.5</v></c>"#,
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_shared_formula_element("A1&B1", "C1:C2", 3, 0.0, &vec![("r", "C1"), ("s", "1")]);
+        writer.xml_shared_formula_ref_element(3, 1.5, &vec![("r", "C2"), ("s", "1")]);
+
+        assert_eq!(writer.stats().elements(), 2);
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_xml_cell_element() {
+        let expected = concat!(
+            r#"<c r="A1" t="s"><v>7</v></c><c r="B1" s="2"><v>-1.25</v></c>"#,
+            r#"<c r="C1" t="b"><v>0</v></c><c r="D1"><f>A1&amp;B1</f><v>0</v></c>"#,
+            r#"<c r="E1" t="inlineStr"><is><t>&lt;b&gt;</t></is></c>"#,
+            r#"<c r="F1" t="e"><v>#DIV/0!</v></c>"#,
+        );
+
+        let values = [
+            CellValue::SharedString(7),
+            CellValue::Number(-1.25),
+            CellValue::Bool(false),
+            CellValue::Formula {
+                formula: "A1&B1".to_string(),
+                result: 0.0,
+            },
+            CellValue::InlineStr("<b>".to_string()),
+            CellValue::Error("#DIV/0!".to_string()),
+        ];
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        for (col, value) in values.iter().enumerate() {
+            let cell_ref = format!("{}1", (b'A' + col as u8) as char);
+            let mut attributes = vec![("r", cell_ref.as_str())];
+            if col == 1 {
+                attributes.push(("s", "2"));
+            }
+            writer.xml_cell_element(value, &attributes);
+        }
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
     #[test]
     fn test_xml_si_element() {
         let expected = r#"<si><t span="8">foo</t></si>"#;
@@ -488,16 +2673,653 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_xml_si_element_with_sanitize_policy() {
+        let expected = r#"<si><t>'=1+2</t></si><si><t>a_x0001_b</t></si><si><t>1+2</t></si>"#;
+        let attributes = vec![];
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.set_sanitize_policy(SanitizePolicy::Escape);
+        writer.xml_si_element("=1+2", &attributes);
+        writer.xml_si_element("a\u{1}b", &attributes);
+
+        writer.set_sanitize_policy(SanitizePolicy::Strip);
+        writer.xml_si_element("=1+2", &attributes);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_typed_attributes() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_check_attribute_order(true);
+
+        writer.xml_start_tag_typed(
+            "hyperlinks",
+            &[("xmlns:r", AttributeValue::Text("urn:r&r"))],
+        );
+        writer.xml_empty_tag_typed(
+            "hyperlink",
+            &[
+                ("ref", AttributeValue::Escaped("A1")),
+                ("display", "<Home>".into()),
+            ],
+        );
+        writer.xml_end_tag("hyperlinks");
+
+        let expected = concat!(
+            r#"<hyperlinks xmlns:r="urn:r&amp;r"><hyperlink ref="A1" "#,
+            r#"display="&lt;Home&gt;"/></hyperlinks>"#,
+        );
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "needs escaping")]
+    fn test_escaped_attribute_needs_escaping() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_empty_tag_typed("c", &[("r", AttributeValue::Escaped("A&1"))]);
+    }
+
+    #[test]
+    fn test_check_attribute_order() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_check_attribute_order(true);
+
+        // Optional attributes are merged into the learned order.
+        writer.xml_empty_tag("c", &vec![("r", "A1"), ("t", "s")]);
+        writer.xml_empty_tag("c", &vec![("r", "A2"), ("s", "1"), ("t", "s")]);
+        writer.xml_string_element(0, &vec![("r", "A3"), ("s", "1")]);
+        writer.xml_empty_tag("col", &vec![("max", "1"), ("min", "1")]);
+
+        let result = std::panic::catch_unwind(move || {
+            writer.xml_empty_tag("c", &vec![("s", "1"), ("r", "A4")]);
+        });
+        assert!(result.is_err());
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_check_attribute_order(true);
+        let result = std::panic::catch_unwind(move || {
+            writer.xml_empty_tag("c", &vec![("r", "A1"), ("r", "A1")]);
+        });
+        assert!(result.is_err());
+
+        // The check is off by default.
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_empty_tag("c", &vec![("r", "A1"), ("s", "1")]);
+        writer.xml_empty_tag("c", &vec![("s", "1"), ("r", "A2")]);
+    }
+
+    #[test]
+    fn test_context() {
+        let mut writer = XMLWriter::new(vec![]);
+        assert_eq!(writer.context(), "the top level");
+
+        writer.xml_start_tag("worksheet", &vec![]);
+        writer.xml_empty_tag("dimension", &vec![]);
+        writer.xml_start_tag("sheetData", &vec![]);
+        for row in 0..3 {
+            writer.xml_row_element(row, None, None, None, false, false);
+            writer.xml_start_tag("c", &vec![]);
+            writer.xml_data_element("v", "1", &vec![]);
+            writer.xml_end_tag("c");
+            writer.xml_end_tag("row");
+        }
+        writer.xml_row_element(3, None, None, None, false, false);
+        writer.xml_start_tag("c", &vec![]);
+        writer.xml_end_tag("c");
+        writer.xml_start_tag("c", &vec![]);
+        assert_eq!(writer.context(), "worksheet/sheetData/row[4]/c[2]");
+
+        writer.xml_end_tag("c");
+        writer.xml_end_tag("row");
+        writer.xml_end_tag("sheetData");
+        writer.set_part_name("xl/worksheets/sheet3.xml");
+        assert_eq!(writer.context(), "xl/worksheets/sheet3.xml at worksheet");
+
+        writer.xml_end_tag("worksheet");
+        writer.xml_end_tag("worksheet");
+        assert_eq!(writer.context(), "xl/worksheets/sheet3.xml");
+    }
+
+    #[test]
+    fn test_xml_data_element_numbers() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_start_tag("c", &vec![]);
+        writer.xml_data_element_u64("v", u64::MAX, &vec![]);
+        writer.xml_data_element_i64("v", i64::MIN, &vec![("a", "<")]);
+        writer.xml_data_element_f64("v", 0.1, &vec![]);
+        writer.xml_data_element_f64("v", 1e20, &vec![]);
+        writer.xml_data_element_f64("v", -2.0, &vec![]);
+        assert_eq!(writer.context(), "c");
+        writer.xml_end_tag("c");
+
+        let expected = concat!(
+            "<c><v>18446744073709551615</v>",
+            r#"<v a="&lt;">-9223372036854775808</v>"#,
+            "<v>0.1</v><v>100000000000000000000</v><v>-2</v></c>"
+        );
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_tag_only() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_start_tag_only("worksheet");
+        writer.xml_empty_tag_only("dimension");
+        writer.xml_start_tag_only("sheetData");
+        assert_eq!(writer.context(), "worksheet/sheetData");
+        writer.xml_end_tag("sheetData");
+        writer.xml_empty_tag_only("sheetData");
+        assert_eq!(writer.stats().elements(), 4);
+        writer.xml_end_tag("worksheet");
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "<worksheet><dimension/><sheetData></sheetData><sheetData/></worksheet>"
+        );
+
+        // The registered namespaces are still written on the root element.
+        let mut writer = XMLWriter::new(vec![]);
+        writer.register_ns("", "urn:main");
+        writer.xml_start_tag_only("worksheet");
+        writer.xml_empty_tag_only("sheetData");
+        writer.xml_end_tag("worksheet");
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            r#"<worksheet xmlns="urn:main"><sheetData/></worksheet>"#
+        );
+    }
+
+    #[test]
+    fn test_fluent() {
+        let mut writer = XMLWriter::new(vec![]);
+
+        writer
+            .fluent()
+            .start("cols", &vec![])
+            .empty("col", &vec![("min", "1"), ("max", "1")])
+            .empty("col", &vec![("min", "2"), ("max", "2")])
+            .end("cols")
+            .data("v", "a & b", &vec![]);
+
+        assert_eq!(writer.context(), "the top level");
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            r#"<cols><col min="1" max="1"/><col min="2" max="2"/></cols><v>a &amp; b</v>"#
+        );
+    }
+
+    #[test]
+    fn test_current_path() {
+        let mut writer = XMLWriter::new(vec![]);
+        assert_eq!(writer.current_depth(), 0);
+        assert!(writer.current_path().is_empty());
+
+        writer.xml_start_tag("worksheet", &vec![]);
+        writer.xml_empty_tag("dimension", &vec![]);
+        writer.xml_start_tag("sheetData", &vec![]);
+        writer.xml_row_element(0, None, None, None, false, false);
+        writer.xml_start_tag("c", &vec![]);
+        assert_eq!(writer.current_depth(), 4);
+        assert_eq!(
+            writer.current_path(),
+            ["worksheet", "sheetData", "row", "c"]
+        );
+
+        writer.xml_end_tag("c");
+        writer.xml_end_tag("row");
+        assert_eq!(writer.current_depth(), 2);
+        assert_eq!(writer.current_path(), ["worksheet", "sheetData"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Couldn't write to file: full, in sheet1.xml at worksheet/sheetData")]
+    fn test_write_failed_context() {
+        // A writer that fails after a number of bytes.
+        struct Full(usize);
+
+        impl Write for Full {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                if buf.len() > self.0 {
+                    return Err(io::Error::other("full"));
+                }
+                self.0 -= buf.len();
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = XMLWriter::new(Full(30));
+        writer.set_part_name("sheet1.xml");
+        writer.xml_start_tag("worksheet", &vec![]);
+        writer.xml_start_tag("sheetData", &vec![]);
+        writer.xml_data_element("foo", "a long string that doesn't fit", &vec![]);
+    }
+
     #[test]
     fn test_xml_rich_si_element() {
-        let expected = r#"<si>foo</si>"#;
+        let expected = concat!(
+            r#"<si><r><t xml:space="preserve">a &amp; </t></r><r><rPr><b/><sz val="11"/>"#,
+            r#"<color theme="1"/><rFont val="Calibri"/><family val="2"/><scheme val="minor"/>"#,
+            r#"</rPr><t>&lt;b&gt;</t></r></si>"#,
+        );
+        let bold = RunProperties::new().set_bold();
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        writer.xml_rich_si_element(&[(None, "a & "), (Some(&bold), "<b>")]);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_rich_si_element_sanitized() {
+        let expected = "<si><r><t>a_x0001_b</t></r></si>";
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        writer.set_sanitize_policy(SanitizePolicy::Escape);
+
+        writer.xml_rich_si_element(&[(None, "a\u{1}b")]);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_inline_string_element() {
+        let expected = concat!(
+            r#"<c r="A1" t="inlineStr"><is><t>a &amp; b</t></is></c>"#,
+            r#"<c r="B1" s="1" t="inlineStr"><is><t xml:space="preserve"> foo</t></is></c>"#,
+        );
 
         let mut tempfile = tempfile().unwrap();
         let mut writer = XMLWriter::new(&tempfile);
 
-        writer.xml_rich_si_element("foo");
+        writer.xml_inline_string_element("a & b", &vec![("r", "A1")]);
+        writer.xml_inline_string_element(" foo", &vec![("r", "B1"), ("s", "1")]);
 
         let got = read_xmlfile_data(&mut tempfile);
         assert_eq!(got, expected);
     }
+
+    #[test]
+    fn test_xml_attribute_escapes() {
+        let value = "\"A\",\"B & C\",<D>\n€&";
+        let expected = format!(r#"<foo bar="{}"/>"#, super::escape_attributes(value));
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_empty_tag("foo", &vec![("bar", value)]);
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(got, expected);
+        assert_eq!(
+            got,
+            r#"<foo bar="&quot;A&quot;,&quot;B &amp; C&quot;,&lt;D&gt;&#xA;€&amp;"/>"#
+        );
+    }
+
+    #[test]
+    fn test_escape_safe_values_are_borrowed() {
+        for value in ["A1", "1", "FF0000", "Sheet 1!$A$1", ""] {
+            assert!(matches!(super::escape_attributes(value), Cow::Borrowed(_)));
+            assert!(matches!(super::escape_data(value), Cow::Borrowed(_)));
+        }
+
+        assert_eq!(super::escape_attributes("a\"b\n"), "a&quot;b&#xA;");
+        assert!(matches!(super::escape_attributes("a\nb"), Cow::Owned(_)));
+        assert_eq!(super::escape_data("a \"<b>\""), "a \"&lt;b&gt;\"");
+        assert!(matches!(super::escape_data("a\"b\n"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_escape_long_values() {
+        // Special bytes at each position of the u64 words that are scanned,
+        // in the remainder, and after multi-byte characters.
+        for position in 0..20 {
+            let mut value = "€abcdefghijklmnopqrstu".to_string();
+            value.insert(position + 3, '<');
+
+            let expected = value.replace('<', "&lt;");
+            assert_eq!(super::escape_data(&value), expected, "{value}");
+            assert_eq!(super::escape_attributes(&value), expected, "{value}");
+        }
+
+        let value = "x".repeat(1_000) + "&" + &"y".repeat(1_000) + "\"";
+        let expected = "x".repeat(1_000) + "&amp;" + &"y".repeat(1_000) + "&quot;";
+        assert_eq!(super::escape_attributes(&value), expected);
+        assert!(matches!(
+            super::escape_data(&"€".repeat(1_000)),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_write_escaped_data() {
+        // More entities than fit in one vectored write, with adjacent
+        // entities and entities at the start and the end.
+        let data = "<a> & b".repeat(50) + "&&";
+        let expected = format!("<t>{}</t>", super::escape_data(&data));
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_data_element("t", &data, &vec![]);
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+
+        // A writer that only writes part of the slices at a time.
+        struct Trickle(Vec<u8>);
+
+        impl Write for Trickle {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let len = buf.len().min(3);
+                self.0.extend_from_slice(&buf[..len]);
+                Ok(len)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = XMLWriter::new(Trickle(vec![]));
+        writer.xml_data_element("t", &data, &vec![]);
+        assert_eq!(String::from_utf8(writer.into_inner().0).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_writer_stats() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_declaration();
+        writer.xml_start_tag("sheetData", &vec![]);
+        writer.xml_row_element(0, None, None, None, false, false);
+        writer.xml_number_element(1.0, &vec![("r", "A1")]);
+        writer.xml_formula_element("A1*2", 2.0, &vec![("r", "B1")]);
+        writer.xml_end_tag("row");
+        writer.xml_end_tag("sheetData");
+        writer.xml_si_element("a", &vec![]);
+        writer.xml_rich_si_element(&[(None, "b")]);
+
+        let stats = writer.stats();
+        let bytes = writer.into_inner().len() as u64;
+
+        assert_eq!(stats.elements(), 6);
+        assert_eq!(stats.bytes(), bytes);
+        assert!(stats.elements_per_sec() >= 0.0);
+    }
+
+    #[test]
+    fn test_buffered_placeholders() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_start_tag("worksheet", &vec![]);
+        let dimension = writer.reserve_placeholder(24);
+        writer.xml_start_tag("sheetData", &vec![]);
+        let spans = writer.reserve_placeholder(12);
+        writer.xml_end_tag("sheetData");
+        writer.xml_end_tag("worksheet");
+
+        writer.patch(spans, r#"spans="1:3""#);
+        writer.patch(dimension, r#"<dimension ref="A1:C3"/>"#);
+
+        let expected = concat!(
+            r#"<worksheet><dimension ref="A1:C3"/><sheetData>spans="1:3" "#,
+            r#"</sheetData></worksheet>"#
+        );
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_unpatched_placeholder() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_empty_tag("foo", &vec![]);
+        writer.reserve_placeholder(3);
+        writer.xml_empty_tag("bar", &vec![]);
+
+        assert_eq!(writer.stats().bytes(), 15);
+        assert_eq!(writer.into_inner(), b"<foo/>   <bar/>");
+    }
+
+    #[test]
+    fn test_seekable_placeholders() {
+        let mut file = tempfile().unwrap();
+        file.write_all(b"<?xml?>").unwrap();
+
+        let mut writer = XMLWriter::new_seekable(&mut file).unwrap();
+        writer.xml_start_tag("worksheet", &vec![]);
+        let dimension = writer.reserve_placeholder(28);
+        writer.xml_empty_tag("sheetData", &vec![]);
+        writer.patch(dimension, r#"<dimension ref="A1:XFD9"/>"#);
+        writer.xml_end_tag("worksheet");
+        drop(writer);
+
+        let expected = concat!(
+            r#"<?xml?><worksheet><dimension ref="A1:XFD9"/>  "#,
+            r#"<sheetData/></worksheet>"#
+        );
+        assert_eq!(read_xmlfile_data(&mut file), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "is longer than the placeholder")]
+    fn test_placeholder_too_short() {
+        let mut writer = XMLWriter::new(vec![]);
+        let placeholder = writer.reserve_placeholder(4);
+        writer.patch(placeholder, "12345");
+    }
+
+    #[test]
+    fn test_checkpoint_rollback() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_part_name("sheet1.xml");
+        writer.xml_start_tag("sheetData", &vec![]);
+        writer.xml_row_element(0, None, None, None, false, true);
+
+        let checkpoint = writer.checkpoint();
+        writer.xml_start_tag("row", &vec![("r", "2")]);
+        writer.xml_number_element(1.0, &vec![("r", "A2")]);
+        writer.rollback(checkpoint);
+
+        assert_eq!(writer.context(), "sheet1.xml at sheetData");
+        assert_eq!(writer.stats().elements(), 2);
+        assert_eq!(writer.stats().bytes(), 23);
+
+        let checkpoint = writer.checkpoint();
+        writer.xml_row_element(1, None, None, None, false, true);
+        writer.commit(checkpoint);
+        writer.xml_end_tag("sheetData");
+
+        let expected = r#"<sheetData><row r="1"/><row r="2"/></sheetData>"#;
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_nested_checkpoints() {
+        let mut writer = XMLWriter::new(vec![]);
+        let outer = writer.checkpoint();
+        writer.xml_empty_tag("a", &vec![]);
+
+        let inner = writer.checkpoint();
+        writer.xml_empty_tag("b", &vec![]);
+        let _placeholder = writer.reserve_placeholder(4);
+        writer.commit(inner);
+
+        writer.rollback(outer);
+        writer.xml_empty_tag("c", &vec![]);
+
+        // The placeholder was discarded with the output, so the output isn't
+        // held in memory for it.
+        assert!(writer.writer.buffer.is_none());
+        assert_eq!(writer.into_inner(), b"<c/>");
+    }
+
+    #[test]
+    #[should_panic(expected = "placeholder was discarded by a rollback")]
+    fn test_patch_rolled_back_placeholder() {
+        let mut writer = XMLWriter::new(vec![]);
+        let outer = writer.checkpoint();
+        writer.xml_empty_tag("a", &vec![]);
+
+        let inner = writer.checkpoint();
+        let placeholder = writer.reserve_placeholder(4);
+        writer.rollback(inner);
+
+        // The new output is at the same position as the discarded
+        // placeholder, and mustn't be overwritten.
+        let _new_placeholder = writer.reserve_placeholder(4);
+        writer.xml_empty_tag("b", &vec![]);
+        writer.commit(outer);
+
+        writer.patch(placeholder, "1234");
+    }
+
+    #[test]
+    #[should_panic(expected = "checkpoint has already been committed or rolled back")]
+    fn test_rollback_discarded_checkpoint() {
+        let mut writer = XMLWriter::new(vec![]);
+        let outer = writer.checkpoint();
+        writer.xml_empty_tag("a", &vec![]);
+        let inner = writer.checkpoint();
+
+        writer.rollback(outer);
+        writer.rollback(inner);
+    }
+
+    #[test]
+    fn test_register_ns() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.register_ns("r", "urn:r");
+        writer.register_ns("x14", "urn:old");
+        writer.register_ns("x14", "urn:x14");
+        writer.register_ns("mc", "urn:mc");
+
+        writer.xml_start_tag(
+            "worksheet",
+            &vec![
+                ("xmlns", "urn:main"),
+                ("xmlns:mc", "urn:mc"),
+                ("mc:Ignorable", "x14"),
+            ],
+        );
+        writer.xml_start_tag("ext", &vec![("xmlns:xm", "urn:xm")]);
+        writer.xml_data_element("xm:f", "A1", &vec![]);
+        writer.xml_end_tag("ext");
+        writer.xml_empty_tag("drawing", &vec![("r:id", "rId1")]);
+        writer.xml_end_tag("worksheet");
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        let expected = concat!(
+            r#"<worksheet xmlns="urn:main" xmlns:mc="urn:mc" xmlns:r="urn:r" "#,
+            r#"xmlns:x14="urn:x14" mc:Ignorable="x14"><ext xmlns:xm="urn:xm">"#,
+            r#"<xm:f>A1</xm:f></ext><drawing r:id="rId1"/></worksheet>"#
+        );
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_register_ns_root_only() {
+        // The namespaces go on the root even if other elements were counted
+        // before it, and not on the elements written before it.
+        let mut writer = XMLWriter::new(vec![]);
+        writer.register_ns("", "urn:main");
+
+        writer.xml_si_element("text", &vec![]);
+        writer.xml_start_tag("sst", &vec![("count", "1")]);
+        writer.xml_si_element("text", &vec![]);
+        writer.xml_end_tag("sst");
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            concat!(
+                r#"<si><t>text</t></si><sst xmlns="urn:main" count="1">"#,
+                r#"<si><t>text</t></si></sst>"#
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "the namespace prefix 'xm' of 'xm:f' in <xm:f> isn't registered or declared, in worksheet"
+    )]
+    fn test_register_ns_undeclared_prefix() {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.register_ns("", "urn:main");
+
+        writer.xml_start_tag("worksheet", &vec![]);
+        writer.xml_start_tag("ext", &vec![("xmlns:xm", "urn:xm")]);
+        writer.xml_end_tag("ext");
+        writer.xml_data_element("xm:f", "A1", &vec![]);
+    }
+
+    #[test]
+    fn test_writer_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<XMLWriter<File>>();
+        assert_send::<XMLWriter<io::BufWriter<File>>>();
+        assert_send::<XMLWriter<Vec<u8>>>();
+    }
+
+    #[test]
+    fn test_dry_run() {
+        fn write<W: Write>(writer: &mut XMLWriter<W>) {
+            writer.set_sanitize_policy(crate::sanitize::SanitizePolicy::Escape);
+            writer.xml_declaration();
+            writer.xml_start_tag("sst", &vec![("count", "3")]);
+            writer.xml_si_element("=1+2 & <3>", &vec![]);
+            writer.xml_rich_si_element(&[(None, "caf\u{e9}"), (None, " \"quoted\"")]);
+            writer.xml_string_element(0, &vec![("r", "A1"), ("s", "1")]);
+            writer.xml_end_tag("sst");
+        }
+
+        let mut dry_run = XMLWriter::dry_run();
+        write(&mut dry_run);
+
+        let mut writer = XMLWriter::new(vec![]);
+        write(&mut writer);
+
+        assert_eq!(dry_run.stats().bytes(), writer.into_inner().len() as u64);
+    }
+
+    #[test]
+    fn test_writer_stats_rates() {
+        let stats = super::WriterStats {
+            elements: 500,
+            bytes: 10_000,
+            elapsed: std::time::Duration::from_millis(250),
+        };
+
+        assert_eq!(stats.elements_per_sec(), 2_000.0);
+        assert_eq!(stats.bytes_per_sec(), 40_000.0);
+
+        let stats = super::WriterStats {
+            elapsed: std::time::Duration::ZERO,
+            ..stats
+        };
+        assert_eq!(stats.elements_per_sec(), 0.0);
+    }
+
+    #[test]
+    fn test_xml_long_attribute() {
+        let value = "\"Item &\",".repeat(10_000);
+        let expected = format!(
+            r#"<formula1 list="{}">x</formula1>"#,
+            "&quot;Item &amp;&quot;,".repeat(10_000)
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.xml_data_element("formula1", "x", &vec![("list", &value)]);
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(got, expected);
+    }
 }
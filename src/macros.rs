@@ -0,0 +1,125 @@
+// The xml! macro for writing nested elements.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+/// Write nested elements with an [`XMLWriter`](crate::XMLWriter). The macro
+/// expands to the calls to the tag methods at compile time, so it is as fast
+/// as writing the calls by hand.
+///
+/// The first argument is the writer and it is followed by the elements. Each
+/// element is a string literal tag with an optional list of attributes in
+/// brackets, and then one of:
+///
+/// - a block of child elements in braces, which are written between
+///   [`xml_start_tag()`](crate::XMLWriter::xml_start_tag) and
+///   [`xml_end_tag()`](crate::XMLWriter::xml_end_tag),
+/// - `= data;`, which writes the data with
+///   [`xml_data_element()`](crate::XMLWriter::xml_data_element),
+/// - `;`, which writes an empty element with
+///   [`xml_empty_tag()`](crate::XMLWriter::xml_empty_tag).
+///
+/// ```
+/// use excel_xmlwriter::{xml, XMLWriter};
+///
+/// let mut writer = XMLWriter::new(vec![]);
+/// let count = 1;
+///
+/// xml!(writer,
+///     "fonts" [("count", &count.to_string())] {
+///         "font" {
+///             "sz" [("val", "11")];
+///             "name" [("val", "Calibri")];
+///         }
+///     }
+///     "dxfs" [("count", "0")];
+///     "extra" = "text";
+/// );
+///
+/// assert_eq!(
+///     writer.into_inner(),
+///     concat!(
+///         r#"<fonts count="1"><font><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+///         r#"<dxfs count="0"/><extra>text</extra>"#
+///     )
+///     .as_bytes()
+/// );
+/// ```
+#[macro_export]
+macro_rules! xml {
+    ($writer:expr, $($elements:tt)*) => {{
+        let writer: &mut $crate::XMLWriter<_> = &mut $writer;
+        $crate::xml!(@elements writer; $($elements)*);
+    }};
+
+    (@elements $writer:ident;) => {};
+
+    (@elements $writer:ident;
+        $tag:literal $([$($attribute:expr),* $(,)?])? { $($children:tt)* } $($rest:tt)*
+    ) => {
+        $writer.xml_start_tag($tag, &vec![$($($attribute),*)?]);
+        $crate::xml!(@elements $writer; $($children)*);
+        $writer.xml_end_tag($tag);
+        $crate::xml!(@elements $writer; $($rest)*);
+    };
+
+    (@elements $writer:ident;
+        $tag:literal $([$($attribute:expr),* $(,)?])? = $data:expr; $($rest:tt)*
+    ) => {
+        $writer.xml_data_element($tag, $data, &vec![$($($attribute),*)?]);
+        $crate::xml!(@elements $writer; $($rest)*);
+    };
+
+    (@elements $writer:ident;
+        $tag:literal $([$($attribute:expr),* $(,)?])?; $($rest:tt)*
+    ) => {
+        $writer.xml_empty_tag($tag, &vec![$($($attribute),*)?]);
+        $crate::xml!(@elements $writer; $($rest)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::XMLWriter;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_xml_macro() {
+        let mut writer = XMLWriter::new(vec![]);
+        let format_code = "0.0";
+
+        xml!(writer,
+            "styleSheet" [("xmlns", "http://schemas.openxmlformats.org/spreadsheetml/2006/main")] {
+                "numFmts" [("count", "1")] {
+                    "numFmt" [("numFmtId", "164"), ("formatCode", format_code)];
+                }
+                "cellStyles" [("count", "1")] {
+                    "cellStyle" [("name", "Normal"), ("xfId", "0"), ("builtinId", "0"),];
+                }
+                "dxfs" [];
+                "tag" = "a & b";
+            }
+        );
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        let expected = concat!(
+            r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            r#"<numFmts count="1"><numFmt numFmtId="164" formatCode="0.0"/></numFmts>"#,
+            r#"<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>"#,
+            r#"<dxfs/><tag>a &amp; b</tag></styleSheet>"#
+        );
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_xml_macro_writer_ref() {
+        let mut writer = XMLWriter::new(vec![]);
+        let writer_ref = &mut writer;
+
+        xml!(*writer_ref, "a" { "b"; } "c" = "1";);
+        assert_eq!(writer.context(), "the top level");
+        assert_eq!(writer.into_inner(), b"<a><b/></a><c>1</c>");
+    }
+}
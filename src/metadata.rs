@@ -0,0 +1,360 @@
+//! The writer for the `xl/metadata.xml` part.
+//!
+//! Dynamic array formulas, which spill their results into the cells around
+//! them, are marked with a `cm` cell metadata attribute that refers to the
+//! dynamic array properties in this part. A workbook with dynamic array
+//! formulas, see
+//! [`Worksheet::has_dynamic_arrays()`](crate::worksheet::Worksheet::has_dynamic_arrays),
+//! needs a single [`Metadata`] part linked from the workbook relationships.
+//!
+//! Rich values, such as images in cells, are marked with a `vm` value
+//! metadata attribute instead. The index for the attribute comes from
+//! [`Metadata::add_rich_value()`], and the rich value parts themselves are
+//! written with the [`rich_data`](crate::rich_data) module.
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::metadata::Metadata;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::relationship::Relationships;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut workbook_relationships = Relationships::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! worksheet.write_dynamic_array_formula(0, 1, 0, 1, "=LEN(A1:A3)");
+//!
+//! if worksheet.has_dynamic_arrays() {
+//!     content_types.add_metadata();
+//!     workbook_relationships.add_document_relationship("sheetMetadata", "metadata.xml");
+//!     packager.add_custom_part(&Metadata::new())?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{DYNAMIC_ARRAY_NS, METADATA_CONTENT_TYPE, RICH_DATA_NS, SPREADSHEETML_NS};
+use crate::part::XlsxPart;
+use crate::XMLWriter;
+
+// The uri of the extension with the dynamic array properties.
+const DYNAMIC_ARRAY_EXT_URI: &str = "{bdbb8cdc-fa1e-496e-a857-3c3f30c029c3}";
+
+// The uri of the extension with the rich value blocks.
+const RICH_VALUE_EXT_URI: &str = "{3e2802c4-a4d2-4d8b-9148-e3be6c30e623}";
+
+// The flags of the metadata types, which are the same for both types apart
+// from `cellMeta`.
+const METADATA_TYPE_FLAGS: [(&str, &str); 11] = [
+    ("minSupportedVersion", "120000"),
+    ("copy", "1"),
+    ("pasteAll", "1"),
+    ("pasteValues", "1"),
+    ("merge", "1"),
+    ("splitFirst", "1"),
+    ("rowColShift", "1"),
+    ("clearFormats", "1"),
+    ("clearComments", "1"),
+    ("assign", "1"),
+    ("coerce", "1"),
+];
+
+/// The `xl/metadata.xml` part with the cell metadata of dynamic array
+/// formulas and the value metadata of rich values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    dynamic_arrays: bool,
+    rich_values: Vec<u32>,
+}
+
+impl Default for Metadata {
+    fn default() -> Metadata {
+        Metadata {
+            dynamic_arrays: true,
+            rich_values: vec![],
+        }
+    }
+}
+
+impl Metadata {
+    /// Create the metadata part, with the cell metadata of dynamic array
+    /// formulas.
+    pub fn new() -> Metadata {
+        Metadata::default()
+    }
+
+    /// Set whether the part has the cell metadata of dynamic array formulas,
+    /// which is referred to by their `cm="1"` attribute. It is on by
+    /// default, and can be turned off for a workbook that only has rich
+    /// values.
+    pub fn set_dynamic_arrays(&mut self, enable: bool) {
+        self.dynamic_arrays = enable;
+    }
+
+    /// Add the value metadata of a rich value, by its index in the
+    /// `xl/richData/rdrichvalue.xml` part, and return the value of the `vm`
+    /// attribute for the cell that has it. The `vm` values start at 1.
+    ///
+    /// ```
+    /// use excel_xmlwriter::metadata::Metadata;
+    /// use excel_xmlwriter::XMLWriter;
+    ///
+    /// let mut metadata = Metadata::new();
+    /// metadata.set_dynamic_arrays(false);
+    /// let vm = metadata.add_rich_value(0).to_string();
+    ///
+    /// let mut writer = XMLWriter::new(vec![]);
+    /// writer.xml_string_element(0, &vec![("r", "A1"), ("vm", &vm)]);
+    ///
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     br#"<c r="A1" t="s" vm="1"><v>0</v></c>"#
+    /// );
+    /// ```
+    pub fn add_rich_value(&mut self, rich_value_index: u32) -> u32 {
+        self.rich_values.push(rich_value_index);
+        self.rich_values.len() as u32
+    }
+
+    /// Write the `xl/metadata.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let mut attributes = vec![("xmlns", SPREADSHEETML_NS)];
+        if !self.rich_values.is_empty() {
+            attributes.push(("xmlns:xlrd", RICH_DATA_NS));
+        }
+        if self.dynamic_arrays {
+            attributes.push(("xmlns:xda", DYNAMIC_ARRAY_NS));
+        }
+        writer.xml_start_tag("metadata", &attributes);
+
+        self.write_metadata_types(writer);
+
+        if self.dynamic_arrays {
+            self.write_future_metadata(writer);
+        }
+        if !self.rich_values.is_empty() {
+            self.write_rich_value_future_metadata(writer);
+        }
+        if self.dynamic_arrays {
+            self.write_cell_metadata(writer);
+        }
+        if !self.rich_values.is_empty() {
+            self.write_value_metadata(writer);
+        }
+
+        writer.xml_end_tag("metadata");
+    }
+
+    // The 1-based index of the XLRICHVALUE type in the <metadataTypes>,
+    // after the XLDAPR type if there is one.
+    fn rich_value_type(&self) -> u32 {
+        if self.dynamic_arrays {
+            2
+        } else {
+            1
+        }
+    }
+
+    // Write the <metadataTypes> element with the XLDAPR type, which is the
+    // dynamic array properties, and the XLRICHVALUE type of rich values.
+    fn write_metadata_types(&self, writer: &mut XMLWriter<impl Write>) {
+        let count =
+            (u32::from(self.dynamic_arrays) + u32::from(!self.rich_values.is_empty())).to_string();
+        writer.xml_start_tag("metadataTypes", &vec![("count", &count)]);
+
+        if self.dynamic_arrays {
+            let mut attributes = vec![("name", "XLDAPR")];
+            attributes.extend(METADATA_TYPE_FLAGS);
+            attributes.push(("cellMeta", "1"));
+            writer.xml_empty_tag("metadataType", &attributes);
+        }
+
+        if !self.rich_values.is_empty() {
+            let mut attributes = vec![("name", "XLRICHVALUE")];
+            attributes.extend(METADATA_TYPE_FLAGS);
+            writer.xml_empty_tag("metadataType", &attributes);
+        }
+
+        writer.xml_end_tag("metadataTypes");
+    }
+
+    // Write the <futureMetadata> element with the dynamic array properties.
+    fn write_future_metadata(&self, writer: &mut XMLWriter<impl Write>) {
+        let attributes = vec![("name", "XLDAPR"), ("count", "1")];
+        writer.xml_start_tag("futureMetadata", &attributes);
+        writer.xml_start_tag("bk", &vec![]);
+        writer.xml_start_tag("extLst", &vec![]);
+        writer.xml_start_tag("ext", &vec![("uri", DYNAMIC_ARRAY_EXT_URI)]);
+
+        let attributes = vec![("fDynamic", "1"), ("fCollapsed", "0")];
+        writer.xml_empty_tag("xda:dynamicArrayProperties", &attributes);
+
+        writer.xml_end_tag("ext");
+        writer.xml_end_tag("extLst");
+        writer.xml_end_tag("bk");
+        writer.xml_end_tag("futureMetadata");
+    }
+
+    // Write the <futureMetadata> element with a block for each rich value,
+    // which refers to the rich value by its index.
+    fn write_rich_value_future_metadata(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.rich_values.len().to_string();
+        let attributes = vec![("name", "XLRICHVALUE"), ("count", count.as_str())];
+        writer.xml_start_tag("futureMetadata", &attributes);
+
+        for rich_value_index in &self.rich_values {
+            let rich_value_index = rich_value_index.to_string();
+
+            writer.xml_start_tag("bk", &vec![]);
+            writer.xml_start_tag("extLst", &vec![]);
+            writer.xml_start_tag("ext", &vec![("uri", RICH_VALUE_EXT_URI)]);
+            writer.xml_empty_tag("xlrd:rvb", &vec![("i", &rich_value_index)]);
+            writer.xml_end_tag("ext");
+            writer.xml_end_tag("extLst");
+            writer.xml_end_tag("bk");
+        }
+
+        writer.xml_end_tag("futureMetadata");
+    }
+
+    // Write the <cellMetadata> element. Its first, and only, block is the
+    // one referred to by the cm="1" attribute of the formula cells.
+    fn write_cell_metadata(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_start_tag("cellMetadata", &vec![("count", "1")]);
+        writer.xml_start_tag("bk", &vec![]);
+        writer.xml_empty_tag("rc", &vec![("t", "1"), ("v", "0")]);
+        writer.xml_end_tag("bk");
+        writer.xml_end_tag("cellMetadata");
+    }
+
+    // Write the <valueMetadata> element. Its blocks are the ones referred to
+    // by the vm attributes of the cells, from 1, and each one refers to the
+    // future metadata block of its rich value.
+    fn write_value_metadata(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.rich_values.len().to_string();
+        let rich_value_type = self.rich_value_type().to_string();
+        writer.xml_start_tag("valueMetadata", &vec![("count", &count)]);
+
+        for index in 0..self.rich_values.len() {
+            let index = index.to_string();
+
+            writer.xml_start_tag("bk", &vec![]);
+            writer.xml_empty_tag("rc", &vec![("t", &rich_value_type), ("v", &index)]);
+            writer.xml_end_tag("bk");
+        }
+
+        writer.xml_end_tag("valueMetadata");
+    }
+}
+
+impl XlsxPart for Metadata {
+    fn part_name(&self) -> &str {
+        "xl/metadata.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        METADATA_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Metadata;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_metadata() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<metadata xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"xmlns:xda="http://schemas.microsoft.com/office/spreadsheetml/2017/dynamicarray">"#,
+            r#"<metadataTypes count="1"><metadataType name="XLDAPR" minSupportedVersion="120000" "#,
+            r#"copy="1" pasteAll="1" pasteValues="1" merge="1" splitFirst="1" rowColShift="1" "#,
+            r#"clearFormats="1" clearComments="1" assign="1" coerce="1" cellMeta="1"/>"#,
+            r#"</metadataTypes><futureMetadata name="XLDAPR" count="1"><bk><extLst>"#,
+            r#"<ext uri="{bdbb8cdc-fa1e-496e-a857-3c3f30c029c3}">"#,
+            r#"<xda:dynamicArrayProperties fDynamic="1" fCollapsed="0"/></ext></extLst></bk>"#,
+            r#"</futureMetadata><cellMetadata count="1"><bk><rc t="1" v="0"/></bk>"#,
+            r#"</cellMetadata></metadata>"#,
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        Metadata::new().assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_assemble_metadata_rich_values() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<metadata xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"xmlns:xlrd="http://schemas.microsoft.com/office/spreadsheetml/2017/richdata" "#,
+            r#"xmlns:xda="http://schemas.microsoft.com/office/spreadsheetml/2017/dynamicarray">"#,
+            r#"<metadataTypes count="2"><metadataType name="XLDAPR" minSupportedVersion="120000" "#,
+            r#"copy="1" pasteAll="1" pasteValues="1" merge="1" splitFirst="1" rowColShift="1" "#,
+            r#"clearFormats="1" clearComments="1" assign="1" coerce="1" cellMeta="1"/>"#,
+            r#"<metadataType name="XLRICHVALUE" minSupportedVersion="120000" "#,
+            r#"copy="1" pasteAll="1" pasteValues="1" merge="1" splitFirst="1" rowColShift="1" "#,
+            r#"clearFormats="1" clearComments="1" assign="1" coerce="1"/>"#,
+            r#"</metadataTypes><futureMetadata name="XLDAPR" count="1"><bk><extLst>"#,
+            r#"<ext uri="{bdbb8cdc-fa1e-496e-a857-3c3f30c029c3}">"#,
+            r#"<xda:dynamicArrayProperties fDynamic="1" fCollapsed="0"/></ext></extLst></bk>"#,
+            r#"</futureMetadata><futureMetadata name="XLRICHVALUE" count="2">"#,
+            r#"<bk><extLst><ext uri="{3e2802c4-a4d2-4d8b-9148-e3be6c30e623}">"#,
+            r#"<xlrd:rvb i="0"/></ext></extLst></bk>"#,
+            r#"<bk><extLst><ext uri="{3e2802c4-a4d2-4d8b-9148-e3be6c30e623}">"#,
+            r#"<xlrd:rvb i="5"/></ext></extLst></bk></futureMetadata>"#,
+            r#"<cellMetadata count="1"><bk><rc t="1" v="0"/></bk></cellMetadata>"#,
+            r#"<valueMetadata count="2"><bk><rc t="2" v="0"/></bk><bk><rc t="2" v="1"/></bk>"#,
+            r#"</valueMetadata></metadata>"#,
+        );
+
+        let mut metadata = Metadata::new();
+        assert_eq!(metadata.add_rich_value(0), 1);
+        assert_eq!(metadata.add_rich_value(5), 2);
+
+        let mut writer = XMLWriter::new(vec![]);
+        metadata.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_assemble_metadata_rich_values_only() {
+        let mut metadata = Metadata::new();
+        metadata.set_dynamic_arrays(false);
+        metadata.add_rich_value(0);
+
+        let mut writer = XMLWriter::new(vec![]);
+        metadata.assemble_xml_file(&mut writer);
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(!got.contains("XLDAPR"));
+        assert!(!got.contains("cellMetadata"));
+        assert!(got.contains(r#"<metadataTypes count="1"><metadataType name="XLRICHVALUE""#));
+        assert!(got.contains(r#"<valueMetadata count="1"><bk><rc t="1" v="0"/></bk>"#));
+    }
+}
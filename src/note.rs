@@ -0,0 +1,257 @@
+//! The cell notes of a worksheet and the writer for the `xl/commentsN.xml`
+//! parts.
+//!
+//! A [`Note`], which Excel used to call a comment, is added to a cell with
+//! [`Worksheet::add_note()`](crate::worksheet::Worksheet::add_note). The
+//! notes of a worksheet are written to two parts: the text and authors in the
+//! [`Comments`] part, and the boxes that display them in a
+//! [`VmlDrawing`](crate::vml::VmlDrawing) part. Both are created from the
+//! worksheet with the number of its notes parts, unique in the workbook and
+//! starting at 1, which is set with
+//! [`Worksheet::set_notes_number()`](crate::worksheet::Worksheet::set_notes_number).
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::note::Note;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! worksheet.add_note(1, 1, &Note::new("Check this value").set_author("Jane"));
+//!
+//! if let (Some(comments), Some(vml_drawing)) = (worksheet.comments(), worksheet.vml_drawing()) {
+//!     content_types.add_comments(1);
+//!     content_types.add_vml();
+//!     packager.add_custom_part(&comments)?;
+//!     packager.add_custom_part(&vml_drawing)?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{COMMENTS_CONTENT_TYPE, SPREADSHEETML_NS};
+use crate::part::XlsxPart;
+use crate::utility::rowcol_to_cell;
+use crate::XMLWriter;
+
+/// A note on a cell, shown in a yellow box when the cell is hovered over.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    text: String,
+    author: Option<String>,
+    visible: bool,
+    width: u32,
+    height: u32,
+}
+
+impl Note {
+    /// Create a note with its text. The note is hidden until the cell is
+    /// hovered over, and has Excel's default size of 128 by 74 pixels.
+    pub fn new(text: &str) -> Note {
+        Note {
+            text: text.to_string(),
+            author: None,
+            visible: false,
+            width: 128,
+            height: 74,
+        }
+    }
+
+    /// Set the author of the note. The default is the default author of the
+    /// worksheet, see
+    /// [`Worksheet::set_default_note_author()`](crate::worksheet::Worksheet::set_default_note_author).
+    pub fn set_author(mut self, author: &str) -> Note {
+        self.author = Some(author.to_string());
+        self
+    }
+
+    /// Set whether the note is always shown. The default is `false`.
+    pub fn set_visible(mut self, visible: bool) -> Note {
+        self.visible = visible;
+        self
+    }
+
+    /// Set the width of the note box in pixels.
+    pub fn set_width(mut self, width: u32) -> Note {
+        self.width = width;
+        self
+    }
+
+    /// Set the height of the note box in pixels.
+    pub fn set_height(mut self, height: u32) -> Note {
+        self.height = height;
+        self
+    }
+
+    // Check if the note is always shown.
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    // The size of the note box in pixels, as (width, height).
+    pub(crate) fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// The `xl/commentsN.xml` part with the text and authors of the notes of a
+/// worksheet, created with
+/// [`Worksheet::comments()`](crate::worksheet::Worksheet::comments).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comments {
+    part_name: String,
+    authors: Vec<String>,
+    notes: Vec<(u32, u16, usize, String)>,
+}
+
+impl Comments {
+    // Create the part from the notes in row and column order. Notes without
+    // an author get the default author. The authors are listed in the order
+    // of their first note.
+    pub(crate) fn new<'a>(
+        number: u32,
+        notes: impl Iterator<Item = (u32, u16, &'a Note)>,
+        default_author: &str,
+    ) -> Comments {
+        let mut authors: Vec<String> = vec![];
+        let mut comments = vec![];
+
+        for (row, col, note) in notes {
+            let author = note.author.as_deref().unwrap_or(default_author);
+            let author_id = match authors.iter().position(|known| known == author) {
+                Some(author_id) => author_id,
+                None => {
+                    authors.push(author.to_string());
+                    authors.len() - 1
+                }
+            };
+
+            comments.push((row, col, author_id, note.text.clone()));
+        }
+
+        Comments {
+            part_name: format!("xl/comments{number}.xml"),
+            authors,
+            notes: comments,
+        }
+    }
+
+    /// Write the `xl/commentsN.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns", SPREADSHEETML_NS)];
+        writer.xml_start_tag("comments", &attributes);
+
+        writer.xml_start_tag("authors", &vec![]);
+        for author in &self.authors {
+            writer.xml_data_element("author", author, &vec![]);
+        }
+        writer.xml_end_tag("authors");
+
+        writer.xml_start_tag("commentList", &vec![]);
+        for (row, col, author_id, text) in &self.notes {
+            let cell_ref = rowcol_to_cell(*row, *col);
+            let author_id = author_id.to_string();
+            let attributes = vec![("ref", cell_ref.as_str()), ("authorId", author_id.as_str())];
+
+            writer.xml_start_tag("comment", &attributes);
+            writer.xml_start_tag("text", &vec![]);
+            writer.xml_start_tag("r", &vec![]);
+
+            // Excel's default font for notes.
+            writer.xml_start_tag("rPr", &vec![]);
+            writer.xml_empty_tag("sz", &vec![("val", "8")]);
+            writer.xml_empty_tag("color", &vec![("indexed", "81")]);
+            writer.xml_empty_tag("rFont", &vec![("val", "Tahoma")]);
+            writer.xml_empty_tag("family", &vec![("val", "2")]);
+            writer.xml_end_tag("rPr");
+
+            // Excel preserves leading and trailing whitespace.
+            let attributes =
+                if text.starts_with(char::is_whitespace) || text.ends_with(char::is_whitespace) {
+                    vec![("xml:space", "preserve")]
+                } else {
+                    vec![]
+                };
+            writer.xml_data_element("t", text, &attributes);
+
+            writer.xml_end_tag("r");
+            writer.xml_end_tag("text");
+            writer.xml_end_tag("comment");
+        }
+        writer.xml_end_tag("commentList");
+
+        writer.xml_end_tag("comments");
+    }
+}
+
+impl XlsxPart for Comments {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        COMMENTS_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Comments, Note};
+    use crate::part::XlsxPart;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_comments() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<comments xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            r#"<authors><author>Jane</author><author>Author</author></authors><commentList>"#,
+            r#"<comment ref="A1" authorId="0"><text><r><rPr><sz val="8"/><color indexed="81"/>"#,
+            r#"<rFont val="Tahoma"/><family val="2"/></rPr><t>Note &lt;1&gt;</t></r></text></comment>"#,
+            r#"<comment ref="C5" authorId="1"><text><r><rPr><sz val="8"/><color indexed="81"/>"#,
+            r#"<rFont val="Tahoma"/><family val="2"/></rPr><t xml:space="preserve">Note 2 </t></r></text></comment>"#,
+            r#"<comment ref="D5" authorId="0"><text><r><rPr><sz val="8"/><color indexed="81"/>"#,
+            r#"<rFont val="Tahoma"/><family val="2"/></rPr><t>Note 3</t></r></text></comment>"#,
+            r#"</commentList></comments>"#,
+        );
+
+        let notes = [
+            (0, 0, Note::new("Note <1>").set_author("Jane")),
+            (4, 2, Note::new("Note 2 ")),
+            (4, 3, Note::new("Note 3").set_author("Jane")),
+        ];
+        let comments = Comments::new(
+            2,
+            notes.iter().map(|(row, col, note)| (*row, *col, note)),
+            "Author",
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        comments.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+        assert_eq!(comments.part_name(), "xl/comments2.xml");
+    }
+}
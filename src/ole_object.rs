@@ -0,0 +1,395 @@
+//! The objects that are embedded in worksheets, and their
+//! `xl/embeddings/oleObjectN` parts.
+//!
+//! An [`OleObject`] is a file, such as a PDF or another workbook, that is
+//! stored in the workbook and opened from its worksheet by double clicking
+//! it. It is added to a cell with
+//! [`Worksheet::embed_object()`](crate::worksheet::Worksheet::embed_object)
+//! or [`Worksheet::add_object()`](crate::worksheet::Worksheet::add_object).
+//!
+//! Excel, Word and PowerPoint files (`.xlsx`, `.docx` and `.pptx`) are
+//! embedded unchanged as packages. Other files are wrapped in an OLE
+//! compound file, as an "OLE Package" object. In the worksheet, the object
+//! is shown as its preview image, which is a document icon unless it is set
+//! with [`OleObject::set_preview()`].
+//!
+//! The object is written to several parts:
+//!
+//! - The [`Embedding`] parts from
+//!   [`Worksheet::embeddings()`](crate::worksheet::Worksheet::embeddings),
+//!   numbered from
+//!   [`Worksheet::set_embedding_number()`](crate::worksheet::Worksheet::set_embedding_number).
+//! - The preview images, which are `xl/media/imageN` parts from
+//!   [`Worksheet::media()`](crate::worksheet::Worksheet::media).
+//! - The shape of the object in the worksheet's
+//!   [`VmlDrawing`](crate::vml::VmlDrawing) part, and its relationship to
+//!   the preview image in the
+//!   [`VmlDrawing::relationships()`](crate::vml::VmlDrawing::relationships)
+//!   part.
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::ole_object::OleObject;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! worksheet.add_object(1, 1, &OleObject::from_bytes(b"%PDF-1.7", "report.pdf"));
+//!
+//! for embedding in worksheet.embeddings() {
+//!     let part_name = format!("/{}", embedding.part_name());
+//!     content_types.add_override(&part_name, embedding.content_type());
+//!     packager.add_part(embedding.part_name(), embedding.data())?;
+//! }
+//! for (part_name, image) in worksheet.media() {
+//!     let image_type = image.image_type();
+//!     content_types.add_default(image_type.extension(), image_type.content_type());
+//!     packager.add_part(&part_name, image.data())?;
+//! }
+//! if let Some(vml_drawing) = worksheet.vml_drawing() {
+//!     content_types.add_vml();
+//!     packager.add_custom_part(&vml_drawing)?;
+//!     packager.add_xml_part("xl/drawings/_rels/vmlDrawing1.vml.rels", |writer| {
+//!         vml_drawing.relationships().assemble_xml_file(writer)
+//!     })?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::fs;
+use std::path::Path;
+
+use crate::compound_file::compound_file;
+use crate::constants::OLE_OBJECT_CONTENT_TYPE;
+use crate::error::XlsxError;
+use crate::image::Image;
+
+// The Office documents that are embedded as packages, with their extension,
+// ProgID and content type.
+const PACKAGES: [(&str, &str, &str); 3] = [
+    (
+        "xlsx",
+        "Excel.Sheet.12",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    (
+        "docx",
+        "Word.Document.12",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    (
+        "pptx",
+        "PowerPoint.Show.12",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+];
+
+// The CLSID of OLE Package objects, {0003000C-0000-0000-C000-000000000046},
+// in its byte order in a file.
+const PACKAGE_CLSID: [u8; 16] = [
+    0x0C, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+// The default preview image, a document icon of 48 by 48 pixels.
+const DEFAULT_PREVIEW: [u8; 140] = [
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x30, 0x00, 0x00, 0x00, 0x30, 0x08, 0x02, 0x00, 0x00, 0x00, 0xD8, 0x60, 0x6E,
+    0xD0, 0x00, 0x00, 0x00, 0x53, 0x49, 0x44, 0x41, 0x54, 0x78, 0xDA, 0xED, 0xD8, 0xC1, 0x09, 0x00,
+    0x21, 0x0C, 0x45, 0x41, 0x4B, 0xB5, 0x34, 0x3B, 0xD5, 0x16, 0x94, 0x84, 0x10, 0x61, 0x7E, 0x01,
+    0xCB, 0x48, 0xDE, 0x69, 0xC7, 0x6E, 0xB6, 0x01, 0x04, 0x54, 0x01, 0x9A, 0x49, 0xCB, 0x04, 0xC5,
+    0x9F, 0x0E, 0x04, 0x04, 0x74, 0xF9, 0xAD, 0xF5, 0x38, 0x27, 0x03, 0xD2, 0x10, 0x90, 0x86, 0x34,
+    0xA4, 0x21, 0x27, 0xD3, 0x90, 0x93, 0x01, 0x69, 0x08, 0x48, 0x43, 0x1A, 0x02, 0xFA, 0x02, 0xD4,
+    0xEB, 0xA7, 0x67, 0xE5, 0x80, 0x80, 0xA2, 0x3B, 0x4A, 0x16, 0x8A, 0x30, 0x5A, 0x31, 0x39, 0xFD,
+    0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+];
+
+/// A file that is embedded in a worksheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OleObject {
+    data: Vec<u8>,
+    filename: String,
+    preview: Image,
+}
+
+impl OleObject {
+    /// Read an object from a file. The object is labelled with the name of
+    /// the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`XlsxError::ObjectRead`] if the file can't be read.
+    pub fn new(path: impl AsRef<Path>) -> Result<OleObject, XlsxError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|error| XlsxError::ObjectRead {
+            path: path.display().to_string(),
+            error: error.to_string(),
+        })?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Ok(OleObject::from_bytes(&data, &filename))
+    }
+
+    /// Create an object from the data of a file and its name, like
+    /// `report.pdf`. The extension of the name decides how the object is
+    /// embedded.
+    pub fn from_bytes(data: &[u8], filename: &str) -> OleObject {
+        OleObject {
+            data: data.to_vec(),
+            filename: filename.to_string(),
+            preview: Image::from_bytes(&DEFAULT_PREVIEW).unwrap(),
+        }
+    }
+
+    /// Set the image that is shown for the object in the worksheet, and
+    /// decides its size. The default is a document icon of 48 by 48 pixels.
+    pub fn set_preview(mut self, image: &Image) -> OleObject {
+        self.preview = image.clone();
+        self
+    }
+
+    /// The name of the file of the object.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    // The preview image.
+    pub(crate) fn preview(&self) -> &Image {
+        &self.preview
+    }
+
+    // The ProgID of the application of the object, like `Excel.Sheet.12`.
+    pub(crate) fn prog_id(&self) -> &'static str {
+        match self.package() {
+            Some((_, prog_id, _)) => prog_id,
+            None => "Package",
+        }
+    }
+
+    // The type of the worksheet relationship to the embedding part.
+    pub(crate) fn relationship_type(&self) -> &'static str {
+        match self.package() {
+            Some(_) => "package",
+            None => "oleObject",
+        }
+    }
+
+    // The embedding part of the object, with its number.
+    pub(crate) fn embedding(&self, number: u32) -> Embedding {
+        let part_name = self.embedding_part_name(number);
+
+        match self.package() {
+            Some((_, _, content_type)) => Embedding {
+                part_name,
+                content_type,
+                data: self.data.clone(),
+            },
+            None => Embedding {
+                part_name,
+                content_type: OLE_OBJECT_CONTENT_TYPE,
+                data: self.package_compound_file(),
+            },
+        }
+    }
+
+    // The name of the embedding part of the object, with its number, without
+    // building the part.
+    pub(crate) fn embedding_part_name(&self, number: u32) -> String {
+        match self.package() {
+            Some((extension, _, _)) => format!("xl/embeddings/oleObject{number}.{extension}"),
+            None => format!("xl/embeddings/oleObject{number}.bin"),
+        }
+    }
+
+    // The extension, ProgID and content type of an Office document.
+    fn package(&self) -> Option<(&'static str, &'static str, &'static str)> {
+        let extension = Path::new(&self.filename).extension()?.to_str()?;
+
+        PACKAGES
+            .into_iter()
+            .find(|(package, _, _)| package.eq_ignore_ascii_case(extension))
+    }
+
+    // Wrap the file in a compound file with the streams of an OLE Package
+    // object.
+    fn package_compound_file(&self) -> Vec<u8> {
+        // The name of the file, in ASCII.
+        let name: Vec<u8> = self
+            .filename
+            .chars()
+            .map(|char| if char.is_ascii() { char as u8 } else { b'_' })
+            .chain([0])
+            .collect();
+
+        // The \x01CompObj stream, with the CLSID, user type and ProgID.
+        let mut comp_obj = vec![];
+        comp_obj.extend(0xFFFE_0001_u32.to_le_bytes());
+        comp_obj.extend(0x0000_0A03_u32.to_le_bytes());
+        comp_obj.extend(0xFFFF_FFFF_u32.to_le_bytes());
+        comp_obj.extend(PACKAGE_CLSID);
+        for string in [b"OLE Package\0".as_slice(), b"", b"Package\0"] {
+            comp_obj.extend((string.len() as u32).to_le_bytes());
+            comp_obj.extend(string);
+        }
+
+        // The \x01Ole10Native stream, with the label and paths of the file
+        // and its data, after the size of the rest of the stream.
+        let mut native = vec![];
+        native.extend(2_u16.to_le_bytes());
+        native.extend(&name);
+        native.extend(&name);
+        native.extend([0, 0, 3, 0]);
+        native.extend((name.len() as u32).to_le_bytes());
+        native.extend(&name);
+        native.extend((self.data.len() as u32).to_le_bytes());
+        native.extend(&self.data);
+        let native = [(native.len() as u32).to_le_bytes().as_slice(), &native].concat();
+
+        compound_file(
+            PACKAGE_CLSID,
+            &[("\u{1}CompObj", &comp_obj), ("\u{1}Ole10Native", &native)],
+        )
+    }
+}
+
+/// An `xl/embeddings/oleObjectN` part with the data of an embedded object,
+/// created with
+/// [`Worksheet::embeddings()`](crate::worksheet::Worksheet::embeddings).
+/// The part is stored with
+/// [`Packager::add_part()`](crate::packager::Packager::add_part), and needs
+/// an override for its content type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Embedding {
+    part_name: String,
+    content_type: &'static str,
+    data: Vec<u8>,
+}
+
+impl Embedding {
+    /// The name of the part, like `xl/embeddings/oleObject1.bin`.
+    pub fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    /// The content type of the part.
+    pub fn content_type(&self) -> &str {
+        self.content_type
+    }
+
+    /// The data of the part.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::OleObject;
+    use crate::compound_file::SIGNATURE;
+    use crate::error::XlsxError;
+    use crate::image::tests::png_data;
+    use crate::image::Image;
+
+    use pretty_assertions::assert_eq;
+
+    // Read a little-endian u32 from a file.
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_default_preview() {
+        let object = OleObject::from_bytes(b"data", "notes.txt");
+
+        assert_eq!(
+            (object.preview().width(), object.preview().height()),
+            (48, 48)
+        );
+
+        let preview = Image::from_bytes(&png_data(100, 60, 0)).unwrap();
+        let object = object.set_preview(&preview);
+        assert_eq!(object.preview(), &preview);
+    }
+
+    #[test]
+    fn test_package_embedding() {
+        let object = OleObject::from_bytes(b"PK\x03\x04", "Budget.XLSX");
+        let embedding = object.embedding(2);
+
+        assert_eq!(object.prog_id(), "Excel.Sheet.12");
+        assert_eq!(object.relationship_type(), "package");
+        assert_eq!(embedding.part_name(), "xl/embeddings/oleObject2.xlsx");
+        assert_eq!(
+            embedding.content_type(),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        );
+        assert_eq!(embedding.data(), b"PK\x03\x04");
+    }
+
+    #[test]
+    fn test_ole_package_embedding() {
+        let data: Vec<u8> = (0..5000).map(|index| index as u8).collect();
+        let object = OleObject::from_bytes(&data, "report.pdf");
+        let embedding = object.embedding(1);
+
+        assert_eq!(object.prog_id(), "Package");
+        assert_eq!(object.relationship_type(), "oleObject");
+        assert_eq!(embedding.part_name(), "xl/embeddings/oleObject1.bin");
+        assert_eq!(
+            embedding.content_type(),
+            "application/vnd.openxmlformats-officedocument.oleObject"
+        );
+
+        // The Ole10Native stream is larger than the mini stream cutoff, so
+        // it is stored in its own sectors after the mini stream.
+        let file = embedding.data();
+        assert_eq!(file[..8], SIGNATURE);
+        assert_eq!(file.len() % 512, 0);
+
+        // The directory has the root, \x01CompObj and \x01Ole10Native.
+        let directory = 512 * (read_u32(file, 48) as usize + 1);
+        let name = |entry: usize| {
+            let offset = directory + 128 * entry;
+            let length = file[offset + 64] as usize - 2;
+            let units: Vec<u16> = file[offset..offset + length]
+                .chunks(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16(&units).unwrap()
+        };
+        assert_eq!(name(0), "Root Entry");
+        assert_eq!(name(1), "\u{1}CompObj");
+        assert_eq!(name(2), "\u{1}Ole10Native");
+
+        // The data of the file follows the header of the Ole10Native stream.
+        let native = directory + 128 * 2;
+        let start = 512 * (read_u32(file, native + 116) as usize + 1);
+        let size = read_u32(file, native + 120) as usize;
+        let stream = &file[start..start + size];
+        assert_eq!(read_u32(stream, 0) as usize, size - 4);
+        assert_eq!(&stream[6..17], b"report.pdf\0");
+        assert_eq!(&stream[size - 5000..], data.as_slice());
+    }
+
+    #[test]
+    fn test_object_read_error() {
+        assert!(matches!(
+            OleObject::new("missing.pdf"),
+            Err(XlsxError::ObjectRead { path, .. }) if path == "missing.pdf"
+        ));
+    }
+}
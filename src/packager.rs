@@ -0,0 +1,1173 @@
+//! The packager that assembles the XML parts into an xlsx zip file.
+//!
+//! Each part is added with its part name, such as `xl/workbook.xml`, either
+//! as raw data or by writing it with an [`XMLWriter`]. Parts are compressed
+//! with deflate by default, or stored if that is smaller.
+//!
+//! Independent parts, such as large worksheets, can be written in parallel
+//! with [`Packager::add_xml_parts_parallel()`].
+//!
+//! Transforms, such as watermarks or external validators, can be registered
+//! with [`Packager::add_transform()`] to post-process the data of each part
+//! before it is compressed.
+//!
+//! The authors, timestamps and file path in the document properties can be
+//! replaced with constants with [`Packager::set_privacy()`], for files that
+//! are shared outside an organization or that need to be reproducible.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::packager::Packager;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! let file = File::create("test.xlsx")?;
+//! let mut packager = Packager::new(file);
+//!
+//! packager.add_xml_part("xl/sharedStrings.xml", |writer| {
+//!     writer.xml_declaration();
+//!     writer.xml_empty_tag("sst", &vec![("count", "0"), ("uniqueCount", "0")]);
+//! })?;
+//!
+//! packager.finish()?;
+//! # std::fs::remove_file("test.xlsx")?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::fmt;
+use std::io::{self, Error, ErrorKind, Write};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::thread;
+#[cfg(feature = "package")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "package")]
+use crate::core_properties::w3cdtf_datetime;
+use crate::deflate::deflate;
+#[cfg(feature = "package")]
+use crate::escape_data;
+#[cfg(feature = "package")]
+use crate::part::{relationships_part_name, XlsxPart};
+use crate::XMLWriter;
+
+// The zip record signatures.
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+
+// The zip version needed to extract deflated entries, 2.0.
+const ZIP_VERSION: u16 = 20;
+
+// The zip flag for UTF-8 encoded part names.
+const UTF8_FLAG: u16 = 0x0800;
+
+// The DOS date of the part timestamps, 1980-01-01. Using a fixed date keeps
+// the output reproducible.
+const DOS_DATE: u16 = 0x0021;
+
+// The signature of an OLE compound file, such as a `vbaProject.bin` part.
+const OLE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+// The size of the chunks of part data that are passed to the transforms.
+const TRANSFORM_CHUNK_SIZE: usize = 64 * 1024;
+
+// The CRC-32 lookup table.
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+// The standard CRC-32 used by zip.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFF_u32;
+
+    for byte in data {
+        crc = (crc >> 8) ^ CRC_TABLE[((crc ^ *byte as u32) & 0xFF) as usize];
+    }
+
+    !crc
+}
+
+/// The compression method for the parts of the package.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Store the parts without compression.
+    Stored,
+
+    /// Compress the parts with deflate, unless that would make them larger.
+    /// The default.
+    #[default]
+    Deflated,
+}
+
+/// A transform that post-processes the data of each part before it is
+/// packaged.
+///
+/// The data is passed to [`transform()`](PartTransform::transform) in chunks
+/// of up to 64KB, so transforms that only inspect or rewrite the data
+/// locally don't need to hold the whole part. Output can be held back
+/// between chunks and written in [`finish()`](PartTransform::finish), which
+/// is called after the last chunk of each part. An error stops the part
+/// from being added and is returned from [`Packager::add_part()`].
+///
+/// Closures with the signature of `transform()` implement the trait.
+/// Transforms must be `Send`, so that a packager can be moved to another
+/// thread with its transforms.
+pub trait PartTransform: Send {
+    /// Transform a chunk of the data of a part, appending the result to
+    /// `output`.
+    fn transform(&mut self, part_name: &str, chunk: &[u8], output: &mut Vec<u8>) -> io::Result<()>;
+
+    /// Append any held back output for the part after its last chunk. The
+    /// default does nothing.
+    fn finish(&mut self, _part_name: &str, _output: &mut Vec<u8>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<F> PartTransform for F
+where
+    F: FnMut(&str, &[u8], &mut Vec<u8>) -> io::Result<()> + Send,
+{
+    fn transform(&mut self, part_name: &str, chunk: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+        self(part_name, chunk, output)
+    }
+}
+
+/// A function that writes a part for
+/// [`Packager::add_xml_parts_parallel()`]. It can borrow data, such as a
+/// worksheet, that outlives the call.
+pub type XmlPartWriter<'a> = Box<dyn FnOnce(&mut XMLWriter<Vec<u8>>) + Send + 'a>;
+
+// The central directory information for a part.
+#[derive(Clone, Debug)]
+struct ZipEntry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+impl ZipEntry {
+    fn flags(&self) -> u16 {
+        if self.name.is_ascii() {
+            0
+        } else {
+            UTF8_FLAG
+        }
+    }
+
+    // The header fields that are common to the local and central headers:
+    // version needed, flags, method, time, date, crc, sizes and name length.
+    fn common_fields(&self, buffer: &mut Vec<u8>) {
+        buffer.extend(ZIP_VERSION.to_le_bytes());
+        buffer.extend(self.flags().to_le_bytes());
+        buffer.extend(self.method.to_le_bytes());
+        buffer.extend(0_u16.to_le_bytes());
+        buffer.extend(DOS_DATE.to_le_bytes());
+        buffer.extend(self.crc.to_le_bytes());
+        buffer.extend(self.compressed_size.to_le_bytes());
+        buffer.extend(self.size.to_le_bytes());
+        buffer.extend((self.name.len() as u16).to_le_bytes());
+        buffer.extend(0_u16.to_le_bytes());
+    }
+}
+
+/// Assemble the parts of an xlsx file into a zip archive.
+///
+/// The packager doesn't check that the package has the parts that Excel
+/// requires, only that the part names are valid and unique.
+pub struct Packager<W: Write> {
+    writer: W,
+    compression: Compression,
+    transforms: Vec<Box<dyn PartTransform>>,
+    #[cfg(feature = "package")]
+    privacy: Option<Privacy>,
+    entries: Vec<ZipEntry>,
+    offset: u64,
+}
+
+impl<W: Write + fmt::Debug> fmt::Debug for Packager<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("Packager");
+        debug
+            .field("writer", &self.writer)
+            .field("compression", &self.compression)
+            .field("transforms", &self.transforms.len());
+        #[cfg(feature = "package")]
+        debug.field("privacy", &self.privacy);
+        debug
+            .field("entries", &self.entries)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+/// The constants that replace the identifying metadata of a package in the
+/// privacy mode of [`Packager::set_privacy()`].
+#[cfg(feature = "package")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Privacy {
+    author: String,
+    company: String,
+    timestamp: SystemTime,
+}
+
+#[cfg(feature = "package")]
+impl Default for Privacy {
+    fn default() -> Self {
+        Privacy::new()
+    }
+}
+
+#[cfg(feature = "package")]
+impl Privacy {
+    /// Create the privacy constants, with an empty author and company and
+    /// the timestamp 1980-01-01T00:00:00Z, the date of the zip entries.
+    pub fn new() -> Privacy {
+        Privacy {
+            author: String::new(),
+            company: String::new(),
+            timestamp: UNIX_EPOCH + Duration::from_secs(315_532_800),
+        }
+    }
+
+    /// Set the name that replaces the author, last modified by and manager
+    /// properties.
+    pub fn set_author(mut self, author: &str) -> Privacy {
+        self.author = author.to_string();
+        self
+    }
+
+    /// Set the name that replaces the company property.
+    pub fn set_company(mut self, company: &str) -> Privacy {
+        self.company = company.to_string();
+        self
+    }
+
+    /// Set the time that replaces the created, modified and last printed
+    /// timestamps.
+    pub fn set_timestamp(mut self, timestamp: SystemTime) -> Privacy {
+        self.timestamp = timestamp;
+        self
+    }
+
+    // Replace the metadata in the parts that hold it. Other parts are
+    // unchanged.
+    fn scrub(&self, name: &str, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        let replacements: &[(&str, &str)] = match name {
+            "docProps/core.xml" => &[
+                ("dc:creator", &self.author),
+                ("cp:lastModifiedBy", &self.author),
+            ],
+            "docProps/app.xml" => &[("Manager", &self.author), ("Company", &self.company)],
+            "xl/workbook.xml" => &[],
+            _ => return Ok(data),
+        };
+
+        let mut xml = String::from_utf8(data).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("part '{name}' isn't UTF-8 XML"),
+            )
+        })?;
+
+        for (tag, text) in replacements {
+            xml = replace_element_text(&xml, tag, text);
+        }
+
+        if name == "docProps/core.xml" {
+            let timestamp = w3cdtf_datetime(self.timestamp);
+            for tag in ["dcterms:created", "dcterms:modified", "cp:lastPrinted"] {
+                xml = replace_element_text(&xml, tag, &timestamp);
+            }
+        }
+
+        if name == "xl/workbook.xml" {
+            xml = remove_abs_path(&xml);
+        }
+
+        Ok(xml.into_bytes())
+    }
+}
+
+impl<W: Write> Packager<W> {
+    /// Create a new packager that writes the zip archive to `writer`.
+    pub fn new(writer: W) -> Packager<W> {
+        Packager {
+            writer,
+            compression: Compression::default(),
+            transforms: vec![],
+            #[cfg(feature = "package")]
+            privacy: None,
+            entries: vec![],
+            offset: 0,
+        }
+    }
+
+    /// Set the compression method for the parts added after this call.
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Add a transform for the data of the parts added after this call.
+    /// Transforms are applied in the order that they are added, and each
+    /// one gets the output of the previous one.
+    ///
+    /// ```
+    /// use excel_xmlwriter::packager::Packager;
+    ///
+    /// let mut packager = Packager::new(vec![]);
+    ///
+    /// packager.add_transform(|part_name: &str, chunk: &[u8], output: &mut Vec<u8>| {
+    ///     if part_name.starts_with("customXml/") && chunk.contains(&0) {
+    ///         return Err(std::io::Error::other("NUL in custom XML"));
+    ///     }
+    ///     output.extend_from_slice(chunk);
+    ///     Ok(())
+    /// });
+    ///
+    /// assert!(packager.add_part("customXml/item1.xml", b"<a>\0</a>").is_err());
+    /// ```
+    pub fn add_transform(&mut self, transform: impl PartTransform + 'static) {
+        self.transforms.push(Box::new(transform));
+    }
+
+    /// Turn on the privacy mode for the parts added after this call, so that
+    /// no author names, timestamps or file paths leak from the metadata of
+    /// the package. They are replaced with the constants of `privacy`, after
+    /// any transforms:
+    ///
+    /// - `docProps/core.xml`: the author and last modified by properties,
+    ///   and the created, modified and last printed timestamps.
+    /// - `docProps/app.xml`: the manager and company properties.
+    /// - `xl/workbook.xml`: the `x15ac:absPath` path of the file that Excel
+    ///   writes, which is removed.
+    ///
+    /// The authors of comments are part of the content of the worksheets and
+    /// aren't changed.
+    ///
+    /// ```
+    /// use excel_xmlwriter::core_properties::Core;
+    /// use excel_xmlwriter::packager::{Packager, Privacy};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut core = Core::new();
+    /// core.set_author("Ann Smith");
+    ///
+    /// let mut packager = Packager::new(vec![]);
+    /// packager.set_privacy(Privacy::new().set_author("Anonymous"));
+    /// packager.add_xml_part("docProps/core.xml", |writer| core.assemble_xml_file(writer))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "package")]
+    pub fn set_privacy(&mut self, privacy: Privacy) {
+        self.privacy = Some(privacy);
+    }
+
+    /// Add a part to the package.
+    ///
+    /// Part names are relative to the root of the package, like
+    /// `xl/worksheets/sheet1.xml`, without a leading `/`. Part names that are
+    /// empty, that use `\` separators, or that have already been added, are
+    /// an `InvalidInput` error, as are parts larger than 4GB since the
+    /// packager doesn't support zip64.
+    ///
+    /// The data is passed through the transforms, if there are any, and the
+    /// privacy mode, if it is on, before it is compressed.
+    pub fn add_part(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("add_part", part = name).entered();
+
+        self.check_part_name(name)?;
+
+        let transformed = self.apply_transforms(name, data)?;
+        let data = transformed.as_deref().unwrap_or(data);
+
+        let size = u32::try_from(data.len())
+            .map_err(|_| invalid_input(format!("part '{name}' is larger than 4GB")))?;
+
+        let compressed = match self.compression {
+            Compression::Stored => None,
+            Compression::Deflated => Some(deflate(data)).filter(|c| c.len() < data.len()),
+        };
+        let (method, contents) = match &compressed {
+            Some(compressed) => (8, compressed.as_slice()),
+            None => (0, data),
+        };
+
+        let offset = u32::try_from(self.offset)
+            .map_err(|_| invalid_input("package is larger than 4GB".to_string()))?;
+
+        let entry = ZipEntry {
+            name: name.to_string(),
+            method,
+            crc: crc32(data),
+            compressed_size: contents.len() as u32,
+            size,
+            offset,
+        };
+
+        let mut header = vec![];
+        header.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        entry.common_fields(&mut header);
+        header.extend(name.as_bytes());
+
+        self.writer
+            .write_all(&header)
+            .and_then(|_| self.writer.write_all(contents))
+            .map_err(|error| {
+                Error::new(
+                    error.kind(),
+                    format!("couldn't write part '{name}': {error}"),
+                )
+            })?;
+
+        self.offset += (header.len() + contents.len()) as u64;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            size = entry.size,
+            compressed_size = entry.compressed_size,
+            "added part"
+        );
+
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    /// Add a part that is written with an [`XMLWriter`], such as the output
+    /// of one of the `assemble_xml_file()` methods. See
+    /// [`add_part()`](Packager::add_part) for the part name rules.
+    pub fn add_xml_part(
+        &mut self,
+        name: &str,
+        write_part: impl FnOnce(&mut XMLWriter<Vec<u8>>),
+    ) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("write_part", part = name).entered();
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_part_name(name);
+        write_part(&mut writer);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elements = writer.stats().elements(), "wrote part");
+
+        self.add_part(name, &writer.into_inner())
+    }
+
+    /// Write independent parts in parallel, on a pool of up to one thread per
+    /// CPU, and then add them to the package in the order that they are
+    /// given, so the package is the same as if they were added one at a time
+    /// with [`add_xml_part()`](Packager::add_xml_part). The parts are held
+    /// in memory until they have all been written. See
+    /// [`add_part()`](Packager::add_part) for the part name rules.
+    ///
+    /// Worksheets that share a [`Styles`](crate::styles::Styles) table can
+    /// be written in parallel with a clone of the table once all of their
+    /// formats have been added to it, and with inline strings instead of a
+    /// shared string table:
+    ///
+    /// ```
+    /// # #[cfg(feature = "worksheet")]
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// use excel_xmlwriter::packager::{Packager, XmlPartWriter};
+    /// use excel_xmlwriter::styles::{Format, Styles};
+    /// use excel_xmlwriter::worksheet::Worksheet;
+    ///
+    /// let bold = Format::new().set_bold();
+    /// let mut styles = Styles::new();
+    /// styles.xf_index(&bold);
+    ///
+    /// let worksheets = [Worksheet::new(), Worksheet::new()];
+    /// let mut parts: Vec<(String, XmlPartWriter)> = vec![];
+    ///
+    /// for (index, worksheet) in worksheets.iter().enumerate() {
+    ///     let mut styles = styles.clone();
+    ///     let bold = bold.clone();
+    ///     let name = format!("xl/worksheets/sheet{}.xml", index + 1);
+    ///
+    ///     parts.push((
+    ///         name,
+    ///         Box::new(move |writer| {
+    ///             let mut sheet = worksheet.start_streaming(writer, &mut styles);
+    ///             sheet.write_string_with_format(0, 0, "Total", &bold).unwrap();
+    ///             sheet.write_number(0, 1, index as f64).unwrap();
+    ///             sheet.finish();
+    ///         }),
+    ///     ));
+    /// }
+    ///
+    /// let mut packager = Packager::new(vec![]);
+    /// packager.add_xml_parts_parallel(parts)?;
+    /// packager.add_xml_part("xl/styles.xml", |writer| styles.assemble_xml_file(writer))?;
+    /// packager.finish()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "worksheet"))]
+    /// # fn main() {}
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The first error from [`add_part()`](Packager::add_part), in the order
+    /// of the parts. The parts before it are added to the package.
+    ///
+    /// # Panics
+    ///
+    /// Panics if writing a part panics, once the other threads have finished.
+    pub fn add_xml_parts_parallel(
+        &mut self,
+        parts: Vec<(String, XmlPartWriter<'_>)>,
+    ) -> io::Result<()> {
+        let workers = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(parts.len());
+
+        // Targets without threads, such as wasm32-unknown-unknown, have a
+        // parallelism of 1, and the parts are written on this thread.
+        if workers <= 1 {
+            for (name, write_part) in parts {
+                self.add_xml_part(&name, write_part)?;
+            }
+            return Ok(());
+        }
+
+        let jobs = Mutex::new(parts.into_iter().enumerate());
+        let written = Mutex::new(vec![]);
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let Some((index, (name, write_part))) = jobs.lock().unwrap().next() else {
+                        break;
+                    };
+
+                    let mut writer = XMLWriter::new(vec![]);
+                    writer.set_part_name(&name);
+                    write_part(&mut writer);
+
+                    written
+                        .lock()
+                        .unwrap()
+                        .push((index, name, writer.into_inner()));
+                });
+            }
+        });
+
+        let mut written = written.into_inner().unwrap();
+        written.sort_by_key(|(index, _, _)| *index);
+
+        for (_, name, data) in written {
+            self.add_part(&name, &data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a VBA project, the `vbaProject.bin` extracted from an existing
+    /// `.xlsm` file, as the `xl/vbaProject.bin` part so that its macros are
+    /// attached to the workbook. Data that isn't an OLE compound file is an
+    /// `InvalidInput` error.
+    ///
+    /// A macro-enabled workbook also needs the content types from
+    /// `ContentTypes::add_vba_project()` and a relationship from the
+    /// workbook, and should be saved with the `.xlsm` extension:
+    ///
+    /// ```
+    /// # #[cfg(feature = "package")]
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// use excel_xmlwriter::content_types::ContentTypes;
+    /// use excel_xmlwriter::packager::Packager;
+    /// use excel_xmlwriter::relationship::Relationships;
+    ///
+    /// # let vba_project = [[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1].as_slice(), &[0; 504]].concat();
+    /// let mut packager = Packager::new(vec![]);
+    /// let mut content_types = ContentTypes::new();
+    /// let mut workbook_relationships = Relationships::new();
+    ///
+    /// packager.add_vba_project(&vba_project)?;
+    /// content_types.add_vba_project();
+    /// workbook_relationships.add_office_relationship("2006/relationships/vbaProject", "vbaProject.bin");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "package"))]
+    /// # fn main() {}
+    /// ```
+    pub fn add_vba_project(&mut self, data: &[u8]) -> io::Result<()> {
+        if !data.starts_with(&OLE_SIGNATURE) {
+            return Err(invalid_input(
+                "the VBA project isn't an OLE compound file".to_string(),
+            ));
+        }
+
+        self.add_part("xl/vbaProject.bin", data)
+    }
+
+    /// Add a custom part, and its relationships part if it has any
+    /// relationships. Boxed parts can be added with `part.as_ref()`. The
+    /// content type override is added separately with
+    /// [`ContentTypes::add_custom_part()`](crate::content_types::ContentTypes::add_custom_part).
+    #[cfg(feature = "package")]
+    pub fn add_custom_part(&mut self, part: &dyn XlsxPart) -> io::Result<()> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("write_part", part = part.part_name()).entered();
+
+        let mut data = vec![];
+        let mut writer = XMLWriter::new(&mut data as &mut dyn Write);
+        writer.set_part_name(part.part_name());
+        part.write(&mut writer);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elements = writer.stats().elements(), "wrote part");
+
+        self.add_part(part.part_name(), &data)?;
+
+        let relationships = part.relationships();
+        if !relationships.is_empty() {
+            self.add_xml_part(&relationships_part_name(part.part_name()), |writer| {
+                relationships.assemble_xml_file(writer)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the zip central directory and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let num_entries = u16::try_from(self.entries.len())
+            .map_err(|_| invalid_input("package has more than 65535 parts".to_string()))?;
+        let directory_offset = u32::try_from(self.offset)
+            .map_err(|_| invalid_input("package is larger than 4GB".to_string()))?;
+
+        let mut directory = vec![];
+        for entry in &self.entries {
+            directory.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            directory.extend(ZIP_VERSION.to_le_bytes());
+            entry.common_fields(&mut directory);
+            // Comment length, disk number, internal and external attributes.
+            directory.extend([0; 10]);
+            directory.extend(entry.offset.to_le_bytes());
+            directory.extend(entry.name.as_bytes());
+        }
+
+        let directory_size = directory.len() as u32;
+
+        directory.extend(END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        directory.extend([0; 4]);
+        directory.extend(num_entries.to_le_bytes());
+        directory.extend(num_entries.to_le_bytes());
+        directory.extend(directory_size.to_le_bytes());
+        directory.extend(directory_offset.to_le_bytes());
+        directory.extend(0_u16.to_le_bytes());
+
+        self.writer.write_all(&directory)?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    // Pass the data of a part through each transform in turn, in chunks,
+    // and then through the privacy mode. Returns None if there are no
+    // transforms and the privacy mode is off.
+    fn apply_transforms(&mut self, name: &str, data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        #[cfg(feature = "package")]
+        let has_privacy = self.privacy.is_some();
+        #[cfg(not(feature = "package"))]
+        let has_privacy = false;
+
+        if self.transforms.is_empty() && !has_privacy {
+            return Ok(None);
+        }
+
+        let mut input = data.to_vec();
+        for transform in &mut self.transforms {
+            let mut output = Vec::with_capacity(input.len());
+            for chunk in input.chunks(TRANSFORM_CHUNK_SIZE) {
+                transform.transform(name, chunk, &mut output)?;
+            }
+            transform.finish(name, &mut output)?;
+            input = output;
+        }
+
+        #[cfg(feature = "package")]
+        if let Some(privacy) = &self.privacy {
+            input = privacy.scrub(name, input)?;
+        }
+
+        Ok(Some(input))
+    }
+
+    fn check_part_name(&self, name: &str) -> io::Result<()> {
+        if name.is_empty() || name.starts_with('/') || name.contains('\\') {
+            return Err(invalid_input(format!("invalid part name '{name}'")));
+        }
+
+        // Part names are case-insensitive.
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.name.eq_ignore_ascii_case(name))
+        {
+            return Err(invalid_input(format!("duplicate part name '{name}'")));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_input(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+// Replace the text of the elements with a tag in an XML part. Empty
+// elements, like `<Manager/>`, are left as they are.
+#[cfg(feature = "package")]
+fn replace_element_text(xml: &str, tag: &str, text: &str) -> String {
+    let start_tag = format!("<{tag}");
+    let end_tag = format!("</{tag}>");
+    let mut output = String::with_capacity(xml.len());
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&start_tag) {
+        let after = &rest[start + start_tag.len()..];
+        let Some(close) = after.find('>') else {
+            break;
+        };
+
+        // Skip tags that only start with the same name, like <CompanyX>, and
+        // empty elements.
+        let is_element = after.starts_with(['>', ' ', '\t', '\r', '\n']);
+        if !is_element || after[..close].ends_with('/') {
+            output.push_str(&rest[..start + start_tag.len()]);
+            rest = after;
+            continue;
+        }
+
+        let content_start = start + start_tag.len() + close + 1;
+        let Some(end) = rest[content_start..].find(&end_tag) else {
+            break;
+        };
+
+        output.push_str(&rest[..content_start]);
+        output.push_str(&escape_data(text));
+        rest = &rest[content_start + end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+// Remove the `x15ac:absPath` elements with the path of the file from a
+// workbook part, along with the `mc:AlternateContent` element that Excel
+// wraps them in.
+#[cfg(feature = "package")]
+fn remove_abs_path(xml: &str) -> String {
+    const ALTERNATE_START: &str = "<mc:AlternateContent";
+    const ALTERNATE_END: &str = "</mc:AlternateContent>";
+
+    let mut xml = xml.to_string();
+
+    while let Some(start) = xml.find("<x15ac:absPath") {
+        let mut end = match xml[start..].find("/>") {
+            Some(offset) if !xml[start..start + offset].contains('>') => start + offset + 2,
+            _ => match xml[start..].find("</x15ac:absPath>") {
+                Some(offset) => start + offset + "</x15ac:absPath>".len(),
+                None => xml.len(),
+            },
+        };
+        let mut start = start;
+
+        if let Some(alternate) = xml[..start].rfind(ALTERNATE_START) {
+            if !xml[alternate..start].contains(ALTERNATE_END) {
+                if let Some(offset) = xml[end..].find(ALTERNATE_END) {
+                    start = alternate;
+                    end += offset + ALTERNATE_END.len();
+                }
+            }
+        }
+
+        xml.replace_range(start..end, "");
+    }
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{crc32, Compression, Packager, PartTransform, XmlPartWriter, OLE_SIGNATURE};
+    use std::io::{self, ErrorKind};
+
+    use pretty_assertions::assert_eq;
+
+    fn read_u16(data: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414FA339
+        );
+    }
+
+    #[test]
+    fn test_add_xml_parts_parallel() {
+        let names: Vec<String> = (1..=8)
+            .map(|i| format!("xl/worksheets/sheet{i}.xml"))
+            .collect();
+
+        let parts: Vec<(String, XmlPartWriter)> = names
+            .iter()
+            .map(|name| {
+                let write_part: XmlPartWriter = Box::new(move |writer| {
+                    writer.xml_data_element("name", name, &vec![]);
+                });
+                (name.clone(), write_part)
+            })
+            .collect();
+
+        let mut parallel = Packager::new(vec![]);
+        parallel.add_xml_parts_parallel(parts).unwrap();
+
+        let mut sequential = Packager::new(vec![]);
+        for name in &names {
+            sequential
+                .add_xml_part(name, |writer| {
+                    writer.xml_data_element("name", name, &vec![]);
+                })
+                .unwrap();
+        }
+
+        assert_eq!(parallel.finish().unwrap(), sequential.finish().unwrap());
+    }
+
+    #[test]
+    fn test_add_xml_parts_parallel_error() {
+        let parts: Vec<(String, XmlPartWriter)> = vec![
+            (
+                "a.xml".to_string(),
+                Box::new(|writer| writer.xml_empty_tag("a", &vec![])),
+            ),
+            (
+                "a.xml".to_string(),
+                Box::new(|writer| writer.xml_empty_tag("b", &vec![])),
+            ),
+        ];
+
+        let mut packager = Packager::new(vec![]);
+        let error = packager.add_xml_parts_parallel(parts).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(packager.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_packager_on_thread() {
+        let mut packager = Packager::new(vec![]);
+        packager.add_transform(|_: &str, chunk: &[u8], output: &mut Vec<u8>| {
+            output.extend_from_slice(chunk);
+            Ok(())
+        });
+
+        let data = std::thread::spawn(move || {
+            packager.add_part("a.xml", b"<a/>").unwrap();
+            packager.finish().unwrap()
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(read_u32(&data, 0), 0x04034b50);
+    }
+
+    #[test]
+    fn test_stored_package() {
+        let mut packager = Packager::new(vec![]);
+        packager.set_compression(Compression::Stored);
+
+        packager.add_part("a.xml", b"<a/>").unwrap();
+        packager
+            .add_xml_part("b/b.xml", |writer| writer.xml_empty_tag("b", &vec![]))
+            .unwrap();
+
+        let zip = packager.finish().unwrap();
+
+        // The first local header and its data.
+        assert_eq!(read_u32(&zip, 0), 0x04034b50);
+        assert_eq!(read_u16(&zip, 8), 0);
+        assert_eq!(read_u32(&zip, 14), crc32(b"<a/>"));
+        assert_eq!(read_u32(&zip, 18), 4);
+        assert_eq!(&zip[30..35], b"a.xml");
+        assert_eq!(&zip[35..39], b"<a/>");
+
+        // The second local header.
+        assert_eq!(read_u32(&zip, 39), 0x04034b50);
+        assert_eq!(&zip[69..76], b"b/b.xml");
+        assert_eq!(&zip[76..80], b"<b/>");
+
+        // The central directory.
+        let end = zip.len() - 22;
+        assert_eq!(read_u32(&zip, end), 0x06054b50);
+        assert_eq!(read_u16(&zip, end + 10), 2);
+
+        let directory_offset = read_u32(&zip, end + 16) as usize;
+        assert_eq!(directory_offset, 80);
+        assert_eq!(read_u32(&zip, directory_offset), 0x02014b50);
+        assert_eq!(read_u32(&zip, directory_offset + 42), 0);
+        assert_eq!(&zip[directory_offset + 46..directory_offset + 51], b"a.xml");
+    }
+
+    #[test]
+    fn test_deflated_package() {
+        let data = "<row/>".repeat(100);
+
+        let mut packager = Packager::new(vec![]);
+        packager.add_part("small.xml", b"<a/>").unwrap();
+        packager.add_part("large.xml", data.as_bytes()).unwrap();
+        let zip = packager.finish().unwrap();
+
+        // Small parts are stored since deflate would make them larger.
+        assert_eq!(read_u16(&zip, 8), 0);
+
+        let offset = 30 + 9 + 4;
+        assert_eq!(read_u16(&zip, offset + 8), 8);
+        assert_eq!(read_u32(&zip, offset + 14), crc32(data.as_bytes()));
+        assert!(read_u32(&zip, offset + 18) < 100);
+        assert_eq!(read_u32(&zip, offset + 22), 600);
+    }
+
+    #[test]
+    fn test_part_name_errors() {
+        let mut packager = Packager::new(vec![]);
+        packager.add_part("xl/workbook.xml", b"").unwrap();
+
+        for name in ["", "/xl/styles.xml", "xl\\styles.xml", "XL/Workbook.xml"] {
+            let error = packager.add_part(name, b"").unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_vba_project() {
+        let mut packager = Packager::new(vec![]);
+
+        let error = packager.add_vba_project(b"Sub Macro()").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        let vba_project = [OLE_SIGNATURE.as_slice(), &[0; 64]].concat();
+        packager.add_vba_project(&vba_project).unwrap();
+
+        let error = packager.add_vba_project(&vba_project).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        let zip = packager.finish().unwrap();
+        assert_eq!(&zip[30..47], b"xl/vbaProject.bin");
+    }
+
+    #[test]
+    fn test_transforms() {
+        // A transform that counts the chunks and appends a comment.
+        struct Watermark(usize);
+
+        impl PartTransform for Watermark {
+            fn transform(
+                &mut self,
+                _part_name: &str,
+                chunk: &[u8],
+                output: &mut Vec<u8>,
+            ) -> io::Result<()> {
+                self.0 += 1;
+                output.extend_from_slice(chunk);
+                Ok(())
+            }
+
+            fn finish(&mut self, part_name: &str, output: &mut Vec<u8>) -> io::Result<()> {
+                if part_name.starts_with("customXml/") {
+                    output.extend_from_slice(format!("<!--{}-->", self.0).as_bytes());
+                }
+                Ok(())
+            }
+        }
+
+        let mut packager = Packager::new(vec![]);
+        packager.set_compression(Compression::Stored);
+        packager.add_transform(
+            |_: &str, chunk: &[u8], output: &mut Vec<u8>| -> io::Result<()> {
+                output.extend(chunk.iter().map(u8::to_ascii_uppercase));
+                Ok(())
+            },
+        );
+        packager.add_transform(Watermark(0));
+
+        packager.add_part("xl/a.xml", b"<a/>").unwrap();
+        packager
+            .add_part("customXml/b.xml", &vec![b'b'; 64 * 1024 + 1])
+            .unwrap();
+        let zip = packager.finish().unwrap();
+
+        assert_eq!(&zip[38..42], b"<A/>");
+        assert_eq!(read_u32(&zip, 14), crc32(b"<A/>"));
+
+        // The second part was passed in 2 chunks, plus 1 for the first part.
+        let offset = 42;
+        let size = read_u32(&zip, offset + 22) as usize;
+        let data_end = offset + 30 + 15 + size;
+        assert_eq!(size, 64 * 1024 + 1 + 8);
+        assert_eq!(&zip[data_end - 9..data_end], b"B<!--3-->");
+    }
+
+    #[cfg(feature = "package")]
+    #[test]
+    fn test_privacy() {
+        use super::Privacy;
+        use crate::app::App;
+        use crate::core_properties::Core;
+        use std::time::SystemTime;
+
+        let mut core = Core::new();
+        core.set_author("Ann Smith");
+        core.set_title("Sales");
+        core.set_created(SystemTime::now());
+
+        let mut app = App::new();
+        app.set_company("Acme & Co");
+        app.set_manager("Bob Jones");
+
+        let workbook = concat!(
+            r#"<workbook><fileVersion appName="xl"/><mc:AlternateContent "#,
+            r#"xmlns:mc="urn:mc"><mc:Choice Requires="x15"><x15ac:absPath "#,
+            r#"url="C:\Users\ann\Documents\" xmlns:x15ac="urn:x15ac"/></mc:Choice>"#,
+            r#"</mc:AlternateContent><sheets/></workbook>"#,
+        );
+
+        let mut packager = Packager::new(vec![]);
+        packager.set_compression(Compression::Stored);
+        packager
+            .add_part("xl/styles.xml", b"<styleSheet/>")
+            .unwrap();
+        packager.set_privacy(Privacy::new().set_author("Anonymous").set_company("A&B"));
+        packager
+            .add_xml_part("docProps/core.xml", |writer| core.assemble_xml_file(writer))
+            .unwrap();
+        packager
+            .add_xml_part("docProps/app.xml", |writer| app.assemble_xml_file(writer))
+            .unwrap();
+        packager
+            .add_part("xl/workbook.xml", workbook.as_bytes())
+            .unwrap();
+        let zip = String::from_utf8_lossy(&packager.finish().unwrap()).to_string();
+
+        for leak in ["Ann", "Bob", "Acme", "absPath", "C:\\", "AlternateContent"] {
+            assert!(!zip.contains(leak), "'{leak}' isn't scrubbed");
+        }
+        for expected in [
+            "<dc:title>Sales</dc:title><dc:creator>Anonymous</dc:creator>",
+            "<cp:lastModifiedBy>Anonymous</cp:lastModifiedBy>",
+            r#"<dcterms:created xsi:type="dcterms:W3CDTF">1980-01-01T00:00:00Z</dcterms:created>"#,
+            r#"<dcterms:modified xsi:type="dcterms:W3CDTF">1980-01-01T00:00:00Z</dcterms:modified>"#,
+            "<Manager>Anonymous</Manager><Company>A&amp;B</Company>",
+            r#"<workbook><fileVersion appName="xl"/><sheets/></workbook>"#,
+        ] {
+            assert!(zip.contains(expected), "'{expected}' is missing");
+        }
+    }
+
+    #[cfg(feature = "package")]
+    #[test]
+    fn test_custom_parts() {
+        use crate::part::XlsxPart;
+        use crate::relationship::Relationships;
+        use crate::XMLWriter;
+        use std::io::Write;
+
+        struct Item(Option<&'static str>);
+
+        impl XlsxPart for Item {
+            fn part_name(&self) -> &str {
+                "customXml/item1.xml"
+            }
+
+            fn content_type(&self) -> &str {
+                "application/xml"
+            }
+
+            fn relationships(&self) -> Relationships {
+                let mut relationships = Relationships::new();
+                if let Some(target) = self.0 {
+                    relationships.add_document_relationship("customXmlProps", target);
+                }
+                relationships
+            }
+
+            fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+                writer.xml_empty_tag("item", &vec![]);
+            }
+        }
+
+        let parts: Vec<Box<dyn XlsxPart>> = vec![Box::new(Item(None))];
+        let mut packager = Packager::new(vec![]);
+        packager.set_compression(Compression::Stored);
+        for part in &parts {
+            packager.add_custom_part(part.as_ref()).unwrap();
+        }
+        let zip = packager.finish().unwrap();
+
+        // A part without relationships is added on its own.
+        assert_eq!(&zip[30..49], b"customXml/item1.xml");
+        assert_eq!(&zip[49..56], b"<item/>");
+        assert_eq!(read_u16(&zip, zip.len() - 12), 1);
+
+        let mut packager = Packager::new(vec![]);
+        packager.set_compression(Compression::Stored);
+        packager
+            .add_custom_part(&Item(Some("itemProps1.xml")))
+            .unwrap();
+        let zip = packager.finish().unwrap();
+
+        let offset = 56;
+        assert_eq!(read_u16(&zip, zip.len() - 12), 2);
+        assert_eq!(
+            &zip[offset + 30..offset + 60],
+            b"customXml/_rels/item1.xml.rels"
+        );
+    }
+}
@@ -0,0 +1,338 @@
+//! The page setup of a worksheet: margins, orientation, paper size, scaling
+//! and the header and footer.
+//!
+//! A [`PageSetup`] is built like a [`Format`](crate::styles::Format) and
+//! added to a worksheet with
+//! [`Worksheet::set_page_setup()`](crate::worksheet::Worksheet::set_page_setup).
+//! It is written to the `<pageMargins>`, `<pageSetup>` and `<headerFooter>`
+//! elements of the worksheet part.
+//!
+//! Headers and footers use Excel's control codes, such as `&L`, `&C` and
+//! `&R` for the left, center and right sections, `&P` for the page number
+//! and `&N` for the number of pages. A literal `&` is written as `&&`.
+//!
+//! ```
+//! use excel_xmlwriter::page_setup::PageSetup;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let page_setup = PageSetup::new()
+//!     .set_landscape()
+//!     .set_paper_size(9)
+//!     .set_fit_to_pages(1, 0)
+//!     .set_header("&CQuarterly Sales")
+//!     .set_footer("&LConfidential&RPage &P of &N");
+//!
+//! let mut worksheet = Worksheet::new();
+//! worksheet.set_page_setup(&page_setup);
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::XMLWriter;
+
+// Excel's limit on the length of a header or footer, including the control
+// codes.
+const MAX_HEADER_FOOTER_LENGTH: usize = 255;
+
+/// The page orientation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Orientation {
+    /// Portrait. The default.
+    #[default]
+    Portrait,
+
+    /// Landscape.
+    Landscape,
+}
+
+/// The page setup of a worksheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageSetup {
+    orientation: Orientation,
+    paper_size: u8,
+    scale: u16,
+    fit_to_pages: Option<(u16, u16)>,
+    margins: [f64; 4],
+    header_margin: f64,
+    footer_margin: f64,
+    header: String,
+    footer: String,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSetup {
+            orientation: Orientation::Portrait,
+            paper_size: 0,
+            scale: 100,
+            fit_to_pages: None,
+            margins: [0.7, 0.7, 0.75, 0.75],
+            header_margin: 0.3,
+            footer_margin: 0.3,
+            header: String::new(),
+            footer: String::new(),
+        }
+    }
+}
+
+impl PageSetup {
+    /// Create a new page setup with Excel's defaults: portrait, the printer's
+    /// default paper size, 100% scale and Excel's normal margins.
+    pub fn new() -> PageSetup {
+        PageSetup::default()
+    }
+
+    /// Set the landscape orientation.
+    pub fn set_landscape(mut self) -> PageSetup {
+        self.orientation = Orientation::Landscape;
+        self
+    }
+
+    /// Set the portrait orientation. This is the default.
+    pub fn set_portrait(mut self) -> PageSetup {
+        self.orientation = Orientation::Portrait;
+        self
+    }
+
+    /// Set the paper size by Excel's index, for example 1 for Letter, 5 for
+    /// Legal or 9 for A4. The default, 0, is the printer's default.
+    pub fn set_paper_size(mut self, paper_size: u8) -> PageSetup {
+        self.paper_size = paper_size;
+        self
+    }
+
+    /// Set the print scale as a percentage in the range 10 to 400. The
+    /// default is 100. The scale is ignored by Excel if the page setup also
+    /// fits the worksheet to pages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scale is outside Excel's range.
+    pub fn set_print_scale(mut self, scale: u16) -> PageSetup {
+        assert!(
+            (10..=400).contains(&scale),
+            "print scale {scale} is outside Excel's range of 10 to 400"
+        );
+
+        self.scale = scale;
+        self
+    }
+
+    /// Fit the printed worksheet to a number of pages wide and high. A value
+    /// of 0 means as many pages as needed in that direction, so
+    /// `set_fit_to_pages(1, 0)` fits the worksheet to the width of a page.
+    pub fn set_fit_to_pages(mut self, width: u16, height: u16) -> PageSetup {
+        self.fit_to_pages = Some((width, height));
+        self
+    }
+
+    /// Set the left, right, top and bottom page margins in inches. The
+    /// defaults are 0.7, 0.7, 0.75 and 0.75.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a margin is negative or not finite.
+    pub fn set_margins(mut self, left: f64, right: f64, top: f64, bottom: f64) -> PageSetup {
+        self.margins = [left, right, top, bottom];
+        self.margins.iter().for_each(|margin| check_margin(*margin));
+        self
+    }
+
+    /// Set the header and footer margins in inches. The defaults are 0.3.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a margin is negative or not finite.
+    pub fn set_header_footer_margins(mut self, header: f64, footer: f64) -> PageSetup {
+        check_margin(header);
+        check_margin(footer);
+
+        self.header_margin = header;
+        self.footer_margin = footer;
+        self
+    }
+
+    /// Set the page header, with Excel's control codes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header is longer than Excel's limit of 255 characters.
+    pub fn set_header(mut self, header: &str) -> PageSetup {
+        check_header_footer(header);
+
+        self.header = header.to_string();
+        self
+    }
+
+    /// Set the page footer, with Excel's control codes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the footer is longer than Excel's limit of 255 characters.
+    pub fn set_footer(mut self, footer: &str) -> PageSetup {
+        check_header_footer(footer);
+
+        self.footer = footer.to_string();
+        self
+    }
+
+    // Check if the worksheet is fitted to pages. Excel also needs the
+    // `fitToPage` property in the `<sheetPr>` element of the worksheet.
+    pub(crate) fn fit_to_page(&self) -> bool {
+        self.fit_to_pages.is_some()
+    }
+
+    // Write the <pageMargins>, <pageSetup> and <headerFooter> elements. The
+    // <pageSetup> and <headerFooter> elements are only written if they differ
+    // from the defaults.
+    pub(crate) fn write_elements(&self, writer: &mut XMLWriter<impl Write>) {
+        let [left, right, top, bottom] = self.margins.map(|margin| margin.to_string());
+        let header = self.header_margin.to_string();
+        let footer = self.footer_margin.to_string();
+
+        let attributes = vec![
+            ("left", left.as_str()),
+            ("right", right.as_str()),
+            ("top", top.as_str()),
+            ("bottom", bottom.as_str()),
+            ("header", header.as_str()),
+            ("footer", footer.as_str()),
+        ];
+        writer.xml_empty_tag("pageMargins", &attributes);
+
+        self.write_page_setup(writer);
+        self.write_header_footer(writer);
+    }
+
+    // Write the <pageSetup> element.
+    fn write_page_setup(&self, writer: &mut XMLWriter<impl Write>) {
+        let paper_size = self.paper_size.to_string();
+        let scale = self.scale.to_string();
+        let (fit_width, fit_height) = self.fit_to_pages.unwrap_or((1, 1));
+        let fit_width = fit_width.to_string();
+        let fit_height = fit_height.to_string();
+
+        let mut attributes = vec![];
+        if self.paper_size != 0 {
+            attributes.push(("paperSize", paper_size.as_str()));
+        }
+        if self.scale != 100 {
+            attributes.push(("scale", scale.as_str()));
+        }
+        if fit_width != "1" {
+            attributes.push(("fitToWidth", fit_width.as_str()));
+        }
+        if fit_height != "1" {
+            attributes.push(("fitToHeight", fit_height.as_str()));
+        }
+        if self.orientation == Orientation::Landscape {
+            attributes.push(("orientation", "landscape"));
+        }
+
+        if !attributes.is_empty() {
+            writer.xml_empty_tag("pageSetup", &attributes);
+        }
+    }
+
+    // Write the <headerFooter> element.
+    fn write_header_footer(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.header.is_empty() && self.footer.is_empty() {
+            return;
+        }
+
+        writer.xml_start_tag("headerFooter", &vec![]);
+        if !self.header.is_empty() {
+            writer.xml_data_element("oddHeader", &self.header, &vec![]);
+        }
+        if !self.footer.is_empty() {
+            writer.xml_data_element("oddFooter", &self.footer, &vec![]);
+        }
+        writer.xml_end_tag("headerFooter");
+    }
+}
+
+fn check_margin(margin: f64) {
+    assert!(
+        margin.is_finite() && margin >= 0.0,
+        "margin {margin} must be a positive number of inches"
+    );
+}
+
+fn check_header_footer(string: &str) {
+    let length = string.chars().count();
+    assert!(
+        length <= MAX_HEADER_FOOTER_LENGTH,
+        "header or footer of {length} characters is longer than Excel's limit of 255"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::PageSetup;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn write_elements(page_setup: &PageSetup) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_check_attribute_order(true);
+
+        page_setup.write_elements(&mut writer);
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_default_page_setup() {
+        let expected = r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/>"#;
+
+        assert_eq!(write_elements(&PageSetup::new()), expected);
+        assert!(!PageSetup::new().fit_to_page());
+    }
+
+    #[test]
+    fn test_page_setup() {
+        let expected = concat!(
+            r#"<pageMargins left="0.25" right="0.25" top="1" bottom="1" header="0.5" footer="0"/>"#,
+            r#"<pageSetup paperSize="9" scale="75" fitToHeight="0" orientation="landscape"/>"#,
+            r#"<headerFooter><oddHeader>&amp;CSales &amp;&amp; Costs</oddHeader>"#,
+            r#"<oddFooter>&amp;LConfidential&amp;RPage &amp;P of &amp;N</oddFooter></headerFooter>"#,
+        );
+
+        let page_setup = PageSetup::new()
+            .set_landscape()
+            .set_paper_size(9)
+            .set_print_scale(75)
+            .set_fit_to_pages(1, 0)
+            .set_margins(0.25, 0.25, 1.0, 1.0)
+            .set_header_footer_margins(0.5, 0.0)
+            .set_header("&CSales && Costs")
+            .set_footer("&LConfidential&RPage &P of &N");
+
+        assert_eq!(write_elements(&page_setup), expected);
+        assert!(page_setup.fit_to_page());
+    }
+
+    #[test]
+    fn test_footer_only() {
+        let expected = concat!(
+            r#"<pageSetup fitToWidth="2" fitToHeight="3"/>"#,
+            r#"<headerFooter><oddFooter>&amp;CPage &amp;P</oddFooter></headerFooter>"#,
+        );
+
+        let page_setup = PageSetup::new()
+            .set_fit_to_pages(2, 3)
+            .set_footer("&CPage &P");
+
+        assert!(write_elements(&page_setup).ends_with(expected));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_header_too_long() {
+        let _ = PageSetup::new().set_header(&"a".repeat(256));
+    }
+}
@@ -0,0 +1,108 @@
+//! The trait for custom part writers.
+//!
+//! Parts that the crate doesn't write, such as `customXml` items or
+//! proprietary parts, can implement [`XlsxPart`] and be added to a package
+//! alongside the built-in parts with
+//! [`Packager::add_custom_part()`](crate::packager::Packager::add_custom_part)
+//! and [`ContentTypes::add_custom_part()`].
+//!
+//! The relationship from the parent part to the custom part, for example
+//! from `xl/workbook.xml` to a `customXml` item, is still added to the
+//! parent's [`Relationships`] by the caller.
+//!
+//! ```
+//! use std::io::Write;
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::part::XlsxPart;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! struct Label(String);
+//!
+//! impl XlsxPart for Label {
+//!     fn part_name(&self) -> &str {
+//!         "customXml/item1.xml"
+//!     }
+//!
+//!     fn content_type(&self) -> &str {
+//!         "application/xml"
+//!     }
+//!
+//!     fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+//!         writer.xml_declaration();
+//!         writer.xml_data_element("label", &self.0, &vec![]);
+//!     }
+//! }
+//!
+//! let parts: Vec<Box<dyn XlsxPart>> = vec![Box::new(Label("Internal".to_string()))];
+//!
+//! let mut content_types = ContentTypes::new();
+//! for part in &parts {
+//!     content_types.add_custom_part(part.as_ref());
+//! }
+//! ```
+//!
+//! [`ContentTypes::add_custom_part()`]: crate::content_types::ContentTypes::add_custom_part
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::relationship::Relationships;
+use crate::XMLWriter;
+
+/// A part of an xlsx package that is written outside of the crate.
+///
+/// The trait is object safe so that parts of different types can be stored
+/// as `Box<dyn XlsxPart>`.
+pub trait XlsxPart {
+    /// The part name, relative to the root of the package and without a
+    /// leading `/`, like `customXml/item1.xml`.
+    fn part_name(&self) -> &str;
+
+    /// The content type of the part, such as `application/xml`.
+    fn content_type(&self) -> &str;
+
+    /// The relationships from the part to other parts or external resources.
+    /// They are written to the `_rels/<name>.rels` part next to the part if
+    /// there are any. The default is no relationships.
+    fn relationships(&self) -> Relationships {
+        Relationships::new()
+    }
+
+    /// Write the XML of the part.
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>);
+}
+
+/// The name of the relationships part for a part name, like
+/// `customXml/_rels/item1.xml.rels` for `customXml/item1.xml`.
+pub fn relationships_part_name(part_name: &str) -> String {
+    match part_name.rsplit_once('/') {
+        Some((directory, name)) => format!("{directory}/_rels/{name}.rels"),
+        None => format!("_rels/{part_name}.rels"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::relationships_part_name;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_relationships_part_name() {
+        let tests = [
+            ("customXml/item1.xml", "customXml/_rels/item1.xml.rels"),
+            ("xl/workbook.xml", "xl/_rels/workbook.xml.rels"),
+            (
+                "xl/worksheets/sheet1.xml",
+                "xl/worksheets/_rels/sheet1.xml.rels",
+            ),
+            ("item.xml", "_rels/item.xml.rels"),
+        ];
+
+        for (part_name, expected) in tests {
+            assert_eq!(relationships_part_name(part_name), expected);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! The protection of a worksheet, which stops users from changing the locked
+//! cells and, optionally, from formatting, inserting or deleting.
+//!
+//! The options are built like a [`Format`](crate::styles::Format) and the
+//! worksheet is protected with
+//! [`Worksheet::protect_sheet()`](crate::worksheet::Worksheet::protect_sheet),
+//! which writes the `<sheetProtection>` element. Each option allows users to
+//! do something in the protected worksheet. By default they can only select
+//! cells, like a worksheet protected in Excel.
+//!
+//! The optional password is stored with Excel's legacy 16-bit hash, which
+//! only deters casual changes. It isn't encryption. The same hash is used
+//! for the protection of the workbook structure with
+//! [`Workbook::protect_workbook()`](crate::workbook::Workbook::protect_workbook).
+//!
+//! ```
+//! use excel_xmlwriter::protection::ProtectionOptions;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let options = ProtectionOptions::new()
+//!     .set_format_columns(true)
+//!     .set_sort(true)
+//!     .set_autofilter(true);
+//!
+//! let mut worksheet = Worksheet::new();
+//! worksheet.protect_sheet("password", &options);
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::XMLWriter;
+
+/// The actions that users are allowed in a protected worksheet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtectionOptions {
+    select_locked_cells: bool,
+    select_unlocked_cells: bool,
+    format_cells: bool,
+    format_columns: bool,
+    format_rows: bool,
+    insert_columns: bool,
+    insert_rows: bool,
+    insert_hyperlinks: bool,
+    delete_columns: bool,
+    delete_rows: bool,
+    sort: bool,
+    autofilter: bool,
+    pivot_tables: bool,
+    edit_objects: bool,
+    edit_scenarios: bool,
+}
+
+impl Default for ProtectionOptions {
+    fn default() -> Self {
+        ProtectionOptions {
+            select_locked_cells: true,
+            select_unlocked_cells: true,
+            format_cells: false,
+            format_columns: false,
+            format_rows: false,
+            insert_columns: false,
+            insert_rows: false,
+            insert_hyperlinks: false,
+            delete_columns: false,
+            delete_rows: false,
+            sort: false,
+            autofilter: false,
+            pivot_tables: false,
+            edit_objects: false,
+            edit_scenarios: false,
+        }
+    }
+}
+
+impl ProtectionOptions {
+    /// Create the options with Excel's defaults, which only allow users to
+    /// select the locked and unlocked cells.
+    pub fn new() -> ProtectionOptions {
+        ProtectionOptions::default()
+    }
+
+    /// Set whether users can select locked cells. The default is `true`.
+    pub fn set_select_locked_cells(mut self, allow: bool) -> ProtectionOptions {
+        self.select_locked_cells = allow;
+        self
+    }
+
+    /// Set whether users can select unlocked cells. The default is `true`.
+    pub fn set_select_unlocked_cells(mut self, allow: bool) -> ProtectionOptions {
+        self.select_unlocked_cells = allow;
+        self
+    }
+
+    /// Set whether users can format cells. The default is `false`.
+    pub fn set_format_cells(mut self, allow: bool) -> ProtectionOptions {
+        self.format_cells = allow;
+        self
+    }
+
+    /// Set whether users can format columns, including their width. The
+    /// default is `false`.
+    pub fn set_format_columns(mut self, allow: bool) -> ProtectionOptions {
+        self.format_columns = allow;
+        self
+    }
+
+    /// Set whether users can format rows, including their height. The default
+    /// is `false`.
+    pub fn set_format_rows(mut self, allow: bool) -> ProtectionOptions {
+        self.format_rows = allow;
+        self
+    }
+
+    /// Set whether users can insert columns. The default is `false`.
+    pub fn set_insert_columns(mut self, allow: bool) -> ProtectionOptions {
+        self.insert_columns = allow;
+        self
+    }
+
+    /// Set whether users can insert rows. The default is `false`.
+    pub fn set_insert_rows(mut self, allow: bool) -> ProtectionOptions {
+        self.insert_rows = allow;
+        self
+    }
+
+    /// Set whether users can insert hyperlinks. The default is `false`.
+    pub fn set_insert_hyperlinks(mut self, allow: bool) -> ProtectionOptions {
+        self.insert_hyperlinks = allow;
+        self
+    }
+
+    /// Set whether users can delete columns. The default is `false`.
+    pub fn set_delete_columns(mut self, allow: bool) -> ProtectionOptions {
+        self.delete_columns = allow;
+        self
+    }
+
+    /// Set whether users can delete rows. The default is `false`.
+    pub fn set_delete_rows(mut self, allow: bool) -> ProtectionOptions {
+        self.delete_rows = allow;
+        self
+    }
+
+    /// Set whether users can sort ranges of unlocked cells. The default is
+    /// `false`.
+    pub fn set_sort(mut self, allow: bool) -> ProtectionOptions {
+        self.sort = allow;
+        self
+    }
+
+    /// Set whether users can use existing autofilters. The default is
+    /// `false`.
+    pub fn set_autofilter(mut self, allow: bool) -> ProtectionOptions {
+        self.autofilter = allow;
+        self
+    }
+
+    /// Set whether users can use pivot tables. The default is `false`.
+    pub fn set_pivot_tables(mut self, allow: bool) -> ProtectionOptions {
+        self.pivot_tables = allow;
+        self
+    }
+
+    /// Set whether users can edit objects such as images and charts. The
+    /// default is `false`.
+    pub fn set_edit_objects(mut self, allow: bool) -> ProtectionOptions {
+        self.edit_objects = allow;
+        self
+    }
+
+    /// Set whether users can edit scenarios. The default is `false`.
+    pub fn set_edit_scenarios(mut self, allow: bool) -> ProtectionOptions {
+        self.edit_scenarios = allow;
+        self
+    }
+
+    // Write the <sheetProtection> element, with the hash of the password if
+    // it isn't empty. The attributes are the things that are protected, so
+    // an allowed action is written as "0", or omitted, the opposite of the
+    // option.
+    pub(crate) fn write_sheet_protection(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        password: &str,
+    ) {
+        let hash = format!("{:X}", password_hash(password));
+
+        let mut attributes = vec![];
+        if !password.is_empty() {
+            attributes.push(("password", hash.as_str()));
+        }
+        attributes.push(("sheet", "1"));
+        if !self.edit_objects {
+            attributes.push(("objects", "1"));
+        }
+        if !self.edit_scenarios {
+            attributes.push(("scenarios", "1"));
+        }
+
+        // These actions are protected unless they are allowed.
+        let allowed = [
+            ("formatCells", self.format_cells),
+            ("formatColumns", self.format_columns),
+            ("formatRows", self.format_rows),
+            ("insertColumns", self.insert_columns),
+            ("insertRows", self.insert_rows),
+            ("insertHyperlinks", self.insert_hyperlinks),
+            ("deleteColumns", self.delete_columns),
+            ("deleteRows", self.delete_rows),
+        ];
+        for (name, allow) in allowed {
+            if allow {
+                attributes.push((name, "0"));
+            }
+        }
+
+        if !self.select_locked_cells {
+            attributes.push(("selectLockedCells", "1"));
+        }
+
+        let allowed = [
+            ("sort", self.sort),
+            ("autoFilter", self.autofilter),
+            ("pivotTables", self.pivot_tables),
+        ];
+        for (name, allow) in allowed {
+            if allow {
+                attributes.push((name, "0"));
+            }
+        }
+
+        if !self.select_unlocked_cells {
+            attributes.push(("selectUnlockedCells", "1"));
+        }
+
+        writer.xml_empty_tag("sheetProtection", &attributes);
+    }
+}
+
+// Excel's legacy 16-bit password hash. Each character is rotated left within
+// 15 bits by its position, starting at 1, and the results are combined with
+// the length and a constant.
+pub(crate) fn password_hash(password: &str) -> u16 {
+    let mut hash: u16 = 0;
+    let mut length: u16 = 0;
+
+    for (index, char) in password.chars().enumerate() {
+        let rotation = (index + 1) % 15;
+        let char = (char as u32 & 0x7FFF) as u16;
+        let rotated = ((char << rotation) | (char >> (15 - rotation))) & 0x7FFF;
+
+        hash ^= rotated;
+        length = length.wrapping_add(1);
+    }
+
+    hash ^ length ^ 0xCE4B
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{password_hash, ProtectionOptions};
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn write_protection(options: &ProtectionOptions, password: &str) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        options.write_sheet_protection(&mut writer, password);
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_password_hash() {
+        assert_eq!(password_hash("password"), 0x83AF);
+        assert_eq!(password_hash("test"), 0xCBEB);
+        assert_eq!(password_hash("abcdefghijklmnopq"), 0xC786);
+        assert_eq!(password_hash(""), 0xCE4B);
+    }
+
+    #[test]
+    fn test_default_protection() {
+        assert_eq!(
+            write_protection(&ProtectionOptions::new(), "password"),
+            r#"<sheetProtection password="83AF" sheet="1" objects="1" scenarios="1"/>"#
+        );
+        assert_eq!(
+            write_protection(&ProtectionOptions::new(), ""),
+            r#"<sheetProtection sheet="1" objects="1" scenarios="1"/>"#
+        );
+    }
+
+    #[test]
+    fn test_protection_options() {
+        let options = ProtectionOptions::new()
+            .set_select_locked_cells(false)
+            .set_select_unlocked_cells(false)
+            .set_format_cells(true)
+            .set_format_columns(true)
+            .set_format_rows(true)
+            .set_insert_columns(true)
+            .set_insert_rows(true)
+            .set_insert_hyperlinks(true)
+            .set_delete_columns(true)
+            .set_delete_rows(true)
+            .set_sort(true)
+            .set_autofilter(true)
+            .set_pivot_tables(true)
+            .set_edit_objects(true)
+            .set_edit_scenarios(true);
+
+        let expected = concat!(
+            r#"<sheetProtection sheet="1" formatCells="0" formatColumns="0" formatRows="0" "#,
+            r#"insertColumns="0" insertRows="0" insertHyperlinks="0" deleteColumns="0" "#,
+            r#"deleteRows="0" selectLockedCells="1" sort="0" autoFilter="0" pivotTables="0" "#,
+            r#"selectUnlockedCells="1"/>"#,
+        );
+
+        assert_eq!(write_protection(&options, ""), expected);
+    }
+}
@@ -0,0 +1,252 @@
+//! The writer for the `.rels` relationship parts, such as `_rels/.rels` and
+//! `xl/_rels/workbook.xml.rels`.
+//!
+//! Relationships link a part to the parts, or external resources, that it
+//! refers to. Each relationship gets a sequential id, `rId1`, `rId2`, etc.,
+//! in the order it is added.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::relationship::Relationships;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut relationships = Relationships::new();
+//!
+//! let id = relationships.add_document_relationship("worksheet", "worksheets/sheet1.xml");
+//! assert_eq!(id, "rId1");
+//!
+//! relationships.assemble_xml_file(&mut writer);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{PACKAGE_RELATIONSHIPS_NS, RELATIONSHIPS_NS};
+use crate::XMLWriter;
+
+const SCHEMA_OFFICE: &str = "http://schemas.microsoft.com/office";
+
+// A single <Relationship> element.
+#[derive(Clone, Debug)]
+struct Relationship {
+    rel_type: String,
+    target: String,
+    target_mode: Option<String>,
+}
+
+/// The relationships of a part.
+#[derive(Debug, Default)]
+pub struct Relationships {
+    relationships: Vec<Relationship>,
+}
+
+impl Relationships {
+    /// Create a new, empty, relationships part.
+    pub fn new() -> Relationships {
+        Relationships::default()
+    }
+
+    /// Add an officeDocument relationship, such as `officeDocument`,
+    /// `worksheet`, `styles` or `sharedStrings`, and return its id.
+    pub fn add_document_relationship(&mut self, rel_type: &str, target: &str) -> String {
+        self.add(format!("{RELATIONSHIPS_NS}/{rel_type}"), target, None)
+    }
+
+    /// Add a package relationship, such as `metadata/core-properties`, and
+    /// return its id.
+    pub fn add_package_relationship(&mut self, rel_type: &str, target: &str) -> String {
+        self.add(
+            format!("{PACKAGE_RELATIONSHIPS_NS}/{rel_type}"),
+            target,
+            None,
+        )
+    }
+
+    /// Add a relationship from a worksheet, such as a `hyperlink`, `drawing`
+    /// or `table`, and return its id. The `target_mode` is `Some("External")`
+    /// for targets outside the package such as URLs.
+    pub fn add_worksheet_relationship(
+        &mut self,
+        rel_type: &str,
+        target: &str,
+        target_mode: Option<&str>,
+    ) -> String {
+        self.add(
+            format!("{RELATIONSHIPS_NS}/{rel_type}"),
+            target,
+            target_mode,
+        )
+    }
+
+    /// Add a relationship in the Microsoft Office schemas, such as
+    /// `2017/10/relationships/person`, and return its id.
+    pub fn add_office_relationship(&mut self, rel_type: &str, target: &str) -> String {
+        self.add(format!("{SCHEMA_OFFICE}/{rel_type}"), target, None)
+    }
+
+    /// The number of relationships in the part.
+    pub fn len(&self) -> usize {
+        self.relationships.len()
+    }
+
+    /// Check if the part has no relationships.
+    pub fn is_empty(&self) -> bool {
+        self.relationships.is_empty()
+    }
+
+    fn add(&mut self, rel_type: String, target: &str, target_mode: Option<&str>) -> String {
+        self.relationships.push(Relationship {
+            rel_type,
+            target: target.to_string(),
+            target_mode: target_mode.map(|mode| mode.to_string()),
+        });
+
+        format!("rId{}", self.relationships.len())
+    }
+
+    /// Write the `.rels` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        self.assemble_xml_file_after(writer, |_| 0);
+    }
+
+    // Write the `.rels` part with the relationships written by `write_first`
+    // before these ones, which returns the number that it wrote. Streaming
+    // worksheets use it to write their hyperlinks without holding them in
+    // memory.
+    pub(crate) fn assemble_xml_file_after<W: Write>(
+        &self,
+        writer: &mut XMLWriter<W>,
+        write_first: impl FnOnce(&mut XMLWriter<W>) -> usize,
+    ) {
+        writer.xml_declaration();
+
+        writer.xml_start_tag("Relationships", &vec![("xmlns", PACKAGE_RELATIONSHIPS_NS)]);
+
+        let first = write_first(writer);
+
+        for (index, relationship) in self.relationships.iter().enumerate() {
+            write_relationship(
+                writer,
+                first + index + 1,
+                &relationship.rel_type,
+                &relationship.target,
+                relationship.target_mode.as_deref(),
+            );
+        }
+
+        writer.xml_end_tag("Relationships");
+    }
+}
+
+// Write a <Relationship> element of an external hyperlink, for the
+// hyperlinks of a streaming worksheet.
+#[cfg(feature = "worksheet")]
+pub(crate) fn write_hyperlink_relationship(
+    writer: &mut XMLWriter<impl Write>,
+    id: usize,
+    url: &str,
+) {
+    let rel_type = format!("{RELATIONSHIPS_NS}/hyperlink");
+    write_relationship(writer, id, &rel_type, url, Some("External"));
+}
+
+// Write a <Relationship> element.
+fn write_relationship(
+    writer: &mut XMLWriter<impl Write>,
+    id: usize,
+    rel_type: &str,
+    target: &str,
+    target_mode: Option<&str>,
+) {
+    let id = format!("rId{id}");
+
+    let mut attributes = vec![("Id", id.as_str()), ("Type", rel_type), ("Target", target)];
+    if let Some(target_mode) = target_mode {
+        attributes.push(("TargetMode", target_mode));
+    }
+
+    writer.xml_empty_tag("Relationship", &attributes);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Relationships;
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_package_relationships() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<Relationships "#,
+            r#"xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+            r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/"#,
+            r#"officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+            r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/"#,
+            r#"2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>"#,
+            r#"<Relationship Id="rId3" Type="http://schemas.microsoft.com/office/"#,
+            r#"2017/10/relationships/person" Target="persons/person.xml"/>"#,
+            r#"</Relationships>"#,
+        );
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        let mut relationships = Relationships::new();
+
+        relationships.add_document_relationship("officeDocument", "xl/workbook.xml");
+        relationships.add_package_relationship("metadata/core-properties", "docProps/core.xml");
+        relationships.add_office_relationship("2017/10/relationships/person", "persons/person.xml");
+
+        relationships.assemble_xml_file(&mut writer);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_assemble_worksheet_relationships() {
+        let expected = concat!(
+            r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/"#,
+            r#"officeDocument/2006/relationships/hyperlink" "#,
+            r#"Target="https://example.com/?a=1&amp;b=2" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/"#,
+            r#"officeDocument/2006/relationships/drawing" "#,
+            r#"Target="../drawings/drawing1.xml"/>"#,
+        );
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        let mut relationships = Relationships::new();
+
+        assert!(relationships.is_empty());
+
+        let id = relationships.add_worksheet_relationship(
+            "hyperlink",
+            "https://example.com/?a=1&b=2",
+            Some("External"),
+        );
+        assert_eq!(id, "rId1");
+
+        let id =
+            relationships.add_worksheet_relationship("drawing", "../drawings/drawing1.xml", None);
+        assert_eq!(id, "rId2");
+        assert_eq!(relationships.len(), 2);
+
+        relationships.assemble_xml_file(&mut writer);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert!(got.contains(expected));
+    }
+}
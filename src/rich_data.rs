@@ -0,0 +1,473 @@
+//! The writers for the `xl/richData/` parts of the images that are embedded
+//! in cells.
+//!
+//! An image embedded in a cell with
+//! [`Worksheet::embed_image_in_cell()`](crate::worksheet::Worksheet::embed_image_in_cell)
+//! is a rich value: the cell is written as a `#VALUE!` error with a `vm`
+//! value metadata attribute, which refers to a block in the workbook's
+//! [`Metadata`](crate::metadata::Metadata) part, which in turn refers to the
+//! rich value of the image in the [`RichData`] parts. The rich value refers
+//! to the media part of the image through the relationships of the
+//! `xl/richData/richValueRel.xml` part.
+//!
+//! The images of all the worksheets are added to a single [`RichData`], in
+//! the order of the worksheets, and the `vm` attributes of each worksheet
+//! start after the images of the worksheets before it, see
+//! [`Worksheet::set_value_metadata_number()`](crate::worksheet::Worksheet::set_value_metadata_number).
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::metadata::Metadata;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::relationship::Relationships;
+//! use excel_xmlwriter::rich_data::RichData;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! # let path = std::env::temp_dir().join("rich_data_doc.gif");
+//! # std::fs::write(&path, [b"GIF89a".as_slice(), &[120, 0, 40, 0]].concat())?;
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut workbook_relationships = Relationships::new();
+//! let mut packager = Packager::new(vec![]);
+//! let mut metadata = Metadata::new();
+//! let mut rich_data = RichData::new();
+//!
+//! worksheet.embed_image_in_cell(1, 2, &path)?;
+//!
+//! for (part_name, image) in worksheet.media() {
+//!     let image_type = image.image_type();
+//!     content_types.add_default(image_type.extension(), image_type.content_type());
+//!     packager.add_part(&part_name, image.data())?;
+//! }
+//!
+//! for (part_name, image) in worksheet.embedded_images() {
+//!     let index = rich_data.add_image(&part_name, image);
+//!     metadata.add_rich_value(index);
+//! }
+//!
+//! if !rich_data.is_empty() {
+//!     metadata.set_dynamic_arrays(worksheet.has_dynamic_arrays());
+//!     content_types.add_metadata();
+//!     workbook_relationships.add_document_relationship("sheetMetadata", "metadata.xml");
+//!     packager.add_custom_part(&metadata)?;
+//!
+//!     rich_data.add_workbook_relationships(&mut workbook_relationships);
+//!     for part in rich_data.parts() {
+//!         content_types.add_custom_part(part.as_ref());
+//!         packager.add_custom_part(part.as_ref())?;
+//!     }
+//! }
+//! # std::fs::remove_file(&path)?;
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{
+    MARKUP_COMPATIBILITY_NS, RELATIONSHIPS_NS, RICH_DATA2_NS, RICH_DATA_NS,
+    RICH_VALUE_CONTENT_TYPE, RICH_VALUE_REL_CONTENT_TYPE, RICH_VALUE_REL_NS,
+    RICH_VALUE_STRUCTURE_CONTENT_TYPE, RICH_VALUE_TYPES_CONTENT_TYPE, SPREADSHEETML_NS,
+};
+use crate::image::Image;
+use crate::part::XlsxPart;
+use crate::relationship::Relationships;
+use crate::XMLWriter;
+
+// The CalcOrigin of an image that is embedded in a cell, rather than
+// returned by the IMAGE() function.
+const CALC_ORIGIN_EMBEDDED: &str = "5";
+
+// The keys of the rich value types that are excluded from calculation
+// comparisons. `_Self` is also excluded from the file.
+const TYPE_KEYS: [&str; 10] = [
+    "_Self",
+    "_DisplayString",
+    "_Flags",
+    "_Format",
+    "_SubLabel",
+    "_Attribution",
+    "_Icon",
+    "_Display",
+    "_CanonicalPropertyNames",
+    "_ClassificationId",
+];
+
+/// The rich values of the images that are embedded in cells, and the
+/// writers for the `xl/richData/` parts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RichData {
+    images: Vec<(String, Option<String>)>,
+}
+
+impl RichData {
+    /// Create an empty set of rich values.
+    pub fn new() -> RichData {
+        RichData::default()
+    }
+
+    /// Add the rich value of an embedded image, with the part name of its
+    /// media part, like `xl/media/image1.png`, and return the index of the
+    /// rich value for
+    /// [`Metadata::add_rich_value()`](crate::metadata::Metadata::add_rich_value).
+    pub fn add_image(&mut self, media_part_name: &str, image: &Image) -> u32 {
+        let target = media_part_name.replacen("xl/", "../", 1);
+        self.images
+            .push((target, image.alt_text().map(str::to_string)));
+
+        self.images.len() as u32 - 1
+    }
+
+    /// Check if there are no rich values, in which case the parts aren't
+    /// needed.
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Add the relationships from the workbook to the rich data parts.
+    pub fn add_workbook_relationships(&self, relationships: &mut Relationships) {
+        relationships.add_office_relationship(
+            "2022/10/relationships/richValueRel",
+            "richData/richValueRel.xml",
+        );
+        relationships.add_office_relationship(
+            "2017/06/relationships/rdRichValue",
+            "richData/rdrichvalue.xml",
+        );
+        relationships.add_office_relationship(
+            "2017/06/relationships/rdRichValueStructure",
+            "richData/rdrichvaluestructure.xml",
+        );
+        relationships.add_office_relationship(
+            "2017/06/relationships/rdRichValueTypes",
+            "richData/rdRichValueTypes.xml",
+        );
+    }
+
+    /// The `xl/richData/` parts, for
+    /// [`Packager::add_custom_part()`](crate::packager::Packager::add_custom_part)
+    /// and
+    /// [`ContentTypes::add_custom_part()`](crate::content_types::ContentTypes::add_custom_part).
+    /// The relationships of the `richValueRel.xml` part to the media parts
+    /// are written by the packager.
+    pub fn parts(&self) -> Vec<Box<dyn XlsxPart + '_>> {
+        vec![
+            Box::new(RichValueRelPart(self)),
+            Box::new(RichValuePart(self)),
+            Box::new(RichValueStructurePart(self)),
+            Box::new(RichValueTypesPart),
+        ]
+    }
+
+    // The structures of the rich values: without alternative text, with
+    // alternative text, or both, in that order if they are used.
+    fn structures(&self) -> Vec<bool> {
+        let mut structures = vec![];
+        for has_alt_text in [false, true] {
+            if self
+                .images
+                .iter()
+                .any(|(_, alt_text)| alt_text.is_some() == has_alt_text)
+            {
+                structures.push(has_alt_text);
+            }
+        }
+
+        structures
+    }
+
+    // Write the `xl/richData/rdrichvalue.xml` part with a rich value for each
+    // image. The values are the index of the image's relationship, the
+    // CalcOrigin, and the alternative text if there is one.
+    fn write_rich_values(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let count = self.images.len().to_string();
+        let attributes = vec![("xmlns", RICH_DATA_NS), ("count", count.as_str())];
+        writer.xml_start_tag("rvData", &attributes);
+
+        let structures = self.structures();
+        for (index, (_, alt_text)) in self.images.iter().enumerate() {
+            let structure = structures
+                .iter()
+                .position(|has_alt_text| *has_alt_text == alt_text.is_some())
+                .unwrap_or_default()
+                .to_string();
+
+            writer.xml_start_tag("rv", &vec![("s", &structure)]);
+            writer.xml_data_element_u64("v", index as u64, &vec![]);
+            writer.xml_data_element("v", CALC_ORIGIN_EMBEDDED, &vec![]);
+            if let Some(alt_text) = alt_text {
+                writer.xml_data_element("v", alt_text, &vec![]);
+            }
+            writer.xml_end_tag("rv");
+        }
+
+        writer.xml_end_tag("rvData");
+    }
+
+    // Write the `xl/richData/rdrichvaluestructure.xml` part with the keys of
+    // the `_localImage` structures.
+    fn write_structures(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let structures = self.structures();
+        let count = structures.len().to_string();
+        let attributes = vec![("xmlns", RICH_DATA_NS), ("count", count.as_str())];
+        writer.xml_start_tag("rvStructures", &attributes);
+
+        for has_alt_text in structures {
+            writer.xml_start_tag("s", &vec![("t", "_localImage")]);
+            writer.xml_empty_tag("k", &vec![("n", "_rvRel:LocalImageIdentifier"), ("t", "i")]);
+            writer.xml_empty_tag("k", &vec![("n", "CalcOrigin"), ("t", "i")]);
+            if has_alt_text {
+                writer.xml_empty_tag("k", &vec![("n", "Text"), ("t", "s")]);
+            }
+            writer.xml_end_tag("s");
+        }
+
+        writer.xml_end_tag("rvStructures");
+    }
+
+    // Write the `xl/richData/richValueRel.xml` part with a relationship id
+    // for each image, in the order of the rich values.
+    fn write_rels(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns", RICH_VALUE_REL_NS), ("xmlns:r", RELATIONSHIPS_NS)];
+        writer.xml_start_tag("richValueRels", &attributes);
+
+        for index in 1..=self.images.len() {
+            let id = format!("rId{index}");
+            writer.xml_empty_tag("rel", &vec![("r:id", &id)]);
+        }
+
+        writer.xml_end_tag("richValueRels");
+    }
+}
+
+// Write the `xl/richData/rdRichValueTypes.xml` part, which is the same in
+// every workbook.
+fn write_rich_value_types(writer: &mut XMLWriter<impl Write>) {
+    writer.xml_declaration();
+
+    let attributes = vec![
+        ("xmlns", RICH_DATA2_NS),
+        ("xmlns:mc", MARKUP_COMPATIBILITY_NS),
+        ("mc:Ignorable", "x"),
+        ("xmlns:x", SPREADSHEETML_NS),
+    ];
+    writer.xml_start_tag("rvTypesInfo", &attributes);
+    writer.xml_start_tag("global", &vec![]);
+    writer.xml_start_tag("keyFlags", &vec![]);
+
+    for key in TYPE_KEYS {
+        writer.xml_start_tag("key", &vec![("name", key)]);
+        if key == "_Self" {
+            writer.xml_empty_tag("flag", &vec![("name", "ExcludeFromFile"), ("value", "1")]);
+        }
+        writer.xml_empty_tag(
+            "flag",
+            &vec![("name", "ExcludeFromCalcComparison"), ("value", "1")],
+        );
+        writer.xml_end_tag("key");
+    }
+
+    writer.xml_end_tag("keyFlags");
+    writer.xml_end_tag("global");
+    writer.xml_end_tag("rvTypesInfo");
+}
+
+// The `xl/richData/richValueRel.xml` part and its relationships.
+struct RichValueRelPart<'a>(&'a RichData);
+
+impl XlsxPart for RichValueRelPart<'_> {
+    fn part_name(&self) -> &str {
+        "xl/richData/richValueRel.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        RICH_VALUE_REL_CONTENT_TYPE
+    }
+
+    fn relationships(&self) -> Relationships {
+        let mut relationships = Relationships::new();
+        for (target, _) in &self.0.images {
+            relationships.add_document_relationship("image", target);
+        }
+
+        relationships
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.0.write_rels(writer);
+    }
+}
+
+// The `xl/richData/rdrichvalue.xml` part.
+struct RichValuePart<'a>(&'a RichData);
+
+impl XlsxPart for RichValuePart<'_> {
+    fn part_name(&self) -> &str {
+        "xl/richData/rdrichvalue.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        RICH_VALUE_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.0.write_rich_values(writer);
+    }
+}
+
+// The `xl/richData/rdrichvaluestructure.xml` part.
+struct RichValueStructurePart<'a>(&'a RichData);
+
+impl XlsxPart for RichValueStructurePart<'_> {
+    fn part_name(&self) -> &str {
+        "xl/richData/rdrichvaluestructure.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        RICH_VALUE_STRUCTURE_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.0.write_structures(writer);
+    }
+}
+
+// The `xl/richData/rdRichValueTypes.xml` part.
+struct RichValueTypesPart;
+
+impl XlsxPart for RichValueTypesPart {
+    fn part_name(&self) -> &str {
+        "xl/richData/rdRichValueTypes.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        RICH_VALUE_TYPES_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        write_rich_value_types(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::RichData;
+    use crate::image::Image;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+
+    // Write a part to a string.
+    fn write_part(rich_data: &RichData, part_name: &str) -> String {
+        let parts = rich_data.parts();
+        let part = parts
+            .iter()
+            .find(|part| part.part_name() == part_name)
+            .unwrap();
+
+        let mut data = vec![];
+        let mut writer = XMLWriter::new(&mut data as &mut dyn Write);
+        part.write(&mut writer);
+
+        String::from_utf8(data).unwrap()
+    }
+
+    fn rich_data() -> RichData {
+        let gif = [b"GIF89a".as_slice(), &[120, 0, 40, 0]].concat();
+        let image = Image::from_bytes(&gif).unwrap();
+
+        let mut rich_data = RichData::new();
+        assert_eq!(rich_data.add_image("xl/media/image1.gif", &image), 0);
+        assert_eq!(
+            rich_data.add_image("xl/media/image2.gif", &image.set_alt_text("Logo")),
+            1
+        );
+
+        rich_data
+    }
+
+    #[test]
+    fn test_rich_values() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<rvData xmlns="http://schemas.microsoft.com/office/spreadsheetml/2017/richdata" count="2">"#,
+            r#"<rv s="0"><v>0</v><v>5</v></rv><rv s="1"><v>1</v><v>5</v><v>Logo</v></rv>"#,
+            r#"</rvData>"#,
+        );
+
+        assert_eq!(
+            write_part(&rich_data(), "xl/richData/rdrichvalue.xml"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_rich_value_structures() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<rvStructures xmlns="http://schemas.microsoft.com/office/spreadsheetml/2017/richdata" count="2">"#,
+            r#"<s t="_localImage"><k n="_rvRel:LocalImageIdentifier" t="i"/><k n="CalcOrigin" t="i"/></s>"#,
+            r#"<s t="_localImage"><k n="_rvRel:LocalImageIdentifier" t="i"/><k n="CalcOrigin" t="i"/>"#,
+            r#"<k n="Text" t="s"/></s></rvStructures>"#,
+        );
+
+        assert_eq!(
+            write_part(&rich_data(), "xl/richData/rdrichvaluestructure.xml"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_rich_value_rels() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<richValueRels xmlns="http://schemas.microsoft.com/office/spreadsheetml/2022/richvaluerel" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            r#"<rel r:id="rId1"/><rel r:id="rId2"/></richValueRels>"#,
+        );
+
+        let rich_data = rich_data();
+        assert_eq!(
+            write_part(&rich_data, "xl/richData/richValueRel.xml"),
+            expected
+        );
+
+        let parts = rich_data.parts();
+        let relationships = parts[0].relationships();
+        let mut writer = XMLWriter::new(vec![]);
+        relationships.assemble_xml_file(&mut writer);
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(got.contains(r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image2.gif"/>"#));
+    }
+
+    #[test]
+    fn test_rich_value_types() {
+        let got = write_part(&RichData::new(), "xl/richData/rdRichValueTypes.xml");
+
+        assert!(got.contains(concat!(
+            r#"<keyFlags><key name="_Self"><flag name="ExcludeFromFile" value="1"/>"#,
+            r#"<flag name="ExcludeFromCalcComparison" value="1"/></key>"#,
+            r#"<key name="_DisplayString"><flag name="ExcludeFromCalcComparison" value="1"/></key>"#,
+        )));
+        assert!(got.ends_with("</keyFlags></global></rvTypesInfo>"));
+    }
+}
@@ -0,0 +1,311 @@
+//! Builder for rich strings, strings with multiple font formats, for use with
+//! [`XMLWriter::xml_rich_si_element()`](crate::XMLWriter::xml_rich_si_element),
+//! which takes the [`runs()`](RichString::runs) of the string.
+//!
+//! ```
+//! use excel_xmlwriter::rich_string::{RichString, RunProperties};
+//!
+//! let bold = RunProperties::new().set_bold();
+//! let rich_string = RichString::new()
+//!     .add_run(None, "This is ")
+//!     .add_run(Some(&bold), "bold");
+//!
+//! assert_eq!(
+//!     rich_string.to_xml(),
+//!     concat!(
+//!         r#"<r><t xml:space="preserve">This is </t></r>"#,
+//!         r#"<r><rPr><b/><sz val="11"/><color theme="1"/><rFont val="Calibri"/>"#,
+//!         r#"<family val="2"/><scheme val="minor"/></rPr><t>bold</t></r>"#
+//!     )
+//! );
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use crate::{escape_attributes, escape_data};
+
+/// The font properties of a run in a rich string. These are written as the
+/// `<rPr>` element of the run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunProperties {
+    bold: bool,
+    italic: bool,
+    strikethrough: bool,
+    underline: bool,
+    superscript: bool,
+    subscript: bool,
+    size: f64,
+    color: Option<u32>,
+    font_name: String,
+    font_family: u8,
+    font_scheme: String,
+}
+
+impl Default for RunProperties {
+    fn default() -> Self {
+        RunProperties {
+            bold: false,
+            italic: false,
+            strikethrough: false,
+            underline: false,
+            superscript: false,
+            subscript: false,
+            size: 11.0,
+            color: None,
+            font_name: "Calibri".to_string(),
+            font_family: 2,
+            font_scheme: "minor".to_string(),
+        }
+    }
+}
+
+impl RunProperties {
+    /// Create a new set of run properties with Excel's default font.
+    pub fn new() -> RunProperties {
+        RunProperties::default()
+    }
+
+    /// Set the bold property.
+    pub fn set_bold(mut self) -> RunProperties {
+        self.bold = true;
+        self
+    }
+
+    /// Set the italic property.
+    pub fn set_italic(mut self) -> RunProperties {
+        self.italic = true;
+        self
+    }
+
+    /// Set the strikethrough property.
+    pub fn set_strikethrough(mut self) -> RunProperties {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Set the single underline property.
+    pub fn set_underline(mut self) -> RunProperties {
+        self.underline = true;
+        self
+    }
+
+    /// Set the superscript property.
+    pub fn set_superscript(mut self) -> RunProperties {
+        self.superscript = true;
+        self.subscript = false;
+        self
+    }
+
+    /// Set the subscript property.
+    pub fn set_subscript(mut self) -> RunProperties {
+        self.subscript = true;
+        self.superscript = false;
+        self
+    }
+
+    /// Set the font size in points. The default is 11.
+    pub fn set_size(mut self, size: f64) -> RunProperties {
+        self.size = size;
+        self
+    }
+
+    /// Set the font color as an RGB value like `0xFF0000`. The default is the
+    /// theme text color.
+    pub fn set_color(mut self, rgb: u32) -> RunProperties {
+        self.color = Some(rgb & 0xFFFFFF);
+        self
+    }
+
+    /// Set the font name. The default is "Calibri". Setting a font other than
+    /// the default also turns off the theme font scheme.
+    pub fn set_font_name(mut self, font_name: &str) -> RunProperties {
+        self.font_name = font_name.to_string();
+        if font_name != "Calibri" {
+            self.font_scheme.clear();
+        }
+        self
+    }
+
+    /// Set the font family number. The default is 2, "Swiss".
+    pub fn set_font_family(mut self, font_family: u8) -> RunProperties {
+        self.font_family = font_family;
+        self
+    }
+
+    // Write the `<rPr>` element in the order that Excel uses.
+    fn write_xml(&self, xml: &mut String) {
+        xml.push_str("<rPr>");
+
+        if self.bold {
+            xml.push_str("<b/>");
+        }
+        if self.italic {
+            xml.push_str("<i/>");
+        }
+        if self.strikethrough {
+            xml.push_str("<strike/>");
+        }
+        if self.underline {
+            xml.push_str("<u/>");
+        }
+        if self.superscript {
+            xml.push_str(r#"<vertAlign val="superscript"/>"#);
+        }
+        if self.subscript {
+            xml.push_str(r#"<vertAlign val="subscript"/>"#);
+        }
+
+        xml.push_str(&format!(r#"<sz val="{}"/>"#, self.size));
+
+        match self.color {
+            Some(rgb) => xml.push_str(&format!(r#"<color rgb="FF{:06X}"/>"#, rgb)),
+            None => xml.push_str(r#"<color theme="1"/>"#),
+        }
+
+        xml.push_str(&format!(
+            r#"<rFont val="{}"/>"#,
+            escape_attributes(&self.font_name)
+        ));
+        xml.push_str(&format!(r#"<family val="{}"/>"#, self.font_family));
+
+        if !self.font_scheme.is_empty() {
+            xml.push_str(&format!(r#"<scheme val="{}"/>"#, self.font_scheme));
+        }
+
+        xml.push_str("</rPr>");
+    }
+}
+
+/// A rich string made up of runs of text, each with optional font
+/// properties. Runs without properties use the cell's format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RichString {
+    runs: Vec<(Option<RunProperties>, String)>,
+}
+
+impl RichString {
+    /// Create a new, empty, rich string.
+    pub fn new() -> RichString {
+        RichString::default()
+    }
+
+    /// Add a run of text with optional font properties.
+    pub fn add_run(mut self, properties: Option<&RunProperties>, text: &str) -> RichString {
+        self.runs.push((properties.cloned(), text.to_string()));
+        self
+    }
+
+    /// Get the unformatted text of the rich string.
+    /// ```
+    /// # use excel_xmlwriter::rich_string::{RichString, RunProperties};
+    /// #
+    /// let italic = RunProperties::new().set_italic();
+    /// let rich_string = RichString::new()
+    ///     .add_run(None, "Some ")
+    ///     .add_run(Some(&italic), "text");
+    ///
+    /// assert_eq!(rich_string.text(), "Some text");
+    /// ```
+    pub fn text(&self) -> String {
+        self.runs.iter().map(|(_, text)| text.as_str()).collect()
+    }
+
+    /// Get the runs of the rich string, as the font properties and text
+    /// pairs that are written by
+    /// [`XMLWriter::xml_rich_si_element()`](crate::XMLWriter::xml_rich_si_element).
+    pub fn runs(&self) -> Vec<(Option<&RunProperties>, &str)> {
+        self.runs
+            .iter()
+            .map(|(properties, text)| (properties.as_ref(), text.as_str()))
+            .collect()
+    }
+
+    /// Generate the escaped `<r>` runs of the rich string.
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::new();
+
+        for (properties, text) in &self.runs {
+            write_run(&mut xml, properties.as_ref(), text);
+        }
+
+        xml
+    }
+}
+
+// Write an escaped `<r>` run with optional font properties.
+pub(crate) fn write_run(xml: &mut String, properties: Option<&RunProperties>, text: &str) {
+    xml.push_str("<r>");
+
+    if let Some(properties) = properties {
+        properties.write_xml(xml);
+    }
+
+    // Excel preserves leading and trailing whitespace.
+    if text.starts_with(char::is_whitespace) || text.ends_with(char::is_whitespace) {
+        xml.push_str(r#"<t xml:space="preserve">"#);
+    } else {
+        xml.push_str("<t>");
+    }
+
+    xml.push_str(&escape_data(text));
+    xml.push_str("</t></r>");
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{RichString, RunProperties};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rich_string_without_properties() {
+        let expected = "<r><t>foo</t></r><r><t>bar</t></r>";
+
+        let rich_string = RichString::new().add_run(None, "foo").add_run(None, "bar");
+
+        assert_eq!(rich_string.to_xml(), expected);
+    }
+
+    #[test]
+    fn test_rich_string_with_escapes() {
+        let expected = r#"<r><t xml:space="preserve">a &lt; b &amp; "c" </t></r>"#;
+
+        let rich_string = RichString::new().add_run(None, r#"a < b & "c" "#);
+
+        assert_eq!(rich_string.to_xml(), expected);
+    }
+
+    #[test]
+    fn test_rich_string_with_properties() {
+        let expected = concat!(
+            r#"<r><rPr><b/><i/><strike/><u/><vertAlign val="superscript"/>"#,
+            r#"<sz val="8.5"/><color rgb="FFFF0000"/><rFont val="Arial"/>"#,
+            r#"<family val="2"/></rPr><t>foo</t></r>"#
+        );
+
+        let properties = RunProperties::new()
+            .set_bold()
+            .set_italic()
+            .set_strikethrough()
+            .set_underline()
+            .set_superscript()
+            .set_size(8.5)
+            .set_color(0xFF0000)
+            .set_font_name("Arial");
+
+        let rich_string = RichString::new().add_run(Some(&properties), "foo");
+
+        assert_eq!(rich_string.to_xml(), expected);
+    }
+
+    #[test]
+    fn test_rich_string_text() {
+        let bold = RunProperties::new().set_bold();
+        let rich_string = RichString::new()
+            .add_run(Some(&bold), "foo")
+            .add_run(None, " bar");
+
+        assert_eq!(rich_string.text(), "foo bar");
+    }
+}
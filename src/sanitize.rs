@@ -0,0 +1,308 @@
+//! Sanitizing of untrusted strings before they are written to cells.
+//!
+//! Strings from untrusted sources can start with characters that spreadsheet
+//! applications, or tools that later export the data to CSV, interpret as the
+//! start of a formula. They can also contain control characters that aren't
+//! valid in XML. A [`SanitizePolicy`] set on the writer with
+//! [`XMLWriter::set_sanitize_policy()`](crate::XMLWriter::set_sanitize_policy)
+//! is applied to every string written via the string cell path.
+//!
+//! ```
+//! use excel_xmlwriter::sanitize::{sanitize_string, SanitizePolicy};
+//!
+//! assert_eq!(sanitize_string("=1+2", SanitizePolicy::Escape), "'=1+2");
+//! assert_eq!(sanitize_string("=1+2", SanitizePolicy::Strip), "1+2");
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+
+/// The policy used to sanitize strings written to cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SanitizePolicy {
+    /// Write strings unchanged, apart from the standard XML escaping. This is
+    /// the default.
+    #[default]
+    None,
+
+    /// Prefix strings that start with a formula trigger character (`=`, `+`,
+    /// `-`, `@`, tab or carriage return) with a single quote and encode
+    /// control characters in Excel's `_xHHHH_` format.
+    ///
+    /// The quote is part of the string, so unlike a quote typed in Excel it
+    /// is shown in the cell and is in the cell's value, which keeps it in
+    /// CSV exports. Use [`QuotePrefix`](SanitizePolicy::QuotePrefix) to keep
+    /// the string unchanged instead.
+    Escape,
+
+    /// Encode control characters like [`Escape`](SanitizePolicy::Escape),
+    /// but leave strings that start with a formula trigger character
+    /// unchanged. A worksheet writes the cells of these strings with
+    /// Excel's hidden quote prefix instead, a `quotePrefix="1"` format, see
+    /// [`Format::set_quote_prefix()`](crate::styles::Format::set_quote_prefix).
+    /// The quote isn't shown in the cell or exported to CSV.
+    ///
+    /// For the strings of a worksheet that are written to the shared string
+    /// table, set the policy on the writers of both parts.
+    QuotePrefix,
+
+    /// Remove leading formula trigger characters and control characters, and
+    /// escape literal `_xHHHH_` sequences like
+    /// [`Escape`](SanitizePolicy::Escape).
+    Strip,
+}
+
+/// Sanitize a string according to a [`SanitizePolicy`].
+///
+/// The string is only copied if it needs to be changed.
+/// ```
+/// # use excel_xmlwriter::sanitize::{sanitize_string, SanitizePolicy};
+/// #
+/// assert_eq!(sanitize_string("@SUM(A1)", SanitizePolicy::Escape), "'@SUM(A1)");
+/// assert_eq!(sanitize_string("a\u{1}b", SanitizePolicy::Escape), "a_x0001_b");
+/// assert_eq!(sanitize_string("a\u{1}b", SanitizePolicy::Strip), "ab");
+/// assert_eq!(sanitize_string("-1", SanitizePolicy::None), "-1");
+/// ```
+pub fn sanitize_string(string: &str, policy: SanitizePolicy) -> Cow<'_, str> {
+    match policy {
+        SanitizePolicy::None => Cow::Borrowed(string),
+        SanitizePolicy::Escape => escape_string(string, true),
+        SanitizePolicy::QuotePrefix => escape_string(string, false),
+        SanitizePolicy::Strip => strip_string(string),
+    }
+}
+
+/// Check if a cell with a string needs Excel's quote prefix under a
+/// [`SanitizePolicy`], which is only the case for
+/// [`SanitizePolicy::QuotePrefix`] and strings that start with a formula
+/// trigger character.
+/// ```
+/// # use excel_xmlwriter::sanitize::{needs_quote_prefix, SanitizePolicy};
+/// #
+/// assert!(needs_quote_prefix("=1+2", SanitizePolicy::QuotePrefix));
+/// assert!(!needs_quote_prefix("1+2", SanitizePolicy::QuotePrefix));
+/// assert!(!needs_quote_prefix("=1+2", SanitizePolicy::Escape));
+/// ```
+pub fn needs_quote_prefix(string: &str, policy: SanitizePolicy) -> bool {
+    policy == SanitizePolicy::QuotePrefix && string.starts_with(is_formula_trigger)
+}
+
+// Characters that can cause a string to be interpreted as a formula.
+fn is_formula_trigger(c: char) -> bool {
+    matches!(c, '=' | '+' | '-' | '@' | '\t' | '\r')
+}
+
+// Control characters that aren't allowed in XML 1.0. Excel stores these in
+// the `_xHHHH_` escaped form.
+fn is_invalid_control(c: char) -> bool {
+    matches!(c, '\u{0}'..='\u{8}' | '\u{B}' | '\u{C}' | '\u{E}'..='\u{1F}')
+}
+
+// Check if the string contains a literal `_xHHHH_` sequence that Excel would
+// decode. These need to be escaped as `_x005F_xHHHH_`.
+fn has_escape_sequence(string: &str) -> bool {
+    string
+        .match_indices("_x")
+        .any(|(i, _)| is_escape_sequence(&string[i..]))
+}
+
+fn is_escape_sequence(string: &str) -> bool {
+    let bytes = string.as_bytes();
+
+    bytes.len() >= 7
+        && bytes.starts_with(b"_x")
+        && bytes[2..6].iter().all(u8::is_ascii_hexdigit)
+        && bytes[6] == b'_'
+}
+
+// Encode the control characters of a string, and prefix it with a quote if
+// `prefix` is set and it starts with a formula trigger character.
+fn escape_string(string: &str, prefix: bool) -> Cow<'_, str> {
+    let needs_prefix = prefix && string.starts_with(is_formula_trigger);
+
+    if !needs_prefix && !string.contains(is_invalid_control) && !has_escape_sequence(string) {
+        return Cow::Borrowed(string);
+    }
+
+    let mut escaped = String::with_capacity(string.len() + 8);
+
+    if needs_prefix {
+        escaped.push('\'');
+    }
+
+    for (i, c) in string.char_indices() {
+        if is_invalid_control(c) {
+            escaped.push_str(&format!("_x{:04X}_", c as u32));
+        } else if c == '_' && is_escape_sequence(&string[i..]) {
+            escaped.push_str("_x005F");
+            escaped.push(c);
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+// Remove leading formula trigger characters and control characters, and
+// escape literal `_xHHHH_` sequences like escape_string().
+fn strip_string(string: &str) -> Cow<'_, str> {
+    let trimmed = string.trim_start_matches(|c| is_formula_trigger(c) || is_invalid_control(c));
+
+    if !trimmed.contains(is_invalid_control) && !has_escape_sequence(trimmed) {
+        return Cow::Borrowed(trimmed);
+    }
+
+    let stripped: String = trimmed
+        .chars()
+        .filter(|&c| !is_invalid_control(c))
+        .collect();
+
+    // Removing a control character can join the parts of a sequence.
+    let mut escaped = String::with_capacity(stripped.len() + 6);
+    for (i, c) in stripped.char_indices() {
+        if c == '_' && is_escape_sequence(&stripped[i..]) {
+            escaped.push_str("_x005F");
+        }
+        escaped.push(c);
+    }
+
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{needs_quote_prefix, sanitize_string, SanitizePolicy};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_sanitize_none() {
+        let tests = vec!["", "foo", "=1+2", "-1", "a\u{1}b"];
+
+        for string in tests {
+            assert_eq!(sanitize_string(string, SanitizePolicy::None), string);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_escape() {
+        let tests = vec![
+            ("", ""),
+            ("foo", "foo"),
+            ("a=b", "a=b"),
+            ("=1+2", "'=1+2"),
+            ("+1", "'+1"),
+            ("-1", "'-1"),
+            ("@SUM(A1)", "'@SUM(A1)"),
+            ("\t=1", "'\t=1"),
+            ("\r=1", "'\r=1"),
+            ("a\nb", "a\nb"),
+            ("a\u{0}b", "a_x0000_b"),
+            ("a\u{1F}b", "a_x001F_b"),
+            ("=\u{7}", "'=_x0007_"),
+            ("_x0041_", "_x005F_x0041_"),
+            ("_x004_", "_x004_"),
+            ("_xZZZZ_", "_xZZZZ_"),
+        ];
+
+        for (string, expected) in tests {
+            assert_eq!(sanitize_string(string, SanitizePolicy::Escape), expected);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_quote_prefix() {
+        let tests = vec![
+            ("", "", false),
+            ("foo", "foo", false),
+            ("a=b", "a=b", false),
+            ("=1+2", "=1+2", true),
+            ("-1", "-1", true),
+            ("\t=1", "\t=1", true),
+            ("=\u{7}", "=_x0007_", true),
+            ("_x0041_", "_x005F_x0041_", false),
+        ];
+
+        for (string, expected, prefix) in tests {
+            assert_eq!(
+                sanitize_string(string, SanitizePolicy::QuotePrefix),
+                expected
+            );
+            assert_eq!(
+                needs_quote_prefix(string, SanitizePolicy::QuotePrefix),
+                prefix
+            );
+            assert!(!needs_quote_prefix(string, SanitizePolicy::Escape));
+        }
+    }
+
+    #[test]
+    fn test_sanitize_strip() {
+        let tests = vec![
+            ("", ""),
+            ("foo", "foo"),
+            ("a=b", "a=b"),
+            ("=1+2", "1+2"),
+            ("=+-@1", "1"),
+            ("\t\r=cmd", "cmd"),
+            ("a\u{0}b\u{1F}c", "abc"),
+            ("\u{1}=1", "1"),
+            ("==", ""),
+            ("_x0041_", "_x005F_x0041_"),
+            ("_x00\u{1}41_", "_x005F_x0041_"),
+        ];
+
+        for (string, expected) in tests {
+            assert_eq!(sanitize_string(string, SanitizePolicy::Strip), expected);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_escape_sequence() {
+        let tests = vec![
+            (SanitizePolicy::None, "_x0007_"),
+            (SanitizePolicy::Escape, "_x005F_x0007_"),
+            (SanitizePolicy::QuotePrefix, "_x005F_x0007_"),
+            (SanitizePolicy::Strip, "_x005F_x0007_"),
+        ];
+
+        for (policy, expected) in tests {
+            assert_eq!(sanitize_string("_x0007_", policy), expected);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_generated_strings() {
+        // Check the policies against a deterministic stream of strings made
+        // from a mix of trigger, control, escape and ordinary characters.
+        let alphabet = [
+            '=', '+', '-', '@', '\t', '\r', '\u{0}', '\u{1B}', '_', 'x', '0', 'a', 'é',
+        ];
+        let mut seed: u32 = 12345;
+
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 16) as usize
+        };
+
+        for _ in 0..5000 {
+            let length = next() % 12;
+            let string: String = (0..length)
+                .map(|_| alphabet[next() % alphabet.len()])
+                .collect();
+
+            let escaped = sanitize_string(&string, SanitizePolicy::Escape);
+            assert!(!escaped.contains(|c: char| c < ' ' && c != '\t' && c != '\n' && c != '\r'));
+            assert!(string.is_empty() || !escaped.starts_with(['=', '+', '-', '@', '\t', '\r']));
+
+            let stripped = sanitize_string(&string, SanitizePolicy::Strip);
+            assert!(!stripped.contains(|c: char| c < ' ' && c != '\t' && c != '\n' && c != '\r'));
+            assert!(!stripped.starts_with(['=', '+', '-', '@', '\t', '\r']));
+        }
+    }
+}
@@ -0,0 +1,836 @@
+//! A `serde` serializer that writes Rust types as XML elements, with the
+//! same escaping as the rest of the crate, for custom parts such as
+//! `customXml` items or web extension payloads.
+//!
+//! A struct is written as an element and its fields as child elements named
+//! after the fields. Fields renamed with a leading `@` are written as
+//! attributes instead, and must come before the child elements. A field
+//! renamed to `$text` is the text of the element, which can only have
+//! attributes besides it. Fields that are `None` are omitted, and sequences
+//! are written as a repeated element.
+//!
+//! ```
+//! use excel_xmlwriter::serializer::to_writer;
+//! use excel_xmlwriter::XMLWriter;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Property {
+//!     #[serde(rename = "@name")]
+//!     name: String,
+//!     #[serde(rename = "$text")]
+//!     value: String,
+//! }
+//!
+//! #[derive(Serialize)]
+//! struct Settings {
+//!     #[serde(rename = "@version")]
+//!     version: u32,
+//!     title: String,
+//!     author: Option<String>,
+//!     property: Vec<Property>,
+//! }
+//!
+//! # fn main() -> Result<(), excel_xmlwriter::error::XlsxError> {
+//! let settings = Settings {
+//!     version: 2,
+//!     title: "Sales & Costs".to_string(),
+//!     author: None,
+//!     property: vec![Property {
+//!         name: "region".to_string(),
+//!         value: "North".to_string(),
+//!     }],
+//! };
+//!
+//! let mut writer = XMLWriter::new(vec![]);
+//! to_writer(&mut writer, "settings", &settings)?;
+//!
+//! assert_eq!(
+//!     String::from_utf8(writer.into_inner()).unwrap(),
+//!     concat!(
+//!         r#"<settings version="2"><title>Sales &amp; Costs</title>"#,
+//!         r#"<property name="region">North</property></settings>"#
+//!     )
+//! );
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Booleans are written as `1` and `0`, as in Excel's own parts. Unit
+//! variants of enums are written as the variant name, and the other variants
+//! as an element named after the variant.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::error::XlsxError;
+use crate::XMLWriter;
+
+// The prefix of the field names that are written as attributes.
+const ATTRIBUTE_PREFIX: char = '@';
+
+// The field name of the text of an element.
+const TEXT_FIELD: &str = "$text";
+
+/// Serialize a value as an element, or a repeated element for a sequence,
+/// named `tag`.
+///
+/// # Errors
+///
+/// [`XlsxError::Serialize`] if the value can't be written as XML, such as
+/// byte arrays, attributes after child elements, or attributes that aren't
+/// strings or numbers.
+pub fn to_writer<T: Serialize + ?Sized>(
+    writer: &mut XMLWriter<impl Write>,
+    tag: &str,
+    value: &T,
+) -> Result<(), XlsxError> {
+    value.serialize(Serializer::new(writer, tag))
+}
+
+/// A `serde` serializer that writes a value as an element named after a tag.
+/// See [`to_writer()`].
+pub struct Serializer<'a, W: Write> {
+    writer: &'a mut XMLWriter<W>,
+    tag: &'a str,
+}
+
+impl<'a, W: Write> Serializer<'a, W> {
+    /// Create a serializer that writes a value as an element named `tag`.
+    pub fn new(writer: &'a mut XMLWriter<W>, tag: &'a str) -> Serializer<'a, W> {
+        Serializer { writer, tag }
+    }
+
+    fn write_text(self, text: &str) -> Result<(), XlsxError> {
+        self.writer.xml_data_element(self.tag, text, &vec![]);
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> ser::Serializer for Serializer<'a, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    type SerializeSeq = SeqSerializer<'a, W>;
+    type SerializeTuple = SeqSerializer<'a, W>;
+    type SerializeTupleStruct = SeqSerializer<'a, W>;
+    type SerializeTupleVariant = SeqSerializer<'a, W>;
+    type SerializeMap = ElementSerializer<'a, W>;
+    type SerializeStruct = ElementSerializer<'a, W>;
+    type SerializeStructVariant = ElementSerializer<'a, W>;
+
+    fn serialize_bool(self, value: bool) -> Result<(), XlsxError> {
+        self.write_text(if value { "1" } else { "0" })
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), XlsxError> {
+        self.write_text(&value.to_string())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), XlsxError> {
+        self.write_text(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), XlsxError> {
+        self.write_text(value)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<(), XlsxError> {
+        Err(unsupported("byte arrays"))
+    }
+
+    fn serialize_none(self) -> Result<(), XlsxError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), XlsxError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), XlsxError> {
+        self.writer.xml_empty_tag(self.tag, &vec![]);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), XlsxError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), XlsxError> {
+        self.write_text(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), XlsxError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), XlsxError> {
+        value.serialize(Serializer::new(self.writer, variant))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'a, W>, XlsxError> {
+        Ok(SeqSerializer {
+            writer: self.writer,
+            tag: self.tag,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'a, W>, XlsxError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'a, W>, XlsxError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SeqSerializer<'a, W>, XlsxError> {
+        Ok(SeqSerializer {
+            writer: self.writer,
+            tag: variant,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ElementSerializer<'a, W>, XlsxError> {
+        Ok(ElementSerializer::new(self.writer, self.tag))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ElementSerializer<'a, W>, XlsxError> {
+        Ok(ElementSerializer::new(self.writer, self.tag))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<ElementSerializer<'a, W>, XlsxError> {
+        Ok(ElementSerializer::new(self.writer, variant))
+    }
+}
+
+/// The serializer for sequences and tuples, which writes each item as an
+/// element with the same tag.
+pub struct SeqSerializer<'a, W: Write> {
+    writer: &'a mut XMLWriter<W>,
+    tag: &'a str,
+}
+
+impl<W: Write> SeqSerializer<'_, W> {
+    fn write_item<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), XlsxError> {
+        value.serialize(Serializer::new(self.writer, self.tag))
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), XlsxError> {
+        self.write_item(value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), XlsxError> {
+        self.write_item(value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), XlsxError> {
+        self.write_item(value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for SeqSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), XlsxError> {
+        self.write_item(value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        Ok(())
+    }
+}
+
+/// The serializer for structs and maps, which writes an element with the
+/// fields as its attributes, text or child elements.
+///
+/// The start tag is written when the first child element is written, since
+/// the attributes have to be known first.
+pub struct ElementSerializer<'a, W: Write> {
+    writer: &'a mut XMLWriter<W>,
+    tag: &'a str,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    started: bool,
+    key: Option<String>,
+}
+
+impl<'a, W: Write> ElementSerializer<'a, W> {
+    fn new(writer: &'a mut XMLWriter<W>, tag: &'a str) -> ElementSerializer<'a, W> {
+        ElementSerializer {
+            writer,
+            tag,
+            attributes: vec![],
+            text: None,
+            started: false,
+            key: None,
+        }
+    }
+
+    fn write_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), XlsxError> {
+        if let Some(name) = key.strip_prefix(ATTRIBUTE_PREFIX) {
+            if self.started {
+                return Err(serialize_error(format!(
+                    "the attribute '{name}' of <{}> is after a child element",
+                    self.tag
+                )));
+            }
+            if let Some(value) = value.serialize(TextSerializer)? {
+                self.attributes.push((name.to_string(), value));
+            }
+        } else if key == TEXT_FIELD {
+            if self.started {
+                return Err(mixed_content(self.tag));
+            }
+            self.text = value.serialize(TextSerializer)?;
+        } else {
+            if self.text.is_some() {
+                return Err(mixed_content(self.tag));
+            }
+            if !self.started {
+                self.writer
+                    .xml_start_tag(self.tag, &attribute_refs(&self.attributes));
+                self.started = true;
+            }
+            value.serialize(Serializer::new(self.writer, key))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_end(self) -> Result<(), XlsxError> {
+        if self.started {
+            self.writer.xml_end_tag(self.tag);
+        } else if let Some(text) = &self.text {
+            self.writer
+                .xml_data_element(self.tag, text, &attribute_refs(&self.attributes));
+        } else {
+            self.writer
+                .xml_empty_tag(self.tag, &attribute_refs(&self.attributes));
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for ElementSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), XlsxError> {
+        self.write_field(key, value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        self.write_end()
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for ElementSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), XlsxError> {
+        self.write_field(key, value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        self.write_end()
+    }
+}
+
+impl<W: Write> ser::SerializeMap for ElementSerializer<'_, W> {
+    type Ok = ();
+    type Error = XlsxError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), XlsxError> {
+        let key = key
+            .serialize(TextSerializer)?
+            .ok_or_else(|| unsupported("map keys that are None"))?;
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), XlsxError> {
+        let key = self.key.take().unwrap_or_default();
+        self.write_field(&key, value)
+    }
+
+    fn end(self) -> Result<(), XlsxError> {
+        self.write_end()
+    }
+}
+
+// A serializer for attribute values, text and map keys, which have to be a
+// single value. None is returned for values that are omitted.
+struct TextSerializer;
+
+impl ser::Serializer for TextSerializer {
+    type Ok = Option<String>;
+    type Error = XlsxError;
+
+    type SerializeSeq = Impossible<Option<String>, XlsxError>;
+    type SerializeTuple = Impossible<Option<String>, XlsxError>;
+    type SerializeTupleStruct = Impossible<Option<String>, XlsxError>;
+    type SerializeTupleVariant = Impossible<Option<String>, XlsxError>;
+    type SerializeMap = Impossible<Option<String>, XlsxError>;
+    type SerializeStruct = Impossible<Option<String>, XlsxError>;
+    type SerializeStructVariant = Impossible<Option<String>, XlsxError>;
+
+    fn serialize_bool(self, value: bool) -> Result<Option<String>, XlsxError> {
+        Ok(Some(if value { "1" } else { "0" }.to_string()))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Option<String>, XlsxError> {
+        Ok(Some(value.to_string()))
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Option<String>, XlsxError> {
+        Err(unsupported("byte arrays"))
+    }
+
+    fn serialize_none(self) -> Result<Option<String>, XlsxError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<String>, XlsxError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<String>, XlsxError> {
+        Ok(Some(String::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<String>, XlsxError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Option<String>, XlsxError> {
+        Ok(Some(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Option<String>, XlsxError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Option<String>, XlsxError> {
+        Err(unsupported("enum variants with data as attributes or text"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, XlsxError> {
+        Err(unsupported("sequences as attributes or text"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, XlsxError> {
+        Err(unsupported("tuples as attributes or text"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, XlsxError> {
+        Err(unsupported("tuple structs as attributes or text"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, XlsxError> {
+        Err(unsupported("enum variants with data as attributes or text"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, XlsxError> {
+        Err(unsupported("maps as attributes or text"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, XlsxError> {
+        Err(unsupported("structs as attributes or text"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, XlsxError> {
+        Err(unsupported("enum variants with data as attributes or text"))
+    }
+}
+
+// Borrow the attributes of an element for the writer.
+fn attribute_refs(attributes: &[(String, String)]) -> Vec<(&str, &str)> {
+    attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect()
+}
+
+fn serialize_error(message: String) -> XlsxError {
+    XlsxError::Serialize(message)
+}
+
+fn unsupported(what: &str) -> XlsxError {
+    serialize_error(format!("{what} can't be serialized to XML"))
+}
+
+fn mixed_content(tag: &str) -> XlsxError {
+    serialize_error(format!("<{tag}> can't have both text and child elements"))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::to_writer;
+    use crate::error::XlsxError;
+    use crate::XMLWriter;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+
+    fn serialize<T: Serialize + ?Sized>(tag: &str, value: &T) -> Result<String, XlsxError> {
+        let mut writer = XMLWriter::new(vec![]);
+        to_writer(&mut writer, tag, value)?;
+
+        Ok(String::from_utf8(writer.into_inner()).unwrap())
+    }
+
+    #[derive(Serialize)]
+    enum Align {
+        Left,
+        Indent(u8),
+    }
+
+    #[derive(Serialize)]
+    struct Cell {
+        #[serde(rename = "@r")]
+        reference: &'static str,
+        #[serde(rename = "@hidden")]
+        hidden: Option<bool>,
+        #[serde(rename = "$text")]
+        value: f64,
+    }
+
+    #[derive(Serialize)]
+    struct Row {
+        #[serde(rename = "@r")]
+        number: u32,
+        #[serde(rename = "@locked")]
+        locked: bool,
+        label: &'static str,
+        align: Align,
+        indent: Align,
+        empty: (),
+        c: Vec<Cell>,
+        note: Option<&'static str>,
+    }
+
+    #[test]
+    fn test_serialize_struct() {
+        let row = Row {
+            number: 1,
+            locked: true,
+            label: "<Total> & \"sum\"",
+            align: Align::Left,
+            indent: Align::Indent(2),
+            empty: (),
+            c: vec![
+                Cell {
+                    reference: "A1",
+                    hidden: None,
+                    value: 1.5,
+                },
+                Cell {
+                    reference: "B1",
+                    hidden: Some(false),
+                    value: 2.0,
+                },
+            ],
+            note: None,
+        };
+
+        let expected = concat!(
+            r#"<row r="1" locked="1"><label>&lt;Total&gt; &amp; "sum"</label>"#,
+            r#"<align>Left</align><Indent>2</Indent><empty/>"#,
+            r#"<c r="A1">1.5</c><c r="B1" hidden="0">2</c></row>"#,
+        );
+        assert_eq!(serialize("row", &row).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_serialize_values() {
+        assert_eq!(serialize("v", &42).unwrap(), "<v>42</v>");
+        assert_eq!(serialize("v", "a<b").unwrap(), "<v>a&lt;b</v>");
+        assert_eq!(serialize("v", &[1, 2]).unwrap(), "<v>1</v><v>2</v>");
+        assert_eq!(serialize("v", &None::<u32>).unwrap(), "");
+
+        let mut map = BTreeMap::new();
+        map.insert("@id", "\"x\"");
+        map.insert("name", "y");
+        assert_eq!(
+            serialize("item", &map).unwrap(),
+            r#"<item id="&quot;x&quot;"><name>y</name></item>"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_errors() {
+        #[derive(Serialize)]
+        struct LateAttribute {
+            child: u32,
+            #[serde(rename = "@id")]
+            id: u32,
+        }
+
+        #[derive(Serialize)]
+        struct Mixed {
+            #[serde(rename = "$text")]
+            text: &'static str,
+            child: u32,
+        }
+
+        #[derive(Serialize)]
+        struct ListAttribute {
+            #[serde(rename = "@ids")]
+            ids: Vec<u32>,
+        }
+
+        assert_eq!(
+            serialize("a", &LateAttribute { child: 1, id: 2 }),
+            Err(XlsxError::Serialize(
+                "the attribute 'id' of <a> is after a child element".to_string()
+            ))
+        );
+        assert_eq!(
+            serialize(
+                "a",
+                &Mixed {
+                    text: "x",
+                    child: 1
+                }
+            ),
+            Err(XlsxError::Serialize(
+                "<a> can't have both text and child elements".to_string()
+            ))
+        );
+        assert_eq!(
+            serialize("a", &ListAttribute { ids: vec![1] }),
+            Err(XlsxError::Serialize(
+                "sequences as attributes or text can't be serialized to XML".to_string()
+            ))
+        );
+        assert_eq!(
+            serialize("a", &Bytes(&[1, 2])),
+            Err(XlsxError::Serialize(
+                "byte arrays can't be serialized to XML".to_string()
+            ))
+        );
+    }
+
+    // A value that serializes as a byte array.
+    struct Bytes(&'static [u8]);
+
+    impl Serialize for Bytes {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
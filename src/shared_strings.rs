@@ -0,0 +1,183 @@
+//! A deduplicating shared string table and a writer for the
+//! `xl/sharedStrings.xml` part.
+//!
+//! ```
+//! use excel_xmlwriter::shared_strings::SharedStringTable;
+//!
+//! let mut string_table = SharedStringTable::new();
+//!
+//! assert_eq!(string_table.shared_string_index("foo"), 0);
+//! assert_eq!(string_table.shared_string_index("bar"), 1);
+//! assert_eq!(string_table.shared_string_index("foo"), 0);
+//!
+//! assert_eq!(string_table.count(), 3);
+//! assert_eq!(string_table.unique_count(), 2);
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::constants::SPREADSHEETML_NS;
+use crate::XMLWriter;
+
+/// A table of unique strings, indexed in the order they were first added,
+/// for the `sharedStrings.xml` part.
+#[derive(Debug, Default)]
+pub struct SharedStringTable {
+    strings: HashMap<String, u32>,
+    count: u32,
+}
+
+impl SharedStringTable {
+    /// Create a new, empty, shared string table.
+    pub fn new() -> SharedStringTable {
+        SharedStringTable::default()
+    }
+
+    /// Get the index of a string in the table, adding it if it isn't already
+    /// there. The index is stable for the lifetime of the table and is the
+    /// value written in a `t="s"` cell. Every call counts as a reference to
+    /// the string for the `count` attribute.
+    pub fn shared_string_index(&mut self, string: &str) -> u32 {
+        self.count += 1;
+
+        if let Some(index) = self.strings.get(string) {
+            return *index;
+        }
+
+        let index = self.strings.len() as u32;
+        self.strings.insert(string.to_string(), index);
+        index
+    }
+
+    /// The total number of string references in the workbook.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// The number of unique strings in the table.
+    pub fn unique_count(&self) -> u32 {
+        self.strings.len() as u32
+    }
+
+    /// Write the `xl/sharedStrings.xml` part.
+    /// ```
+    /// # use std::fs::File;
+    /// # use excel_xmlwriter::XMLWriter;
+    /// # use excel_xmlwriter::shared_strings::SharedStringTable;
+    /// #
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let xmlfile = File::create("test.xml")?;
+    /// let mut writer = XMLWriter::new(&xmlfile);
+    /// let mut string_table = SharedStringTable::new();
+    ///
+    /// string_table.shared_string_index("Hello");
+    /// string_table.assemble_xml_file(&mut writer);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let count = self.count.to_string();
+        let unique_count = self.unique_count().to_string();
+        let attributes = vec![
+            ("xmlns", SPREADSHEETML_NS),
+            ("count", count.as_str()),
+            ("uniqueCount", unique_count.as_str()),
+        ];
+
+        writer.xml_start_tag("sst", &attributes);
+
+        let mut strings: Vec<(&String, &u32)> = self.strings.iter().collect();
+        strings.sort_by_key(|(_, index)| **index);
+
+        for (string, _) in strings {
+            // Excel preserves leading and trailing whitespace.
+            let attributes = if string.starts_with(char::is_whitespace)
+                || string.ends_with(char::is_whitespace)
+            {
+                vec![("xml:space", "preserve")]
+            } else {
+                vec![]
+            };
+
+            writer.xml_si_element(string, &attributes);
+        }
+
+        writer.xml_end_tag("sst");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SharedStringTable;
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_shared_string_index() {
+        let mut string_table = SharedStringTable::new();
+
+        assert_eq!(string_table.shared_string_index("foo"), 0);
+        assert_eq!(string_table.shared_string_index("bar"), 1);
+        assert_eq!(string_table.shared_string_index("foo"), 0);
+        assert_eq!(string_table.shared_string_index("Foo"), 2);
+        assert_eq!(string_table.shared_string_index("bar"), 1);
+
+        assert_eq!(string_table.count(), 5);
+        assert_eq!(string_table.unique_count(), 3);
+    }
+
+    #[test]
+    fn test_assemble_xml_file() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"count="7" uniqueCount="3"><si><t>neptune</t></si><si><t>mars</t></si>"#,
+            r#"<si><t xml:space="preserve"> &amp; venus</t></si></sst>"#,
+        );
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        let mut string_table = SharedStringTable::new();
+
+        for string in [
+            "neptune", "mars", "neptune", " & venus", "mars", "mars", "neptune",
+        ] {
+            string_table.shared_string_index(string);
+        }
+
+        string_table.assemble_xml_file(&mut writer);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_assemble_xml_file_empty() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"count="0" uniqueCount="0"></sst>"#,
+        );
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        let string_table = SharedStringTable::new();
+
+        string_table.assemble_xml_file(&mut writer);
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+}
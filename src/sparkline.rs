@@ -0,0 +1,371 @@
+//! The sparklines of a worksheet, the small charts that are drawn in a cell.
+//!
+//! A [`Sparkline`] plots a range of data, usually a row or column of a
+//! worksheet, in a single cell, and is added with
+//! [`Worksheet::add_sparkline()`](crate::worksheet::Worksheet::add_sparkline).
+//! Sparklines were added in Excel 2010, so they are written in the `x14`
+//! extension of the worksheet, in its `<extLst>` element. Older versions of
+//! Excel ignore them.
+//!
+//! ```
+//! use excel_xmlwriter::sparkline::{Sparkline, SparklineType};
+//! use excel_xmlwriter::styles::Color;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//!
+//! // Plot the data in A1:E1 as a column sparkline in F1.
+//! let sparkline = Sparkline::new("Sheet1", 0, 0, 0, 4)
+//!     .set_type(SparklineType::Column)
+//!     .set_series_color(Color::Rgb(0x4F81BD))
+//!     .set_show_high_point(true);
+//!
+//! worksheet.add_sparkline(0, 5, &sparkline);
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::styles::Color;
+use crate::utility::{quote_sheet_name, rowcol_to_cell};
+use crate::XMLWriter;
+
+/// The types of sparkline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SparklineType {
+    /// A line. The default.
+    #[default]
+    Line,
+    /// A column for each value.
+    Column,
+    /// A column above or below the axis for each positive or negative value.
+    WinLoss,
+}
+
+/// A sparkline, with the range of its data and its style.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sparkline {
+    range: String,
+    sparkline_type: SparklineType,
+    series_color: Color,
+    negative_color: Color,
+    axis_color: Color,
+    markers_color: Color,
+    high_point_color: Color,
+    low_point_color: Color,
+    first_point_color: Color,
+    last_point_color: Color,
+    markers: bool,
+    high_point: bool,
+    low_point: bool,
+    first_point: bool,
+    last_point: bool,
+    negative_points: bool,
+    axis: bool,
+    right_to_left: bool,
+    custom_min: Option<f64>,
+    custom_max: Option<f64>,
+}
+
+impl Sparkline {
+    /// Create a sparkline of a range of data in a worksheet. The first and
+    /// last cells can be given in either order. The sparkline is a line with
+    /// Excel's default colors.
+    pub fn new(
+        sheet_name: &str,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+    ) -> Sparkline {
+        let (first_row, last_row) = (first_row.min(last_row), first_row.max(last_row));
+        let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+
+        Sparkline {
+            range: format!(
+                "{}!{}:{}",
+                quote_sheet_name(sheet_name),
+                rowcol_to_cell(first_row, first_col),
+                rowcol_to_cell(last_row, last_col)
+            ),
+            sparkline_type: SparklineType::Line,
+            series_color: Color::Rgb(0x376092),
+            negative_color: Color::Rgb(0xD00000),
+            axis_color: Color::Rgb(0x000000),
+            markers_color: Color::Rgb(0xD00000),
+            high_point_color: Color::Rgb(0xD00000),
+            low_point_color: Color::Rgb(0xD00000),
+            first_point_color: Color::Rgb(0xD00000),
+            last_point_color: Color::Rgb(0xD00000),
+            markers: false,
+            high_point: false,
+            low_point: false,
+            first_point: false,
+            last_point: false,
+            negative_points: false,
+            axis: false,
+            right_to_left: false,
+            custom_min: None,
+            custom_max: None,
+        }
+    }
+
+    /// Set the type of the sparkline.
+    pub fn set_type(mut self, sparkline_type: SparklineType) -> Sparkline {
+        self.sparkline_type = sparkline_type;
+        self
+    }
+
+    /// Set the color of the line or columns.
+    pub fn set_series_color(mut self, color: Color) -> Sparkline {
+        self.series_color = color;
+        self
+    }
+
+    /// Set the color of the negative points.
+    pub fn set_negative_color(mut self, color: Color) -> Sparkline {
+        self.negative_color = color;
+        self
+    }
+
+    /// Set the color of the horizontal axis.
+    pub fn set_axis_color(mut self, color: Color) -> Sparkline {
+        self.axis_color = color;
+        self
+    }
+
+    /// Set the color of the markers of a line sparkline.
+    pub fn set_markers_color(mut self, color: Color) -> Sparkline {
+        self.markers_color = color;
+        self
+    }
+
+    /// Set the color of the highest point.
+    pub fn set_high_point_color(mut self, color: Color) -> Sparkline {
+        self.high_point_color = color;
+        self
+    }
+
+    /// Set the color of the lowest point.
+    pub fn set_low_point_color(mut self, color: Color) -> Sparkline {
+        self.low_point_color = color;
+        self
+    }
+
+    /// Set the color of the first point.
+    pub fn set_first_point_color(mut self, color: Color) -> Sparkline {
+        self.first_point_color = color;
+        self
+    }
+
+    /// Set the color of the last point.
+    pub fn set_last_point_color(mut self, color: Color) -> Sparkline {
+        self.last_point_color = color;
+        self
+    }
+
+    /// Set whether a line sparkline has a marker at each point. The default
+    /// is `false`.
+    pub fn set_show_markers(mut self, show: bool) -> Sparkline {
+        self.markers = show;
+        self
+    }
+
+    /// Set whether the highest point is highlighted. The default is `false`.
+    pub fn set_show_high_point(mut self, show: bool) -> Sparkline {
+        self.high_point = show;
+        self
+    }
+
+    /// Set whether the lowest point is highlighted. The default is `false`.
+    pub fn set_show_low_point(mut self, show: bool) -> Sparkline {
+        self.low_point = show;
+        self
+    }
+
+    /// Set whether the first point is highlighted. The default is `false`.
+    pub fn set_show_first_point(mut self, show: bool) -> Sparkline {
+        self.first_point = show;
+        self
+    }
+
+    /// Set whether the last point is highlighted. The default is `false`.
+    pub fn set_show_last_point(mut self, show: bool) -> Sparkline {
+        self.last_point = show;
+        self
+    }
+
+    /// Set whether the negative points are highlighted. The default is
+    /// `false`.
+    pub fn set_show_negative_points(mut self, show: bool) -> Sparkline {
+        self.negative_points = show;
+        self
+    }
+
+    /// Set whether the horizontal axis is shown, at zero. The default is
+    /// `false`.
+    pub fn set_show_axis(mut self, show: bool) -> Sparkline {
+        self.axis = show;
+        self
+    }
+
+    /// Set whether the data is plotted from right to left. The default is
+    /// `false`.
+    pub fn set_right_to_left(mut self, right_to_left: bool) -> Sparkline {
+        self.right_to_left = right_to_left;
+        self
+    }
+
+    /// Set the minimum of the vertical axis. The default is the lowest value
+    /// of the data.
+    pub fn set_custom_min(mut self, min: f64) -> Sparkline {
+        self.custom_min = Some(min);
+        self
+    }
+
+    /// Set the maximum of the vertical axis. The default is the highest value
+    /// of the data.
+    pub fn set_custom_max(mut self, max: f64) -> Sparkline {
+        self.custom_max = Some(max);
+        self
+    }
+
+    // Write the `<x14:sparklineGroup>` element of the sparkline in a cell.
+    pub(crate) fn write_sparkline_group(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        row: u32,
+        col: u16,
+    ) {
+        let custom_max = self.custom_max.map(|max| max.to_string());
+        let custom_min = self.custom_min.map(|min| min.to_string());
+
+        let mut attributes = vec![];
+        if let Some(custom_max) = &custom_max {
+            attributes.push(("manualMax", custom_max.as_str()));
+        }
+        if let Some(custom_min) = &custom_min {
+            attributes.push(("manualMin", custom_min.as_str()));
+        }
+        match self.sparkline_type {
+            SparklineType::Line => {}
+            SparklineType::Column => attributes.push(("type", "column")),
+            SparklineType::WinLoss => attributes.push(("type", "stacked")),
+        }
+        attributes.push(("displayEmptyCellsAs", "gap"));
+
+        let flags = [
+            ("markers", self.markers),
+            ("high", self.high_point),
+            ("low", self.low_point),
+            ("first", self.first_point),
+            ("last", self.last_point),
+            ("negative", self.negative_points),
+            ("displayXAxis", self.axis),
+        ];
+        for (name, on) in flags {
+            if on {
+                attributes.push((name, "1"));
+            }
+        }
+        if custom_min.is_some() {
+            attributes.push(("minAxisType", "custom"));
+        }
+        if custom_max.is_some() {
+            attributes.push(("maxAxisType", "custom"));
+        }
+        if self.right_to_left {
+            attributes.push(("rightToLeft", "1"));
+        }
+        writer.xml_start_tag("x14:sparklineGroup", &attributes);
+
+        let colors = [
+            ("x14:colorSeries", self.series_color),
+            ("x14:colorNegative", self.negative_color),
+            ("x14:colorAxis", self.axis_color),
+            ("x14:colorMarkers", self.markers_color),
+            ("x14:colorFirst", self.first_point_color),
+            ("x14:colorLast", self.last_point_color),
+            ("x14:colorHigh", self.high_point_color),
+            ("x14:colorLow", self.low_point_color),
+        ];
+        for (tag, color) in colors {
+            let (name, value) = color.attribute();
+            writer.xml_empty_tag(tag, &vec![(name, value.as_str())]);
+        }
+
+        writer.xml_start_tag("x14:sparklines", &vec![]);
+        writer.xml_start_tag("x14:sparkline", &vec![]);
+        writer.xml_data_element("xm:f", &self.range, &vec![]);
+        writer.xml_data_element("xm:sqref", &rowcol_to_cell(row, col), &vec![]);
+        writer.xml_end_tag("x14:sparkline");
+        writer.xml_end_tag("x14:sparklines");
+
+        writer.xml_end_tag("x14:sparklineGroup");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Sparkline, SparklineType};
+    use crate::styles::Color;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn write_sparkline(sparkline: &Sparkline, row: u32, col: u16) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        sparkline.write_sparkline_group(&mut writer, row, col);
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_default_sparkline() {
+        let expected = concat!(
+            r#"<x14:sparklineGroup displayEmptyCellsAs="gap"><x14:colorSeries rgb="FF376092"/>"#,
+            r#"<x14:colorNegative rgb="FFD00000"/><x14:colorAxis rgb="FF000000"/>"#,
+            r#"<x14:colorMarkers rgb="FFD00000"/><x14:colorFirst rgb="FFD00000"/>"#,
+            r#"<x14:colorLast rgb="FFD00000"/><x14:colorHigh rgb="FFD00000"/>"#,
+            r#"<x14:colorLow rgb="FFD00000"/><x14:sparklines><x14:sparkline>"#,
+            r#"<xm:f>'Q1 Sales'!A1:E1</xm:f><xm:sqref>F1</xm:sqref></x14:sparkline>"#,
+            r#"</x14:sparklines></x14:sparklineGroup>"#,
+        );
+
+        let sparkline = Sparkline::new("Q1 Sales", 0, 4, 0, 0);
+
+        assert_eq!(write_sparkline(&sparkline, 0, 5), expected);
+    }
+
+    #[test]
+    fn test_sparkline_options() {
+        let sparkline = Sparkline::new("Sheet1", 1, 0, 1, 9)
+            .set_type(SparklineType::WinLoss)
+            .set_series_color(Color::Theme(4))
+            .set_show_markers(true)
+            .set_show_high_point(true)
+            .set_show_low_point(true)
+            .set_show_first_point(true)
+            .set_show_last_point(true)
+            .set_show_negative_points(true)
+            .set_show_axis(true)
+            .set_right_to_left(true)
+            .set_custom_min(-1.5)
+            .set_custom_max(10.0);
+        let got = write_sparkline(&sparkline, 1, 10);
+
+        assert!(got.starts_with(concat!(
+            r#"<x14:sparklineGroup manualMax="10" manualMin="-1.5" type="stacked" "#,
+            r#"displayEmptyCellsAs="gap" markers="1" high="1" low="1" first="1" last="1" "#,
+            r#"negative="1" displayXAxis="1" minAxisType="custom" maxAxisType="custom" "#,
+            r#"rightToLeft="1"><x14:colorSeries theme="4"/>"#,
+        )));
+        assert!(got.contains("<xm:f>Sheet1!A2:J2</xm:f><xm:sqref>K2</xm:sqref>"));
+
+        let sparkline = Sparkline::new("Sheet1", 0, 0, 4, 0).set_type(SparklineType::Column);
+        assert!(write_sparkline(&sparkline, 5, 0)
+            .starts_with(r#"<x14:sparklineGroup type="column" displayEmptyCellsAs="gap">"#));
+    }
+}
@@ -0,0 +1,1736 @@
+//! Cell formatting and the writer for the `xl/styles.xml` part.
+//!
+//! A [`Format`] describes the number format, font, fill, border, alignment
+//! and protection properties of a cell. The [`Styles`] table deduplicates
+//! formats into the `cellXfs` indices used in the `s` attribute of cells, and
+//! the fonts, fills and borders that they refer to.
+//!
+//! ```
+//! use excel_xmlwriter::styles::{Color, Format, Styles};
+//!
+//! let mut styles = Styles::new();
+//!
+//! let bold = Format::new().set_bold();
+//! let red = Format::new().set_font_color(Color::Rgb(0xFF0000));
+//!
+//! assert_eq!(styles.xf_index(&Format::new()), 0);
+//! assert_eq!(styles.xf_index(&bold), 1);
+//! assert_eq!(styles.xf_index(&red), 2);
+//! assert_eq!(styles.xf_index(&Format::new().set_bold()), 1);
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::constants::SPREADSHEETML_NS;
+use crate::XMLWriter;
+
+// The first numFmtId for custom number formats. Lower ids are built-in.
+const FIRST_CUSTOM_NUM_FORMAT_ID: u16 = 164;
+
+/// A color used in fonts, fills and borders.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// The automatic, or default, color for the element.
+    #[default]
+    Automatic,
+
+    /// An RGB color like `0xFF0000` for red.
+    Rgb(u32),
+
+    /// A theme color index.
+    Theme(u8),
+}
+
+impl Color {
+    // Get the attribute name and value used to write the color.
+    pub(crate) fn attribute(&self) -> (&'static str, String) {
+        match self {
+            Color::Automatic => ("auto", "1".to_string()),
+            Color::Rgb(rgb) => ("rgb", format!("FF{:06X}", rgb & 0xFFFFFF)),
+            Color::Theme(theme) => ("theme", theme.to_string()),
+        }
+    }
+}
+
+/// Font underline styles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FormatUnderline {
+    /// No underline. The default.
+    #[default]
+    None,
+    /// Single underline.
+    Single,
+    /// Double underline.
+    Double,
+    /// Single accounting underline.
+    SingleAccounting,
+    /// Double accounting underline.
+    DoubleAccounting,
+}
+
+/// Font superscript and subscript styles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FormatScript {
+    /// Normal text. The default.
+    #[default]
+    None,
+    /// Superscript.
+    Superscript,
+    /// Subscript.
+    Subscript,
+}
+
+/// Fill patterns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FormatPattern {
+    /// No fill. The default.
+    #[default]
+    None,
+    /// Solid fill.
+    Solid,
+    /// Medium gray pattern.
+    MediumGray,
+    /// Dark gray pattern.
+    DarkGray,
+    /// Light gray pattern.
+    LightGray,
+    /// Dark horizontal lines.
+    DarkHorizontal,
+    /// Dark vertical lines.
+    DarkVertical,
+    /// Dark diagonal stripes, top left to bottom right.
+    DarkDown,
+    /// Dark diagonal stripes, bottom left to top right.
+    DarkUp,
+    /// Dark grid.
+    DarkGrid,
+    /// Dark trellis.
+    DarkTrellis,
+    /// Light horizontal lines.
+    LightHorizontal,
+    /// Light vertical lines.
+    LightVertical,
+    /// Light diagonal stripes, top left to bottom right.
+    LightDown,
+    /// Light diagonal stripes, bottom left to top right.
+    LightUp,
+    /// Light grid.
+    LightGrid,
+    /// Light trellis.
+    LightTrellis,
+    /// 12.5% gray.
+    Gray125,
+    /// 6.25% gray.
+    Gray0625,
+}
+
+impl FormatPattern {
+    fn value(&self) -> &'static str {
+        match self {
+            FormatPattern::None => "none",
+            FormatPattern::Solid => "solid",
+            FormatPattern::MediumGray => "mediumGray",
+            FormatPattern::DarkGray => "darkGray",
+            FormatPattern::LightGray => "lightGray",
+            FormatPattern::DarkHorizontal => "darkHorizontal",
+            FormatPattern::DarkVertical => "darkVertical",
+            FormatPattern::DarkDown => "darkDown",
+            FormatPattern::DarkUp => "darkUp",
+            FormatPattern::DarkGrid => "darkGrid",
+            FormatPattern::DarkTrellis => "darkTrellis",
+            FormatPattern::LightHorizontal => "lightHorizontal",
+            FormatPattern::LightVertical => "lightVertical",
+            FormatPattern::LightDown => "lightDown",
+            FormatPattern::LightUp => "lightUp",
+            FormatPattern::LightGrid => "lightGrid",
+            FormatPattern::LightTrellis => "lightTrellis",
+            FormatPattern::Gray125 => "gray125",
+            FormatPattern::Gray0625 => "gray0625",
+        }
+    }
+}
+
+/// Border line styles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FormatBorder {
+    /// No border. The default.
+    #[default]
+    None,
+    /// Thin line.
+    Thin,
+    /// Medium line.
+    Medium,
+    /// Dashed line.
+    Dashed,
+    /// Dotted line.
+    Dotted,
+    /// Thick line.
+    Thick,
+    /// Double line.
+    Double,
+    /// Hair line.
+    Hair,
+    /// Medium dashed line.
+    MediumDashed,
+    /// Dash dot line.
+    DashDot,
+    /// Medium dash dot line.
+    MediumDashDot,
+    /// Dash dot dot line.
+    DashDotDot,
+    /// Medium dash dot dot line.
+    MediumDashDotDot,
+    /// Slant dash dot line.
+    SlantDashDot,
+}
+
+impl FormatBorder {
+    fn value(&self) -> &'static str {
+        match self {
+            FormatBorder::None => "none",
+            FormatBorder::Thin => "thin",
+            FormatBorder::Medium => "medium",
+            FormatBorder::Dashed => "dashed",
+            FormatBorder::Dotted => "dotted",
+            FormatBorder::Thick => "thick",
+            FormatBorder::Double => "double",
+            FormatBorder::Hair => "hair",
+            FormatBorder::MediumDashed => "mediumDashed",
+            FormatBorder::DashDot => "dashDot",
+            FormatBorder::MediumDashDot => "mediumDashDot",
+            FormatBorder::DashDotDot => "dashDotDot",
+            FormatBorder::MediumDashDotDot => "mediumDashDotDot",
+            FormatBorder::SlantDashDot => "slantDashDot",
+        }
+    }
+}
+
+/// The direction of diagonal borders.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FormatDiagonalBorder {
+    /// No diagonal border. The default.
+    #[default]
+    None,
+    /// Diagonal from bottom left to top right.
+    BorderUp,
+    /// Diagonal from top left to bottom right.
+    BorderDown,
+    /// Both diagonals.
+    BorderUpDown,
+}
+
+/// Horizontal and vertical cell alignments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FormatAlign {
+    /// The default, general, alignment.
+    #[default]
+    General,
+    /// Left horizontal alignment.
+    Left,
+    /// Center horizontal alignment.
+    Center,
+    /// Right horizontal alignment.
+    Right,
+    /// Fill horizontal alignment.
+    Fill,
+    /// Justify horizontal alignment.
+    Justify,
+    /// Center across selection horizontal alignment.
+    CenterAcross,
+    /// Distributed horizontal alignment.
+    Distributed,
+    /// Top vertical alignment.
+    Top,
+    /// Bottom vertical alignment. This is Excel's default.
+    Bottom,
+    /// Center vertical alignment.
+    VerticalCenter,
+    /// Justify vertical alignment.
+    VerticalJustify,
+    /// Distributed vertical alignment.
+    VerticalDistributed,
+}
+
+/// The workbook default font.
+///
+/// The default font is the font of the Normal style and of any format that
+/// doesn't set its own font name or size. Excel also measures column widths
+/// in the width of a digit in the default font, and the default row height
+/// depends on it, so worksheets take these metrics from the default font of
+/// the [`Styles`] table they are written with.
+///
+/// The metrics of Excel's Calibri 11 and Arial 10 defaults are built in. For
+/// other fonts they are scaled from Calibri 11 and can be set exactly with
+/// [`set_metrics()`](DefaultFont::set_metrics).
+///
+/// ```
+/// use excel_xmlwriter::styles::{DefaultFont, Styles};
+///
+/// let mut styles = Styles::new();
+/// styles.set_default_font(&DefaultFont::new("Arial", 10.0));
+///
+/// assert_eq!(styles.default_font().row_height(), 12.75);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefaultFont {
+    name: String,
+    size: f64,
+    family: u8,
+    max_digit_width: f64,
+    row_height: f64,
+}
+
+impl Default for DefaultFont {
+    fn default() -> Self {
+        DefaultFont::new("Calibri", 11.0)
+    }
+}
+
+impl DefaultFont {
+    /// Create a default font with a name and a size in points. The font
+    /// family is 2, "Swiss".
+    pub fn new(name: &str, size: f64) -> DefaultFont {
+        let (max_digit_width, row_height) = match (name, size) {
+            ("Calibri", 11.0) => (7.0, 15.0),
+            ("Arial", 10.0) => (7.0, 12.75),
+            // Scale from Calibri 11, rounding the row height to the 0.75
+            // point (1 pixel) steps that Excel uses.
+            _ => (
+                (7.0 * size / 11.0).round().max(1.0),
+                (15.0 * size / 11.0 / 0.75).round() * 0.75,
+            ),
+        };
+
+        DefaultFont {
+            name: name.to_string(),
+            size,
+            family: 2,
+            max_digit_width,
+            row_height,
+        }
+    }
+
+    /// Set the font family number.
+    pub fn set_family(mut self, family: u8) -> DefaultFont {
+        self.family = family;
+        self
+    }
+
+    /// Set the width in pixels of the widest digit in the font, and the
+    /// default row height in points, for fonts that the built-in metrics
+    /// don't cover.
+    pub fn set_metrics(mut self, max_digit_width: f64, row_height: f64) -> DefaultFont {
+        self.max_digit_width = max_digit_width;
+        self.row_height = row_height;
+        self
+    }
+
+    /// The font name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The font size in points.
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+
+    /// The width in pixels of the widest digit in the font.
+    pub fn max_digit_width(&self) -> f64 {
+        self.max_digit_width
+    }
+
+    /// The default row height in points.
+    pub fn row_height(&self) -> f64 {
+        self.row_height
+    }
+
+    // Convert a column width in character units, as shown in Excel, to the
+    // stored width which includes the cell padding and is rounded to the
+    // nearest pixel and truncated to 1/256 of a character.
+    #[cfg(feature = "worksheet")]
+    pub(crate) fn column_width(&self, width: f64) -> f64 {
+        let max_digit_width = self.max_digit_width;
+        let padding = 2.0 * (max_digit_width / 4.0).ceil() + 1.0;
+
+        if width <= 0.0 {
+            return 0.0;
+        }
+
+        let pixels = if width < 1.0 {
+            (width * (max_digit_width + padding)).round()
+        } else {
+            (width * max_digit_width).round() + padding
+        };
+
+        (pixels / max_digit_width * 256.0).floor() / 256.0
+    }
+}
+
+// The font properties of a format.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Font {
+    bold: bool,
+    italic: bool,
+    underline: FormatUnderline,
+    strikethrough: bool,
+    script: FormatScript,
+    color: Color,
+    // The name, size and family default to those of the workbook default
+    // font when the styles are written.
+    name: Option<String>,
+    size: Option<f64>,
+    family: Option<u8>,
+}
+
+// The fill properties of a format.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct Fill {
+    pattern: FormatPattern,
+    foreground_color: Color,
+    background_color: Color,
+}
+
+// The border properties of a format.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct Border {
+    left: (FormatBorder, Color),
+    right: (FormatBorder, Color),
+    top: (FormatBorder, Color),
+    bottom: (FormatBorder, Color),
+    diagonal: (FormatBorder, Color),
+    diagonal_type: FormatDiagonalBorder,
+}
+
+// The alignment properties of a format.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+struct Alignment {
+    horizontal: FormatAlign,
+    vertical: FormatAlign,
+    rotation: i16,
+    text_wrap: bool,
+    indent: u8,
+    shrink: bool,
+}
+
+/// The format properties of a cell.
+///
+/// Formats are built up with chained `set_*()` methods. Two formats with the
+/// same properties map to the same `cellXfs` index in the [`Styles`] table.
+/// ```
+/// use excel_xmlwriter::styles::{Color, Format, FormatAlign, FormatBorder};
+///
+/// let format = Format::new()
+///     .set_bold()
+///     .set_font_color(Color::Rgb(0x0000FF))
+///     .set_border(FormatBorder::Thin)
+///     .set_align(FormatAlign::Center);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Format {
+    num_format_index: u16,
+    num_format: String,
+    font: Font,
+    fill: Fill,
+    border: Border,
+    alignment: Alignment,
+    locked: bool,
+    hidden: bool,
+    hyperlink: bool,
+    quote_prefix: bool,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            num_format_index: 0,
+            num_format: String::new(),
+            font: Font::default(),
+            fill: Fill::default(),
+            border: Border::default(),
+            alignment: Alignment::default(),
+            locked: true,
+            hidden: false,
+            hyperlink: false,
+            quote_prefix: false,
+        }
+    }
+}
+
+impl Format {
+    /// Create a new format with Excel's default properties.
+    pub fn new() -> Format {
+        Format::default()
+    }
+
+    /// Set one of Excel's built-in number formats by index, for example 2
+    /// for `0.00`, 10 for `0.00%` or 14 for a date.
+    pub fn set_num_format_index(mut self, index: u8) -> Format {
+        self.num_format_index = index as u16;
+        self.num_format.clear();
+        self
+    }
+
+    /// Set a custom number format, like `#,##0.00;[Red](#,##0.00)` or
+    /// `yyyy-mm-dd`. Custom formats are stored in the `<numFmts>` element of
+    /// the styles part, with ids from 164, and formats with the same format
+    /// string share an id.
+    pub fn set_num_format(mut self, num_format: &str) -> Format {
+        self.num_format = num_format.to_string();
+        self.num_format_index = 0;
+        self
+    }
+
+    /// Set the bold property.
+    pub fn set_bold(mut self) -> Format {
+        self.font.bold = true;
+        self
+    }
+
+    /// Set the italic property.
+    pub fn set_italic(mut self) -> Format {
+        self.font.italic = true;
+        self
+    }
+
+    /// Set the underline style.
+    pub fn set_underline(mut self, underline: FormatUnderline) -> Format {
+        self.font.underline = underline;
+        self
+    }
+
+    /// Set the format to Excel's built-in Hyperlink cell style, an underlined
+    /// font in the theme hyperlink color. This is the default format of
+    /// cells written with
+    /// [`Worksheet::write_url()`](crate::worksheet::Worksheet::write_url).
+    pub fn set_hyperlink(mut self) -> Format {
+        self.font.underline = FormatUnderline::Single;
+        self.font.color = Color::Theme(10);
+        self.hyperlink = true;
+        self
+    }
+
+    /// Set the strikethrough property.
+    pub fn set_font_strikethrough(mut self) -> Format {
+        self.font.strikethrough = true;
+        self
+    }
+
+    /// Set the superscript or subscript property.
+    pub fn set_font_script(mut self, script: FormatScript) -> Format {
+        self.font.script = script;
+        self
+    }
+
+    /// Set the font size in points. The default is the size of the workbook
+    /// [`DefaultFont`].
+    pub fn set_font_size(mut self, size: f64) -> Format {
+        self.font.size = Some(size);
+        self
+    }
+
+    /// Set the font color.
+    pub fn set_font_color(mut self, color: Color) -> Format {
+        self.font.color = color;
+        self
+    }
+
+    /// Set the font name. The default is the name of the workbook
+    /// [`DefaultFont`]. Fonts other than "Calibri" don't use the theme font
+    /// scheme.
+    pub fn set_font_name(mut self, name: &str) -> Format {
+        self.font.name = Some(name.to_string());
+        self
+    }
+
+    /// Set the font family number. The default is the family of the workbook
+    /// [`DefaultFont`].
+    pub fn set_font_family(mut self, family: u8) -> Format {
+        self.font.family = Some(family);
+        self
+    }
+
+    /// Set the fill pattern.
+    pub fn set_pattern(mut self, pattern: FormatPattern) -> Format {
+        self.fill.pattern = pattern;
+        self
+    }
+
+    /// Set the fill background color. If no pattern has been set this also
+    /// sets a solid fill.
+    pub fn set_background_color(mut self, color: Color) -> Format {
+        self.fill.background_color = color;
+        self
+    }
+
+    /// Set the fill foreground color, the color of the pattern.
+    pub fn set_foreground_color(mut self, color: Color) -> Format {
+        self.fill.foreground_color = color;
+        self
+    }
+
+    /// Set all four cell borders to the same style.
+    pub fn set_border(self, border: FormatBorder) -> Format {
+        self.set_border_left(border)
+            .set_border_right(border)
+            .set_border_top(border)
+            .set_border_bottom(border)
+    }
+
+    /// Set all four cell border colors.
+    pub fn set_border_color(self, color: Color) -> Format {
+        self.set_border_left_color(color)
+            .set_border_right_color(color)
+            .set_border_top_color(color)
+            .set_border_bottom_color(color)
+    }
+
+    /// Set the left border style.
+    pub fn set_border_left(mut self, border: FormatBorder) -> Format {
+        self.border.left.0 = border;
+        self
+    }
+
+    /// Set the left border color.
+    pub fn set_border_left_color(mut self, color: Color) -> Format {
+        self.border.left.1 = color;
+        self
+    }
+
+    /// Set the right border style.
+    pub fn set_border_right(mut self, border: FormatBorder) -> Format {
+        self.border.right.0 = border;
+        self
+    }
+
+    /// Set the right border color.
+    pub fn set_border_right_color(mut self, color: Color) -> Format {
+        self.border.right.1 = color;
+        self
+    }
+
+    /// Set the top border style.
+    pub fn set_border_top(mut self, border: FormatBorder) -> Format {
+        self.border.top.0 = border;
+        self
+    }
+
+    /// Set the top border color.
+    pub fn set_border_top_color(mut self, color: Color) -> Format {
+        self.border.top.1 = color;
+        self
+    }
+
+    /// Set the bottom border style.
+    pub fn set_border_bottom(mut self, border: FormatBorder) -> Format {
+        self.border.bottom.0 = border;
+        self
+    }
+
+    /// Set the bottom border color.
+    pub fn set_border_bottom_color(mut self, color: Color) -> Format {
+        self.border.bottom.1 = color;
+        self
+    }
+
+    /// Set the diagonal border style and direction.
+    pub fn set_border_diagonal(
+        mut self,
+        border: FormatBorder,
+        diagonal_type: FormatDiagonalBorder,
+    ) -> Format {
+        self.border.diagonal.0 = border;
+        self.border.diagonal_type = diagonal_type;
+        self
+    }
+
+    /// Set the diagonal border color.
+    pub fn set_border_diagonal_color(mut self, color: Color) -> Format {
+        self.border.diagonal.1 = color;
+        self
+    }
+
+    /// Set a horizontal or vertical alignment. This can be called twice to
+    /// set both.
+    pub fn set_align(mut self, align: FormatAlign) -> Format {
+        match align {
+            FormatAlign::Top
+            | FormatAlign::Bottom
+            | FormatAlign::VerticalCenter
+            | FormatAlign::VerticalJustify
+            | FormatAlign::VerticalDistributed => self.alignment.vertical = align,
+            _ => self.alignment.horizontal = align,
+        }
+        self
+    }
+
+    /// Set the text wrap property.
+    pub fn set_text_wrap(mut self) -> Format {
+        self.alignment.text_wrap = true;
+        self
+    }
+
+    /// Set the indentation level.
+    pub fn set_indent(mut self, indent: u8) -> Format {
+        self.alignment.indent = indent;
+        self
+    }
+
+    /// Set the text rotation in degrees, -90 to 90, or 270 for stacked text.
+    pub fn set_rotation(mut self, rotation: i16) -> Format {
+        self.alignment.rotation = match rotation {
+            270 => 255,
+            -90..=-1 => 90 - rotation,
+            0..=90 => rotation,
+            _ => 0,
+        };
+        self
+    }
+
+    /// Set the shrink to fit property.
+    pub fn set_shrink(mut self) -> Format {
+        self.alignment.shrink = true;
+        self
+    }
+
+    /// Turn off the locked property. Cells are locked by default, which only
+    /// has an effect when the worksheet is protected.
+    pub fn set_unlocked(mut self) -> Format {
+        self.locked = false;
+        self
+    }
+
+    /// Set the hidden property, which hides formulas when the worksheet is
+    /// protected.
+    pub fn set_hidden(mut self) -> Format {
+        self.hidden = true;
+        self
+    }
+
+    /// Set Excel's quote prefix property, the hidden quote that Excel adds
+    /// when a string like `=1+2` is typed with a leading `'`. The cell keeps
+    /// the string as text when it is edited, and the quote isn't part of its
+    /// value. See [`SanitizePolicy::QuotePrefix`](crate::sanitize::SanitizePolicy::QuotePrefix).
+    pub fn set_quote_prefix(mut self) -> Format {
+        self.quote_prefix = true;
+        self
+    }
+
+    // Excel switches a solid fill with only a background color to use the
+    // foreground color, and adds a solid pattern when only a color is set.
+    fn normalized_fill(&self) -> Fill {
+        let mut fill = self.fill.clone();
+
+        if fill.pattern == FormatPattern::None
+            && (fill.background_color != Color::Automatic
+                || fill.foreground_color != Color::Automatic)
+        {
+            fill.pattern = FormatPattern::Solid;
+        }
+
+        if fill.pattern == FormatPattern::Solid
+            && fill.background_color != Color::Automatic
+            && fill.foreground_color == Color::Automatic
+        {
+            fill.foreground_color = fill.background_color;
+            fill.background_color = Color::Automatic;
+        }
+
+        fill
+    }
+
+    fn has_alignment(&self) -> bool {
+        self.alignment != Alignment::default()
+    }
+
+    fn has_protection(&self) -> bool {
+        !self.locked || self.hidden
+    }
+}
+
+// The indices of a format's components in the style table.
+#[derive(Clone, Copy, Debug)]
+struct XfIndices {
+    num_format: u16,
+    font: u32,
+    fill: u32,
+    border: u32,
+}
+
+/// The table of unique formats, fonts, fills and borders in a workbook, and
+/// the writer for the `xl/styles.xml` part.
+///
+/// Index 0 is always the default format.
+///
+/// A table can be cloned once all of the formats in a workbook have been
+/// added, so that worksheets can be written on separate threads with the
+/// same indices.
+#[derive(Clone, Debug)]
+pub struct Styles {
+    default_font: DefaultFont,
+    xf_formats: Vec<(Format, XfIndices)>,
+    xf_keys: HashMap<String, u32>,
+    num_formats: Vec<String>,
+    num_format_keys: HashMap<String, u16>,
+    fonts: Vec<Font>,
+    font_keys: HashMap<String, u32>,
+    fills: Vec<Fill>,
+    fill_keys: HashMap<Fill, u32>,
+    borders: Vec<Border>,
+    border_keys: HashMap<Border, u32>,
+    hyperlink_font: Option<u32>,
+    dxf_formats: Vec<Format>,
+    dxf_keys: HashMap<String, u32>,
+}
+
+impl Default for Styles {
+    fn default() -> Self {
+        Styles::new()
+    }
+}
+
+impl Styles {
+    /// Create a new style table containing the default format.
+    pub fn new() -> Styles {
+        let mut styles = Styles {
+            default_font: DefaultFont::default(),
+            xf_formats: vec![],
+            xf_keys: HashMap::new(),
+            num_formats: vec![],
+            num_format_keys: HashMap::new(),
+            fonts: vec![],
+            font_keys: HashMap::new(),
+            fills: vec![],
+            fill_keys: HashMap::new(),
+            borders: vec![],
+            border_keys: HashMap::new(),
+            hyperlink_font: None,
+            dxf_formats: vec![],
+            dxf_keys: HashMap::new(),
+        };
+
+        // Excel requires the first two fills to be the "none" and "gray125"
+        // patterns.
+        styles.fill_index(&Fill::default());
+        styles.fill_index(&Fill {
+            pattern: FormatPattern::Gray125,
+            ..Fill::default()
+        });
+
+        styles.xf_index(&Format::default());
+        styles
+    }
+
+    /// Get the `cellXfs` index of a format, adding it to the table if it
+    /// isn't already there. This is the value of the `s` attribute of a cell.
+    pub fn xf_index(&mut self, format: &Format) -> u32 {
+        let key = format!("{:?}", format);
+
+        if let Some(index) = self.xf_keys.get(&key) {
+            return *index;
+        }
+
+        let indices = XfIndices {
+            num_format: self.num_format_id(format),
+            font: self.font_index(&format.font),
+            fill: self.fill_index(&format.normalized_fill()),
+            border: self.border_index(&format.border),
+        };
+
+        // The Hyperlink cell style uses the font of the first hyperlink
+        // format.
+        if format.hyperlink && self.hyperlink_font.is_none() {
+            self.hyperlink_font = Some(indices.font);
+        }
+
+        let index = self.xf_formats.len() as u32;
+        self.xf_formats.push((format.clone(), indices));
+        self.xf_keys.insert(key, index);
+        index
+    }
+
+    /// Get the `dxfs` index of a differential format, as used by conditional
+    /// formats, adding it to the table if it isn't already there. Only the
+    /// font style and color, fill and border properties of the format are
+    /// written.
+    pub fn dxf_index(&mut self, format: &Format) -> u32 {
+        let key = format!("{:?}", format);
+
+        if let Some(index) = self.dxf_keys.get(&key) {
+            return *index;
+        }
+
+        let index = self.dxf_formats.len() as u32;
+        self.dxf_formats.push(format.clone());
+        self.dxf_keys.insert(key, index);
+        index
+    }
+
+    /// The number of unique cell formats in the table.
+    pub fn xf_count(&self) -> u32 {
+        self.xf_formats.len() as u32
+    }
+
+    /// Set the workbook default font. This changes the font of the Normal
+    /// style and of formats without their own font name or size, and the
+    /// column width and row height metrics of worksheets.
+    pub fn set_default_font(&mut self, font: &DefaultFont) {
+        self.default_font = font.clone();
+    }
+
+    /// The workbook default font.
+    pub fn default_font(&self) -> &DefaultFont {
+        &self.default_font
+    }
+
+    // Get the numFmtId of a format: the index of a built-in number format, or
+    // the id of a custom format string, which is added to the table if it
+    // isn't already there.
+    fn num_format_id(&mut self, format: &Format) -> u16 {
+        if format.num_format.is_empty() {
+            return format.num_format_index;
+        }
+
+        if let Some(id) = self.num_format_keys.get(&format.num_format) {
+            return *id;
+        }
+
+        let id = FIRST_CUSTOM_NUM_FORMAT_ID + self.num_formats.len() as u16;
+        self.num_formats.push(format.num_format.clone());
+        self.num_format_keys.insert(format.num_format.clone(), id);
+        id
+    }
+
+    fn font_index(&mut self, font: &Font) -> u32 {
+        // Fonts contain an f64 so they are keyed on their debug string.
+        let key = format!("{:?}", font);
+
+        if let Some(index) = self.font_keys.get(&key) {
+            return *index;
+        }
+
+        let index = self.fonts.len() as u32;
+        self.fonts.push(font.clone());
+        self.font_keys.insert(key, index);
+        index
+    }
+
+    fn fill_index(&mut self, fill: &Fill) -> u32 {
+        if let Some(index) = self.fill_keys.get(fill) {
+            return *index;
+        }
+
+        let index = self.fills.len() as u32;
+        self.fills.push(fill.clone());
+        self.fill_keys.insert(fill.clone(), index);
+        index
+    }
+
+    fn border_index(&mut self, border: &Border) -> u32 {
+        if let Some(index) = self.border_keys.get(border) {
+            return *index;
+        }
+
+        let index = self.borders.len() as u32;
+        self.borders.push(border.clone());
+        self.border_keys.insert(border.clone(), index);
+        index
+    }
+
+    /// Write the `xl/styles.xml` part.
+    /// ```
+    /// # use std::fs::File;
+    /// # use excel_xmlwriter::XMLWriter;
+    /// # use excel_xmlwriter::styles::{Format, Styles};
+    /// #
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// # let xmlfile = File::create("test.xml")?;
+    /// let mut writer = XMLWriter::new(&xmlfile);
+    /// let mut styles = Styles::new();
+    ///
+    /// styles.xf_index(&Format::new().set_bold());
+    /// styles.assemble_xml_file(&mut writer);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns", SPREADSHEETML_NS)];
+        writer.xml_start_tag("styleSheet", &attributes);
+
+        self.write_num_formats(writer);
+        self.write_fonts(writer);
+        self.write_fills(writer);
+        self.write_borders(writer);
+        self.write_cell_style_xfs(writer);
+        self.write_cell_xfs(writer);
+        self.write_cell_styles(writer);
+
+        self.write_dxfs(writer);
+
+        let attributes = vec![
+            ("count", "0"),
+            ("defaultTableStyle", "TableStyleMedium9"),
+            ("defaultPivotStyle", "PivotStyleLight16"),
+        ];
+        writer.xml_empty_tag("tableStyles", &attributes);
+
+        writer.xml_end_tag("styleSheet");
+    }
+
+    // Write the <numFmts> element, if there are custom number formats.
+    fn write_num_formats(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.num_formats.is_empty() {
+            return;
+        }
+
+        let count = self.num_formats.len().to_string();
+        writer.xml_start_tag("numFmts", &vec![("count", count.as_str())]);
+
+        for (index, num_format) in self.num_formats.iter().enumerate() {
+            let id = (FIRST_CUSTOM_NUM_FORMAT_ID + index as u16).to_string();
+            let attributes = vec![
+                ("numFmtId", id.as_str()),
+                ("formatCode", num_format.as_str()),
+            ];
+            writer.xml_empty_tag("numFmt", &attributes);
+        }
+
+        writer.xml_end_tag("numFmts");
+    }
+
+    // Write the <fonts> element.
+    fn write_fonts(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.fonts.len().to_string();
+        writer.xml_start_tag("fonts", &vec![("count", count.as_str())]);
+
+        for font in &self.fonts {
+            writer.xml_start_tag("font", &vec![]);
+
+            if font.bold {
+                writer.xml_empty_tag("b", &vec![]);
+            }
+            if font.italic {
+                writer.xml_empty_tag("i", &vec![]);
+            }
+            if font.strikethrough {
+                writer.xml_empty_tag("strike", &vec![]);
+            }
+
+            write_underline(writer, font.underline);
+
+            match font.script {
+                FormatScript::None => {}
+                FormatScript::Superscript => {
+                    writer.xml_empty_tag("vertAlign", &vec![("val", "superscript")])
+                }
+                FormatScript::Subscript => {
+                    writer.xml_empty_tag("vertAlign", &vec![("val", "subscript")])
+                }
+            }
+
+            let default_font = &self.default_font;
+            let name = font.name.as_deref().unwrap_or(&default_font.name);
+            let size = font.size.unwrap_or(default_font.size).to_string();
+            let family = font.family.unwrap_or(default_font.family).to_string();
+
+            writer.xml_empty_tag("sz", &vec![("val", size.as_str())]);
+
+            // The default font color is the theme text color.
+            let (color_name, color_value) = match font.color {
+                Color::Automatic => Color::Theme(1).attribute(),
+                color => color.attribute(),
+            };
+            writer.xml_empty_tag("color", &vec![(color_name, color_value.as_str())]);
+
+            writer.xml_empty_tag("name", &vec![("val", name)]);
+            writer.xml_empty_tag("family", &vec![("val", family.as_str())]);
+
+            // Calibri is the minor font of the default theme.
+            if name == "Calibri" {
+                writer.xml_empty_tag("scheme", &vec![("val", "minor")]);
+            }
+
+            writer.xml_end_tag("font");
+        }
+
+        writer.xml_end_tag("fonts");
+    }
+
+    // Write the <fills> element.
+    fn write_fills(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.fills.len().to_string();
+        writer.xml_start_tag("fills", &vec![("count", count.as_str())]);
+
+        for fill in &self.fills {
+            writer.xml_start_tag("fill", &vec![]);
+
+            let attributes = vec![("patternType", fill.pattern.value())];
+
+            if fill.foreground_color == Color::Automatic
+                && fill.background_color == Color::Automatic
+            {
+                writer.xml_empty_tag("patternFill", &attributes);
+            } else {
+                writer.xml_start_tag("patternFill", &attributes);
+
+                if fill.foreground_color != Color::Automatic {
+                    let (name, value) = fill.foreground_color.attribute();
+                    writer.xml_empty_tag("fgColor", &vec![(name, value.as_str())]);
+                }
+
+                match fill.background_color {
+                    Color::Automatic => writer.xml_empty_tag("bgColor", &vec![("indexed", "64")]),
+                    color => {
+                        let (name, value) = color.attribute();
+                        writer.xml_empty_tag("bgColor", &vec![(name, value.as_str())]);
+                    }
+                }
+
+                writer.xml_end_tag("patternFill");
+            }
+
+            writer.xml_end_tag("fill");
+        }
+
+        writer.xml_end_tag("fills");
+    }
+
+    // Write the <borders> element.
+    fn write_borders(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.borders.len().to_string();
+        writer.xml_start_tag("borders", &vec![("count", count.as_str())]);
+
+        for border in &self.borders {
+            let mut attributes = vec![];
+            match border.diagonal_type {
+                FormatDiagonalBorder::None => {}
+                FormatDiagonalBorder::BorderUp => attributes.push(("diagonalUp", "1")),
+                FormatDiagonalBorder::BorderDown => attributes.push(("diagonalDown", "1")),
+                FormatDiagonalBorder::BorderUpDown => {
+                    attributes.push(("diagonalUp", "1"));
+                    attributes.push(("diagonalDown", "1"));
+                }
+            }
+
+            writer.xml_start_tag("border", &attributes);
+
+            write_border_side(writer, "left", border.left);
+            write_border_side(writer, "right", border.right);
+            write_border_side(writer, "top", border.top);
+            write_border_side(writer, "bottom", border.bottom);
+            write_border_side(writer, "diagonal", border.diagonal);
+
+            writer.xml_end_tag("border");
+        }
+
+        writer.xml_end_tag("borders");
+    }
+
+    // Write the <cellStyleXfs> element.
+    fn write_cell_style_xfs(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = if self.hyperlink_font.is_some() {
+            "2"
+        } else {
+            "1"
+        };
+        writer.xml_start_tag("cellStyleXfs", &vec![("count", count)]);
+
+        let attributes = vec![
+            ("numFmtId", "0"),
+            ("fontId", "0"),
+            ("fillId", "0"),
+            ("borderId", "0"),
+        ];
+        writer.xml_empty_tag("xf", &attributes);
+
+        // The style xf of the built-in Hyperlink cell style.
+        if let Some(font_index) = self.hyperlink_font {
+            let font_id = font_index.to_string();
+            let attributes = vec![
+                ("numFmtId", "0"),
+                ("fontId", font_id.as_str()),
+                ("fillId", "0"),
+                ("borderId", "0"),
+                ("applyNumberFormat", "0"),
+                ("applyFill", "0"),
+                ("applyBorder", "0"),
+                ("applyAlignment", "0"),
+                ("applyProtection", "0"),
+            ];
+            writer.xml_empty_tag("xf", &attributes);
+        }
+
+        writer.xml_end_tag("cellStyleXfs");
+    }
+
+    // Write the <cellXfs> element.
+    fn write_cell_xfs(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.xf_formats.len().to_string();
+        writer.xml_start_tag("cellXfs", &vec![("count", count.as_str())]);
+
+        for (format, indices) in &self.xf_formats {
+            let num_format_id = indices.num_format.to_string();
+            let font_id = indices.font.to_string();
+            let fill_id = indices.fill.to_string();
+            let border_id = indices.border.to_string();
+
+            // Hyperlink formats inherit their font from the Hyperlink style.
+            let xf_id = if format.hyperlink { "1" } else { "0" };
+
+            let mut attributes = vec![
+                ("numFmtId", num_format_id.as_str()),
+                ("fontId", font_id.as_str()),
+                ("fillId", fill_id.as_str()),
+                ("borderId", border_id.as_str()),
+                ("xfId", xf_id),
+            ];
+
+            if format.quote_prefix {
+                attributes.push(("quotePrefix", "1"));
+            }
+            if indices.num_format > 0 {
+                attributes.push(("applyNumberFormat", "1"));
+            }
+            if indices.font > 0 && !format.hyperlink {
+                attributes.push(("applyFont", "1"));
+            }
+            if indices.fill > 1 {
+                attributes.push(("applyFill", "1"));
+            }
+            if indices.border > 0 {
+                attributes.push(("applyBorder", "1"));
+            }
+            if format.has_alignment() {
+                attributes.push(("applyAlignment", "1"));
+            }
+            if format.has_protection() {
+                attributes.push(("applyProtection", "1"));
+            }
+
+            if !format.has_alignment() && !format.has_protection() {
+                writer.xml_empty_tag("xf", &attributes);
+                continue;
+            }
+
+            writer.xml_start_tag("xf", &attributes);
+
+            if format.has_alignment() {
+                write_alignment(writer, &format.alignment);
+            }
+
+            if format.has_protection() {
+                let mut attributes = vec![];
+                if !format.locked {
+                    attributes.push(("locked", "0"));
+                }
+                if format.hidden {
+                    attributes.push(("hidden", "1"));
+                }
+                writer.xml_empty_tag("protection", &attributes);
+            }
+
+            writer.xml_end_tag("xf");
+        }
+
+        writer.xml_end_tag("cellXfs");
+    }
+
+    // Write the <cellStyles> element.
+    fn write_cell_styles(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = if self.hyperlink_font.is_some() {
+            "2"
+        } else {
+            "1"
+        };
+        writer.xml_start_tag("cellStyles", &vec![("count", count)]);
+
+        // Excel sorts the cell styles by name.
+        if self.hyperlink_font.is_some() {
+            let attributes = vec![("name", "Hyperlink"), ("xfId", "1"), ("builtinId", "8")];
+            writer.xml_empty_tag("cellStyle", &attributes);
+        }
+
+        let attributes = vec![("name", "Normal"), ("xfId", "0"), ("builtinId", "0")];
+        writer.xml_empty_tag("cellStyle", &attributes);
+
+        writer.xml_end_tag("cellStyles");
+    }
+
+    // Write the <dxfs> element. Differential formats only contain the
+    // properties that differ from the cell's own format, so the font has no
+    // name or size and an automatic color isn't written.
+    fn write_dxfs(&self, writer: &mut XMLWriter<impl Write>) {
+        let count = self.dxf_formats.len().to_string();
+        if self.dxf_formats.is_empty() {
+            writer.xml_empty_tag("dxfs", &vec![("count", count.as_str())]);
+            return;
+        }
+
+        writer.xml_start_tag("dxfs", &vec![("count", count.as_str())]);
+
+        for format in &self.dxf_formats {
+            writer.xml_start_tag("dxf", &vec![]);
+
+            let font = &format.font;
+            if font.bold
+                || font.italic
+                || font.strikethrough
+                || font.underline != FormatUnderline::None
+                || font.color != Color::Automatic
+            {
+                writer.xml_start_tag("font", &vec![]);
+
+                if font.bold {
+                    writer.xml_empty_tag("b", &vec![]);
+                }
+                if font.italic {
+                    writer.xml_empty_tag("i", &vec![]);
+                }
+                if font.strikethrough {
+                    writer.xml_empty_tag("strike", &vec![]);
+                }
+                write_underline(writer, font.underline);
+
+                if font.color != Color::Automatic {
+                    let (name, value) = font.color.attribute();
+                    writer.xml_empty_tag("color", &vec![(name, value.as_str())]);
+                }
+
+                writer.xml_end_tag("font");
+            }
+
+            // A solid dxf fill uses the background color, without a pattern.
+            let fill = format.normalized_fill();
+            if fill.pattern == FormatPattern::Solid {
+                writer.xml_start_tag("fill", &vec![]);
+                writer.xml_start_tag("patternFill", &vec![]);
+                let (name, value) = fill.foreground_color.attribute();
+                writer.xml_empty_tag("bgColor", &vec![(name, value.as_str())]);
+                writer.xml_end_tag("patternFill");
+                writer.xml_end_tag("fill");
+            } else if fill.pattern != FormatPattern::None {
+                writer.xml_start_tag("fill", &vec![]);
+                writer.xml_start_tag("patternFill", &vec![("patternType", fill.pattern.value())]);
+                for (element, color) in [
+                    ("fgColor", fill.foreground_color),
+                    ("bgColor", fill.background_color),
+                ] {
+                    if color != Color::Automatic {
+                        let (name, value) = color.attribute();
+                        writer.xml_empty_tag(element, &vec![(name, value.as_str())]);
+                    }
+                }
+                writer.xml_end_tag("patternFill");
+                writer.xml_end_tag("fill");
+            }
+
+            // Diagonal borders aren't supported in conditional formats.
+            let border = &format.border;
+            if border.left.0 != FormatBorder::None
+                || border.right.0 != FormatBorder::None
+                || border.top.0 != FormatBorder::None
+                || border.bottom.0 != FormatBorder::None
+            {
+                writer.xml_start_tag("border", &vec![]);
+                write_border_side(writer, "left", border.left);
+                write_border_side(writer, "right", border.right);
+                write_border_side(writer, "top", border.top);
+                write_border_side(writer, "bottom", border.bottom);
+                writer.xml_end_tag("border");
+            }
+
+            writer.xml_end_tag("dxf");
+        }
+
+        writer.xml_end_tag("dxfs");
+    }
+}
+
+// Write the <u> element of a font.
+fn write_underline(writer: &mut XMLWriter<impl Write>, underline: FormatUnderline) {
+    match underline {
+        FormatUnderline::None => {}
+        FormatUnderline::Single => writer.xml_empty_tag("u", &vec![]),
+        FormatUnderline::Double => writer.xml_empty_tag("u", &vec![("val", "double")]),
+        FormatUnderline::SingleAccounting => {
+            writer.xml_empty_tag("u", &vec![("val", "singleAccounting")])
+        }
+        FormatUnderline::DoubleAccounting => {
+            writer.xml_empty_tag("u", &vec![("val", "doubleAccounting")])
+        }
+    }
+}
+
+// Write one of the <left>, <right>, etc., elements of a border.
+fn write_border_side(
+    writer: &mut XMLWriter<impl Write>,
+    side: &str,
+    (style, color): (FormatBorder, Color),
+) {
+    if style == FormatBorder::None {
+        writer.xml_empty_tag(side, &vec![]);
+        return;
+    }
+
+    writer.xml_start_tag(side, &vec![("style", style.value())]);
+
+    let (name, value) = color.attribute();
+    writer.xml_empty_tag("color", &vec![(name, value.as_str())]);
+
+    writer.xml_end_tag(side);
+}
+
+// Write the <alignment> element.
+fn write_alignment(writer: &mut XMLWriter<impl Write>, alignment: &Alignment) {
+    let horizontal = match alignment.horizontal {
+        FormatAlign::Left => "left",
+        FormatAlign::Center => "center",
+        FormatAlign::Right => "right",
+        FormatAlign::Fill => "fill",
+        FormatAlign::Justify => "justify",
+        FormatAlign::CenterAcross => "centerContinuous",
+        FormatAlign::Distributed => "distributed",
+        _ => "",
+    };
+
+    let vertical = match alignment.vertical {
+        FormatAlign::Top => "top",
+        FormatAlign::VerticalCenter => "center",
+        FormatAlign::VerticalJustify => "justify",
+        FormatAlign::VerticalDistributed => "distributed",
+        _ => "",
+    };
+
+    let rotation = alignment.rotation.to_string();
+    let indent = alignment.indent.to_string();
+
+    let mut attributes = vec![];
+    if !horizontal.is_empty() {
+        attributes.push(("horizontal", horizontal));
+    }
+    if !vertical.is_empty() {
+        attributes.push(("vertical", vertical));
+    }
+    if alignment.rotation != 0 {
+        attributes.push(("textRotation", rotation.as_str()));
+    }
+    if alignment.text_wrap {
+        attributes.push(("wrapText", "1"));
+    }
+    if alignment.indent != 0 {
+        attributes.push(("indent", indent.as_str()));
+    }
+    if alignment.shrink {
+        attributes.push(("shrinkToFit", "1"));
+    }
+
+    writer.xml_empty_tag("alignment", &attributes);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{
+        Color, DefaultFont, Format, FormatAlign, FormatBorder, FormatDiagonalBorder, FormatPattern,
+        FormatUnderline, Styles,
+    };
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_styles(styles: &Styles) -> String {
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        writer.set_check_attribute_order(true);
+
+        styles.assemble_xml_file(&mut writer);
+
+        read_xmlfile_data(&mut tempfile)
+    }
+
+    #[test]
+    fn test_xf_index_deduplication() {
+        let mut styles = Styles::new();
+
+        assert_eq!(styles.xf_index(&Format::new()), 0);
+        assert_eq!(styles.xf_index(&Format::new().set_bold()), 1);
+        assert_eq!(styles.xf_index(&Format::new().set_italic()), 2);
+        assert_eq!(styles.xf_index(&Format::new().set_bold()), 1);
+        assert_eq!(styles.xf_count(), 3);
+
+        // Formats that differ only in alignment share a font.
+        styles.xf_index(&Format::new().set_bold().set_align(FormatAlign::Center));
+        assert_eq!(styles.fonts.len(), 3);
+        assert_eq!(styles.xf_count(), 4);
+    }
+
+    #[test]
+    fn test_assemble_default_styles() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            r#"<fonts count="1"><font><sz val="11"/><color theme="1"/><name val="Calibri"/>"#,
+            r#"<family val="2"/><scheme val="minor"/></font></fonts>"#,
+            r#"<fills count="2"><fill><patternFill patternType="none"/></fill>"#,
+            r#"<fill><patternFill patternType="gray125"/></fill></fills>"#,
+            r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/>"#,
+            r#"</border></borders>"#,
+            r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/>"#,
+            r#"</cellStyleXfs>"#,
+            r#"<cellXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" "#,
+            r#"xfId="0"/></cellXfs>"#,
+            r#"<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/>"#,
+            r#"</cellStyles><dxfs count="0"/><tableStyles count="0" "#,
+            r#"defaultTableStyle="TableStyleMedium9" defaultPivotStyle="PivotStyleLight16"/>"#,
+            r#"</styleSheet>"#,
+        );
+
+        assert_eq!(assemble_styles(&Styles::new()), expected);
+    }
+
+    #[test]
+    fn test_assemble_font_styles() {
+        let mut styles = Styles::new();
+
+        styles.xf_index(
+            &Format::new()
+                .set_bold()
+                .set_italic()
+                .set_underline(FormatUnderline::Double)
+                .set_font_size(14.0)
+                .set_font_color(Color::Rgb(0xFF0000))
+                .set_font_name("Arial"),
+        );
+
+        let got = assemble_styles(&styles);
+
+        assert!(got.contains(concat!(
+            r#"<fonts count="2"><font><sz val="11"/><color theme="1"/><name val="Calibri"/>"#,
+            r#"<family val="2"/><scheme val="minor"/></font><font><b/><i/><u val="double"/>"#,
+            r#"<sz val="14"/><color rgb="FFFF0000"/><name val="Arial"/><family val="2"/>"#,
+            r#"</font></fonts>"#,
+        )));
+
+        assert!(got.contains(concat!(
+            r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" "#,
+            r#"applyFont="1"/>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_default_font() {
+        let mut styles = Styles::new();
+
+        styles.set_default_font(&DefaultFont::new("Arial", 10.0));
+        styles.xf_index(&Format::new().set_bold());
+        styles.xf_index(&Format::new().set_font_name("Calibri"));
+
+        let got = assemble_styles(&styles);
+
+        // Formats without a font name or size take the default font.
+        assert!(got.contains(concat!(
+            r#"<fonts count="3"><font><sz val="10"/><color theme="1"/><name val="Arial"/>"#,
+            r#"<family val="2"/></font><font><b/><sz val="10"/><color theme="1"/>"#,
+            r#"<name val="Arial"/><family val="2"/></font><font><sz val="10"/>"#,
+            r#"<color theme="1"/><name val="Calibri"/><family val="2"/>"#,
+            r#"<scheme val="minor"/></font></fonts>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_fill_styles() {
+        let mut styles = Styles::new();
+
+        styles.xf_index(&Format::new().set_background_color(Color::Rgb(0xFFFF00)));
+        styles.xf_index(
+            &Format::new()
+                .set_pattern(FormatPattern::LightGrid)
+                .set_foreground_color(Color::Rgb(0xFF0000))
+                .set_background_color(Color::Theme(4)),
+        );
+
+        let got = assemble_styles(&styles);
+
+        assert!(got.contains(concat!(
+            r#"<fill><patternFill patternType="solid"><fgColor rgb="FFFFFF00"/>"#,
+            r#"<bgColor indexed="64"/></patternFill></fill>"#,
+            r#"<fill><patternFill patternType="lightGrid"><fgColor rgb="FFFF0000"/>"#,
+            r#"<bgColor theme="4"/></patternFill></fill></fills>"#,
+        )));
+
+        assert!(got.contains(concat!(
+            r#"<xf numFmtId="0" fontId="0" fillId="2" borderId="0" xfId="0" "#,
+            r#"applyFill="1"/>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_border_styles() {
+        let mut styles = Styles::new();
+
+        styles.xf_index(
+            &Format::new()
+                .set_border(FormatBorder::Thin)
+                .set_border_bottom(FormatBorder::Double)
+                .set_border_bottom_color(Color::Rgb(0x0000FF))
+                .set_border_diagonal(FormatBorder::Dashed, FormatDiagonalBorder::BorderUpDown),
+        );
+
+        let got = assemble_styles(&styles);
+
+        assert!(got.contains(concat!(
+            r#"<border diagonalUp="1" diagonalDown="1"><left style="thin"><color auto="1"/>"#,
+            r#"</left><right style="thin"><color auto="1"/></right><top style="thin">"#,
+            r#"<color auto="1"/></top><bottom style="double"><color rgb="FF0000FF"/>"#,
+            r#"</bottom><diagonal style="dashed"><color auto="1"/></diagonal></border>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_alignment_and_protection_styles() {
+        let mut styles = Styles::new();
+
+        styles.xf_index(
+            &Format::new()
+                .set_num_format_index(2)
+                .set_align(FormatAlign::Center)
+                .set_align(FormatAlign::Top)
+                .set_rotation(-45)
+                .set_text_wrap()
+                .set_indent(1)
+                .set_unlocked()
+                .set_hidden(),
+        );
+
+        let got = assemble_styles(&styles);
+
+        assert!(got.contains(concat!(
+            r#"<xf numFmtId="2" fontId="0" fillId="0" borderId="0" xfId="0" "#,
+            r#"applyNumberFormat="1" applyAlignment="1" applyProtection="1">"#,
+            r#"<alignment horizontal="center" vertical="top" textRotation="135" "#,
+            r#"wrapText="1" indent="1"/><protection locked="0" hidden="1"/></xf>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_quote_prefix_style() {
+        let mut styles = Styles::new();
+
+        assert_eq!(styles.xf_index(&Format::new().set_quote_prefix()), 1);
+        assert_eq!(
+            styles.xf_index(&Format::new().set_bold().set_quote_prefix()),
+            2
+        );
+
+        let got = assemble_styles(&styles);
+
+        assert!(got.contains(concat!(
+            r#"<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0" quotePrefix="1"/>"#,
+            r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" quotePrefix="1" "#,
+            r#"applyFont="1"/></cellXfs>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_num_format_styles() {
+        let mut styles = Styles::new();
+
+        let currency = Format::new().set_num_format("#,##0.00;[Red](#,##0.00)");
+        let date = Format::new().set_num_format("yyyy-mm-dd");
+        let bold_currency = currency.clone().set_bold();
+
+        assert_eq!(styles.xf_index(&currency), 1);
+        assert_eq!(styles.xf_index(&date), 2);
+        assert_eq!(styles.xf_index(&bold_currency), 3);
+        assert_eq!(styles.xf_index(&Format::new().set_num_format_index(14)), 4);
+
+        let got = assemble_styles(&styles);
+
+        assert!(got.contains(concat!(
+            r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+            r##"<numFmts count="2"><numFmt numFmtId="164" formatCode="#,##0.00;[Red](#,##0.00)"/>"##,
+            r#"<numFmt numFmtId="165" formatCode="yyyy-mm-dd"/></numFmts><fonts "#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<xf numFmtId="164" fontId="0" fillId="0" borderId="0" xfId="0" "#,
+            r#"applyNumberFormat="1"/><xf numFmtId="165" fontId="0" fillId="0" borderId="0" "#,
+            r#"xfId="0" applyNumberFormat="1"/><xf numFmtId="164" fontId="1" fillId="0" "#,
+            r#"borderId="0" xfId="0" applyNumberFormat="1" applyFont="1"/>"#,
+            r#"<xf numFmtId="14" fontId="0" fillId="0" borderId="0" xfId="0" "#,
+            r#"applyNumberFormat="1"/>"#,
+        )));
+    }
+
+    #[test]
+    fn test_assemble_hyperlink_style() {
+        let expected = concat!(
+            r#"<fonts count="2"><font><sz val="11"/><color theme="1"/><name val="Calibri"/>"#,
+            r#"<family val="2"/><scheme val="minor"/></font><font><u/><sz val="11"/>"#,
+            r#"<color theme="10"/><name val="Calibri"/><family val="2"/><scheme val="minor"/>"#,
+            r#"</font></fonts>"#,
+        );
+        let expected_xfs = concat!(
+            r#"<cellStyleXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/>"#,
+            r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" applyNumberFormat="0" "#,
+            r#"applyFill="0" applyBorder="0" applyAlignment="0" applyProtection="0"/>"#,
+            r#"</cellStyleXfs><cellXfs count="3">"#,
+            r#"<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+            r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="1"/>"#,
+            r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="1" xfId="1" applyBorder="1"/>"#,
+            r#"</cellXfs><cellStyles count="2">"#,
+            r#"<cellStyle name="Hyperlink" xfId="1" builtinId="8"/>"#,
+            r#"<cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>"#,
+        );
+
+        let mut styles = Styles::new();
+        let hyperlink = Format::new().set_hyperlink();
+
+        assert_eq!(styles.xf_index(&hyperlink), 1);
+        assert_eq!(
+            styles.xf_index(&hyperlink.clone().set_border(FormatBorder::Thin)),
+            2
+        );
+
+        let got = assemble_styles(&styles);
+        assert!(got.contains(expected));
+        assert!(got.contains(expected_xfs));
+    }
+
+    #[test]
+    fn test_assemble_dxf_styles() {
+        let expected = concat!(
+            r#"<dxfs count="2"><dxf><font><b/><color rgb="FF9C0006"/></font>"#,
+            r#"<fill><patternFill><bgColor rgb="FFFFC7CE"/></patternFill></fill></dxf>"#,
+            r#"<dxf><font><i/><u/></font><border><left style="thin"><color auto="1"/></left>"#,
+            r#"<right style="thin"><color auto="1"/></right><top style="thin"><color auto="1"/>"#,
+            r#"</top><bottom style="thin"><color auto="1"/></bottom></border></dxf></dxfs>"#,
+        );
+
+        let mut styles = Styles::new();
+        let red = Format::new()
+            .set_bold()
+            .set_font_color(Color::Rgb(0x9C0006))
+            .set_background_color(Color::Rgb(0xFFC7CE));
+        let boxed = Format::new()
+            .set_italic()
+            .set_underline(FormatUnderline::Single)
+            .set_border(FormatBorder::Thin);
+
+        assert_eq!(styles.dxf_index(&red), 0);
+        assert_eq!(styles.dxf_index(&boxed), 1);
+        assert_eq!(styles.dxf_index(&red), 0);
+
+        // Differential formats don't add cell formats or fonts.
+        assert_eq!(styles.xf_count(), 1);
+        assert!(assemble_styles(&styles).contains(expected));
+    }
+}
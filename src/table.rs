@@ -0,0 +1,326 @@
+//! The worksheet tables and the writer for the `xl/tables/tableN.xml` parts.
+//!
+//! A [`Table`] is added to a range of cells with
+//! [`Worksheet::add_table()`](crate::worksheet::Worksheet::add_table), which
+//! writes the column headers to the first row of the range and adds the
+//! `<tableParts>` element and the relationship to the worksheet part. The
+//! tables of a worksheet are then written as parts of their own, with the
+//! [`XlsxPart`] trait, and need a content type from
+//! [`ContentTypes::add_table()`](crate::content_types::ContentTypes::add_table).
+//!
+//! Each table has a number that is unique in the workbook, starting at 1,
+//! which is used for its part name and its default name, `Table1`, `Table2`,
+//! etc. Table names share the namespace of the defined names, so they should
+//! also be reserved with
+//! [`Workbook::add_table_name()`](crate::workbook::Workbook::add_table_name).
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::table::Table;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut packager = Packager::new(vec![]);
+//!
+//! let table = Table::new(1)
+//!     .set_name("Sales")
+//!     .set_columns(&["Region", "Q1", "Q2"]);
+//! worksheet.add_table(0, 0, 4, 2, &table)?;
+//!
+//! for table in worksheet.tables() {
+//!     content_types.add_table(table.number());
+//!     packager.add_custom_part(table)?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{SPREADSHEETML_NS, TABLE_CONTENT_TYPE};
+use crate::part::XlsxPart;
+use crate::utility::rowcol_to_cell;
+use crate::XMLWriter;
+
+/// A worksheet table, also known as a list object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    number: u32,
+    part_name: String,
+    name: String,
+    columns: Vec<String>,
+    range: (u32, u16, u32, u16),
+    header_row: bool,
+    autofilter: bool,
+    style_name: String,
+    first_column: bool,
+    last_column: bool,
+    banded_rows: bool,
+    banded_columns: bool,
+}
+
+impl Table {
+    /// Create a table with its number in the workbook, starting at 1. The
+    /// table has a header row with autofilter dropdowns, banded rows and
+    /// Excel's default `TableStyleMedium9` style.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    pub fn new(number: u32) -> Table {
+        assert!(number > 0, "table numbers start at 1");
+
+        Table {
+            number,
+            part_name: format!("xl/tables/table{number}.xml"),
+            name: format!("Table{number}"),
+            columns: vec![],
+            range: (0, 0, 0, 0),
+            header_row: true,
+            autofilter: true,
+            style_name: "TableStyleMedium9".to_string(),
+            first_column: false,
+            last_column: false,
+            banded_rows: true,
+            banded_columns: false,
+        }
+    }
+
+    /// Set the name of the table. The default is `TableN`, from the table
+    /// number.
+    pub fn set_name(mut self, name: &str) -> Table {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set the column headers, from the first column of the table. Columns
+    /// without a header get Excel's default `Column1`, `Column2`, etc.
+    pub fn set_columns(mut self, columns: &[&str]) -> Table {
+        self.columns = columns.iter().map(|column| column.to_string()).collect();
+        self
+    }
+
+    /// Set whether the table has a header row. The default is `true`.
+    pub fn set_header_row(mut self, header_row: bool) -> Table {
+        self.header_row = header_row;
+        self
+    }
+
+    /// Set whether the header row has autofilter dropdowns. The default is
+    /// `true`.
+    pub fn set_autofilter(mut self, autofilter: bool) -> Table {
+        self.autofilter = autofilter;
+        self
+    }
+
+    /// Set the table style, like `TableStyleLight11`.
+    pub fn set_style_name(mut self, style_name: &str) -> Table {
+        self.style_name = style_name.to_string();
+        self
+    }
+
+    /// Set whether the first column is highlighted. The default is `false`.
+    pub fn set_first_column(mut self, first_column: bool) -> Table {
+        self.first_column = first_column;
+        self
+    }
+
+    /// Set whether the last column is highlighted. The default is `false`.
+    pub fn set_last_column(mut self, last_column: bool) -> Table {
+        self.last_column = last_column;
+        self
+    }
+
+    /// Set whether the rows are banded. The default is `true`.
+    pub fn set_banded_rows(mut self, banded_rows: bool) -> Table {
+        self.banded_rows = banded_rows;
+        self
+    }
+
+    /// Set whether the columns are banded. The default is `false`.
+    pub fn set_banded_columns(mut self, banded_columns: bool) -> Table {
+        self.banded_columns = banded_columns;
+        self
+    }
+
+    /// The number of the table in the workbook.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// The name of the table.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check if the table has a header row.
+    pub fn has_header_row(&self) -> bool {
+        self.header_row
+    }
+
+    // Set the range of the table, and fill in the default names of the
+    // columns without a header.
+    pub(crate) fn set_range(&mut self, range: (u32, u16, u32, u16)) {
+        self.range = range;
+
+        let column_count = (range.3 - range.1) as usize + 1;
+        for index in self.columns.len()..column_count {
+            self.columns.push(format!("Column{}", index + 1));
+        }
+    }
+
+    // The range of the table as (first_row, first_col, last_row, last_col).
+    pub(crate) fn range(&self) -> (u32, u16, u32, u16) {
+        self.range
+    }
+
+    // The column headers, after the range has been set.
+    pub(crate) fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    // The range of the table as a string, like `A1:C10`.
+    fn range_string(&self) -> String {
+        let (first_row, first_col, last_row, last_col) = self.range;
+        format!(
+            "{}:{}",
+            rowcol_to_cell(first_row, first_col),
+            rowcol_to_cell(last_row, last_col)
+        )
+    }
+
+    /// Write the `xl/tables/tableN.xml` part of a table from
+    /// [`Worksheet::tables()`](crate::worksheet::Worksheet::tables).
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let id = self.number.to_string();
+        let range = self.range_string();
+
+        let mut attributes = vec![
+            ("xmlns", SPREADSHEETML_NS),
+            ("id", id.as_str()),
+            ("name", self.name.as_str()),
+            ("displayName", self.name.as_str()),
+            ("ref", range.as_str()),
+        ];
+        if !self.header_row {
+            attributes.push(("headerRowCount", "0"));
+        }
+        attributes.push(("totalsRowShown", "0"));
+        writer.xml_start_tag("table", &attributes);
+
+        if self.header_row && self.autofilter {
+            writer.xml_empty_tag("autoFilter", &vec![("ref", range.as_str())]);
+        }
+
+        let count = self.columns.len().to_string();
+        writer.xml_start_tag("tableColumns", &vec![("count", count.as_str())]);
+        for (index, column) in self.columns.iter().enumerate() {
+            let id = (index + 1).to_string();
+            let attributes = vec![("id", id.as_str()), ("name", column.as_str())];
+            writer.xml_empty_tag("tableColumn", &attributes);
+        }
+        writer.xml_end_tag("tableColumns");
+
+        let flag = |on: bool| if on { "1" } else { "0" };
+        let attributes = vec![
+            ("name", self.style_name.as_str()),
+            ("showFirstColumn", flag(self.first_column)),
+            ("showLastColumn", flag(self.last_column)),
+            ("showRowStripes", flag(self.banded_rows)),
+            ("showColumnStripes", flag(self.banded_columns)),
+        ];
+        writer.xml_empty_tag("tableStyleInfo", &attributes);
+
+        writer.xml_end_tag("table");
+    }
+}
+
+impl XlsxPart for Table {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        TABLE_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Table;
+    use crate::part::XlsxPart;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_table(table: &Table) -> String {
+        let mut writer = XMLWriter::new(vec![]);
+        table.assemble_xml_file(&mut writer);
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_assemble_table() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<table xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"id="2" name="Table2" displayName="Table2" ref="B3:D7" totalsRowShown="0">"#,
+            r#"<autoFilter ref="B3:D7"/><tableColumns count="3">"#,
+            r#"<tableColumn id="1" name="Name &amp; Id"/><tableColumn id="2" name="Column2"/>"#,
+            r#"<tableColumn id="3" name="Column3"/></tableColumns>"#,
+            r#"<tableStyleInfo name="TableStyleMedium9" showFirstColumn="0" showLastColumn="0" "#,
+            r#"showRowStripes="1" showColumnStripes="0"/></table>"#,
+        );
+
+        let mut table = Table::new(2).set_columns(&["Name & Id"]);
+        table.set_range((2, 1, 6, 3));
+
+        assert_eq!(assemble_table(&table), expected);
+        assert_eq!(table.part_name(), "xl/tables/table2.xml");
+    }
+
+    #[test]
+    fn test_assemble_table_options() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<table xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"id="1" name="Sales" displayName="Sales" ref="A1:B4" headerRowCount="0" totalsRowShown="0">"#,
+            r#"<tableColumns count="2"><tableColumn id="1" name="Region"/>"#,
+            r#"<tableColumn id="2" name="Total"/></tableColumns>"#,
+            r#"<tableStyleInfo name="TableStyleLight11" showFirstColumn="1" showLastColumn="1" "#,
+            r#"showRowStripes="0" showColumnStripes="1"/></table>"#,
+        );
+
+        let mut table = Table::new(1)
+            .set_name("Sales")
+            .set_columns(&["Region", "Total"])
+            .set_header_row(false)
+            .set_style_name("TableStyleLight11")
+            .set_first_column(true)
+            .set_last_column(true)
+            .set_banded_rows(false)
+            .set_banded_columns(true);
+        table.set_range((0, 0, 3, 1));
+
+        assert_eq!(assemble_table(&table), expected);
+    }
+}
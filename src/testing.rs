@@ -0,0 +1,492 @@
+//! Helpers for reading back the XML written by an [`XMLWriter`] and
+//! comparing it with the expected XML, as used by the tests of the crate.
+//! Crates that build on the writer, such as an xlsx writer, can use them
+//! in their own tests.
+//!
+//! ```
+//! use excel_xmlwriter::testing::{assert_xml_eq, write_to_string};
+//!
+//! let got = write_to_string(|writer| {
+//!     writer.xml_start_tag("row", &vec![("r", "1"), ("spans", "1:1")]);
+//!     writer.xml_end_tag("row");
+//! });
+//!
+//! // The attribute order and the self-closing tag don't matter.
+//! assert_xml_eq(&got, r#"<row spans="1:1" r="1"/>"#);
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::XMLWriter;
+
+/// Read back the XML written to a file, or to another seekable reader such
+/// as a [`Cursor`](std::io::Cursor), from the start.
+///
+/// # Panics
+///
+/// If the reader can't be read, or the XML isn't UTF-8.
+pub fn read_xmlfile_data<R: Read + Seek>(file: &mut R) -> String {
+    let mut got = String::new();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_to_string(&mut got).unwrap();
+    got
+}
+
+/// Write XML to memory with a closure and return it as a string.
+///
+/// # Panics
+///
+/// If the XML isn't UTF-8.
+pub fn write_to_string(write: impl FnOnce(&mut XMLWriter<Vec<u8>>)) -> String {
+    let mut writer = XMLWriter::new(vec![]);
+    write(&mut writer);
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+/// Split an XML document into a list of elements and text sections, for
+/// comparing documents. Attributes are sorted so that attribute order
+/// doesn't cause differences, and an empty start/end tag pair is converted
+/// to a self-closing tag. Whitespace between elements is ignored.
+pub fn canonicalize(xml: &str) -> Vec<String> {
+    let mut elements: Vec<String> = vec![];
+    let mut remaining = xml;
+
+    while !remaining.is_empty() {
+        let (token, rest) = match remaining.find('<') {
+            Some(0) => match remaining.find('>') {
+                Some(end) => remaining.split_at(end + 1),
+                None => (remaining, ""),
+            },
+            Some(start) => remaining.split_at(start),
+            None => (remaining, ""),
+        };
+        remaining = rest;
+
+        if !token.starts_with('<') {
+            if !token.trim().is_empty() {
+                elements.push(token.to_string());
+            }
+            continue;
+        }
+
+        if token.starts_with("</") {
+            let name = &token[2..token.len() - 1];
+            let start_tag = elements.last().and_then(|last| {
+                last.strip_prefix('<')
+                    .filter(|tag| tag.ends_with('>') && !tag.ends_with("/>"))
+                    .filter(|tag| tag.split([' ', '>']).next() == Some(name))
+                    .map(|tag| tag.to_string())
+            });
+
+            match start_tag {
+                Some(tag) => {
+                    elements.pop();
+                    elements.push(format!("<{}/>", &tag[..tag.len() - 1]));
+                }
+                None => elements.push(token.to_string()),
+            }
+            continue;
+        }
+
+        if token.starts_with("<?") || token.starts_with("<!") {
+            elements.push(token.to_string());
+            continue;
+        }
+
+        elements.push(canonicalize_tag(token));
+    }
+
+    elements
+}
+
+/// The first structural difference between two XML documents, from
+/// [`xml_diff()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlDifference {
+    /// The path of the element that contains the difference, in the format
+    /// of [`XMLWriter::context()`], like `worksheet/sheetData/row[2]`.
+    pub path: String,
+
+    /// The element, attribute or text in the first document, or
+    /// `the end of the document`.
+    pub got: String,
+
+    /// The element, attribute or text in the second document.
+    pub expected: String,
+}
+
+impl fmt::Display for XmlDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "in {}, got {} but expected {}",
+            self.path, self.got, self.expected
+        )
+    }
+}
+
+/// Compare two XML documents and return the first structural difference,
+/// or `None` if they are the same. The order of the attributes of an
+/// element, the quotes around attribute values, empty start/end tag pairs
+/// and self-closing tags, and whitespace between elements don't count as
+/// differences, so a generated part can be compared with the same part
+/// saved by Excel.
+///
+/// ```
+/// use excel_xmlwriter::testing::xml_diff;
+///
+/// let got = r#"<sheetData><row r="1" spans="1:2"/><row r="2"/></sheetData>"#;
+/// let expected = r#"<sheetData><row spans="1:2" r="1"/><row r="3"/></sheetData>"#;
+///
+/// let difference = xml_diff(got, expected).unwrap();
+/// assert_eq!(
+///     difference.to_string(),
+///     r#"in sheetData/row[2], got r="2" but expected r="3""#
+/// );
+/// ```
+pub fn xml_diff(got: &str, expected: &str) -> Option<XmlDifference> {
+    let got = parse(got);
+    let expected = parse(expected);
+    let mut path = XmlPath::default();
+
+    for index in 0..got.len().max(expected.len()) {
+        let (got, expected) = (got.get(index), expected.get(index));
+
+        match (got, expected) {
+            (Some(got), Some(expected)) if got == expected => {
+                path.update(got);
+                continue;
+            }
+            (Some(Node::Start(name, got)), Some(Node::Start(expected_name, expected)))
+                if name == expected_name =>
+            {
+                path.update(&Node::Start(name.clone(), vec![]));
+                let (got, expected) = attribute_difference(got, expected);
+
+                return Some(XmlDifference {
+                    path: path.to_string(),
+                    got,
+                    expected,
+                });
+            }
+            _ => {
+                return Some(XmlDifference {
+                    path: path.to_string(),
+                    got: describe(got),
+                    expected: describe(expected),
+                })
+            }
+        }
+    }
+
+    None
+}
+
+/// Assert that two XML documents are the same, apart from the differences
+/// that [`xml_diff()`] ignores.
+///
+/// # Panics
+///
+/// If the documents are different, with the first difference.
+#[track_caller]
+pub fn assert_xml_eq(got: &str, expected: &str) {
+    if let Some(difference) = xml_diff(got, expected) {
+        panic!("the XML differs {difference}");
+    }
+}
+
+// A start tag with its sorted attributes, an end tag, text, or a
+// declaration, comment or other markup that is compared as it is. Empty
+// tags are parsed as a start tag and an end tag.
+#[derive(Debug, PartialEq)]
+enum Node {
+    Start(String, Vec<(String, String)>),
+    End(String),
+    Text(String),
+    Other(String),
+}
+
+// Parse the canonical elements of a document into nodes.
+fn parse(xml: &str) -> Vec<Node> {
+    let mut nodes = vec![];
+
+    for element in canonicalize(xml) {
+        if let Some(name) = element.strip_prefix("</") {
+            nodes.push(Node::End(name.trim_end_matches('>').to_string()));
+        } else if element.starts_with("<?") || element.starts_with("<!") {
+            nodes.push(Node::Other(element));
+        } else if let Some(tag) = element.strip_prefix('<') {
+            let is_empty = tag.ends_with("/>");
+            let tag = tag.trim_end_matches('>').trim_end_matches('/');
+            let (name, attributes) = tag.split_once(' ').unwrap_or((tag, ""));
+
+            nodes.push(Node::Start(name.to_string(), parse_attributes(attributes)));
+            if is_empty {
+                nodes.push(Node::End(name.to_string()));
+            }
+        } else {
+            nodes.push(Node::Text(element));
+        }
+    }
+
+    nodes
+}
+
+// Parse the `name="value"` pairs of a canonical tag, without the quotes.
+fn parse_attributes(mut attributes: &str) -> Vec<(String, String)> {
+    let mut pairs = vec![];
+
+    while let Some((name, rest)) = attributes.split_once('=') {
+        let quote = rest.chars().next().unwrap_or('"');
+        let value = rest.get(1..).unwrap_or_default();
+        let (value, rest) = value.split_once(quote).unwrap_or((value, ""));
+
+        pairs.push((name.trim().to_string(), value.to_string()));
+        attributes = rest;
+    }
+
+    pairs
+}
+
+// The first difference between the attributes of two start tags with the
+// same name.
+fn attribute_difference(
+    got: &[(String, String)],
+    expected: &[(String, String)],
+) -> (String, String) {
+    let describe = |attributes: &[(String, String)], name: &str| {
+        attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map_or(format!("no {name} attribute"), |(key, value)| {
+                format!(r#"{key}="{value}""#)
+            })
+    };
+
+    let mut names: Vec<&str> = got
+        .iter()
+        .chain(expected)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    names.sort_unstable();
+
+    names
+        .into_iter()
+        .map(|name| (describe(got, name), describe(expected, name)))
+        .find(|(got, expected)| got != expected)
+        .expect("the attributes are different")
+}
+
+// Describe a node, or the end of a document, in a difference.
+fn describe(node: Option<&Node>) -> String {
+    match node {
+        Some(Node::Start(name, _)) => format!("<{name}>"),
+        Some(Node::End(name)) => format!("</{name}>"),
+        Some(Node::Text(text)) => format!("{text:?}"),
+        Some(Node::Other(markup)) => markup.clone(),
+        None => "the end of the document".to_string(),
+    }
+}
+
+// The path of the open elements, with the position of each element among
+// its preceding siblings with the same name, as in XMLWriter::context().
+#[derive(Default)]
+struct XmlPath {
+    elements: Vec<(String, usize)>,
+    last_child: Option<(String, usize)>,
+    parent_last_children: Vec<Option<(String, usize)>>,
+}
+
+impl XmlPath {
+    // Update the path for a node that is the same in both documents.
+    fn update(&mut self, node: &Node) {
+        match node {
+            Node::Start(name, _) => {
+                let position = match &self.last_child {
+                    Some((last, position)) if last == name => position + 1,
+                    _ => 1,
+                };
+
+                self.elements.push((name.clone(), position));
+                self.parent_last_children
+                    .push(Some((name.clone(), position)));
+                self.last_child = None;
+            }
+            Node::End(_) => {
+                self.elements.pop();
+                self.last_child = self.parent_last_children.pop().flatten();
+            }
+            Node::Text(_) | Node::Other(_) => {}
+        }
+    }
+}
+
+impl fmt::Display for XmlPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.elements.is_empty() {
+            return write!(f, "the top level");
+        }
+
+        for (index, (name, position)) in self.elements.iter().enumerate() {
+            if index > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{name}")?;
+            if *position > 1 {
+                write!(f, "[{position}]")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Sort the attributes of a start or empty tag.
+fn canonicalize_tag(tag: &str) -> String {
+    let is_empty = tag.ends_with("/>");
+    let inner = tag[1..tag.len() - if is_empty { 2 } else { 1 }].trim();
+
+    let (name, attributes) = inner.split_once(char::is_whitespace).unwrap_or((inner, ""));
+
+    let mut pairs = vec![];
+    let mut attributes = attributes.trim();
+    while let Some(equals) = attributes.find('=') {
+        let key = attributes[..equals].trim();
+        let value_part = attributes[equals + 1..].trim_start();
+        let quote = value_part.chars().next().unwrap_or('"');
+        let end = value_part[1..]
+            .find(quote)
+            .map_or(value_part.len(), |i| i + 2);
+
+        pairs.push(format!("{}={}", key, &value_part[..end]));
+        attributes = value_part[end..].trim_start();
+    }
+    pairs.sort();
+
+    let mut canonical = format!("<{name}");
+    for pair in pairs {
+        canonical.push(' ');
+        canonical.push_str(&pair);
+    }
+    canonical.push_str(if is_empty { "/>" } else { ">" });
+
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{assert_xml_eq, canonicalize, read_xmlfile_data, write_to_string, xml_diff};
+    use std::io::{Cursor, Write};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_canonicalize() {
+        let xml = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<sheetData><row spans="1:1" r="1"><c t="s" r="A1"><v>0</v></c></row>"#,
+            r#"<row r="2"></row></sheetData>"#,
+        );
+
+        let expected = vec![
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "<sheetData>",
+            r#"<row r="1" spans="1:1">"#,
+            r#"<c r="A1" t="s">"#,
+            "<v>",
+            "0",
+            "</v>",
+            "</c>",
+            "</row>",
+            r#"<row r="2"/>"#,
+            "</sheetData>",
+        ];
+
+        assert_eq!(canonicalize(xml), expected);
+    }
+
+    #[test]
+    fn test_canonicalize_quoted_attributes() {
+        let xml = r#"<a z='1' y="x y" x = "2"/>"#;
+
+        assert_eq!(canonicalize(xml), vec![r#"<a x="2" y="x y" z='1'/>"#]);
+    }
+
+    #[test]
+    fn test_read_xmlfile_data() {
+        let mut cursor = Cursor::new(vec![]);
+        cursor.write_all(b"<a/>").unwrap();
+
+        assert_eq!(read_xmlfile_data(&mut cursor), "<a/>");
+    }
+
+    #[test]
+    fn test_assert_xml_eq() {
+        let got = write_to_string(|writer| {
+            writer.xml_start_tag("c", &vec![("r", "A1"), ("t", "s")]);
+            writer.xml_data_element("v", "0", &vec![]);
+            writer.xml_end_tag("c");
+        });
+
+        assert_eq!(got, r#"<c r="A1" t="s"><v>0</v></c>"#);
+        assert_xml_eq(&got, "<c t=\"s\" r=\"A1\">\n  <v>0</v>\n</c>");
+    }
+
+    #[test]
+    #[should_panic(expected = r#"the XML differs in v, got "0" but expected "1""#)]
+    fn test_assert_xml_eq_differs() {
+        assert_xml_eq("<v>0</v>", "<v>1</v>");
+    }
+
+    #[test]
+    fn test_xml_diff() {
+        let xml = r#"<a><b x="1" y='2'/><c></c></a>"#;
+
+        assert_eq!(xml_diff(xml, r#"<a><b y="2" x="1"></b><c/></a>"#), None);
+
+        let cases = [
+            (
+                "<a><b/></a>",
+                r#"<a><b x="1"/></a>"#,
+                "a/b",
+                "no x attribute",
+                r#"x="1""#,
+            ),
+            (
+                r#"<a><b/><b y="2"/></a>"#,
+                r#"<a><b/><b y="3"/></a>"#,
+                "a/b[2]",
+                r#"y="2""#,
+                r#"y="3""#,
+            ),
+            ("<a><c/></a>", "<a><b/></a>", "a", "<c>", "<b>"),
+            ("<a></a>", "<a><b/></a>", "a", "</a>", "<b>"),
+            (
+                "<a><b/></a>",
+                "<a><b/></a><d/>",
+                "the top level",
+                "the end of the document",
+                "<d>",
+            ),
+            (
+                "<a><b>y</b></a>",
+                "<a><b>x</b></a>",
+                "a/b",
+                r#""y""#,
+                r#""x""#,
+            ),
+        ];
+
+        for (got, expected, path, got_node, expected_node) in cases {
+            let difference = xml_diff(got, expected).unwrap();
+            assert_eq!(difference.path, path);
+            assert_eq!(difference.got, got_node);
+            assert_eq!(difference.expected, expected_node);
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! The writer for the default `xl/theme/theme1.xml` part.
+//!
+//! Excel requires a theme part, and the default styles refer to its colors
+//! and fonts. This writes the standard "Office" theme from tables of its
+//! colors, fonts and effects rather than from a single large string.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::theme::Theme;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//!
+//! Theme::new().assemble_xml_file(&mut writer);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::DRAWINGML_NS;
+use crate::XMLWriter;
+
+// The theme colors, in the order of the theme color indices.
+const COLOR_SCHEME: [(&str, ThemeColor); 12] = [
+    ("a:dk1", ThemeColor::System("windowText", "000000")),
+    ("a:lt1", ThemeColor::System("window", "FFFFFF")),
+    ("a:dk2", ThemeColor::Rgb("1F497D")),
+    ("a:lt2", ThemeColor::Rgb("EEECE1")),
+    ("a:accent1", ThemeColor::Rgb("4F81BD")),
+    ("a:accent2", ThemeColor::Rgb("C0504D")),
+    ("a:accent3", ThemeColor::Rgb("9BBB59")),
+    ("a:accent4", ThemeColor::Rgb("8064A2")),
+    ("a:accent5", ThemeColor::Rgb("4BACC6")),
+    ("a:accent6", ThemeColor::Rgb("F79646")),
+    ("a:hlink", ThemeColor::Rgb("0000FF")),
+    ("a:folHlink", ThemeColor::Rgb("800080")),
+];
+
+// The script specific fonts of the major (headings) and minor (body) fonts.
+// Where the two differ the minor font is given second.
+const SCRIPT_FONTS: [(&str, &str, &str); 29] = [
+    ("Jpan", "ＭＳ Ｐゴシック", "ＭＳ Ｐゴシック"),
+    ("Hang", "맑은 고딕", "맑은 고딕"),
+    ("Hans", "宋体", "宋体"),
+    ("Hant", "新細明體", "新細明體"),
+    ("Arab", "Times New Roman", "Arial"),
+    ("Hebr", "Times New Roman", "Arial"),
+    ("Thai", "Tahoma", "Tahoma"),
+    ("Ethi", "Nyala", "Nyala"),
+    ("Beng", "Vrinda", "Vrinda"),
+    ("Gujr", "Shruti", "Shruti"),
+    ("Khmr", "MoolBoran", "DaunPenh"),
+    ("Knda", "Tunga", "Tunga"),
+    ("Guru", "Raavi", "Raavi"),
+    ("Cans", "Euphemia", "Euphemia"),
+    ("Cher", "Plantagenet Cherokee", "Plantagenet Cherokee"),
+    ("Yiii", "Microsoft Yi Baiti", "Microsoft Yi Baiti"),
+    ("Tibt", "Microsoft Himalaya", "Microsoft Himalaya"),
+    ("Thaa", "MV Boli", "MV Boli"),
+    ("Deva", "Mangal", "Mangal"),
+    ("Telu", "Gautami", "Gautami"),
+    ("Taml", "Latha", "Latha"),
+    ("Syrc", "Estrangelo Edessa", "Estrangelo Edessa"),
+    ("Orya", "Kalinga", "Kalinga"),
+    ("Mlym", "Kartika", "Kartika"),
+    ("Laoo", "DokChampa", "DokChampa"),
+    ("Sinh", "Iskoola Pota", "Iskoola Pota"),
+    ("Mong", "Mongolian Baiti", "Mongolian Baiti"),
+    ("Viet", "Times New Roman", "Arial"),
+    ("Uigh", "Microsoft Uighur", "Microsoft Uighur"),
+];
+
+// A color modifier, such as a tint, applied to the placeholder color.
+type ColorMods = &'static [(&'static str, &'static str)];
+
+// The stops of a gradient fill, as positions and color modifiers.
+type GradientStops = &'static [(&'static str, ColorMods)];
+
+// The gradients of the fill style list.
+const FILL_GRADIENTS: [(GradientStops, GradientShade); 2] = [
+    (
+        &[
+            ("0", &[("a:tint", "50000"), ("a:satMod", "300000")]),
+            ("35000", &[("a:tint", "37000"), ("a:satMod", "300000")]),
+            ("100000", &[("a:tint", "15000"), ("a:satMod", "350000")]),
+        ],
+        GradientShade::Linear("1"),
+    ),
+    (
+        &[
+            ("0", &[("a:shade", "51000"), ("a:satMod", "130000")]),
+            ("80000", &[("a:shade", "93000"), ("a:satMod", "130000")]),
+            ("100000", &[("a:shade", "94000"), ("a:satMod", "135000")]),
+        ],
+        GradientShade::Linear("0"),
+    ),
+];
+
+// The gradients of the background fill style list.
+const BACKGROUND_GRADIENTS: [(GradientStops, GradientShade); 2] = [
+    (
+        &[
+            ("0", &[("a:tint", "40000"), ("a:satMod", "350000")]),
+            (
+                "40000",
+                &[
+                    ("a:tint", "45000"),
+                    ("a:shade", "99000"),
+                    ("a:satMod", "350000"),
+                ],
+            ),
+            ("100000", &[("a:shade", "20000"), ("a:satMod", "255000")]),
+        ],
+        GradientShade::Circle(["50000", "-80000", "50000", "180000"]),
+    ),
+    (
+        &[
+            ("0", &[("a:tint", "80000"), ("a:satMod", "300000")]),
+            ("100000", &[("a:shade", "30000"), ("a:satMod", "200000")]),
+        ],
+        GradientShade::Circle(["50000", "50000", "50000", "50000"]),
+    ),
+];
+
+// The line widths, in EMUs, of the line style list, with the color modifiers
+// of each line.
+const LINE_STYLES: [(&str, ColorMods); 3] = [
+    ("9525", &[("a:shade", "95000"), ("a:satMod", "105000")]),
+    ("25400", &[]),
+    ("38100", &[]),
+];
+
+// The outer shadow distance and alpha of the effect style list.
+const EFFECT_SHADOWS: [(&str, &str); 3] =
+    [("20000", "38000"), ("23000", "35000"), ("23000", "35000")];
+
+// A color in the theme color scheme.
+#[derive(Clone, Copy, Debug)]
+enum ThemeColor {
+    // A system color with its last known RGB value.
+    System(&'static str, &'static str),
+    Rgb(&'static str),
+}
+
+// The shading of a gradient fill.
+#[derive(Clone, Copy, Debug)]
+enum GradientShade {
+    // A linear top to bottom gradient with its `scaled` attribute.
+    Linear(&'static str),
+    // A circular path gradient with the fill-to rectangle as left, top,
+    // right and bottom offsets.
+    Circle([&'static str; 4]),
+}
+
+/// The writer for the default `xl/theme/theme1.xml` part.
+#[derive(Debug, Default)]
+pub struct Theme {}
+
+impl Theme {
+    /// Create a new default theme part.
+    pub fn new() -> Theme {
+        Theme::default()
+    }
+
+    /// Write the `xl/theme/theme1.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns:a", DRAWINGML_NS), ("name", "Office Theme")];
+        writer.xml_start_tag("a:theme", &attributes);
+        writer.xml_start_tag("a:themeElements", &vec![]);
+
+        self.write_color_scheme(writer);
+        self.write_font_scheme(writer);
+        self.write_format_scheme(writer);
+
+        writer.xml_end_tag("a:themeElements");
+        writer.xml_empty_tag("a:objectDefaults", &vec![]);
+        writer.xml_empty_tag("a:extraClrSchemeLst", &vec![]);
+        writer.xml_end_tag("a:theme");
+    }
+
+    // Write the <a:clrScheme> element.
+    fn write_color_scheme(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_start_tag("a:clrScheme", &vec![("name", "Office")]);
+
+        for (tag, color) in COLOR_SCHEME {
+            writer.xml_start_tag(tag, &vec![]);
+            match color {
+                ThemeColor::System(name, last_color) => {
+                    let attributes = vec![("val", name), ("lastClr", last_color)];
+                    writer.xml_empty_tag("a:sysClr", &attributes);
+                }
+                ThemeColor::Rgb(rgb) => writer.xml_empty_tag("a:srgbClr", &vec![("val", rgb)]),
+            }
+            writer.xml_end_tag(tag);
+        }
+
+        writer.xml_end_tag("a:clrScheme");
+    }
+
+    // Write the <a:fontScheme> element.
+    fn write_font_scheme(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_start_tag("a:fontScheme", &vec![("name", "Office")]);
+
+        for (tag, latin) in [("a:majorFont", "Cambria"), ("a:minorFont", "Calibri")] {
+            writer.xml_start_tag(tag, &vec![]);
+
+            writer.xml_empty_tag("a:latin", &vec![("typeface", latin)]);
+            writer.xml_empty_tag("a:ea", &vec![("typeface", "")]);
+            writer.xml_empty_tag("a:cs", &vec![("typeface", "")]);
+
+            for (script, major, minor) in SCRIPT_FONTS {
+                let typeface = if tag == "a:majorFont" { major } else { minor };
+                let attributes = vec![("script", script), ("typeface", typeface)];
+                writer.xml_empty_tag("a:font", &attributes);
+            }
+
+            writer.xml_end_tag(tag);
+        }
+
+        writer.xml_end_tag("a:fontScheme");
+    }
+
+    // Write the <a:fmtScheme> element.
+    fn write_format_scheme(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_start_tag("a:fmtScheme", &vec![("name", "Office")]);
+
+        // Fill styles.
+        writer.xml_start_tag("a:fillStyleLst", &vec![]);
+        write_solid_fill(writer, &[]);
+        for (stops, shade) in FILL_GRADIENTS {
+            write_gradient_fill(writer, stops, shade);
+        }
+        writer.xml_end_tag("a:fillStyleLst");
+
+        // Line styles.
+        writer.xml_start_tag("a:lnStyleLst", &vec![]);
+        for (width, color_mods) in LINE_STYLES {
+            let attributes = vec![
+                ("w", width),
+                ("cap", "flat"),
+                ("cmpd", "sng"),
+                ("algn", "ctr"),
+            ];
+            writer.xml_start_tag("a:ln", &attributes);
+            write_solid_fill(writer, color_mods);
+            writer.xml_empty_tag("a:prstDash", &vec![("val", "solid")]);
+            writer.xml_end_tag("a:ln");
+        }
+        writer.xml_end_tag("a:lnStyleLst");
+
+        // Effect styles. The last style adds a 3D bevel.
+        writer.xml_start_tag("a:effectStyleLst", &vec![]);
+        for (index, (distance, alpha)) in EFFECT_SHADOWS.into_iter().enumerate() {
+            writer.xml_start_tag("a:effectStyle", &vec![]);
+            write_effect_list(writer, distance, alpha);
+            if index == EFFECT_SHADOWS.len() - 1 {
+                write_bevel(writer);
+            }
+            writer.xml_end_tag("a:effectStyle");
+        }
+        writer.xml_end_tag("a:effectStyleLst");
+
+        // Background fill styles.
+        writer.xml_start_tag("a:bgFillStyleLst", &vec![]);
+        write_solid_fill(writer, &[]);
+        for (stops, shade) in BACKGROUND_GRADIENTS {
+            write_gradient_fill(writer, stops, shade);
+        }
+        writer.xml_end_tag("a:bgFillStyleLst");
+
+        writer.xml_end_tag("a:fmtScheme");
+    }
+}
+
+// Write an <a:schemeClr> placeholder color with optional color modifiers.
+fn write_placeholder_color(writer: &mut XMLWriter<impl Write>, color_mods: ColorMods) {
+    let attributes = vec![("val", "phClr")];
+
+    if color_mods.is_empty() {
+        writer.xml_empty_tag("a:schemeClr", &attributes);
+        return;
+    }
+
+    writer.xml_start_tag("a:schemeClr", &attributes);
+    for (tag, value) in color_mods {
+        writer.xml_empty_tag(tag, &vec![("val", value)]);
+    }
+    writer.xml_end_tag("a:schemeClr");
+}
+
+// Write an <a:solidFill> element.
+fn write_solid_fill(writer: &mut XMLWriter<impl Write>, color_mods: ColorMods) {
+    writer.xml_start_tag("a:solidFill", &vec![]);
+    write_placeholder_color(writer, color_mods);
+    writer.xml_end_tag("a:solidFill");
+}
+
+// Write an <a:gradFill> element.
+fn write_gradient_fill(
+    writer: &mut XMLWriter<impl Write>,
+    stops: GradientStops,
+    shade: GradientShade,
+) {
+    writer.xml_start_tag("a:gradFill", &vec![("rotWithShape", "1")]);
+
+    writer.xml_start_tag("a:gsLst", &vec![]);
+    for (position, color_mods) in stops {
+        writer.xml_start_tag("a:gs", &vec![("pos", position)]);
+        write_placeholder_color(writer, color_mods);
+        writer.xml_end_tag("a:gs");
+    }
+    writer.xml_end_tag("a:gsLst");
+
+    match shade {
+        GradientShade::Linear(scaled) => {
+            let attributes = vec![("ang", "16200000"), ("scaled", scaled)];
+            writer.xml_empty_tag("a:lin", &attributes);
+        }
+        GradientShade::Circle([left, top, right, bottom]) => {
+            writer.xml_start_tag("a:path", &vec![("path", "circle")]);
+            let attributes = vec![("l", left), ("t", top), ("r", right), ("b", bottom)];
+            writer.xml_empty_tag("a:fillToRect", &attributes);
+            writer.xml_end_tag("a:path");
+        }
+    }
+
+    writer.xml_end_tag("a:gradFill");
+}
+
+// Write an <a:effectLst> element with an outer shadow.
+fn write_effect_list(writer: &mut XMLWriter<impl Write>, distance: &str, alpha: &str) {
+    writer.xml_start_tag("a:effectLst", &vec![]);
+
+    let attributes = vec![
+        ("blurRad", "40000"),
+        ("dist", distance),
+        ("dir", "5400000"),
+        ("rotWithShape", "0"),
+    ];
+    writer.xml_start_tag("a:outerShdw", &attributes);
+    writer.xml_start_tag("a:srgbClr", &vec![("val", "000000")]);
+    writer.xml_empty_tag("a:alpha", &vec![("val", alpha)]);
+    writer.xml_end_tag("a:srgbClr");
+    writer.xml_end_tag("a:outerShdw");
+
+    writer.xml_end_tag("a:effectLst");
+}
+
+// Write the <a:scene3d> and <a:sp3d> elements of a bevelled effect.
+fn write_bevel(writer: &mut XMLWriter<impl Write>) {
+    writer.xml_start_tag("a:scene3d", &vec![]);
+
+    writer.xml_start_tag("a:camera", &vec![("prst", "orthographicFront")]);
+    writer.xml_empty_tag("a:rot", &vec![("lat", "0"), ("lon", "0"), ("rev", "0")]);
+    writer.xml_end_tag("a:camera");
+
+    writer.xml_start_tag("a:lightRig", &vec![("rig", "threePt"), ("dir", "t")]);
+    writer.xml_empty_tag(
+        "a:rot",
+        &vec![("lat", "0"), ("lon", "0"), ("rev", "1200000")],
+    );
+    writer.xml_end_tag("a:lightRig");
+
+    writer.xml_end_tag("a:scene3d");
+
+    writer.xml_start_tag("a:sp3d", &vec![]);
+    writer.xml_empty_tag("a:bevelT", &vec![("w", "63500"), ("h", "25400")]);
+    writer.xml_end_tag("a:sp3d");
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Theme;
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_theme() -> String {
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        Theme::new().assemble_xml_file(&mut writer);
+
+        read_xmlfile_data(&mut tempfile)
+    }
+
+    #[test]
+    fn test_assemble_theme_color_scheme() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" "#,
+            r#"name="Office Theme"><a:themeElements><a:clrScheme name="Office"><a:dk1>"#,
+            r#"<a:sysClr val="windowText" lastClr="000000"/></a:dk1><a:lt1>"#,
+            r#"<a:sysClr val="window" lastClr="FFFFFF"/></a:lt1><a:dk2>"#,
+            r#"<a:srgbClr val="1F497D"/></a:dk2><a:lt2><a:srgbClr val="EEECE1"/></a:lt2>"#,
+            r#"<a:accent1><a:srgbClr val="4F81BD"/></a:accent1><a:accent2>"#,
+            r#"<a:srgbClr val="C0504D"/></a:accent2><a:accent3><a:srgbClr val="9BBB59"/>"#,
+            r#"</a:accent3><a:accent4><a:srgbClr val="8064A2"/></a:accent4><a:accent5>"#,
+            r#"<a:srgbClr val="4BACC6"/></a:accent5><a:accent6><a:srgbClr val="F79646"/>"#,
+            r#"</a:accent6><a:hlink><a:srgbClr val="0000FF"/></a:hlink><a:folHlink>"#,
+            r#"<a:srgbClr val="800080"/></a:folHlink></a:clrScheme>"#,
+        );
+
+        let got = assemble_theme();
+        assert!(got.starts_with(expected));
+        assert!(got
+            .ends_with(r#"</a:themeElements><a:objectDefaults/><a:extraClrSchemeLst/></a:theme>"#));
+    }
+
+    #[test]
+    fn test_assemble_theme_font_scheme() {
+        let got = assemble_theme();
+
+        assert!(got.contains(concat!(
+            r#"<a:fontScheme name="Office"><a:majorFont><a:latin typeface="Cambria"/>"#,
+            r#"<a:ea typeface=""/><a:cs typeface=""/>"#,
+            r#"<a:font script="Jpan" typeface="ＭＳ Ｐゴシック"/>"#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<a:font script="Uigh" typeface="Microsoft Uighur"/></a:majorFont>"#,
+            r#"<a:minorFont><a:latin typeface="Calibri"/>"#,
+        )));
+        assert!(got.contains(r#"<a:font script="Khmr" typeface="DaunPenh"/>"#));
+        assert_eq!(got.matches("<a:font script=").count(), 58);
+    }
+
+    #[test]
+    fn test_assemble_theme_format_scheme() {
+        let got = assemble_theme();
+
+        assert!(got.contains(concat!(
+            r#"<a:fmtScheme name="Office"><a:fillStyleLst><a:solidFill>"#,
+            r#"<a:schemeClr val="phClr"/></a:solidFill><a:gradFill rotWithShape="1">"#,
+            r#"<a:gsLst><a:gs pos="0"><a:schemeClr val="phClr"><a:tint val="50000"/>"#,
+            r#"<a:satMod val="300000"/></a:schemeClr></a:gs>"#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<a:lnStyleLst><a:ln w="9525" cap="flat" cmpd="sng" algn="ctr">"#,
+            r#"<a:solidFill><a:schemeClr val="phClr"><a:shade val="95000"/>"#,
+            r#"<a:satMod val="105000"/></a:schemeClr></a:solidFill>"#,
+            r#"<a:prstDash val="solid"/></a:ln>"#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<a:sp3d><a:bevelT w="63500" h="25400"/></a:sp3d></a:effectStyle>"#,
+            r#"</a:effectStyleLst>"#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<a:path path="circle"><a:fillToRect l="50000" t="50000" r="50000" "#,
+            r#"b="50000"/></a:path></a:gradFill></a:bgFillStyleLst></a:fmtScheme>"#,
+        )));
+        assert_eq!(got.matches("<a:effectStyle>").count(), 3);
+    }
+}
@@ -0,0 +1,447 @@
+//! The threaded comments of a worksheet and the writers for the
+//! `xl/threadedComments/threadedCommentN.xml` and `xl/persons/person.xml`
+//! parts.
+//!
+//! A [`ThreadedComment`] is a comment with replies, which is added to a cell
+//! with
+//! [`Worksheet::add_threaded_comment()`](crate::worksheet::Worksheet::add_threaded_comment).
+//! The comments of a worksheet are written to a [`ThreadedComments`] part,
+//! with the same number as its notes parts, and the authors of all the
+//! comments in the workbook are written to a single [`Persons`] part.
+//!
+//! Versions of Excel before threaded comments read a note in their place, so
+//! each thread is also written as a note, with the text of the comment and
+//! its replies, to the worksheet's
+//! [`comments()`](crate::worksheet::Worksheet::comments) and
+//! [`vml_drawing()`](crate::worksheet::Worksheet::vml_drawing) parts, which
+//! are needed as well.
+//!
+//! The parts are linked with relationships from the workbook to the persons
+//! part, and from the worksheet to its threaded comments part. The ids of
+//! the comments and the authors are GUIDs derived from their content, so the
+//! output is the same for the same input.
+//!
+//! ```
+//! # #[cfg(feature = "zip")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use excel_xmlwriter::content_types::ContentTypes;
+//! use excel_xmlwriter::packager::Packager;
+//! use excel_xmlwriter::relationship::Relationships;
+//! use excel_xmlwriter::threaded_comment::{Persons, ThreadedComment};
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut worksheet = Worksheet::new();
+//! let mut content_types = ContentTypes::new();
+//! let mut workbook_relationships = Relationships::new();
+//! let mut packager = Packager::new(vec![]);
+//! let mut persons = Persons::new();
+//!
+//! persons.add_person("Jane", "jane@example.com", "AD");
+//!
+//! let comment = ThreadedComment::new("Jane", "Is this final?").add_reply("Sam", "Yes");
+//! worksheet.add_threaded_comment(1, 1, &comment);
+//!
+//! if let Some(threaded_comments) = worksheet.threaded_comments() {
+//!     persons.add_authors(&threaded_comments);
+//!     content_types.add_threaded_comments(1);
+//!     packager.add_custom_part(&threaded_comments)?;
+//! }
+//!
+//! content_types.add_persons();
+//! workbook_relationships.add_office_relationship("2017/10/relationships/person", "persons/person.xml");
+//! packager.add_custom_part(&persons)?;
+//! #
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "zip"))]
+//! # fn main() {}
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+use std::time::SystemTime;
+
+use crate::constants::{
+    PERSONS_CONTENT_TYPE, SPREADSHEETML_NS, THREADED_COMMENTS_CONTENT_TYPE, THREADED_COMMENTS_NS,
+};
+use crate::core_properties::w3cdtf_datetime;
+use crate::note::Note;
+use crate::part::XlsxPart;
+use crate::utility::rowcol_to_cell;
+use crate::XMLWriter;
+
+// The text that Excel writes in the notes of threaded comments, for versions
+// of Excel that don't support them.
+const LEGACY_NOTE_TEXT: &str = "[Threaded comment]\n\nYour version of Excel allows you to \
+    read this threaded comment; however, any edits to it will get removed if the file is \
+    opened in a newer version of Excel. Learn more: \
+    https://go.microsoft.com/fwlink/?linkid=870924\n\nComment:\n    ";
+
+// A comment or a reply in a thread.
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    author: String,
+    text: String,
+    date: SystemTime,
+}
+
+/// A threaded comment on a cell, with its replies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadedComment {
+    entries: Vec<Entry>,
+    resolved: bool,
+}
+
+impl ThreadedComment {
+    /// Create a comment with its author's display name and its text. The
+    /// date of the comment defaults to the current time.
+    pub fn new(author: &str, text: &str) -> ThreadedComment {
+        ThreadedComment {
+            entries: vec![Entry {
+                author: author.to_string(),
+                text: text.to_string(),
+                date: SystemTime::now(),
+            }],
+            resolved: false,
+        }
+    }
+
+    /// Add a reply to the thread, dated with the current time.
+    pub fn add_reply(self, author: &str, text: &str) -> ThreadedComment {
+        self.add_reply_with_date(author, text, SystemTime::now())
+    }
+
+    /// Add a reply to the thread with its date.
+    pub fn add_reply_with_date(
+        mut self,
+        author: &str,
+        text: &str,
+        date: SystemTime,
+    ) -> ThreadedComment {
+        self.entries.push(Entry {
+            author: author.to_string(),
+            text: text.to_string(),
+            date,
+        });
+        self
+    }
+
+    /// Set the date of the comment that starts the thread.
+    pub fn set_date(mut self, date: SystemTime) -> ThreadedComment {
+        self.entries[0].date = date;
+        self
+    }
+
+    /// Set whether the thread is resolved. The default is `false`.
+    pub fn set_resolved(mut self, resolved: bool) -> ThreadedComment {
+        self.resolved = resolved;
+        self
+    }
+
+    // The note that older versions of Excel show for the thread, in the
+    // notes part with the given number. Its author refers to the id of the
+    // first comment in the thread.
+    pub(crate) fn legacy_note(&self, number: u32, row: u32, col: u16) -> Note {
+        let mut text = LEGACY_NOTE_TEXT.to_string();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                text.push_str("\nReply:\n    ");
+            }
+            text.push_str(&entry.text);
+        }
+
+        let author = format!("tc={}", comment_id(number, row, col, 0));
+        Note::new(&text).set_author(&author)
+    }
+}
+
+/// The `xl/threadedComments/threadedCommentN.xml` part with the threaded
+/// comments of a worksheet, created with
+/// [`Worksheet::threaded_comments()`](crate::worksheet::Worksheet::threaded_comments).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreadedComments {
+    number: u32,
+    part_name: String,
+    comments: Vec<(u32, u16, ThreadedComment)>,
+}
+
+impl ThreadedComments {
+    // Create the part from the comments in row and column order.
+    pub(crate) fn new<'a>(
+        number: u32,
+        comments: impl Iterator<Item = (u32, u16, &'a ThreadedComment)>,
+    ) -> ThreadedComments {
+        ThreadedComments {
+            number,
+            part_name: format!("xl/threadedComments/threadedComment{number}.xml"),
+            comments: comments
+                .map(|(row, col, comment)| (row, col, comment.clone()))
+                .collect(),
+        }
+    }
+
+    // The display names of the authors, in the order of their first comment.
+    fn authors(&self) -> Vec<&str> {
+        let mut authors: Vec<&str> = vec![];
+        for (_, _, comment) in &self.comments {
+            for entry in &comment.entries {
+                if !authors.contains(&entry.author.as_str()) {
+                    authors.push(&entry.author);
+                }
+            }
+        }
+
+        authors
+    }
+
+    /// Write the `xl/threadedComments/threadedCommentN.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns", THREADED_COMMENTS_NS),
+            ("xmlns:x", SPREADSHEETML_NS),
+        ];
+        writer.xml_start_tag("ThreadedComments", &attributes);
+
+        for (row, col, comment) in &self.comments {
+            let cell_ref = rowcol_to_cell(*row, *col);
+            let parent_id = comment_id(self.number, *row, *col, 0);
+
+            for (index, entry) in comment.entries.iter().enumerate() {
+                // Excel writes the time with hundredths of a second.
+                let date = w3cdtf_datetime(entry.date).replace('Z', ".00");
+                let person_id = person_id(&entry.author);
+                let id = comment_id(self.number, *row, *col, index);
+
+                let mut attributes = vec![
+                    ("ref", cell_ref.as_str()),
+                    ("dT", date.as_str()),
+                    ("personId", person_id.as_str()),
+                    ("id", id.as_str()),
+                ];
+                if index > 0 {
+                    attributes.push(("parentId", parent_id.as_str()));
+                } else if comment.resolved {
+                    attributes.push(("done", "1"));
+                }
+
+                writer.xml_start_tag("threadedComment", &attributes);
+                writer.xml_data_element("text", &entry.text, &vec![]);
+                writer.xml_end_tag("threadedComment");
+            }
+        }
+
+        writer.xml_end_tag("ThreadedComments");
+    }
+}
+
+impl XlsxPart for ThreadedComments {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        THREADED_COMMENTS_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+/// The `xl/persons/person.xml` part with the authors of the threaded
+/// comments in the workbook.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Persons {
+    persons: Vec<(String, String, String)>,
+}
+
+impl Persons {
+    /// Create a new, empty, persons part.
+    pub fn new() -> Persons {
+        Persons::default()
+    }
+
+    /// Add an author with the user id and identity provider that Excel uses
+    /// to look them up, such as an email address with the `AD` provider for
+    /// Active Directory. Authors are identified by their display name, so
+    /// names that have already been added are ignored.
+    pub fn add_person(&mut self, display_name: &str, user_id: &str, provider_id: &str) {
+        if self.persons.iter().any(|(name, _, _)| name == display_name) {
+            return;
+        }
+
+        self.persons.push((
+            display_name.to_string(),
+            user_id.to_string(),
+            provider_id.to_string(),
+        ));
+    }
+
+    /// Add the authors of a worksheet's threaded comments that haven't been
+    /// added, with their display name as the user id and no identity
+    /// provider.
+    pub fn add_authors(&mut self, threaded_comments: &ThreadedComments) {
+        for author in threaded_comments.authors() {
+            self.add_person(author, author, "None");
+        }
+    }
+
+    /// Write the `xl/persons/person.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![
+            ("xmlns", THREADED_COMMENTS_NS),
+            ("xmlns:x", SPREADSHEETML_NS),
+        ];
+        writer.xml_start_tag("personList", &attributes);
+
+        for (display_name, user_id, provider_id) in &self.persons {
+            let id = person_id(display_name);
+            let attributes = vec![
+                ("displayName", display_name.as_str()),
+                ("id", id.as_str()),
+                ("userId", user_id.as_str()),
+                ("providerId", provider_id.as_str()),
+            ];
+            writer.xml_empty_tag("person", &attributes);
+        }
+
+        writer.xml_end_tag("personList");
+    }
+}
+
+impl XlsxPart for Persons {
+    fn part_name(&self) -> &str {
+        "xl/persons/person.xml"
+    }
+
+    fn content_type(&self) -> &str {
+        PERSONS_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+// The id of an author, from their display name.
+fn person_id(display_name: &str) -> String {
+    guid(&format!("person:{display_name}"))
+}
+
+// The id of a comment in a thread, from the part number, the cell and its
+// position in the thread. The number makes the ids unique in the workbook.
+fn comment_id(number: u32, row: u32, col: u16, index: usize) -> String {
+    guid(&format!("comment:{number}:{row}:{col}:{index}"))
+}
+
+// Create a version 4 style GUID, like `{2F1B...}`, from two FNV-1a hashes
+// of a string, so that the same string always gets the same GUID.
+fn guid(seed: &str) -> String {
+    let fnv = |offset: u64| {
+        seed.bytes().fold(offset, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    };
+    let high = fnv(0xCBF2_9CE4_8422_2325);
+    let low = fnv(0x6C62_272E_07BB_0142);
+
+    format!(
+        "{{{:08X}-{:04X}-4{:03X}-{:04X}-{:012X}}}",
+        high >> 32,
+        (high >> 16) & 0xFFFF,
+        high & 0x0FFF,
+        (low >> 48) & 0x3FFF | 0x8000,
+        low & 0xFFFF_FFFF_FFFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{guid, person_id, Persons, ThreadedComment, ThreadedComments};
+    use crate::XMLWriter;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_guid() {
+        let id = guid("person:Jane");
+
+        assert_eq!(id.len(), 38);
+        assert_eq!(id, guid("person:Jane"));
+        assert_ne!(id, guid("person:Sam"));
+        assert_eq!(&id[15..16], "4");
+        assert!(matches!(&id[20..21], "8" | "9" | "A" | "B"));
+    }
+
+    #[test]
+    fn test_assemble_threaded_comments() {
+        let date = UNIX_EPOCH + Duration::from_secs(1_672_531_200);
+        let comment = ThreadedComment::new("Jane", "Is this <final>?")
+            .set_date(date)
+            .add_reply_with_date("Sam", "Yes", date + Duration::from_secs(61))
+            .set_resolved(true);
+        let comments = [(1, 1, comment)];
+        let threaded_comments = ThreadedComments::new(
+            1,
+            comments
+                .iter()
+                .map(|(row, col, comment)| (*row, *col, comment)),
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        threaded_comments.assemble_xml_file(&mut writer);
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        let jane = person_id("Jane");
+        let sam = person_id("Sam");
+        let first = super::comment_id(1, 1, 1, 0);
+        let reply = super::comment_id(1, 1, 1, 1);
+        let expected = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                "\n",
+                r#"<ThreadedComments xmlns="http://schemas.microsoft.com/office/spreadsheetml/2018/threadedcomments" "#,
+                r#"xmlns:x="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+                r#"<threadedComment ref="B2" dT="2023-01-01T00:00:00.00" personId="{jane}" id="{first}" done="1">"#,
+                r#"<text>Is this &lt;final&gt;?</text></threadedComment>"#,
+                r#"<threadedComment ref="B2" dT="2023-01-01T00:01:01.00" personId="{sam}" id="{reply}" parentId="{first}">"#,
+                r#"<text>Yes</text></threadedComment></ThreadedComments>"#,
+            ),
+            jane = jane,
+            sam = sam,
+            first = first,
+            reply = reply,
+        );
+        assert_eq!(got, expected);
+
+        let mut persons = Persons::new();
+        persons.add_person("Jane", "jane@example.com", "AD");
+        persons.add_authors(&threaded_comments);
+
+        let mut writer = XMLWriter::new(vec![]);
+        persons.assemble_xml_file(&mut writer);
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        let expected = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                "\n",
+                r#"<personList xmlns="http://schemas.microsoft.com/office/spreadsheetml/2018/threadedcomments" "#,
+                r#"xmlns:x="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+                r#"<person displayName="Jane" id="{jane}" userId="jane@example.com" providerId="AD"/>"#,
+                r#"<person displayName="Sam" id="{sam}" userId="Sam" providerId="None"/>"#,
+                r#"</personList>"#,
+            ),
+            jane = jane,
+            sam = sam,
+        );
+        assert_eq!(got, expected);
+    }
+}
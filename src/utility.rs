@@ -0,0 +1,935 @@
+//! Utility functions for converting between zero-indexed row/column numbers
+//! and Excel's `A1` style cell references and column names, and for
+//! inspecting formulas.
+//!
+//! ```
+//! use excel_xmlwriter::utility::{
+//!     cell_to_rowcol, col_to_name, name_to_col, rowcol_to_cell, rowcol_to_cell_abs,
+//! };
+//!
+//! assert_eq!(col_to_name(16_383), "XFD");
+//! assert_eq!(name_to_col("XFD"), 16_383);
+//! assert_eq!(rowcol_to_cell(6, 1), "B7");
+//! assert_eq!(rowcol_to_cell_abs(6, 1), "$B$7");
+//! assert_eq!(cell_to_rowcol("$B$7"), (6, 1));
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The number of rows in an Excel worksheet.
+pub const ROW_MAX: u32 = 1_048_576;
+
+/// The number of columns in an Excel worksheet. The last column is `XFD`.
+pub const COL_MAX: u16 = 16_384;
+
+/// Convert zero-indexed row and column numbers to an `A1` style string.
+///
+/// # Panics
+///
+/// Panics if `row` or `col` is beyond Excel's last row or column.
+/// ```
+/// # use excel_xmlwriter::utility::rowcol_to_cell;
+/// #
+/// assert_eq!(rowcol_to_cell(0, 0), "A1");
+/// assert_eq!(rowcol_to_cell(6, 1), "B7");
+/// assert_eq!(rowcol_to_cell(1_048_575, 16_383), "XFD1048576");
+/// ```
+pub fn rowcol_to_cell(row: u32, col: u16) -> String {
+    format!("{}{}", col_to_name(col), row_to_name(row))
+}
+
+/// Convert zero-indexed row and column numbers to an absolute `$A$1` style
+/// string.
+///
+/// # Panics
+///
+/// Panics if `row` or `col` is beyond Excel's last row or column.
+/// ```
+/// # use excel_xmlwriter::utility::rowcol_to_cell_abs;
+/// #
+/// assert_eq!(rowcol_to_cell_abs(6, 1), "$B$7");
+/// ```
+pub fn rowcol_to_cell_abs(row: u32, col: u16) -> String {
+    format!("${}${}", col_to_name(col), row_to_name(row))
+}
+
+// Convert a zero-indexed row number to Excel's one-indexed row number.
+fn row_to_name(row: u32) -> u32 {
+    assert!(
+        row < ROW_MAX,
+        "row {row} is beyond Excel's last row 1048576"
+    );
+
+    row + 1
+}
+
+/// Convert an `A1` style string, with or without `$` absolute markers, to
+/// zero-indexed row and column numbers.
+///
+/// # Panics
+///
+/// Panics if the column part of the reference isn't a valid column name, see
+/// [`name_to_col()`], or if the row part isn't a row number from 1 to
+/// 1048576.
+/// ```
+/// # use excel_xmlwriter::utility::cell_to_rowcol;
+/// #
+/// assert_eq!(cell_to_rowcol("A1"), (0, 0));
+/// assert_eq!(cell_to_rowcol("$B$7"), (6, 1));
+/// assert_eq!(cell_to_rowcol("B$7"), (6, 1));
+/// ```
+pub fn cell_to_rowcol(cell: &str) -> (u32, u16) {
+    let cell = cell.replace('$', "");
+    let split = cell
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(cell.len());
+
+    let (letters, digits) = cell.split_at(split);
+
+    let col = name_to_col(letters);
+    let row = match digits.parse::<u32>() {
+        Ok(row) if (1..=ROW_MAX).contains(&row) => row - 1,
+        _ => panic!("invalid row number in cell reference '{cell}'"),
+    };
+
+    (row, col)
+}
+
+/// Convert a zero-indexed column number to Excel's column letters.
+///
+/// # Panics
+///
+/// Panics if `col` is beyond Excel's last column, `XFD`.
+/// ```
+/// # use excel_xmlwriter::utility::col_to_name;
+/// #
+/// assert_eq!(col_to_name(0), "A");
+/// assert_eq!(col_to_name(26), "AA");
+/// assert_eq!(col_to_name(16_383), "XFD");
+/// ```
+pub fn col_to_name(col: u16) -> String {
+    assert!(
+        col < COL_MAX,
+        "column {col} is beyond Excel's last column XFD"
+    );
+
+    // Excel columns are base-26 but without a zero digit: A-Z, AA-AZ, etc.
+    let mut col = col as u32 + 1;
+    let mut letters = Vec::new();
+
+    while col > 0 {
+        let remainder = (col - 1) % 26;
+        letters.push((b'A' + remainder as u8) as char);
+        col = (col - 1) / 26;
+    }
+
+    letters.iter().rev().collect()
+}
+
+/// Convert Excel's column letters, in either case, to a zero-indexed column
+/// number.
+///
+/// # Panics
+///
+/// Panics if `name` is empty, contains characters other than `A-Z` or is
+/// beyond Excel's last column, `XFD`.
+/// ```
+/// # use excel_xmlwriter::utility::name_to_col;
+/// #
+/// assert_eq!(name_to_col("A"), 0);
+/// assert_eq!(name_to_col("aa"), 26);
+/// assert_eq!(name_to_col("XFD"), 16_383);
+/// ```
+pub fn name_to_col(name: &str) -> u16 {
+    assert!(
+        !name.is_empty() && name.len() <= 3 && name.bytes().all(|b| b.is_ascii_alphabetic()),
+        "invalid column name '{name}'"
+    );
+
+    let mut col: u32 = 0;
+
+    for letter in name.bytes() {
+        col = col * 26 + (letter.to_ascii_uppercase() - b'A' + 1) as u32;
+    }
+
+    assert!(
+        col <= COL_MAX as u32,
+        "column '{name}' is beyond Excel's last column XFD"
+    );
+
+    (col - 1) as u16
+}
+
+/// Get the worksheet names that a formula refers to, such as `Sheet2` in
+/// `=Sheet2!A1`, in the order that they appear.
+///
+/// Quoted names are unquoted and both ends of a 3D reference like
+/// `Sheet1:Sheet3!A1` are returned. References to other workbooks, string
+/// literals and error values like `#REF!` are ignored.
+/// ```
+/// # use excel_xmlwriter::utility::formula_sheet_names;
+/// #
+/// assert_eq!(formula_sheet_names("=Sheet2!A1*2"), vec!["Sheet2"]);
+/// assert_eq!(formula_sheet_names("='Q1 ''22'!A1"), vec!["Q1 '22"]);
+/// assert_eq!(formula_sheet_names("=SUM(A1:A9)"), Vec::<String>::new());
+/// ```
+pub fn formula_sheet_names(formula: &str) -> Vec<String> {
+    let mut names = vec![];
+    let mut token = String::new();
+    let mut external = false;
+    let mut chars = formula.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            // String literals, with "" as an escaped quote.
+            '"' => {
+                while let Some(char) = chars.next() {
+                    if char == '"' && chars.next_if_eq(&'"').is_none() {
+                        break;
+                    }
+                }
+                token.clear();
+            }
+
+            // Quoted sheet names, with '' as an escaped quote.
+            '\'' => {
+                let mut name = String::new();
+                while let Some(char) = chars.next() {
+                    if char == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                    name.push(char);
+                }
+
+                // Names with a [book] prefix are in another workbook.
+                if chars.next_if_eq(&'!').is_some() && !name.contains('[') {
+                    names.extend(name.split(':').map(|name| name.to_string()));
+                }
+                token.clear();
+            }
+
+            // Error values such as #REF! and #DIV/0!.
+            '#' => {
+                while chars
+                    .next_if(|c| c.is_alphanumeric() || *c == '/')
+                    .is_some()
+                {}
+                chars.next_if(|c| *c == '!' || *c == '?');
+                token.clear();
+            }
+
+            '!' => {
+                if !token.is_empty() && !external {
+                    names.extend(token.split(':').map(|name| name.to_string()));
+                }
+                token.clear();
+            }
+
+            // A [book] prefix, or the end of a structured reference.
+            ']' => {
+                token.clear();
+                external = true;
+            }
+
+            ':' if !token.is_empty() => token.push(char),
+
+            char if char.is_alphanumeric() || char == '_' || char == '.' => token.push(char),
+
+            _ => {
+                token.clear();
+                external = false;
+            }
+        }
+    }
+
+    names
+}
+
+/// Quote a worksheet name for use in a formula or defined name, if Excel
+/// requires it. Names are quoted if they contain characters other than
+/// letters, digits, underscores and periods, start with a digit or look like
+/// a cell reference. Single quotes in the name are doubled.
+/// ```
+/// # use excel_xmlwriter::utility::quote_sheet_name;
+/// #
+/// assert_eq!(quote_sheet_name("Sheet1"), "Sheet1");
+/// assert_eq!(quote_sheet_name("Q1 Sales"), "'Q1 Sales'");
+/// assert_eq!(quote_sheet_name("Bob's"), "'Bob''s'");
+/// ```
+pub fn quote_sheet_name(name: &str) -> String {
+    let name_uppercase = name.to_uppercase();
+
+    let needs_quotes = name.is_empty()
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        || is_a1_reference(&name_uppercase)
+        || is_r1c1_reference(&name_uppercase);
+
+    if needs_quotes {
+        format!("'{}'", name.replace('\'', "''"))
+    } else {
+        name.to_string()
+    }
+}
+
+/// Prefix the functions that were added to Excel after the original file
+/// format, like `XLOOKUP`, `TEXTJOIN` and `LET`, with `_xlfn.` as Excel
+/// stores them in files. Without the prefix Excel shows a `#NAME?` error
+/// until the formula is edited.
+///
+/// The `FILTER` and `SORT` worksheet functions are prefixed with
+/// `_xlfn._xlws.`, and the names declared by `LET` and the parameters of
+/// `LAMBDA` functions, and their uses, are prefixed with `_xlpm.`. Names that
+/// are already prefixed, string literals and quoted sheet names are left
+/// unchanged.
+/// ```
+/// # use excel_xmlwriter::utility::prefix_future_functions;
+/// #
+/// assert_eq!(
+///     prefix_future_functions("=XLOOKUP(A1,B:B,C:C)"),
+///     "=_xlfn.XLOOKUP(A1,B:B,C:C)"
+/// );
+/// assert_eq!(
+///     prefix_future_functions("=SORT(UNIQUE(A1:A9))"),
+///     "=_xlfn._xlws.SORT(_xlfn.UNIQUE(A1:A9))"
+/// );
+/// assert_eq!(
+///     prefix_future_functions("=LET(x,1,x+1)"),
+///     "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)"
+/// );
+/// assert_eq!(prefix_future_functions("=SUM(A1:A9)"), "=SUM(A1:A9)");
+/// ```
+pub fn prefix_future_functions(formula: &str) -> String {
+    let mut prefixed = String::with_capacity(formula.len());
+    let mut token = String::new();
+    let mut scopes: Vec<Scope> = vec![];
+    let mut in_array = false;
+    let mut chars = formula.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            // String literals and quoted sheet names, with a doubled quote
+            // as an escaped quote, are copied unchanged.
+            '"' | '\'' => {
+                push_name(&mut prefixed, &mut token, &mut scopes, false);
+                prefixed.push(char);
+
+                let quote = char;
+                while let Some(char) = chars.next() {
+                    prefixed.push(char);
+                    if char == quote {
+                        match chars.next_if_eq(&quote) {
+                            Some(char) => prefixed.push(char),
+                            None => break,
+                        }
+                    }
+                }
+            }
+
+            '(' => {
+                let name = token.to_uppercase();
+                if is_parameter(&scopes, &name) {
+                    prefixed.push_str("_xlpm.");
+                } else if WORKSHEET_FUNCTIONS.contains(&name.as_str()) {
+                    prefixed.push_str("_xlfn._xlws.");
+                } else if FUTURE_FUNCTIONS.contains(&name.as_str()) {
+                    prefixed.push_str("_xlfn.");
+                }
+                prefixed.push_str(&token);
+                token.clear();
+                prefixed.push(char);
+
+                scopes.push(Scope {
+                    function: name,
+                    arg: 0,
+                    arg_empty: true,
+                    names: vec![],
+                });
+            }
+
+            char if char.is_alphanumeric() || char == '_' || char == '.' => token.push(char),
+
+            _ => {
+                // A LET name or LAMBDA parameter is a whole argument, which
+                // is followed by another argument.
+                let next = match char {
+                    char if char.is_whitespace() => chars.clone().find(|c| !c.is_whitespace()),
+                    char => Some(char),
+                };
+                let declares = next == Some(',') && !in_array;
+                push_name(&mut prefixed, &mut token, &mut scopes, declares);
+                prefixed.push(char);
+
+                match char {
+                    ')' => {
+                        scopes.pop();
+                        if let Some(scope) = scopes.last_mut() {
+                            scope.arg_empty = false;
+                        }
+                    }
+                    ',' if !in_array => {
+                        if let Some(scope) = scopes.last_mut() {
+                            scope.arg += 1;
+                            scope.arg_empty = true;
+                        }
+                    }
+                    '{' => in_array = true,
+                    '}' => in_array = false,
+                    char if char.is_whitespace() => {}
+                    _ => {
+                        if let Some(scope) = scopes.last_mut() {
+                            scope.arg_empty = false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    push_name(&mut prefixed, &mut token, &mut scopes, false);
+    prefixed
+}
+
+// A parenthesized part of a formula, with the names that it declares if it
+// is the arguments of a LET or LAMBDA function.
+struct Scope {
+    function: String,
+    arg: usize,
+    arg_empty: bool,
+    names: Vec<String>,
+}
+
+// Add a name, or another token such as a number or cell reference, to a
+// formula, with an `_xlpm.` prefix if it is a LET name or LAMBDA parameter.
+// If `declares` is set, a name that is a whole argument where LET or LAMBDA
+// expects a name is declared in the innermost scope.
+fn push_name(prefixed: &mut String, token: &mut String, scopes: &mut [Scope], declares: bool) {
+    if token.is_empty() {
+        return;
+    }
+
+    let name = token.to_uppercase();
+
+    if let Some(scope) = scopes.last_mut() {
+        let is_declaration = declares
+            && scope.arg_empty
+            && match scope.function.as_str() {
+                "LET" | "_XLFN.LET" => scope.arg % 2 == 0,
+                "LAMBDA" | "_XLFN.LAMBDA" => true,
+                _ => false,
+            };
+
+        if is_declaration && !name.starts_with("_XLPM.") && !scope.names.contains(&name) {
+            scope.names.push(name.clone());
+        }
+        scope.arg_empty = false;
+    }
+
+    if is_parameter(scopes, &name) {
+        prefixed.push_str("_xlpm.");
+    }
+    prefixed.push_str(token);
+    token.clear();
+}
+
+// Check if an uppercase name is declared by one of the open LET or LAMBDA
+// functions of a formula.
+fn is_parameter(scopes: &[Scope], name: &str) -> bool {
+    scopes
+        .iter()
+        .any(|scope| scope.names.iter().any(|declared| declared == name))
+}
+
+// The worksheet functions that Excel stores with an `_xlfn._xlws.` prefix.
+const WORKSHEET_FUNCTIONS: [&str; 2] = ["FILTER", "SORT"];
+
+// The functions that Excel stores with an `_xlfn.` prefix.
+const FUTURE_FUNCTIONS: [&str; 165] = [
+    "ACOT",
+    "ACOTH",
+    "AGGREGATE",
+    "ARABIC",
+    "ARRAYTOTEXT",
+    "BASE",
+    "BETA.DIST",
+    "BETA.INV",
+    "BINOM.DIST",
+    "BINOM.DIST.RANGE",
+    "BINOM.INV",
+    "BITAND",
+    "BITLSHIFT",
+    "BITOR",
+    "BITRSHIFT",
+    "BITXOR",
+    "BYCOL",
+    "BYROW",
+    "CEILING.MATH",
+    "CEILING.PRECISE",
+    "CHISQ.DIST",
+    "CHISQ.DIST.RT",
+    "CHISQ.INV",
+    "CHISQ.INV.RT",
+    "CHISQ.TEST",
+    "CHOOSECOLS",
+    "CHOOSEROWS",
+    "COMBINA",
+    "CONCAT",
+    "CONFIDENCE.NORM",
+    "CONFIDENCE.T",
+    "COT",
+    "COTH",
+    "COVARIANCE.P",
+    "COVARIANCE.S",
+    "CSC",
+    "CSCH",
+    "DAYS",
+    "DECIMAL",
+    "DROP",
+    "ECMA.CEILING",
+    "ENCODEURL",
+    "ERF.PRECISE",
+    "ERFC.PRECISE",
+    "EXPAND",
+    "EXPON.DIST",
+    "F.DIST",
+    "F.DIST.RT",
+    "F.INV",
+    "F.INV.RT",
+    "F.TEST",
+    "FIELDVALUE",
+    "FILTERXML",
+    "FLOOR.MATH",
+    "FLOOR.PRECISE",
+    "FORECAST.ETS",
+    "FORECAST.ETS.CONFINT",
+    "FORECAST.ETS.SEASONALITY",
+    "FORECAST.ETS.STAT",
+    "FORECAST.LINEAR",
+    "FORMULATEXT",
+    "GAMMA",
+    "GAMMA.DIST",
+    "GAMMA.INV",
+    "GAMMALN.PRECISE",
+    "GAUSS",
+    "GROUPBY",
+    "HSTACK",
+    "HYPGEOM.DIST",
+    "IFNA",
+    "IFS",
+    "IMAGE",
+    "IMCOSH",
+    "IMCOT",
+    "IMCSC",
+    "IMCSCH",
+    "IMSEC",
+    "IMSECH",
+    "IMSINH",
+    "IMTAN",
+    "ISFORMULA",
+    "ISO.CEILING",
+    "ISOMITTED",
+    "ISOWEEKNUM",
+    "LAMBDA",
+    "LET",
+    "LOGNORM.DIST",
+    "LOGNORM.INV",
+    "MAKEARRAY",
+    "MAP",
+    "MAXIFS",
+    "MINIFS",
+    "MODE.MULT",
+    "MODE.SNGL",
+    "MUNIT",
+    "NEGBINOM.DIST",
+    "NETWORKDAYS.INTL",
+    "NORM.DIST",
+    "NORM.INV",
+    "NORM.S.DIST",
+    "NORM.S.INV",
+    "NUMBERVALUE",
+    "PDURATION",
+    "PERCENTILE.EXC",
+    "PERCENTILE.INC",
+    "PERCENTOF",
+    "PERCENTRANK.EXC",
+    "PERCENTRANK.INC",
+    "PERMUTATIONA",
+    "PHI",
+    "PIVOTBY",
+    "POISSON.DIST",
+    "QUARTILE.EXC",
+    "QUARTILE.INC",
+    "QUERYSTRING",
+    "RANDARRAY",
+    "RANK.AVG",
+    "RANK.EQ",
+    "REDUCE",
+    "REGEXEXTRACT",
+    "REGEXREPLACE",
+    "REGEXTEST",
+    "RRI",
+    "SCAN",
+    "SEC",
+    "SECH",
+    "SEQUENCE",
+    "SHEET",
+    "SHEETS",
+    "SKEW.P",
+    "SORTBY",
+    "STDEV.P",
+    "STDEV.S",
+    "STOCKHISTORY",
+    "SWITCH",
+    "T.DIST",
+    "T.DIST.2T",
+    "T.DIST.RT",
+    "T.INV",
+    "T.INV.2T",
+    "T.TEST",
+    "TAKE",
+    "TEXTAFTER",
+    "TEXTBEFORE",
+    "TEXTJOIN",
+    "TEXTSPLIT",
+    "TOCOL",
+    "TOROW",
+    "TRIMRANGE",
+    "UNICHAR",
+    "UNICODE",
+    "UNIQUE",
+    "VALUETOTEXT",
+    "VAR.P",
+    "VAR.S",
+    "VSTACK",
+    "WEBSERVICE",
+    "WEIBULL.DIST",
+    "WORKDAY.INTL",
+    "WRAPCOLS",
+    "WRAPROWS",
+    "XLOOKUP",
+    "XMATCH",
+    "XOR",
+    "Z.TEST",
+];
+
+// Check if an uppercase name is an A1 style cell reference, like `XFD1048576`.
+pub(crate) fn is_a1_reference(name: &str) -> bool {
+    let split = name
+        .find(|c: char| !c.is_ascii_uppercase())
+        .unwrap_or(name.len());
+    let (letters, digits) = name.split_at(split);
+
+    if letters.is_empty()
+        || letters.len() > 3
+        || digits.is_empty()
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+    {
+        return false;
+    }
+
+    let col = letters
+        .bytes()
+        .fold(0, |col, letter| col * 26 + (letter - b'A' + 1) as u32);
+
+    match digits.parse::<u32>() {
+        Ok(row) => col <= COL_MAX as u32 && (1..=ROW_MAX).contains(&row),
+        Err(_) => false,
+    }
+}
+
+// Check if an uppercase name is an R1C1 style reference, like `R`, `C2` or
+// `R1C1`, which Excel reserves even with the A1 reference style.
+pub(crate) fn is_r1c1_reference(name: &str) -> bool {
+    let is_digit = |c: char| c.is_ascii_digit();
+
+    let (has_row, rest) = match name.strip_prefix('R') {
+        Some(rest) => (true, rest.trim_start_matches(is_digit)),
+        None => (false, name),
+    };
+    let (has_col, rest) = match rest.strip_prefix('C') {
+        Some(rest) => (true, rest.trim_start_matches(is_digit)),
+        None => (false, rest),
+    };
+
+    (has_row || has_col) && rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{
+        cell_to_rowcol, col_to_name, formula_sheet_names, name_to_col, prefix_future_functions,
+        quote_sheet_name, rowcol_to_cell, rowcol_to_cell_abs,
+    };
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rowcol_to_cell() {
+        let tests = vec![
+            (0, 0, "A1"),
+            (0, 1, "B1"),
+            (0, 25, "Z1"),
+            (0, 26, "AA1"),
+            (0, 51, "AZ1"),
+            (0, 52, "BA1"),
+            (0, 701, "ZZ1"),
+            (0, 702, "AAA1"),
+            (0, 16_383, "XFD1"),
+            (6, 1, "B7"),
+            (1_048_575, 0, "A1048576"),
+        ];
+
+        for (row, col, cell) in tests {
+            assert_eq!(rowcol_to_cell(row, col), cell);
+        }
+    }
+
+    #[test]
+    fn test_rowcol_to_cell_abs() {
+        let tests = vec![
+            (0, 0, "$A$1"),
+            (6, 1, "$B$7"),
+            (1_048_575, 16_383, "$XFD$1048576"),
+        ];
+
+        for (row, col, cell) in tests {
+            assert_eq!(rowcol_to_cell_abs(row, col), cell);
+        }
+    }
+
+    #[test]
+    fn test_cell_to_rowcol() {
+        let tests = vec![
+            ("A1", 0, 0),
+            ("B1", 0, 1),
+            ("Z1", 0, 25),
+            ("AA1", 0, 26),
+            ("AZ1", 0, 51),
+            ("ZZ1", 0, 701),
+            ("AAA1", 0, 702),
+            ("XFD1048576", 1_048_575, 16_383),
+            ("$B$7", 6, 1),
+            ("$B7", 6, 1),
+            ("B$7", 6, 1),
+            ("b7", 6, 1),
+        ];
+
+        for (cell, row, col) in tests {
+            assert_eq!(cell_to_rowcol(cell), (row, col));
+        }
+    }
+
+    #[test]
+    fn test_rowcol_round_trip() {
+        for row in [0, 1, 99, 1_048_575] {
+            for col in 0..16_384 {
+                assert_eq!(cell_to_rowcol(&rowcol_to_cell(row, col)), (row, col));
+            }
+        }
+    }
+
+    #[test]
+    fn test_col_to_name() {
+        let tests = vec![
+            (0, "A"),
+            (25, "Z"),
+            (26, "AA"),
+            (701, "ZZ"),
+            (702, "AAA"),
+            (16_383, "XFD"),
+        ];
+
+        for (col, name) in tests {
+            assert_eq!(col_to_name(col), name);
+            assert_eq!(name_to_col(name), col);
+        }
+    }
+
+    #[test]
+    fn test_name_to_col_lowercase() {
+        assert_eq!(name_to_col("xfd"), 16_383);
+        assert_eq!(name_to_col("Ab"), 27);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_col_to_name_out_of_range() {
+        col_to_name(16_384);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rowcol_to_cell_row_out_of_range() {
+        rowcol_to_cell(1_048_576, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rowcol_to_cell_abs_row_overflow() {
+        rowcol_to_cell_abs(u32::MAX, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cell_to_rowcol_no_row() {
+        cell_to_rowcol("A");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cell_to_rowcol_row_zero() {
+        cell_to_rowcol("A0");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cell_to_rowcol_trailing_characters() {
+        cell_to_rowcol("A1B");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cell_to_rowcol_row_out_of_range() {
+        cell_to_rowcol("A1048577");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_name_to_col_out_of_range() {
+        name_to_col("XFE");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_name_to_col_too_long() {
+        name_to_col("AAAA");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_name_to_col_invalid_character() {
+        name_to_col("A1");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_name_to_col_empty() {
+        name_to_col("");
+    }
+
+    #[test]
+    fn test_formula_sheet_names() {
+        let tests = [
+            ("=A1+B2", vec![]),
+            ("=Sheet2!A1", vec!["Sheet2"]),
+            ("=SUM(Data!A1:A10)+Sheet1!$B$2", vec!["Data", "Sheet1"]),
+            ("='My Sheet'!A1", vec!["My Sheet"]),
+            ("='It''s'!A1", vec!["It's"]),
+            ("=Sheet1:Sheet3!A1", vec!["Sheet1", "Sheet3"]),
+            ("=SUM('Jan 1:Jan 3'!A1)", vec!["Jan 1", "Jan 3"]),
+            ("=données!A1", vec!["données"]),
+            ("=Sheet_1.x!A1", vec!["Sheet_1.x"]),
+            (r#"="Sheet2!A1""#, vec![]),
+            (r#"="say ""hi"""&Sheet2!A1"#, vec!["Sheet2"]),
+            ("=#REF!+1", vec![]),
+            ("=IF(ISERROR(#DIV/0!),Data!A1,0)", vec!["Data"]),
+            ("=[1]Sheet1!A1", vec![]),
+            ("='[Book.xlsx]Sheet1'!A1", vec![]),
+            ("=SUM(Table1[Sales])+Sheet2!A1", vec!["Sheet2"]),
+            ("Sheet2!$A$1:$A$10", vec!["Sheet2"]),
+        ];
+
+        for (formula, expected) in tests {
+            assert_eq!(formula_sheet_names(formula), expected, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_prefix_future_functions() {
+        let tests = [
+            ("=SUM(A1:A9)", "=SUM(A1:A9)"),
+            ("=xlookup(A1,B:B,C:C)", "=_xlfn.xlookup(A1,B:B,C:C)"),
+            ("=_xlfn.XLOOKUP(A1,B:B,C:C)", "=_xlfn.XLOOKUP(A1,B:B,C:C)"),
+            ("=STDEV.S(A1:A9)", "=_xlfn.STDEV.S(A1:A9)"),
+            ("=STDEV(A1:A9)", "=STDEV(A1:A9)"),
+            (
+                "=FILTER(A1:A9,B1:B9>0)",
+                "=_xlfn._xlws.FILTER(A1:A9,B1:B9>0)",
+            ),
+            (
+                "=LET(x,TEXTJOIN(\",\",TRUE,A1:A3),x)",
+                "=_xlfn.LET(_xlpm.x,_xlfn.TEXTJOIN(\",\",TRUE,A1:A3),_xlpm.x)",
+            ),
+            ("=LET(x,1,x+1)", "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)"),
+            (
+                "=LET(x, A1, y, x * 2, x + y)",
+                "=_xlfn.LET(_xlpm.x, A1, _xlpm.y, _xlpm.x * 2, _xlpm.x + _xlpm.y)",
+            ),
+            (
+                "=LET(f,LAMBDA(a,b,a*b),f(2,X1))",
+                "=_xlfn.LET(_xlpm.f,_xlfn.LAMBDA(_xlpm.a,_xlpm.b,_xlpm.a*_xlpm.b),_xlpm.f(2,X1))",
+            ),
+            (
+                "=MAP(A1:A3,LAMBDA(v,v+1))+v",
+                "=_xlfn.MAP(A1:A3,_xlfn.LAMBDA(_xlpm.v,_xlpm.v+1))+v",
+            ),
+            (
+                "=LET(x,{1,2},total,SUM(x),total)",
+                "=_xlfn.LET(_xlpm.x,{1,2},_xlpm.total,SUM(_xlpm.x),_xlpm.total)",
+            ),
+            (
+                "=LET(x,(1),y,x,\"x\"&y)",
+                "=_xlfn.LET(_xlpm.x,(1),_xlpm.y,_xlpm.x,\"x\"&_xlpm.y)",
+            ),
+            (
+                "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)",
+                "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)",
+            ),
+            ("=ENCODEURL(A1)", "=_xlfn.ENCODEURL(A1)"),
+            ("=WORKDAY.INTL(A1,5)", "=_xlfn.WORKDAY.INTL(A1,5)"),
+            (r#"="IFS(" & IFS(A1,1)"#, r#"="IFS(" & _xlfn.IFS(A1,1)"#),
+            (r#"="say ""IFS("""&A1"#, r#"="say ""IFS("""&A1"#),
+            ("='IFS(1)'!A1+XOR(A2)", "='IFS(1)'!A1+_xlfn.XOR(A2)"),
+            (
+                "=Sheet1!A1+MAXIFS(A:A,B:B,1)",
+                "=Sheet1!A1+_xlfn.MAXIFS(A:A,B:B,1)",
+            ),
+            ("=MYXOR(A1)", "=MYXOR(A1)"),
+        ];
+
+        for (formula, expected) in tests {
+            assert_eq!(prefix_future_functions(formula), expected, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_quote_sheet_name() {
+        let tests = vec![
+            ("Sheet1", "Sheet1"),
+            ("Sales_2022.Q1", "Sales_2022.Q1"),
+            ("Ventes", "Ventes"),
+            ("Données", "Données"),
+            ("Q1 Sales", "'Q1 Sales'"),
+            ("Sales-Q1", "'Sales-Q1'"),
+            ("2022", "'2022'"),
+            ("1Sales", "'1Sales'"),
+            ("A1", "'A1'"),
+            ("xfd1048576", "'xfd1048576'"),
+            ("R1C1", "'R1C1'"),
+            ("Bob's", "'Bob''s'"),
+            ("", "''"),
+        ];
+
+        for (name, expected) in tests {
+            assert_eq!(quote_sheet_name(name), expected);
+        }
+    }
+}
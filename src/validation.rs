@@ -0,0 +1,775 @@
+//! Validation of worksheet, styles and workbook parts against the element
+//! structure of the ECMA-376 SpreadsheetML schemas.
+//!
+//! Excel expects the child elements of most elements in the order of the
+//! `xsd:sequence` in the schema. When they aren't, it reports unreadable
+//! content and "repairs" the file, usually by dropping the part. The
+//! [`validate_part()`] function checks the order, the number of occurrences
+//! and the names of the elements in a part, and reports the path of the first
+//! offending element, so these mistakes are caught in tests rather than by
+//! users.
+//!
+//! ```
+//! use excel_xmlwriter::validation::validate_part;
+//!
+//! let xml = concat!(
+//!     r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+//!     r#"<sheetData/><pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/>"#,
+//!     r#"<mergeCells count="1"><mergeCell ref="A1:B1"/></mergeCells></worksheet>"#
+//! );
+//!
+//! let error = validate_part(xml.as_bytes()).unwrap_err();
+//! assert_eq!(
+//!     error.to_string(),
+//!     "<mergeCells> is out of order and must come before <pageMargins>, in worksheet/mergeCells"
+//! );
+//! ```
+//!
+//! The schemas are compiled into the crate as tables of the content models
+//! of the elements that the crate writes, and their parents. Attributes,
+//! text and the content of the elements of other namespaces, such as the
+//! `x14:` extensions in an `<extLst>`, aren't validated.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::error::XlsxError;
+
+// The maximum number of occurrences of an unbounded element.
+const UNBOUNDED: u32 = u32::MAX;
+
+// The content model of a complex type in the schema.
+enum Content {
+    // The child elements in the order of the sequence.
+    Sequence(&'static [Particle]),
+
+    // Between a minimum and a maximum number of the child elements, in any
+    // order.
+    Choice(&'static [Particle], u32, u32),
+}
+
+// A child element in a content model, with the minimum and maximum number
+// of occurrences and the content model of its type, if it is validated.
+struct Particle {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    content: Option<&'static Content>,
+}
+
+const fn element(
+    name: &'static str,
+    min: u32,
+    max: u32,
+    content: Option<&'static Content>,
+) -> Particle {
+    Particle {
+        name,
+        min,
+        max,
+        content,
+    }
+}
+
+// The root elements of the parts that can be validated.
+static ROOTS: [Particle; 3] = [
+    element("worksheet", 1, 1, Some(&CT_WORKSHEET)),
+    element("styleSheet", 1, 1, Some(&CT_STYLESHEET)),
+    element("workbook", 1, 1, Some(&CT_WORKBOOK)),
+];
+
+// -----------------------------------------------------------------------
+// Worksheet types.
+// -----------------------------------------------------------------------
+
+static CT_WORKSHEET: Content = Content::Sequence(&[
+    element("sheetPr", 0, 1, Some(&CT_SHEET_PR)),
+    element("dimension", 0, 1, None),
+    element("sheetViews", 0, 1, Some(&CT_SHEET_VIEWS)),
+    element("sheetFormatPr", 0, 1, None),
+    element("cols", 0, UNBOUNDED, Some(&CT_COLS)),
+    element("sheetData", 1, 1, Some(&CT_SHEET_DATA)),
+    element("sheetCalcPr", 0, 1, None),
+    element("sheetProtection", 0, 1, None),
+    element("protectedRanges", 0, 1, None),
+    element("scenarios", 0, 1, None),
+    element("autoFilter", 0, 1, Some(&CT_AUTO_FILTER)),
+    element("sortState", 0, 1, None),
+    element("dataConsolidate", 0, 1, None),
+    element("customSheetViews", 0, 1, None),
+    element("mergeCells", 0, 1, Some(&CT_MERGE_CELLS)),
+    element("phoneticPr", 0, 1, None),
+    element(
+        "conditionalFormatting",
+        0,
+        UNBOUNDED,
+        Some(&CT_CONDITIONAL_FORMATTING),
+    ),
+    element("dataValidations", 0, 1, Some(&CT_DATA_VALIDATIONS)),
+    element("hyperlinks", 0, 1, Some(&CT_HYPERLINKS)),
+    element("printOptions", 0, 1, None),
+    element("pageMargins", 0, 1, None),
+    element("pageSetup", 0, 1, None),
+    element("headerFooter", 0, 1, Some(&CT_HEADER_FOOTER)),
+    element("rowBreaks", 0, 1, Some(&CT_PAGE_BREAK)),
+    element("colBreaks", 0, 1, Some(&CT_PAGE_BREAK)),
+    element("customProperties", 0, 1, None),
+    element("cellWatches", 0, 1, None),
+    element("ignoredErrors", 0, 1, None),
+    element("smartTags", 0, 1, None),
+    element("drawing", 0, 1, None),
+    element("legacyDrawing", 0, 1, None),
+    element("legacyDrawingHF", 0, 1, None),
+    element("drawingHF", 0, 1, None),
+    element("picture", 0, 1, None),
+    element("oleObjects", 0, 1, None),
+    element("controls", 0, 1, None),
+    element("webPublishItems", 0, 1, None),
+    element("tableParts", 0, 1, Some(&CT_TABLE_PARTS)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_SHEET_PR: Content = Content::Sequence(&[
+    element("tabColor", 0, 1, None),
+    element("outlinePr", 0, 1, None),
+    element("pageSetUpPr", 0, 1, None),
+]);
+
+static CT_SHEET_VIEWS: Content = Content::Sequence(&[
+    element("sheetView", 1, UNBOUNDED, Some(&CT_SHEET_VIEW)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_SHEET_VIEW: Content = Content::Sequence(&[
+    element("pane", 0, 1, None),
+    element("selection", 0, 4, None),
+    element("pivotSelection", 0, 4, None),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_COLS: Content = Content::Sequence(&[element("col", 1, UNBOUNDED, None)]);
+
+static CT_SHEET_DATA: Content = Content::Sequence(&[element("row", 0, UNBOUNDED, Some(&CT_ROW))]);
+
+static CT_ROW: Content = Content::Sequence(&[
+    element("c", 0, UNBOUNDED, Some(&CT_CELL)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_CELL: Content = Content::Sequence(&[
+    element("f", 0, 1, None),
+    element("v", 0, 1, None),
+    element("is", 0, 1, Some(&CT_RST)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_RST: Content = Content::Sequence(&[
+    element("t", 0, 1, None),
+    element("r", 0, UNBOUNDED, Some(&CT_RELT)),
+    element("rPh", 0, UNBOUNDED, None),
+    element("phoneticPr", 0, 1, None),
+]);
+
+static CT_RELT: Content =
+    Content::Sequence(&[element("rPr", 0, 1, None), element("t", 1, 1, None)]);
+
+static CT_AUTO_FILTER: Content = Content::Sequence(&[
+    element("filterColumn", 0, UNBOUNDED, None),
+    element("sortState", 0, 1, None),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_MERGE_CELLS: Content = Content::Sequence(&[element("mergeCell", 1, UNBOUNDED, None)]);
+
+static CT_CONDITIONAL_FORMATTING: Content = Content::Sequence(&[
+    element("cfRule", 1, UNBOUNDED, Some(&CT_CF_RULE)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_CF_RULE: Content = Content::Sequence(&[
+    element("formula", 0, 3, None),
+    element("colorScale", 0, 1, Some(&CT_COLOR_SCALE)),
+    element("dataBar", 0, 1, Some(&CT_DATA_BAR)),
+    element("iconSet", 0, 1, Some(&CT_ICON_SET)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_COLOR_SCALE: Content = Content::Sequence(&[
+    element("cfvo", 2, UNBOUNDED, None),
+    element("color", 2, UNBOUNDED, None),
+]);
+
+static CT_DATA_BAR: Content =
+    Content::Sequence(&[element("cfvo", 2, 2, None), element("color", 1, 1, None)]);
+
+static CT_ICON_SET: Content = Content::Sequence(&[element("cfvo", 2, UNBOUNDED, None)]);
+
+static CT_DATA_VALIDATIONS: Content = Content::Sequence(&[element(
+    "dataValidation",
+    1,
+    UNBOUNDED,
+    Some(&CT_DATA_VALIDATION),
+)]);
+
+static CT_DATA_VALIDATION: Content = Content::Sequence(&[
+    element("formula1", 0, 1, None),
+    element("formula2", 0, 1, None),
+]);
+
+static CT_HYPERLINKS: Content = Content::Sequence(&[element("hyperlink", 1, UNBOUNDED, None)]);
+
+static CT_HEADER_FOOTER: Content = Content::Sequence(&[
+    element("oddHeader", 0, 1, None),
+    element("oddFooter", 0, 1, None),
+    element("evenHeader", 0, 1, None),
+    element("evenFooter", 0, 1, None),
+    element("firstHeader", 0, 1, None),
+    element("firstFooter", 0, 1, None),
+]);
+
+static CT_PAGE_BREAK: Content = Content::Sequence(&[element("brk", 0, UNBOUNDED, None)]);
+
+static CT_TABLE_PARTS: Content = Content::Sequence(&[element("tablePart", 0, UNBOUNDED, None)]);
+
+// -----------------------------------------------------------------------
+// Styles types.
+// -----------------------------------------------------------------------
+
+static CT_STYLESHEET: Content = Content::Sequence(&[
+    element("numFmts", 0, 1, Some(&CT_NUM_FMTS)),
+    element("fonts", 0, 1, Some(&CT_FONTS)),
+    element("fills", 0, 1, Some(&CT_FILLS)),
+    element("borders", 0, 1, Some(&CT_BORDERS)),
+    element("cellStyleXfs", 0, 1, Some(&CT_CELL_XFS)),
+    element("cellXfs", 0, 1, Some(&CT_CELL_XFS)),
+    element("cellStyles", 0, 1, Some(&CT_CELL_STYLES)),
+    element("dxfs", 0, 1, Some(&CT_DXFS)),
+    element("tableStyles", 0, 1, Some(&CT_TABLE_STYLES)),
+    element("colors", 0, 1, Some(&CT_COLORS)),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_NUM_FMTS: Content = Content::Sequence(&[element("numFmt", 0, UNBOUNDED, None)]);
+
+static CT_FONTS: Content = Content::Sequence(&[element("font", 0, UNBOUNDED, Some(&CT_FONT))]);
+
+static CT_FONT: Content = Content::Choice(
+    &[
+        element("b", 0, 1, None),
+        element("i", 0, 1, None),
+        element("strike", 0, 1, None),
+        element("condense", 0, 1, None),
+        element("extend", 0, 1, None),
+        element("outline", 0, 1, None),
+        element("shadow", 0, 1, None),
+        element("u", 0, 1, None),
+        element("vertAlign", 0, 1, None),
+        element("sz", 0, 1, None),
+        element("color", 0, 1, None),
+        element("name", 0, 1, None),
+        element("family", 0, 1, None),
+        element("charset", 0, 1, None),
+        element("scheme", 0, 1, None),
+    ],
+    0,
+    UNBOUNDED,
+);
+
+static CT_FILLS: Content = Content::Sequence(&[element("fill", 0, UNBOUNDED, Some(&CT_FILL))]);
+
+static CT_FILL: Content = Content::Choice(
+    &[
+        element("patternFill", 0, 1, Some(&CT_PATTERN_FILL)),
+        element("gradientFill", 0, 1, Some(&CT_GRADIENT_FILL)),
+    ],
+    0,
+    1,
+);
+
+static CT_PATTERN_FILL: Content = Content::Sequence(&[
+    element("fgColor", 0, 1, None),
+    element("bgColor", 0, 1, None),
+]);
+
+static CT_GRADIENT_FILL: Content = Content::Sequence(&[element("stop", 0, UNBOUNDED, None)]);
+
+static CT_BORDERS: Content =
+    Content::Sequence(&[element("border", 0, UNBOUNDED, Some(&CT_BORDER))]);
+
+static CT_BORDER: Content = Content::Sequence(&[
+    element("start", 0, 1, Some(&CT_BORDER_PR)),
+    element("end", 0, 1, Some(&CT_BORDER_PR)),
+    element("left", 0, 1, Some(&CT_BORDER_PR)),
+    element("right", 0, 1, Some(&CT_BORDER_PR)),
+    element("top", 0, 1, Some(&CT_BORDER_PR)),
+    element("bottom", 0, 1, Some(&CT_BORDER_PR)),
+    element("diagonal", 0, 1, Some(&CT_BORDER_PR)),
+    element("vertical", 0, 1, Some(&CT_BORDER_PR)),
+    element("horizontal", 0, 1, Some(&CT_BORDER_PR)),
+]);
+
+static CT_BORDER_PR: Content = Content::Sequence(&[element("color", 0, 1, None)]);
+
+static CT_CELL_XFS: Content = Content::Sequence(&[element("xf", 1, UNBOUNDED, Some(&CT_XF))]);
+
+static CT_XF: Content = Content::Sequence(&[
+    element("alignment", 0, 1, None),
+    element("protection", 0, 1, None),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_CELL_STYLES: Content = Content::Sequence(&[element("cellStyle", 1, UNBOUNDED, None)]);
+
+static CT_DXFS: Content = Content::Sequence(&[element("dxf", 0, UNBOUNDED, Some(&CT_DXF))]);
+
+static CT_DXF: Content = Content::Sequence(&[
+    element("font", 0, 1, Some(&CT_FONT)),
+    element("numFmt", 0, 1, None),
+    element("fill", 0, 1, Some(&CT_FILL)),
+    element("alignment", 0, 1, None),
+    element("border", 0, 1, Some(&CT_BORDER)),
+    element("protection", 0, 1, None),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_TABLE_STYLES: Content = Content::Sequence(&[element("tableStyle", 0, UNBOUNDED, None)]);
+
+static CT_COLORS: Content = Content::Sequence(&[
+    element("indexedColors", 0, 1, None),
+    element("mruColors", 0, 1, None),
+]);
+
+// -----------------------------------------------------------------------
+// Workbook types.
+// -----------------------------------------------------------------------
+
+static CT_WORKBOOK: Content = Content::Sequence(&[
+    element("fileVersion", 0, 1, None),
+    element("fileSharing", 0, 1, None),
+    element("workbookPr", 0, 1, None),
+    element("workbookProtection", 0, 1, None),
+    element("bookViews", 0, 1, Some(&CT_BOOK_VIEWS)),
+    element("sheets", 1, 1, Some(&CT_SHEETS)),
+    element("functionGroups", 0, 1, None),
+    element("externalReferences", 0, 1, None),
+    element("definedNames", 0, 1, Some(&CT_DEFINED_NAMES)),
+    element("calcPr", 0, 1, None),
+    element("oleSize", 0, 1, None),
+    element("customWorkbookViews", 0, 1, None),
+    element("pivotCaches", 0, 1, None),
+    element("smartTagPr", 0, 1, None),
+    element("smartTagTypes", 0, 1, None),
+    element("webPublishing", 0, 1, None),
+    element("fileRecoveryPr", 0, UNBOUNDED, None),
+    element("webPublishObjects", 0, 1, None),
+    element("extLst", 0, 1, None),
+]);
+
+static CT_BOOK_VIEWS: Content = Content::Sequence(&[element("workbookView", 1, UNBOUNDED, None)]);
+
+static CT_SHEETS: Content = Content::Sequence(&[element("sheet", 1, UNBOUNDED, None)]);
+
+static CT_DEFINED_NAMES: Content = Content::Sequence(&[element("definedName", 0, UNBOUNDED, None)]);
+
+/// Validate a worksheet, styles or workbook part, which is chosen by the
+/// root element, against the element structure of the schema.
+///
+/// # Errors
+///
+/// [`XlsxError::SchemaViolation`] with the path of the first element that
+/// is out of order, repeated too often, missing or not in the schema, or if
+/// the part isn't well-formed XML or isn't one of the validated parts.
+pub fn validate_part(xml: &[u8]) -> Result<(), XlsxError> {
+    let mut reader = Reader::from_reader(xml);
+    let mut validator = Validator::default();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|error| validator.violation(error.to_string()))?;
+
+        match event {
+            Event::Start(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                validator.start(&name)?;
+            }
+            Event::Empty(start) => {
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                validator.start(&name)?;
+                validator.end()?;
+            }
+            Event::End(_) => validator.end()?,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if validator.root_seen {
+        Ok(())
+    } else {
+        Err(validator.violation("the part doesn't have a root element".to_string()))
+    }
+}
+
+// An open element and the state of the validation of its children.
+struct Frame {
+    name: String,
+    position: usize,
+    content: Option<&'static Content>,
+    particle: usize,
+    count: u32,
+    last_child: Option<(String, usize)>,
+}
+
+#[derive(Default)]
+struct Validator {
+    frames: Vec<Frame>,
+    root_seen: bool,
+}
+
+impl Validator {
+    // Validate a start tag against the content model of its parent.
+    fn start(&mut self, name: &str) -> Result<(), XlsxError> {
+        let content = match self.frames.last_mut() {
+            None if self.root_seen => Err(format!("<{name}> is after the root element")),
+            None => {
+                self.root_seen = true;
+                ROOTS
+                    .iter()
+                    .find(|root| root.name == name)
+                    .map(|root| root.content)
+                    .ok_or_else(|| {
+                        format!("<{name}> isn't the root of a worksheet, styles or workbook part")
+                    })
+            }
+            Some(parent) => parent.next_child(name),
+        };
+
+        let position = match self.frames.last_mut() {
+            Some(parent) => {
+                let position = match &parent.last_child {
+                    Some((last, position)) if last == name => position + 1,
+                    _ => 1,
+                };
+                parent.last_child = Some((name.to_string(), position));
+                position
+            }
+            None => 1,
+        };
+
+        self.frames.push(Frame {
+            name: name.to_string(),
+            position,
+            content: None,
+            particle: 0,
+            count: 0,
+            last_child: None,
+        });
+
+        match content {
+            Ok(content) => {
+                self.frames.last_mut().unwrap().content = content;
+                Ok(())
+            }
+            Err(reason) => Err(self.violation(reason)),
+        }
+    }
+
+    // Check that the element that is closed has all of its required
+    // children.
+    fn end(&mut self) -> Result<(), XlsxError> {
+        let missing = self.frames.last().and_then(Frame::missing_child);
+
+        match missing {
+            Some(reason) => Err(self.violation(reason)),
+            None => {
+                self.frames.pop();
+                Ok(())
+            }
+        }
+    }
+
+    // A schema violation at the current path.
+    fn violation(&self, reason: String) -> XlsxError {
+        let path = self
+            .frames
+            .iter()
+            .map(|frame| {
+                if frame.position > 1 {
+                    format!("{}[{}]", frame.name, frame.position)
+                } else {
+                    frame.name.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        XlsxError::SchemaViolation {
+            path: if path.is_empty() {
+                "the top level".to_string()
+            } else {
+                path
+            },
+            reason,
+        }
+    }
+}
+
+impl Frame {
+    // Match a child element against the content model and return the
+    // content model of the child, if it is validated. The elements of other
+    // namespaces are extensions, which aren't validated.
+    fn next_child(&mut self, name: &str) -> Result<Option<&'static Content>, String> {
+        let Some(content) = self.content else {
+            return Ok(None);
+        };
+
+        if name.contains(':') {
+            return Ok(None);
+        }
+
+        match content {
+            Content::Sequence(particles) => {
+                let Some(offset) = particles[self.particle..]
+                    .iter()
+                    .position(|particle| particle.name == name)
+                else {
+                    let earlier = particles[..self.particle]
+                        .iter()
+                        .any(|particle| particle.name == name);
+
+                    return Err(match (&self.last_child, earlier) {
+                        (Some((last, _)), true) => {
+                            format!("<{name}> is out of order and must come before <{last}>")
+                        }
+                        _ => format!("<{name}> isn't a valid child of <{}>", self.name),
+                    });
+                };
+
+                if offset == 0 {
+                    let particle = &particles[self.particle];
+                    if self.count >= particle.max {
+                        return Err(too_many(particle));
+                    }
+                    self.count += 1;
+                    return Ok(particle.content);
+                }
+
+                if let Some(reason) = self.missing_before(self.particle + offset) {
+                    return Err(format!("{reason} before <{name}>"));
+                }
+
+                self.particle += offset;
+                self.count = 1;
+                Ok(particles[self.particle].content)
+            }
+            Content::Choice(particles, _, max) => {
+                let Some(particle) = particles.iter().find(|particle| particle.name == name) else {
+                    return Err(format!("<{name}> isn't a valid child of <{}>", self.name));
+                };
+
+                if self.count >= *max {
+                    return Err(format!(
+                        "<{}> can only have {max} child element{}",
+                        self.name,
+                        if *max == 1 { "" } else { "s" }
+                    ));
+                }
+                self.count += 1;
+                Ok(particle.content)
+            }
+        }
+    }
+
+    // The reason that the element is incomplete, if a required child is
+    // missing.
+    fn missing_child(&self) -> Option<String> {
+        match self.content? {
+            Content::Sequence(particles) => self
+                .missing_before(particles.len())
+                .map(|reason| format!("{reason} in <{}>", self.name)),
+            Content::Choice(_, min, _) if self.count < *min => {
+                Some(format!("<{}> doesn't have a child element", self.name))
+            }
+            Content::Choice(..) => None,
+        }
+    }
+
+    // The first required element of a sequence that is missing before the
+    // particle at an index.
+    fn missing_before(&self, index: usize) -> Option<String> {
+        let Some(Content::Sequence(particles)) = self.content else {
+            return None;
+        };
+
+        particles[self.particle..index]
+            .iter()
+            .enumerate()
+            .find(|(offset, particle)| {
+                let count = if *offset == 0 { self.count } else { 0 };
+                count < particle.min
+            })
+            .map(|(_, particle)| format!("the required <{}> is missing", particle.name))
+    }
+}
+
+// The error for an element that occurs more often than the schema allows.
+fn too_many(particle: &Particle) -> String {
+    match particle.max {
+        1 => format!("<{}> can only occur once", particle.name),
+        max => format!("<{}> can only occur {max} times", particle.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::validate_part;
+    use crate::error::XlsxError;
+    use crate::shared_strings::SharedStringTable;
+    use crate::styles::{Color, Format, FormatBorder, FormatPattern, Styles};
+    use crate::workbook::Workbook;
+    use crate::worksheet::Worksheet;
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    // Validate a part and return the path and reason of the violation.
+    fn violation(xml: &str) -> (String, String) {
+        match validate_part(xml.as_bytes()) {
+            Err(XlsxError::SchemaViolation { path, reason }) => (path, reason),
+            result => panic!("expected a schema violation, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_generated_parts() {
+        let mut styles = Styles::new();
+        let mut string_table = SharedStringTable::new();
+        let mut worksheet = Worksheet::new();
+
+        let format = Format::new()
+            .set_bold()
+            .set_font_color(Color::Rgb(0xFF0000))
+            .set_background_color(Color::Rgb(0xFFFF00))
+            .set_pattern(FormatPattern::Solid)
+            .set_border(FormatBorder::Thin);
+
+        worksheet.set_zoom(150);
+        worksheet.write_string_with_format(0, 0, "Total", &format);
+        worksheet.write_number(0, 1, 1234.5);
+        worksheet.write_formula(1, 1, "=B1*2");
+        worksheet
+            .merge_range(3, 0, 3, 2, "Merged", &format)
+            .unwrap();
+        worksheet
+            .write_url(4, 0, "https://www.rust-lang.org")
+            .unwrap();
+
+        let mut writer = XMLWriter::new(vec![]);
+        worksheet.assemble_xml_file(&mut writer, &mut styles, &mut string_table);
+        validate_part(&writer.into_inner()).unwrap();
+
+        let mut writer = XMLWriter::new(vec![]);
+        styles.assemble_xml_file(&mut writer);
+        validate_part(&writer.into_inner()).unwrap();
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.define_name("Sales", "=Sheet1!$A$1:$B$2").unwrap();
+
+        let mut writer = XMLWriter::new(vec![]);
+        workbook.assemble_xml_file(&mut writer);
+        validate_part(&writer.into_inner()).unwrap();
+    }
+
+    #[test]
+    fn test_validate_out_of_order() {
+        let got = violation(concat!(
+            "<worksheet><sheetData><row r=\"1\"><c r=\"A1\"><v>1</v><f>A2</f></c></row>",
+            "</sheetData></worksheet>"
+        ));
+
+        assert_eq!(
+            got,
+            (
+                "worksheet/sheetData/row/c/f".to_string(),
+                "<f> is out of order and must come before <v>".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_repeated() {
+        let got = violation("<worksheet><sheetData/><sheetData/></worksheet>");
+
+        assert_eq!(
+            got,
+            (
+                "worksheet/sheetData[2]".to_string(),
+                "<sheetData> can only occur once".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_missing() {
+        let got = violation("<worksheet><dimension ref=\"A1\"/><pageMargins/></worksheet>");
+
+        assert_eq!(
+            got,
+            (
+                "worksheet/pageMargins".to_string(),
+                "the required <sheetData> is missing before <pageMargins>".to_string()
+            )
+        );
+
+        let got = violation("<workbook><bookViews><workbookView/></bookViews></workbook>");
+
+        assert_eq!(
+            got,
+            (
+                "workbook".to_string(),
+                "the required <sheets> is missing in <workbook>".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_and_extensions() {
+        let got = violation("<styleSheet><fonts><font><bold/></font></fonts></styleSheet>");
+
+        assert_eq!(
+            got,
+            (
+                "styleSheet/fonts/font/bold".to_string(),
+                "<bold> isn't a valid child of <font>".to_string()
+            )
+        );
+
+        validate_part(
+            concat!(
+                "<styleSheet><fonts><font><sz val=\"11\"/><b/></font></fonts>",
+                "<extLst><ext><x14:slicerStyles/></ext></extLst>",
+                "<mc:AlternateContent/></styleSheet>"
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_validate_root() {
+        let got = violation("<chartSpace/>");
+
+        assert_eq!(
+            got,
+            (
+                "chartSpace".to_string(),
+                "<chartSpace> isn't the root of a worksheet, styles or workbook part".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,682 @@
+//! The writer for the `xl/drawings/vmlDrawingN.vml` parts.
+//!
+//! Excel still uses the legacy VML format for the boxes of cell notes, for
+//! form control buttons and for the shapes of embedded objects. The VML
+//! drawing of a worksheet is created with
+//! [`Worksheet::vml_drawing()`](crate::worksheet::Worksheet::vml_drawing),
+//! see the [`note`](crate::note), [`button`](crate::button) and
+//! [`ole_object`](crate::ole_object) modules.
+//!
+//! The boxes are positioned from the default column width of 64 pixels and
+//! row height of 20 pixels, so in worksheets with other column widths or row
+//! heights they are offset from the cells that they would have in Excel.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::button::Button;
+use crate::constants::{VML_DRAWING_CONTENT_TYPE, VML_EXCEL_NS, VML_NS, VML_OFFICE_NS};
+use crate::image::Image;
+use crate::note::Note;
+use crate::part::XlsxPart;
+use crate::relationship::Relationships;
+use crate::utility::{COL_MAX, ROW_MAX};
+use crate::XMLWriter;
+
+// The default column width and row height, in pixels.
+const COL_WIDTH: u32 = 64;
+const ROW_HEIGHT: u32 = 20;
+
+// The position of a shape, in pixels, with the anchor cells and the offsets
+// within them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Position {
+    pub(crate) start_col: u32,
+    pub(crate) x1: u32,
+    pub(crate) start_row: u32,
+    pub(crate) y1: u32,
+    pub(crate) end_col: u32,
+    pub(crate) x2: u32,
+    pub(crate) end_row: u32,
+    pub(crate) y2: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Position {
+    // Position a shape from its top left cell and offset, and its size.
+    pub(crate) fn new(
+        start_row: u32,
+        start_col: u32,
+        y1: u32,
+        x1: u32,
+        width: u32,
+        height: u32,
+    ) -> Position {
+        let (end_col, x2) = (
+            start_col + (x1 + width) / COL_WIDTH,
+            (x1 + width) % COL_WIDTH,
+        );
+        let (end_row, y2) = (
+            start_row + (y1 + height) / ROW_HEIGHT,
+            (y1 + height) % ROW_HEIGHT,
+        );
+
+        Position {
+            start_col,
+            x1,
+            start_row,
+            y1,
+            end_col,
+            x2,
+            end_row,
+            y2,
+            width,
+            height,
+        }
+    }
+
+    // The <x:Anchor> value, like `2, 15, 0, 10, 4, 15, 4, 4`.
+    fn anchor(&self) -> String {
+        format!(
+            "{}, {}, {}, {}, {}, {}, {}, {}",
+            self.start_col,
+            self.x1,
+            self.start_row,
+            self.y1,
+            self.end_col,
+            self.x2,
+            self.end_row,
+            self.y2
+        )
+    }
+
+    // The absolute position and size in points, for the style attribute.
+    fn style(&self, z_index: usize) -> String {
+        let left = (self.start_col * COL_WIDTH + self.x1) as f64 * 0.75;
+        let top = (self.start_row * ROW_HEIGHT + self.y1) as f64 * 0.75;
+        let width = self.width as f64 * 0.75;
+        let height = self.height as f64 * 0.75;
+
+        format!(
+            "position:absolute;margin-left:{left}pt;margin-top:{top}pt;\
+             width:{width}pt;height:{height}pt;z-index:{z_index}"
+        )
+    }
+}
+
+// The box of a note.
+#[derive(Clone, Debug, PartialEq)]
+struct NoteShape {
+    row: u32,
+    col: u16,
+    visible: bool,
+    position: Position,
+}
+
+// A form control button.
+#[derive(Clone, Debug, PartialEq)]
+struct ButtonShape {
+    caption: String,
+    macro_reference: Option<String>,
+    position: Position,
+}
+
+// The shape of an embedded object, with the target of the relationship to
+// its preview image.
+#[derive(Clone, Debug, PartialEq)]
+struct ObjectShape {
+    target: String,
+    position: Position,
+}
+
+// The id of a shape, like 1025, from the number of the drawing and the index
+// of the shape. The ids are in a block of 1024 for each drawing.
+pub(crate) fn shape_id(number: u32, index: usize) -> usize {
+    1024 * number as usize + 1 + index
+}
+
+/// The `xl/drawings/vmlDrawingN.vml` part with the shapes of a worksheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VmlDrawing {
+    number: u32,
+    part_name: String,
+    buttons: Vec<ButtonShape>,
+    notes: Vec<NoteShape>,
+    objects: Vec<ObjectShape>,
+}
+
+impl VmlDrawing {
+    // Create an empty drawing with the number of its part.
+    pub(crate) fn new(number: u32) -> VmlDrawing {
+        VmlDrawing {
+            number,
+            part_name: format!("xl/drawings/vmlDrawing{number}.vml"),
+            buttons: vec![],
+            notes: vec![],
+            objects: vec![],
+        }
+    }
+
+    // Add the shape of an embedded object at the top left corner of a cell,
+    // at the size of its preview image, with the part name of the image. The
+    // objects are written after the buttons and notes, so they have the last
+    // shape ids.
+    pub(crate) fn add_object(
+        &mut self,
+        row: u32,
+        col: u16,
+        preview: &Image,
+        media_part_name: &str,
+    ) {
+        let (width, height) = preview.size();
+
+        self.objects.push(ObjectShape {
+            target: media_part_name.replacen("xl/", "../", 1),
+            position: Position::new(row, col as u32, 0, 0, width, height),
+        });
+    }
+
+    /// The relationships of the drawing to the preview images of the
+    /// embedded objects, for the `xl/drawings/_rels/vmlDrawingN.vml.rels`
+    /// part. The part is only needed if the relationships aren't empty.
+    pub fn relationships(&self) -> Relationships {
+        let mut relationships = Relationships::new();
+
+        for object in &self.objects {
+            relationships.add_document_relationship("image", &object.target);
+        }
+
+        relationships
+    }
+
+    // Add a button at the top left corner of a cell. The buttons are written
+    // before the notes, so they have the first shape ids.
+    pub(crate) fn add_button(&mut self, row: u32, col: u16, button: &Button) {
+        let (width, height) = button.size();
+
+        self.buttons.push(ButtonShape {
+            caption: button.caption().to_string(),
+            macro_reference: button.macro_reference(),
+            position: Position::new(row, col as u32, 0, 0, width, height),
+        });
+    }
+
+    // Add the box of a note. Like Excel, the box is placed to the right of
+    // the cell and a row above it, or shifted left and up for cells near the
+    // last row and column.
+    pub(crate) fn add_note(&mut self, row: u32, col: u16, note: &Note) {
+        let (start_row, y1) = match row {
+            0 => (0, 2),
+            _ if row == ROW_MAX - 3 => (ROW_MAX - 7, 16),
+            _ if row == ROW_MAX - 2 => (ROW_MAX - 6, 16),
+            _ if row == ROW_MAX - 1 => (ROW_MAX - 5, 14),
+            _ => (row - 1, 10),
+        };
+        let (start_col, x1) = match COL_MAX - col {
+            3 => (COL_MAX - 6, 49),
+            2 => (COL_MAX - 5, 49),
+            1 => (COL_MAX - 4, 49),
+            _ => (col + 1, 15),
+        };
+        let (width, height) = note.size();
+
+        self.notes.push(NoteShape {
+            row,
+            col,
+            visible: note.is_visible(),
+            position: Position::new(start_row, start_col as u32, y1, x1, width, height),
+        });
+    }
+
+    /// Write the `xl/drawings/vmlDrawingN.vml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        let attributes = vec![
+            ("xmlns:v", VML_NS),
+            ("xmlns:o", VML_OFFICE_NS),
+            ("xmlns:x", VML_EXCEL_NS),
+        ];
+        writer.xml_start_tag("xml", &attributes);
+
+        let data = self.number.to_string();
+        writer.xml_start_tag("o:shapelayout", &vec![("v:ext", "edit")]);
+        writer.xml_empty_tag("o:idmap", &vec![("v:ext", "edit"), ("data", data.as_str())]);
+        writer.xml_end_tag("o:shapelayout");
+
+        if !self.buttons.is_empty() {
+            write_button_shapetype(writer);
+        }
+
+        for (index, button) in self.buttons.iter().enumerate() {
+            write_button_shape(writer, button, shape_id(self.number, index), index + 1);
+        }
+
+        if !self.notes.is_empty() {
+            write_note_shapetype(writer);
+        }
+
+        for (index, note) in self.notes.iter().enumerate() {
+            let index = self.buttons.len() + index;
+            let id = format!("_x0000_s{}", shape_id(self.number, index));
+            let visibility = if note.visible { "visible" } else { "hidden" };
+            let style = format!("{};visibility:{visibility}", note.position.style(index + 1));
+
+            let attributes = vec![
+                ("id", id.as_str()),
+                ("type", "#_x0000_t202"),
+                ("style", style.as_str()),
+                ("fillcolor", "#ffffe1"),
+                ("o:insetmode", "auto"),
+            ];
+            writer.xml_start_tag("v:shape", &attributes);
+
+            writer.xml_empty_tag("v:fill", &vec![("color2", "#ffffe1")]);
+            let attributes = vec![("on", "t"), ("color", "black"), ("obscured", "t")];
+            writer.xml_empty_tag("v:shadow", &attributes);
+            writer.xml_empty_tag("v:path", &vec![("o:connecttype", "none")]);
+
+            writer.xml_start_tag("v:textbox", &vec![("style", "mso-direction-alt:auto")]);
+            writer.xml_data_element("div", "", &vec![("style", "text-align:left")]);
+            writer.xml_end_tag("v:textbox");
+
+            let row = note.row.to_string();
+            let col = note.col.to_string();
+            writer.xml_start_tag("x:ClientData", &vec![("ObjectType", "Note")]);
+            writer.xml_empty_tag("x:MoveWithCells", &vec![]);
+            writer.xml_empty_tag("x:SizeWithCells", &vec![]);
+            writer.xml_data_element("x:Anchor", &note.position.anchor(), &vec![]);
+            writer.xml_data_element("x:AutoFill", "False", &vec![]);
+            writer.xml_data_element("x:Row", &row, &vec![]);
+            writer.xml_data_element("x:Column", &col, &vec![]);
+            if note.visible {
+                writer.xml_empty_tag("x:Visible", &vec![]);
+            }
+            writer.xml_end_tag("x:ClientData");
+
+            writer.xml_end_tag("v:shape");
+        }
+
+        if !self.objects.is_empty() {
+            write_picture_shapetype(writer);
+        }
+
+        let first_object = self.buttons.len() + self.notes.len();
+        for (index, object) in self.objects.iter().enumerate() {
+            let rel_id = format!("rId{}", index + 1);
+            let index = first_object + index;
+            write_object_shape(
+                writer,
+                object,
+                shape_id(self.number, index),
+                index + 1,
+                &rel_id,
+            );
+        }
+
+        writer.xml_end_tag("xml");
+    }
+}
+
+impl XlsxPart for VmlDrawing {
+    fn part_name(&self) -> &str {
+        &self.part_name
+    }
+
+    fn content_type(&self) -> &str {
+        VML_DRAWING_CONTENT_TYPE
+    }
+
+    fn write(&self, writer: &mut XMLWriter<&mut dyn Write>) {
+        self.assemble_xml_file(writer);
+    }
+}
+
+// Write the <v:shapetype> element for the text boxes of notes.
+fn write_note_shapetype(writer: &mut XMLWriter<impl Write>) {
+    let attributes = vec![
+        ("id", "_x0000_t202"),
+        ("coordsize", "21600,21600"),
+        ("o:spt", "202"),
+        ("path", "m,l,21600r21600,l21600,xe"),
+    ];
+    writer.xml_start_tag("v:shapetype", &attributes);
+    writer.xml_empty_tag("v:stroke", &vec![("joinstyle", "miter")]);
+    let attributes = vec![("gradientshapeok", "t"), ("o:connecttype", "rect")];
+    writer.xml_empty_tag("v:path", &attributes);
+    writer.xml_end_tag("v:shapetype");
+}
+
+// Write the <v:shapetype> element for form control buttons.
+fn write_button_shapetype(writer: &mut XMLWriter<impl Write>) {
+    let attributes = vec![
+        ("id", "_x0000_t201"),
+        ("coordsize", "21600,21600"),
+        ("o:spt", "201"),
+        ("path", "m,l,21600r21600,l21600,xe"),
+    ];
+    writer.xml_start_tag("v:shapetype", &attributes);
+    writer.xml_empty_tag("v:stroke", &vec![("joinstyle", "miter")]);
+    let attributes = vec![
+        ("shadowok", "f"),
+        ("o:extrusionok", "f"),
+        ("strokeok", "f"),
+        ("fillok", "f"),
+        ("o:connecttype", "rect"),
+    ];
+    writer.xml_empty_tag("v:path", &attributes);
+    let attributes = vec![("v:ext", "edit"), ("shapetype", "t")];
+    writer.xml_empty_tag("o:lock", &attributes);
+    writer.xml_end_tag("v:shapetype");
+}
+
+// Write the <v:shape> element of a button, with its caption and macro.
+fn write_button_shape(
+    writer: &mut XMLWriter<impl Write>,
+    button: &ButtonShape,
+    shape_id: usize,
+    z_index: usize,
+) {
+    let id = format!("_x0000_s{shape_id}");
+    let style = format!("{};mso-wrap-style:tight", button.position.style(z_index));
+
+    let attributes = vec![
+        ("id", id.as_str()),
+        ("type", "#_x0000_t201"),
+        ("style", style.as_str()),
+        ("o:button", "t"),
+        ("fillcolor", "buttonFace [67]"),
+        ("strokecolor", "windowText [64]"),
+        ("o:insetmode", "auto"),
+    ];
+    writer.xml_start_tag("v:shape", &attributes);
+
+    let attributes = vec![("color2", "buttonFace [67]"), ("o:detectmouseclick", "t")];
+    writer.xml_empty_tag("v:fill", &attributes);
+    writer.xml_empty_tag("o:lock", &vec![("v:ext", "edit"), ("rotation", "t")]);
+
+    let attributes = vec![("style", "mso-direction-alt:auto"), ("o:singleclick", "f")];
+    writer.xml_start_tag("v:textbox", &attributes);
+    writer.xml_start_tag("div", &vec![("style", "text-align:center")]);
+    let attributes = vec![("face", "Calibri"), ("size", "220"), ("color", "#000000")];
+    writer.xml_data_element("font", &button.caption, &attributes);
+    writer.xml_end_tag("div");
+    writer.xml_end_tag("v:textbox");
+
+    writer.xml_start_tag("x:ClientData", &vec![("ObjectType", "Button")]);
+    writer.xml_data_element("x:Anchor", &button.position.anchor(), &vec![]);
+    writer.xml_data_element("x:PrintObject", "False", &vec![]);
+    writer.xml_data_element("x:AutoFill", "False", &vec![]);
+    if let Some(macro_reference) = &button.macro_reference {
+        writer.xml_data_element("x:FmlaMacro", macro_reference, &vec![]);
+    }
+    writer.xml_data_element("x:TextHAlign", "Center", &vec![]);
+    writer.xml_data_element("x:TextVAlign", "Center", &vec![]);
+    writer.xml_end_tag("x:ClientData");
+
+    writer.xml_end_tag("v:shape");
+}
+
+// Write the <v:shapetype> element for pictures, which is used for the
+// preview images of embedded objects.
+fn write_picture_shapetype(writer: &mut XMLWriter<impl Write>) {
+    let attributes = vec![
+        ("id", "_x0000_t75"),
+        ("coordsize", "21600,21600"),
+        ("o:spt", "75"),
+        ("o:preferrelative", "t"),
+        ("path", "m@4@5l@4@11@9@11@9@5xe"),
+        ("filled", "f"),
+        ("stroked", "f"),
+    ];
+    writer.xml_start_tag("v:shapetype", &attributes);
+    writer.xml_empty_tag("v:stroke", &vec![("joinstyle", "miter")]);
+
+    let equations = [
+        "if lineDrawn pixelLineWidth 0",
+        "sum @0 1 0",
+        "sum 0 0 @1",
+        "prod @2 1 2",
+        "prod @3 21600 pixelWidth",
+        "prod @3 21600 pixelHeight",
+        "sum @0 0 1",
+        "prod @6 1 2",
+        "prod @7 21600 pixelWidth",
+        "sum @8 21600 0",
+        "prod @7 21600 pixelHeight",
+        "sum @10 21600 0",
+    ];
+    writer.xml_start_tag_only("v:formulas");
+    for equation in equations {
+        writer.xml_empty_tag("v:f", &vec![("eqn", equation)]);
+    }
+    writer.xml_end_tag("v:formulas");
+
+    let attributes = vec![
+        ("o:extrusionok", "f"),
+        ("gradientshapeok", "t"),
+        ("o:connecttype", "rect"),
+    ];
+    writer.xml_empty_tag("v:path", &attributes);
+    writer.xml_empty_tag("o:lock", &vec![("v:ext", "edit"), ("aspectratio", "t")]);
+    writer.xml_end_tag("v:shapetype");
+}
+
+// Write the <v:shape> element of an embedded object, with the relationship
+// id of its preview image.
+fn write_object_shape(
+    writer: &mut XMLWriter<impl Write>,
+    object: &ObjectShape,
+    shape_id: usize,
+    z_index: usize,
+    rel_id: &str,
+) {
+    let id = format!("_x0000_s{shape_id}");
+    let style = object.position.style(z_index);
+
+    let attributes = vec![
+        ("id", id.as_str()),
+        ("type", "#_x0000_t75"),
+        ("style", style.as_str()),
+        ("filled", "t"),
+        ("fillcolor", "window [65]"),
+        ("stroked", "t"),
+        ("strokecolor", "windowText [64]"),
+        ("o:insetmode", "auto"),
+    ];
+    writer.xml_start_tag("v:shape", &attributes);
+
+    writer.xml_empty_tag("v:fill", &vec![("color2", "window [65]")]);
+    writer.xml_empty_tag("v:imagedata", &vec![("o:relid", rel_id), ("o:title", "")]);
+
+    writer.xml_start_tag("x:ClientData", &vec![("ObjectType", "Pict")]);
+    writer.xml_empty_tag("x:SizeWithCells", &vec![]);
+    writer.xml_data_element("x:Anchor", &object.position.anchor(), &vec![]);
+    writer.xml_data_element("x:CF", "Pict", &vec![]);
+    writer.xml_empty_tag("x:AutoPict", &vec![]);
+    writer.xml_end_tag("x:ClientData");
+
+    writer.xml_end_tag("v:shape");
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::VmlDrawing;
+    use crate::button::Button;
+    use crate::image::tests::png_data;
+    use crate::image::Image;
+    use crate::note::Note;
+    use crate::utility::{COL_MAX, ROW_MAX};
+    use crate::XMLWriter;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_assemble_vml_drawing() {
+        let expected = concat!(
+            r#"<xml xmlns:v="urn:schemas-microsoft-com:vml" "#,
+            r#"xmlns:o="urn:schemas-microsoft-com:office:office" "#,
+            r#"xmlns:x="urn:schemas-microsoft-com:office:excel">"#,
+            r#"<o:shapelayout v:ext="edit"><o:idmap v:ext="edit" data="1"/></o:shapelayout>"#,
+            r#"<v:shapetype id="_x0000_t202" coordsize="21600,21600" o:spt="202" "#,
+            r#"path="m,l,21600r21600,l21600,xe"><v:stroke joinstyle="miter"/>"#,
+            r#"<v:path gradientshapeok="t" o:connecttype="rect"/></v:shapetype>"#,
+            r##"<v:shape id="_x0000_s1025" type="#_x0000_t202" "##,
+            r#"style="position:absolute;margin-left:107.25pt;margin-top:7.5pt;width:96pt;"#,
+            r##"height:55.5pt;z-index:1;visibility:hidden" fillcolor="#ffffe1" o:insetmode="auto">"##,
+            r##"<v:fill color2="#ffffe1"/><v:shadow on="t" color="black" obscured="t"/>"##,
+            r#"<v:path o:connecttype="none"/><v:textbox style="mso-direction-alt:auto">"#,
+            r#"<div style="text-align:left"></div></v:textbox><x:ClientData ObjectType="Note">"#,
+            r#"<x:MoveWithCells/><x:SizeWithCells/><x:Anchor>2, 15, 0, 10, 4, 15, 4, 4</x:Anchor>"#,
+            r#"<x:AutoFill>False</x:AutoFill><x:Row>1</x:Row><x:Column>1</x:Column>"#,
+            r#"</x:ClientData></v:shape>"#,
+            r##"<v:shape id="_x0000_s1026" type="#_x0000_t202" "##,
+            r#"style="position:absolute;margin-left:59.25pt;margin-top:1.5pt;width:150pt;"#,
+            r##"height:75pt;z-index:2;visibility:visible" fillcolor="#ffffe1" o:insetmode="auto">"##,
+            r##"<v:fill color2="#ffffe1"/><v:shadow on="t" color="black" obscured="t"/>"##,
+            r#"<v:path o:connecttype="none"/><v:textbox style="mso-direction-alt:auto">"#,
+            r#"<div style="text-align:left"></div></v:textbox><x:ClientData ObjectType="Note">"#,
+            r#"<x:MoveWithCells/><x:SizeWithCells/><x:Anchor>1, 15, 0, 2, 4, 23, 5, 2</x:Anchor>"#,
+            r#"<x:AutoFill>False</x:AutoFill><x:Row>0</x:Row><x:Column>0</x:Column><x:Visible/>"#,
+            r#"</x:ClientData></v:shape></xml>"#,
+        );
+
+        let mut vml_drawing = VmlDrawing::new(1);
+        vml_drawing.add_note(1, 1, &Note::new("B2"));
+        vml_drawing.add_note(
+            0,
+            0,
+            &Note::new("A1")
+                .set_visible(true)
+                .set_width(200)
+                .set_height(100),
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        vml_drawing.assemble_xml_file(&mut writer);
+
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_note_positions_at_limits() {
+        let mut vml_drawing = VmlDrawing::new(2);
+        vml_drawing.add_note(ROW_MAX - 1, COL_MAX - 1, &Note::new(""));
+        vml_drawing.add_note(ROW_MAX - 3, COL_MAX - 3, &Note::new(""));
+
+        let position = vml_drawing.notes[0].position;
+        assert_eq!(
+            (
+                position.start_row,
+                position.y1,
+                position.start_col,
+                position.x1
+            ),
+            (ROW_MAX - 5, 14, COL_MAX as u32 - 4, 49)
+        );
+        assert_eq!(position.end_col, COL_MAX as u32 - 2);
+
+        let position = vml_drawing.notes[1].position;
+        assert_eq!(
+            (
+                position.start_row,
+                position.y1,
+                position.start_col,
+                position.x1
+            ),
+            (ROW_MAX - 7, 16, COL_MAX as u32 - 6, 49)
+        );
+    }
+
+    #[test]
+    fn test_assemble_vml_drawing_with_buttons() {
+        let expected = concat!(
+            r#"<xml xmlns:v="urn:schemas-microsoft-com:vml" "#,
+            r#"xmlns:o="urn:schemas-microsoft-com:office:office" "#,
+            r#"xmlns:x="urn:schemas-microsoft-com:office:excel">"#,
+            r#"<o:shapelayout v:ext="edit"><o:idmap v:ext="edit" data="1"/></o:shapelayout>"#,
+            r#"<v:shapetype id="_x0000_t201" coordsize="21600,21600" o:spt="201" "#,
+            r#"path="m,l,21600r21600,l21600,xe"><v:stroke joinstyle="miter"/>"#,
+            r#"<v:path shadowok="f" o:extrusionok="f" strokeok="f" fillok="f" o:connecttype="rect"/>"#,
+            r#"<o:lock v:ext="edit" shapetype="t"/></v:shapetype>"#,
+            r##"<v:shape id="_x0000_s1025" type="#_x0000_t201" "##,
+            r#"style="position:absolute;margin-left:96pt;margin-top:15pt;width:48pt;"#,
+            r#"height:15pt;z-index:1;mso-wrap-style:tight" o:button="t" "#,
+            r#"fillcolor="buttonFace [67]" strokecolor="windowText [64]" o:insetmode="auto">"#,
+            r#"<v:fill color2="buttonFace [67]" o:detectmouseclick="t"/>"#,
+            r#"<o:lock v:ext="edit" rotation="t"/>"#,
+            r#"<v:textbox style="mso-direction-alt:auto" o:singleclick="f">"#,
+            r##"<div style="text-align:center"><font face="Calibri" size="220" color="#000000">"##,
+            r#"Run</font></div></v:textbox><x:ClientData ObjectType="Button">"#,
+            r#"<x:Anchor>2, 0, 1, 0, 3, 0, 2, 0</x:Anchor><x:PrintObject>False</x:PrintObject>"#,
+            r#"<x:AutoFill>False</x:AutoFill><x:FmlaMacro>[0]!Button1_Click</x:FmlaMacro>"#,
+            r#"<x:TextHAlign>Center</x:TextHAlign><x:TextVAlign>Center</x:TextVAlign>"#,
+            r#"</x:ClientData></v:shape>"#,
+            r#"<v:shapetype id="_x0000_t202" coordsize="21600,21600" o:spt="202" "#,
+            r#"path="m,l,21600r21600,l21600,xe"><v:stroke joinstyle="miter"/>"#,
+            r#"<v:path gradientshapeok="t" o:connecttype="rect"/></v:shapetype>"#,
+            r##"<v:shape id="_x0000_s1026" type="#_x0000_t202" "##,
+            r#"style="position:absolute;margin-left:107.25pt;margin-top:7.5pt;width:96pt;"#,
+            r##"height:55.5pt;z-index:2;visibility:hidden" fillcolor="#ffffe1" o:insetmode="auto">"##,
+        );
+
+        let mut vml_drawing = VmlDrawing::new(1);
+        vml_drawing.add_button(1, 2, &Button::new("Run").set_macro("Button1_Click"));
+        vml_drawing.add_note(1, 1, &Note::new("B2"));
+
+        let mut writer = XMLWriter::new(vec![]);
+        vml_drawing.assemble_xml_file(&mut writer);
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(&got[..expected.len()], expected);
+    }
+
+    #[test]
+    fn test_assemble_vml_drawing_with_objects() {
+        let expected = concat!(
+            r#"<v:shapetype id="_x0000_t75" coordsize="21600,21600" o:spt="75" "#,
+            r#"o:preferrelative="t" path="m@4@5l@4@11@9@11@9@5xe" filled="f" stroked="f">"#,
+            r#"<v:stroke joinstyle="miter"/><v:formulas><v:f eqn="if lineDrawn pixelLineWidth 0"/>"#,
+            r#"<v:f eqn="sum @0 1 0"/><v:f eqn="sum 0 0 @1"/><v:f eqn="prod @2 1 2"/>"#,
+            r#"<v:f eqn="prod @3 21600 pixelWidth"/><v:f eqn="prod @3 21600 pixelHeight"/>"#,
+            r#"<v:f eqn="sum @0 0 1"/><v:f eqn="prod @6 1 2"/><v:f eqn="prod @7 21600 pixelWidth"/>"#,
+            r#"<v:f eqn="sum @8 21600 0"/><v:f eqn="prod @7 21600 pixelHeight"/>"#,
+            r#"<v:f eqn="sum @10 21600 0"/></v:formulas>"#,
+            r#"<v:path o:extrusionok="f" gradientshapeok="t" o:connecttype="rect"/>"#,
+            r#"<o:lock v:ext="edit" aspectratio="t"/></v:shapetype>"#,
+            r##"<v:shape id="_x0000_s1026" type="#_x0000_t75" "##,
+            r#"style="position:absolute;margin-left:48pt;margin-top:15pt;width:36pt;"#,
+            r#"height:36pt;z-index:2" filled="t" fillcolor="window [65]" stroked="t" "#,
+            r#"strokecolor="windowText [64]" o:insetmode="auto"><v:fill color2="window [65]"/>"#,
+            r#"<v:imagedata o:relid="rId1" o:title=""/><x:ClientData ObjectType="Pict">"#,
+            r#"<x:SizeWithCells/><x:Anchor>1, 0, 1, 0, 1, 48, 3, 8</x:Anchor><x:CF>Pict</x:CF>"#,
+            r#"<x:AutoPict/></x:ClientData></v:shape></xml>"#,
+        );
+
+        let mut vml_drawing = VmlDrawing::new(1);
+        vml_drawing.add_note(1, 1, &Note::new("B2"));
+        vml_drawing.add_object(
+            1,
+            1,
+            &Image::from_bytes(&png_data(48, 48, 0)).unwrap(),
+            "xl/media/image1.png",
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        vml_drawing.assemble_xml_file(&mut writer);
+
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(got.ends_with(expected), "{got}");
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        vml_drawing.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId1" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" "#,
+            r#"Target="../media/image1.png"/>"#,
+        )));
+    }
+}
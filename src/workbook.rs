@@ -0,0 +1,1226 @@
+//! The writer for the `xl/workbook.xml` part.
+//!
+//! The workbook part lists the worksheets in the workbook, with the
+//! relationship ids that link them to their parts, and the defined names.
+//!
+//! Worksheets are identified by the index returned from
+//! [`Workbook::add_sheet()`], which doesn't change if the worksheet tabs are
+//! reordered with [`Workbook::move_sheet()`]. The `sheetId`, the relationship
+//! id and the part name of a worksheet follow its index, while the
+//! `activeTab`, `firstSheet` and `localSheetId` values, which Excel treats as
+//! tab positions, are recomputed from the tab order when the part is written.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::workbook::{SheetState, Workbook};
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut workbook = Workbook::new();
+//!
+//! workbook.add_sheet("Sheet1");
+//! let data = workbook.add_sheet("Data");
+//! workbook.define_name("Sales", "=Data!$A$1:$A$10")?;
+//! workbook.set_sheet_state(data, SheetState::Hidden)?;
+//!
+//! workbook.assemble_xml_file(&mut writer);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::io::Write;
+
+use crate::constants::{RELATIONSHIPS_NS, SPREADSHEETML_NS};
+use crate::error::XlsxError;
+use crate::external_link::{replace_external_references, ExternalLink, ExternalReference};
+use crate::protection::password_hash;
+use crate::utility::{
+    formula_sheet_names, is_a1_reference, is_r1c1_reference, quote_sheet_name, rowcol_to_cell_abs,
+};
+use crate::XMLWriter;
+
+/// The visibility of a worksheet tab.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SheetState {
+    /// The worksheet is visible. The default.
+    #[default]
+    Visible,
+
+    /// The worksheet is hidden and can be unhidden from Excel's UI.
+    Hidden,
+
+    /// The worksheet is hidden and can only be unhidden with VBA.
+    VeryHidden,
+}
+
+// The built-in names, without the `_xlnm.` prefix, that can be defined with
+// add_defined_name(). The `_FilterDatabase` name is added with the autofilter.
+const BUILTIN_NAMES: [&str; 6] = [
+    "Consolidate_Area",
+    "Criteria",
+    "Database",
+    "Extract",
+    "Print_Area",
+    "Print_Titles",
+];
+
+// A worksheet entry in the <sheets> element.
+#[derive(Clone, Debug)]
+struct Sheet {
+    name: String,
+    state: SheetState,
+}
+
+// An entry in the <definedNames> element.
+#[derive(Clone, Debug)]
+struct DefinedName {
+    name: String,
+    local_sheet: Option<u16>,
+    formula: String,
+    hidden: bool,
+}
+
+impl DefinedName {
+    // Excel sorts defined names case-insensitively, ignoring the `_xlnm.`
+    // prefix of built-in names, and then by the tab position of the local
+    // sheet.
+    fn sort_key(&self, position: Option<u16>) -> (String, Option<u16>) {
+        let name = self.name.strip_prefix("_xlnm.").unwrap_or(&self.name);
+        (name.to_lowercase(), position)
+    }
+}
+
+/// The writer for the `xl/workbook.xml` part.
+#[derive(Debug, Default)]
+pub struct Workbook {
+    sheets: Vec<Sheet>,
+    tab_order: Vec<u16>,
+    defined_names: Vec<DefinedName>,
+    table_names: Vec<String>,
+    active_sheet: u16,
+    first_sheet: Option<u16>,
+    protection: Option<(String, bool, bool)>,
+    external_links: Vec<ExternalLink>,
+}
+
+impl Workbook {
+    /// Create a new workbook part with no worksheets.
+    pub fn new() -> Workbook {
+        Workbook::default()
+    }
+
+    /// Add a worksheet and return its zero-based index. The worksheet gets
+    /// the `sheetId` index + 1 and the relationship id `rId<index + 1>`, so
+    /// the worksheets should be the first relationships in the workbook
+    /// `.rels` part, in the same order. The worksheet tab is added after the
+    /// existing tabs.
+    pub fn add_sheet(&mut self, name: &str) -> u16 {
+        self.sheets.push(Sheet {
+            name: name.to_string(),
+            state: SheetState::Visible,
+        });
+
+        let index = (self.sheets.len() - 1) as u16;
+        self.tab_order.push(index);
+        index
+    }
+
+    /// Move a worksheet tab to a zero-based tab position, shifting the tabs
+    /// in between. The worksheet keeps its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't a worksheet in the workbook or the position
+    /// is beyond the last tab.
+    pub fn move_sheet(&mut self, index: u16, position: u16) {
+        let current = self.tab_position(index);
+        assert!(
+            (position as usize) < self.tab_order.len(),
+            "tab position {position} is beyond the last worksheet tab"
+        );
+
+        self.tab_order.remove(current as usize);
+        self.tab_order.insert(position as usize, index);
+    }
+
+    /// The zero-based tab position of a worksheet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't a worksheet in the workbook.
+    pub fn tab_position(&self, index: u16) -> u16 {
+        self.sheet(index);
+
+        self.tab_order
+            .iter()
+            .position(|tab| *tab == index)
+            .unwrap_or_default() as u16
+    }
+
+    /// The number of worksheets in the workbook.
+    pub fn sheet_count(&self) -> u16 {
+        self.sheets.len() as u16
+    }
+
+    /// Set the visibility of a worksheet.
+    ///
+    /// Excel requires at least one visible worksheet, and the active
+    /// worksheet and first visible tab must be visible, so hiding any of them
+    /// is an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't a worksheet in the workbook.
+    pub fn set_sheet_state(&mut self, index: u16, state: SheetState) -> Result<(), XlsxError> {
+        let sheet = self.sheet(index);
+
+        if state != SheetState::Visible && sheet.state == SheetState::Visible {
+            let visible_sheets = self
+                .sheets
+                .iter()
+                .filter(|sheet| sheet.state == SheetState::Visible)
+                .count();
+
+            if visible_sheets == 1 {
+                return Err(XlsxError::NoVisibleSheets);
+            }
+            if index == self.active_sheet {
+                return Err(XlsxError::HiddenActiveSheet(sheet.name.clone()));
+            }
+            if Some(index) == self.first_sheet {
+                return Err(XlsxError::HiddenFirstSheet(sheet.name.clone()));
+            }
+        }
+
+        self.sheets[index as usize].state = state;
+        Ok(())
+    }
+
+    /// Set the active worksheet, the one that is displayed when the file is
+    /// opened. The default is the first worksheet. The active worksheet must
+    /// be visible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't a worksheet in the workbook.
+    pub fn set_active_sheet(&mut self, index: u16) -> Result<(), XlsxError> {
+        let sheet = self.sheet(index);
+
+        if sheet.state != SheetState::Visible {
+            return Err(XlsxError::HiddenActiveSheet(sheet.name.clone()));
+        }
+
+        self.active_sheet = index;
+        Ok(())
+    }
+
+    /// Protect the workbook, with an optional password. Locking the structure
+    /// stops users from adding, deleting, renaming, moving, hiding or
+    /// unhiding worksheets, and locking the windows stops them from moving
+    /// or resizing the workbook windows. As with
+    /// [`Worksheet::protect_sheet()`](crate::worksheet::Worksheet::protect_sheet),
+    /// the password is stored with Excel's legacy 16-bit hash, which isn't
+    /// encryption.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::workbook::Workbook;
+    /// #
+    /// let mut workbook = Workbook::new();
+    /// workbook.add_sheet("Sheet1");
+    ///
+    /// workbook.protect_workbook("password", true, false);
+    /// ```
+    pub fn protect_workbook(&mut self, password: &str, lock_structure: bool, lock_windows: bool) {
+        self.protection = Some((password.to_string(), lock_structure, lock_windows));
+    }
+
+    /// Set the worksheet whose tab is the first one shown in the tab bar,
+    /// for workbooks with more tabs than fit on screen. The default is the
+    /// first tab. The worksheet must be visible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't a worksheet in the workbook.
+    pub fn set_first_sheet(&mut self, index: u16) -> Result<(), XlsxError> {
+        let sheet = self.sheet(index);
+
+        if sheet.state != SheetState::Visible {
+            return Err(XlsxError::HiddenFirstSheet(sheet.name.clone()));
+        }
+
+        self.first_sheet = Some(index);
+        Ok(())
+    }
+
+    fn sheet(&self, index: u16) -> &Sheet {
+        match self.sheets.get(index as usize) {
+            Some(sheet) => sheet,
+            None => panic!("worksheet index {index} is beyond the last worksheet"),
+        }
+    }
+
+    /// Add a defined name. The `local_sheet` index restricts the scope of the
+    /// name to a worksheet and is written as its tab position. The formula is
+    /// written without a leading `=`.
+    ///
+    /// Excel rejects a file with a name that looks like a cell reference or
+    /// that is used twice in the same scope, so these are errors. Names are
+    /// compared case-insensitively, and a table name clashes with a defined
+    /// name of any scope.
+    ///
+    /// Names with the reserved `_xlnm.` prefix must be one of Excel's
+    /// built-in names, such as `_xlnm.Print_Area` or `_xlnm.Print_Titles`,
+    /// and must be scoped to a worksheet.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::workbook::Workbook;
+    /// # use excel_xmlwriter::error::XlsxError;
+    /// #
+    /// let mut workbook = Workbook::new();
+    /// workbook.add_sheet("Sheet1");
+    ///
+    /// assert!(workbook.add_defined_name("Sales", None, "=Sheet1!$A$1").is_ok());
+    /// assert!(workbook.add_defined_name("Sales", Some(0), "=Sheet1!$A$2").is_ok());
+    /// assert_eq!(
+    ///     workbook.add_defined_name("SALES", None, "=Sheet1!$A$3"),
+    ///     Err(XlsxError::DuplicateName("SALES".to_string()))
+    /// );
+    /// assert_eq!(
+    ///     workbook.add_defined_name("Q1", None, "=Sheet1!$A$4"),
+    ///     Err(XlsxError::InvalidName("Q1".to_string()))
+    /// );
+    /// ```
+    pub fn add_defined_name(
+        &mut self,
+        name: &str,
+        local_sheet: Option<u16>,
+        formula: &str,
+    ) -> Result<(), XlsxError> {
+        check_name(name)?;
+        check_reserved_name(name, local_sheet)?;
+
+        let name_lowercase = name.to_lowercase();
+        let is_duplicate = self.is_table_name(&name_lowercase)
+            || self.defined_names.iter().any(|defined_name| {
+                defined_name.local_sheet == local_sheet
+                    && defined_name.name.to_lowercase() == name_lowercase
+            });
+
+        if is_duplicate {
+            return Err(XlsxError::DuplicateName(name.to_string()));
+        }
+
+        self.defined_names.push(DefinedName {
+            name: name.to_string(),
+            local_sheet,
+            formula: formula.strip_prefix('=').unwrap_or(formula).to_string(),
+            hidden: false,
+        });
+
+        Ok(())
+    }
+
+    /// Add a workbook-scoped defined name, like `Sales` for the formula
+    /// `=Sheet1!$A$1:$A$10`. This is the same as
+    /// [`add_defined_name()`](Workbook::add_defined_name) without a local
+    /// worksheet, and has the same errors.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::workbook::Workbook;
+    /// # use excel_xmlwriter::error::XlsxError;
+    /// #
+    /// let mut workbook = Workbook::new();
+    /// workbook.add_sheet("Sheet1");
+    ///
+    /// assert!(workbook.define_name("Sales", "=Sheet1!$A$1:$A$10").is_ok());
+    /// assert_eq!(
+    ///     workbook.define_name("_xlnm.Print_Area", "=Sheet1!$A$1:$D$20"),
+    ///     Err(XlsxError::ReservedName("_xlnm.Print_Area".to_string()))
+    /// );
+    /// ```
+    pub fn define_name(&mut self, name: &str, formula: &str) -> Result<(), XlsxError> {
+        self.add_defined_name(name, None, formula)
+    }
+
+    /// Add the hidden `_xlnm._FilterDatabase` defined name that Excel
+    /// requires for the autofilter range of a worksheet, see
+    /// [`Worksheet::autofilter()`](crate::worksheet::Worksheet::autofilter).
+    /// A previous autofilter name for the worksheet is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't a worksheet in the workbook.
+    pub fn add_autofilter_name(
+        &mut self,
+        index: u16,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+    ) {
+        let sheet_name = quote_sheet_name(&self.sheet(index).name);
+        let formula = format!(
+            "{sheet_name}!{}:{}",
+            rowcol_to_cell_abs(first_row, first_col),
+            rowcol_to_cell_abs(last_row, last_col)
+        );
+
+        self.defined_names.retain(|defined_name| {
+            !(defined_name.hidden
+                && defined_name.local_sheet == Some(index)
+                && defined_name.name == "_xlnm._FilterDatabase")
+        });
+
+        self.defined_names.push(DefinedName {
+            name: "_xlnm._FilterDatabase".to_string(),
+            local_sheet: Some(index),
+            formula,
+            hidden: true,
+        });
+    }
+
+    /// Reserve the name of a table. Table names are unique in the workbook
+    /// and share the namespace of the defined names, so a name that is
+    /// already used by a table or a defined name of any scope is an error,
+    /// as is a name that Excel doesn't accept, see
+    /// [`add_defined_name()`](Workbook::add_defined_name).
+    pub fn add_table_name(&mut self, name: &str) -> Result<(), XlsxError> {
+        check_name(name)?;
+        check_reserved_name(name, None)?;
+
+        let name_lowercase = name.to_lowercase();
+        let is_duplicate = self.is_table_name(&name_lowercase)
+            || self
+                .defined_names
+                .iter()
+                .any(|defined_name| defined_name.name.to_lowercase() == name_lowercase);
+
+        if is_duplicate {
+            return Err(XlsxError::DuplicateName(name.to_string()));
+        }
+
+        self.table_names.push(name.to_string());
+        Ok(())
+    }
+
+    fn is_table_name(&self, name_lowercase: &str) -> bool {
+        self.table_names
+            .iter()
+            .any(|table_name| table_name.to_lowercase() == name_lowercase)
+    }
+
+    /// Add a link to another workbook, with the names of the worksheets
+    /// that are referred to, and return the number of the link, which is
+    /// the number of the workbook in formulas, like `[1]Sheet1!A1`. A link
+    /// to a path that is already linked is reused. Excel compares the paths
+    /// case-insensitively.
+    ///
+    /// The links are written as the `xl/externalLinks/externalLinkN.xml`
+    /// parts from [`external_links()`](Workbook::external_links), with the
+    /// relationship ids `rId<sheet count + N>`, so their relationships of
+    /// type `externalLink` and target `externalLinks/externalLinkN.xml`
+    /// should follow the worksheets in the workbook `.rels` part, in the
+    /// same order.
+    pub fn add_external_link(&mut self, target: &str, sheet_names: &[&str]) -> u32 {
+        let target_lowercase = target.to_lowercase();
+        let index = match self
+            .external_links
+            .iter()
+            .position(|link| link.target().to_lowercase() == target_lowercase)
+        {
+            Some(index) => index,
+            None => {
+                let number = self.external_links.len() as u32 + 1;
+                self.external_links.push(ExternalLink::new(number, target));
+                self.external_links.len() - 1
+            }
+        };
+
+        let link = &mut self.external_links[index];
+        for sheet_name in sheet_names {
+            link.add_sheet_name(sheet_name);
+        }
+
+        link.number()
+    }
+
+    /// Convert the workbook names in a formula, like `[Book2.xlsx]Sheet1!A1`
+    /// or `[Book2.xlsx]!Rates`, to the numbers of their links, adding the
+    /// links and their worksheets and defined names, see
+    /// [`add_external_link()`](Workbook::add_external_link). Excel asks to
+    /// repair a file with formulas that use the workbook names.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::workbook::Workbook;
+    /// #
+    /// let mut workbook = Workbook::new();
+    /// workbook.add_sheet("Sheet1");
+    ///
+    /// let formula = workbook.external_formula("=[Book2.xlsx]Sheet1!A1+'[Book 3.xlsx]Q1'!A1");
+    /// assert_eq!(formula, "=[1]Sheet1!A1+'[2]Q1'!A1");
+    /// ```
+    pub fn external_formula(&mut self, formula: &str) -> String {
+        replace_external_references(formula, |reference| {
+            let number = match reference {
+                ExternalReference::Sheet { book, sheet } => self.add_external_link(book, &[sheet]),
+                ExternalReference::Name { book, name } => {
+                    let number = self.add_external_link(book, &[]);
+                    self.external_links[number as usize - 1].add_defined_name(name);
+                    number
+                }
+            };
+
+            number.to_string()
+        })
+    }
+
+    /// The `xl/externalLinks/externalLinkN.xml` parts of the links to other
+    /// workbooks, in the order of their numbers.
+    pub fn external_links(&self) -> Vec<ExternalLink> {
+        self.external_links.clone()
+    }
+
+    /// Check that the worksheet names that a formula refers to are in the
+    /// workbook. Excel compares worksheet names case-insensitively.
+    ///
+    /// Excel doesn't complain about a formula with an unknown worksheet when
+    /// the file is opened, it silently replaces the reference with `#REF!`,
+    /// so this can be used to catch the error when the file is written.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::workbook::Workbook;
+    /// # use excel_xmlwriter::error::XlsxError;
+    /// #
+    /// let mut workbook = Workbook::new();
+    /// workbook.add_sheet("Sales Data");
+    ///
+    /// assert!(workbook.validate_formula("='sales data'!A1*2").is_ok());
+    /// assert!(workbook.validate_formula("=SalesData!A1").is_err());
+    /// ```
+    pub fn validate_formula(&self, formula: &str) -> Result<(), XlsxError> {
+        for sheet in formula_sheet_names(formula) {
+            let sheet_lowercase = sheet.to_lowercase();
+
+            if !self
+                .sheets
+                .iter()
+                .any(|known| known.name.to_lowercase() == sheet_lowercase)
+            {
+                return Err(XlsxError::UnknownSheetReference {
+                    sheet,
+                    formula: formula.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the workbook for errors that Excel wouldn't report until the
+    /// file is opened. Currently this checks the worksheet references in the
+    /// defined names, see [`validate_formula()`](Workbook::validate_formula).
+    pub fn validate(&self) -> Result<(), XlsxError> {
+        for defined_name in &self.defined_names {
+            self.validate_formula(&defined_name.formula)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the `xl/workbook.xml` part.
+    pub fn assemble_xml_file(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns", SPREADSHEETML_NS), ("xmlns:r", RELATIONSHIPS_NS)];
+        writer.xml_start_tag("workbook", &attributes);
+
+        self.write_file_version(writer);
+        self.write_workbook_pr(writer);
+        self.write_workbook_protection(writer);
+        self.write_book_views(writer);
+        self.write_sheets(writer);
+        self.write_external_references(writer);
+        self.write_defined_names(writer);
+        self.write_calc_pr(writer);
+
+        writer.xml_end_tag("workbook");
+    }
+
+    // Write the <workbookProtection> element, with the hash of the password
+    // if it isn't empty.
+    fn write_workbook_protection(&self, writer: &mut XMLWriter<impl Write>) {
+        let Some((password, lock_structure, lock_windows)) = &self.protection else {
+            return;
+        };
+
+        let hash = format!("{:X}", password_hash(password));
+
+        let mut attributes = vec![];
+        if !password.is_empty() {
+            attributes.push(("workbookPassword", hash.as_str()));
+        }
+        if *lock_structure {
+            attributes.push(("lockStructure", "1"));
+        }
+        if *lock_windows {
+            attributes.push(("lockWindows", "1"));
+        }
+        writer.xml_empty_tag("workbookProtection", &attributes);
+    }
+
+    // Write the <fileVersion> element.
+    fn write_file_version(&self, writer: &mut XMLWriter<impl Write>) {
+        let attributes = vec![
+            ("appName", "xl"),
+            ("lastEdited", "4"),
+            ("lowestEdited", "4"),
+            ("rupBuild", "4505"),
+        ];
+        writer.xml_empty_tag("fileVersion", &attributes);
+    }
+
+    // Write the <workbookPr> element.
+    fn write_workbook_pr(&self, writer: &mut XMLWriter<impl Write>) {
+        let attributes = vec![("defaultThemeVersion", "124226")];
+        writer.xml_empty_tag("workbookPr", &attributes);
+    }
+
+    // Write the <bookViews> element.
+    fn write_book_views(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_start_tag("bookViews", &vec![]);
+
+        let first_tab = self.first_sheet.map_or(0, |index| self.tab_position(index));
+        let active_tab = if self.sheets.is_empty() {
+            0
+        } else {
+            self.tab_position(self.active_sheet)
+        };
+        let first_tab_value = first_tab.to_string();
+        let active_tab_value = active_tab.to_string();
+
+        let mut attributes = vec![
+            ("xWindow", "240"),
+            ("yWindow", "15"),
+            ("windowWidth", "16095"),
+            ("windowHeight", "9660"),
+        ];
+        if first_tab > 0 {
+            attributes.push(("firstSheet", first_tab_value.as_str()));
+        }
+        if active_tab > 0 {
+            attributes.push(("activeTab", active_tab_value.as_str()));
+        }
+        writer.xml_empty_tag("workbookView", &attributes);
+
+        writer.xml_end_tag("bookViews");
+    }
+
+    // Write the <sheets> element.
+    fn write_sheets(&self, writer: &mut XMLWriter<impl Write>) {
+        writer.xml_start_tag("sheets", &vec![]);
+
+        for index in &self.tab_order {
+            let sheet = &self.sheets[*index as usize];
+            let sheet_id = (index + 1).to_string();
+            let rel_id = format!("rId{}", index + 1);
+
+            let mut attributes = vec![
+                ("name", sheet.name.as_str()),
+                ("sheetId", sheet_id.as_str()),
+            ];
+            match sheet.state {
+                SheetState::Visible => {}
+                SheetState::Hidden => attributes.push(("state", "hidden")),
+                SheetState::VeryHidden => attributes.push(("state", "veryHidden")),
+            }
+            attributes.push(("r:id", rel_id.as_str()));
+
+            writer.xml_empty_tag("sheet", &attributes);
+        }
+
+        writer.xml_end_tag("sheets");
+    }
+
+    // Write the <externalReferences> element, with the relationship ids
+    // that follow the worksheets.
+    fn write_external_references(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.external_links.is_empty() {
+            return;
+        }
+
+        writer.xml_start_tag("externalReferences", &vec![]);
+
+        for link in &self.external_links {
+            let rel_id = format!("rId{}", self.sheets.len() as u32 + link.number());
+            writer.xml_empty_tag("externalReference", &vec![("r:id", rel_id.as_str())]);
+        }
+
+        writer.xml_end_tag("externalReferences");
+    }
+
+    // Write the <definedNames> element.
+    fn write_defined_names(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.defined_names.is_empty() {
+            return;
+        }
+
+        // Local names refer to the tab position of their worksheet.
+        let mut defined_names: Vec<(&DefinedName, Option<u16>)> = self
+            .defined_names
+            .iter()
+            .map(|defined_name| {
+                let position = defined_name
+                    .local_sheet
+                    .map(|index| self.tab_position(index));
+                (defined_name, position)
+            })
+            .collect();
+        defined_names.sort_by_key(|(defined_name, position)| defined_name.sort_key(*position));
+
+        writer.xml_start_tag("definedNames", &vec![]);
+
+        for (defined_name, position) in defined_names {
+            let local_sheet = position.map(|position| position.to_string());
+
+            let mut attributes = vec![("name", defined_name.name.as_str())];
+            if let Some(local_sheet) = &local_sheet {
+                attributes.push(("localSheetId", local_sheet.as_str()));
+            }
+            if defined_name.hidden {
+                attributes.push(("hidden", "1"));
+            }
+
+            writer.xml_data_element("definedName", &defined_name.formula, &attributes);
+        }
+
+        writer.xml_end_tag("definedNames");
+    }
+
+    // Write the <calcPr> element.
+    fn write_calc_pr(&self, writer: &mut XMLWriter<impl Write>) {
+        let attributes = vec![("calcId", "124519"), ("fullCalcOnLoad", "1")];
+        writer.xml_empty_tag("calcPr", &attributes);
+    }
+}
+
+// Check that a defined name or table name is one that Excel accepts. It must
+// start with a letter, underscore or backslash, contain only letters, digits,
+// underscores, periods and backslashes, and not look like an A1 or R1C1 cell
+// reference.
+fn check_name(name: &str) -> Result<(), XlsxError> {
+    let mut chars = name.chars();
+    let is_valid = match chars.next() {
+        Some(first) => {
+            (first.is_alphabetic() || first == '_' || first == '\\')
+                && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '\\'))
+        }
+        None => false,
+    };
+
+    let name_uppercase = name.to_uppercase();
+    if !is_valid
+        || name.chars().count() > 255
+        || is_a1_reference(&name_uppercase)
+        || is_r1c1_reference(&name_uppercase)
+    {
+        return Err(XlsxError::InvalidName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+// Check that a name with the reserved `_xlnm.` prefix is a built-in name that
+// can be defined, and that it is scoped to a worksheet like Excel requires.
+fn check_reserved_name(name: &str, local_sheet: Option<u16>) -> Result<(), XlsxError> {
+    let prefix = name.get(..6).unwrap_or_default();
+    if !prefix.eq_ignore_ascii_case("_xlnm.") {
+        return Ok(());
+    }
+
+    let is_builtin = BUILTIN_NAMES
+        .iter()
+        .any(|builtin| name[6..].eq_ignore_ascii_case(builtin));
+
+    if !is_builtin || local_sheet.is_none() {
+        return Err(XlsxError::ReservedName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{check_name, check_reserved_name, SheetState, Workbook};
+    use crate::error::XlsxError;
+    use crate::testing::read_xmlfile_data;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_workbook(workbook: &Workbook) -> String {
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        writer.set_check_attribute_order(true);
+
+        workbook.assemble_xml_file(&mut writer);
+
+        read_xmlfile_data(&mut tempfile)
+    }
+
+    #[test]
+    fn test_assemble_workbook() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            r#"<fileVersion appName="xl" lastEdited="4" lowestEdited="4" rupBuild="4505"/>"#,
+            r#"<workbookPr defaultThemeVersion="124226"/><bookViews><workbookView "#,
+            r#"xWindow="240" yWindow="15" windowWidth="16095" windowHeight="9660"/>"#,
+            r#"</bookViews><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets>"#,
+            r#"<calcPr calcId="124519" fullCalcOnLoad="1"/></workbook>"#,
+        );
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+
+        assert_eq!(assemble_workbook(&workbook), expected);
+    }
+
+    #[test]
+    fn test_external_references() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+
+        assert_eq!(
+            workbook.external_formula("=[Book2.xlsx]Sheet1!A1+'[Book 3.xlsx]Q1'!A1"),
+            "=[1]Sheet1!A1+'[2]Q1'!A1"
+        );
+        assert_eq!(
+            workbook.external_formula("=[book2.xlsx]Data!A1*[Book2.xlsx]!Rates+[1]Sheet1!A2"),
+            "=[1]Data!A1*[1]!Rates+[1]Sheet1!A2"
+        );
+        assert_eq!(workbook.add_external_link("Book 3.xlsx", &["Q2"]), 2);
+        assert!(workbook.validate_formula("=[1]Data!A1").is_ok());
+
+        let links = workbook.external_links();
+        assert_eq!(links.len(), 2);
+        assert_eq!((links[0].number(), links[0].target()), (1, "Book2.xlsx"));
+
+        let mut writer = XMLWriter::new(vec![]);
+        links[0].assemble_xml_file(&mut writer);
+        assert!(String::from_utf8(writer.into_inner())
+            .unwrap()
+            .contains(concat!(
+                r#"<sheetNames><sheetName val="Sheet1"/><sheetName val="Data"/></sheetNames>"#,
+                r#"<definedNames><definedName name="Rates"/></definedNames>"#,
+            )));
+
+        workbook.define_name("Total", "=Sheet1!$A$1").unwrap();
+        assert!(assemble_workbook(&workbook).contains(concat!(
+            r#"</sheets><externalReferences><externalReference r:id="rId3"/>"#,
+            r#"<externalReference r:id="rId4"/></externalReferences><definedNames>"#,
+        )));
+    }
+
+    #[test]
+    fn test_workbook_protection() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+
+        workbook.protect_workbook("password", true, true);
+        assert!(assemble_workbook(&workbook).contains(concat!(
+            r#"<workbookPr defaultThemeVersion="124226"/>"#,
+            r#"<workbookProtection workbookPassword="83AF" lockStructure="1" lockWindows="1"/>"#,
+            r#"<bookViews>"#,
+        )));
+
+        workbook.protect_workbook("", false, true);
+        assert!(assemble_workbook(&workbook).contains(r#"<workbookProtection lockWindows="1"/>"#));
+    }
+
+    #[test]
+    fn test_assemble_workbook_with_sheets() {
+        let expected = concat!(
+            r#"<sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/>"#,
+            r#"<sheet name="Q&amp;A" sheetId="2" r:id="rId2"/>"#,
+            r#"<sheet name="Sheet3" sheetId="3" r:id="rId3"/></sheets>"#,
+        );
+
+        let mut workbook = Workbook::new();
+        assert_eq!(workbook.add_sheet("Sheet1"), 0);
+        assert_eq!(workbook.add_sheet("Q&A"), 1);
+        assert_eq!(workbook.add_sheet("Sheet3"), 2);
+        assert_eq!(workbook.sheet_count(), 3);
+
+        assert!(assemble_workbook(&workbook).contains(expected));
+    }
+
+    #[test]
+    fn test_assemble_defined_names() {
+        let expected = concat!(
+            r#"</sheets><definedNames>"#,
+            r#"<definedName name="_xlnm.Print_Titles" localSheetId="0">Sheet1!$1:$1"#,
+            r#"</definedName><definedName name="Sales">Sheet1!$A$1:$A$10</definedName>"#,
+            r#"<definedName name="sales" localSheetId="1">Sheet2!$B$1</definedName>"#,
+            r#"<definedName name="Total">Sheet1!$A$11&gt;0</definedName></definedNames>"#,
+            r#"<calcPr"#,
+        );
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+        workbook
+            .add_defined_name("Total", None, "=Sheet1!$A$11>0")
+            .unwrap();
+        workbook
+            .add_defined_name("sales", Some(1), "Sheet2!$B$1")
+            .unwrap();
+        workbook
+            .add_defined_name("Sales", None, "Sheet1!$A$1:$A$10")
+            .unwrap();
+        workbook
+            .add_defined_name("_xlnm.Print_Titles", Some(0), "Sheet1!$1:$1")
+            .unwrap();
+
+        assert!(assemble_workbook(&workbook).contains(expected));
+    }
+
+    #[test]
+    fn test_assemble_sheet_states() {
+        let expected = concat!(
+            r#"<workbookView xWindow="240" yWindow="15" windowWidth="16095" "#,
+            r#"windowHeight="9660" activeTab="1"/></bookViews><sheets>"#,
+            r#"<sheet name="Sheet1" sheetId="1" state="hidden" r:id="rId1"/>"#,
+            r#"<sheet name="Sheet2" sheetId="2" r:id="rId2"/>"#,
+            r#"<sheet name="Sheet3" sheetId="3" state="veryHidden" r:id="rId3"/></sheets>"#,
+        );
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+        workbook.add_sheet("Sheet3");
+
+        workbook.set_active_sheet(1).unwrap();
+        workbook.set_sheet_state(0, SheetState::Hidden).unwrap();
+        workbook.set_sheet_state(2, SheetState::VeryHidden).unwrap();
+
+        assert!(assemble_workbook(&workbook).contains(expected));
+    }
+
+    #[test]
+    fn test_sheet_state_validation() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+
+        // The active sheet can't be hidden.
+        assert_eq!(
+            workbook.set_sheet_state(0, SheetState::Hidden),
+            Err(XlsxError::HiddenActiveSheet("Sheet1".to_string()))
+        );
+
+        // A hidden sheet can't be made active.
+        workbook.set_sheet_state(1, SheetState::Hidden).unwrap();
+        assert_eq!(
+            workbook.set_active_sheet(1),
+            Err(XlsxError::HiddenActiveSheet("Sheet2".to_string()))
+        );
+
+        // The last visible sheet can't be hidden.
+        assert_eq!(
+            workbook.set_sheet_state(0, SheetState::VeryHidden),
+            Err(XlsxError::NoVisibleSheets)
+        );
+
+        // Unhiding is always allowed.
+        workbook.set_sheet_state(1, SheetState::Visible).unwrap();
+        workbook.set_active_sheet(1).unwrap();
+        workbook.set_sheet_state(0, SheetState::Hidden).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sheet_state_out_of_range() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+
+        workbook.set_sheet_state(1, SheetState::Hidden).unwrap();
+    }
+
+    #[test]
+    fn test_assemble_moved_sheets() {
+        let expected = concat!(
+            r#"<workbookView xWindow="240" yWindow="15" windowWidth="16095" "#,
+            r#"windowHeight="9660" firstSheet="1" activeTab="2"/></bookViews><sheets>"#,
+            r#"<sheet name="Sheet3" sheetId="3" r:id="rId3"/>"#,
+            r#"<sheet name="Sheet1" sheetId="1" r:id="rId1"/>"#,
+            r#"<sheet name="Sheet2" sheetId="2" r:id="rId2"/></sheets><definedNames>"#,
+            r#"<definedName name="Data" localSheetId="0">Sheet3!$A$1</definedName>"#,
+            r#"<definedName name="Data" localSheetId="2">Sheet2!$A$1</definedName>"#,
+            r#"</definedNames>"#,
+        );
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+        workbook.add_sheet("Sheet3");
+        workbook
+            .add_defined_name("Data", Some(1), "Sheet2!$A$1")
+            .unwrap();
+        workbook
+            .add_defined_name("Data", Some(2), "Sheet3!$A$1")
+            .unwrap();
+
+        workbook.set_active_sheet(1).unwrap();
+        workbook.set_first_sheet(0).unwrap();
+        workbook.move_sheet(2, 0);
+
+        assert_eq!(workbook.tab_position(2), 0);
+        assert_eq!(workbook.tab_position(0), 1);
+        assert_eq!(workbook.tab_position(1), 2);
+        assert!(assemble_workbook(&workbook).contains(expected));
+
+        // Moving a tab back restores the original order.
+        workbook.move_sheet(2, 2);
+        assert!(assemble_workbook(&workbook).contains(concat!(
+            r#"windowHeight="9660" activeTab="1"/></bookViews><sheets>"#,
+            r#"<sheet name="Sheet1" sheetId="1" r:id="rId1"/>"#,
+            r#"<sheet name="Sheet2" sheetId="2" r:id="rId2"/>"#,
+            r#"<sheet name="Sheet3" sheetId="3" r:id="rId3"/></sheets>"#,
+        )));
+    }
+
+    #[test]
+    fn test_first_sheet_validation() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+        workbook.add_sheet("Sheet3");
+
+        workbook.set_first_sheet(1).unwrap();
+        assert_eq!(
+            workbook.set_sheet_state(1, SheetState::Hidden),
+            Err(XlsxError::HiddenFirstSheet("Sheet2".to_string()))
+        );
+
+        workbook.set_sheet_state(2, SheetState::Hidden).unwrap();
+        assert_eq!(
+            workbook.set_first_sheet(2),
+            Err(XlsxError::HiddenFirstSheet("Sheet3".to_string()))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_move_sheet_out_of_range() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+
+        workbook.move_sheet(0, 1);
+    }
+
+    #[test]
+    fn test_validate_formula() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Q1 Sales");
+
+        assert_eq!(workbook.validate_formula("=A1+1"), Ok(()));
+        assert_eq!(workbook.validate_formula("=SHEET1!A1"), Ok(()));
+        assert_eq!(workbook.validate_formula("='Q1 Sales'!A1"), Ok(()));
+        assert_eq!(
+            workbook.validate_formula("=Sheet1:Sheet2!A1"),
+            Err(XlsxError::UnknownSheetReference {
+                sheet: "Sheet2".to_string(),
+                formula: "=Sheet1:Sheet2!A1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_defined_names() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook
+            .add_defined_name("Sales", None, "=Sheet1!$A$1:$A$10")
+            .unwrap();
+
+        assert_eq!(workbook.validate(), Ok(()));
+
+        workbook
+            .add_defined_name("Costs", None, "=Costs!$A$1")
+            .unwrap();
+        assert_eq!(
+            workbook.validate(),
+            Err(XlsxError::UnknownSheetReference {
+                sheet: "Costs".to_string(),
+                formula: "Costs!$A$1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_name() {
+        let valid_names = [
+            "Sales",
+            "_Sales",
+            "\\Sales",
+            "Sales.2022",
+            "Sales_Q1",
+            "Ventes_été",
+            "ABCD1",
+            "XFE1",
+            "A1048577",
+            "A0",
+            "R1C1X",
+            "RC_1",
+            "_xlnm.Print_Titles",
+        ];
+        for name in valid_names {
+            assert_eq!(check_name(name), Ok(()), "{name}");
+        }
+
+        let invalid_names = [
+            "",
+            "1Sales",
+            ".Sales",
+            "Sales Q1",
+            "Sales-Q1",
+            "Sales!",
+            "A1",
+            "a1",
+            "XFD1048576",
+            "AB12",
+            "Row1",
+            "R",
+            "c",
+            "RC",
+            "R1",
+            "C1",
+            "R1C1",
+            "r12c3",
+            "R1C",
+        ];
+        for name in invalid_names {
+            assert_eq!(
+                check_name(name),
+                Err(XlsxError::InvalidName(name.to_string())),
+                "{name}"
+            );
+        }
+
+        assert!(check_name(&"a".repeat(255)).is_ok());
+        assert!(check_name(&"a".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn test_check_reserved_name() {
+        let tests = [
+            ("Print_Area", None, true),
+            ("_xlnm.Print_Area", Some(0), true),
+            ("_XLNM.print_titles", Some(1), true),
+            ("_xlnm.Criteria", Some(0), true),
+            ("_xlnm.Print_Area", None, false),
+            ("_xlnm._FilterDatabase", Some(0), false),
+            ("_xlnm.Sales", Some(0), false),
+            ("_xlnm.", Some(0), false),
+        ];
+
+        for (name, local_sheet, is_valid) in tests {
+            let expected = if is_valid {
+                Ok(())
+            } else {
+                Err(XlsxError::ReservedName(name.to_string()))
+            };
+            assert_eq!(check_reserved_name(name, local_sheet), expected, "{name}");
+        }
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.define_name("Sales", "=Sheet1!$A$1:$A$10").unwrap();
+
+        assert_eq!(
+            workbook.add_table_name("_xlnm.Print_Area"),
+            Err(XlsxError::ReservedName("_xlnm.Print_Area".to_string()))
+        );
+        assert!(assemble_workbook(&workbook)
+            .contains(r#"<definedName name="Sales">Sheet1!$A$1:$A$10</definedName>"#));
+    }
+
+    #[test]
+    fn test_name_collisions() {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Sheet2");
+
+        workbook
+            .add_defined_name("Sales", None, "=Sheet1!$A$1")
+            .unwrap();
+        workbook
+            .add_defined_name("Sales", Some(1), "=Sheet2!$A$1")
+            .unwrap();
+        workbook
+            .add_defined_name("Costs", Some(0), "=Sheet1!$B$1")
+            .unwrap();
+
+        // Names are unique in each scope, ignoring case.
+        assert_eq!(
+            workbook.add_defined_name("sales", None, "=Sheet1!$A$2"),
+            Err(XlsxError::DuplicateName("sales".to_string()))
+        );
+        assert_eq!(
+            workbook.add_defined_name("COSTS", Some(0), "=Sheet1!$B$2"),
+            Err(XlsxError::DuplicateName("COSTS".to_string()))
+        );
+
+        // Table names clash with defined names of any scope.
+        assert_eq!(
+            workbook.add_table_name("costs"),
+            Err(XlsxError::DuplicateName("costs".to_string()))
+        );
+        assert_eq!(
+            workbook.add_table_name("B2"),
+            Err(XlsxError::InvalidName("B2".to_string()))
+        );
+
+        workbook.add_table_name("Table1").unwrap();
+        assert_eq!(
+            workbook.add_table_name("TABLE1"),
+            Err(XlsxError::DuplicateName("TABLE1".to_string()))
+        );
+        assert_eq!(
+            workbook.add_defined_name("table1", Some(1), "=Sheet2!$C$1"),
+            Err(XlsxError::DuplicateName("table1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_autofilter_name() {
+        let expected = concat!(
+            r#"<definedNames><definedName name="_xlnm._FilterDatabase" localSheetId="0" "#,
+            r#"hidden="1">Sheet1!$A$1:$D$10</definedName>"#,
+            r#"<definedName name="_xlnm._FilterDatabase" localSheetId="1" "#,
+            r#"hidden="1">'Q1 Sales'!$B$2:$C$2</definedName></definedNames>"#,
+        );
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        workbook.add_sheet("Q1 Sales");
+
+        workbook.add_autofilter_name(1, 0, 0, 0, 0);
+        workbook.add_autofilter_name(0, 0, 0, 9, 3);
+        workbook.add_autofilter_name(1, 1, 1, 1, 2);
+
+        assert!(assemble_workbook(&workbook).contains(expected));
+        assert_eq!(workbook.validate(), Ok(()));
+    }
+}
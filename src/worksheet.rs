@@ -0,0 +1,5263 @@
+//! The worksheet layer and the writer for the `xl/worksheets/sheetN.xml`
+//! parts.
+//!
+//! Cells are written with [`Worksheet::write_string()`],
+//! [`Worksheet::write_number()`] and [`Worksheet::write_formula()`], or their
+//! `_with_format()` variants, and are buffered until the part is written.
+//! Strings are added to the shared string table in row and column order when
+//! the part is written, which is the order Excel uses.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::shared_strings::SharedStringTable;
+//! use excel_xmlwriter::styles::{Format, Styles};
+//! use excel_xmlwriter::worksheet::Worksheet;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), std::io::Error> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut styles = Styles::new();
+//! let mut string_table = SharedStringTable::new();
+//! let mut worksheet = Worksheet::new();
+//!
+//! let bold = Format::new().set_bold();
+//!
+//! worksheet.write_string_with_format(0, 0, "Total", &bold);
+//! worksheet.write_number(0, 1, 1234.5);
+//! worksheet.write_formula(1, 1, "=B1*2");
+//!
+//! worksheet.assemble_xml_file(&mut writer, &mut styles, &mut string_table);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! For very large worksheets, [`Worksheet::start_streaming()`] returns a
+//! [`StreamingWorksheet`] that writes each cell to the part as soon as it is
+//! written, so memory use doesn't grow with the number of cells. The cells
+//! must be written in row order, and strings are written inline in the cells
+//! rather than in the shared string table.
+//!
+//! ```
+//! # use std::fs::File;
+//! use excel_xmlwriter::styles::Styles;
+//! use excel_xmlwriter::worksheet::Worksheet;
+//! use excel_xmlwriter::XMLWriter;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let xmlfile = File::create("test.xml")?;
+//! let mut writer = XMLWriter::new(&xmlfile);
+//! let mut styles = Styles::new();
+//! let mut worksheet = Worksheet::new();
+//! worksheet.set_zoom(150);
+//!
+//! let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+//! for row in 0..1000 {
+//!     stream.write_string(row, 0, "Item")?;
+//!     stream.write_number(row, 1, row as f64)?;
+//! }
+//! stream.finish();
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Rows and columns can also have formats:
+//!
+//! ```
+//! use excel_xmlwriter::styles::{Format, Styles};
+//! use excel_xmlwriter::worksheet::Worksheet;
+//!
+//! let mut styles = Styles::new();
+//! let mut worksheet = Worksheet::new();
+//!
+//! let bold = Format::new().set_bold();
+//! let italic = Format::new().set_italic();
+//! worksheet.set_column_format(1, 3, &bold);
+//! worksheet.set_row_format(0, &italic);
+//!
+//! // Unformatted cells in a formatted column take the column format.
+//! assert_eq!(worksheet.cell_xf_index(&mut styles, 1, 2, None), 1);
+//! assert_eq!(worksheet.cell_xf_index(&mut styles, 1, 5, None), 0);
+//!
+//! // The row format takes precedence over the column format.
+//! assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 2, None), 2);
+//! ```
+//!
+//! # Format precedence
+//!
+//! Excel stores the formats of rows, columns and cells independently and
+//! doesn't merge them. The format that is displayed for a cell is resolved
+//! in the following order, as implemented by
+//! [`Worksheet::cell_xf_index()`]:
+//!
+//! 1. The cell's own format.
+//! 2. The row format, set with [`Worksheet::set_row_format()`].
+//! 3. The column format, set with [`Worksheet::set_column_format()`].
+//! 4. The default format.
+//!
+//! Empty cells display the row format if there is one, and otherwise the
+//! column format. Since a written cell always stores a single format, a cell
+//! written without a format takes the resolved row or column format so that
+//! writing it doesn't change how it is displayed.
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, Write};
+#[cfg(feature = "drawings")]
+use std::path::Path;
+
+#[cfg(feature = "drawings")]
+use crate::button::{Button, ControlProperties};
+#[cfg(feature = "charts")]
+use crate::chart::Chart;
+use crate::conditional_format::ConditionalFormat;
+#[cfg(feature = "drawings")]
+use crate::constants::{MARKUP_COMPATIBILITY_NS, SPREADSHEET_DRAWING_NS};
+use crate::constants::{RELATIONSHIPS_NS, SPREADSHEETML_NS, X14_NS, XM_NS};
+use crate::data_validation::DataValidation;
+#[cfg(feature = "drawings")]
+use crate::drawing::{self, Drawing};
+use crate::error::XlsxError;
+#[cfg(feature = "drawings")]
+use crate::image::Image;
+#[cfg(feature = "drawings")]
+use crate::note::{Comments, Note};
+#[cfg(feature = "drawings")]
+use crate::ole_object::{Embedding, OleObject};
+use crate::page_setup::PageSetup;
+use crate::protection::ProtectionOptions;
+use crate::relationship::{self, Relationships};
+use crate::sanitize::{needs_quote_prefix, SanitizePolicy};
+use crate::shared_strings::SharedStringTable;
+use crate::sparkline::Sparkline;
+use crate::styles::{Color, Format, Styles};
+use crate::table::Table;
+#[cfg(feature = "drawings")]
+use crate::threaded_comment::{ThreadedComment, ThreadedComments};
+use crate::utility::{rowcol_to_cell, COL_MAX, ROW_MAX};
+#[cfg(feature = "drawings")]
+use crate::vml::{self, Position, VmlDrawing};
+use crate::XMLWriter;
+
+// Excel's default column width, in character units of the default font.
+const DEFAULT_COL_WIDTH: f64 = 8.43;
+
+// Excel's limit on the length of a hyperlink URL.
+const MAX_URL_LENGTH: usize = 2079;
+
+// Excel's maximum outline level of grouped rows and columns.
+const MAX_OUTLINE_LEVEL: u8 = 7;
+
+// The URL schemes of external hyperlinks.
+const URL_SCHEMES: [&str; 5] = ["http://", "https://", "ftp://", "ftps://", "mailto:"];
+
+// The number of rows between the `tracing` events of the row batches.
+#[cfg(feature = "tracing")]
+const TRACE_ROW_BATCH: u64 = 10_000;
+
+// The data of a worksheet cell.
+#[derive(Clone, Debug, PartialEq)]
+enum CellData {
+    String(String),
+    Number(f64),
+    Formula(String, f64),
+    // An array formula and the range of its results. Dynamic array formulas
+    // spill their results and are marked with cell metadata.
+    ArrayFormula {
+        formula: String,
+        range: String,
+        dynamic: bool,
+    },
+    // An image embedded in the cell, which is written as a rich value.
+    #[cfg(feature = "drawings")]
+    EmbeddedImage(Box<Image>),
+    Blank,
+}
+
+/// The types of Excel's error checking warnings, the green triangles in the
+/// corner of cells, that can be ignored for ranges with
+/// [`Worksheet::ignore_error_range()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IgnoreError {
+    /// A formula that results in an error.
+    EvalError,
+
+    /// A text date with a two digit year.
+    TwoDigitTextYear,
+
+    /// A number stored as text.
+    NumberStoredAsText,
+
+    /// A formula that is inconsistent with the formulas around it.
+    Formula,
+
+    /// A formula that omits adjacent cells.
+    FormulaRange,
+
+    /// An unlocked cell with a formula.
+    UnlockedFormula,
+
+    /// A formula that refers to empty cells.
+    EmptyCellReference,
+
+    /// A value that isn't valid for the cell's data validation list.
+    ListDataValidation,
+
+    /// A formula that is inconsistent with the formula of its table column.
+    CalculatedColumn,
+}
+
+impl IgnoreError {
+    // The attribute of the <ignoredError> element.
+    fn attribute(self) -> &'static str {
+        match self {
+            IgnoreError::EvalError => "evalError",
+            IgnoreError::TwoDigitTextYear => "twoDigitTextYear",
+            IgnoreError::NumberStoredAsText => "numberStoredAsText",
+            IgnoreError::Formula => "formula",
+            IgnoreError::FormulaRange => "formulaRange",
+            IgnoreError::UnlockedFormula => "unlockedFormula",
+            IgnoreError::EmptyCellReference => "emptyCellReference",
+            IgnoreError::ListDataValidation => "listDataValidation",
+            IgnoreError::CalculatedColumn => "calculatedColumn",
+        }
+    }
+}
+
+// The target of a hyperlink.
+#[derive(Clone, Debug, PartialEq)]
+enum Hyperlink {
+    // A URL, with an optional `#` anchor, which is stored in the worksheet
+    // relationships.
+    External { url: String, anchor: Option<String> },
+
+    // A location in the workbook, like `Sheet2!A1`.
+    Internal(String),
+}
+
+// The hyperlinks of a streaming worksheet, as compact records in a memory
+// buffer, or in a file after StreamingWorksheet::spill_hyperlinks(), which
+// are read back for the <hyperlinks> element and the relationships. Each
+// record is the row and column, the kind of hyperlink, and its strings with
+// their lengths.
+struct HyperlinkSpill {
+    file: BufWriter<Box<dyn SpillFile>>,
+    count: usize,
+    external_count: usize,
+}
+
+// The buffer or file of the hyperlink records.
+trait SpillFile: Read + Write + Seek + Send {}
+
+impl<T: Read + Write + Seek + Send> SpillFile for T {}
+
+// The kinds of hyperlink records: an external URL without and with an
+// anchor, and a location in the workbook.
+const SPILL_EXTERNAL: u8 = 0;
+const SPILL_EXTERNAL_ANCHOR: u8 = 1;
+const SPILL_INTERNAL: u8 = 2;
+
+// The size of the spill buffer. It is larger than any record, which has at
+// most 4 bytes for each of the 2079 characters of the URL, so a record is
+// either buffered whole or, if flushing the buffer fails, not at all.
+const SPILL_BUFFER_SIZE: usize = 64 * 1024;
+
+impl HyperlinkSpill {
+    fn new() -> HyperlinkSpill {
+        HyperlinkSpill {
+            file: BufWriter::with_capacity(SPILL_BUFFER_SIZE, Box::new(Cursor::new(vec![]))),
+            count: 0,
+            external_count: 0,
+        }
+    }
+
+    // Move the records to a file, which is truncated first, and write the
+    // next records to it.
+    fn spill_to(&mut self, mut file: File) -> io::Result<()> {
+        file.set_len(0)?;
+        file.rewind()?;
+
+        self.file.flush()?;
+        let records = self.file.get_mut();
+        records.rewind()?;
+        io::copy(records, &mut file)?;
+
+        self.file = BufWriter::with_capacity(SPILL_BUFFER_SIZE, Box::new(file));
+        Ok(())
+    }
+
+    // Add the record of a hyperlink.
+    fn push(&mut self, row: u32, col: u16, hyperlink: &Hyperlink) -> io::Result<()> {
+        let mut record = vec![];
+        record.extend_from_slice(&row.to_le_bytes());
+        record.extend_from_slice(&col.to_le_bytes());
+
+        match hyperlink {
+            Hyperlink::External { url, anchor: None } => {
+                record.push(SPILL_EXTERNAL);
+                push_spill_string(&mut record, url);
+            }
+            Hyperlink::External {
+                url,
+                anchor: Some(anchor),
+            } => {
+                record.push(SPILL_EXTERNAL_ANCHOR);
+                push_spill_string(&mut record, url);
+                push_spill_string(&mut record, anchor);
+            }
+            Hyperlink::Internal(location) => {
+                record.push(SPILL_INTERNAL);
+                push_spill_string(&mut record, location);
+            }
+        }
+
+        self.file.write_all(&record)?;
+
+        self.count += 1;
+        if matches!(hyperlink, Hyperlink::External { .. }) {
+            self.external_count += 1;
+        }
+        Ok(())
+    }
+
+    // Read back the hyperlinks, in the order they were added. The records
+    // are read once the part has been written, so an I/O error is a panic.
+    fn for_each(&mut self, f: impl FnMut(u32, u16, Hyperlink)) {
+        if let Err(error) = self.read_records(f) {
+            panic!("the hyperlinks couldn't be read from the spill file: {error}");
+        }
+    }
+
+    fn read_records(&mut self, mut f: impl FnMut(u32, u16, Hyperlink)) -> io::Result<()> {
+        self.file.flush()?;
+        let records = self.file.get_mut();
+        records.rewind()?;
+        let mut reader = BufReader::new(records);
+
+        for _ in 0..self.count {
+            let mut row = [0; 4];
+            let mut col = [0; 2];
+            let mut kind = [0; 1];
+            reader.read_exact(&mut row)?;
+            reader.read_exact(&mut col)?;
+            reader.read_exact(&mut kind)?;
+
+            let hyperlink = match kind[0] {
+                SPILL_INTERNAL => Hyperlink::Internal(read_spill_string(&mut reader)?),
+                kind => {
+                    let url = read_spill_string(&mut reader)?;
+                    let anchor = match kind {
+                        SPILL_EXTERNAL_ANCHOR => Some(read_spill_string(&mut reader)?),
+                        _ => None,
+                    };
+                    Hyperlink::External { url, anchor }
+                }
+            };
+
+            f(u32::from_le_bytes(row), u16::from_le_bytes(col), hyperlink);
+        }
+
+        Ok(())
+    }
+
+    // Write the <hyperlinks> element, like Worksheet::write_hyperlinks().
+    fn write_hyperlinks(&mut self, writer: &mut XMLWriter<impl Write>) {
+        if self.count == 0 {
+            return;
+        }
+
+        writer.xml_start_tag_only("hyperlinks");
+
+        let mut rel_index = 0;
+        self.for_each(|row, col, hyperlink| {
+            write_hyperlink(writer, row, col, &hyperlink, &mut rel_index);
+        });
+
+        writer.xml_end_tag("hyperlinks");
+    }
+}
+
+// An image or chart in the drawing of the worksheet.
+#[cfg(feature = "drawings")]
+#[derive(Clone, Debug, PartialEq)]
+enum DrawingObject {
+    Image(Image),
+    #[cfg(feature = "charts")]
+    Chart(Chart),
+}
+
+#[cfg(feature = "drawings")]
+impl DrawingObject {
+    // The image of the object, if it isn't a chart.
+    fn image(&self) -> Option<&Image> {
+        match self {
+            DrawingObject::Image(image) => Some(image),
+            #[cfg(feature = "charts")]
+            DrawingObject::Chart(_) => None,
+        }
+    }
+}
+
+// The frozen or split panes of the worksheet view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Panes {
+    // The number of rows and columns above and to the left of the split.
+    Frozen {
+        row: u32,
+        col: u16,
+    },
+
+    // The split positions in twips, and the top left cell of the bottom
+    // right pane.
+    Split {
+        x_split: u32,
+        y_split: u32,
+        row: u32,
+        col: u16,
+    },
+}
+
+// The properties of a column in the <cols> element.
+#[derive(Clone, Debug, PartialEq)]
+struct Column {
+    width: f64,
+    format: Option<Format>,
+    hidden: bool,
+    outline_level: u8,
+    collapsed: bool,
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Column {
+            width: DEFAULT_COL_WIDTH,
+            format: None,
+            hidden: false,
+            outline_level: 0,
+            collapsed: false,
+        }
+    }
+}
+
+// The visibility and outline properties of a row in its <row> element.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct RowOptions {
+    hidden: bool,
+    outline_level: u8,
+    collapsed: bool,
+}
+
+// The <outlinePr> settings of the worksheet, which are all on by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OutlineSettings {
+    show_symbols: bool,
+    summary_below: bool,
+    summary_right: bool,
+}
+
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        OutlineSettings {
+            show_symbols: true,
+            summary_below: true,
+            summary_right: true,
+        }
+    }
+}
+
+// A worksheet cell and its optional format.
+#[derive(Clone, Debug, PartialEq)]
+struct Cell {
+    data: CellData,
+    format: Option<Format>,
+}
+
+// A zero-indexed range of cells as (first_row, first_col, last_row, last_col).
+type CellRange = (u32, u16, u32, u16);
+
+// The properties of a <col> range as (width, xf_index, hidden, outline_level,
+// collapsed).
+type ColumnProperties = (f64, Option<u32>, bool, u8, bool);
+
+/// A worksheet and the writer for its XML part.
+#[derive(Debug, Default)]
+pub struct Worksheet {
+    cells: BTreeMap<u32, BTreeMap<u16, Cell>>,
+    columns: BTreeMap<u16, Column>,
+    row_formats: BTreeMap<u32, Format>,
+    row_options: BTreeMap<u32, RowOptions>,
+    outline_settings: OutlineSettings,
+    tab_color: Option<Color>,
+    merged_ranges: Vec<(u32, u16, u32, u16)>,
+    hyperlinks: BTreeMap<(u32, u16), Hyperlink>,
+    autofilter: Option<(u32, u16, u32, u16)>,
+    panes: Option<Panes>,
+    zoom: Option<u16>,
+    page_setup: PageSetup,
+    protection: Option<(String, ProtectionOptions)>,
+    conditional_formats: Vec<(CellRange, Vec<ConditionalFormat>)>,
+    data_validations: Vec<(CellRange, DataValidation)>,
+    tables: Vec<Table>,
+    #[cfg(feature = "drawings")]
+    notes: BTreeMap<(u32, u16), Note>,
+    #[cfg(feature = "drawings")]
+    threaded_comments: BTreeMap<(u32, u16), ThreadedComment>,
+    #[cfg(feature = "drawings")]
+    notes_number: Option<u32>,
+    #[cfg(feature = "drawings")]
+    default_note_author: Option<String>,
+    #[cfg(feature = "drawings")]
+    buttons: Vec<(u32, u16, Button)>,
+    #[cfg(feature = "drawings")]
+    control_number: Option<u32>,
+    #[cfg(feature = "drawings")]
+    objects: Vec<(u32, u16, OleObject)>,
+    #[cfg(feature = "drawings")]
+    embedding_number: Option<u32>,
+    #[cfg(feature = "drawings")]
+    drawing_objects: Vec<(u32, u16, DrawingObject)>,
+    sparklines: BTreeMap<(u32, u16), Sparkline>,
+    #[cfg(feature = "drawings")]
+    drawing_number: Option<u32>,
+    #[cfg(feature = "drawings")]
+    image_number: Option<u32>,
+    #[cfg(feature = "drawings")]
+    value_metadata_number: Option<u32>,
+    ignored_errors: BTreeMap<IgnoreError, Vec<CellRange>>,
+}
+
+impl Worksheet {
+    /// Create a new, empty, worksheet.
+    pub fn new() -> Worksheet {
+        Worksheet::default()
+    }
+
+    /// Write a string to a zero-indexed cell. The string is stored in the
+    /// shared string table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_string(&mut self, row: u32, col: u16, string: &str) {
+        self.insert_cell(row, col, CellData::String(string.to_string()), None);
+    }
+
+    /// Write a string to a zero-indexed cell with a format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_string_with_format(&mut self, row: u32, col: u16, string: &str, format: &Format) {
+        let data = CellData::String(string.to_string());
+        self.insert_cell(row, col, data, Some(format));
+    }
+
+    /// Write a number to a zero-indexed cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits, or if the
+    /// number is NaN or infinite, which Excel can't represent.
+    pub fn write_number(&mut self, row: u32, col: u16, number: f64) {
+        assert_finite(row, col, number);
+        self.insert_cell(row, col, CellData::Number(number), None);
+    }
+
+    /// Write a number to a zero-indexed cell with a format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits, or if the
+    /// number is NaN or infinite, which Excel can't represent.
+    pub fn write_number_with_format(&mut self, row: u32, col: u16, number: f64, format: &Format) {
+        assert_finite(row, col, number);
+        self.insert_cell(row, col, CellData::Number(number), Some(format));
+    }
+
+    /// Write a formula to a zero-indexed cell. The leading `=` is optional.
+    /// The formula result is written as 0 and Excel recalculates it when the
+    /// file is opened.
+    ///
+    /// Functions added in newer versions of Excel, like `XLOOKUP`, need an
+    /// `_xlfn.` prefix in the file, which can be added with
+    /// [`prefix_future_functions()`](crate::utility::prefix_future_functions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_formula(&mut self, row: u32, col: u16, formula: &str) {
+        self.insert_cell(row, col, formula_data(formula), None);
+    }
+
+    /// Write a formula to a zero-indexed cell with a format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_formula_with_format(
+        &mut self,
+        row: u32,
+        col: u16,
+        formula: &str,
+        format: &Format,
+    ) {
+        self.insert_cell(row, col, formula_data(formula), Some(format));
+    }
+
+    /// Write an array, or CSE, formula to a zero-indexed range of cells. The
+    /// formula is stored in the first cell, and the other cells of the range
+    /// are written as 0 until Excel recalculates the results. The leading
+    /// `=` and Excel's `{}` array braces are optional.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// worksheet.write_array_formula(0, 2, 2, 2, "{=A1:A3*B1:B3}");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn write_array_formula(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        formula: &str,
+    ) {
+        let range = (first_row, first_col, last_row, last_col);
+        self.insert_array_formula(range, formula, None, false);
+    }
+
+    /// Write an array formula to a zero-indexed range of cells with a format,
+    /// which is used for all the cells of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn write_array_formula_with_format(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        formula: &str,
+        format: &Format,
+    ) {
+        let range = (first_row, first_col, last_row, last_col);
+        self.insert_array_formula(range, formula, Some(format), false);
+    }
+
+    /// Write a dynamic array formula, which spills its results into the cells
+    /// around it, to a zero-indexed range of cells. The range is the initial
+    /// size of the results, usually a single cell, and Excel resizes it when
+    /// the formula is recalculated.
+    ///
+    /// The cell is marked with the cell metadata of the workbook's
+    /// [`Metadata`](crate::metadata::Metadata) part, which needs to be
+    /// written if [`has_dynamic_arrays()`](Worksheet::has_dynamic_arrays) is
+    /// `true`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn write_dynamic_array_formula(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        formula: &str,
+    ) {
+        let range = (first_row, first_col, last_row, last_col);
+        self.insert_array_formula(range, formula, None, true);
+    }
+
+    /// Write a dynamic array formula to a zero-indexed range of cells with a
+    /// format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn write_dynamic_array_formula_with_format(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        formula: &str,
+        format: &Format,
+    ) {
+        let range = (first_row, first_col, last_row, last_col);
+        self.insert_array_formula(range, formula, Some(format), true);
+    }
+
+    /// Check whether the worksheet has dynamic array formulas, which need the
+    /// workbook's [`Metadata`](crate::metadata::Metadata) part.
+    pub fn has_dynamic_arrays(&self) -> bool {
+        self.cells
+            .values()
+            .flat_map(|cols| cols.values())
+            .any(|cell| matches!(cell.data, CellData::ArrayFormula { dynamic: true, .. }))
+    }
+
+    // Store an array formula in the first cell of a range, and 0 in the other
+    // cells, like Excel.
+    fn insert_array_formula(
+        &mut self,
+        (first_row, first_col, last_row, last_col): CellRange,
+        formula: &str,
+        format: Option<&Format>,
+        dynamic: bool,
+    ) {
+        let (first_row, first_col, last_row, last_col) =
+            check_range(first_row, first_col, last_row, last_col);
+
+        for row in first_row..=last_row {
+            for col in first_col..=last_col {
+                self.insert_cell(row, col, CellData::Number(0.0), format);
+            }
+        }
+
+        let formula = formula
+            .strip_prefix('{')
+            .and_then(|formula| formula.strip_suffix('}'))
+            .unwrap_or(formula);
+        let formula = formula.strip_prefix('=').unwrap_or(formula);
+
+        let data = CellData::ArrayFormula {
+            formula: formula.to_string(),
+            range: sqref((first_row, first_col, last_row, last_col)),
+            dynamic,
+        };
+        self.insert_cell(first_row, first_col, data, format);
+    }
+
+    /// Write a hyperlink to a zero-indexed cell. The cell text is the URL,
+    /// without a `mailto:` or `internal:` prefix, in the Hyperlink cell
+    /// style.
+    ///
+    /// External URLs start with `http://`, `https://`, `ftp://`, `ftps://` or
+    /// `mailto:` and can have a `#` anchor. Links to a location in the
+    /// workbook start with `internal:`, like `internal:Sheet2!A1`.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::InvalidUrl`] if the URL doesn't have one of the supported
+    /// prefixes or is longer than Excel's limit of 2079 characters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_url(&mut self, row: u32, col: u16, url: &str) -> Result<(), XlsxError> {
+        self.write_url_with_text(row, col, url, url_text(url))
+    }
+
+    /// Write a hyperlink to a zero-indexed cell with alternative text for the
+    /// cell. See [`write_url()`](Worksheet::write_url).
+    ///
+    /// ```
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// # fn main() -> Result<(), excel_xmlwriter::error::XlsxError> {
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// worksheet.write_url(0, 0, "https://www.rust-lang.org/")?;
+    /// worksheet.write_url_with_text(1, 0, "mailto:jmcnamara@cpan.org", "Email")?;
+    /// worksheet.write_url_with_text(2, 0, "internal:Sheet2!A1", "Details")?;
+    ///
+    /// // The external links are stored in the worksheet relationships.
+    /// assert_eq!(worksheet.relationships().len(), 2);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::InvalidUrl`] if the URL isn't supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_url_with_text(
+        &mut self,
+        row: u32,
+        col: u16,
+        url: &str,
+        text: &str,
+    ) -> Result<(), XlsxError> {
+        let hyperlink = parse_url(url)?;
+
+        let data = CellData::String(text.to_string());
+        self.insert_cell(row, col, data, Some(&Format::new().set_hyperlink()));
+        self.hyperlinks.insert((row, col), hyperlink);
+
+        Ok(())
+    }
+
+    /// The relationships of the worksheet, for the
+    /// `xl/worksheets/_rels/sheetN.xml.rels` part. The part is only needed if
+    /// the relationships aren't empty.
+    pub fn relationships(&self) -> Relationships {
+        let mut relationships = Relationships::new();
+
+        for hyperlink in self.hyperlinks.values() {
+            if let Hyperlink::External { url, .. } = hyperlink {
+                relationships.add_worksheet_relationship("hyperlink", url, Some("External"));
+            }
+        }
+
+        self.add_part_relationships(&mut relationships);
+
+        relationships
+    }
+
+    // Add the relationships that follow those of the external hyperlinks, to
+    // the other parts of the worksheet.
+    fn add_part_relationships(&self, relationships: &mut Relationships) {
+        #[cfg(feature = "drawings")]
+        if !self.drawing_objects.is_empty() {
+            let target = format!("../drawings/drawing{}.xml", self.drawing_number());
+            relationships.add_worksheet_relationship("drawing", &target, None);
+        }
+
+        #[cfg(feature = "drawings")]
+        if self.has_vml() {
+            let target = format!("../drawings/vmlDrawing{}.vml", self.notes_number());
+            relationships.add_worksheet_relationship("vmlDrawing", &target, None);
+        }
+
+        for table in &self.tables {
+            let target = format!("../tables/table{}.xml", table.number());
+            relationships.add_worksheet_relationship("table", &target, None);
+        }
+
+        #[cfg(feature = "drawings")]
+        if self.has_notes() {
+            let target = format!("../comments{}.xml", self.notes_number());
+            relationships.add_worksheet_relationship("comments", &target, None);
+        }
+
+        #[cfg(feature = "drawings")]
+        if !self.threaded_comments.is_empty() {
+            let target = format!(
+                "../threadedComments/threadedComment{}.xml",
+                self.notes_number()
+            );
+            relationships.add_office_relationship("2017/10/relationships/threadedComment", &target);
+        }
+
+        #[cfg(feature = "drawings")]
+        for control in self.control_properties() {
+            let target = format!("../ctrlProps/ctrlProp{}.xml", control.number());
+            relationships.add_worksheet_relationship("ctrlProp", &target, None);
+        }
+
+        #[cfg(feature = "drawings")]
+        {
+            let first_embedding = self.embedding_number.unwrap_or(1);
+            let previews = self.object_previews();
+            for (index, ((_, _, object), (preview, _))) in
+                self.objects.iter().zip(&previews).enumerate()
+            {
+                let part_name = object.embedding_part_name(first_embedding + index as u32);
+                let target = part_name.replacen("xl/", "../", 1);
+                relationships.add_worksheet_relationship(object.relationship_type(), &target, None);
+                let target = preview.replacen("xl/", "../", 1);
+                relationships.add_worksheet_relationship("image", &target, None);
+            }
+        }
+    }
+
+    /// Add an autofilter to a range of zero-indexed cells. The first row of
+    /// the range is the header row that gets the filter dropdowns. The first
+    /// and last cells can be given in either order.
+    ///
+    /// Excel also requires a hidden `_xlnm._FilterDatabase` defined name for
+    /// the range, which is added to the workbook with
+    /// [`Workbook::add_autofilter_name()`](crate::workbook::Workbook::add_autofilter_name).
+    ///
+    /// ```
+    /// # use excel_xmlwriter::workbook::Workbook;
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut workbook = Workbook::new();
+    /// let index = workbook.add_sheet("Sheet1");
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// worksheet.autofilter(0, 0, 50, 3);
+    ///
+    /// if let Some((first_row, first_col, last_row, last_col)) = worksheet.autofilter_range() {
+    ///     workbook.add_autofilter_name(index, first_row, first_col, last_row, last_col);
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn autofilter(&mut self, first_row: u32, first_col: u16, last_row: u32, last_col: u16) {
+        self.autofilter = Some(check_range(first_row, first_col, last_row, last_col));
+    }
+
+    /// The zero-indexed autofilter range as `(first_row, first_col,
+    /// last_row, last_col)`, if there is one.
+    pub fn autofilter_range(&self) -> Option<(u32, u16, u32, u16)> {
+        self.autofilter
+    }
+
+    /// Add a conditional format to a range of zero-indexed cells. Rules added
+    /// to the same range are written together, and rules are prioritized in
+    /// the order they are added, across all ranges. The first and last cells
+    /// can be given in either order.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::conditional_format::{ConditionalFormatBlanks, ConditionalFormatFormula};
+    /// # use excel_xmlwriter::styles::{Color, Format};
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    /// let yellow = Format::new().set_background_color(Color::Rgb(0xFFEB9C));
+    ///
+    /// worksheet.add_conditional_format(1, 0, 20, 3, ConditionalFormatBlanks::new().set_format(&yellow));
+    /// worksheet.add_conditional_format(
+    ///     1,
+    ///     0,
+    ///     20,
+    ///     3,
+    ///     ConditionalFormatFormula::new("=$D2<0").set_format(&yellow),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn add_conditional_format(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        rule: impl Into<ConditionalFormat>,
+    ) {
+        let range = check_range(first_row, first_col, last_row, last_col);
+
+        match self
+            .conditional_formats
+            .iter_mut()
+            .find(|(existing, _)| *existing == range)
+        {
+            Some((_, rules)) => rules.push(rule.into()),
+            None => self.conditional_formats.push((range, vec![rule.into()])),
+        }
+    }
+
+    /// Add a data validation to a range of zero-indexed cells. The first and
+    /// last cells can be given in either order.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::data_validation::DataValidation;
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// # fn main() -> Result<(), excel_xmlwriter::error::XlsxError> {
+    /// let mut worksheet = Worksheet::new();
+    /// let data_validation = DataValidation::new()
+    ///     .allow_list_strings(&["Low", "Medium", "High"])
+    ///     .set_error_title("Priority");
+    ///
+    /// worksheet.add_data_validation(1, 2, 50, 2, &data_validation)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::DataValidationTooLong`] if a title, message or the list
+    /// of values is longer than Excel allows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn add_data_validation(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        data_validation: &DataValidation,
+    ) -> Result<(), XlsxError> {
+        let range = check_range(first_row, first_col, last_row, last_col);
+        data_validation.validate(&sqref(range))?;
+
+        self.data_validations.push((range, data_validation.clone()));
+        Ok(())
+    }
+
+    /// Add a table to a range of zero-indexed cells. If the table has a
+    /// header row, the column headers are written to the first row of the
+    /// range, keeping the formats of any cells that are already there. The
+    /// first and last cells can be given in either order.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::table::Table;
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// # fn main() -> Result<(), excel_xmlwriter::error::XlsxError> {
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// worksheet.add_table(0, 0, 10, 1, &Table::new(1).set_columns(&["Item", "Cost"]))?;
+    /// worksheet.write_string(1, 0, "Paper");
+    /// worksheet.write_number(1, 1, 4.5);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`XlsxError::TableOverlap`] if the range overlaps another table.
+    /// - [`XlsxError::TableColumnCount`] if the table has more column headers
+    ///   than the range has columns.
+    /// - [`XlsxError::DuplicateTableColumn`] if two column headers are the
+    ///   same.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn add_table(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        table: &Table,
+    ) -> Result<(), XlsxError> {
+        let range = check_range(first_row, first_col, last_row, last_col);
+        let (first_row, first_col, last_row, last_col) = range;
+
+        let overlap = self.tables.iter().map(Table::range).find(|existing| {
+            first_row <= existing.2
+                && existing.0 <= last_row
+                && first_col <= existing.3
+                && existing.1 <= last_col
+        });
+        if let Some(existing) = overlap {
+            return Err(XlsxError::TableOverlap {
+                range: range_to_string(range),
+                existing: range_to_string(existing),
+            });
+        }
+
+        let count = table.columns().len();
+        if count > (last_col - first_col) as usize + 1 {
+            return Err(XlsxError::TableColumnCount {
+                range: range_to_string(range),
+                count,
+            });
+        }
+
+        let mut table = table.clone();
+        table.set_range(range);
+
+        let mut headers = BTreeSet::new();
+        for column in table.columns() {
+            if !headers.insert(column.to_lowercase()) {
+                return Err(XlsxError::DuplicateTableColumn(column.clone()));
+            }
+        }
+
+        if table.has_header_row() {
+            for (col, column) in (first_col..=last_col).zip(table.columns()) {
+                let format = self
+                    .cells
+                    .get(&first_row)
+                    .and_then(|cols| cols.get(&col))
+                    .and_then(|cell| cell.format.clone());
+                let data = CellData::String(column.clone());
+                self.insert_cell(first_row, col, data, format.as_ref());
+            }
+        }
+
+        self.tables.push(table);
+        Ok(())
+    }
+
+    /// The tables of the worksheet, in the order they were added, to be
+    /// written as parts of their own. See the [`table`](crate::table)
+    /// module.
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// Add a sparkline to a zero-indexed cell, replacing any sparkline that
+    /// is already there. See the [`sparkline`](crate::sparkline) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn add_sparkline(&mut self, row: u32, col: u16, sparkline: &Sparkline) {
+        check_cell(row, col);
+
+        self.sparklines.insert((row, col), sparkline.clone());
+    }
+
+    /// Add a note to a zero-indexed cell, replacing any note or threaded
+    /// comment that is already there. See the [`note`](crate::note) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn add_note(&mut self, row: u32, col: u16, note: &Note) {
+        check_cell(row, col);
+
+        self.threaded_comments.remove(&(row, col));
+        self.notes.insert((row, col), note.clone());
+    }
+
+    /// Add a threaded comment to a zero-indexed cell, replacing any note or
+    /// threaded comment that is already there. See the
+    /// [`threaded_comment`](crate::threaded_comment) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn add_threaded_comment(&mut self, row: u32, col: u16, comment: &ThreadedComment) {
+        check_cell(row, col);
+
+        self.notes.remove(&(row, col));
+        self.threaded_comments.insert((row, col), comment.clone());
+    }
+
+    /// Insert a form control button at the top left corner of a zero-indexed
+    /// cell, with its caption and the name of the macro that it runs, like
+    /// `Button1_Click`. The button has the size of a default cell. See the
+    /// [`button`](crate::button) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn insert_button(&mut self, row: u32, col: u16, caption: &str, macro_name: &str) {
+        self.add_button(row, col, &Button::new(caption).set_macro(macro_name));
+    }
+
+    /// Add a form control button at the top left corner of a zero-indexed
+    /// cell. See the [`button`](crate::button) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn add_button(&mut self, row: u32, col: u16, button: &Button) {
+        check_cell(row, col);
+
+        self.buttons.push((row, col, button.clone()));
+    }
+
+    /// Embed a file, such as a PDF or another workbook, at the top left
+    /// corner of a zero-indexed cell, where it is shown as a document icon.
+    /// See the [`ole_object`](crate::ole_object) module.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`XlsxError::ObjectRead`] if the file can't be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn embed_object(
+        &mut self,
+        row: u32,
+        col: u16,
+        path: impl AsRef<Path>,
+    ) -> Result<(), XlsxError> {
+        let object = OleObject::new(path)?;
+        self.add_object(row, col, &object);
+
+        Ok(())
+    }
+
+    /// Add an embedded object, with its preview image, at the top left
+    /// corner of a zero-indexed cell. See the
+    /// [`ole_object`](crate::ole_object) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn add_object(&mut self, row: u32, col: u16, object: &OleObject) {
+        check_cell(row, col);
+
+        self.objects.push((row, col, object.clone()));
+    }
+
+    /// Set the author of the notes that don't have one. The default is
+    /// `Author`.
+    #[cfg(feature = "drawings")]
+    pub fn set_default_note_author(&mut self, author: &str) {
+        self.default_note_author = Some(author.to_string());
+    }
+
+    /// Set the number of the `xl/commentsN.xml`,
+    /// `xl/drawings/vmlDrawingN.vml` and
+    /// `xl/threadedComments/threadedCommentN.xml` parts of the worksheet's
+    /// notes and threaded comments, which is unique in the workbook. The
+    /// default is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    #[cfg(feature = "drawings")]
+    pub fn set_notes_number(&mut self, number: u32) {
+        assert!(number > 0, "notes part numbers start at 1");
+
+        self.notes_number = Some(number);
+    }
+
+    /// The `xl/commentsN.xml` part with the text of the notes, if the
+    /// worksheet has notes or threaded comments. Threaded comments are
+    /// written as notes for older versions of Excel.
+    #[cfg(feature = "drawings")]
+    pub fn comments(&self) -> Option<Comments> {
+        if !self.has_notes() {
+            return None;
+        }
+
+        let notes = self.legacy_notes();
+        let notes = notes.iter().map(|((row, col), note)| (*row, *col, note));
+        let default_author = self.default_note_author.as_deref().unwrap_or("Author");
+
+        Some(Comments::new(self.notes_number(), notes, default_author))
+    }
+
+    /// The `xl/drawings/vmlDrawingN.vml` part with the buttons and the boxes
+    /// of the notes, if the worksheet has buttons, notes or threaded
+    /// comments. The part has the number of the notes parts.
+    #[cfg(feature = "drawings")]
+    pub fn vml_drawing(&self) -> Option<VmlDrawing> {
+        if !self.has_vml() {
+            return None;
+        }
+
+        let mut vml_drawing = VmlDrawing::new(self.notes_number());
+        for (row, col, button) in &self.buttons {
+            vml_drawing.add_button(*row, *col, button);
+        }
+        for ((row, col), note) in &self.legacy_notes() {
+            vml_drawing.add_note(*row, *col, note);
+        }
+        for ((row, col, _), (part_name, preview)) in self.objects.iter().zip(self.object_previews())
+        {
+            vml_drawing.add_object(*row, *col, preview, &part_name);
+        }
+
+        Some(vml_drawing)
+    }
+
+    /// The `xl/threadedComments/threadedCommentN.xml` part with the threaded
+    /// comments, if the worksheet has threaded comments.
+    #[cfg(feature = "drawings")]
+    pub fn threaded_comments(&self) -> Option<ThreadedComments> {
+        if self.threaded_comments.is_empty() {
+            return None;
+        }
+
+        let comments = self
+            .threaded_comments
+            .iter()
+            .map(|((row, col), comment)| (*row, *col, comment));
+
+        Some(ThreadedComments::new(self.notes_number(), comments))
+    }
+
+    // The number of the notes parts, which defaults to 1.
+    #[cfg(feature = "drawings")]
+    fn notes_number(&self) -> u32 {
+        self.notes_number.unwrap_or(1)
+    }
+
+    // Check if the worksheet needs the notes parts, for its notes or the
+    // notes of its threaded comments.
+    #[cfg(feature = "drawings")]
+    fn has_notes(&self) -> bool {
+        !self.notes.is_empty() || !self.threaded_comments.is_empty()
+    }
+
+    // Check if the worksheet needs the VML drawing, for its notes, buttons
+    // or embedded objects.
+    #[cfg(feature = "drawings")]
+    fn has_vml(&self) -> bool {
+        self.has_notes() || !self.buttons.is_empty() || !self.objects.is_empty()
+    }
+
+    // The number of relationships in relationships() before those of the
+    // buttons, which are followed by those of the embedded objects, after
+    // the given number of external hyperlink relationships.
+    #[cfg(feature = "drawings")]
+    fn control_rel_offset(&self, hyperlinks: usize) -> usize {
+        hyperlinks
+            + usize::from(!self.drawing_objects.is_empty())
+            + usize::from(self.has_vml())
+            + self.tables.len()
+            + usize::from(self.has_notes())
+            + usize::from(!self.threaded_comments.is_empty())
+    }
+
+    /// Set the number of the first `xl/ctrlProps/ctrlPropN.xml` part of the
+    /// worksheet's buttons, which are numbered in the order they were added.
+    /// The numbers are unique in the workbook, so the buttons of the next
+    /// worksheet start after the last button of this one. The default is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    #[cfg(feature = "drawings")]
+    pub fn set_control_number(&mut self, number: u32) {
+        assert!(number > 0, "control part numbers start at 1");
+
+        self.control_number = Some(number);
+    }
+
+    /// The `xl/ctrlProps/ctrlPropN.xml` parts of the buttons, in the order
+    /// they were added.
+    #[cfg(feature = "drawings")]
+    pub fn control_properties(&self) -> Vec<ControlProperties> {
+        let first = self.control_number.unwrap_or(1);
+
+        (0..self.buttons.len() as u32)
+            .map(|index| ControlProperties::new(first + index))
+            .collect()
+    }
+
+    /// Set the number of the first `xl/embeddings/oleObjectN` part of the
+    /// worksheet's embedded objects, which are numbered in the order they
+    /// were added. The numbers are unique in the workbook, so the objects of
+    /// the next worksheet start after the last object of this one. The
+    /// default is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    #[cfg(feature = "drawings")]
+    pub fn set_embedding_number(&mut self, number: u32) {
+        assert!(number > 0, "embedding part numbers start at 1");
+
+        self.embedding_number = Some(number);
+    }
+
+    /// The `xl/embeddings/oleObjectN` parts of the embedded objects, in the
+    /// order they were added. Their preview images are in
+    /// [`media()`](Worksheet::media).
+    #[cfg(feature = "drawings")]
+    pub fn embeddings(&self) -> Vec<Embedding> {
+        let first = self.embedding_number.unwrap_or(1);
+
+        self.objects
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, object))| object.embedding(first + index as u32))
+            .collect()
+    }
+
+    // The notes and the notes of the threaded comments, in row and column
+    // order.
+    #[cfg(feature = "drawings")]
+    fn legacy_notes(&self) -> BTreeMap<(u32, u16), Note> {
+        let mut notes = self.notes.clone();
+        for ((row, col), comment) in &self.threaded_comments {
+            let note = comment.legacy_note(self.notes_number(), *row, *col);
+            notes.insert((*row, *col), note);
+        }
+
+        notes
+    }
+
+    /// Insert an image from a file at the top left corner of a zero-indexed
+    /// cell. See the [`drawing`] module.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`XlsxError::ImageRead`] if the file can't be read, and
+    /// [`XlsxError::UnsupportedImage`] if it isn't a PNG, JPEG, GIF or BMP
+    /// image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn insert_image(
+        &mut self,
+        row: u32,
+        col: u16,
+        path: impl AsRef<Path>,
+    ) -> Result<(), XlsxError> {
+        let image = Image::new(path)?;
+        self.add_image(row, col, &image);
+
+        Ok(())
+    }
+
+    /// Add an image, with its scale and alternative text, at the top left
+    /// corner of a zero-indexed cell. See the [`image`](crate::image)
+    /// module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn add_image(&mut self, row: u32, col: u16, image: &Image) {
+        check_cell(row, col);
+
+        self.drawing_objects
+            .push((row, col, DrawingObject::Image(image.clone())));
+    }
+
+    /// Ignore an error checking warning for a zero-indexed cell. See
+    /// [`Worksheet::ignore_error_range()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn ignore_error(&mut self, row: u32, col: u16, error: IgnoreError) {
+        self.ignore_error_range(row, col, row, col, error);
+    }
+
+    /// Ignore an error checking warning, such as a number stored as text,
+    /// for a range of zero-indexed cells, so that Excel doesn't mark the
+    /// cells with a green triangle. The ranges of each type of warning are
+    /// written to a single `<ignoredError>` element.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::worksheet::{IgnoreError, Worksheet};
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// worksheet.write_string(0, 0, "0123");
+    /// worksheet.ignore_error(0, 0, IgnoreError::NumberStoredAsText);
+    /// worksheet.ignore_error_range(1, 2, 10, 2, IgnoreError::FormulaRange);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn ignore_error_range(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        error: IgnoreError,
+    ) {
+        let range = check_range(first_row, first_col, last_row, last_col);
+
+        self.ignored_errors.entry(error).or_default().push(range);
+    }
+
+    /// Embed an image from a file in a zero-indexed cell, where it is
+    /// scaled to fit the cell like Excel's "Place in Cell". The image is a
+    /// rich value, and the workbook needs the parts from the
+    /// [`rich_data`](crate::rich_data) module.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`XlsxError::ImageRead`] if the file can't be read, and
+    /// [`XlsxError::UnsupportedImage`] if it isn't a PNG, JPEG, GIF or BMP
+    /// image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn embed_image_in_cell(
+        &mut self,
+        row: u32,
+        col: u16,
+        path: impl AsRef<Path>,
+    ) -> Result<(), XlsxError> {
+        let image = Image::new(path)?;
+        self.embed_image(row, col, &image);
+
+        Ok(())
+    }
+
+    /// Embed an image, with its alternative text, in a zero-indexed cell.
+    /// The image replaces any data in the cell, and is replaced by any data
+    /// written to the cell afterwards. The scale of the image is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "drawings")]
+    pub fn embed_image(&mut self, row: u32, col: u16, image: &Image) {
+        let data = CellData::EmbeddedImage(Box::new(image.clone()));
+        self.insert_cell(row, col, data, None);
+    }
+
+    /// Check if the worksheet has images embedded in cells, which need the
+    /// rich value parts.
+    #[cfg(feature = "drawings")]
+    pub fn has_embedded_images(&self) -> bool {
+        self.embedded_image_cells().next().is_some()
+    }
+
+    /// Set the `vm` value metadata number of the first image embedded in a
+    /// cell. The images are numbered in row and column order, and the
+    /// numbers are unique in the workbook, so the images of the next
+    /// worksheet start after the last image of this one. The default is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    #[cfg(feature = "drawings")]
+    pub fn set_value_metadata_number(&mut self, number: u32) {
+        assert!(number > 0, "value metadata numbers start at 1");
+
+        self.value_metadata_number = Some(number);
+    }
+
+    /// The `xl/media/imageN` part names and the images embedded in cells, in
+    /// row and column order, for
+    /// [`RichData::add_image()`](crate::rich_data::RichData::add_image). The
+    /// part numbers follow the images of the drawing.
+    #[cfg(feature = "drawings")]
+    pub fn embedded_images(&self) -> Vec<(String, &Image)> {
+        let drawing_images = self
+            .drawing_objects
+            .iter()
+            .filter(|(_, _, object)| matches!(object, DrawingObject::Image(_)))
+            .count();
+        let first = self.image_number.unwrap_or(1) + drawing_images as u32;
+
+        self.embedded_image_cells()
+            .enumerate()
+            .map(|(index, image)| {
+                let part_name = format!(
+                    "xl/media/image{}.{}",
+                    first + index as u32,
+                    image.image_type().extension()
+                );
+                (part_name, image)
+            })
+            .collect()
+    }
+
+    // The `xl/media/imageN` part names and the preview images of the
+    // embedded objects, numbered after the other images.
+    #[cfg(feature = "drawings")]
+    fn object_previews(&self) -> Vec<(String, &Image)> {
+        let first = self.image_number.unwrap_or(1)
+            + self
+                .drawing_objects
+                .iter()
+                .filter(|(_, _, object)| matches!(object, DrawingObject::Image(_)))
+                .count() as u32
+            + self.embedded_image_cells().count() as u32;
+
+        self.objects
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, object))| {
+                let image = object.preview();
+                let part_name = format!(
+                    "xl/media/image{}.{}",
+                    first + index as u32,
+                    image.image_type().extension()
+                );
+                (part_name, image)
+            })
+            .collect()
+    }
+
+    // The images embedded in cells, in row and column order.
+    #[cfg(feature = "drawings")]
+    fn embedded_image_cells(&self) -> impl Iterator<Item = &Image> {
+        self.cells
+            .values()
+            .flat_map(|cols| cols.values())
+            .filter_map(|cell| match &cell.data {
+                CellData::EmbeddedImage(image) => Some(image.as_ref()),
+                _ => None,
+            })
+    }
+
+    /// Insert a chart at the top left corner of a zero-indexed cell, at
+    /// Excel's default size of 480 by 288 pixels. The chart part is written
+    /// separately, see the [`chart`](crate::chart) module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    #[cfg(feature = "charts")]
+    pub fn insert_chart(&mut self, row: u32, col: u16, chart: &Chart) {
+        check_cell(row, col);
+
+        self.drawing_objects
+            .push((row, col, DrawingObject::Chart(chart.clone())));
+    }
+
+    /// Set the number of the `xl/drawings/drawingN.xml` part of the
+    /// worksheet's images and charts, which is unique in the workbook. The
+    /// default is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    #[cfg(feature = "drawings")]
+    pub fn set_drawing_number(&mut self, number: u32) {
+        assert!(number > 0, "drawing part numbers start at 1");
+
+        self.drawing_number = Some(number);
+    }
+
+    /// Set the number of the first `xl/media/imageN` part of the worksheet's
+    /// images, which are numbered in the order they were added. The numbers
+    /// are unique in the workbook, so the images of the next worksheet start
+    /// after the last image of this one. The default is 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is 0.
+    #[cfg(feature = "drawings")]
+    pub fn set_image_number(&mut self, number: u32) {
+        assert!(number > 0, "image part numbers start at 1");
+
+        self.image_number = Some(number);
+    }
+
+    /// The `xl/media/imageN` part names and the images of the worksheet, in
+    /// the order they were added, followed by the
+    /// [`embedded_images()`](Worksheet::embedded_images). The image data is
+    /// stored unchanged with
+    /// [`Packager::add_part()`](crate::packager::Packager::add_part).
+    #[cfg(feature = "drawings")]
+    pub fn media(&self) -> Vec<(String, &Image)> {
+        let first = self.image_number.unwrap_or(1);
+
+        self.drawing_objects
+            .iter()
+            .filter_map(|(_, _, object)| object.image())
+            .enumerate()
+            .map(|(index, image)| {
+                let part_name = format!(
+                    "xl/media/image{}.{}",
+                    first + index as u32,
+                    image.image_type().extension()
+                );
+                (part_name, image)
+            })
+            .chain(self.embedded_images())
+            .chain(self.object_previews())
+            .collect()
+    }
+
+    /// The `xl/drawings/drawingN.xml` part with the images and charts, if
+    /// the worksheet has any.
+    #[cfg(feature = "drawings")]
+    pub fn drawing(&self) -> Option<Drawing> {
+        if self.drawing_objects.is_empty() {
+            return None;
+        }
+
+        let mut drawing = Drawing::new(self.drawing_number());
+        let mut media = self.media().into_iter();
+        for (row, col, object) in &self.drawing_objects {
+            match object {
+                DrawingObject::Image(image) => {
+                    if let Some((part_name, _)) = media.next() {
+                        drawing.add_image(*row, *col, image, &part_name);
+                    }
+                }
+                #[cfg(feature = "charts")]
+                DrawingObject::Chart(chart) => drawing.add_chart(*row, *col, chart),
+            }
+        }
+
+        Some(drawing)
+    }
+
+    // The number of the drawing part, which defaults to 1.
+    #[cfg(feature = "drawings")]
+    fn drawing_number(&self) -> u32 {
+        self.drawing_number.unwrap_or(1)
+    }
+
+    /// Merge a range of zero-indexed cells and write a string to the first
+    /// cell, or a blank cell if the string is empty. The other cells in the
+    /// range are written as blank cells with the same format, so that
+    /// formatting such as borders is displayed around the whole range. The
+    /// first and last cells can be given in either order.
+    ///
+    /// Excel requires a merged range to span at least two cells and not to
+    /// overlap another merged range, so these are errors.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::error::XlsxError;
+    /// # use excel_xmlwriter::styles::Format;
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    /// let bold = Format::new().set_bold();
+    ///
+    /// assert!(worksheet.merge_range(1, 1, 1, 3, "Title", &bold).is_ok());
+    /// assert_eq!(
+    ///     worksheet.merge_range(0, 2, 2, 2, "Overlap", &bold),
+    ///     Err(XlsxError::MergeRangeOverlap {
+    ///         range: "C1:C3".to_string(),
+    ///         existing: "B2:D2".to_string(),
+    ///     })
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows or columns are beyond Excel's limits.
+    pub fn merge_range(
+        &mut self,
+        first_row: u32,
+        first_col: u16,
+        last_row: u32,
+        last_col: u16,
+        string: &str,
+        format: &Format,
+    ) -> Result<(), XlsxError> {
+        let range = check_range(first_row, first_col, last_row, last_col);
+        let (first_row, first_col, last_row, last_col) = range;
+
+        if first_row == last_row && first_col == last_col {
+            return Err(XlsxError::MergeRangeSingleCell(range_to_string(range)));
+        }
+
+        let overlap = self.merged_ranges.iter().find(|existing| {
+            first_row <= existing.2
+                && existing.0 <= last_row
+                && first_col <= existing.3
+                && existing.1 <= last_col
+        });
+        if let Some(existing) = overlap {
+            return Err(XlsxError::MergeRangeOverlap {
+                range: range_to_string(range),
+                existing: range_to_string(*existing),
+            });
+        }
+
+        for row in first_row..=last_row {
+            for col in first_col..=last_col {
+                self.insert_cell(row, col, CellData::Blank, Some(format));
+            }
+        }
+        if !string.is_empty() {
+            let data = CellData::String(string.to_string());
+            self.insert_cell(first_row, first_col, data, Some(format));
+        }
+
+        self.merged_ranges.push(range);
+        Ok(())
+    }
+
+    // Store a cell, replacing any previous cell at the same position.
+    fn insert_cell(&mut self, row: u32, col: u16, data: CellData, format: Option<&Format>) {
+        check_cell(row, col);
+
+        let cell = Cell {
+            data,
+            format: format.cloned(),
+        };
+        self.cells.entry(row).or_default().insert(col, cell);
+    }
+
+    /// Set the default format for a range of zero-indexed columns.
+    ///
+    /// The format applies to all the cells in the columns without a cell
+    /// having to be written. Cells that are written in the columns without a
+    /// format of their own are given the column format, see
+    /// [`cell_xf_index()`](Worksheet::cell_xf_index). Cells with an explicit
+    /// format use that format instead; Excel doesn't merge the two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns are beyond Excel's last column.
+    pub fn set_column_format(&mut self, first_col: u16, last_col: u16, format: &Format) {
+        let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+        check_col(last_col);
+
+        for col in first_col..=last_col {
+            self.columns.entry(col).or_default().format = Some(format.clone());
+        }
+    }
+
+    /// Set the width, format and visibility of a range of zero-indexed
+    /// columns. The width is in the character units shown in Excel, where
+    /// the default is 8.43, and is converted to the width that Excel stores.
+    /// The format is the default format of the columns, as in
+    /// [`set_column_format()`](Worksheet::set_column_format).
+    ///
+    /// Adjacent columns with the same properties are written as a single
+    /// `<col>` range.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::styles::Format;
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    /// let bold = Format::new().set_bold();
+    ///
+    /// worksheet.set_column(0, 0, 20.0, Some(&bold), false);
+    /// worksheet.set_column(1, 3, 8.43, None, true);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns are beyond Excel's last column, or if the width
+    /// isn't in Excel's range of 0 to 255.
+    pub fn set_column(
+        &mut self,
+        first_col: u16,
+        last_col: u16,
+        width: f64,
+        format: Option<&Format>,
+        hidden: bool,
+    ) {
+        let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+        check_col(last_col);
+        assert!(
+            (0.0..=255.0).contains(&width),
+            "column width {width} is outside Excel's range of 0 to 255"
+        );
+
+        for col in first_col..=last_col {
+            let column = self.columns.entry(col).or_default();
+            column.width = width;
+            column.format = format.cloned();
+            column.hidden = hidden;
+        }
+    }
+
+    /// Hide a range of zero-indexed columns, keeping their width and format.
+    /// Columns with the default width are stored with a zero width, as in
+    /// Excel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns are beyond Excel's last column.
+    pub fn set_column_hidden(&mut self, first_col: u16, last_col: u16) {
+        let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+        check_col(last_col);
+
+        for col in first_col..=last_col {
+            self.columns.entry(col).or_default().hidden = true;
+        }
+    }
+
+    /// Set the default format for a zero-indexed row.
+    ///
+    /// The format applies to all the cells in the row without a cell having
+    /// to be written and takes precedence over column formats. Cells with an
+    /// explicit format use that format instead. See the [module
+    /// documentation](crate::worksheet#format-precedence).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is beyond Excel's last row.
+    pub fn set_row_format(&mut self, row: u32, format: &Format) {
+        check_row(row);
+
+        self.row_formats.insert(row, format.clone());
+    }
+
+    /// Hide a zero-indexed row. A hidden row without cells is written as an
+    /// empty row element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is beyond Excel's last row.
+    pub fn set_row_hidden(&mut self, row: u32) {
+        check_row(row);
+
+        self.row_options.entry(row).or_default().hidden = true;
+    }
+
+    /// Group a range of zero-indexed rows in an outline, so that they can be
+    /// collapsed with the outline symbols in Excel. Each call increases the
+    /// outline level of the rows by one, so groups can be nested up to
+    /// Excel's maximum of 7 levels.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// // Rows 2 to 9 are a group with a nested, collapsed, group of rows 2
+    /// // to 4. Row 5 has the symbol of the collapsed group.
+    /// worksheet.group_rows(1, 8);
+    /// worksheet.group_rows_collapsed(1, 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows are beyond Excel's last row, or if the outline
+    /// level of a row would be more than 7.
+    pub fn group_rows(&mut self, first_row: u32, last_row: u32) {
+        let (first_row, last_row) = (first_row.min(last_row), first_row.max(last_row));
+        check_row(last_row);
+
+        for row in first_row..=last_row {
+            let options = self.row_options.entry(row).or_default();
+            assert!(
+                options.outline_level < MAX_OUTLINE_LEVEL,
+                "row {row} is beyond Excel's maximum outline level of 7"
+            );
+            options.outline_level += 1;
+        }
+    }
+
+    /// Group a range of zero-indexed rows in an outline, as in
+    /// [`group_rows()`](Worksheet::group_rows), and collapse the group. The
+    /// rows are hidden, and the row after the group is marked as collapsed
+    /// since it has the outline symbol of the group, or the row before the
+    /// group if the summary rows are above the details, see
+    /// [`set_outline_settings()`](Worksheet::set_outline_settings).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows are beyond Excel's last row, or if the outline
+    /// level of a row would be more than 7.
+    pub fn group_rows_collapsed(&mut self, first_row: u32, last_row: u32) {
+        let (first_row, last_row) = (first_row.min(last_row), first_row.max(last_row));
+        self.group_rows(first_row, last_row);
+
+        for row in first_row..=last_row {
+            self.row_options.entry(row).or_default().hidden = true;
+        }
+
+        let summary_row = if self.outline_settings.summary_below {
+            last_row.checked_add(1).filter(|row| *row < ROW_MAX)
+        } else {
+            first_row.checked_sub(1)
+        };
+        if let Some(row) = summary_row {
+            self.row_options.entry(row).or_default().collapsed = true;
+        }
+    }
+
+    /// Group a range of zero-indexed columns in an outline, so that they can
+    /// be collapsed with the outline symbols in Excel. Each call increases
+    /// the outline level of the columns by one, so groups can be nested up
+    /// to Excel's maximum of 7 levels. The other properties of the columns
+    /// are set with [`set_column()`](Worksheet::set_column).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns are beyond Excel's last column, or if the
+    /// outline level of a column would be more than 7.
+    pub fn group_columns(&mut self, first_col: u16, last_col: u16) {
+        let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+        check_col(last_col);
+
+        for col in first_col..=last_col {
+            let column = self.columns.entry(col).or_default();
+            assert!(
+                column.outline_level < MAX_OUTLINE_LEVEL,
+                "column {col} is beyond Excel's maximum outline level of 7"
+            );
+            column.outline_level += 1;
+        }
+    }
+
+    /// Group a range of zero-indexed columns in an outline, as in
+    /// [`group_columns()`](Worksheet::group_columns), and collapse the group.
+    /// The columns are hidden, and the column after the group is marked as
+    /// collapsed, or the column before the group if the summary columns are
+    /// to the left of the details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the columns are beyond Excel's last column, or if the
+    /// outline level of a column would be more than 7.
+    pub fn group_columns_collapsed(&mut self, first_col: u16, last_col: u16) {
+        let (first_col, last_col) = (first_col.min(last_col), first_col.max(last_col));
+        self.group_columns(first_col, last_col);
+
+        for col in first_col..=last_col {
+            self.columns.entry(col).or_default().hidden = true;
+        }
+
+        let summary_col = if self.outline_settings.summary_right {
+            last_col.checked_add(1).filter(|col| *col < COL_MAX)
+        } else {
+            first_col.checked_sub(1)
+        };
+        if let Some(col) = summary_col {
+            self.columns.entry(col).or_default().collapsed = true;
+        }
+    }
+
+    /// Set the outline settings of the worksheet: whether the outline
+    /// symbols are shown, and whether the summary rows and columns with the
+    /// symbols are below and to the right of the grouped details. They are
+    /// all on by default, as in Excel. The settings should be made before
+    /// groups are collapsed, since they decide which row or column is marked
+    /// as collapsed.
+    pub fn set_outline_settings(
+        &mut self,
+        show_symbols: bool,
+        summary_below: bool,
+        summary_right: bool,
+    ) {
+        self.outline_settings = OutlineSettings {
+            show_symbols,
+            summary_below,
+            summary_right,
+        };
+    }
+
+    /// Set the color of the worksheet tab, with the same [`Color`] type as
+    /// formats. [`Color::Automatic`] removes the tab color.
+    ///
+    /// ```
+    /// # use excel_xmlwriter::styles::Color;
+    /// # use excel_xmlwriter::worksheet::Worksheet;
+    /// #
+    /// let mut worksheet = Worksheet::new();
+    ///
+    /// worksheet.set_tab_color(Color::Rgb(0xFF0000));
+    /// ```
+    pub fn set_tab_color(&mut self, color: Color) {
+        self.tab_color = (color != Color::Automatic).then_some(color);
+    }
+
+    /// Set the worksheet zoom as a percentage in the range 10 to 400. The
+    /// default is 100.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the zoom is outside Excel's range.
+    pub fn set_zoom(&mut self, zoom: u16) {
+        assert!(
+            (10..=400).contains(&zoom),
+            "zoom {zoom} is outside Excel's range of 10 to 400"
+        );
+
+        self.zoom = if zoom == 100 { None } else { Some(zoom) };
+    }
+
+    /// Freeze the rows above, and the columns to the left of, a zero-indexed
+    /// cell so that they stay visible when the worksheet is scrolled. For
+    /// example `freeze_panes(1, 0)` freezes the top row and
+    /// `freeze_panes(1, 1)` freezes the top row and the first column.
+    /// Setting `(0, 0)` removes the panes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn freeze_panes(&mut self, row: u32, col: u16) {
+        check_cell(row, col);
+
+        self.panes = if row == 0 && col == 0 {
+            None
+        } else {
+            Some(Panes::Frozen { row, col })
+        };
+    }
+
+    /// Split the worksheet view into panes that scroll independently. The
+    /// horizontal position `x` is in the character units of column widths
+    /// and the vertical position `y` is in the points of row heights, so
+    /// `split_panes(8.43, 15.0)` splits the view after the first column and
+    /// row at their default sizes. A position of `0.0` doesn't split in that
+    /// direction, and setting both to `0.0` removes the panes.
+    ///
+    /// The first visible cell of the bottom right pane is estimated from the
+    /// default column width and row height.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a position is negative or not finite.
+    pub fn split_panes(&mut self, x: f64, y: f64) {
+        assert!(
+            x.is_finite() && x >= 0.0 && y.is_finite() && y >= 0.0,
+            "split positions ({x}, {y}) must be positive"
+        );
+
+        if x == 0.0 && y == 0.0 {
+            self.panes = None;
+            return;
+        }
+
+        // Convert the column width to pixels, with the same padding as Excel,
+        // and then to twips. The row height in points is converted directly.
+        let x_pixels = if x < 1.0 { x * 12.0 } else { x * 7.0 + 5.0 };
+        let x_split = if x == 0.0 {
+            0
+        } else {
+            (x_pixels * 15.0).round() as u32 + 390
+        };
+        let y_split = if y == 0.0 {
+            0
+        } else {
+            (y * 20.0).round() as u32 + 300
+        };
+
+        // Estimate the top left cell from the default 64 pixel column width
+        // and 15 point row height.
+        let row = ((y / 15.0).round() as u32).min(ROW_MAX - 1);
+        let col = ((x_pixels / 64.0).round() as u16).min(COL_MAX - 1);
+
+        self.panes = Some(Panes::Split {
+            x_split,
+            y_split,
+            row,
+            col,
+        });
+    }
+
+    /// Set the page setup of the worksheet, for printing. See the
+    /// [`page_setup`](crate::page_setup) module.
+    pub fn set_page_setup(&mut self, page_setup: &PageSetup) {
+        self.page_setup = page_setup.clone();
+    }
+
+    /// Protect the worksheet, so that users can't change its locked cells,
+    /// with an optional password. An empty password protects the worksheet
+    /// without one. The options allow actions such as formatting or sorting,
+    /// see the [`protection`](crate::protection) module.
+    ///
+    /// Cells are locked by default. Cells that users should still be able to
+    /// edit need a format with
+    /// [`Format::set_unlocked()`](crate::styles::Format::set_unlocked).
+    pub fn protect_sheet(&mut self, password: &str, options: &ProtectionOptions) {
+        self.protection = Some((password.to_string(), options.clone()));
+    }
+
+    /// Get the `cellXfs` index to use for a cell, taking the row and column
+    /// formats into account.
+    ///
+    /// A cell's own format takes precedence, followed by the row format and
+    /// then the column format. An unformatted cell gets the row or column
+    /// format so that writing the cell doesn't remove the formatting that
+    /// Excel shows for it. Otherwise the cell has the default format, index
+    /// 0.
+    pub fn cell_xf_index(
+        &self,
+        styles: &mut Styles,
+        row: u32,
+        col: u16,
+        format: Option<&Format>,
+    ) -> u32 {
+        match self.cell_format(row, col, format) {
+            Some(format) => styles.xf_index(format),
+            None => 0,
+        }
+    }
+
+    // The format of a cell, or the row or column format that it takes.
+    fn cell_format<'a>(
+        &'a self,
+        row: u32,
+        col: u16,
+        format: Option<&'a Format>,
+    ) -> Option<&'a Format> {
+        format
+            .or_else(|| self.row_formats.get(&row))
+            .or_else(|| self.columns.get(&col)?.format.as_ref())
+    }
+
+    // The cellXfs index of a cell like cell_xf_index(), with Excel's quote
+    // prefix for a string that the writer's sanitize policy leaves to the
+    // format.
+    fn data_xf_index(
+        &self,
+        styles: &mut Styles,
+        policy: SanitizePolicy,
+        row: u32,
+        col: u16,
+        data: &CellData,
+        format: Option<&Format>,
+    ) -> u32 {
+        match data {
+            CellData::String(string) if needs_quote_prefix(string, policy) => {
+                let format = self.cell_format(row, col, format).cloned();
+                styles.xf_index(&format.unwrap_or_default().set_quote_prefix())
+            }
+            _ => self.cell_xf_index(styles, row, col, format),
+        }
+    }
+
+    /// Write the worksheet part. Strings in the worksheet are added to the
+    /// shared string table.
+    pub fn assemble_xml_file(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        styles: &mut Styles,
+        string_table: &mut SharedStringTable,
+    ) {
+        let dimension = self.dimension();
+        self.write_header(writer, styles, Some(&dimension));
+
+        self.write_sheet_data(writer, styles, string_table);
+
+        self.write_footer(writer, styles, None);
+    }
+
+    /// Start writing the worksheet part in streaming mode. The settings of
+    /// the worksheet, such as the column and row formats and the zoom, are
+    /// written or applied as usual, but cells written to the worksheet with
+    /// [`write_string()`](Worksheet::write_string) and the other cell
+    /// methods, including the hyperlinks of
+    /// [`write_url()`](Worksheet::write_url), are ignored. The cells are
+    /// written with the returned [`StreamingWorksheet`] instead, which must
+    /// be finished with [`StreamingWorksheet::finish()`] to complete the
+    /// part. It returns the relationships of the worksheet, which include
+    /// the hyperlinks of the stream.
+    ///
+    /// The `<dimension>` element is omitted since the range of the cells
+    /// isn't known when the start of the part is written. It is optional and
+    /// Excel recalculates it when the file is opened.
+    pub fn start_streaming<'w, W: Write>(
+        &'w self,
+        writer: &'w mut XMLWriter<W>,
+        styles: &'w mut Styles,
+    ) -> StreamingWorksheet<'w, W> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(part = %writer.context(), "started streaming worksheet");
+
+        self.write_header(writer, styles, None);
+        writer.xml_start_tag_only("sheetData");
+
+        StreamingWorksheet {
+            worksheet: self,
+            writer,
+            styles,
+            last_cell: None,
+            next_row: 0,
+            hyperlinks: HyperlinkSpill::new(),
+            #[cfg(feature = "tracing")]
+            rows_written: 0,
+        }
+    }
+
+    // Write the start of the part, up to the <sheetData> element.
+    fn write_header(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        styles: &mut Styles,
+        dimension: Option<&str>,
+    ) {
+        writer.xml_declaration();
+
+        let attributes = vec![("xmlns", SPREADSHEETML_NS), ("xmlns:r", RELATIONSHIPS_NS)];
+        writer.xml_start_tag("worksheet", &attributes);
+
+        self.write_sheet_pr(writer);
+
+        if let Some(dimension) = dimension {
+            writer.xml_empty_tag("dimension", &vec![("ref", dimension)]);
+        }
+
+        self.write_sheet_views(writer);
+
+        let row_height = styles.default_font().row_height().to_string();
+        let row_level = self
+            .row_options
+            .values()
+            .map(|options| options.outline_level);
+        let row_level = row_level.max().unwrap_or_default().to_string();
+        let col_level = self.columns.values().map(|column| column.outline_level);
+        let col_level = col_level.max().unwrap_or_default().to_string();
+
+        let mut attributes = vec![("defaultRowHeight", row_height.as_str())];
+        if row_level != "0" {
+            attributes.push(("outlineLevelRow", row_level.as_str()));
+        }
+        if col_level != "0" {
+            attributes.push(("outlineLevelCol", col_level.as_str()));
+        }
+        writer.xml_empty_tag("sheetFormatPr", &attributes);
+
+        self.write_cols(writer, styles);
+    }
+
+    // Write the <sheetPr> element with the tab color, outline settings and
+    // fit to page setting, if any of them are set.
+    fn write_sheet_pr(&self, writer: &mut XMLWriter<impl Write>) {
+        let outline_settings = self.outline_settings;
+        let has_outline_settings = outline_settings != OutlineSettings::default();
+        let fit_to_page = self.page_setup.fit_to_page();
+
+        if self.tab_color.is_none() && !has_outline_settings && !fit_to_page {
+            return;
+        }
+
+        writer.xml_start_tag_only("sheetPr");
+
+        if let Some(color) = self.tab_color {
+            let (name, value) = color.attribute();
+            writer.xml_empty_tag("tabColor", &vec![(name, value.as_str())]);
+        }
+
+        if has_outline_settings {
+            let mut attributes = vec![];
+            if !outline_settings.summary_below {
+                attributes.push(("summaryBelow", "0"));
+            }
+            if !outline_settings.summary_right {
+                attributes.push(("summaryRight", "0"));
+            }
+            if !outline_settings.show_symbols {
+                attributes.push(("showOutlineSymbols", "0"));
+            }
+            writer.xml_empty_tag("outlinePr", &attributes);
+        }
+
+        if fit_to_page {
+            writer.xml_empty_tag("pageSetUpPr", &vec![("fitToPage", "1")]);
+        }
+
+        writer.xml_end_tag("sheetPr");
+    }
+
+    // Write the end of the part, after the <sheetData> element. The
+    // hyperlinks of a streaming worksheet are read back from its spill
+    // instead of those of the worksheet.
+    fn write_footer(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        styles: &mut Styles,
+        spill: Option<&mut HyperlinkSpill>,
+    ) {
+        if let Some((password, options)) = &self.protection {
+            options.write_sheet_protection(writer, password);
+        }
+
+        if let Some(range) = self.autofilter {
+            let range = range_to_string(range);
+            writer.xml_empty_tag("autoFilter", &vec![("ref", range.as_str())]);
+        }
+
+        self.write_merge_cells(writer);
+        self.write_conditional_formats(writer, styles);
+        self.write_data_validations(writer);
+
+        // The external hyperlinks are the first relationships, and the ids
+        // of the drawing, vmlDrawing and table relationships follow them.
+        let rel_index = match spill {
+            Some(spill) => {
+                spill.write_hyperlinks(writer);
+                spill.external_count
+            }
+            None => {
+                self.write_hyperlinks(writer);
+                self.hyperlinks
+                    .values()
+                    .filter(|hyperlink| matches!(hyperlink, Hyperlink::External { .. }))
+                    .count()
+            }
+        };
+
+        self.page_setup.write_elements(writer);
+        self.write_ignored_errors(writer);
+
+        #[cfg(feature = "drawings")]
+        let rel_index = self.write_drawings(writer, rel_index);
+
+        self.write_table_parts(writer, rel_index);
+        self.write_ext_list(writer);
+
+        writer.xml_end_tag("worksheet");
+    }
+
+    // Write the <ignoredErrors> element, with an <ignoredError> element for
+    // the ranges of each type of warning.
+    fn write_ignored_errors(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.ignored_errors.is_empty() {
+            return;
+        }
+
+        writer.xml_start_tag_only("ignoredErrors");
+
+        for (error, ranges) in &self.ignored_errors {
+            let ranges: Vec<String> = ranges.iter().map(|range| sqref(*range)).collect();
+            let ranges = ranges.join(" ");
+            let attributes = vec![("sqref", ranges.as_str()), (error.attribute(), "1")];
+            writer.xml_empty_tag("ignoredError", &attributes);
+        }
+
+        writer.xml_end_tag("ignoredErrors");
+    }
+
+    // Write the <mergeCells> element.
+    fn write_merge_cells(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.merged_ranges.is_empty() {
+            return;
+        }
+
+        let count = self.merged_ranges.len().to_string();
+        writer.xml_start_tag("mergeCells", &vec![("count", count.as_str())]);
+
+        for range in &self.merged_ranges {
+            let range = range_to_string(*range);
+            writer.xml_empty_tag("mergeCell", &vec![("ref", range.as_str())]);
+        }
+
+        writer.xml_end_tag("mergeCells");
+    }
+
+    // Write the <conditionalFormatting> elements. The rules are prioritized
+    // in the order they were added to the worksheet, across all the ranges.
+    fn write_conditional_formats(&self, writer: &mut XMLWriter<impl Write>, styles: &mut Styles) {
+        let mut priority = 1;
+
+        for (range, rules) in &self.conditional_formats {
+            let sqref = sqref(*range);
+            writer.xml_start_tag("conditionalFormatting", &vec![("sqref", sqref.as_str())]);
+
+            let first_cell = rowcol_to_cell(range.0, range.1);
+            for rule in rules {
+                let dxf_index = styles.dxf_index(rule.format());
+                rule.write_rule(writer, &first_cell, dxf_index, priority);
+                priority += 1;
+            }
+
+            writer.xml_end_tag("conditionalFormatting");
+        }
+    }
+
+    // Write the <dataValidations> element.
+    fn write_data_validations(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.data_validations.is_empty() {
+            return;
+        }
+
+        let count = self.data_validations.len().to_string();
+        writer.xml_start_tag("dataValidations", &vec![("count", count.as_str())]);
+
+        for (range, data_validation) in &self.data_validations {
+            data_validation.write_data_validation(writer, &sqref(*range));
+        }
+
+        writer.xml_end_tag("dataValidations");
+    }
+
+    // Write the <hyperlinks> element. The relationship ids of the external
+    // links follow the order of relationships().
+    fn write_hyperlinks(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.hyperlinks.is_empty() {
+            return;
+        }
+
+        writer.xml_start_tag_only("hyperlinks");
+
+        let mut rel_index = 0;
+        for ((row, col), hyperlink) in &self.hyperlinks {
+            write_hyperlink(writer, *row, *col, hyperlink, &mut rel_index);
+        }
+
+        writer.xml_end_tag("hyperlinks");
+    }
+
+    // Write the <drawing> and <legacyDrawing> elements, with relationship
+    // ids after the last id that has been written, and the embedded objects
+    // and buttons. Returns the last relationship id that has been written.
+    #[cfg(feature = "drawings")]
+    fn write_drawings(&self, writer: &mut XMLWriter<impl Write>, mut rel_index: usize) -> usize {
+        let hyperlinks = rel_index;
+
+        if !self.drawing_objects.is_empty() {
+            rel_index += 1;
+            let rel_id = format!("rId{rel_index}");
+            writer.xml_empty_tag("drawing", &vec![("r:id", rel_id.as_str())]);
+        }
+
+        if self.has_vml() {
+            rel_index += 1;
+            let rel_id = format!("rId{rel_index}");
+            writer.xml_empty_tag("legacyDrawing", &vec![("r:id", rel_id.as_str())]);
+        }
+
+        self.write_ole_objects(writer, hyperlinks);
+        self.write_controls(writer, hyperlinks);
+
+        rel_index
+    }
+
+    // Write the <oleObjects> element of the embedded objects. Excel 2010 and
+    // later read the anchors and preview images from an x14 alternate
+    // content block. The relationships of each object, to its embedding and
+    // preview image, are the last ones in relationships().
+    #[cfg(feature = "drawings")]
+    fn write_ole_objects(&self, writer: &mut XMLWriter<impl Write>, hyperlinks: usize) {
+        if self.objects.is_empty() {
+            return;
+        }
+
+        let mut rel_index = self.control_rel_offset(hyperlinks) + self.buttons.len();
+        let first_shape = self.buttons.len() + self.legacy_notes().len();
+
+        writer.xml_start_tag("oleObjects", &vec![("xmlns:xdr", SPREADSHEET_DRAWING_NS)]);
+
+        for (index, (row, col, object)) in self.objects.iter().enumerate() {
+            let rel_id = format!("rId{}", rel_index + 1);
+            let preview_rel_id = format!("rId{}", rel_index + 2);
+            rel_index += 2;
+            let shape_id = vml::shape_id(self.notes_number(), first_shape + index).to_string();
+
+            let attributes = vec![
+                ("progId", object.prog_id()),
+                ("dvAspect", "DVASPECT_ICON"),
+                ("shapeId", shape_id.as_str()),
+                ("r:id", rel_id.as_str()),
+            ];
+
+            let alternate_attributes =
+                vec![("xmlns:mc", MARKUP_COMPATIBILITY_NS), ("xmlns:x14", X14_NS)];
+            writer.xml_start_tag("mc:AlternateContent", &alternate_attributes);
+            writer.xml_start_tag("mc:Choice", &vec![("Requires", "x14")]);
+            writer.xml_start_tag("oleObject", &attributes);
+
+            let object_attributes = vec![
+                ("defaultSize", "0"),
+                ("autoPict", "0"),
+                ("r:id", preview_rel_id.as_str()),
+            ];
+            writer.xml_start_tag("objectPr", &object_attributes);
+
+            let (width, height) = object.preview().size();
+            let position = Position::new(*row, *col as u32, 0, 0, width, height);
+            writer.xml_start_tag("anchor", &vec![("moveWithCells", "1")]);
+            drawing::write_cell_position(
+                writer,
+                "from",
+                position.start_col,
+                position.x1,
+                position.start_row,
+                position.y1,
+            );
+            drawing::write_cell_position(
+                writer,
+                "to",
+                position.end_col,
+                position.x2,
+                position.end_row,
+                position.y2,
+            );
+            writer.xml_end_tag("anchor");
+
+            writer.xml_end_tag("objectPr");
+            writer.xml_end_tag("oleObject");
+            writer.xml_end_tag("mc:Choice");
+
+            writer.xml_start_tag_only("mc:Fallback");
+            writer.xml_empty_tag("oleObject", &attributes);
+            writer.xml_end_tag("mc:Fallback");
+            writer.xml_end_tag("mc:AlternateContent");
+        }
+
+        writer.xml_end_tag("oleObjects");
+    }
+
+    // Write the <controls> element of the buttons. Excel 2010 and later read
+    // it from an x14 alternate content block. The ctrlProp relationships
+    // follow the other relationships in relationships(), apart from those of
+    // the embedded objects.
+    #[cfg(feature = "drawings")]
+    fn write_controls(&self, writer: &mut XMLWriter<impl Write>, hyperlinks: usize) {
+        if self.buttons.is_empty() {
+            return;
+        }
+
+        let mut rel_index = self.control_rel_offset(hyperlinks);
+
+        let attributes = vec![("xmlns:mc", MARKUP_COMPATIBILITY_NS), ("xmlns:x14", X14_NS)];
+        writer.xml_start_tag("mc:AlternateContent", &attributes);
+        writer.xml_start_tag("mc:Choice", &vec![("Requires", "x14")]);
+        writer.xml_start_tag("controls", &vec![("xmlns:xdr", SPREADSHEET_DRAWING_NS)]);
+
+        for (index, (row, col, button)) in self.buttons.iter().enumerate() {
+            rel_index += 1;
+            let rel_id = format!("rId{rel_index}");
+            let shape_id = vml::shape_id(self.notes_number(), index).to_string();
+            let name = format!("Button {}", index + 1);
+
+            writer.xml_start_tag_only("mc:AlternateContent");
+            writer.xml_start_tag("mc:Choice", &vec![("Requires", "x14")]);
+
+            let attributes = vec![
+                ("shapeId", shape_id.as_str()),
+                ("r:id", rel_id.as_str()),
+                ("name", name.as_str()),
+            ];
+            writer.xml_start_tag("control", &attributes);
+
+            let macro_reference = button.macro_reference();
+            let mut attributes = vec![
+                ("defaultSize", "0"),
+                ("print", "0"),
+                ("autoFill", "0"),
+                ("autoPict", "0"),
+            ];
+            if let Some(macro_reference) = &macro_reference {
+                attributes.push(("macro", macro_reference.as_str()));
+            }
+            writer.xml_start_tag("controlPr", &attributes);
+
+            let (width, height) = button.size();
+            let position = Position::new(*row, *col as u32, 0, 0, width, height);
+            writer.xml_start_tag("anchor", &vec![("moveWithCells", "1")]);
+            drawing::write_cell_position(
+                writer,
+                "from",
+                position.start_col,
+                position.x1,
+                position.start_row,
+                position.y1,
+            );
+            drawing::write_cell_position(
+                writer,
+                "to",
+                position.end_col,
+                position.x2,
+                position.end_row,
+                position.y2,
+            );
+            writer.xml_end_tag("anchor");
+
+            writer.xml_end_tag("controlPr");
+            writer.xml_end_tag("control");
+            writer.xml_end_tag("mc:Choice");
+            writer.xml_end_tag("mc:AlternateContent");
+        }
+
+        writer.xml_end_tag("controls");
+        writer.xml_end_tag("mc:Choice");
+        writer.xml_end_tag("mc:AlternateContent");
+    }
+
+    // Write the <tableParts> element, with relationship ids after the last
+    // id that has been written.
+    fn write_table_parts(&self, writer: &mut XMLWriter<impl Write>, mut rel_index: usize) {
+        if self.tables.is_empty() {
+            return;
+        }
+
+        let count = self.tables.len().to_string();
+        writer.xml_start_tag("tableParts", &vec![("count", count.as_str())]);
+
+        for _ in &self.tables {
+            rel_index += 1;
+            let rel_id = format!("rId{rel_index}");
+            writer.xml_empty_tag("tablePart", &vec![("r:id", rel_id.as_str())]);
+        }
+
+        writer.xml_end_tag("tableParts");
+    }
+
+    // Write the <extLst> element with the x14 extension for the sparklines.
+    fn write_ext_list(&self, writer: &mut XMLWriter<impl Write>) {
+        if self.sparklines.is_empty() {
+            return;
+        }
+
+        writer.xml_start_tag_only("extLst");
+        let attributes = vec![
+            ("xmlns:x14", X14_NS),
+            ("uri", "{05C60535-1F16-4fd2-B633-F4F36F0B64E0}"),
+        ];
+        writer.xml_start_tag("ext", &attributes);
+
+        let attributes = vec![("xmlns:xm", XM_NS)];
+        writer.xml_start_tag("x14:sparklineGroups", &attributes);
+        for ((row, col), sparkline) in &self.sparklines {
+            sparkline.write_sparkline_group(writer, *row, *col);
+        }
+        writer.xml_end_tag("x14:sparklineGroups");
+
+        writer.xml_end_tag("ext");
+        writer.xml_end_tag("extLst");
+    }
+
+    // Write the <sheetViews> element.
+    fn write_sheet_views(&self, writer: &mut XMLWriter<impl Write>) {
+        let zoom = self.zoom.map(|zoom| zoom.to_string());
+
+        let mut attributes = vec![];
+        if let Some(zoom) = &zoom {
+            attributes.push(("zoomScale", zoom.as_str()));
+            attributes.push(("zoomScaleNormal", zoom.as_str()));
+        }
+        attributes.push(("workbookViewId", "0"));
+
+        writer.xml_start_tag_only("sheetViews");
+        match self.panes {
+            Some(panes) => {
+                writer.xml_start_tag("sheetView", &attributes);
+                write_panes(writer, panes);
+                writer.xml_end_tag("sheetView");
+            }
+            None => writer.xml_empty_tag("sheetView", &attributes),
+        }
+        writer.xml_end_tag("sheetViews");
+    }
+
+    // Get the range of the cells in the worksheet, like `A1:C10`, or `A1`
+    // for an empty worksheet.
+    fn dimension(&self) -> String {
+        let (Some(first_row), Some(last_row)) =
+            (self.cells.keys().next(), self.cells.keys().last())
+        else {
+            return "A1".to_string();
+        };
+
+        let first_col = self
+            .cells
+            .values()
+            .filter_map(|cols| cols.keys().next())
+            .min();
+        let last_col = self
+            .cells
+            .values()
+            .filter_map(|cols| cols.keys().last())
+            .max();
+        let (first_col, last_col) = (*first_col.unwrap_or(&0), *last_col.unwrap_or(&0));
+
+        let first_cell = rowcol_to_cell(*first_row, first_col);
+        let last_cell = rowcol_to_cell(*last_row, last_col);
+
+        if first_cell == last_cell {
+            first_cell
+        } else {
+            format!("{first_cell}:{last_cell}")
+        }
+    }
+
+    // Get the column span of each block of 16 rows, which Excel writes as
+    // an optimization hint in the rows of the block.
+    fn row_spans(&self) -> BTreeMap<u32, (u16, u16)> {
+        let mut spans: BTreeMap<u32, (u16, u16)> = BTreeMap::new();
+
+        for (row, cols) in &self.cells {
+            let (Some(first_col), Some(last_col)) = (cols.keys().next(), cols.keys().last()) else {
+                continue;
+            };
+
+            spans
+                .entry(row / 16)
+                .and_modify(|(min, max)| {
+                    *min = (*min).min(*first_col);
+                    *max = (*max).max(*last_col);
+                })
+                .or_insert((*first_col, *last_col));
+        }
+
+        spans
+    }
+
+    // Write the <sheetData> element.
+    fn write_sheet_data(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        styles: &mut Styles,
+        string_table: &mut SharedStringTable,
+    ) {
+        let rows: BTreeSet<u32> = self
+            .cells
+            .keys()
+            .chain(self.row_formats.keys())
+            .chain(self.row_options.keys())
+            .copied()
+            .collect();
+
+        if rows.is_empty() {
+            writer.xml_empty_tag_only("sheetData");
+            return;
+        }
+
+        let spans = self.row_spans();
+
+        writer.xml_start_tag_only("sheetData");
+
+        #[cfg(feature = "drawings")]
+        let mut value_metadata = self.value_metadata_number.unwrap_or(1);
+
+        #[cfg(feature = "tracing")]
+        let mut rows_written = 0;
+
+        for row in rows {
+            #[cfg(feature = "tracing")]
+            {
+                rows_written += 1;
+                trace_row_batch(writer, rows_written, row);
+            }
+
+            let row_xf_index = self
+                .row_formats
+                .get(&row)
+                .map(|format| styles.xf_index(format));
+
+            // Formatted, hidden or grouped rows without cells are written as
+            // empty row elements.
+            let Some(cols) = self.cells.get(&row) else {
+                self.write_row(writer, row, None, row_xf_index, true);
+                continue;
+            };
+
+            let row_spans = spans.get(&(row / 16)).copied();
+            self.write_row(writer, row, row_spans, row_xf_index, false);
+
+            for (col, cell) in cols {
+                let xf_index = self.data_xf_index(
+                    styles,
+                    writer.sanitize_policy(),
+                    row,
+                    *col,
+                    &cell.data,
+                    cell.format.as_ref(),
+                );
+
+                #[cfg(feature = "drawings")]
+                let cell_value_metadata = match cell.data {
+                    CellData::EmbeddedImage(_) => {
+                        value_metadata += 1;
+                        Some(value_metadata - 1)
+                    }
+                    _ => None,
+                };
+                #[cfg(not(feature = "drawings"))]
+                let cell_value_metadata = None;
+
+                write_cell(
+                    writer,
+                    row,
+                    *col,
+                    xf_index,
+                    &cell.data,
+                    cell_value_metadata,
+                    Some(&mut *string_table),
+                );
+            }
+
+            writer.xml_end_tag("row");
+        }
+
+        writer.xml_end_tag("sheetData");
+    }
+
+    // Write a <row> element. Rows without outline or visibility options are
+    // written with the optimized xml_row_element().
+    fn write_row(
+        &self,
+        writer: &mut XMLWriter<impl Write>,
+        row: u32,
+        spans: Option<(u16, u16)>,
+        xf_index: Option<u32>,
+        empty: bool,
+    ) {
+        let options = self.row_options.get(&row).copied().unwrap_or_default();
+        if options.outline_level == 0 && !options.collapsed {
+            writer.xml_row_element(row, spans, None, xf_index, options.hidden, empty);
+            return;
+        }
+
+        let row_number = (row + 1).to_string();
+        let spans =
+            spans.map(|(first_col, last_col)| format!("{}:{}", first_col + 1, last_col + 1));
+        let xf_index = xf_index.map(|xf_index| xf_index.to_string());
+        let outline_level = options.outline_level.to_string();
+
+        let mut attributes = vec![("r", row_number.as_str())];
+        if let Some(spans) = &spans {
+            attributes.push(("spans", spans.as_str()));
+        }
+        if let Some(xf_index) = &xf_index {
+            attributes.push(("s", xf_index.as_str()));
+            attributes.push(("customFormat", "1"));
+        }
+        if options.hidden {
+            attributes.push(("hidden", "1"));
+        }
+        if options.outline_level > 0 {
+            attributes.push(("outlineLevel", outline_level.as_str()));
+        }
+        if options.collapsed {
+            attributes.push(("collapsed", "1"));
+        }
+
+        if empty {
+            writer.xml_empty_tag("row", &attributes);
+        } else {
+            writer.xml_start_tag("row", &attributes);
+        }
+    }
+
+    // Write the <cols> element. Adjacent columns with the same properties
+    // are collapsed into a single <col> range.
+    fn write_cols(&self, writer: &mut XMLWriter<impl Write>, styles: &mut Styles) {
+        if self.columns.is_empty() {
+            return;
+        }
+
+        // Collapse adjacent columns with the same properties into ranges of
+        // (first_col, last_col, properties).
+        let mut ranges: Vec<(u16, u16, ColumnProperties)> = vec![];
+
+        for (col, column) in &self.columns {
+            let xf_index = column.format.as_ref().map(|format| styles.xf_index(format));
+            let properties = (
+                column.width,
+                xf_index,
+                column.hidden,
+                column.outline_level,
+                column.collapsed,
+            );
+
+            match ranges.last_mut() {
+                Some((_, last_col, last_properties))
+                    if *last_col + 1 == *col && *last_properties == properties =>
+                {
+                    *last_col = *col;
+                }
+                _ => ranges.push((*col, *col, properties)),
+            }
+        }
+
+        writer.xml_start_tag_only("cols");
+
+        for (first_col, last_col, properties) in ranges {
+            let (width, xf_index, hidden, outline_level, collapsed) = properties;
+
+            // Hidden columns with the default width are stored with a zero
+            // width, which Excel also treats as a custom width.
+            let width = if hidden && width == DEFAULT_COL_WIDTH {
+                0.0
+            } else {
+                width
+            };
+            let is_custom_width = width != DEFAULT_COL_WIDTH;
+
+            let min = (first_col + 1).to_string();
+            let max = (last_col + 1).to_string();
+            let width = styles.default_font().column_width(width).to_string();
+            let style = xf_index.map(|xf_index| xf_index.to_string());
+            let outline_level = outline_level.to_string();
+
+            let mut attributes = vec![
+                ("min", min.as_str()),
+                ("max", max.as_str()),
+                ("width", width.as_str()),
+            ];
+            if let Some(style) = &style {
+                attributes.push(("style", style.as_str()));
+            }
+            if hidden {
+                attributes.push(("hidden", "1"));
+            }
+            if is_custom_width {
+                attributes.push(("customWidth", "1"));
+            }
+            if outline_level != "0" {
+                attributes.push(("outlineLevel", outline_level.as_str()));
+            }
+            if collapsed {
+                attributes.push(("collapsed", "1"));
+            }
+            writer.xml_empty_tag("col", &attributes);
+        }
+
+        writer.xml_end_tag("cols");
+    }
+}
+
+/// A worksheet that is written in streaming mode, created with
+/// [`Worksheet::start_streaming()`].
+///
+/// Each cell is written to the part immediately, so only the position of the
+/// last cell is kept in memory. Cells must be written in row order, and in
+/// column order within a row. Strings are written as inline strings, which
+/// Excel reads the same as shared strings but which don't need a table of
+/// all the strings in the workbook.
+///
+/// Hyperlinks are written to the `<hyperlinks>` element and the worksheet
+/// relationships after the cells, so they are kept until then as compact
+/// records in a memory buffer, or in a file with
+/// [`spill_hyperlinks()`](StreamingWorksheet::spill_hyperlinks) for
+/// worksheets with so many hyperlinks that the buffer would matter.
+///
+/// ```
+/// # use excel_xmlwriter::styles::Styles;
+/// # use excel_xmlwriter::worksheet::Worksheet;
+/// # use excel_xmlwriter::XMLWriter;
+/// #
+/// # fn main() -> Result<(), excel_xmlwriter::error::XlsxError> {
+/// let worksheet = Worksheet::new();
+/// let mut styles = Styles::new();
+/// let mut writer = XMLWriter::new(vec![]);
+///
+/// let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+/// stream.spill_hyperlinks(tempfile::tempfile().unwrap())?;
+///
+/// for row in 0..1000 {
+///     let url = format!("https://www.example.com/items/{row}");
+///     stream.write_url(row, 0, &url)?;
+/// }
+///
+/// let mut relationships = stream.finish();
+/// assert_eq!(relationships.len(), 1000);
+///
+/// let mut rels_writer = XMLWriter::new(vec![]);
+/// relationships.assemble_xml_file(&mut rels_writer);
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamingWorksheet<'w, W: Write> {
+    worksheet: &'w Worksheet,
+    writer: &'w mut XMLWriter<W>,
+    styles: &'w mut Styles,
+    last_cell: Option<(u32, u16)>,
+    next_row: u32,
+    hyperlinks: HyperlinkSpill,
+    #[cfg(feature = "tracing")]
+    rows_written: u64,
+}
+
+impl<W: Write> StreamingWorksheet<'_, W> {
+    /// Write an inline string to a zero-indexed cell.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, wrapped in an [`XlsxError::Context`] with the part
+    /// name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_string(&mut self, row: u32, col: u16, string: &str) -> Result<(), XlsxError> {
+        self.write_cell(row, col, &CellData::String(string.to_string()), None)
+    }
+
+    /// Write an inline string to a zero-indexed cell with a format.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, wrapped in an [`XlsxError::Context`] with the part
+    /// name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_string_with_format(
+        &mut self,
+        row: u32,
+        col: u16,
+        string: &str,
+        format: &Format,
+    ) -> Result<(), XlsxError> {
+        let data = CellData::String(string.to_string());
+        self.write_cell(row, col, &data, Some(format))
+    }
+
+    /// Write a number to a zero-indexed cell.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, or [`XlsxError::NonFiniteNumber`] if the number is
+    /// NaN or infinite, wrapped in an [`XlsxError::Context`] with the part
+    /// name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_number(&mut self, row: u32, col: u16, number: f64) -> Result<(), XlsxError> {
+        self.write_cell(row, col, &CellData::Number(number), None)
+    }
+
+    /// Write a number to a zero-indexed cell with a format.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, or [`XlsxError::NonFiniteNumber`] if the number is
+    /// NaN or infinite, wrapped in an [`XlsxError::Context`] with the part
+    /// name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_number_with_format(
+        &mut self,
+        row: u32,
+        col: u16,
+        number: f64,
+        format: &Format,
+    ) -> Result<(), XlsxError> {
+        self.write_cell(row, col, &CellData::Number(number), Some(format))
+    }
+
+    /// Write a formula to a zero-indexed cell. The leading `=` is optional
+    /// and the result is written as 0.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, wrapped in an [`XlsxError::Context`] with the part
+    /// name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_formula(&mut self, row: u32, col: u16, formula: &str) -> Result<(), XlsxError> {
+        self.write_cell(row, col, &formula_data(formula), None)
+    }
+
+    /// Write a formula to a zero-indexed cell with a format.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, wrapped in an [`XlsxError::Context`] with the part
+    /// name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_formula_with_format(
+        &mut self,
+        row: u32,
+        col: u16,
+        formula: &str,
+        format: &Format,
+    ) -> Result<(), XlsxError> {
+        self.write_cell(row, col, &formula_data(formula), Some(format))
+    }
+
+    /// Write a hyperlink to a zero-indexed cell, like
+    /// [`Worksheet::write_url()`].
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::InvalidUrl`] if the URL isn't supported,
+    /// [`XlsxError::CellOutOfOrder`] if the cell isn't after the last cell
+    /// that was written, or [`XlsxError::HyperlinkSpill`] if the hyperlink
+    /// couldn't be written to the spill file, wrapped in an
+    /// [`XlsxError::Context`] with the part name and element path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_url(&mut self, row: u32, col: u16, url: &str) -> Result<(), XlsxError> {
+        self.write_url_with_text(row, col, url, url_text(url))
+    }
+
+    /// Write a hyperlink to a zero-indexed cell with alternative text for the
+    /// cell, like [`Worksheet::write_url_with_text()`].
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`write_url()`](StreamingWorksheet::write_url). If the
+    /// hyperlink can't be written to the spill file, the cell has already
+    /// been written, without the hyperlink.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or column is beyond Excel's limits.
+    pub fn write_url_with_text(
+        &mut self,
+        row: u32,
+        col: u16,
+        url: &str,
+        text: &str,
+    ) -> Result<(), XlsxError> {
+        let hyperlink = parse_url(url).map_err(|error| self.context_error(error))?;
+
+        let data = CellData::String(text.to_string());
+        self.write_cell(row, col, &data, Some(&Format::new().set_hyperlink()))?;
+
+        self.hyperlinks
+            .push(row, col, &hyperlink)
+            .map_err(|error| self.context_error(XlsxError::HyperlinkSpill(error.to_string())))
+    }
+
+    /// Keep the hyperlinks in a file, such as a temporary file from
+    /// `tempfile::tempfile()`, instead of a memory buffer. The file is
+    /// truncated and the hyperlinks that have already been written are
+    /// moved to it. It must be open for reading as well as writing, since
+    /// the hyperlinks are read back at the end of the part.
+    ///
+    /// # Errors
+    ///
+    /// [`XlsxError::HyperlinkSpill`] if the hyperlinks couldn't be written to
+    /// the file, wrapped in an [`XlsxError::Context`] with the part name and
+    /// element path.
+    pub fn spill_hyperlinks(&mut self, file: File) -> Result<(), XlsxError> {
+        self.hyperlinks
+            .spill_to(file)
+            .map_err(|error| self.context_error(XlsxError::HyperlinkSpill(error.to_string())))
+    }
+
+    /// Write the end of the worksheet part, closing the last row, and return
+    /// the relationships of the worksheet, which include the external
+    /// hyperlinks that were written to the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hyperlinks can't be read back from the spill file.
+    pub fn finish(mut self) -> StreamedRelationships {
+        if self.last_cell.is_some() {
+            self.writer.xml_end_tag("row");
+        }
+        self.write_formatted_rows(ROW_MAX);
+
+        self.writer.xml_end_tag("sheetData");
+        self.worksheet
+            .write_footer(self.writer, self.styles, Some(&mut self.hyperlinks));
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            part = %self.writer.context(),
+            rows = self.rows_written,
+            "finished streaming worksheet"
+        );
+
+        let mut relationships = Relationships::new();
+        self.worksheet.add_part_relationships(&mut relationships);
+
+        StreamedRelationships {
+            hyperlinks: self.hyperlinks,
+            relationships,
+        }
+    }
+
+    fn write_cell(
+        &mut self,
+        row: u32,
+        col: u16,
+        data: &CellData,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        check_cell(row, col);
+
+        if matches!(data, CellData::Number(number) if !number.is_finite()) {
+            return Err(self.context_error(XlsxError::NonFiniteNumber { row, col }));
+        }
+
+        match self.last_cell {
+            Some((last_row, last_col))
+                if row < last_row || (row == last_row && col <= last_col) =>
+            {
+                return Err(self.context_error(XlsxError::CellOutOfOrder { row, col }));
+            }
+            Some((last_row, _)) if row == last_row => {}
+            last_cell => {
+                if last_cell.is_some() {
+                    self.writer.xml_end_tag("row");
+                }
+                self.write_formatted_rows(row);
+
+                let row_xf_index = self.row_xf_index(row);
+                self.worksheet
+                    .write_row(self.writer, row, None, row_xf_index, false);
+                self.next_row = row + 1;
+
+                #[cfg(feature = "tracing")]
+                {
+                    self.rows_written += 1;
+                    trace_row_batch(self.writer, self.rows_written, row);
+                }
+            }
+        }
+
+        let xf_index = self.worksheet.data_xf_index(
+            self.styles,
+            self.writer.sanitize_policy(),
+            row,
+            col,
+            data,
+            format,
+        );
+        write_cell(self.writer, row, col, xf_index, data, None, None);
+
+        self.last_cell = Some((row, col));
+        Ok(())
+    }
+
+    // Wrap an error with the part name and element path.
+    fn context_error(&self, error: XlsxError) -> XlsxError {
+        XlsxError::Context {
+            context: self.writer.context(),
+            error: Box::new(error),
+        }
+    }
+
+    // Write the formatted, hidden or grouped rows without cells before a
+    // row, as empty row elements.
+    fn write_formatted_rows(&mut self, before_row: u32) {
+        let worksheet = self.worksheet;
+        let rows: BTreeSet<u32> = worksheet
+            .row_formats
+            .range(self.next_row..before_row)
+            .map(|(row, _)| *row)
+            .chain(
+                worksheet
+                    .row_options
+                    .range(self.next_row..before_row)
+                    .map(|(row, _)| *row),
+            )
+            .collect();
+
+        for row in rows {
+            let row_xf_index = self.row_xf_index(row);
+            worksheet.write_row(self.writer, row, None, row_xf_index, true);
+        }
+    }
+
+    fn row_xf_index(&mut self, row: u32) -> Option<u32> {
+        self.worksheet
+            .row_formats
+            .get(&row)
+            .map(|format| self.styles.xf_index(format))
+    }
+}
+
+/// The relationships of a worksheet that was written in streaming mode, for
+/// the `xl/worksheets/_rels/sheetN.xml.rels` part, returned by
+/// [`StreamingWorksheet::finish()`]. The part is only needed if the
+/// relationships aren't empty.
+///
+/// The external hyperlinks of the stream are the first relationships, and
+/// are read back from its buffer or spill file when the part is written.
+/// The hyperlinks of the [`Worksheet`] itself are ignored in streaming mode,
+/// like its cells, so use these relationships instead of
+/// [`Worksheet::relationships()`].
+pub struct StreamedRelationships {
+    hyperlinks: HyperlinkSpill,
+    relationships: Relationships,
+}
+
+impl StreamedRelationships {
+    /// The number of relationships.
+    pub fn len(&self) -> usize {
+        self.hyperlinks.external_count + self.relationships.len()
+    }
+
+    /// Whether there are no relationships.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the `.rels` part.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hyperlinks can't be read back from the spill file.
+    pub fn assemble_xml_file(&mut self, writer: &mut XMLWriter<impl Write>) {
+        let hyperlinks = &mut self.hyperlinks;
+
+        self.relationships
+            .assemble_xml_file_after(writer, |writer| {
+                let mut rel_index = 0;
+                hyperlinks.for_each(|_, _, hyperlink| {
+                    if let Hyperlink::External { url, .. } = hyperlink {
+                        rel_index += 1;
+                        relationship::write_hyperlink_relationship(writer, rel_index, &url);
+                    }
+                });
+                rel_index
+            });
+    }
+}
+
+// Emit a `tracing` event after each batch of rows, with the number of rows
+// written so far and the writer's throughput.
+#[cfg(feature = "tracing")]
+fn trace_row_batch(writer: &XMLWriter<impl Write>, rows_written: u64, row: u32) {
+    if rows_written.is_multiple_of(TRACE_ROW_BATCH) {
+        let stats = writer.stats();
+        tracing::debug!(
+            part = %writer.context(),
+            rows = rows_written,
+            row,
+            bytes = stats.bytes(),
+            elements_per_sec = stats.elements_per_sec(),
+            "wrote row batch"
+        );
+    }
+}
+
+// Write a <c> cell element. Strings are written to the shared string table
+// if there is one, and otherwise as inline strings. Embedded images are
+// written as `#VALUE!` errors with their value metadata number.
+fn write_cell(
+    writer: &mut XMLWriter<impl Write>,
+    row: u32,
+    col: u16,
+    xf_index: u32,
+    data: &CellData,
+    #[cfg_attr(not(feature = "drawings"), allow(unused_variables))] value_metadata: Option<u32>,
+    string_table: Option<&mut SharedStringTable>,
+) {
+    let cell_ref = rowcol_to_cell(row, col);
+    let xf_index = xf_index.to_string();
+
+    let mut attributes = vec![("r", cell_ref.as_str())];
+    if xf_index != "0" {
+        attributes.push(("s", xf_index.as_str()));
+    }
+
+    match data {
+        CellData::String(string) => match string_table {
+            Some(string_table) => {
+                let index = string_table.shared_string_index(string);
+                writer.xml_string_element(index, &attributes);
+            }
+            None => writer.xml_inline_string_element(string, &attributes),
+        },
+        CellData::Number(number) => writer.xml_number_element(*number, &attributes),
+        CellData::Formula(formula, result) => {
+            writer.xml_formula_element(formula, *result, &attributes);
+        }
+        CellData::ArrayFormula {
+            formula,
+            range,
+            dynamic,
+        } => {
+            if *dynamic {
+                attributes.push(("cm", "1"));
+            }
+            writer.xml_start_tag("c", &attributes);
+            writer.xml_data_element("f", formula, &vec![("t", "array"), ("ref", range)]);
+            writer.xml_data_element("v", "0", &vec![]);
+            writer.xml_end_tag("c");
+        }
+        #[cfg(feature = "drawings")]
+        CellData::EmbeddedImage(_) => {
+            let value_metadata = value_metadata.map(|number| number.to_string());
+            attributes.push(("t", "e"));
+            if let Some(value_metadata) = &value_metadata {
+                attributes.push(("vm", value_metadata));
+            }
+            writer.xml_start_tag("c", &attributes);
+            writer.xml_data_element("v", "#VALUE!", &vec![]);
+            writer.xml_end_tag("c");
+        }
+        CellData::Blank => writer.xml_empty_tag("c", &attributes),
+    }
+}
+
+// Write the <pane> element and the <selection> elements of the panes. The
+// active pane is the bottom right pane of the split, and the split direction
+// decides which of the panes exist.
+fn write_panes(writer: &mut XMLWriter<impl Write>, panes: Panes) {
+    let (x_split, y_split, row, col, state) = match panes {
+        Panes::Frozen { row, col } => (col as u32, row, row, col, Some("frozen")),
+        Panes::Split {
+            x_split,
+            y_split,
+            row,
+            col,
+        } => (x_split, y_split, row, col, None),
+    };
+
+    let active_pane = match (x_split > 0, y_split > 0) {
+        (true, true) => "bottomRight",
+        (false, true) => "bottomLeft",
+        _ => "topRight",
+    };
+
+    let x_split = x_split.to_string();
+    let y_split = y_split.to_string();
+    let top_left_cell = rowcol_to_cell(row, col);
+
+    let mut attributes = vec![];
+    if x_split != "0" {
+        attributes.push(("xSplit", x_split.as_str()));
+    }
+    if y_split != "0" {
+        attributes.push(("ySplit", y_split.as_str()));
+    }
+    attributes.push(("topLeftCell", top_left_cell.as_str()));
+    attributes.push(("activePane", active_pane));
+    if let Some(state) = state {
+        attributes.push(("state", state));
+    }
+    writer.xml_empty_tag("pane", &attributes);
+
+    // With panes in both directions, Excel also keeps the selections of the
+    // top right and bottom left panes, at the first cell of each.
+    if active_pane == "bottomRight" {
+        let top_right_cell = rowcol_to_cell(0, col);
+        let bottom_left_cell = rowcol_to_cell(row, 0);
+
+        for (pane, cell) in [
+            ("topRight", &top_right_cell),
+            ("bottomLeft", &bottom_left_cell),
+        ] {
+            let attributes = vec![("pane", pane), ("activeCell", cell), ("sqref", cell)];
+            writer.xml_empty_tag("selection", &attributes);
+        }
+    }
+
+    writer.xml_empty_tag("selection", &vec![("pane", active_pane)]);
+}
+
+// Convert a zero-indexed cell range to a range like `B2:D4`.
+fn range_to_string((first_row, first_col, last_row, last_col): (u32, u16, u32, u16)) -> String {
+    format!(
+        "{}:{}",
+        rowcol_to_cell(first_row, first_col),
+        rowcol_to_cell(last_row, last_col)
+    )
+}
+
+// Convert a zero-indexed cell range to a `sqref` attribute value, which is
+// a single cell like `B2` if the range is one cell.
+fn sqref(range: CellRange) -> String {
+    if range.0 == range.2 && range.1 == range.3 {
+        rowcol_to_cell(range.0, range.1)
+    } else {
+        range_to_string(range)
+    }
+}
+
+// Check that a zero-indexed row is within Excel's limits.
+fn check_row(row: u32) {
+    assert!(row < ROW_MAX, "row {row} is beyond Excel's last row");
+}
+
+// Check that a zero-indexed column is within Excel's limits.
+fn check_col(col: u16) {
+    assert!(col < COL_MAX, "column {col} is beyond Excel's last column");
+}
+
+// Check that a zero-indexed cell is within Excel's limits.
+fn check_cell(row: u32, col: u16) {
+    check_row(row);
+    check_col(col);
+}
+
+// Check that a range of zero-indexed cells is within Excel's limits and
+// return it with the first and last cells in order, since they can be given
+// in either order.
+fn check_range(first_row: u32, first_col: u16, last_row: u32, last_col: u16) -> CellRange {
+    let range = (
+        first_row.min(last_row),
+        first_col.min(last_col),
+        first_row.max(last_row),
+        first_col.max(last_col),
+    );
+    check_cell(range.2, range.3);
+
+    range
+}
+
+// Check that a number is one that Excel can store. NaN and infinities would
+// be written as `NaN` and `inf`, which Excel reports as a corrupt part.
+fn assert_finite(row: u32, col: u16, number: f64) {
+    assert!(
+        number.is_finite(),
+        "the number {number} in cell ({row}, {col}) is NaN or infinite"
+    );
+}
+
+// Store a formula without its leading `=`, with a default result of 0.
+fn formula_data(formula: &str) -> CellData {
+    let formula = formula.strip_prefix('=').unwrap_or(formula);
+    CellData::Formula(formula.to_string(), 0.0)
+}
+
+// Parse the target of a hyperlink, which is an external URL with one of the
+// supported schemes or a location in the workbook after `internal:`.
+fn parse_url(url: &str) -> Result<Hyperlink, XlsxError> {
+    let hyperlink = if let Some(location) = url.strip_prefix("internal:") {
+        Hyperlink::Internal(location.to_string())
+    } else if URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme)) {
+        let (url, anchor) = match url.split_once('#') {
+            Some((url, anchor)) => (url, Some(anchor.to_string())),
+            None => (url, None),
+        };
+        Hyperlink::External {
+            url: url.to_string(),
+            anchor,
+        }
+    } else {
+        return Err(XlsxError::InvalidUrl(url.to_string()));
+    };
+
+    if url.chars().count() > MAX_URL_LENGTH {
+        return Err(XlsxError::InvalidUrl(url.to_string()));
+    }
+
+    Ok(hyperlink)
+}
+
+// The default cell text of a hyperlink, the URL without a `mailto:` or
+// `internal:` prefix.
+fn url_text(url: &str) -> &str {
+    url.strip_prefix("mailto:")
+        .or_else(|| url.strip_prefix("internal:"))
+        .unwrap_or(url)
+}
+
+// Write a <hyperlink> element. The relationship id of an external link is
+// the one after `rel_index`, which is incremented.
+fn write_hyperlink(
+    writer: &mut XMLWriter<impl Write>,
+    row: u32,
+    col: u16,
+    hyperlink: &Hyperlink,
+    rel_index: &mut usize,
+) {
+    let cell_ref = rowcol_to_cell(row, col);
+    let mut attributes = vec![("ref", cell_ref.as_str())];
+
+    match hyperlink {
+        Hyperlink::External { anchor, .. } => {
+            *rel_index += 1;
+            let rel_id = format!("rId{rel_index}");
+            attributes.push(("r:id", &rel_id));
+            if let Some(anchor) = anchor {
+                attributes.push(("location", anchor));
+            }
+            writer.xml_empty_tag("hyperlink", &attributes);
+        }
+        Hyperlink::Internal(location) => {
+            attributes.push(("location", location));
+            attributes.push(("display", location));
+            writer.xml_empty_tag("hyperlink", &attributes);
+        }
+    }
+}
+
+// Add a string to a hyperlink record, after its length in bytes.
+fn push_spill_string(record: &mut Vec<u8>, string: &str) {
+    record.extend_from_slice(&(string.len() as u32).to_le_bytes());
+    record.extend_from_slice(string.as_bytes());
+}
+
+// Read a string of a hyperlink record.
+fn read_spill_string(reader: &mut impl Read) -> io::Result<String> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+
+    let mut string = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut string)?;
+
+    String::from_utf8(string).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{IgnoreError, Worksheet};
+    #[cfg(feature = "drawings")]
+    use crate::button::Button;
+    #[cfg(feature = "charts")]
+    use crate::chart::{Chart, ChartType};
+    use crate::conditional_format::{
+        CellCriteria, ConditionalFormatBlanks, ConditionalFormatCell, ConditionalFormatFormula,
+    };
+    use crate::data_validation::DataValidation;
+    use crate::error::XlsxError;
+    #[cfg(feature = "drawings")]
+    use crate::image::tests::png_data;
+    #[cfg(feature = "drawings")]
+    use crate::image::Image;
+    #[cfg(feature = "drawings")]
+    use crate::note::Note;
+    #[cfg(feature = "drawings")]
+    use crate::ole_object::OleObject;
+    use crate::page_setup::PageSetup;
+    #[cfg(feature = "drawings")]
+    use crate::part::XlsxPart;
+    use crate::protection::ProtectionOptions;
+    use crate::sanitize::SanitizePolicy;
+    use crate::shared_strings::SharedStringTable;
+    use crate::sparkline::{Sparkline, SparklineType};
+    use crate::styles::{Color, DefaultFont, Format, Styles};
+    use crate::table::Table;
+    use crate::testing::read_xmlfile_data;
+    #[cfg(feature = "drawings")]
+    use crate::threaded_comment::ThreadedComment;
+    use crate::XMLWriter;
+    use tempfile::tempfile;
+
+    use pretty_assertions::assert_eq;
+
+    fn assemble_worksheet(worksheet: &Worksheet, styles: &mut Styles) -> String {
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        writer.set_check_attribute_order(true);
+
+        worksheet.assemble_xml_file(&mut writer, styles, &mut SharedStringTable::new());
+
+        read_xmlfile_data(&mut tempfile)
+    }
+
+    #[test]
+    fn test_assemble_empty_worksheet() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            r#"<dimension ref="A1"/><sheetViews><sheetView workbookViewId="0"/></sheetViews>"#,
+            r#"<sheetFormatPr defaultRowHeight="15"/><sheetData/><pageMargins left="0.7" "#,
+            r#"right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/></worksheet>"#,
+        );
+
+        let mut styles = Styles::new();
+        let worksheet = Worksheet::new();
+
+        assert_eq!(assemble_worksheet(&worksheet, &mut styles), expected);
+    }
+
+    #[test]
+    fn test_column_formats() {
+        let expected = concat!(
+            r#"<cols><col min="1" max="1" width="9.140625" style="1"/>"#,
+            r#"<col min="3" max="5" width="9.140625" style="2"/>"#,
+            r#"<col min="6" max="6" width="9.140625" style="1"/></cols>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let bold = Format::new().set_bold();
+        let italic = Format::new().set_italic();
+
+        worksheet.set_column_format(0, 0, &bold);
+        worksheet.set_column_format(4, 2, &italic);
+        worksheet.set_column_format(5, 5, &Format::new().set_bold());
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    fn test_set_column() {
+        let expected = concat!(
+            r#"<cols><col min="1" max="1" width="20.7109375" style="1" customWidth="1"/>"#,
+            r#"<col min="2" max="3" width="9.140625"/>"#,
+            r#"<col min="4" max="5" width="0" hidden="1" customWidth="1"/>"#,
+            r#"<col min="6" max="6" width="12.7109375" hidden="1" customWidth="1"/>"#,
+            r#"<col min="7" max="7" width="9.140625" style="2"/></cols>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let bold = Format::new().set_bold();
+        let italic = Format::new().set_italic();
+
+        worksheet.set_column(0, 0, 20.0, Some(&bold), false);
+        worksheet.set_column(2, 1, 8.43, None, false);
+        worksheet.set_column(3, 3, 8.43, None, true);
+        worksheet.set_column(4, 4, 8.43, None, true);
+        worksheet.set_column(5, 5, 12.0, None, true);
+        worksheet.set_column(6, 6, 8.43, None, false);
+        worksheet.set_column_format(6, 6, &italic);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected), "{got}");
+
+        // The column format applies to the cells in the column.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 0, None), 1);
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 1, None), 0);
+    }
+
+    #[test]
+    fn test_column_format_overwrite() {
+        let expected = concat!(
+            r#"<cols><col min="1" max="1" width="9.140625" style="1"/>"#,
+            r#"<col min="2" max="2" width="9.140625" style="2"/>"#,
+            r#"<col min="3" max="3" width="9.140625" style="1"/></cols>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_column_format(0, 2, &Format::new().set_bold());
+        worksheet.set_column_format(1, 1, &Format::new().set_italic());
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    fn test_cell_xf_index() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let bold = Format::new().set_bold();
+        let italic = Format::new().set_italic();
+
+        worksheet.set_column_format(1, 1, &bold);
+
+        // Unformatted cell in an unformatted column.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 0, None), 0);
+
+        // Unformatted cell in a formatted column.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 1, None), 1);
+
+        // Formatted cell in a formatted column.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 1, Some(&italic)), 2);
+
+        // Formatted cell in an unformatted column.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 0, Some(&italic)), 2);
+    }
+
+    #[test]
+    fn test_cell_xf_index_precedence() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let column_format = Format::new().set_bold();
+        let row_format = Format::new().set_italic();
+        let cell_format = Format::new().set_font_size(20.0);
+
+        worksheet.set_column_format(1, 1, &column_format);
+        worksheet.set_row_format(1, &row_format);
+
+        // Unformatted cell in a formatted column.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 0, 1, None), 1);
+
+        // Unformatted cell in a formatted row.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 1, 0, None), 2);
+
+        // Unformatted cell in a formatted row and column. The row wins.
+        assert_eq!(worksheet.cell_xf_index(&mut styles, 1, 1, None), 2);
+
+        // Formatted cell in a formatted row and column. The cell wins.
+        assert_eq!(
+            worksheet.cell_xf_index(&mut styles, 1, 1, Some(&cell_format)),
+            3
+        );
+    }
+
+    #[test]
+    fn test_row_formats() {
+        let expected = concat!(
+            r#"<sheetData><row r="1" s="1" customFormat="1"/>"#,
+            r#"<row r="3" s="2" customFormat="1"/></sheetData>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_row_format(2, &Format::new().set_italic());
+        worksheet.set_row_format(0, &Format::new().set_bold());
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_row_format_out_of_range() {
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_row_format(1_048_576, &Format::new());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_column_format_out_of_range() {
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_column_format(0, 16_384, &Format::new());
+    }
+
+    #[test]
+    fn test_zoom() {
+        let expected = r#"<sheetViews><sheetView zoomScale="150" zoomScaleNormal="150" workbookViewId="0"/></sheetViews>"#;
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_zoom(150);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected));
+
+        // The default zoom isn't written.
+        worksheet.set_zoom(100);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<sheetView workbookViewId="0"/>"#));
+    }
+
+    #[test]
+    fn test_page_setup() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let page_setup = PageSetup::new()
+            .set_fit_to_pages(1, 0)
+            .set_footer("&CPage &P");
+        worksheet.set_page_setup(&page_setup);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"relationships"><sheetPr><pageSetUpPr fitToPage="1"/></sheetPr>"#,
+            r#"<dimension ref="A1"/>"#,
+        )));
+        assert!(got.ends_with(concat!(
+            r#"header="0.3" footer="0.3"/><pageSetup fitToHeight="0"/>"#,
+            r#"<headerFooter><oddFooter>&amp;CPage &amp;P</oddFooter></headerFooter></worksheet>"#,
+        )));
+    }
+
+    #[test]
+    fn test_panes() {
+        let tests = [
+            (
+                1,
+                0,
+                concat!(
+                    r#"<pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/>"#,
+                    r#"<selection pane="bottomLeft"/>"#,
+                ),
+            ),
+            (
+                0,
+                1,
+                concat!(
+                    r#"<pane xSplit="1" topLeftCell="B1" activePane="topRight" state="frozen"/>"#,
+                    r#"<selection pane="topRight"/>"#,
+                ),
+            ),
+            (
+                2,
+                3,
+                concat!(
+                    r#"<pane xSplit="3" ySplit="2" topLeftCell="D3" activePane="bottomRight" state="frozen"/>"#,
+                    r#"<selection pane="topRight" activeCell="D1" sqref="D1"/>"#,
+                    r#"<selection pane="bottomLeft" activeCell="A3" sqref="A3"/>"#,
+                    r#"<selection pane="bottomRight"/>"#,
+                ),
+            ),
+        ];
+
+        for (row, col, expected) in tests {
+            let mut styles = Styles::new();
+            let mut worksheet = Worksheet::new();
+            worksheet.freeze_panes(row, col);
+
+            let expected = format!(
+                r#"<sheetViews><sheetView workbookViewId="0">{expected}</sheetView></sheetViews>"#
+            );
+            let got = assemble_worksheet(&worksheet, &mut styles);
+            assert!(got.contains(&expected), "{got}");
+        }
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        worksheet.split_panes(8.43, 15.0);
+
+        let expected = concat!(
+            r#"<sheetView workbookViewId="0">"#,
+            r#"<pane xSplit="1350" ySplit="600" topLeftCell="B2" activePane="bottomRight"/>"#,
+            r#"<selection pane="topRight" activeCell="B1" sqref="B1"/>"#,
+            r#"<selection pane="bottomLeft" activeCell="A2" sqref="A2"/>"#,
+            r#"<selection pane="bottomRight"/></sheetView>"#,
+        );
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected), "{got}");
+
+        worksheet.split_panes(0.0, 30.0);
+        let expected = concat!(
+            r#"<pane ySplit="900" topLeftCell="A3" activePane="bottomLeft"/>"#,
+            r#"<selection pane="bottomLeft"/>"#,
+        );
+        assert!(assemble_worksheet(&worksheet, &mut styles).contains(expected));
+
+        // Panes at (0, 0) are removed.
+        worksheet.freeze_panes(0, 0);
+        assert!(assemble_worksheet(&worksheet, &mut styles)
+            .contains(r#"<sheetView workbookViewId="0"/>"#));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zoom_out_of_range() {
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_zoom(401);
+    }
+
+    #[test]
+    fn test_default_font_metrics() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_column_format(0, 0, &Format::new().set_bold());
+
+        // Arial 10 has the same digit width as Calibri 11.
+        styles.set_default_font(&DefaultFont::new("Arial", 10.0));
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<sheetFormatPr defaultRowHeight="12.75"/>"#));
+        assert!(got.contains(r#"<col min="1" max="1" width="9.140625" style="1"/>"#));
+
+        // A wider font with explicit metrics.
+        styles.set_default_font(&DefaultFont::new("Verdana", 12.0).set_metrics(9.0, 15.75));
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<sheetFormatPr defaultRowHeight="15.75"/>"#));
+        assert!(got.contains(r#"<col min="1" max="1" width="9.21875" style="1"/>"#));
+    }
+
+    #[test]
+    fn test_write_cells() {
+        let expected = concat!(
+            r#"<dimension ref="A1:C3"/>"#,
+            r#"<sheetViews><sheetView workbookViewId="0"/></sheetViews>"#,
+            r#"<sheetFormatPr defaultRowHeight="15"/><sheetData>"#,
+            r#"<row r="1" spans="1:3"><c r="A1" t="s"><v>0</v></c>"#,
+            r#"<c r="B1"><v>123.5</v></c><c r="C1" s="1" t="s"><v>1</v></c></row>"#,
+            r#"<row r="3" spans="1:3"><c r="A3" t="s"><v>0</v></c>"#,
+            r#"<c r="B3"><f>SUM(B1:B2)</f><v>0</v></c></row></sheetData>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut string_table = SharedStringTable::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_formula(2, 1, "=SUM(B1:B2)");
+        worksheet.write_string(2, 0, "Hello");
+        worksheet.write_string_with_format(0, 2, "World", &Format::new().set_bold());
+        worksheet.write_number(0, 1, 123.5);
+        worksheet.write_string(0, 0, "Hello");
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        worksheet.assemble_xml_file(&mut writer, &mut styles, &mut string_table);
+        let got = read_xmlfile_data(&mut tempfile);
+
+        assert!(got.contains(expected));
+
+        // Strings are added to the table in row and column order.
+        assert_eq!(string_table.count(), 3);
+        assert_eq!(string_table.unique_count(), 2);
+        assert_eq!(string_table.shared_string_index("World"), 1);
+    }
+
+    #[test]
+    fn test_write_cells_with_formats() {
+        let expected = concat!(
+            r#"<sheetData><row r="1" spans="1:2" s="1" customFormat="1">"#,
+            r#"<c r="A1" s="1"><v>1</v></c><c r="B1" s="2"><v>2</v></c></row>"#,
+            r#"<row r="2" spans="1:2"><c r="A2"><v>3</v></c>"#,
+            r#"<c r="B2" s="3"><f>A1+A2</f><v>0</v></c></row>"#,
+            r#"<row r="20" spans="5:5"><c r="E20"><v>4</v></c></row>"#,
+            r#"<row r="21" s="2" customFormat="1"/></sheetData>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let bold = Format::new().set_bold();
+        let italic = Format::new().set_italic();
+        let red = Format::new().set_font_color(crate::styles::Color::Rgb(0xFF0000));
+
+        worksheet.set_row_format(0, &bold);
+        worksheet.set_row_format(20, &italic);
+        worksheet.write_number(0, 0, 1.0);
+        worksheet.write_number_with_format(0, 1, 2.0, &italic);
+        worksheet.write_number(1, 0, 3.0);
+        worksheet.write_formula_with_format(1, 1, "A1+A2", &red);
+        worksheet.write_number(19, 4, 4.0);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<dimension ref="A1:E20"/>"#));
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    fn test_overwrite_cell() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_string(3, 3, "Hello");
+        worksheet.write_number(3, 3, 5.0);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<dimension ref="D4"/>"#));
+        assert!(got.contains(r#"<row r="4" spans="4:4"><c r="D4"><v>5</v></c></row>"#));
+    }
+
+    #[test]
+    fn test_write_array_formulas() {
+        let expected = concat!(
+            r#"<sheetData><row r="1" spans="1:4"><c r="A1" s="1"><f t="array" ref="A1:A2">"#,
+            r#"A3:A4*B3:B4</f><v>0</v></c><c r="C1"><f t="array" ref="C1">SUM(A3:B4)</f>"#,
+            r#"<v>0</v></c><c r="D1" cm="1"><f t="array" ref="D1">LEN(A3:A4)</f><v>0</v>"#,
+            r#"</c></row><row r="2" spans="1:4"><c r="A2" s="1"><v>0</v></c></row></sheetData>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.write_array_formula_with_format(1, 0, 0, 0, "{=A3:A4*B3:B4}", &bold);
+        worksheet.write_array_formula(0, 2, 0, 2, "SUM(A3:B4)");
+        assert!(!worksheet.has_dynamic_arrays());
+
+        worksheet.write_dynamic_array_formula(0, 3, 0, 3, "=LEN(A3:A4)");
+        assert!(worksheet.has_dynamic_arrays());
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_write_cell_out_of_range() {
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_number(0, 16_384, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "is NaN or infinite")]
+    fn test_write_non_finite_number() {
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_number(0, 0, f64::INFINITY);
+    }
+
+    #[test]
+    fn test_streaming_worksheet() {
+        let expected = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n",
+            r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            r#"<sheetViews><sheetView workbookViewId="0"/></sheetViews>"#,
+            r#"<sheetFormatPr defaultRowHeight="15"/>"#,
+            r#"<cols><col min="3" max="3" width="9.140625" style="1"/></cols><sheetData>"#,
+            r#"<row r="1" s="2" customFormat="1"/>"#,
+            r#"<row r="2"><c r="A2" t="inlineStr"><is><t>Hello</t></is></c>"#,
+            r#"<c r="C2" s="1"><v>1.5</v></c></row>"#,
+            r#"<row r="4" s="2" customFormat="1"><c r="A4" s="2"><f>C2*2</f><v>0</v></c>"#,
+            r#"<c r="B4" s="3" t="inlineStr"><is><t xml:space="preserve">World </t></is></c>"#,
+            r#"</row><row r="6" s="2" customFormat="1"/></sheetData>"#,
+            r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" "#,
+            r#"header="0.3" footer="0.3"/></worksheet>"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let bold = Format::new().set_bold();
+        let italic = Format::new().set_italic();
+        let strikethrough = Format::new().set_font_strikethrough();
+
+        worksheet.set_column_format(2, 2, &bold);
+        worksheet.set_row_format(0, &italic);
+        worksheet.set_row_format(3, &italic);
+        worksheet.set_row_format(5, &italic);
+
+        // Buffered cells aren't written in streaming mode.
+        worksheet.write_number(0, 0, 99.0);
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+        stream.write_string(1, 0, "Hello").unwrap();
+        stream.write_number(1, 2, 1.5).unwrap();
+        stream.write_formula(3, 0, "=C2*2").unwrap();
+        stream
+            .write_string_with_format(3, 1, "World ", &strikethrough)
+            .unwrap();
+        stream.finish();
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_streaming_worksheet_order() {
+        let mut styles = Styles::new();
+        let worksheet = Worksheet::new();
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        writer.set_part_name("xl/worksheets/sheet1.xml");
+
+        let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+        stream.write_number(2, 2, 1.0).unwrap();
+
+        for (row, col) in [(2, 2), (2, 1), (1, 5)] {
+            assert_eq!(
+                stream.write_number(row, col, 2.0),
+                Err(XlsxError::Context {
+                    context: "xl/worksheets/sheet1.xml at worksheet/sheetData/row".to_string(),
+                    error: Box::new(XlsxError::CellOutOfOrder { row, col }),
+                })
+            );
+        }
+
+        for number in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(
+                stream.write_number(2, 3, number),
+                Err(XlsxError::Context {
+                    context: "xl/worksheets/sheet1.xml at worksheet/sheetData/row".to_string(),
+                    error: Box::new(XlsxError::NonFiniteNumber { row: 2, col: 3 }),
+                })
+            );
+        }
+
+        stream.write_number(2, 3, 3.0).unwrap();
+        stream.write_number(3, 0, 4.0).unwrap();
+        stream.finish();
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert!(got.contains(concat!(
+            r#"<sheetData><row r="3"><c r="C3"><v>1</v></c><c r="D3"><v>3</v></c></row>"#,
+            r#"<row r="4"><c r="A4"><v>4</v></c></row></sheetData>"#,
+        )));
+    }
+
+    #[test]
+    fn test_streaming_empty_worksheet() {
+        let mut styles = Styles::new();
+        let worksheet = Worksheet::new();
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+
+        worksheet.start_streaming(&mut writer, &mut styles).finish();
+
+        let got = read_xmlfile_data(&mut tempfile);
+        assert!(got.contains(r#"<sheetFormatPr defaultRowHeight="15"/><sheetData></sheetData>"#));
+    }
+
+    #[test]
+    fn test_sanitize_quote_prefix() {
+        let mut styles = Styles::new();
+        let mut string_table = SharedStringTable::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.write_string(0, 0, "=1+2");
+        worksheet.write_string_with_format(0, 1, "-3", &bold);
+        worksheet.write_string(0, 2, "a=b");
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_sanitize_policy(SanitizePolicy::QuotePrefix);
+        worksheet.assemble_xml_file(&mut writer, &mut styles, &mut string_table);
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(got.contains(concat!(
+            r#"<c r="A1" s="1" t="s"><v>0</v></c><c r="B1" s="2" t="s"><v>1</v></c>"#,
+            r#"<c r="C1" t="s"><v>2</v></c>"#,
+        )));
+        assert_eq!(styles.xf_index(&Format::new().set_quote_prefix()), 1);
+        assert_eq!(styles.xf_index(&bold.clone().set_quote_prefix()), 2);
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_sanitize_policy(SanitizePolicy::QuotePrefix);
+
+        let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+        stream.write_string(0, 0, "@SUM(A2)").unwrap();
+        stream.write_string_with_format(0, 1, "+4", &bold).unwrap();
+        stream.finish();
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(got.contains(concat!(
+            r#"<c r="A1" s="1" t="inlineStr"><is><t>@SUM(A2)</t></is></c>"#,
+            r#"<c r="B1" s="2" t="inlineStr"><is><t>+4</t></is></c>"#,
+        )));
+    }
+
+    // Write a streaming worksheet with hyperlinks and a table, with the
+    // hyperlinks in memory or spilled to a file after the first one.
+    fn stream_hyperlinks(spill: bool) -> (String, String) {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        let table = Table::new(1).set_columns(&["Link"]);
+        worksheet.add_table(5, 0, 6, 0, &table).unwrap();
+
+        // Buffered hyperlinks aren't written in streaming mode.
+        worksheet.write_url(9, 0, "https://example.org/").unwrap();
+
+        let mut writer = XMLWriter::new(vec![]);
+        writer.set_part_name("xl/worksheets/sheet1.xml");
+
+        let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+        stream
+            .write_url(0, 0, "https://www.rust-lang.org/")
+            .unwrap();
+        if spill {
+            stream.spill_hyperlinks(tempfile().unwrap()).unwrap();
+        }
+        stream.write_url(0, 1, "internal:Sheet2!A1").unwrap();
+
+        assert_eq!(
+            stream.write_url(0, 1, "https://example.com/"),
+            Err(XlsxError::Context {
+                context: "xl/worksheets/sheet1.xml at worksheet/sheetData/row".to_string(),
+                error: Box::new(XlsxError::CellOutOfOrder { row: 0, col: 1 }),
+            })
+        );
+
+        stream
+            .write_url_with_text(1, 0, "https://example.com/a?b=1&c=2#Section 2", "Docs")
+            .unwrap();
+        let mut relationships = stream.finish();
+        assert_eq!(relationships.len(), 3);
+
+        let mut rels_writer = XMLWriter::new(vec![]);
+        relationships.assemble_xml_file(&mut rels_writer);
+
+        (
+            String::from_utf8(writer.into_inner()).unwrap(),
+            String::from_utf8(rels_writer.into_inner()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_streaming_write_url() {
+        let (got, got_rels) = stream_hyperlinks(false);
+
+        let expected = concat!(
+            r#"<sheetData><row r="1"><c r="A1" s="1" t="inlineStr"><is><t>https://www.rust-lang.org/</t></is></c>"#,
+            r#"<c r="B1" s="1" t="inlineStr"><is><t>Sheet2!A1</t></is></c></row>"#,
+            r#"<row r="2"><c r="A2" s="1" t="inlineStr"><is><t>Docs</t></is></c></row></sheetData>"#,
+            r#"<hyperlinks><hyperlink ref="A1" r:id="rId1"/>"#,
+            r#"<hyperlink ref="B1" location="Sheet2!A1" display="Sheet2!A1"/>"#,
+            r#"<hyperlink ref="A2" r:id="rId2" location="Section 2"/></hyperlinks>"#,
+        );
+        assert!(got.contains(expected));
+        assert!(got.contains(r#"<tableParts count="1"><tablePart r:id="rId3"/></tableParts>"#));
+
+        let expected = concat!(
+            r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" "#,
+            r#"Target="https://www.rust-lang.org/" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" "#,
+            r#"Target="https://example.com/a?b=1&amp;c=2" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/table" "#,
+            r#"Target="../tables/table1.xml"/></Relationships>"#,
+        );
+        assert!(got_rels.contains(expected));
+    }
+
+    #[test]
+    fn test_streaming_write_url_spill() {
+        assert_eq!(stream_hyperlinks(true), stream_hyperlinks(false));
+    }
+
+    #[test]
+    fn test_merge_range() {
+        let expected = concat!(
+            r#"<dimension ref="B2:D3"/>"#,
+            r#"<sheetViews><sheetView workbookViewId="0"/></sheetViews>"#,
+            r#"<sheetFormatPr defaultRowHeight="15"/><sheetData>"#,
+            r#"<row r="2" spans="2:4"><c r="B2" s="1" t="s"><v>0</v></c>"#,
+            r#"<c r="C2" s="1"/><c r="D2" s="1"/></row>"#,
+            r#"<row r="3" spans="2:4"><c r="B3" s="1"/><c r="C3" s="1"/><c r="D3" s="1"/>"#,
+            r#"</row></sheetData><mergeCells count="2"><mergeCell ref="B2:D2"/>"#,
+            r#"<mergeCell ref="B3:D3"/></mergeCells><pageMargins"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.merge_range(1, 1, 1, 3, "Title", &bold).unwrap();
+        worksheet.merge_range(2, 3, 2, 1, "", &bold).unwrap();
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    fn test_merge_range_errors() {
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.merge_range(1, 1, 3, 3, "", &bold).unwrap();
+
+        assert_eq!(
+            worksheet.merge_range(5, 5, 5, 5, "", &bold),
+            Err(XlsxError::MergeRangeSingleCell("F6:F6".to_string()))
+        );
+
+        for (first_row, first_col, last_row, last_col) in [(0, 0, 1, 1), (3, 3, 4, 4), (2, 0, 2, 9)]
+        {
+            assert_eq!(
+                worksheet.merge_range(first_row, first_col, last_row, last_col, "", &bold),
+                Err(XlsxError::MergeRangeOverlap {
+                    range: super::range_to_string((first_row, first_col, last_row, last_col)),
+                    existing: "B2:D4".to_string(),
+                })
+            );
+        }
+
+        // Adjacent ranges don't overlap.
+        worksheet.merge_range(4, 1, 4, 3, "", &bold).unwrap();
+        worksheet.merge_range(0, 4, 3, 4, "", &bold).unwrap();
+    }
+
+    #[test]
+    fn test_write_url() {
+        let expected = concat!(
+            r#"<sheetData><row r="1" spans="1:2">"#,
+            r#"<c r="A1" s="1" t="s"><v>0</v></c><c r="B1" s="1" t="s"><v>1</v></c></row>"#,
+            r#"<row r="2" spans="1:2"><c r="A2" s="1" t="s"><v>2</v></c>"#,
+            r#"<c r="B2" s="1" t="s"><v>3</v></c></row></sheetData>"#,
+            r#"<hyperlinks><hyperlink ref="A1" r:id="rId1"/>"#,
+            r#"<hyperlink ref="B1" r:id="rId2" location="Section 2"/>"#,
+            r#"<hyperlink ref="A2" location="'Q1 Sales'!B5" display="'Q1 Sales'!B5"/>"#,
+            r#"<hyperlink ref="B2" r:id="rId3"/></hyperlinks><pageMargins"#,
+        );
+
+        let mut styles = Styles::new();
+        let mut string_table = SharedStringTable::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet
+            .write_url(1, 1, "mailto:jmcnamara@cpan.org")
+            .unwrap();
+        worksheet.write_url(1, 0, "internal:'Q1 Sales'!B5").unwrap();
+        worksheet
+            .write_url_with_text(0, 1, "https://example.com/a?b=1&c=2#Section 2", "Docs")
+            .unwrap();
+        worksheet
+            .write_url(0, 0, "https://www.rust-lang.org/")
+            .unwrap();
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        worksheet.assemble_xml_file(&mut writer, &mut styles, &mut string_table);
+        let got = read_xmlfile_data(&mut tempfile);
+
+        assert!(got.contains(expected));
+        assert_eq!(string_table.shared_string_index("Docs"), 1);
+        assert_eq!(string_table.shared_string_index("'Q1 Sales'!B5"), 2);
+        assert_eq!(string_table.shared_string_index("jmcnamara@cpan.org"), 3);
+
+        let expected = concat!(
+            r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" "#,
+            r#"Target="https://www.rust-lang.org/" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" "#,
+            r#"Target="https://example.com/a?b=1&amp;c=2" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" "#,
+            r#"Target="mailto:jmcnamara@cpan.org" TargetMode="External"/></Relationships>"#,
+        );
+
+        let mut writer = XMLWriter::new(vec![]);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let got = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    fn test_write_url_errors() {
+        let mut worksheet = Worksheet::new();
+
+        for url in [
+            "www.rust-lang.org",
+            "file:///tmp/a.xlsx",
+            "HTTP//example.com",
+            "",
+        ] {
+            assert_eq!(
+                worksheet.write_url(0, 0, url),
+                Err(XlsxError::InvalidUrl(url.to_string()))
+            );
+        }
+
+        let url = format!("https://example.com/{}", "a".repeat(2059));
+        assert!(worksheet.write_url(0, 0, &url).is_ok());
+
+        let url = format!("https://example.com/{}", "a".repeat(2060));
+        assert_eq!(
+            worksheet.write_url(0, 0, &url),
+            Err(XlsxError::InvalidUrl(url.clone()))
+        );
+
+        assert_eq!(worksheet.relationships().len(), 1);
+    }
+
+    #[test]
+    fn test_autofilter() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.autofilter(9, 3, 0, 0);
+        worksheet.merge_range(12, 0, 12, 3, "Total", &bold).unwrap();
+        assert_eq!(worksheet.autofilter_range(), Some((0, 0, 9, 3)));
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"</sheetData><autoFilter ref="A1:D10"/><mergeCells count="1">"#,
+            r#"<mergeCell ref="A13:D13"/></mergeCells><pageMargins"#,
+        )));
+    }
+
+    #[test]
+    fn test_conditional_formats() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let red = Format::new().set_font_color(Color::Rgb(0x9C0006));
+        let bold = Format::new().set_bold();
+
+        worksheet.write_number(0, 0, 1.0);
+        worksheet.add_conditional_format(
+            9,
+            0,
+            0,
+            0,
+            ConditionalFormatCell::new(CellCriteria::GreaterThan("5".to_string())).set_format(&red),
+        );
+        worksheet.add_conditional_format(2, 2, 2, 2, ConditionalFormatBlanks::new());
+        worksheet.add_conditional_format(
+            0,
+            0,
+            9,
+            0,
+            ConditionalFormatFormula::new("=$B1=0").set_format(&bold),
+        );
+        worksheet.add_conditional_format(
+            2,
+            2,
+            2,
+            2,
+            ConditionalFormatBlanks::new_no_blanks().set_format(&red),
+        );
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"</sheetData><conditionalFormatting sqref="A1:A10">"#,
+            r#"<cfRule type="cellIs" dxfId="0" priority="1" operator="greaterThan">"#,
+            r#"<formula>5</formula></cfRule>"#,
+            r#"<cfRule type="expression" dxfId="1" priority="2"><formula>$B1=0</formula>"#,
+            r#"</cfRule></conditionalFormatting><conditionalFormatting sqref="C3">"#,
+            r#"<cfRule type="containsBlanks" dxfId="2" priority="3">"#,
+            r#"<formula>LEN(TRIM(C3))=0</formula></cfRule>"#,
+            r#"<cfRule type="notContainsBlanks" dxfId="0" priority="4">"#,
+            r#"<formula>LEN(TRIM(C3))&gt;0</formula></cfRule>"#,
+            r#"</conditionalFormatting><pageMargins"#,
+        )));
+
+        // The rule formats are differential formats, not cell formats.
+        assert_eq!(styles.xf_count(), 1);
+    }
+
+    #[test]
+    fn test_data_validations() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet
+            .add_data_validation(
+                9,
+                1,
+                1,
+                1,
+                &DataValidation::new().allow_list_strings(&["Yes", "No"]),
+            )
+            .unwrap();
+        worksheet
+            .add_data_validation(
+                0,
+                3,
+                0,
+                3,
+                &DataValidation::new().set_input_message("Enter a date"),
+            )
+            .unwrap();
+        worksheet.add_conditional_format(0, 0, 0, 0, ConditionalFormatBlanks::new());
+
+        let error = worksheet.add_data_validation(
+            0,
+            0,
+            0,
+            0,
+            &DataValidation::new().set_error_title(&"x".repeat(33)),
+        );
+        assert_eq!(
+            error,
+            Err(XlsxError::DataValidationTooLong {
+                property: "error title".to_string(),
+                range: "A1".to_string(),
+            })
+        );
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"</conditionalFormatting><dataValidations count="2">"#,
+            r#"<dataValidation type="list" allowBlank="1" showInputMessage="1" showErrorMessage="1" sqref="B2:B10">"#,
+            r#"<formula1>"Yes,No"</formula1></dataValidation>"#,
+            r#"<dataValidation allowBlank="1" showInputMessage="1" showErrorMessage="1" prompt="Enter a date" sqref="D1"/>"#,
+            r#"</dataValidations><pageMargins"#,
+        )));
+    }
+
+    #[test]
+    fn test_tables() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.write_string_with_format(1, 1, "Old", &bold);
+        worksheet.write_url(0, 0, "https://example.com").unwrap();
+        worksheet
+            .add_table(4, 2, 1, 1, &Table::new(3).set_columns(&["Name"]))
+            .unwrap();
+        worksheet
+            .add_table(
+                1,
+                5,
+                2,
+                5,
+                &Table::new(4).set_header_row(false).set_columns(&["X"]),
+            )
+            .unwrap();
+
+        assert_eq!(
+            worksheet.add_table(3, 0, 3, 1, &Table::new(5)),
+            Err(XlsxError::TableOverlap {
+                range: "A4:B4".to_string(),
+                existing: "B2:C5".to_string(),
+            })
+        );
+        assert_eq!(
+            worksheet.add_table(9, 0, 10, 0, &Table::new(5).set_columns(&["A", "B"])),
+            Err(XlsxError::TableColumnCount {
+                range: "A10:A11".to_string(),
+                count: 2,
+            })
+        );
+        assert_eq!(
+            worksheet.add_table(9, 0, 10, 1, &Table::new(5).set_columns(&["Column2"])),
+            Err(XlsxError::DuplicateTableColumn("Column2".to_string()))
+        );
+        assert_eq!(worksheet.tables().len(), 2);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"<row r="2" spans="1:3"><c r="B2" s="2" t="s"><v>1</v></c>"#,
+            r#"<c r="C2" t="s"><v>2</v></c></row></sheetData>"#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<tableParts count="2"><tablePart r:id="rId2"/><tablePart r:id="rId3"/>"#,
+            r#"</tableParts></worksheet>"#,
+        )));
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId3" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/table" "#,
+            r#"Target="../tables/table4.xml"/>"#,
+        )));
+    }
+
+    #[cfg(feature = "drawings")]
+    #[test]
+    fn test_notes() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        assert!(worksheet.comments().is_none());
+        assert!(worksheet.vml_drawing().is_none());
+
+        worksheet.write_url(0, 0, "https://example.com").unwrap();
+        worksheet.add_note(2, 1, &Note::new("Old"));
+        worksheet.add_note(2, 1, &Note::new("New"));
+        worksheet.add_note(0, 3, &Note::new("First").set_author("Jane"));
+        worksheet.add_table(5, 0, 6, 0, &Table::new(1)).unwrap();
+        worksheet.set_default_note_author("Sam");
+        worksheet.set_notes_number(3);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"<legacyDrawing r:id="rId2"/><tableParts count="1"><tablePart r:id="rId3"/>"#,
+            r#"</tableParts></worksheet>"#,
+        )));
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId2" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/vmlDrawing" "#,
+            r#"Target="../drawings/vmlDrawing3.vml"/>"#,
+        )));
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId4" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/comments" "#,
+            r#"Target="../comments3.xml"/>"#,
+        )));
+
+        let mut comments = vec![];
+        let mut writer = XMLWriter::new(&mut comments);
+        worksheet.comments().unwrap().assemble_xml_file(&mut writer);
+        let comments = String::from_utf8(comments).unwrap();
+        assert!(
+            comments.contains(r#"<authors><author>Jane</author><author>Sam</author></authors>"#)
+        );
+        assert!(comments.contains(r#"<comment ref="D1" authorId="0">"#));
+        assert!(comments.contains(r#"<comment ref="B3" authorId="1">"#));
+        assert!(comments.contains("<t>New</t>"));
+        assert!(!comments.contains("<t>Old</t>"));
+
+        let mut vml = vec![];
+        let mut writer = XMLWriter::new(&mut vml);
+        worksheet
+            .vml_drawing()
+            .unwrap()
+            .assemble_xml_file(&mut writer);
+        let vml = String::from_utf8(vml).unwrap();
+        assert!(vml.contains(r#"<o:idmap v:ext="edit" data="3"/>"#));
+        assert!(vml.contains(r#"<v:shape id="_x0000_s3074""#));
+    }
+
+    #[cfg(feature = "drawings")]
+    #[test]
+    fn test_buttons() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        assert!(worksheet.control_properties().is_empty());
+
+        worksheet.write_url(0, 0, "https://example.com").unwrap();
+        worksheet.insert_button(1, 2, "Run", "Button1_Click");
+        worksheet.add_button(4, 0, &Button::new("Stop").set_width(128));
+        worksheet.add_note(0, 3, &Note::new("Note"));
+        worksheet.set_notes_number(2);
+        worksheet.set_control_number(5);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<legacyDrawing r:id="rId2"/>"#,
+            r#"<mc:AlternateContent xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" "#,
+            r#"xmlns:x14="http://schemas.microsoft.com/office/spreadsheetml/2009/9/main">"#,
+            r#"<mc:Choice Requires="x14">"#,
+            r#"<controls xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing">"#,
+            r#"<mc:AlternateContent><mc:Choice Requires="x14">"#,
+            r#"<control shapeId="2049" r:id="rId4" name="Button 1">"#,
+            r#"<controlPr defaultSize="0" print="0" autoFill="0" autoPict="0" macro="[0]!Button1_Click">"#,
+            r#"<anchor moveWithCells="1"><from><xdr:col>2</xdr:col><xdr:colOff>0</xdr:colOff>"#,
+            r#"<xdr:row>1</xdr:row><xdr:rowOff>0</xdr:rowOff></from><to><xdr:col>3</xdr:col>"#,
+            r#"<xdr:colOff>0</xdr:colOff><xdr:row>2</xdr:row><xdr:rowOff>0</xdr:rowOff></to>"#,
+            r#"</anchor></controlPr></control></mc:Choice></mc:AlternateContent>"#,
+            r#"<mc:AlternateContent><mc:Choice Requires="x14">"#,
+            r#"<control shapeId="2050" r:id="rId5" name="Button 2">"#,
+            r#"<controlPr defaultSize="0" print="0" autoFill="0" autoPict="0">"#,
+            r#"<anchor moveWithCells="1"><from><xdr:col>0</xdr:col><xdr:colOff>0</xdr:colOff>"#,
+            r#"<xdr:row>4</xdr:row><xdr:rowOff>0</xdr:rowOff></from><to><xdr:col>2</xdr:col>"#,
+            r#"<xdr:colOff>0</xdr:colOff><xdr:row>5</xdr:row><xdr:rowOff>0</xdr:rowOff></to>"#,
+            r#"</anchor></controlPr></control></mc:Choice></mc:AlternateContent>"#,
+            r#"</controls></mc:Choice></mc:AlternateContent></worksheet>"#,
+        );
+        assert!(got.ends_with(expected), "{got}");
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId5" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/ctrlProp" "#,
+            r#"Target="../ctrlProps/ctrlProp6.xml"/>"#,
+        )));
+
+        let numbers: Vec<u32> = worksheet
+            .control_properties()
+            .iter()
+            .map(|control| control.number())
+            .collect();
+        assert_eq!(numbers, vec![5, 6]);
+
+        let mut vml = vec![];
+        let mut writer = XMLWriter::new(&mut vml);
+        worksheet
+            .vml_drawing()
+            .unwrap()
+            .assemble_xml_file(&mut writer);
+        let vml = String::from_utf8(vml).unwrap();
+        assert!(vml.contains(r##"<v:shape id="_x0000_s2049" type="#_x0000_t201""##));
+        assert!(vml.contains(r##"<v:shape id="_x0000_s2051" type="#_x0000_t202""##));
+    }
+
+    #[cfg(feature = "drawings")]
+    #[test]
+    fn test_buttons_without_notes() {
+        let mut worksheet = Worksheet::new();
+        worksheet.insert_button(0, 0, "Run", "Run");
+
+        assert!(worksheet.comments().is_none());
+        assert!(worksheet.vml_drawing().is_some());
+        assert_eq!(worksheet.relationships().len(), 2);
+    }
+
+    #[cfg(feature = "drawings")]
+    #[test]
+    fn test_ole_objects() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        assert!(worksheet.embeddings().is_empty());
+        assert!(matches!(
+            worksheet.embed_object(0, 0, "missing.pdf"),
+            Err(XlsxError::ObjectRead { path, .. }) if path == "missing.pdf"
+        ));
+
+        let image = Image::from_bytes(&png_data(64, 40, 0)).unwrap();
+        worksheet.add_image(5, 0, &image);
+        worksheet.add_note(0, 3, &Note::new("Note"));
+        worksheet.add_object(1, 1, &OleObject::from_bytes(b"%PDF", "report.pdf"));
+        worksheet.add_object(
+            1,
+            4,
+            &OleObject::from_bytes(b"PK", "data.xlsx").set_preview(&image),
+        );
+        worksheet.set_embedding_number(3);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<drawing r:id="rId1"/><legacyDrawing r:id="rId2"/>"#,
+            r#"<oleObjects xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing">"#,
+            r#"<mc:AlternateContent xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" "#,
+            r#"xmlns:x14="http://schemas.microsoft.com/office/spreadsheetml/2009/9/main">"#,
+            r#"<mc:Choice Requires="x14">"#,
+            r#"<oleObject progId="Package" dvAspect="DVASPECT_ICON" shapeId="1026" r:id="rId4">"#,
+            r#"<objectPr defaultSize="0" autoPict="0" r:id="rId5"><anchor moveWithCells="1">"#,
+            r#"<from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>1</xdr:row>"#,
+            r#"<xdr:rowOff>0</xdr:rowOff></from><to><xdr:col>1</xdr:col>"#,
+            r#"<xdr:colOff>457200</xdr:colOff><xdr:row>3</xdr:row><xdr:rowOff>76200</xdr:rowOff>"#,
+            r#"</to></anchor></objectPr></oleObject></mc:Choice><mc:Fallback>"#,
+            r#"<oleObject progId="Package" dvAspect="DVASPECT_ICON" shapeId="1026" r:id="rId4"/>"#,
+            r#"</mc:Fallback></mc:AlternateContent>"#,
+        );
+        assert!(got.contains(expected), "{got}");
+        assert!(got.contains(concat!(
+            r#"<oleObject progId="Excel.Sheet.12" dvAspect="DVASPECT_ICON" shapeId="1027" "#,
+            r#"r:id="rId6"/></mc:Fallback></mc:AlternateContent></oleObjects></worksheet>"#,
+        )));
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId4" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/oleObject" "#,
+            r#"Target="../embeddings/oleObject3.bin"/>"#,
+            r#"<Relationship Id="rId5" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" "#,
+            r#"Target="../media/image2.png"/>"#,
+            r#"<Relationship Id="rId6" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/package" "#,
+            r#"Target="../embeddings/oleObject4.xlsx"/>"#,
+        )));
+
+        // The ids of the controls and objects follow the other relationships.
+        let mut linked = Worksheet::new();
+        linked.write_url(0, 0, "https://example.com").unwrap();
+        linked.insert_button(2, 2, "Run", "Run_Click");
+        linked.add_object(4, 4, &OleObject::from_bytes(b"%PDF", "report.pdf"));
+        let got = assemble_worksheet(&linked, &mut styles);
+        assert!(got.contains(r#"<legacyDrawing r:id="rId2"/>"#));
+        assert!(got.contains(r#"shapeId="1026" r:id="rId4"/></mc:Fallback>"#));
+        assert!(got.contains(r#"<control shapeId="1025" r:id="rId3" name="Button 1">"#));
+        assert_eq!(linked.relationships().len(), 5);
+
+        let part_names: Vec<String> = worksheet
+            .embeddings()
+            .iter()
+            .map(|embedding| embedding.part_name().to_string())
+            .collect();
+        assert_eq!(
+            part_names,
+            vec![
+                "xl/embeddings/oleObject3.bin",
+                "xl/embeddings/oleObject4.xlsx"
+            ]
+        );
+
+        let media: Vec<String> = worksheet
+            .media()
+            .into_iter()
+            .map(|(part_name, _)| part_name)
+            .collect();
+        assert_eq!(
+            media,
+            vec![
+                "xl/media/image1.png",
+                "xl/media/image2.png",
+                "xl/media/image3.png"
+            ]
+        );
+
+        let vml_drawing = worksheet.vml_drawing().unwrap();
+        assert_eq!(vml_drawing.relationships().len(), 2);
+    }
+
+    #[cfg(feature = "drawings")]
+    #[test]
+    fn test_threaded_comments() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        assert!(worksheet.threaded_comments().is_none());
+
+        worksheet.add_note(1, 1, &Note::new("Replaced"));
+        worksheet.add_note(0, 0, &Note::new("Note"));
+        worksheet.add_threaded_comment(
+            1,
+            1,
+            &ThreadedComment::new("Jane", "Is this final?").add_reply("Sam", "Yes"),
+        );
+        worksheet.set_notes_number(2);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<legacyDrawing r:id="rId1"/></worksheet>"#));
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId3" "#,
+            r#"Type="http://schemas.microsoft.com/office/2017/10/relationships/threadedComment" "#,
+            r#"Target="../threadedComments/threadedComment2.xml"/>"#,
+        )));
+
+        let mut comments = vec![];
+        let mut writer = XMLWriter::new(&mut comments);
+        worksheet.comments().unwrap().assemble_xml_file(&mut writer);
+        let comments = String::from_utf8(comments).unwrap();
+        assert!(comments.contains(r#"<authors><author>Author</author><author>tc={"#));
+        assert!(comments.contains("Comment:\n    Is this final?\nReply:\n    Yes</t>"));
+        assert!(!comments.contains("Replaced"));
+
+        let threaded_comments = worksheet.threaded_comments().unwrap();
+        assert_eq!(
+            threaded_comments.part_name(),
+            "xl/threadedComments/threadedComment2.xml"
+        );
+
+        // A note replaces the thread on its cell.
+        worksheet.add_note(1, 1, &Note::new("Note"));
+        assert!(worksheet.threaded_comments().is_none());
+    }
+
+    #[cfg(feature = "charts")]
+    #[test]
+    fn test_drawing() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        assert!(worksheet.drawing().is_none());
+        assert!(worksheet.media().is_empty());
+
+        let mut path = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut path, &png_data(64, 40, 0)).unwrap();
+
+        worksheet.write_url(0, 0, "https://example.com").unwrap();
+        worksheet.insert_image(1, 1, path.path()).unwrap();
+        worksheet.insert_chart(4, 0, &Chart::new(3, ChartType::Column));
+        worksheet.add_image(
+            0,
+            5,
+            &Image::from_bytes(&png_data(10, 10, 0))
+                .unwrap()
+                .set_alt_text("Icon"),
+        );
+        worksheet.add_note(0, 2, &Note::new("Note"));
+        worksheet.set_drawing_number(2);
+        worksheet.set_image_number(5);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<drawing r:id="rId2"/><legacyDrawing r:id="rId3"/></worksheet>"#));
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        worksheet.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(concat!(
+            r#"<Relationship Id="rId2" "#,
+            r#"Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing" "#,
+            r#"Target="../drawings/drawing2.xml"/>"#,
+        )));
+
+        let media = worksheet.media();
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[0].0, "xl/media/image5.png");
+        assert_eq!(media[1].0, "xl/media/image6.png");
+        assert_eq!(media[0].1.data(), png_data(64, 40, 0));
+
+        let drawing = worksheet.drawing().unwrap();
+        assert_eq!(drawing.part_name(), "xl/drawings/drawing2.xml");
+
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        drawing.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(r#"Target="../media/image5.png"/>"#));
+        assert!(relationships.contains(r#"Target="../charts/chart3.xml"/>"#));
+        assert!(relationships.contains(r#"Target="../media/image6.png"/>"#));
+
+        assert!(matches!(
+            worksheet.insert_image(0, 0, "missing.png"),
+            Err(XlsxError::ImageRead { path, .. }) if path == "missing.png"
+        ));
+    }
+
+    #[test]
+    fn test_tab_color() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_tab_color(Color::Rgb(0xFF0000));
+        worksheet.set_outline_settings(true, false, true);
+        worksheet.set_page_setup(&PageSetup::new().set_fit_to_pages(1, 0));
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<sheetPr><tabColor rgb="FFFF0000"/><outlinePr summaryBelow="0"/>"#,
+            r#"<pageSetUpPr fitToPage="1"/></sheetPr>"#,
+        );
+        assert!(got.contains(expected));
+
+        worksheet.set_tab_color(Color::Theme(4));
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<sheetPr><tabColor theme="4"/>"#));
+
+        worksheet.set_tab_color(Color::Automatic);
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(!got.contains("tabColor"));
+    }
+
+    #[test]
+    fn test_hidden_rows_and_columns() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        worksheet.write_number(0, 0, 1.0);
+        worksheet.set_row_hidden(0);
+        worksheet.set_row_hidden(2);
+        worksheet.set_row_format(3, &bold);
+        worksheet.set_row_hidden(3);
+        worksheet.set_column(1, 1, 20.0, None, false);
+        worksheet.set_column_hidden(2, 1);
+        worksheet.set_column_hidden(4, 4);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<cols><col min="2" max="2" width="20.7109375" hidden="1" customWidth="1"/>"#,
+            r#"<col min="3" max="3" width="0" hidden="1" customWidth="1"/>"#,
+            r#"<col min="5" max="5" width="0" hidden="1" customWidth="1"/></cols><sheetData>"#,
+            r#"<row r="1" spans="1:1" hidden="1"><c r="A1"><v>1</v></c></row>"#,
+            r#"<row r="3" hidden="1"/><row r="4" s="1" customFormat="1" hidden="1"/></sheetData>"#,
+        );
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    fn test_outline_rows() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_number(1, 0, 1.0);
+        worksheet.write_number(4, 0, 2.0);
+        worksheet.group_rows(1, 5);
+        worksheet.group_rows_collapsed(2, 3);
+        worksheet.set_row_hidden(8);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<sheetFormatPr defaultRowHeight="15" outlineLevelRow="2"/><sheetData>"#,
+            r#"<row r="2" spans="1:1" outlineLevel="1"><c r="A2"><v>1</v></c></row>"#,
+            r#"<row r="3" hidden="1" outlineLevel="2"/>"#,
+            r#"<row r="4" hidden="1" outlineLevel="2"/>"#,
+            r#"<row r="5" spans="1:1" outlineLevel="1" collapsed="1"><c r="A5"><v>2</v></c></row>"#,
+            r#"<row r="6" outlineLevel="1"/>"#,
+            r#"<row r="9" hidden="1"/></sheetData>"#,
+        );
+        assert!(got.contains(expected));
+
+        let mut tempfile = tempfile().unwrap();
+        let mut writer = XMLWriter::new(&tempfile);
+        let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+        stream.write_number(4, 0, 2.0).unwrap();
+        stream.finish();
+        drop(writer);
+        let got = read_xmlfile_data(&mut tempfile);
+        assert!(got.contains(concat!(
+            r#"<row r="2" outlineLevel="1"/><row r="3" hidden="1" outlineLevel="2"/>"#,
+            r#"<row r="4" hidden="1" outlineLevel="2"/>"#,
+            r#"<row r="5" outlineLevel="1" collapsed="1"><c r="A5"><v>2</v></c></row>"#,
+            r#"<row r="6" outlineLevel="1"/><row r="9" hidden="1"/></sheetData>"#,
+        )));
+    }
+
+    #[test]
+    fn test_outline_columns() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.set_outline_settings(true, false, false);
+        worksheet.group_columns_collapsed(2, 3);
+        worksheet.set_column(4, 4, 20.0, None, false);
+        worksheet.group_columns(4, 4);
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<sheetPr><outlinePr summaryBelow="0" summaryRight="0"/></sheetPr>"#,
+            r#"<dimension ref="A1"/><sheetViews><sheetView workbookViewId="0"/>"#,
+            r#"</sheetViews><sheetFormatPr defaultRowHeight="15" outlineLevelCol="1"/><cols>"#,
+            r#"<col min="2" max="2" width="9.140625" collapsed="1"/>"#,
+            r#"<col min="3" max="4" width="0" hidden="1" customWidth="1" outlineLevel="1"/>"#,
+            r#"<col min="5" max="5" width="20.7109375" customWidth="1" outlineLevel="1"/></cols>"#,
+        );
+        assert!(got.contains(expected));
+    }
+
+    #[test]
+    #[should_panic(expected = "maximum outline level of 7")]
+    fn test_outline_level_out_of_range() {
+        let mut worksheet = Worksheet::new();
+
+        for _ in 0..8 {
+            worksheet.group_rows(0, 2);
+        }
+    }
+
+    #[test]
+    fn test_ignored_errors() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.ignore_error_range(9, 2, 1, 2, IgnoreError::FormulaRange);
+        worksheet.ignore_error(0, 0, IgnoreError::NumberStoredAsText);
+        worksheet.ignore_error_range(3, 0, 4, 1, IgnoreError::NumberStoredAsText);
+        #[cfg(feature = "charts")]
+        worksheet.insert_chart(0, 4, &Chart::new(1, ChartType::Column));
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        let expected = concat!(
+            r#"<ignoredErrors><ignoredError sqref="A1 A4:B5" numberStoredAsText="1"/>"#,
+            r#"<ignoredError sqref="C2:C10" formulaRange="1"/></ignoredErrors>"#,
+        );
+        assert!(got.contains(expected));
+
+        // The <ignoredErrors> element comes before the <drawing> element.
+        #[cfg(feature = "charts")]
+        assert!(got.contains(r#"</ignoredErrors><drawing r:id="rId1"/>"#));
+    }
+
+    #[cfg(feature = "drawings")]
+    #[test]
+    fn test_embedded_images() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+        let bold = Format::new().set_bold();
+
+        assert!(!worksheet.has_embedded_images());
+
+        let mut path = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut path, &png_data(64, 40, 0)).unwrap();
+
+        worksheet.add_image(0, 0, &Image::from_bytes(&png_data(10, 10, 0)).unwrap());
+        worksheet.embed_image_in_cell(3, 1, path.path()).unwrap();
+        worksheet.embed_image(1, 2, &Image::from_bytes(&png_data(8, 8, 0)).unwrap());
+        worksheet.embed_image(2, 0, &Image::from_bytes(&png_data(8, 8, 0)).unwrap());
+        worksheet.write_string_with_format(2, 0, "Replaced", &bold);
+        worksheet.set_image_number(3);
+        worksheet.set_value_metadata_number(4);
+
+        assert!(worksheet.has_embedded_images());
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(r#"<c r="C2" t="e" vm="4"><v>#VALUE!</v></c>"#));
+        assert!(got.contains(r#"<c r="B4" t="e" vm="5"><v>#VALUE!</v></c>"#));
+        assert!(!got.contains(r#"vm="6""#));
+
+        let embedded = worksheet.embedded_images();
+        assert_eq!(embedded.len(), 2);
+        assert_eq!(embedded[0].0, "xl/media/image4.png");
+        assert_eq!(embedded[1].0, "xl/media/image5.png");
+        assert_eq!(embedded[1].1.data(), png_data(64, 40, 0));
+
+        let media = worksheet.media();
+        assert_eq!(media.len(), 3);
+        assert_eq!(media[0].0, "xl/media/image3.png");
+        assert_eq!(media[2].0, "xl/media/image5.png");
+
+        let drawing = worksheet.drawing().unwrap();
+        let mut relationships = vec![];
+        let mut writer = XMLWriter::new(&mut relationships);
+        drawing.relationships().assemble_xml_file(&mut writer);
+        let relationships = String::from_utf8(relationships).unwrap();
+        assert!(relationships.contains(r#"Target="../media/image3.png"/>"#));
+        assert!(!relationships.contains(r#"Target="../media/image4.png"/>"#));
+    }
+
+    #[test]
+    fn test_sparklines() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_number(0, 0, 1.0);
+        worksheet.add_table(2, 0, 3, 0, &Table::new(1)).unwrap();
+        worksheet.add_sparkline(1, 5, &Sparkline::new("Sheet1", 1, 0, 1, 4));
+        worksheet.add_sparkline(
+            0,
+            5,
+            &Sparkline::new("Sheet1", 0, 0, 0, 4).set_type(SparklineType::Column),
+        );
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"</tableParts><extLst><ext xmlns:x14="http://schemas.microsoft.com/office/spreadsheetml/2009/9/main" "#,
+            r#"uri="{05C60535-1F16-4fd2-B633-F4F36F0B64E0}">"#,
+            r#"<x14:sparklineGroups xmlns:xm="http://schemas.microsoft.com/office/excel/2006/main">"#,
+            r#"<x14:sparklineGroup type="column" displayEmptyCellsAs="gap">"#,
+        )));
+        assert!(got.contains(concat!(
+            r#"<xm:f>Sheet1!A2:E2</xm:f><xm:sqref>F2</xm:sqref></x14:sparkline></x14:sparklines>"#,
+            r#"</x14:sparklineGroup></x14:sparklineGroups></ext></extLst></worksheet>"#,
+        )));
+        assert!(got.find("<xm:sqref>F1</xm:sqref>") < got.find("<xm:sqref>F2</xm:sqref>"));
+    }
+
+    #[test]
+    fn test_protect_sheet() {
+        let mut styles = Styles::new();
+        let mut worksheet = Worksheet::new();
+
+        worksheet.write_number(0, 0, 1.0);
+        worksheet.autofilter(0, 0, 0, 0);
+        worksheet.protect_sheet("password", &ProtectionOptions::new().set_autofilter(true));
+
+        let got = assemble_worksheet(&worksheet, &mut styles);
+        assert!(got.contains(concat!(
+            r#"</sheetData><sheetProtection password="83AF" sheet="1" objects="1" scenarios="1" "#,
+            r#"autoFilter="0"/><autoFilter ref="A1:A1"/>"#,
+        )));
+    }
+}
@@ -0,0 +1,128 @@
+// Tests for the XmlElement derive macro, which has to be used from outside
+// the crate.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use excel_xmlwriter::{XMLWriter, XmlElement};
+use pretty_assertions::assert_eq;
+
+#[derive(XmlElement)]
+struct PageMargins {
+    left: f64,
+    right: f64,
+    top: f64,
+    bottom: f64,
+    header: f64,
+    footer: f64,
+}
+
+#[derive(XmlElement)]
+#[xml(tag = "cfvo")]
+struct ConditionalValue {
+    r#type: &'static str,
+    val: Option<String>,
+}
+
+#[derive(XmlElement)]
+#[xml(tag = "colorScale")]
+struct ColorScale {
+    #[xml(child)]
+    values: Vec<ConditionalValue>,
+    #[xml(child)]
+    extra: Option<ConditionalValue>,
+    #[xml(skip)]
+    #[allow(dead_code)]
+    id: u32,
+}
+
+#[derive(XmlElement)]
+#[xml(tag = "t")]
+struct Text<'a> {
+    #[xml(rename = "xml:space")]
+    space: Option<&'a str>,
+    #[xml(text)]
+    text: &'a str,
+}
+
+#[derive(XmlElement)]
+struct SheetFormatPr {
+    default_row_height: f64,
+    custom_height: bool,
+    #[xml(rename = "x14ac:dyDescent")]
+    dy_descent: Option<f64>,
+    #[xml(text)]
+    note: Option<String>,
+}
+
+// Write an element to a string.
+fn write_to_string(element: &impl XmlElement) -> String {
+    let mut writer = XMLWriter::new(vec![]);
+    element.write_element(&mut writer);
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_derive_attributes() {
+    let margins = PageMargins {
+        left: 0.7,
+        right: 0.7,
+        top: 0.75,
+        bottom: 0.75,
+        header: 0.3,
+        footer: 0.3,
+    };
+
+    assert_eq!(
+        write_to_string(&margins),
+        r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/>"#
+    );
+}
+
+#[test]
+fn test_derive_children() {
+    let scale = ColorScale {
+        values: vec![
+            ConditionalValue {
+                r#type: "min",
+                val: None,
+            },
+            ConditionalValue {
+                r#type: "percentile",
+                val: Some("50".to_string()),
+            },
+        ],
+        extra: None,
+        id: 1,
+    };
+
+    assert_eq!(
+        write_to_string(&scale),
+        r#"<colorScale><cfvo type="min"/><cfvo type="percentile" val="50"/></colorScale>"#
+    );
+}
+
+#[test]
+fn test_derive_text() {
+    let text = Text {
+        space: Some("preserve"),
+        text: " Sales & \"Costs\" ",
+    };
+
+    assert_eq!(
+        write_to_string(&text),
+        r#"<t xml:space="preserve"> Sales &amp; "Costs" </t>"#
+    );
+
+    let format = SheetFormatPr {
+        default_row_height: 15.0,
+        custom_height: false,
+        dy_descent: Some(0.25),
+        note: None,
+    };
+
+    assert_eq!(
+        write_to_string(&format),
+        r#"<sheetFormatPr defaultRowHeight="15" customHeight="0" x14ac:dyDescent="0.25"/>"#
+    );
+}
@@ -0,0 +1,180 @@
+// Integration test that composes a complete minimal xlsx file using only the
+// public APIs of the crate. It also serves as a reference for the parts, and
+// the order of the elements in the parts, that Excel requires.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use excel_xmlwriter::app::App;
+use excel_xmlwriter::content_types::ContentTypes;
+use excel_xmlwriter::core_properties::Core;
+use excel_xmlwriter::packager::{Compression, Packager};
+use excel_xmlwriter::relationship::Relationships;
+use excel_xmlwriter::shared_strings::SharedStringTable;
+use excel_xmlwriter::styles::{Format, Styles};
+use excel_xmlwriter::theme::Theme;
+use excel_xmlwriter::workbook::Workbook;
+use excel_xmlwriter::worksheet::Worksheet;
+use excel_xmlwriter::XMLWriter;
+use std::time::UNIX_EPOCH;
+
+use pretty_assertions::assert_eq;
+
+// Write a part using a closure and return the XML data. The attribute order
+// is checked so that the output is deterministic.
+fn write_part(mut write: impl FnMut(&mut XMLWriter<Vec<u8>>)) -> Vec<u8> {
+    let mut writer = XMLWriter::new(vec![]);
+    writer.set_check_attribute_order(true);
+
+    write(&mut writer);
+
+    writer.into_inner()
+}
+
+fn minimal_xlsx_parts() -> Vec<(&'static str, Vec<u8>)> {
+    let mut string_table = SharedStringTable::new();
+    let mut styles = Styles::new();
+    let mut worksheet = Worksheet::new();
+    worksheet.write_string_with_format(0, 0, "Hello", &Format::new().set_bold());
+    let worksheet = write_part(|w| worksheet.assemble_xml_file(w, &mut styles, &mut string_table));
+
+    vec![
+        (
+            "[Content_Types].xml",
+            write_part(|w| {
+                let mut content_types = ContentTypes::new();
+                content_types.add_workbook();
+                content_types.add_worksheet(1);
+                content_types.add_styles();
+                content_types.add_shared_strings();
+                content_types.add_theme();
+                content_types.add_doc_properties();
+                content_types.assemble_xml_file(w);
+            }),
+        ),
+        (
+            "_rels/.rels",
+            write_part(|w| {
+                let mut relationships = Relationships::new();
+                relationships.add_document_relationship("officeDocument", "xl/workbook.xml");
+                relationships
+                    .add_package_relationship("metadata/core-properties", "docProps/core.xml");
+                relationships.add_document_relationship("extended-properties", "docProps/app.xml");
+                relationships.assemble_xml_file(w);
+            }),
+        ),
+        (
+            "docProps/app.xml",
+            write_part(|w| {
+                let mut app = App::new();
+                app.add_heading_pair("Worksheets", 1);
+                app.add_part_name("Sheet1");
+                app.assemble_xml_file(w);
+            }),
+        ),
+        (
+            "docProps/core.xml",
+            write_part(|w| {
+                let mut core = Core::new();
+                core.set_created(UNIX_EPOCH);
+                core.assemble_xml_file(w);
+            }),
+        ),
+        (
+            "xl/_rels/workbook.xml.rels",
+            write_part(|w| {
+                let mut relationships = Relationships::new();
+                relationships.add_document_relationship("worksheet", "worksheets/sheet1.xml");
+                relationships.add_document_relationship("theme", "theme/theme1.xml");
+                relationships.add_document_relationship("styles", "styles.xml");
+                relationships.add_document_relationship("sharedStrings", "sharedStrings.xml");
+                relationships.assemble_xml_file(w);
+            }),
+        ),
+        (
+            "xl/workbook.xml",
+            write_part(|w| {
+                let mut workbook = Workbook::new();
+                workbook.add_sheet("Sheet1");
+                workbook.assemble_xml_file(w);
+            }),
+        ),
+        ("xl/worksheets/sheet1.xml", worksheet),
+        (
+            "xl/theme/theme1.xml",
+            write_part(|w| Theme::new().assemble_xml_file(w)),
+        ),
+        ("xl/styles.xml", write_part(|w| styles.assemble_xml_file(w))),
+        (
+            "xl/sharedStrings.xml",
+            write_part(|w| string_table.assemble_xml_file(w)),
+        ),
+    ]
+}
+
+#[test]
+fn test_minimal_xlsx_parts() {
+    let parts = minimal_xlsx_parts();
+
+    let expected = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        "\n",
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+        r#"<dimension ref="A1"/><sheetViews><sheetView workbookViewId="0"/></sheetViews>"#,
+        r#"<sheetFormatPr defaultRowHeight="15"/><sheetData><row r="1" spans="1:1">"#,
+        r#"<c r="A1" s="1" t="s"><v>0</v></c></row></sheetData>"#,
+        r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/>"#,
+        r#"</worksheet>"#,
+    );
+    let (_, got) = &parts[6];
+    assert_eq!(std::str::from_utf8(got).unwrap(), expected);
+
+    let expected = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        "\n",
+        r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"count="1" uniqueCount="1"><si><t>Hello</t></si></sst>"#,
+    );
+    let (_, got) = &parts[9];
+    assert_eq!(std::str::from_utf8(got).unwrap(), expected);
+}
+
+#[test]
+fn test_minimal_xlsx_file() {
+    let parts = minimal_xlsx_parts();
+
+    let mut packager = Packager::new(vec![]);
+    packager.set_compression(Compression::Stored);
+    for (name, data) in &parts {
+        packager.add_part(name, data).unwrap();
+    }
+    let zip = packager.finish().unwrap();
+
+    // Check the zip signatures and that every part is stored in order.
+    assert!(zip.starts_with(&[0x50, 0x4b, 0x03, 0x04]));
+    assert_eq!(
+        &zip[zip.len() - 22..zip.len() - 18],
+        &[0x50, 0x4b, 0x05, 0x06]
+    );
+
+    let mut offset = 0;
+    for (name, data) in &parts {
+        let name_start = offset + 30;
+        assert_eq!(&zip[name_start..name_start + name.len()], name.as_bytes());
+        offset = name_start + name.len() + data.len();
+    }
+}
+
+#[test]
+fn test_minimal_xlsx_deflated_file() {
+    // Write the file so that it can be inspected or opened in Excel.
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("minimal.xlsx");
+    let file = std::fs::File::create(path).unwrap();
+
+    let mut packager = Packager::new(file);
+    for (name, data) in minimal_xlsx_parts() {
+        packager.add_part(name, &data).unwrap();
+    }
+    packager.finish().unwrap();
+}
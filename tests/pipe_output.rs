@@ -0,0 +1,84 @@
+// Integration tests for writing parts and packages to a pipe, which can't
+// seek, as used when the output is piped to another program such as `zip`.
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use excel_xmlwriter::packager::Packager;
+use excel_xmlwriter::shared_strings::SharedStringTable;
+use excel_xmlwriter::styles::Styles;
+use excel_xmlwriter::worksheet::Worksheet;
+use excel_xmlwriter::XMLWriter;
+use std::io::{pipe, BufWriter, PipeWriter, Read, Write};
+use std::thread;
+
+use pretty_assertions::assert_eq;
+
+// Write to a pipe with a closure and return the data read from the other
+// end of the pipe.
+fn read_from_pipe(write: impl FnOnce(PipeWriter)) -> Vec<u8> {
+    let (mut reader, writer) = pipe().unwrap();
+
+    let reader_thread = thread::spawn(move || {
+        let mut data = vec![];
+        reader.read_to_end(&mut data).unwrap();
+        data
+    });
+
+    write(writer);
+
+    reader_thread.join().unwrap()
+}
+
+#[test]
+fn test_streaming_worksheet_to_pipe() {
+    let mut styles = Styles::new();
+    let worksheet = Worksheet::new();
+
+    let got = read_from_pipe(|pipe| {
+        let mut writer = XMLWriter::new(BufWriter::new(pipe));
+
+        let mut stream = worksheet.start_streaming(&mut writer, &mut styles);
+        for row in 0..10_000 {
+            stream.write_string(row, 0, "Item").unwrap();
+            stream.write_number(row, 1, row as f64).unwrap();
+        }
+        stream.finish();
+
+        writer.into_inner().flush().unwrap();
+    });
+
+    let got = String::from_utf8(got).unwrap();
+    assert!(got.starts_with(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#));
+    assert!(got.contains(concat!(
+        r#"<row r="10000"><c r="A10000" t="inlineStr"><is><t>Item</t></is></c>"#,
+        r#"<c r="B10000"><v>9999</v></c></row></sheetData>"#,
+    )));
+    assert!(got.ends_with("</worksheet>"));
+}
+
+// Write a small package and return the writer.
+fn write_package<W: Write>(writer: W) -> W {
+    let mut packager = Packager::new(writer);
+
+    let mut string_table = SharedStringTable::new();
+    string_table.shared_string_index("Hello");
+
+    packager
+        .add_xml_part("xl/sharedStrings.xml", |writer| {
+            string_table.assemble_xml_file(writer)
+        })
+        .unwrap();
+    packager.finish().unwrap()
+}
+
+#[test]
+fn test_package_to_pipe() {
+    // The package written to a pipe is the same as one written to memory.
+    let expected = write_package(vec![]);
+    let got = read_from_pipe(|pipe| {
+        write_package(pipe);
+    });
+
+    assert_eq!(got, expected);
+}
@@ -0,0 +1,407 @@
+// Snapshot tests for the output of each of the part writers, for
+// representative inputs. The expected bytes are stored in `tests/snapshots/`
+// so that changes to the escaping and formatting core that would change the
+// XML that Excel reads are caught.
+//
+// After an intended change to the output, review the diffs and update the
+// snapshots with:
+//
+//     UPDATE_SNAPSHOTS=1 cargo test --test snapshots
+//
+// SPDX-License-Identifier: MIT
+// Copyright 2022, John McNamara, jmcnamara@cpan.org
+
+use excel_xmlwriter::app::App;
+use excel_xmlwriter::chart::{Chart, ChartSeries, ChartType};
+use excel_xmlwriter::conditional_format::{
+    CellCriteria, ConditionalFormatCell, ConditionalFormatText, TextCriteria,
+};
+use excel_xmlwriter::content_types::ContentTypes;
+use excel_xmlwriter::core_properties::Core;
+use excel_xmlwriter::data_validation::{DataValidation, DataValidationCriteria};
+use excel_xmlwriter::image::Image;
+use excel_xmlwriter::metadata::Metadata;
+use excel_xmlwriter::note::Note;
+use excel_xmlwriter::page_setup::PageSetup;
+use excel_xmlwriter::relationship::Relationships;
+use excel_xmlwriter::shared_strings::SharedStringTable;
+use excel_xmlwriter::styles::{
+    Color, Format, FormatAlign, FormatBorder, FormatPattern, FormatUnderline, Styles,
+};
+use excel_xmlwriter::table::Table;
+use excel_xmlwriter::theme::Theme;
+use excel_xmlwriter::threaded_comment::{Persons, ThreadedComment};
+use excel_xmlwriter::workbook::{SheetState, Workbook};
+use excel_xmlwriter::worksheet::Worksheet;
+use excel_xmlwriter::XMLWriter;
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use pretty_assertions::assert_eq;
+
+// The snapshots of the parts that are validated against the schemas with
+// the `validation` feature.
+#[cfg(feature = "validation")]
+const VALIDATED_PARTS: [&str; 5] = [
+    "streaming_worksheet",
+    "styles",
+    "workbook",
+    "worksheet",
+    "worksheet_styles",
+];
+
+// Write a part and compare it with its snapshot, or update the snapshot if
+// UPDATE_SNAPSHOTS is set.
+fn assert_snapshot(name: &str, write: impl FnOnce(&mut XMLWriter<Vec<u8>>)) {
+    let mut writer = XMLWriter::new(vec![]);
+    writer.set_check_attribute_order(true);
+    write(&mut writer);
+    let got = writer.into_inner();
+
+    // Check the worksheet, styles and workbook parts against the schemas.
+    #[cfg(feature = "validation")]
+    if VALIDATED_PARTS.contains(&name) {
+        excel_xmlwriter::validation::validate_part(&got)
+            .unwrap_or_else(|error| panic!("snapshot {name}: {error}"));
+    }
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.xml"));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &got).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read(&path).unwrap_or_else(|error| {
+        panic!(
+            "couldn't read snapshot {}: {error}. Create it with UPDATE_SNAPSHOTS=1",
+            path.display()
+        )
+    });
+
+    // Compare the bytes, but show the differences with one element per line.
+    if got != expected {
+        let lines = |data: &[u8]| String::from_utf8_lossy(data).replace("><", ">\n<");
+        assert_eq!(lines(&got), lines(&expected), "snapshot {name}");
+        panic!("snapshot {name} differs only in its line breaks");
+    }
+}
+
+#[test]
+fn snapshot_app() {
+    assert_snapshot("app", |writer| {
+        let mut app = App::new();
+        app.add_heading_pair("Worksheets", 2);
+        app.add_heading_pair("Named Ranges", 1);
+        app.add_part_name("Sheet1");
+        app.add_part_name("Data & Notes");
+        app.add_part_name("Sales");
+        app.set_company("Example <Ltd>");
+        app.set_manager("Jane Doe");
+        app.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_core() {
+    assert_snapshot("core", |writer| {
+        let mut core = Core::new();
+        core.set_created(UNIX_EPOCH + Duration::from_secs(1_640_995_200));
+        core.set_title("Quarterly \"Sales\"");
+        core.set_subject("Sales & Costs");
+        core.set_author("Jane Doe");
+        core.set_keywords("sales, costs");
+        core.set_comment("Line 1\nLine 2");
+        core.set_category("Reports");
+        core.set_status("Draft");
+        core.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_content_types() {
+    assert_snapshot("content_types", |writer| {
+        let mut content_types = ContentTypes::new();
+        content_types.add_workbook();
+        content_types.add_worksheet(1);
+        content_types.add_worksheet(2);
+        content_types.add_styles();
+        content_types.add_shared_strings();
+        content_types.add_theme();
+        content_types.add_doc_properties();
+        content_types.add_default("png", "image/png");
+        content_types.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_relationships() {
+    assert_snapshot("relationships", |writer| {
+        let mut relationships = Relationships::new();
+        relationships.add_document_relationship("worksheet", "worksheets/sheet1.xml");
+        relationships.add_package_relationship("metadata/core-properties", "docProps/core.xml");
+        relationships.add_worksheet_relationship(
+            "hyperlink",
+            "https://example.com/?a=1&b=2",
+            Some("External"),
+        );
+        relationships.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_theme() {
+    assert_snapshot("theme", |writer| Theme::new().assemble_xml_file(writer));
+}
+
+#[test]
+fn snapshot_shared_strings() {
+    assert_snapshot("shared_strings", |writer| {
+        let mut string_table = SharedStringTable::new();
+        for string in ["Hello", " padded ", "<&>\"'", "Hello", "tab\there", "€uro"] {
+            string_table.shared_string_index(string);
+        }
+        string_table.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_styles() {
+    assert_snapshot("styles", |writer| {
+        let mut styles = Styles::new();
+        let formats = [
+            Format::new().set_bold(),
+            Format::new()
+                .set_italic()
+                .set_underline(FormatUnderline::Double)
+                .set_font_color(Color::Rgb(0xFF0000)),
+            Format::new().set_num_format_index(10),
+            Format::new()
+                .set_pattern(FormatPattern::Solid)
+                .set_background_color(Color::Rgb(0xFFFF00)),
+            Format::new()
+                .set_border(FormatBorder::Thin)
+                .set_border_color(Color::Theme(4)),
+            Format::new()
+                .set_align(FormatAlign::Center)
+                .set_text_wrap()
+                .set_rotation(45),
+            Format::new().set_unlocked().set_hidden(),
+            Format::new().set_hyperlink(),
+        ];
+        for format in &formats {
+            styles.xf_index(format);
+        }
+        styles.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_workbook() {
+    assert_snapshot("workbook", |writer| {
+        let mut workbook = Workbook::new();
+        workbook.add_sheet("Sheet1");
+        let data = workbook.add_sheet("Data & Notes");
+        workbook.add_sheet("Sales");
+        workbook.set_sheet_state(data, SheetState::Hidden).unwrap();
+        workbook.set_active_sheet(2).unwrap();
+        workbook.define_name("Sales", "=Sales!$A$1:$A$10").unwrap();
+        workbook
+            .add_defined_name("_xlnm.Print_Titles", Some(0), "Sheet1!$1:$1")
+            .unwrap();
+        workbook.add_autofilter_name(2, 0, 0, 9, 3);
+        workbook.assemble_xml_file(writer);
+    });
+}
+
+#[test]
+fn snapshot_worksheet() {
+    let mut string_table = SharedStringTable::new();
+    let mut styles = Styles::new();
+    let mut worksheet = Worksheet::new();
+
+    let bold = Format::new().set_bold();
+    let italic = Format::new().set_italic();
+
+    worksheet.write_string_with_format(0, 0, "Region", &bold);
+    worksheet.write_string_with_format(0, 1, "Sales", &bold);
+    worksheet.write_string(1, 0, "North & East");
+    worksheet.write_number(1, 1, 1234.5);
+    worksheet.write_string(2, 0, " West ");
+    worksheet.write_number(2, 1, -0.25);
+    worksheet.write_formula_with_format(3, 1, "=SUM(B2:B3)", &italic);
+    worksheet
+        .write_url(5, 0, "https://example.com/?a=1&b=2")
+        .unwrap();
+    worksheet
+        .write_url_with_text(6, 0, "internal:Sheet2!A1", "Next")
+        .unwrap();
+    worksheet
+        .merge_range(8, 0, 8, 2, "Merged", &Format::new())
+        .unwrap();
+    worksheet.set_column(0, 0, 20.0, None, false);
+    worksheet.set_column_format(1, 1, &italic);
+    worksheet.set_row_format(10, &bold);
+    worksheet.autofilter(0, 0, 3, 1);
+    worksheet.add_conditional_format(
+        1,
+        1,
+        2,
+        1,
+        ConditionalFormatCell::new(CellCriteria::LessThan("0".to_string())).set_format(
+            &Format::new()
+                .set_font_color(Color::Rgb(0x9C0006))
+                .set_background_color(Color::Rgb(0xFFC7CE)),
+        ),
+    );
+    worksheet.add_conditional_format(
+        1,
+        0,
+        2,
+        0,
+        ConditionalFormatText::new(TextCriteria::Contains("\"East\" & <West>".to_string()))
+            .set_format(&bold),
+    );
+    worksheet
+        .add_data_validation(
+            1,
+            1,
+            2,
+            1,
+            &DataValidation::new()
+                .allow_decimal_number(DataValidationCriteria::GreaterThanOrEqualTo(
+                    "-1".to_string(),
+                ))
+                .set_input_title("Sales")
+                .set_error_message("Sales must be >= -1"),
+        )
+        .unwrap();
+    worksheet.freeze_panes(1, 0);
+    worksheet.set_zoom(120);
+    worksheet.set_page_setup(
+        &PageSetup::new()
+            .set_landscape()
+            .set_fit_to_pages(1, 0)
+            .set_footer("&CPage &P of &N"),
+    );
+
+    assert_snapshot("worksheet", |writer| {
+        worksheet.assemble_xml_file(writer, &mut styles, &mut string_table)
+    });
+
+    // The conditional formats add differential formats to the styles.
+    assert_snapshot("worksheet_styles", |writer| {
+        styles.assemble_xml_file(writer)
+    });
+}
+
+#[test]
+fn snapshot_streaming_worksheet() {
+    let mut styles = Styles::new();
+    let mut worksheet = Worksheet::new();
+    worksheet.set_row_format(2, &Format::new().set_bold());
+
+    assert_snapshot("streaming_worksheet", |writer| {
+        let mut stream = worksheet.start_streaming(writer, &mut styles);
+        stream.write_string(0, 0, "Item").unwrap();
+        stream.write_string(0, 1, " padded ").unwrap();
+        stream.write_number(1, 0, 1.5).unwrap();
+        stream.write_formula(3, 1, "=A2*2").unwrap();
+        stream.finish();
+    });
+}
+
+#[test]
+fn snapshot_table() {
+    let mut worksheet = Worksheet::new();
+    let table = Table::new(1)
+        .set_name("Sales")
+        .set_columns(&["Region", "Q1 & Q2", "<Total>"])
+        .set_style_name("TableStyleLight11")
+        .set_banded_columns(true);
+    worksheet.add_table(2, 1, 6, 3, &table).unwrap();
+
+    assert_snapshot("table", |writer| {
+        worksheet.tables()[0].assemble_xml_file(writer)
+    });
+}
+
+#[test]
+fn snapshot_notes() {
+    let mut worksheet = Worksheet::new();
+    worksheet.add_note(0, 0, &Note::new("Reviewed <ok>").set_author("Jane"));
+    worksheet.add_note(3, 2, &Note::new(" Check & confirm").set_visible(true));
+
+    assert_snapshot("comments", |writer| {
+        worksheet.comments().unwrap().assemble_xml_file(writer)
+    });
+    assert_snapshot("vml_drawing", |writer| {
+        worksheet.vml_drawing().unwrap().assemble_xml_file(writer)
+    });
+}
+
+#[test]
+fn snapshot_threaded_comments() {
+    let date = UNIX_EPOCH + Duration::from_secs(1_672_531_200);
+    let comment = ThreadedComment::new("Jane", "Is this final?")
+        .set_date(date)
+        .add_reply_with_date("Sam", "Yes & done", date + Duration::from_secs(3600))
+        .set_resolved(true);
+
+    let mut worksheet = Worksheet::new();
+    worksheet.add_threaded_comment(1, 1, &comment);
+    let threaded_comments = worksheet.threaded_comments().unwrap();
+
+    let mut persons = Persons::new();
+    persons.add_person("Jane", "jane@example.com", "AD");
+    persons.add_authors(&threaded_comments);
+
+    assert_snapshot("threaded_comments", |writer| {
+        threaded_comments.assemble_xml_file(writer)
+    });
+    assert_snapshot("persons", |writer| persons.assemble_xml_file(writer));
+}
+
+#[test]
+fn snapshot_chart() {
+    let chart = Chart::new(1, ChartType::Line)
+        .set_title("Monthly sales")
+        .set_x_axis_title("Month")
+        .set_y_axis_title("Units")
+        .add_series(
+            ChartSeries::new("Sales Data", 1, 1, 12, 1)
+                .set_categories("Sales Data", 1, 0, 12, 0)
+                .set_name_cell("Sales Data", 0, 1),
+        )
+        .add_series(
+            ChartSeries::new("Sales Data", 1, 2, 12, 2)
+                .set_categories("Sales Data", 1, 0, 12, 0)
+                .set_name("Target"),
+        );
+
+    assert_snapshot("chart", |writer| chart.assemble_xml_file(writer));
+}
+
+#[test]
+fn snapshot_drawing() {
+    // The header of a GIF image of 120 by 40 pixels.
+    let data = [b"GIF89a".as_slice(), &[120, 0, 40, 0]].concat();
+    let image = Image::from_bytes(&data).unwrap().set_alt_text("Logo");
+
+    let mut worksheet = Worksheet::new();
+    worksheet.add_image(1, 1, &image);
+    worksheet.insert_chart(4, 3, &Chart::new(1, ChartType::Pie));
+
+    assert_snapshot("drawing", |writer| {
+        worksheet.drawing().unwrap().assemble_xml_file(writer)
+    });
+}
+
+#[test]
+fn snapshot_metadata() {
+    assert_snapshot("metadata", |writer| {
+        Metadata::new().assemble_xml_file(writer)
+    });
+}